@@ -80,7 +80,8 @@ pub fn benchmark_data_loader(c: &mut Criterion) {
             tokio::runtime::Runtime::new().unwrap().spawn(async move {
                 let rt = TargetRuntime {
                     http: client_clone.clone(),
-                    http2_only: client_clone,
+                    http2_only: client_clone.clone(),
+                    http_insecure: client_clone,
                     env: Arc::new(Env {}),
                     file: Arc::new(File {}),
                     cache: Arc::new(Cache {}),
@@ -242,8 +242,10 @@ fn request_context() -> RequestContext {
     let server = Server::try_from(config_module).unwrap();
     let http = Arc::new(Http::init(&upstream));
     let http2 = Arc::new(Http::init(&upstream.clone().http2_only(true)));
+    let http_insecure = Arc::new(Http::init(&upstream.clone().verify_ssl(false)));
     let runtime = TargetRuntime {
         http2_only: http2,
+        http_insecure,
         http,
         env: Arc::new(Env {}),
         file: Arc::new(File {}),
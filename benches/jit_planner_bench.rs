@@ -0,0 +1,67 @@
+use async_graphql::parser::parse_query;
+use criterion::{black_box, Criterion};
+use tailcall::core::blueprint::Blueprint;
+use tailcall::core::config::{Config, ConfigModule};
+use tailcall::core::jit::Builder;
+use tailcall_valid::Validator;
+
+/// 3 fields
+const SMALL: &str = "{ posts { id title } }";
+
+/// 20 fields
+const MEDIUM: &str = r#"
+{
+  posts {
+    id userId title body
+    user { id name username email phone blog }
+    comments { id name email body title }
+  }
+  users { id }
+}
+"#;
+
+/// 18 fields in every aliased selection of posts
+const POST: &str = r#"
+posts {
+  id userId title body
+  user { id name username email phone blog }
+  comments { id name email body title }
+}
+"#;
+
+/// 10 fields
+const USERS: &str = "users { id name username email albums { id title photos { id } } }";
+
+/// 100 fields
+fn large_query() -> String {
+    let posts = (0..5)
+        .map(|i| format!("p{i}: {POST}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("{{ {posts} {USERS} }}")
+}
+
+pub fn benchmark_jit_planner(c: &mut Criterion) {
+    let sdl = std::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER).unwrap();
+    let config_module = ConfigModule::from(Config::from_sdl(&sdl).to_result().unwrap());
+    let blueprint = Blueprint::try_from(&config_module).unwrap();
+
+    let large = large_query();
+    let queries = [
+        ("small", SMALL),
+        ("medium", MEDIUM),
+        ("large", large.as_str()),
+    ];
+
+    for (name, query) in queries {
+        let document = parse_query(query).unwrap();
+
+        c.bench_function(&format!("jit_planner_{name}"), |b| {
+            b.iter(|| {
+                let plan = Builder::new(&blueprint, &document).build(None).unwrap();
+                black_box(plan);
+            })
+        });
+    }
+}
@@ -0,0 +1,43 @@
+use async_graphql_value::ConstValue;
+use criterion::{black_box, Criterion};
+use hyper::body::Bytes;
+use serde_json::json;
+use tailcall::core::http::Response;
+use tailcall::core::json::Projection;
+
+/// A list of users where the query only reads `id` and `name`.
+pub fn large_body(users: usize) -> Bytes {
+    let users = (0..users)
+        .map(|id| {
+            json!({
+                "id": id,
+                "name": format!("user {id}"),
+                "bio": "lorem ipsum dolor sit amet ".repeat(8),
+                "address": {"street": "Kulas Light", "city": "Gwenborough", "zipcode": "92998-3874"},
+                "tags": ["a", "b", "c"],
+            })
+        })
+        .collect::<Vec<_>>();
+    Bytes::from(serde_json::to_vec(&users).unwrap())
+}
+
+pub fn projection() -> Projection {
+    Projection::Fields(
+        ["id", "name"]
+            .into_iter()
+            .map(|name| (name.to_string(), Projection::All))
+            .collect(),
+    )
+}
+
+pub fn benchmark_projection(c: &mut Criterion) {
+    let response = Response { body: large_body(10_000), ..Response::empty() };
+    let projection = projection();
+
+    c.bench_function("large_body_full_parse", |b| {
+        b.iter(|| black_box(response.clone().to_json::<ConstValue>().unwrap()))
+    });
+    c.bench_function("large_body_projected_parse", |b| {
+        b.iter(|| black_box(response.clone().to_projected_json(&projection).unwrap()))
+    });
+}
@@ -6,7 +6,9 @@ mod from_json_bench;
 mod handle_request_bench;
 mod http_execute_bench;
 mod impl_path_string_for_evaluation_context;
+mod jit_planner_bench;
 mod json_like_bench;
+mod projection_bench;
 mod protobuf_convert_output;
 mod request_template_bench;
 
@@ -22,6 +24,8 @@ fn all_benchmarks(c: &mut Criterion) {
     from_json_bench::benchmark_from_json_method(c);
     bench_synth::bench_synth_nested(c);
     bench_synth::bench_synth_nested_borrow(c);
+    jit_planner_bench::benchmark_jit_planner(c);
+    projection_bench::benchmark_projection(c);
 }
 
 criterion_group! {
@@ -1,7 +1,14 @@
+use std::str::FromStr;
+use std::time::Duration;
+
 use clap::{Parser, Subcommand};
 use strum_macros::Display;
 use tailcall_version::VERSION;
 
+use crate::core::blueprint::TypeLang;
+use crate::core::http::MockMode;
+use crate::core::tracing::LogFormat;
+
 const ABOUT: &str = r"
    __        _ __           ____
   / /_____ _(_) /________ _/ / /
@@ -14,6 +21,11 @@ const ABOUT: &str = r"
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
+
+    /// Format of the logs, `text` or `json`. Takes precedence over the
+    /// `TAILCALL_LOG_FORMAT` env variable and `@telemetry(logFormat:)`
+    #[arg(long, global = true)]
+    pub log_format: Option<LogFormat>,
 }
 
 #[derive(Subcommand, Display)]
@@ -22,14 +34,33 @@ pub enum Command {
     Start {
         /// Path for the configuration files or http(s) link to config files
         /// separated by spaces if more than one
-        #[arg(required = true)]
+        #[arg(required_unless_present = "config_env")]
         file_paths: Vec<String>,
 
+        /// Reads the configuration from this environment variable instead of
+        /// the files, eg. `CONFIG="$(cat app.graphql)" tailcall start
+        /// --config-env CONFIG`. Its format, GraphQL, YAML or JSON, is
+        /// detected and the links are relative to the working directory
+        #[arg(long, conflicts_with_all = ["file_paths", "profile"])]
+        config_env: Option<String>,
+
         /// Controls SSL/TLS certificate verification for remote config files
         /// Set to false to skip certificate verification (not recommended for
         /// production)
         #[arg(short, long, action = clap::ArgAction::Set, default_value_t = true)]
         verify_ssl: bool,
+
+        /// Serves upstream requests from the files linked with
+        /// `@link(type: Mocks)`. In `strict` mode (the default) requests
+        /// without a matching mock fail, in `partial` mode they are sent to
+        /// the real upstream
+        #[arg(long, num_args = 0..=1, default_missing_value = "strict")]
+        mock: Option<MockMode>,
+
+        /// Merges the overlay of the profile, eg. `tailcall.prod.yml` for
+        /// `--profile prod`, over the configuration files
+        #[arg(long)]
+        profile: Option<String>,
     },
 
     /// Validate a composition spec
@@ -52,6 +83,21 @@ pub enum Command {
         /// production)
         #[arg(short, long, action = clap::ArgAction::Set, default_value_t = true)]
         verify_ssl: bool,
+
+        /// Treats warnings (N + 1 queries, outdated rc files) as errors and
+        /// exits with a non-zero code when any are found
+        #[arg(long)]
+        strict: bool,
+
+        /// Merges the overlay of the profile, eg. `tailcall.prod.yml` for
+        /// `--profile prod`, over the configuration files
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Prints how long reading each file and link and building the
+        /// blueprint took
+        #[arg(long)]
+        timing: bool,
     },
 
     /// Initialize a new project
@@ -59,12 +105,326 @@ pub enum Command {
         // default is current directory
         #[arg(default_value = ".")]
         folder_path: String,
+
+        /// Sends a GET to this REST endpoint and generates a query field
+        /// resolved by it, typed after its JSON response
+        #[arg(long)]
+        probe: Option<String>,
+
+        /// Header sent with the probe, eg. `Authorization: Bearer <token>`,
+        /// can be repeated
+        #[arg(long = "header", requires = "probe")]
+        headers: Vec<String>,
+
+        /// Port of the server, asked for when not set
+        #[arg(long)]
+        port: Option<u16>,
+
+        /// Enables the playground, asked for when not set
+        #[arg(long)]
+        playground: Option<bool>,
+
+        /// Base URL of the upstream in the generated config, asked for when
+        /// not set. Defaults to the origin of the probe
+        #[arg(long, requires = "probe")]
+        base_url: Option<String>,
     },
 
     /// Generates a Tailcall Configuration from one or more source files.
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Gen {
+        #[command(subcommand)]
+        command: Option<GenCommand>,
+
         /// Path of the configuration file
         #[arg(required = true)]
+        file_path: Option<String>,
+
+        /// Fails the generation when any of the inputs can't be generated,
+        /// instead of skipping it with a warning
+        #[arg(long)]
+        strict: bool,
+
+        /// Generates only the proto services matching these comma separated
+        /// patterns, and the types they use, eg. `shop.v1.UserService,*.Order*`
+        #[arg(long, value_delimiter = ',')]
+        include_services: Vec<String>,
+
+        /// Names the types generated from proto with this prefix in place of
+        /// their package
+        #[arg(long, conflicts_with = "strip_package_prefix")]
+        type_prefix: Option<String>,
+
+        /// Drops the package from the names of the types generated from proto
+        #[arg(long)]
+        strip_package_prefix: bool,
+
+        /// Prints the requests and tokens the LLM inference would use,
+        /// without asking the LLM nor writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Asks the LLM again the questions answered by a run that didn't
+        /// complete, instead of reusing their answers
+        #[arg(long)]
+        no_resume: bool,
+    },
+
+    /// Captures the schema of the configuration files as a baseline, to
+    /// detect the changes made after it with `diff`
+    Snapshot {
+        /// Path for the configuration files separated by spaces if more than
+        /// one
+        #[arg(long, required = true, num_args = 1..)]
+        schema: Vec<String>,
+
+        /// Path of the JSON file the snapshot is written to
+        #[arg(long, default_value = "schema.json")]
+        output: String,
+    },
+
+    /// Reports the changes of the configuration files since a snapshot, and
+    /// fails when some of them are breaking
+    Diff {
+        /// Path of the snapshot created with `snapshot`
+        #[arg(required = true)]
+        snapshot: String,
+
+        /// Path for the configuration files separated by spaces if more than
+        /// one
+        #[arg(required = true)]
+        file_paths: Vec<String>,
+    },
+
+    /// Measures how fast the configuration serves a query, sending it in a
+    /// loop through the executor, without going through the network
+    Bench {
+        /// Path for the configuration files separated by spaces if more than
+        /// one
+        #[arg(long, required = true, num_args = 1..)]
+        schema: Vec<String>,
+
+        /// The GraphQL query sent, eg. `query { posts { id } }`
+        #[arg(long)]
+        query: String,
+
+        /// Number of queries in flight at once
+        #[arg(long, default_value_t = 10)]
+        concurrency: usize,
+
+        /// How long the query is sent for, eg. `30s`, `2m` or `500ms`
+        #[arg(long, default_value = "10s", value_parser = parse_duration)]
+        duration: Duration,
+    },
+
+    /// Converts a schema from one format to another, eg. `tailcall convert
+    /// --from openapi --to graphql openapi.yml`. The output is printed,
+    /// so that conversions can be piped into each other
+    Convert {
+        /// Path or URL of the schema, `-` reads it from stdin. The proto files
+        /// are read from a path, since their imports are relative to it
+        #[arg(default_value = "-")]
         file_path: String,
+
+        /// Format of the schema, `openapi` (an OpenAPI 3 spec in JSON or
+        /// YAML), `json-schema`, `proto`, `introspection` (the JSON result of
+        /// an introspection query) or `graphql` (a configuration)
+        #[arg(long)]
+        from: ConvertFrom,
+
+        /// Format of the output, `graphql` (a configuration), `sdl` (the
+        /// schema exposed to the clients), `json-schema` (the arguments of
+        /// the root fields), `ts` or `flow`
+        #[arg(long)]
+        to: ConvertTo,
+
+        /// Path of the file the output is written to instead of being printed
+        #[arg(long)]
+        out: Option<String>,
+    },
+
+    /// Inspects the configuration files
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Prints the effective configuration, after merging the files and the
+    /// overlay of the profile
+    Show {
+        /// Path for the configuration files separated by spaces if more than
+        /// one
+        #[arg(required = true)]
+        file_paths: Vec<String>,
+
+        /// Merges the overlay of the profile, eg. `tailcall.prod.yml` for
+        /// `--profile prod`, over the configuration files
+        #[arg(long)]
+        profile: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GenCommand {
+    /// Generates the TypeScript or Flow types of the schema for its clients,
+    /// eg. `tailcall gen types --lang ts --out schema.d.ts app.graphql`
+    Types {
+        /// Path for the configuration files separated by spaces if more than
+        /// one
+        #[arg(required = true)]
+        file_paths: Vec<String>,
+
+        /// Language of the types, `ts` or `flow`
+        #[arg(long, default_value = "ts")]
+        lang: TypeLang,
+
+        /// Path of the file the types are written to
+        #[arg(long, required = true)]
+        out: String,
+
+        /// Type a scalar is written as, eg. `DateTime=Date`, can be repeated.
+        /// The scalars default to `string`, `number` or `unknown` after the
+        /// values they accept
+        #[arg(long = "scalar", value_parser = parse_scalar)]
+        scalars: Vec<(String, String)>,
+
+        /// Writes the types again whenever the configuration files change
+        #[arg(long)]
+        watch: bool,
     },
 }
+
+/// The formats `convert` reads a schema from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConvertFrom {
+    OpenApi,
+    JsonSchema,
+    Proto,
+    Introspection,
+    GraphQL,
+}
+
+impl FromStr for ConvertFrom {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "openapi" => Ok(ConvertFrom::OpenApi),
+            "json-schema" => Ok(ConvertFrom::JsonSchema),
+            "proto" => Ok(ConvertFrom::Proto),
+            "introspection" => Ok(ConvertFrom::Introspection),
+            "graphql" => Ok(ConvertFrom::GraphQL),
+            _ => Err(format!(
+                "unknown format `{s}`, use `openapi`, `json-schema`, `proto`, `introspection` or `graphql`"
+            )),
+        }
+    }
+}
+
+/// The formats `convert` writes a schema as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConvertTo {
+    GraphQL,
+    Sdl,
+    JsonSchema,
+    Types(TypeLang),
+}
+
+impl FromStr for ConvertTo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "graphql" => Ok(ConvertTo::GraphQL),
+            "sdl" => Ok(ConvertTo::Sdl),
+            "json-schema" => Ok(ConvertTo::JsonSchema),
+            _ => s.parse().map(ConvertTo::Types).map_err(|_| {
+                format!("unknown format `{s}`, use `graphql`, `sdl`, `json-schema`, `ts` or `flow`")
+            }),
+        }
+    }
+}
+
+/// Reads a scalar and its type, eg. `DateTime=Date`.
+fn parse_scalar(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, type_of)) if !name.trim().is_empty() && !type_of.trim().is_empty() => {
+            Ok((name.trim().to_string(), type_of.trim().to_string()))
+        }
+        _ => Err(format!(
+            "`{value}` isn't a scalar and its type, eg. `DateTime=Date`"
+        )),
+    }
+}
+
+/// Reads a duration in milliseconds, seconds or minutes, eg. `500ms`, `30s` or
+/// `2m`. A number without a unit is in seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((value, ""), |index| value.split_at(index));
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| format!("`{value}` isn't a duration, eg. `30s`"))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => number
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("`{value}` is too long")),
+        unit => Err(format!("unknown unit `{unit}`, use `ms`, `s` or `m`")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_duration, parse_scalar, ConvertFrom, ConvertTo};
+    use crate::core::blueprint::TypeLang;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("5"), Ok(Duration::from_secs(5)));
+        assert!(parse_duration("1h").is_err());
+        assert!(parse_duration("s").is_err());
+        assert_eq!(
+            parse_duration(&format!("{}m", u64::MAX)),
+            Err(format!("`{}m` is too long", u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_parse_scalar() {
+        assert_eq!(
+            parse_scalar("DateTime=Date"),
+            Ok(("DateTime".to_string(), "Date".to_string()))
+        );
+        assert!(parse_scalar("DateTime").is_err());
+        assert!(parse_scalar("=Date").is_err());
+    }
+
+    #[test]
+    fn test_parse_convert_formats() {
+        assert_eq!("proto".parse(), Ok(ConvertFrom::Proto));
+        assert_eq!("Introspection".parse(), Ok(ConvertFrom::Introspection));
+        assert_eq!("openapi".parse(), Ok(ConvertFrom::OpenApi));
+        assert_eq!("json-schema".parse(), Ok(ConvertFrom::JsonSchema));
+        assert!("swagger".parse::<ConvertFrom>().is_err());
+        assert_eq!("sdl".parse(), Ok(ConvertTo::Sdl));
+        assert_eq!(
+            "typescript".parse(),
+            Ok(ConvertTo::Types(TypeLang::TypeScript))
+        );
+        assert_eq!("flow".parse(), Ok(ConvertTo::Types(TypeLang::Flow)));
+        assert_eq!("json-schema".parse(), Ok(ConvertTo::JsonSchema));
+        assert!("openapi".parse::<ConvertTo>().is_err());
+    }
+}
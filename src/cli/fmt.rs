@@ -21,9 +21,11 @@ impl Fmt {
         Fmt::meta(&n_plus_one_info.to_string())
     }
 
-    pub fn log_n_plus_one(show_npo: bool, config: &Config) {
+    /// Logs the N + 1 queries found in the config and returns their count
+    pub fn log_n_plus_one(show_npo: bool, config: &Config) -> usize {
         let n_plus_one_info = config.n_plus_one();
-        let mut message = format!("N + 1 detected: {}", n_plus_one_info.size());
+        let size = n_plus_one_info.size();
+        let mut message = format!("N + 1 detected: {}", size);
 
         if show_npo {
             message.push('\n');
@@ -31,5 +33,22 @@ impl Fmt {
         }
 
         tracing::info!("{}", message);
+        size
+    }
+
+    /// Warns about the fields that fail when their `DELETE` endpoint answers
+    /// with no content and returns the warnings.
+    pub fn warn_non_null_delete(config: &Config) -> Vec<String> {
+        config
+            .non_null_delete_fields()
+            .into_iter()
+            .map(|field| {
+                let warning = format!(
+                    "{field} can't resolve to null when its DELETE endpoint answers with 204 No Content, consider making its type nullable"
+                );
+                tracing::warn!("{}", warning);
+                warning
+            })
+            .collect()
     }
 }
@@ -43,6 +43,7 @@ pub struct LLMConfig {
 pub struct PresetConfig {
     pub merge_type: Option<f32>,
     pub infer_type_names: Option<bool>,
+    pub infer_field_names: Option<bool>,
     pub tree_shake: Option<bool>,
     pub unwrap_single_field_types: Option<bool>,
 }
@@ -140,6 +141,10 @@ impl ValidateFrom<PresetConfig> for Preset {
             preset = preset.infer_type_names(use_better_names);
         }
 
+        if let Some(infer_field_names) = config.infer_field_names {
+            preset = preset.infer_field_names(infer_field_names);
+        }
+
         if let Some(unwrap_single_field_types) = config.unwrap_single_field_types {
             preset = preset.unwrap_single_field_types(unwrap_single_field_types);
         }
@@ -340,6 +345,7 @@ mod tests {
         let config_preset = PresetConfig {
             tree_shake: None,
             infer_type_names: None,
+            infer_field_names: None,
             merge_type: Some(2.0),
             unwrap_single_field_types: None,
         };
@@ -354,6 +360,7 @@ mod tests {
         let config_preset = PresetConfig {
             tree_shake: Some(true),
             infer_type_names: Some(true),
+            infer_field_names: None,
             merge_type: Some(0.5),
             unwrap_single_field_types: None,
         };
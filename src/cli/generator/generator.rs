@@ -9,10 +9,11 @@ use tailcall_valid::{ValidateInto, Validator};
 
 use super::config::{Config, LLMConfig, Resolved, Source};
 use super::source::ConfigSource;
-use crate::cli::llm::InferTypeName;
-use crate::core::config::transformer::{Preset, RenameTypes};
+use crate::cli::llm::checkpoint::Checkpoint;
+use crate::cli::llm::{InferFieldNames, InferTypeName};
+use crate::core::config::transformer::{Preset, RenameFields, RenameTypes};
 use crate::core::config::{self, ConfigModule, ConfigReaderContext};
-use crate::core::generator::{Generator as ConfigGenerator, Input};
+use crate::core::generator::{Generator as ConfigGenerator, Input, ProtoOptions};
 use crate::core::proto_reader::ProtoReader;
 use crate::core::resource_reader::{Resource, ResourceReader};
 use crate::core::runtime::TargetRuntime;
@@ -24,11 +25,47 @@ pub struct Generator {
     /// path of config file.
     config_path: String,
     runtime: TargetRuntime,
+    /// fails instead of skipping the inputs that can't be generated.
+    strict: bool,
+    /// options of the generation from the proto inputs.
+    proto_options: ProtoOptions,
+    /// estimates the usage of the LLM instead of asking it, and doesn't
+    /// write the output.
+    dry_run: bool,
+    /// reuses the answers of the LLM kept by a run that didn't complete.
+    resume: bool,
 }
 
 impl Generator {
     pub fn new(config_path: &str, runtime: TargetRuntime) -> Self {
-        Self { config_path: config_path.to_string(), runtime }
+        Self {
+            config_path: config_path.to_string(),
+            runtime,
+            strict: false,
+            proto_options: ProtoOptions::default(),
+            dry_run: false,
+            resume: true,
+        }
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn proto_options(mut self, proto_options: ProtoOptions) -> Self {
+        self.proto_options = proto_options;
+        self
     }
 
     /// Writes the configuration to the output file if allowed.
@@ -169,29 +206,79 @@ impl Generator {
         let preset: Preset = preset.validate_into().to_result()?;
         let input_samples = self.resolve_io(config).await?;
         let infer_type_names = preset.infer_type_names;
+        let infer_field_names = preset.infer_field_names;
         let mut config_gen = ConfigGenerator::default()
             .inputs(input_samples)
+            .proto_options(self.proto_options.clone())
             .transformers(vec![Box::new(preset)]);
 
         if let Some(query_name) = query_type {
             config_gen = config_gen.query(query_name);
         }
 
-        let mut config = config_gen.mutation(mutation_type_name).generate(true)?;
+        let (mut config, report) = config_gen
+            .mutation(mutation_type_name)
+            .generate_with_report(true)?;
+        if self.strict && !report.skipped.is_empty() {
+            return Err(anyhow!("{}", report));
+        }
+
+        let mut checkpoint = None;
+        if let Some(LLMConfig { model: Some(model), secret }) = llm {
+            let secret = secret.map(|s| s.to_string());
+            if self.dry_run {
+                if infer_type_names {
+                    let usage = InferTypeName::new(model.clone(), secret.clone())
+                        .estimate(config.config())?;
+                    tracing::info!("Inferring the type names would take about {usage}");
+                }
+                if infer_field_names {
+                    let usage = InferFieldNames::new(model, secret).estimate(config.config())?;
+                    tracing::info!("Inferring the field names would take about {usage}");
+                }
+            } else if infer_type_names || infer_field_names {
+                // both share the checkpoint, read before any name is changed
+                let dir = Path::new(&path).parent().unwrap_or(Path::new("."));
+                let state = Checkpoint::load(dir, &config.to_sdl(), self.resume);
+
+                if infer_type_names {
+                    let mut llm_gen = InferTypeName::new(model.clone(), secret.clone());
+                    let suggested_names = llm_gen.generate(config.config(), Some(&state)).await?;
+                    tracing::info!("Inferring the type names took {}", llm_gen.usage());
+                    let cfg = RenameTypes::new(suggested_names.iter())
+                        .transform(config.config().to_owned())
+                        .to_result()?;
 
-        if infer_type_names {
-            if let Some(LLMConfig { model: Some(model), secret }) = llm {
-                let mut llm_gen = InferTypeName::new(model, secret.map(|s| s.to_string()));
-                let suggested_names = llm_gen.generate(config.config()).await?;
-                let cfg = RenameTypes::new(suggested_names.iter())
-                    .transform(config.config().to_owned())
-                    .to_result()?;
+                    config = ConfigModule::from(cfg);
+                }
+
+                if infer_field_names {
+                    let mut llm_gen = InferFieldNames::new(model, secret);
+                    let suggested_names = llm_gen.generate(config.config(), Some(&state)).await?;
+                    tracing::info!("Inferring the field names took {}", llm_gen.usage());
+                    let cfg = RenameFields::new(suggested_names.into_iter())
+                        .transform(config.config().to_owned())
+                        .to_result()?;
 
-                config = ConfigModule::from(cfg);
+                    config = ConfigModule::from(cfg);
+                }
+
+                checkpoint = Some(state);
             }
         }
 
-        self.write(&config, &path).await?;
+        if !self.dry_run {
+            self.write(&config, &path).await?;
+        }
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.remove();
+        }
+
+        if report.skipped.is_empty() {
+            tracing::info!("{}", report);
+        } else {
+            tracing::warn!("{}", report);
+        }
         Ok(config)
     }
 }
@@ -0,0 +1,174 @@
+//! Keeps the answers of the LLM in a file next to the output of `gen`, so that
+//! a run that stops half way is resumed without asking them again.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Name of the file the answers are kept in.
+pub const STATE_FILE: &str = ".tailcall-gen-state.json";
+
+/// Version of the format of the file, the files of other versions are ignored.
+const VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    version: u32,
+    /// Hash of the config the questions are about.
+    config_hash: String,
+    /// The answers by the hash of their question.
+    answers: BTreeMap<String, Value>,
+}
+
+pub struct Checkpoint {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+impl Checkpoint {
+    /// Reads the answers kept in `dir` for the config. They are discarded when
+    /// `resume` is false or when they were given for another config.
+    pub fn load(dir: &Path, config: &str, resume: bool) -> Self {
+        let path = dir.join(STATE_FILE);
+        let config_hash = hash(config);
+        let saved = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<State>(&bytes).ok())
+            .filter(|_| resume);
+
+        let state = match saved {
+            Some(state) if state.version == VERSION && state.config_hash == config_hash => {
+                tracing::info!(
+                    "Resuming with the {} answers kept in {}",
+                    state.answers.len(),
+                    path.display()
+                );
+                state
+            }
+            Some(_) => {
+                tracing::warn!(
+                    "Ignoring the answers kept in {}, they don't match the config",
+                    path.display()
+                );
+                State { version: VERSION, config_hash, answers: BTreeMap::new() }
+            }
+            None => State { version: VERSION, config_hash, answers: BTreeMap::new() },
+        };
+
+        Self { path, state: Mutex::new(state) }
+    }
+
+    /// The answer kept for the question.
+    pub fn get<Q: Serialize, A: DeserializeOwned>(&self, question: &Q) -> Option<A> {
+        let key = hash(&serde_json::to_string(question).ok()?);
+        let state = self.state.lock().unwrap();
+        serde_json::from_value(state.answers.get(&key)?.clone()).ok()
+    }
+
+    /// Keeps the answer of the question and writes all the answers to the
+    /// file. The run goes on when the file can't be written.
+    pub fn save<Q: Serialize, A: Serialize>(&self, question: &Q, answer: &A) {
+        let (Ok(question), Ok(answer)) = (
+            serde_json::to_string(question),
+            serde_json::to_value(answer),
+        ) else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.answers.insert(hash(&question), answer);
+        let written = serde_json::to_vec(&*state)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(std::fs::write(&self.path, bytes)?));
+        if let Err(err) = written {
+            tracing::warn!("Unable to write {}: {}", self.path.display(), err);
+        }
+    }
+
+    /// Removes the file once the run is complete.
+    pub fn remove(self) {
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Unable to remove {}: {}", self.path.display(), err);
+            }
+        }
+    }
+}
+
+fn hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::{Checkpoint, STATE_FILE};
+
+    const CONFIG: &str = "type Query { user: GEN__T1 }";
+
+    /// A sidecar with the answer of the question `"user"` for [CONFIG].
+    fn seeded() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = Checkpoint::load(dir.path(), CONFIG, true);
+        checkpoint.save(&"user", &vec!["User", "Person"]);
+        dir
+    }
+
+    #[test]
+    fn test_resume() {
+        let dir = seeded();
+
+        let checkpoint = Checkpoint::load(dir.path(), CONFIG, true);
+
+        assert_eq!(
+            checkpoint.get::<_, Vec<String>>(&"user"),
+            Some(vec!["User".to_string(), "Person".to_string()])
+        );
+        assert_eq!(checkpoint.get::<_, Vec<String>>(&"post"), None);
+    }
+
+    #[test]
+    fn test_config_changed() {
+        let dir = seeded();
+
+        let checkpoint = Checkpoint::load(dir.path(), "type Query { user: GEN__T2 }", true);
+
+        assert_eq!(checkpoint.get::<_, Vec<String>>(&"user"), None);
+    }
+
+    #[test]
+    fn test_no_resume_and_other_version() {
+        let dir = seeded();
+        assert_eq!(
+            Checkpoint::load(dir.path(), CONFIG, false).get::<_, Vec<String>>(&"user"),
+            None
+        );
+
+        let path = dir.path().join(STATE_FILE);
+        let mut state: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        state["version"] = json!(0);
+        std::fs::write(&path, state.to_string()).unwrap();
+        assert_eq!(
+            Checkpoint::load(dir.path(), CONFIG, true).get::<_, Vec<String>>(&"user"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let dir = seeded();
+
+        Checkpoint::load(dir.path(), CONFIG, true).remove();
+
+        assert!(!dir.path().join(STATE_FILE).exists());
+    }
+}
@@ -6,6 +6,22 @@ pub enum Error {
     GenAI(genai::Error),
     EmptyResponse,
     Serde(serde_json::Error),
+    /// The provider rejected the request because of its rate limits.
+    #[from(skip)]
+    RateLimited,
+}
+
+impl Error {
+    /// Reads the rate limit responses, `429 Too Many Requests`, from the
+    /// errors of the provider.
+    pub fn from_genai(error: genai::Error) -> Self {
+        let message = error.to_string().to_lowercase();
+        if message.contains("429") || message.contains("rate limit") {
+            Error::RateLimited
+        } else {
+            Error::GenAI(error)
+        }
+    }
 }
 
 pub type Result<A> = std::result::Result<A, Error>;
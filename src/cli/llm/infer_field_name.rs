@@ -0,0 +1,388 @@
+use genai::chat::{ChatMessage, ChatRequest, ChatResponse};
+use indexmap::{indexset, IndexSet};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::checkpoint::Checkpoint;
+use super::naming::{pick_names, Reprompt, Suggestions, TakenNames};
+use super::{Error, Result, Usage, Wizard};
+use crate::core::config::{Config, Resolver};
+use crate::core::generator::PREFIX;
+use crate::core::Mustache;
+
+const BASE_TEMPLATE: &str = include_str!("prompts/infer_field_name.md");
+
+/// Questions in flight at once.
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Suggests names for the root fields with an auto-generated name, eg.
+/// `GEN__news__NewsService__GetAllNews`, after the operation resolving them.
+pub struct InferFieldNames {
+    wizard: Wizard<Question, Answer>,
+    concurrency: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Answer {
+    suggestions: Vec<String>,
+}
+
+impl TryFrom<ChatResponse> for Answer {
+    type Error = Error;
+
+    fn try_from(response: ChatResponse) -> Result<Self> {
+        let message_content = response.content.ok_or(Error::EmptyResponse)?;
+        let text_content = message_content.text_as_str().ok_or(Error::EmptyResponse)?;
+        Ok(serde_json::from_str(text_content)?)
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Question {
+    #[serde(skip_serializing_if = "IndexSet::is_empty")]
+    ignore: IndexSet<String>,
+    operation_id: String,
+    /// The HTTP method, or `gRPC`.
+    method: String,
+    /// The path of the URL, or the gRPC method.
+    path: String,
+    /// Names suggested before that were already taken.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    taken: Vec<String>,
+}
+
+impl Reprompt for Question {
+    fn with_taken(mut self, taken: &[String]) -> Self {
+        self.taken.extend_from_slice(taken);
+        self
+    }
+}
+
+impl Suggestions for Answer {
+    fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+}
+
+#[derive(Serialize)]
+struct Context {
+    input: Question,
+    output: Answer,
+}
+
+impl TryInto<ChatRequest> for Question {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ChatRequest> {
+        let input = Question {
+            ignore: indexset! { "users".into() },
+            operation_id: "getV2UsersUserIdPostsPostId".to_string(),
+            method: "GET".to_string(),
+            path: "/v2/users/{{.args.userId}}/posts/{{.args.postId}}".to_string(),
+            taken: vec![],
+        };
+
+        let output = Answer {
+            suggestions: vec![
+                "userPost".into(),
+                "post".into(),
+                "postOfUser".into(),
+                "userPostById".into(),
+                "postById".into(),
+            ],
+        };
+
+        let template = Mustache::parse(BASE_TEMPLATE);
+
+        let context = Context { input, output };
+
+        let rendered_prompt = template.render(&serde_json::to_value(&context)?);
+
+        let mut messages = vec![
+            ChatMessage::system(rendered_prompt),
+            ChatMessage::user(serde_json::to_string(&json!({
+                "operationId": &self.operation_id,
+                "method": &self.method,
+                "path": &self.path,
+            }))?),
+        ];
+        if !self.taken.is_empty() {
+            messages.push(ChatMessage::user(format!(
+                "These names are taken: [{}]. Suggest different names.",
+                self.taken.join(", ")
+            )));
+        }
+
+        Ok(ChatRequest::new(messages))
+    }
+}
+
+impl InferFieldNames {
+    pub fn new(model: String, secret: Option<String>) -> InferFieldNames {
+        Self {
+            wizard: Wizard::new(model, secret),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Sets the number of questions in flight at once.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// The fields of the root types with an auto-generated name and an
+    /// `@http` or `@grpc` resolver, by root type, with the questions asked to
+    /// name them.
+    fn questions(config: &Config) -> Vec<(&String, Vec<(&String, Question)>)> {
+        let roots = [
+            &config.schema.query,
+            &config.schema.mutation,
+            &config.schema.subscription,
+        ];
+        roots
+            .into_iter()
+            .flatten()
+            .filter_map(|root| Some((root, config.types.get(root)?)))
+            .map(|(root, type_)| {
+                let ignore = type_
+                    .fields
+                    .keys()
+                    .filter(|name| !name.starts_with(PREFIX))
+                    .cloned()
+                    .collect::<IndexSet<_>>();
+                let questions = type_
+                    .fields
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(PREFIX))
+                    .filter_map(|(name, field)| {
+                        let (method, path) =
+                            field.resolvers.iter().find_map(|resolver| match resolver {
+                                Resolver::Http(http) => {
+                                    Some((http.method.to_string(), url_path(&http.url)))
+                                }
+                                Resolver::Grpc(grpc) => {
+                                    Some(("gRPC".to_string(), grpc.method.clone()))
+                                }
+                                _ => None,
+                            })?;
+                        let question = Question {
+                            ignore: ignore.clone(),
+                            operation_id: name.strip_prefix(PREFIX).unwrap_or(name).to_string(),
+                            method,
+                            path,
+                            taken: vec![],
+                        };
+                        Some((name, question))
+                    })
+                    .collect::<Vec<_>>();
+                (root, questions)
+            })
+            .filter(|(_, questions)| !questions.is_empty())
+            .collect()
+    }
+
+    /// Estimates the usage of [InferFieldNames::generate] without asking the
+    /// provider.
+    pub fn estimate(&self, config: &Config) -> Result<Usage> {
+        let questions = Self::questions(config)
+            .into_iter()
+            .flat_map(|(_, questions)| questions)
+            .map(|(_, question)| question)
+            .collect();
+        self.wizard.estimate(questions)
+    }
+
+    /// The usage of the provider so far.
+    pub fn usage(&self) -> Usage {
+        self.wizard.usage()
+    }
+
+    /// Asks the questions that have no answer in the checkpoint, keeping
+    /// their answers in it.
+    async fn ask(
+        &self,
+        questions: Vec<Question>,
+        checkpoint: Option<&Checkpoint>,
+    ) -> Vec<Result<Answer>> {
+        let mut answers = questions
+            .iter()
+            .map(|question| checkpoint.and_then(|checkpoint| checkpoint.get(question)))
+            .map(|answer| answer.map(Ok))
+            .collect::<Vec<Option<Result<Answer>>>>();
+        let (indices, pending): (Vec<_>, Vec<_>) = questions
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| answers[*index].is_none())
+            .unzip();
+
+        let total = pending.len();
+        let mut answered = 0;
+        let asked = self
+            .wizard
+            .ask_many(pending.clone(), self.concurrency, |index, answer| {
+                answered += 1;
+                tracing::info!("Inferred field names: {}/{} done", answered, total);
+                if let (Some(checkpoint), Ok(answer)) = (checkpoint, answer) {
+                    checkpoint.save(&pending[index], answer);
+                }
+            })
+            .await;
+
+        for (index, answer) in indices.into_iter().zip(asked) {
+            answers[index] = Some(answer);
+        }
+        answers
+            .into_iter()
+            .map(|answer| answer.unwrap_or(Err(Error::EmptyResponse)))
+            .collect()
+    }
+
+    /// Suggests a name for each root field with an auto-generated name, as
+    /// the root type, the field and its new name. The answers are kept in
+    /// the checkpoint, when there is one, and read back from it instead of
+    /// asking the questions again.
+    pub async fn generate(
+        &mut self,
+        config: &Config,
+        checkpoint: Option<&Checkpoint>,
+    ) -> Result<Vec<(String, String, String)>> {
+        let mut renames = vec![];
+        let this = &*self;
+
+        for (root, questions) in Self::questions(config) {
+            let (field_names, questions): (Vec<_>, Vec<_>) = questions.into_iter().unzip();
+            let mut taken = TakenNames::fields(config, root);
+            let names = pick_names(questions, &mut taken, |questions| {
+                this.ask(questions, checkpoint)
+            })
+            .await;
+
+            for (field_name, name) in field_names.into_iter().zip(names) {
+                if let Some(name) = name {
+                    tracing::info!("Name for {}.{}: {}", root, field_name, name);
+                    renames.push((root.to_owned(), field_name.to_owned(), name));
+                }
+            }
+        }
+
+        Ok(renames)
+    }
+}
+
+/// The path of the URL, with the templates it contains, eg.
+/// `/users/{{.args.id}}` for `http://api.com/users/{{.args.id}}`.
+fn url_path(url: &str) -> String {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |index| &rest[index..]),
+        None => url,
+    };
+    path.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use genai::chat::{ChatRequest, ChatResponse, MessageContent};
+    use indexmap::indexset;
+    use tailcall_valid::Validator;
+
+    use super::{url_path, Answer, Question};
+    use crate::cli::llm::InferFieldNames;
+    use crate::core::config::Config;
+
+    #[test]
+    fn test_to_chat_request_conversion() {
+        let question = Question {
+            ignore: indexset! {"news".to_owned()},
+            operation_id: "news__NewsService__GetAllNews".to_string(),
+            method: "gRPC".to_string(),
+            path: "news.NewsService.GetAllNews".to_string(),
+            taken: vec![],
+        };
+        let request: ChatRequest = question.try_into().unwrap();
+        insta::assert_debug_snapshot!(request);
+    }
+
+    #[test]
+    fn test_chat_response_parse() {
+        let resp = ChatResponse {
+            content: Some(MessageContent::Text(
+                "{\"suggestions\":[\"news\",\"allNews\",\"articles\",\"stories\",\"headlines\"]}"
+                    .to_owned(),
+            )),
+            ..Default::default()
+        };
+        let answer = Answer::try_from(resp).unwrap();
+        assert_eq!(
+            answer.suggestions,
+            ["news", "allNews", "articles", "stories", "headlines"]
+        );
+    }
+
+    #[test]
+    fn test_questions() {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query {
+              users: [User] @http(url: "http://upstream/users")
+              GEN__getV2UsersUserIdPostsPostId(userId: Int!, postId: Int!): Post
+                @http(url: "http://upstream/v2/users/{{.args.userId}}/posts/{{.args.postId}}")
+              GEN__news__NewsService__GetAllNews: [Post]
+                @grpc(url: "http://localhost:50051", method: "news.NewsService.GetAllNews")
+              GEN__static: Post @expr(body: {id: 1})
+            }
+            type User { id: Int }
+            type Post { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let questions = InferFieldNames::questions(&config);
+
+        assert_eq!(questions.len(), 1);
+        let (root, questions) = &questions[0];
+        assert_eq!(root.as_str(), "Query");
+        let questions = questions
+            .iter()
+            .map(|(name, question)| {
+                assert_eq!(question.ignore, indexset! {"users".to_owned()});
+                (
+                    name.as_str(),
+                    question.operation_id.as_str(),
+                    question.method.as_str(),
+                    question.path.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            questions,
+            [
+                (
+                    "GEN__getV2UsersUserIdPostsPostId",
+                    "getV2UsersUserIdPostsPostId",
+                    "GET",
+                    "/v2/users/{{.args.userId}}/posts/{{.args.postId}}"
+                ),
+                (
+                    "GEN__news__NewsService__GetAllNews",
+                    "news__NewsService__GetAllNews",
+                    "gRPC",
+                    "news.NewsService.GetAllNews"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_path() {
+        assert_eq!(
+            url_path("http://api.com/users/{{.args.id}}"),
+            "/users/{{.args.id}}"
+        );
+        assert_eq!(url_path("https://api.com"), "/");
+        assert_eq!(url_path("/users"), "/users");
+    }
+}
@@ -5,15 +5,21 @@ use indexmap::{indexset, IndexSet};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::{Error, Result, Wizard};
+use super::checkpoint::Checkpoint;
+use super::naming::{pick_names, Reprompt, Suggestions, TakenNames};
+use super::{Error, Result, Usage, Wizard};
 use crate::core::config::Config;
 use crate::core::generator::PREFIX;
 use crate::core::Mustache;
 
 const BASE_TEMPLATE: &str = include_str!("prompts/infer_type_name.md");
 
+/// Questions in flight at once.
+const DEFAULT_CONCURRENCY: usize = 4;
+
 pub struct InferTypeName {
     wizard: Wizard<Question, Answer>,
+    concurrency: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +42,22 @@ struct Question {
     #[serde(skip_serializing_if = "IndexSet::is_empty")]
     ignore: IndexSet<String>,
     fields: Vec<(String, String)>,
+    /// Names suggested before that were already taken.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    taken: Vec<String>,
+}
+
+impl Reprompt for Question {
+    fn with_taken(mut self, taken: &[String]) -> Self {
+        self.taken.extend_from_slice(taken);
+        self
+    }
+}
+
+impl Suggestions for Answer {
+    fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
 }
 
 #[derive(Serialize)]
@@ -55,6 +77,7 @@ impl TryInto<ChatRequest> for Question {
                 ("name".to_string(), "String".to_string()),
                 ("age".to_string(), "Int".to_string()),
             ],
+            taken: vec![],
         };
 
         let output = Answer {
@@ -73,18 +96,35 @@ impl TryInto<ChatRequest> for Question {
 
         let rendered_prompt = template.render(&serde_json::to_value(&context)?);
 
-        Ok(ChatRequest::new(vec![
+        let mut messages = vec![
             ChatMessage::system(rendered_prompt),
             ChatMessage::user(serde_json::to_string(&json!({
                 "fields": &self.fields,
             }))?),
-        ]))
+        ];
+        if !self.taken.is_empty() {
+            messages.push(ChatMessage::user(format!(
+                "These names are taken: [{}]. Suggest different names.",
+                self.taken.join(", ")
+            )));
+        }
+
+        Ok(ChatRequest::new(messages))
     }
 }
 
 impl InferTypeName {
     pub fn new(model: String, secret: Option<String>) -> InferTypeName {
-        Self { wizard: Wizard::new(model, secret) }
+        Self {
+            wizard: Wizard::new(model, secret),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Sets the number of questions in flight at once.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
     }
 
     /// All generated type names starts with PREFIX
@@ -93,77 +133,113 @@ impl InferTypeName {
         type_name.starts_with(PREFIX)
     }
 
-    pub async fn generate(&mut self, config: &Config) -> Result<HashMap<String, String>> {
-        let mut new_name_mappings: HashMap<String, String> = HashMap::new();
+    /// The auto-generated types of the config with the questions asked to
+    /// name them.
+    fn questions<'a>(config: &'a Config) -> (Vec<&'a String>, Vec<Question>) {
+        let used_type_names = config
+            .types
+            .iter()
+            .filter(|(ty_name, _)| !Self::is_auto_generated(ty_name))
+            .map(|(ty_name, _)| ty_name.to_owned())
+            .collect::<IndexSet<_>>();
+
         // Filter out root operation types and types with non-auto-generated names
-        let types_to_be_processed = config
+        config
             .types
             .iter()
             .filter(|(type_name, _)| {
                 !config.is_root_operation_type(type_name) && Self::is_auto_generated(type_name)
             })
-            .collect::<Vec<_>>();
+            .map(|(type_name, type_)| {
+                let question = Question {
+                    ignore: used_type_names.clone(),
+                    fields: type_
+                        .fields
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.type_of.name().to_owned()))
+                        .collect(),
+                    taken: vec![],
+                };
+                (type_name, question)
+            })
+            .unzip()
+    }
 
-        let mut used_type_names = config
-            .types
+    /// Estimates the usage of [InferTypeName::generate] without asking the
+    /// provider.
+    pub fn estimate(&self, config: &Config) -> Result<Usage> {
+        let (_, questions) = Self::questions(config);
+        self.wizard.estimate(questions)
+    }
+
+    /// The usage of the provider so far.
+    pub fn usage(&self) -> Usage {
+        self.wizard.usage()
+    }
+
+    /// Asks the questions that have no answer in the checkpoint, keeping
+    /// their answers in it.
+    async fn ask(
+        &self,
+        questions: Vec<Question>,
+        checkpoint: Option<&Checkpoint>,
+    ) -> Vec<Result<Answer>> {
+        let mut answers = questions
             .iter()
-            .filter(|(ty_name, _)| !Self::is_auto_generated(ty_name))
-            .map(|(ty_name, _)| ty_name.to_owned())
-            .collect::<IndexSet<_>>();
+            .map(|question| checkpoint.and_then(|checkpoint| checkpoint.get(question)))
+            .map(|answer| answer.map(Ok))
+            .collect::<Vec<Option<Result<Answer>>>>();
+        let (indices, pending): (Vec<_>, Vec<_>) = questions
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| answers[*index].is_none())
+            .unzip();
 
-        let total = types_to_be_processed.len();
-        for (i, (type_name, type_)) in types_to_be_processed.into_iter().enumerate() {
-            // convert type to sdl format.
-            let question = Question {
-                ignore: used_type_names.clone(),
-                fields: type_
-                    .fields
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v.type_of.name().to_owned()))
-                    .collect(),
-            };
-
-            let mut delay = 3;
-            loop {
-                let answer = self.wizard.ask(question.clone()).await;
-                match answer {
-                    Ok(answer) => {
-                        let name = &answer.suggestions.join(", ");
-                        for name in answer.suggestions {
-                            if config.types.contains_key(&name) || used_type_names.contains(&name) {
-                                continue;
-                            }
-                            used_type_names.insert(name.clone());
-                            new_name_mappings.insert(type_name.to_owned(), name);
-                            break;
-                        }
-                        tracing::info!(
-                            "Suggestions for {}: [{}] - {}/{}",
-                            type_name,
-                            name,
-                            i + 1,
-                            total
-                        );
-
-                        // TODO: case where suggested names are already used, then extend the base
-                        // question with `suggest different names, we have already used following
-                        // names: [names list]`
-                        break;
-                    }
-                    Err(e) => {
-                        // TODO: log errors after certain number of retries.
-                        if let Error::GenAI(_) = e {
-                            // TODO: retry only when it's required.
-                            tracing::warn!(
-                                "Unable to retrieve a name for the type '{}'. Retrying in {}s",
-                                type_name,
-                                delay
-                            );
-                            tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
-                            delay *= std::cmp::min(delay * 2, 60);
-                        }
-                    }
+        let total = pending.len();
+        let mut answered = 0;
+        let asked = self
+            .wizard
+            .ask_many(pending.clone(), self.concurrency, |index, answer| {
+                answered += 1;
+                tracing::info!("Inferred type names: {}/{} done", answered, total);
+                if let (Some(checkpoint), Ok(answer)) = (checkpoint, answer) {
+                    checkpoint.save(&pending[index], answer);
                 }
+            })
+            .await;
+
+        for (index, answer) in indices.into_iter().zip(asked) {
+            answers[index] = Some(answer);
+        }
+        answers
+            .into_iter()
+            .map(|answer| answer.unwrap_or(Err(Error::EmptyResponse)))
+            .collect()
+    }
+
+    /// Suggests a name for each auto-generated type. The answers are kept in
+    /// the checkpoint, when there is one, and read back from it instead of
+    /// asking the questions again.
+    pub async fn generate(
+        &mut self,
+        config: &Config,
+        checkpoint: Option<&Checkpoint>,
+    ) -> Result<HashMap<String, String>> {
+        let mut new_name_mappings: HashMap<String, String> = HashMap::new();
+        let (type_names, questions) = Self::questions(config);
+
+        // The questions are asked concurrently, so the names suggested twice
+        // are resolved by asking again the ones that come later.
+        let this = &*self;
+        let names = pick_names(questions, &mut TakenNames::new(config), |questions| {
+            this.ask(questions, checkpoint)
+        })
+        .await;
+
+        for (type_name, name) in type_names.into_iter().zip(names) {
+            if let Some(name) = name {
+                tracing::info!("Name for {}: {}", type_name, name);
+                new_name_mappings.insert(type_name.to_owned(), name);
             }
         }
 
@@ -175,9 +251,11 @@ impl InferTypeName {
 mod test {
     use genai::chat::{ChatRequest, ChatResponse, MessageContent};
     use indexmap::indexset;
+    use tailcall_valid::Validator;
 
     use super::{Answer, Question};
     use crate::cli::llm::InferTypeName;
+    use crate::core::config::Config;
 
     #[test]
     fn test_to_chat_request_conversion() {
@@ -188,6 +266,7 @@ mod test {
                 ("name".to_string(), "String".to_string()),
                 ("age".to_string(), "Int".to_string()),
             ],
+            taken: vec![],
         };
         let request: ChatRequest = question.try_into().unwrap();
         insta::assert_debug_snapshot!(request);
@@ -205,6 +284,31 @@ mod test {
         insta::assert_debug_snapshot!(answer);
     }
 
+    #[test]
+    fn test_estimate() {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query { posts: [GEN__T1] @http(url: "http://upstream/posts") }
+            type GEN__T1 { id: Int, title: String, user: GEN__T2 }
+            type GEN__T2 { id: Int, name: String }
+            type User { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let usage = InferTypeName::new("gpt-4o-mini".to_string(), None)
+            .estimate(&config)
+            .unwrap();
+
+        let (_, questions) = InferTypeName::questions(&config);
+        assert_eq!(questions.len(), 2);
+        assert_eq!(usage.requests, 2);
+        assert!(usage.input_tokens > 0);
+        assert_eq!(usage.output_tokens, 0);
+    }
+
     #[test]
     fn test_is_auto_generated() {
         assert!(InferTypeName::is_auto_generated("GEN__T1"));
@@ -1,8 +1,12 @@
+pub mod checkpoint;
 mod error;
+pub mod infer_field_name;
 pub mod infer_type_name;
 pub use error::Error;
 use error::Result;
+pub use infer_field_name::InferFieldNames;
 pub use infer_type_name::InferTypeName;
+mod naming;
 mod wizard;
 
-pub use wizard::Wizard;
+pub use wizard::{Usage, Wizard};
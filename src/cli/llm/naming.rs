@@ -0,0 +1,282 @@
+//! Picks the names suggested by the LLM so that they don't collide with the
+//! names in use. When all the suggestions of an answer are taken, the question
+//! is asked again with the taken suggestions, and after a few rounds the best
+//! suggestion is numbered instead, eg. `User2`.
+
+use std::future::Future;
+
+use indexmap::IndexSet;
+
+use super::Result;
+use crate::core::config::Config;
+use crate::core::generator::PREFIX;
+
+/// Times a question is asked again before numbering its best suggestion.
+const MAX_ROUNDS: usize = 3;
+
+/// A question that can be asked again, telling which names are taken.
+pub trait Reprompt: Clone {
+    fn with_taken(self, taken: &[String]) -> Self;
+}
+
+/// An answer with the suggested names, the best one first.
+pub trait Suggestions {
+    fn suggestions(&self) -> &[String];
+}
+
+/// The names in use, which grow with the names picked.
+pub struct TakenNames(IndexSet<String>);
+
+impl TakenNames {
+    /// The names of the types of the config, root operation types included.
+    /// The generated names, which are the ones being replaced, aren't taken.
+    pub fn new(config: &Config) -> Self {
+        let roots = [
+            &config.schema.query,
+            &config.schema.mutation,
+            &config.schema.subscription,
+        ];
+        let names = config
+            .types
+            .keys()
+            .chain(roots.into_iter().flatten())
+            .filter(|name| !name.starts_with(PREFIX))
+            .cloned()
+            .collect();
+        Self(names)
+    }
+
+    /// The names of the fields of the type, except the generated ones.
+    pub fn fields(config: &Config, type_name: &str) -> Self {
+        let names = config
+            .types
+            .get(type_name)
+            .map(|type_| {
+                type_
+                    .fields
+                    .keys()
+                    .filter(|name| !name.starts_with(PREFIX))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self(names)
+    }
+
+    /// Takes the first suggestion that isn't taken.
+    fn take_first(&mut self, suggestions: &[String]) -> Option<String> {
+        let name = suggestions.iter().find(|name| !self.0.contains(*name))?;
+        self.0.insert(name.clone());
+        Some(name.clone())
+    }
+
+    /// Takes the name followed by the first number that makes it free.
+    fn take_numbered(&mut self, name: &str) -> String {
+        let mut n = 2;
+        while self.0.contains(&format!("{name}{n}")) {
+            n += 1;
+        }
+        let name = format!("{name}{n}");
+        self.0.insert(name.clone());
+        name
+    }
+}
+
+/// Asks the questions with `ask` and picks a name for each of them, in the
+/// order of the questions. A question that couldn't be answered gets no name.
+pub async fn pick_names<Q, A, F, Fut>(
+    questions: Vec<Q>,
+    taken: &mut TakenNames,
+    mut ask: F,
+) -> Vec<Option<String>>
+where
+    Q: Reprompt,
+    A: Suggestions,
+    F: FnMut(Vec<Q>) -> Fut,
+    Fut: Future<Output = Vec<Result<A>>>,
+{
+    let mut names = vec![None; questions.len()];
+    let mut best = vec![None; questions.len()];
+    let mut pending = questions.into_iter().enumerate().collect::<Vec<_>>();
+
+    for _ in 0..=MAX_ROUNDS {
+        if pending.is_empty() {
+            break;
+        }
+
+        let answers = ask(pending.iter().map(|(_, q)| q.clone()).collect()).await;
+        let mut colliding = vec![];
+        for ((index, question), answer) in pending.into_iter().zip(answers) {
+            let answer = match answer {
+                Ok(answer) => answer,
+                Err(err) => {
+                    tracing::warn!("Unable to retrieve the names: {}", err);
+                    continue;
+                }
+            };
+            let suggestions = answer.suggestions();
+            match taken.take_first(suggestions) {
+                Some(name) => names[index] = Some(name),
+                None => {
+                    if best[index].is_none() {
+                        best[index] = suggestions.first().cloned();
+                    }
+                    colliding.push((index, question.with_taken(suggestions)));
+                }
+            }
+        }
+        pending = colliding;
+    }
+
+    for (index, _) in pending {
+        if let Some(name) = &best[index] {
+            names[index] = Some(taken.take_numbered(name));
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tailcall_valid::Validator;
+
+    use super::{pick_names, Reprompt, Suggestions, TakenNames};
+    use crate::cli::llm::{Error, Result};
+    use crate::core::config::Config;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Question {
+        id: usize,
+        taken: Vec<String>,
+    }
+
+    impl Reprompt for Question {
+        fn with_taken(mut self, taken: &[String]) -> Self {
+            self.taken.extend_from_slice(taken);
+            self
+        }
+    }
+
+    struct Answer(Vec<String>);
+
+    impl Suggestions for Answer {
+        fn suggestions(&self) -> &[String] {
+            &self.0
+        }
+    }
+
+    fn taken() -> TakenNames {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query { user: User @expr(body: {}) }
+            type User { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        TakenNames::new(&config)
+    }
+
+    fn questions(count: usize) -> Vec<Question> {
+        (0..count)
+            .map(|id| Question { id, taken: vec![] })
+            .collect()
+    }
+
+    /// Answers the questions with the suggestions scripted for each round,
+    /// recording the questions asked.
+    fn scripted(
+        rounds: &[HashMap<usize, Vec<&str>>],
+        asked: &mut Vec<Vec<Question>>,
+        questions: Vec<Question>,
+    ) -> Vec<Result<Answer>> {
+        let script = &rounds[asked.len().min(rounds.len() - 1)];
+        asked.push(questions.clone());
+        questions
+            .iter()
+            .map(|question| match script.get(&question.id) {
+                Some(names) => Ok(Answer(names.iter().map(|n| n.to_string()).collect())),
+                None => Err(Error::EmptyResponse),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_pick_names_reprompt() {
+        let rounds = [
+            HashMap::from([
+                (0, vec!["User", "Query"]),
+                (1, vec!["Post"]),
+                (2, vec!["Post"]),
+            ]),
+            HashMap::from([(0, vec!["Profile"]), (2, vec!["Post", "Article"])]),
+        ];
+        let mut asked = vec![];
+        let mut taken = taken();
+
+        let names = pick_names(questions(3), &mut taken, |questions| {
+            let answers = scripted(&rounds, &mut asked, questions);
+            async move { answers }
+        })
+        .await;
+
+        assert_eq!(
+            names,
+            [
+                Some("Profile".to_string()),
+                Some("Post".to_string()),
+                Some("Article".to_string())
+            ]
+        );
+        assert_eq!(
+            asked[1],
+            [
+                Question { id: 0, taken: vec!["User".into(), "Query".into()] },
+                Question { id: 2, taken: vec!["Post".into()] },
+            ]
+        );
+        assert_eq!(asked.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pick_names_numbered() {
+        let rounds = [HashMap::from([(0, vec!["User"]), (1, vec!["User"])])];
+        let mut asked = vec![];
+        let mut taken = taken();
+
+        let names = pick_names(questions(3), &mut taken, |questions| {
+            let answers = scripted(&rounds, &mut asked, questions);
+            async move { answers }
+        })
+        .await;
+
+        assert_eq!(
+            names,
+            [Some("User2".to_string()), Some("User3".to_string()), None]
+        );
+        assert_eq!(asked.len(), 4);
+    }
+
+    #[test]
+    fn test_taken_names_skip_generated() {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query { user: GEN__T1 @expr(body: {}) GEN__getUsers: [GEN__T1] @expr(body: []) }
+            type GEN__T1 { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let taken = TakenNames::new(&config);
+        assert_eq!(taken.0.iter().collect::<Vec<_>>(), ["Query"]);
+
+        let taken = TakenNames::fields(&config, "Query");
+        assert_eq!(taken.0.iter().collect::<Vec<_>>(), ["user"]);
+    }
+}
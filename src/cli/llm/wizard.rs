@@ -1,15 +1,66 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use derive_setters::Setters;
+use futures_util::stream::{FuturesUnordered, StreamExt};
 use genai::adapter::AdapterKind;
 use genai::chat::{ChatOptions, ChatRequest, ChatResponse};
 use genai::resolver::AuthResolver;
 use genai::Client;
 
-use super::Result;
+use super::{Error, Result};
+
+/// Times a question is sent back to the queue after being rate limited before
+/// giving up on it.
+const MAX_RATE_LIMITED: u32 = 10;
+
+/// Longest delay between two attempts of a question.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Characters of text per token, roughly, for the estimates.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// The requests sent to the provider and the tokens they used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests, {} input tokens, {} output tokens",
+            self.requests, self.input_tokens, self.output_tokens
+        )
+    }
+}
 
 #[derive(Setters, Clone)]
 pub struct Wizard<Q, A> {
     client: Client,
     model: String,
+    /// Delay before the first retry of a failed question, doubled on each
+    /// retry.
+    retry_delay: Duration,
+    /// Attempts of a question before its error is returned.
+    max_attempts: u32,
+    /// The usage of the provider, shared by the clones of the wizard.
+    #[setters(skip)]
+    usage: Arc<Mutex<Usage>>,
     _q: std::marker::PhantomData<Q>,
     _a: std::marker::PhantomData<A>,
 }
@@ -33,6 +84,9 @@ impl<Q, A> Wizard<Q, A> {
                 .insert_adapter_config(adapter, config)
                 .build(),
             model,
+            retry_delay: Duration::from_secs(3),
+            max_attempts: 5,
+            usage: Default::default(),
             _q: Default::default(),
             _a: Default::default(),
         }
@@ -46,7 +100,304 @@ impl<Q, A> Wizard<Q, A> {
         let response = self
             .client
             .exec_chat(self.model.as_str(), q.try_into()?, None)
-            .await?;
-        A::try_from(response)
+            .await;
+
+        let mut usage = Usage { requests: 1, ..Default::default() };
+        if let Ok(response) = &response {
+            let tokens = |tokens: Option<i32>| tokens.unwrap_or_default().max(0) as u64;
+            usage.input_tokens = tokens(response.usage.input_tokens);
+            usage.output_tokens = tokens(response.usage.output_tokens);
+        }
+        *self.usage.lock().unwrap() += usage;
+
+        A::try_from(response.map_err(Error::from_genai)?)
+    }
+
+    /// The requests sent so far and the tokens the provider reported for them.
+    pub fn usage(&self) -> Usage {
+        *self.usage.lock().unwrap()
+    }
+
+    /// Estimates the usage of asking the questions, without asking them. The
+    /// input tokens are estimated from the size of the requests, the output
+    /// tokens aren't known before the answers.
+    pub fn estimate(&self, questions: Vec<Q>) -> Result<Usage>
+    where
+        Q: TryInto<ChatRequest, Error = super::Error>,
+    {
+        let mut usage = Usage::default();
+        for question in questions {
+            let request: ChatRequest = question.try_into()?;
+            usage += Usage {
+                requests: 1,
+                input_tokens: estimate_tokens(&request) as u64,
+                output_tokens: 0,
+            };
+        }
+        Ok(usage)
+    }
+
+    /// Asks the questions with up to `concurrency` of them in flight and
+    /// returns the answers in the order of the questions. `on_answer` is
+    /// called with the index of each question as soon as it's answered.
+    pub async fn ask_many(
+        &self,
+        questions: Vec<Q>,
+        concurrency: usize,
+        on_answer: impl FnMut(usize, &Result<A>),
+    ) -> Vec<Result<A>>
+    where
+        Q: Clone + TryInto<ChatRequest, Error = super::Error>,
+        A: TryFrom<ChatResponse, Error = super::Error>,
+    {
+        let retry = Retry { delay: self.retry_delay, max_attempts: self.max_attempts };
+        ask_many(questions, concurrency, retry, |q| self.ask(q), on_answer).await
+    }
+}
+
+/// The tokens of the text of the request.
+fn estimate_tokens(request: &ChatRequest) -> usize {
+    let chars = request
+        .system
+        .iter()
+        .map(String::len)
+        .chain(
+            request
+                .messages
+                .iter()
+                .filter_map(|message| message.content.text_as_str().map(str::len)),
+        )
+        .sum::<usize>();
+    chars.div_ceil(CHARS_PER_TOKEN)
+}
+
+#[derive(Clone, Copy)]
+struct Retry {
+    delay: Duration,
+    max_attempts: u32,
+}
+
+impl Retry {
+    /// The delay before the attempt following `retries` retries.
+    fn backoff(&self, retries: u32) -> Duration {
+        self.delay
+            .saturating_mul(2u32.saturating_pow(retries))
+            .min(MAX_RETRY_DELAY)
+    }
+}
+
+/// Schedules the questions on `ask`. The concurrency is halved every time the
+/// provider rate limits a question, which is then queued again, and grows back
+/// by one with every answer, up to `concurrency`.
+async fn ask_many<Q, A, F, Fut>(
+    questions: Vec<Q>,
+    concurrency: usize,
+    retry: Retry,
+    ask: F,
+    mut on_answer: impl FnMut(usize, &Result<A>),
+) -> Vec<Result<A>>
+where
+    Q: Clone,
+    F: Fn(Q) -> Fut,
+    Fut: Future<Output = Result<A>>,
+{
+    let total = questions.len();
+    let max_concurrency = concurrency.max(1);
+    let mut concurrency = max_concurrency;
+
+    let mut queue = questions
+        .into_iter()
+        .enumerate()
+        .map(|(index, question)| (index, question, 0))
+        .collect::<VecDeque<_>>();
+    let mut answers = std::iter::repeat_with(|| None)
+        .take(total)
+        .collect::<Vec<Option<Result<A>>>>();
+    let mut in_flight = FuturesUnordered::new();
+
+    loop {
+        while in_flight.len() < concurrency {
+            let Some((index, question, rate_limited)) = queue.pop_front() else {
+                break;
+            };
+            in_flight.push(attempt(index, question, rate_limited, retry, &ask));
+        }
+
+        let Some((index, question, rate_limited, answer)) = in_flight.next().await else {
+            break;
+        };
+
+        match answer {
+            Err(Error::RateLimited) if rate_limited < MAX_RATE_LIMITED => {
+                concurrency = (concurrency / 2).max(1);
+                tracing::warn!("Rate limited, lowering the concurrency to {}", concurrency);
+                queue.push_front((index, question, rate_limited + 1));
+            }
+            answer => {
+                if answer.is_ok() {
+                    concurrency = (concurrency + 1).min(max_concurrency);
+                }
+                on_answer(index, &answer);
+                answers[index] = Some(answer);
+            }
+        }
+    }
+
+    answers
+        .into_iter()
+        .map(|answer| answer.unwrap_or(Err(Error::EmptyResponse)))
+        .collect()
+}
+
+/// Asks a question, retrying on failures other than rate limits, which are
+/// left to the scheduler.
+async fn attempt<Q, A, F, Fut>(
+    index: usize,
+    question: Q,
+    rate_limited: u32,
+    retry: Retry,
+    ask: &F,
+) -> (usize, Q, u32, Result<A>)
+where
+    Q: Clone,
+    F: Fn(Q) -> Fut,
+    Fut: Future<Output = Result<A>>,
+{
+    if rate_limited > 0 {
+        tokio::time::sleep(retry.backoff(rate_limited - 1)).await;
+    }
+
+    let mut retries = 0;
+    loop {
+        match ask(question.clone()).await {
+            Err(err) if !matches!(err, Error::RateLimited) && retries + 1 < retry.max_attempts => {
+                let delay = retry.backoff(retries);
+                tracing::warn!("Unable to get an answer: {}. Retrying in {:?}", err, delay);
+                tokio::time::sleep(delay).await;
+                retries += 1;
+            }
+            answer => return (index, question, rate_limited, answer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use genai::chat::{ChatMessage, ChatRequest};
+
+    use super::{ask_many, estimate_tokens, Retry};
+    use crate::cli::llm::{Error, Result};
+
+    const RETRY: Retry = Retry { delay: Duration::from_millis(1), max_attempts: 3 };
+
+    /// A provider that answers with the square of the question, tracking the
+    /// requests in flight.
+    #[derive(Default)]
+    struct Provider {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        /// Errors returned, in order, by the next calls for a question.
+        errors: Mutex<HashMap<u64, Vec<Error>>>,
+    }
+
+    impl Provider {
+        fn fail(self, question: u64, errors: Vec<Error>) -> Self {
+            self.errors.lock().unwrap().insert(question, errors);
+            self
+        }
+
+        async fn ask(&self, question: u64) -> Result<u64> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            // The later questions are answered first.
+            tokio::time::sleep(Duration::from_millis(20 - question)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let error = self
+                .errors
+                .lock()
+                .unwrap()
+                .get_mut(&question)
+                .and_then(|errors| (!errors.is_empty()).then(|| errors.remove(0)));
+            match error {
+                Some(error) => Err(error),
+                None => Ok(question * question),
+            }
+        }
+    }
+
+    fn errors(count: usize, error: impl Fn() -> Error) -> Vec<Error> {
+        (0..count).map(|_| error()).collect()
+    }
+
+    fn answers(answers: Vec<Result<u64>>) -> Vec<Option<u64>> {
+        answers.into_iter().map(|answer| answer.ok()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_ask_many_in_order() {
+        let provider = Provider::default();
+        let mut answered = vec![];
+
+        let actual = ask_many(
+            (0..10).collect(),
+            3,
+            RETRY,
+            |q| provider.ask(q),
+            |index, answer| answered.push((index, answer.as_ref().ok().copied())),
+        )
+        .await;
+
+        let expected = (0..10).map(|q| Some(q * q)).collect::<Vec<_>>();
+        assert_eq!(answers(actual), expected);
+        answered.sort();
+        assert_eq!(
+            answered,
+            (0..10)
+                .map(|q| (q as usize, Some(q * q)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_ask_many_retries() {
+        let provider = Provider::default()
+            .fail(2, errors(2, || Error::EmptyResponse))
+            .fail(4, errors(3, || Error::EmptyResponse));
+
+        let actual = ask_many((0..6).collect(), 2, RETRY, |q| provider.ask(q), |_, _| {}).await;
+
+        assert_eq!(
+            answers(actual),
+            [Some(0), Some(1), Some(4), Some(9), None, Some(25)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ask_many_rate_limited() {
+        let provider = Provider::default()
+            .fail(0, errors(3, || Error::RateLimited))
+            .fail(1, errors(1, || Error::RateLimited));
+
+        let actual = ask_many((0..8).collect(), 4, RETRY, |q| provider.ask(q), |_, _| {}).await;
+
+        let expected = (0..8).map(|q| Some(q * q)).collect::<Vec<_>>();
+        assert_eq!(answers(actual), expected);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        let request = ChatRequest::new(vec![
+            ChatMessage::system("a".repeat(10)),
+            ChatMessage::user("b".repeat(7)),
+        ]);
+
+        assert_eq!(estimate_tokens(&request), 5);
     }
 }
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 
+use crate::core::app_context::AppContextHandle;
 use crate::core::runtime::TargetRuntime;
 
 fn cache_metrics(runtime: &TargetRuntime) -> Result<()> {
@@ -19,14 +20,59 @@ fn cache_metrics(runtime: &TargetRuntime) -> Result<()> {
     Ok(())
 }
 
+/// The size of the schema being served, read on every collection so that it
+/// follows the reloads of the config.
+fn blueprint_metrics(app_ctx: AppContextHandle) -> Result<()> {
+    let meter = opentelemetry::global::meter("blueprint");
+    let gauge = |name: &'static str, description: &'static str| {
+        meter
+            .u64_observable_gauge(name)
+            .with_description(description)
+            .init()
+    };
+    let types = gauge("blueprint.type.count", "Number of types in the schema");
+    let fields = gauge("blueprint.field.count", "Number of fields in the schema");
+    let resolvers = gauge(
+        "blueprint.resolver.count",
+        "Number of fields with a resolver",
+    );
+    let unions = gauge("blueprint.union.count", "Number of unions in the schema");
+    let enums = gauge("blueprint.enum.count", "Number of enums in the schema");
+    let directives = gauge(
+        "blueprint.directive.count",
+        "Number of directives in the schema",
+    );
+
+    let instruments = [
+        types.as_any(),
+        fields.as_any(),
+        resolvers.as_any(),
+        unions.as_any(),
+        enums.as_any(),
+        directives.as_any(),
+    ];
+    meter.register_callback(&instruments, move |observer| {
+        let metrics = app_ctx.load().blueprint.metrics();
+        observer.observe_u64(&types, metrics.type_count as u64, &[]);
+        observer.observe_u64(&fields, metrics.field_count as u64, &[]);
+        observer.observe_u64(&resolvers, metrics.resolver_count as u64, &[]);
+        observer.observe_u64(&unions, metrics.union_count as u64, &[]);
+        observer.observe_u64(&enums, metrics.enum_count as u64, &[]);
+        observer.observe_u64(&directives, metrics.directive_count as u64, &[]);
+    })?;
+
+    Ok(())
+}
+
 fn process_resources_metrics() -> Result<()> {
     let meter = opentelemetry::global::meter("process-resources");
 
     opentelemetry_system_metrics::init_process_observer(meter).map_err(|err| anyhow!(err))
 }
 
-pub fn init_metrics(runtime: &TargetRuntime) -> Result<()> {
+pub fn init_metrics(runtime: &TargetRuntime, app_ctx: AppContextHandle) -> Result<()> {
     cache_metrics(runtime)?;
+    blueprint_metrics(app_ctx)?;
     process_resources_metrics()?;
 
     Ok(())
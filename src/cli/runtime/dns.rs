@@ -0,0 +1,347 @@
+//! Resolves the upstream hosts for the native http client, applying the
+//! overrides and caching the answers configured in @upstream.
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use hickory_resolver::config::{LookupIpStrategy, ResolverConfig, ResolverOpts};
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::TokioAsyncResolver;
+use once_cell::sync::{Lazy, OnceCell};
+use opentelemetry::metrics::{Counter, Histogram};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+use crate::core::blueprint::{DnsCache, Upstream};
+
+static DNS_CACHE_HIT_COUNT: Lazy<Counter<u64>> = Lazy::new(|| {
+    let meter = opentelemetry::global::meter("dns");
+
+    meter
+        .u64_counter("dns.cache.hit.count")
+        .with_description("Number of upstream hosts resolved from the DNS cache")
+        .init()
+});
+
+static DNS_CACHE_MISS_COUNT: Lazy<Counter<u64>> = Lazy::new(|| {
+    let meter = opentelemetry::global::meter("dns");
+
+    meter
+        .u64_counter("dns.cache.miss.count")
+        .with_description("Number of upstream hosts that had to be looked up")
+        .init()
+});
+
+static DNS_LOOKUP_DURATION: Lazy<Histogram<f64>> = Lazy::new(|| {
+    let meter = opentelemetry::global::meter("dns");
+
+    meter
+        .f64_histogram("dns.lookup.duration")
+        .with_description("Duration of the DNS lookups in seconds")
+        .init()
+});
+
+/// The addresses a host resolved to and how long they can be reused for.
+#[derive(Clone, Debug)]
+pub struct DnsAnswer {
+    pub addresses: Vec<IpAddr>,
+    pub ttl: Duration,
+}
+
+/// Looks hosts up in DNS. Resolves to `None` when the host doesn't exist.
+#[async_trait::async_trait]
+pub trait DnsResolver: Send + Sync {
+    async fn lookup(&self, host: &str) -> Result<Option<DnsAnswer>>;
+}
+
+/// Looks hosts up with the system's resolver configuration, asking for both
+/// the A and AAAA records.
+#[derive(Default)]
+pub struct HickoryResolver {
+    resolver: OnceCell<TokioAsyncResolver>,
+}
+
+impl HickoryResolver {
+    fn resolver(&self) -> &TokioAsyncResolver {
+        self.resolver.get_or_init(|| {
+            let (config, mut opts) = hickory_resolver::system_conf::read_system_conf()
+                .unwrap_or_else(|_| (ResolverConfig::default(), ResolverOpts::default()));
+            opts.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+            TokioAsyncResolver::tokio(config, opts)
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl DnsResolver for HickoryResolver {
+    async fn lookup(&self, host: &str) -> Result<Option<DnsAnswer>> {
+        match self.resolver().lookup_ip(host).await {
+            Ok(lookup) => Ok(Some(DnsAnswer {
+                addresses: lookup.iter().collect(),
+                ttl: lookup
+                    .valid_until()
+                    .saturating_duration_since(Instant::now()),
+            })),
+            Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum CachedAnswer {
+    Found(Vec<IpAddr>),
+    NotFound,
+}
+
+#[derive(Clone)]
+struct CacheEntry {
+    answer: CachedAnswer,
+    expires_at: Instant,
+}
+
+/// Resolves the hosts of an upstream, preferring its `dnsOverrides` and
+/// keeping the lookups in memory when `dnsCache` is set.
+#[derive(Clone)]
+pub struct UpstreamResolver {
+    resolver: Arc<dyn DnsResolver>,
+    overrides: BTreeMap<String, IpAddr>,
+    cache: Option<DnsCache>,
+    entries: Arc<DashMap<String, CacheEntry>>,
+}
+
+impl UpstreamResolver {
+    pub fn new(resolver: Arc<dyn DnsResolver>, upstream: &Upstream) -> Self {
+        Self {
+            resolver,
+            overrides: upstream.dns_overrides.clone(),
+            cache: upstream.dns_cache.clone(),
+            entries: Default::default(),
+        }
+    }
+
+    async fn resolve_at(&self, host: &str, now: Instant) -> Result<Vec<IpAddr>> {
+        let host = host.to_lowercase();
+        if let Some(address) = self.overrides.get(&host) {
+            return Ok(vec![*address]);
+        }
+
+        let Some(cache) = &self.cache else {
+            return match self.lookup(&host).await? {
+                Some(answer) => happy_eyeballs(&host, answer.addresses),
+                None => Err(not_found(&host)),
+            };
+        };
+
+        let cached = self
+            .entries
+            .get(&host)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.answer.clone());
+
+        let answer = match cached {
+            Some(answer) => {
+                DNS_CACHE_HIT_COUNT.add(1, &[]);
+                answer
+            }
+            None => {
+                DNS_CACHE_MISS_COUNT.add(1, &[]);
+                let (answer, ttl) = match self.lookup(&host).await? {
+                    Some(answer) => (
+                        CachedAnswer::Found(answer.addresses),
+                        answer.ttl.min(Duration::from_secs(cache.max_ttl)),
+                    ),
+                    None => (
+                        CachedAnswer::NotFound,
+                        Duration::from_secs(cache.negative_ttl),
+                    ),
+                };
+                self.entries.insert(
+                    host.clone(),
+                    CacheEntry { answer: answer.clone(), expires_at: now + ttl },
+                );
+                answer
+            }
+        };
+
+        match answer {
+            CachedAnswer::Found(addresses) => happy_eyeballs(&host, addresses),
+            CachedAnswer::NotFound => Err(not_found(&host)),
+        }
+    }
+
+    async fn lookup(&self, host: &str) -> Result<Option<DnsAnswer>> {
+        let start = Instant::now();
+        let answer = self.resolver.lookup(host).await;
+        DNS_LOOKUP_DURATION.record(start.elapsed().as_secs_f64(), &[]);
+        answer
+    }
+}
+
+fn not_found(host: &str) -> anyhow::Error {
+    anyhow!("No addresses found for upstream host `{}`", host)
+}
+
+/// Orders the IPv6 addresses first. The connector races the address families
+/// and falls back to IPv4 when IPv6 doesn't connect in time.
+fn happy_eyeballs(host: &str, addresses: Vec<IpAddr>) -> Result<Vec<IpAddr>> {
+    if addresses.is_empty() {
+        return Err(not_found(host));
+    }
+
+    let (mut ordered, ipv4): (Vec<_>, Vec<_>) =
+        addresses.into_iter().partition(|address| address.is_ipv6());
+    ordered.extend(ipv4);
+    Ok(ordered)
+}
+
+impl Resolve for UpstreamResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addresses = resolver.resolve_at(name.as_str(), Instant::now()).await?;
+            let addrs: Addrs = Box::new(
+                addresses
+                    .into_iter()
+                    .map(|address| SocketAddr::new(address, 0)),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockResolver {
+        answer: Option<DnsAnswer>,
+        lookups: AtomicUsize,
+    }
+
+    impl MockResolver {
+        fn new(addresses: &[&str], ttl: u64) -> Self {
+            Self {
+                answer: Some(DnsAnswer {
+                    addresses: addresses.iter().map(|a| a.parse().unwrap()).collect(),
+                    ttl: Duration::from_secs(ttl),
+                }),
+                lookups: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DnsResolver for MockResolver {
+        async fn lookup(&self, _: &str) -> Result<Option<DnsAnswer>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.answer.clone())
+        }
+    }
+
+    fn resolver(mock: &Arc<MockResolver>, upstream: &Upstream) -> UpstreamResolver {
+        UpstreamResolver::new(mock.clone(), upstream)
+    }
+
+    fn cached() -> Upstream {
+        Upstream {
+            dns_cache: Some(DnsCache { max_ttl: 300, negative_ttl: 30 }),
+            ..Default::default()
+        }
+    }
+
+    fn ips(addresses: &[&str]) -> Vec<IpAddr> {
+        addresses.iter().map(|a| a.parse().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_override_precedence() {
+        let mock = Arc::new(MockResolver::new(&["10.0.0.1"], 60));
+        let upstream = cached().dns_overrides(BTreeMap::from([(
+            "api.example.com".to_string(),
+            "10.0.0.2".parse().unwrap(),
+        )]));
+        let resolver = resolver(&mock, &upstream);
+        let now = Instant::now();
+
+        let addresses = resolver.resolve_at("API.example.com", now).await.unwrap();
+        assert_eq!(addresses, ips(&["10.0.0.2"]));
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 0);
+
+        let addresses = resolver.resolve_at("example.com", now).await.unwrap();
+        assert_eq!(addresses, ips(&["10.0.0.1"]));
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_expiry() {
+        let mock = Arc::new(MockResolver::new(&["10.0.0.1"], 60));
+        let resolver = resolver(&mock, &cached());
+        let now = Instant::now();
+
+        resolver.resolve_at("example.com", now).await.unwrap();
+        resolver
+            .resolve_at("example.com", now + Duration::from_secs(59))
+            .await
+            .unwrap();
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 1);
+
+        resolver
+            .resolve_at("example.com", now + Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_ttl_clamp() {
+        let mock = Arc::new(MockResolver::new(&["10.0.0.1"], 3600));
+        let resolver = resolver(&mock, &cached());
+        let now = Instant::now();
+
+        resolver.resolve_at("example.com", now).await.unwrap();
+        resolver
+            .resolve_at("example.com", now + Duration::from_secs(300))
+            .await
+            .unwrap();
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_negative_cache() {
+        let mock = Arc::new(MockResolver::default());
+        let resolver = resolver(&mock, &cached());
+        let now = Instant::now();
+
+        assert!(resolver.resolve_at("missing.com", now).await.is_err());
+        assert!(resolver
+            .resolve_at("missing.com", now + Duration::from_secs(29))
+            .await
+            .is_err());
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 1);
+
+        assert!(resolver
+            .resolve_at("missing.com", now + Duration::from_secs(30))
+            .await
+            .is_err());
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_without_cache() {
+        let mock = Arc::new(MockResolver::new(&["10.0.0.1", "::1"], 60));
+        let resolver = resolver(&mock, &Upstream::default());
+        let now = Instant::now();
+
+        let addresses = resolver.resolve_at("example.com", now).await.unwrap();
+        assert_eq!(addresses, ips(&["::1", "10.0.0.1"]));
+        resolver.resolve_at("example.com", now).await.unwrap();
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use http_cache_reqwest::{Cache, CacheMode, HttpCache, HttpCacheOptions};
 use hyper::body::Bytes;
 use once_cell::sync::Lazy;
@@ -16,10 +19,11 @@ use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use tailcall_http_cache::HttpCacheManager;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use super::dns::{DnsResolver, HickoryResolver, UpstreamResolver};
 use super::HttpIO;
 use crate::core::blueprint::telemetry::Telemetry;
-use crate::core::blueprint::Upstream;
-use crate::core::http::Response;
+use crate::core::blueprint::{DnsCache, Upstream};
+use crate::core::http::{Response, StatusError};
 
 static HTTP_CLIENT_REQUEST_COUNT: Lazy<Counter<u64>> = Lazy::new(|| {
     let meter = opentelemetry::global::meter("http_request");
@@ -86,6 +90,16 @@ impl Default for NativeHttp {
 
 impl NativeHttp {
     pub fn init(upstream: &Upstream, telemetry: &Telemetry) -> Self {
+        Self::init_with_resolver(upstream, telemetry, Arc::new(HickoryResolver::default()))
+    }
+
+    /// Builds the client with `resolver` doing the DNS lookups whenever the
+    /// upstream overrides or caches them.
+    pub fn init_with_resolver(
+        upstream: &Upstream,
+        telemetry: &Telemetry,
+        resolver: Arc<dyn DnsResolver>,
+    ) -> Self {
         let mut builder = Client::builder()
             .tcp_keepalive(Some(Duration::from_secs(upstream.tcp_keep_alive)))
             .timeout(Duration::from_secs(upstream.timeout))
@@ -103,6 +117,10 @@ impl NativeHttp {
             builder = builder.http2_prior_knowledge();
         }
 
+        if upstream.dns_cache.is_some() || !upstream.dns_overrides.is_empty() {
+            builder = builder.dns_resolver(Arc::new(UpstreamResolver::new(resolver, upstream)));
+        }
+
         // Add Http Proxy
         if let Some(ref proxy) = upstream.proxy {
             builder = builder.proxy(
@@ -128,6 +146,85 @@ impl NativeHttp {
     }
 }
 
+/// The settings a client is built with. Upstreams that agree on them share a
+/// client, and with it the connection pool.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ClientKey {
+    pool_idle_timeout: u64,
+    pool_max_idle_per_host: usize,
+    keep_alive_interval: u64,
+    keep_alive_timeout: u64,
+    keep_alive_while_idle: bool,
+    proxy: Option<String>,
+    connect_timeout: u64,
+    dns_cache: Option<DnsCache>,
+    dns_overrides: BTreeMap<String, IpAddr>,
+    timeout: u64,
+    tcp_keep_alive: u64,
+    user_agent: String,
+    http_cache: u64,
+    http2_only: bool,
+    verify_ssl: bool,
+    enable_telemetry: bool,
+}
+
+impl ClientKey {
+    fn new(upstream: &Upstream, telemetry: &Telemetry) -> Self {
+        Self {
+            pool_idle_timeout: upstream.pool_idle_timeout,
+            pool_max_idle_per_host: upstream.pool_max_idle_per_host,
+            keep_alive_interval: upstream.keep_alive_interval,
+            keep_alive_timeout: upstream.keep_alive_timeout,
+            keep_alive_while_idle: upstream.keep_alive_while_idle,
+            proxy: upstream.proxy.as_ref().map(|proxy| proxy.url.clone()),
+            connect_timeout: upstream.connect_timeout,
+            dns_cache: upstream.dns_cache.clone(),
+            dns_overrides: upstream.dns_overrides.clone(),
+            timeout: upstream.timeout,
+            tcp_keep_alive: upstream.tcp_keep_alive,
+            user_agent: upstream.user_agent.clone(),
+            http_cache: upstream.http_cache,
+            http2_only: upstream.http2_only,
+            verify_ssl: upstream.verify_ssl,
+            enable_telemetry: telemetry.export.is_some(),
+        }
+    }
+}
+
+/// Hands out a client per distinct set of upstream settings, reusing the
+/// client that was already built for the same settings.
+pub struct NativeHttpPool {
+    clients: Mutex<HashMap<ClientKey, Arc<NativeHttp>>>,
+    resolver: Arc<dyn DnsResolver>,
+}
+
+impl Default for NativeHttpPool {
+    fn default() -> Self {
+        Self::with_resolver(Arc::new(HickoryResolver::default()))
+    }
+}
+
+impl NativeHttpPool {
+    pub fn with_resolver(resolver: Arc<dyn DnsResolver>) -> Self {
+        Self { clients: Default::default(), resolver }
+    }
+
+    pub fn get(&self, upstream: &Upstream, telemetry: &Telemetry) -> Arc<NativeHttp> {
+        self.clients
+            .lock()
+            .unwrap()
+            .entry(ClientKey::new(upstream, telemetry))
+            .or_insert_with(|| {
+                Arc::new(NativeHttp::init_with_resolver(
+                    upstream,
+                    telemetry,
+                    self.resolver.clone(),
+                ))
+            })
+            .clone()
+    }
+}
+
 #[async_trait::async_trait]
 impl HttpIO for NativeHttp {
     #[allow(clippy::blocks_in_conditions)]
@@ -166,6 +263,7 @@ impl HttpIO for NativeHttp {
             request.version()
         );
         tracing::debug!("request: {:?}", request);
+        let upstream = request.url().authority().to_string();
         let response = self.client.execute(request).await;
         tracing::debug!("response: {:?}", response);
 
@@ -176,12 +274,20 @@ impl HttpIO for NativeHttp {
             tracing::Span::current().set_attribute(status_code.key, status_code.value);
         }
 
-        Ok(Response::from_reqwest(
-            response?
-                .error_for_status()
-                .map_err(|err| err.without_url())?,
-        )
-        .await?)
+        // A server that only speaks HTTP/1.1 fails the connection outright when
+        // it's sent HTTP/2 without negotiation, so point at the likely cause.
+        let response = match response {
+            Err(err) if self.http2_only => {
+                return Err(anyhow!(err).context(format!(
+                    "HTTP/2 request to upstream `{}` failed, make sure it supports HTTP/2 \
+                     or disable `http2Only` in @upstream",
+                    upstream
+                )));
+            }
+            response => response,
+        };
+
+        Ok(Response::from_reqwest(StatusError::check(response?).await?).await?)
     }
 }
 
@@ -189,8 +295,10 @@ impl HttpIO for NativeHttp {
 mod tests {
     use reqwest::Method;
     use tokio;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     use super::*;
+    use crate::cli::runtime::DnsAnswer;
     use crate::core::http::Response;
 
     fn start_mock_server() -> httpmock::MockServer {
@@ -282,4 +390,92 @@ mod tests {
         let resp = make_request(&url1, &native_http).await;
         assert_eq!(resp.headers.get("x-cache-lookup").unwrap(), "MISS");
     }
+
+    #[test]
+    fn test_native_http_pool() {
+        let pool = NativeHttpPool::default();
+        let telemetry = Telemetry::default();
+        let upstream = Upstream::default();
+
+        let http = pool.get(&upstream, &telemetry);
+        assert!(Arc::ptr_eq(&http, &pool.get(&upstream, &telemetry)));
+        assert!(Arc::ptr_eq(
+            &http,
+            &pool.get(
+                &upstream.clone().on_request(Some("onRequest".into())),
+                &telemetry
+            )
+        ));
+
+        let http2 = pool.get(&upstream.clone().http2_only(true), &telemetry);
+        assert!(!Arc::ptr_eq(&http, &http2));
+        assert!(Arc::ptr_eq(
+            &http2,
+            &pool.get(&upstream.clone().http2_only(true), &telemetry)
+        ));
+
+        let pooled = pool.get(&upstream.clone().pool_max_idle_per_host(1), &telemetry);
+        assert!(!Arc::ptr_eq(&http, &pooled));
+        assert_eq!(pool.clients.lock().unwrap().len(), 3);
+    }
+
+    struct Ipv6FirstResolver;
+
+    #[async_trait::async_trait]
+    impl DnsResolver for Ipv6FirstResolver {
+        async fn lookup(&self, _: &str) -> Result<Option<DnsAnswer>> {
+            Ok(Some(DnsAnswer {
+                addresses: vec!["::1".parse()?, "127.0.0.1".parse()?],
+                ttl: Duration::from_secs(60),
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_http_falls_back_to_ipv4() {
+        // Only listens on IPv4, so the connection to ::1 is refused.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nHello")
+                .await
+                .unwrap();
+        });
+
+        let upstream = Upstream {
+            dns_cache: Some(DnsCache { max_ttl: 300, negative_ttl: 30 }),
+            ..Default::default()
+        };
+        let pool = NativeHttpPool::with_resolver(Arc::new(Ipv6FirstResolver));
+        let native_http = pool.get(&upstream, &Default::default());
+        let request_url = format!("http://upstream.test:{}/", port);
+        let response = make_request(&request_url, &native_http).await;
+
+        assert_eq!(response.status, reqwest::StatusCode::OK);
+        assert_eq!(response.body, Bytes::from("Hello"));
+    }
+
+    #[tokio::test]
+    async fn test_native_http_http2_only_against_http1() {
+        let server = start_mock_server();
+
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/test");
+            then.status(200).body("Hello");
+        });
+
+        let upstream = Upstream { http2_only: true, ..Default::default() };
+        let native_http = NativeHttp::init(&upstream, &Default::default());
+        let request_url = format!("http://localhost:{}/test", server.port());
+        let request = reqwest::Request::new(Method::GET, request_url.parse().unwrap());
+        let error = native_http.execute(request).await.unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains(&format!("upstream `localhost:{}`", server.port())));
+    }
 }
@@ -1,3 +1,4 @@
+mod dns;
 mod env;
 mod file;
 mod http;
@@ -6,11 +7,17 @@ use std::fs;
 use std::hash::Hash;
 use std::sync::Arc;
 
-pub use http::NativeHttp;
+use anyhow::Context;
+pub use dns::{DnsAnswer, DnsResolver};
+pub use http::{NativeHttp, NativeHttpPool};
 use inquire::{Confirm, Select};
+use once_cell::sync::OnceCell;
 
 use crate::core::blueprint::Blueprint;
 use crate::core::cache::InMemoryCache;
+use crate::core::http::{
+    Cassette, CassetteHttp, CassetteRecorder, MockHttp, TAILCALL_RECORD, TAILCALL_REPLAY,
+};
 use crate::core::runtime::TargetRuntime;
 use crate::core::worker::{Command, Event};
 use crate::core::{blueprint, EnvIO, FileIO, HttpIO, WorkerIO};
@@ -49,20 +56,93 @@ fn init_resolver_worker_io(
     }
 }
 
+/// The cassette set through the environment, see [init_cassette].
+enum EnvCassette {
+    Record(Arc<CassetteRecorder>),
+    Replay(Arc<Cassette>),
+}
+
+static CASSETTE: OnceCell<EnvCassette> = OnceCell::new();
+
+/// Reads the cassette set through the environment once, for all the runtimes
+/// to share it. Fails when the cassette to replay can't be read, or when the
+/// file to record into exists and isn't a cassette, instead of overwriting it.
+pub fn init_cassette() -> anyhow::Result<()> {
+    let cassette = if let Ok(path) = std::env::var(TAILCALL_REPLAY) {
+        EnvCassette::Replay(Arc::new(read_cassette(&path)?))
+    } else if let Ok(path) = std::env::var(TAILCALL_RECORD) {
+        let cassette = if fs::metadata(&path).is_ok() {
+            read_cassette(&path)?
+        } else {
+            Cassette::default()
+        };
+        let recorder = CassetteRecorder::new(path, cassette, init_file());
+        EnvCassette::Record(Arc::new(recorder))
+    } else {
+        return Ok(());
+    };
+    let _ = CASSETTE.set(cassette);
+    Ok(())
+}
+
+/// Waits until the interactions recorded into the cassette set through the
+/// environment are written, when there's one.
+pub async fn flush_cassette() {
+    if let Some(EnvCassette::Record(recorder)) = CASSETTE.get() {
+        recorder.flush().await;
+    }
+}
+
+fn read_cassette(path: &str) -> anyhow::Result<Cassette> {
+    fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(serde_json::from_str(&content)?))
+        .with_context(|| format!("Failed to read cassette {path}"))
+}
+
+// Records upstream traffic into, or replays it from, the cassette set through
+// the environment
+fn with_cassette(http: Arc<dyn HttpIO>) -> Arc<dyn HttpIO> {
+    match CASSETTE.get() {
+        Some(EnvCassette::Record(recorder)) => {
+            Arc::new(CassetteHttp::record(recorder.clone(), http))
+        }
+        Some(EnvCassette::Replay(cassette)) => Arc::new(CassetteHttp::replay(cassette.clone())),
+        None => http,
+    }
+}
+
+// Serves upstream requests from the mock table when mocking is enabled
+fn with_mocks(blueprint: &Blueprint, http: Arc<dyn HttpIO>) -> Arc<dyn HttpIO> {
+    match blueprint.mocks.clone() {
+        Some(table) => Arc::new(MockHttp::new(table, http)),
+        None => http,
+    }
+}
+
 // Provides access to http in native rust environment
-fn init_http(blueprint: &Blueprint) -> Arc<dyn HttpIO> {
-    Arc::new(http::NativeHttp::init(
-        &blueprint.upstream,
-        &blueprint.telemetry,
-    ))
+fn init_http(blueprint: &Blueprint, pool: &NativeHttpPool) -> Arc<dyn HttpIO> {
+    let http = pool.get(&blueprint.upstream, &blueprint.telemetry);
+    with_mocks(blueprint, with_cassette(http))
 }
 
 // Provides access to http in native rust environment
-fn init_http2_only(blueprint: &Blueprint) -> Arc<dyn HttpIO> {
-    Arc::new(http::NativeHttp::init(
+fn init_http2_only(blueprint: &Blueprint, pool: &NativeHttpPool) -> Arc<dyn HttpIO> {
+    let http = pool.get(
         &blueprint.upstream.clone().http2_only(true),
         &blueprint.telemetry,
-    ))
+    );
+    with_mocks(blueprint, with_cassette(http))
+}
+
+// Provides access to http in native rust environment without verifying the
+// TLS certificates
+fn init_http_insecure(blueprint: &Blueprint, pool: &NativeHttpPool) -> Arc<dyn HttpIO> {
+    let http = pool.get(
+        &blueprint.upstream.clone().verify_ssl(false),
+        &blueprint.telemetry,
+    );
+    with_mocks(blueprint, with_cassette(http))
 }
 
 fn init_in_memory_cache<K: Hash + Eq, V: Clone>() -> InMemoryCache<K, V> {
@@ -73,9 +153,12 @@ pub fn init(blueprint: &Blueprint) -> TargetRuntime {
     #[cfg(not(feature = "js"))]
     tracing::warn!("JS capabilities are disabled in this build");
 
+    let pool = NativeHttpPool::default();
+
     TargetRuntime {
-        http: init_http(blueprint),
-        http2_only: init_http2_only(blueprint),
+        http: init_http(blueprint, &pool),
+        http2_only: init_http2_only(blueprint, &pool),
+        http_insecure: init_http_insecure(blueprint, &pool),
         env: init_env(),
         file: init_file(),
         cache: Arc::new(init_in_memory_cache()),
@@ -0,0 +1,267 @@
+//! Endpoints to operate a running server, served on their own port so they
+//! can be kept private. Every request must carry the configured token, as
+//! `Authorization: Bearer <token>`.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use async_graphql_value::ConstValue;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+
+use super::reload::Reloader;
+use crate::core::app_context::AppContextHandle;
+use crate::core::blueprint::{Admin, Blueprint};
+use crate::core::jit::{self, QueryComplexity};
+use crate::core::tracing::{log_filter, LogFilterHandle};
+use crate::core::Errata;
+
+pub async fn start_admin(
+    admin: Admin,
+    hostname: IpAddr,
+    reloader: Arc<Reloader>,
+    app_ctx: AppContextHandle,
+    log_filter: LogFilterHandle,
+) -> anyhow::Result<()> {
+    let addr = SocketAddr::new(hostname, admin.port);
+    let token = Arc::new(admin.token);
+    let make_svc = make_service_fn(move |_conn| {
+        let token = token.clone();
+        let reloader = reloader.clone();
+        let app_ctx = app_ctx.clone();
+        let log_filter = log_filter.clone();
+        async move {
+            Ok::<_, anyhow::Error>(service_fn(move |req| {
+                handle_admin(
+                    req,
+                    token.clone(),
+                    reloader.clone(),
+                    app_ctx.clone(),
+                    log_filter.clone(),
+                )
+            }))
+        }
+    });
+
+    let builder = hyper::Server::try_bind(&addr).map_err(Errata::from)?;
+    tracing::info!("🔧 Admin endpoints at [{}]", addr);
+    builder.serve(make_svc).await.map_err(Errata::from)?;
+
+    Ok(())
+}
+
+async fn handle_admin(
+    req: Request<Body>,
+    token: Arc<String>,
+    reloader: Arc<Reloader>,
+    app_ctx: AppContextHandle,
+    log_filter: LogFilterHandle,
+) -> anyhow::Result<Response<Body>> {
+    if !is_authorized(&req, &token) {
+        return Ok(Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::empty())?);
+    }
+
+    match (req.method(), req.uri().path()) {
+        (&Method::POST, "/admin/reload") => {
+            let result = reloader.reload().await;
+            let status = if result.is_ok() {
+                StatusCode::OK
+            } else {
+                StatusCode::UNPROCESSABLE_ENTITY
+            };
+            Ok(Response::builder()
+                .status(status)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&result)?))?)
+        }
+        (&Method::GET, "/admin/complexity") => complexity(&req, &app_ctx.load().blueprint),
+        (&Method::PUT, "/admin/log-level") => log_level(req, &log_filter).await,
+        _ => Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())?),
+    }
+}
+
+/// Measures the operation passed as the `query` parameter. The plan isn't
+/// checked against the admission rules, so that the rejected operations can
+/// be looked into.
+fn complexity(req: &Request<Body>, blueprint: &Blueprint) -> anyhow::Result<Response<Body>> {
+    let query = req.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(name, _)| name == "query")
+            .map(|(_, value)| value.into_owned())
+    });
+    let Some(query) = query else {
+        return bad_request("The `query` parameter is required");
+    };
+
+    match jit::Request::<ConstValue>::new(&query).build_plan(blueprint) {
+        Ok(plan) => {
+            let report = QueryComplexity::new(usize::MAX).report(&plan);
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&report)?))?)
+        }
+        Err(err) => bad_request(&err.to_string()),
+    }
+}
+
+/// Replaces the filter of the logs with the directives sent as the body, eg.
+/// `tailcall::core::http=debug`. The level of `TAILCALL_LOG_LEVEL` still
+/// applies to the modules they don't name.
+async fn log_level(req: Request<Body>, handle: &LogFilterHandle) -> anyhow::Result<Response<Body>> {
+    let body = hyper::body::to_bytes(req.into_body()).await?;
+    let directives = String::from_utf8_lossy(&body);
+    let directives = directives.trim();
+    let filter = match log_filter(Some(directives)) {
+        Ok(filter) => filter,
+        Err(err) => return bad_request(&err.to_string()),
+    };
+
+    if let Err(err) = handle.reload(filter) {
+        return Ok(Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(
+                &serde_json::json!({ "error": err.to_string() }),
+            )?))?);
+    }
+    tracing::info!("The log filter is now `{}`", directives);
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(
+            &serde_json::json!({ "filter": directives }),
+        )?))?)
+}
+
+fn bad_request(error: &str) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(
+            &serde_json::json!({ "error": error }),
+        )?))?)
+}
+
+fn is_authorized(req: &Request<Body>, token: &str) -> bool {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+/// Compares the tokens without stopping at the first difference, so the time
+/// taken doesn't tell how much of the token was guessed.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::Validator;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::reload;
+
+    use super::*;
+    use crate::core::config::Config;
+
+    fn request(authorization: Option<&str>) -> Request<Body> {
+        let mut builder = Request::post("http://localhost/admin/reload");
+        if let Some(value) = authorization {
+            builder = builder.header(AUTHORIZATION, value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized(&request(Some("Bearer secret")), "secret"));
+        assert!(!is_authorized(&request(Some("Bearer secrets")), "secret"));
+        assert!(!is_authorized(&request(Some("secret")), "secret"));
+        assert!(!is_authorized(&request(None), "secret"));
+    }
+
+    async fn complexity_json(blueprint: &Blueprint, uri: &str) -> (StatusCode, serde_json::Value) {
+        let req = Request::get(uri).body(Body::empty()).unwrap();
+        let response = complexity(&req, blueprint).unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_complexity() {
+        let sdl = r#"
+            schema @server @upstream { query: Query }
+            type Query { users: [User] @http(url: "http://localhost/users") }
+            type User { id: Int name: String }
+        "#;
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&config.into()).unwrap();
+
+        let (status, json) = complexity_json(
+            &blueprint,
+            "http://localhost/admin/complexity?query=%7B%20users%20%7B%20id%20name%20%7D%20%7D",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "complexity": 21,
+                "depth": 2,
+                "estimatedFetchCount": 1,
+                "fields": [
+                    {"path": "users", "complexity": 21},
+                    {"path": "users.id", "complexity": 1},
+                    {"path": "users.name", "complexity": 1},
+                ],
+            })
+        );
+
+        let (status, _) = complexity_json(&blueprint, "http://localhost/admin/complexity").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, json) =
+            complexity_json(&blueprint, "http://localhost/admin/complexity?query=%7B").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_log_level() {
+        let (filter, handle) = reload::Layer::new(log_filter(None).unwrap());
+        let handle = LogFilterHandle::new(handle);
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(filter));
+
+        let put = |body: &'static str| {
+            Request::put("http://localhost/admin/log-level")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let http_debug =
+            || tracing::enabled!(target: "tailcall::core::http", tracing::Level::DEBUG);
+        assert!(!http_debug());
+
+        let response = log_level(put("tailcall::core::http=debug"), &handle)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(http_debug());
+
+        let response = log_level(put("tailcall::core::http=loud"), &handle)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].is_string());
+        assert!(http_debug());
+    }
+}
@@ -1,6 +1,10 @@
+use std::future::Future;
 use std::sync::Arc;
 
+use hyper::server::accept::Accept;
+use hyper::server::Builder;
 use hyper::service::{make_service_fn, service_fn};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::oneshot;
 
 use super::server_config::ServerConfig;
@@ -12,27 +16,53 @@ pub async fn start_http_1(
     sc: Arc<ServerConfig>,
     server_up_sender: Option<oneshot::Sender<()>>,
 ) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    if let Some(socket) = sc.blueprint.server.unix_socket.clone() {
+        use super::unix_socket;
+
+        let (listener, socket_file) = unix_socket::bind(&socket)?;
+        let builder = hyper::Server::builder(unix_socket::incoming(listener));
+        let result = serve(sc, builder, server_up_sender, super::shutdown_signal()).await;
+        drop(socket_file);
+
+        return result;
+    }
+
     let addr = sc.addr();
-    let make_svc_single_req = make_service_fn(|_conn| {
+    let builder = hyper::Server::try_bind(&addr).map_err(Errata::from)?;
+
+    serve(sc, builder, server_up_sender, super::shutdown_signal()).await
+}
+
+async fn serve<I>(
+    sc: Arc<ServerConfig>,
+    builder: Builder<I>,
+    server_up_sender: Option<oneshot::Sender<()>>,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()>
+where
+    I: Accept,
+    I::Conn: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    I::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let make_svc_single_req = make_service_fn(|_conn: &I::Conn| {
         let state = Arc::clone(&sc);
         async move {
             Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLRequest>(req, state.app_ctx.clone())
+                handle_request::<GraphQLRequest>(req, state.app_ctx.load())
             }))
         }
     });
 
-    let make_svc_batch_req = make_service_fn(|_conn| {
+    let make_svc_batch_req = make_service_fn(|_conn: &I::Conn| {
         let state = Arc::clone(&sc);
         async move {
             Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.clone())
+                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.load())
             }))
         }
     });
-    let builder = hyper::Server::try_bind(&addr)
-        .map_err(Errata::from)?
-        .http1_pipeline_flush(sc.app_ctx.blueprint.server.pipeline_flush);
+    let builder = builder.http1_pipeline_flush(sc.blueprint.server.pipeline_flush);
     super::log_launch(sc.as_ref());
 
     if let Some(sender) = server_up_sender {
@@ -43,9 +73,15 @@ pub async fn start_http_1(
 
     let server: std::prelude::v1::Result<(), hyper::Error> =
         if sc.blueprint.server.enable_batch_requests {
-            builder.serve(make_svc_batch_req).await
+            builder
+                .serve(make_svc_batch_req)
+                .with_graceful_shutdown(shutdown)
+                .await
         } else {
-            builder.serve(make_svc_single_req).await
+            builder
+                .serve(make_svc_single_req)
+                .with_graceful_shutdown(shutdown)
+                .await
         };
 
     let result = server.map_err(Errata::from);
@@ -30,7 +30,7 @@ pub async fn start_http_2(
         let state = Arc::clone(&sc);
         async move {
             Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLRequest>(req, state.app_ctx.clone())
+                handle_request::<GraphQLRequest>(req, state.app_ctx.load())
             }))
         }
     });
@@ -39,7 +39,7 @@ pub async fn start_http_2(
         let state = Arc::clone(&sc);
         async move {
             Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.clone())
+                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.load())
             }))
         }
     });
@@ -56,9 +56,15 @@ pub async fn start_http_2(
 
     let server: std::prelude::v1::Result<(), hyper::Error> =
         if sc.blueprint.server.enable_batch_requests {
-            builder.serve(make_svc_batch_req).await
+            builder
+                .serve(make_svc_batch_req)
+                .with_graceful_shutdown(super::shutdown_signal())
+                .await
         } else {
-            builder.serve(make_svc_single_req).await
+            builder
+                .serve(make_svc_single_req)
+                .with_graceful_shutdown(super::shutdown_signal())
+                .await
         };
 
     let result = server.map_err(Errata::from);
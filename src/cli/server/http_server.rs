@@ -3,23 +3,74 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use tokio::sync::oneshot::{self};
+use tracing::Instrument;
 
+use super::admin::start_admin;
 use super::http_1::start_http_1;
 use super::http_2::start_http_2;
+use super::reload::Reloader;
 use super::server_config::ServerConfig;
 use crate::cli::telemetry::init_opentelemetry;
+use crate::core::apollo_usage::UsageCollector;
 use crate::core::blueprint::{Blueprint, Http};
 use crate::core::config::ConfigModule;
+use crate::core::http::{MockMode, MockTable};
+use crate::core::timing::{Phase, Timings};
 use crate::core::Errata;
 
 pub struct Server {
     config_module: ConfigModule,
     server_up_sender: Option<oneshot::Sender<()>>,
+    mock: Option<MockMode>,
+    reload_source: Option<(Vec<String>, Option<String>)>,
+    timings: Timings,
+}
+
+/// Builds the blueprint of the config, with its upstream mocks when a mode is
+/// given.
+pub fn to_blueprint(config_module: &ConfigModule, mock: Option<MockMode>) -> Result<Blueprint> {
+    let mut blueprint = Blueprint::try_from(config_module).map_err(Errata::from)?;
+    if let Some(mode) = mock {
+        let mocks = config_module.extensions().mocks.clone();
+        tracing::info!(
+            "Serving {} upstream mock(s) in {:?} mode",
+            mocks.len(),
+            mode
+        );
+        blueprint.mocks = Some(MockTable { mode, mocks });
+    }
+    Ok(blueprint)
 }
 
 impl Server {
     pub fn new(config_module: ConfigModule) -> Self {
-        Self { config_module, server_up_sender: None }
+        Self {
+            config_module,
+            server_up_sender: None,
+            mock: None,
+            reload_source: None,
+            timings: Timings::default(),
+        }
+    }
+
+    /// The phases timed while reading the config, to which building the
+    /// blueprint and the schema are added before the summary is logged.
+    pub fn timings(mut self, timings: Timings) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Serves upstream requests from the linked mock files in the given mode
+    pub fn mock(mut self, mock: Option<MockMode>) -> Self {
+        self.mock = mock;
+        self
+    }
+
+    /// The files, and profile, the config is read from. They're read again
+    /// when the config is reloaded, which is only possible when they're known.
+    pub fn reload_source(mut self, file_paths: Vec<String>, profile: Option<String>) -> Self {
+        self.reload_source = Some((file_paths, profile));
+        self
     }
 
     pub fn server_up_receiver(&mut self) -> oneshot::Receiver<()> {
@@ -32,11 +83,60 @@ impl Server {
 
     /// Starts the server in the current Runtime
     pub async fn start(self) -> Result<()> {
-        let blueprint = Blueprint::try_from(&self.config_module).map_err(Errata::from)?;
+        let blueprint = self.timings.time(Phase::Blueprint, || {
+            to_blueprint(&self.config_module, self.mock)
+        })?;
         let endpoints = self.config_module.extensions().endpoint_set.clone();
-        let server_config = Arc::new(ServerConfig::new(blueprint.clone(), endpoints).await?);
+        let timer = self.timings.start(Phase::Schema, None);
+        let server_config = Arc::new(
+            ServerConfig::new(blueprint.clone(), endpoints)
+                .instrument(timer.span())
+                .await?,
+        );
+        timer.finish();
+        tracing::info!("{}", self.timings.summary());
+        let app_ctx = server_config.app_ctx.load();
+
+        let log_filter = init_opentelemetry(
+            blueprint.telemetry.clone(),
+            &app_ctx.runtime,
+            server_config.app_ctx.clone(),
+        )?;
+
+        if let Some(usage) = &app_ctx.usage {
+            let usage = Arc::downgrade(usage);
+            let http = app_ctx.runtime.http.clone();
+            tokio::spawn(UsageCollector::report_periodically(usage, http));
+        }
 
-        init_opentelemetry(blueprint.telemetry.clone(), &server_config.app_ctx.runtime)?;
+        match self.reload_source {
+            Some((file_paths, profile)) => {
+                let reloader = Arc::new(Reloader::new(
+                    file_paths,
+                    profile,
+                    self.mock,
+                    app_ctx.runtime.clone(),
+                    server_config.app_ctx.clone(),
+                ));
+                #[cfg(unix)]
+                tokio::spawn(super::reload::reload_on_hangup(reloader.clone()));
+                if let Some(admin) = blueprint.server.admin.clone() {
+                    let hostname = blueprint.server.hostname;
+                    let app_ctx = server_config.app_ctx.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) =
+                            start_admin(admin, hostname, reloader, app_ctx, log_filter).await
+                        {
+                            tracing::error!("Admin server failed: {}", err);
+                        }
+                    });
+                }
+            }
+            None if blueprint.server.admin.is_some() => {
+                tracing::warn!("The config can't be reloaded, the admin endpoints are disabled");
+            }
+            None => {}
+        }
 
         match blueprint.server.http.clone() {
             Http::HTTP2 { cert, key } => {
@@ -1,14 +1,44 @@
+mod admin;
 pub mod http_1;
 pub mod http_2;
 pub mod http_server;
 pub mod playground;
+mod reload;
 pub mod server_config;
+#[cfg(unix)]
+mod unix_socket;
 
 pub use http_server::Server;
 
 use self::server_config::ServerConfig;
 
+/// Resolves on Ctrl+C or SIGTERM, so that the server shuts down gracefully,
+/// removing its socket file and writing the recorded cassette.
+async fn shutdown_signal() {
+    let terminate = async {
+        #[cfg(unix)]
+        if let Ok(mut terminate) =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        {
+            terminate.recv().await;
+            return;
+        }
+        std::future::pending::<()>().await
+    };
+
+    futures_util::future::select(Box::pin(tokio::signal::ctrl_c()), Box::pin(terminate)).await;
+}
+
 fn log_launch(sc: &ServerConfig) {
+    if let Some(socket) = &sc.blueprint.server.unix_socket {
+        tracing::info!(
+            "🚀 Tailcall launched at [{}] over {}",
+            socket,
+            sc.http_version()
+        );
+        return;
+    }
+
     let addr = sc.addr().to_string();
     tracing::info!(
         "🚀 Tailcall launched at [{}] over {}",
@@ -16,9 +46,15 @@ fn log_launch(sc: &ServerConfig) {
         sc.http_version()
     );
 
-    let gql_slug = sc.app_ctx.blueprint.server.routes.graphql();
-
+    let gql_slug = sc.blueprint.server.routes.graphql();
     let graphiql_url = sc.graphiql_url() + gql_slug;
+    tracing::info!("🔗 GraphQL endpoint: {}", graphiql_url);
+
+    if let Some(playground) = &sc.blueprint.server.playground {
+        tracing::info!("🌍 Playground: {}{}", sc.graphiql_url(), playground.path);
+        return;
+    }
+
     let url = playground::build_url(&graphiql_url);
     tracing::info!("🌍 Playground: {}", url);
 }
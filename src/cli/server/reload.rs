@@ -0,0 +1,237 @@
+//! Rebuilds the [AppContext] from the config sources and swaps it in without
+//! restarting the server, on `POST /admin/reload` or `SIGHUP`.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::http_server::to_blueprint;
+use super::server_config::ServerConfig;
+use crate::core::apollo_usage::UsageCollector;
+use crate::core::app_context::{AppContext, AppContextHandle};
+use crate::core::config::reader::ConfigReader;
+use crate::core::http::MockMode;
+use crate::core::print_schema::print_schema;
+use crate::core::runtime::TargetRuntime;
+use crate::core::Errata;
+
+/// Outcome of a reload, sent as JSON by `POST /admin/reload`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ReloadResult {
+    /// The new schema is served.
+    Ok {
+        #[serde(rename = "schemaHash")]
+        schema_hash: String,
+    },
+    /// The config couldn't be loaded, the previous schema is still served.
+    Error { errors: Errata },
+}
+
+impl ReloadResult {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, ReloadResult::Ok { .. })
+    }
+}
+
+/// SHA-256 of the SDL the context serves.
+pub fn schema_hash(app_ctx: &AppContext) -> String {
+    hex::encode(Sha256::digest(print_schema(app_ctx.schema.clone())))
+}
+
+pub struct Reloader {
+    file_paths: Vec<String>,
+    profile: Option<String>,
+    mock: Option<MockMode>,
+    runtime: TargetRuntime,
+    app_ctx: AppContextHandle,
+}
+
+impl Reloader {
+    pub fn new(
+        file_paths: Vec<String>,
+        profile: Option<String>,
+        mock: Option<MockMode>,
+        runtime: TargetRuntime,
+        app_ctx: AppContextHandle,
+    ) -> Self {
+        Self { file_paths, profile, mock, runtime, app_ctx }
+    }
+
+    /// Re-reads the config and serves the new requests with it. When the
+    /// config is invalid the previous context is kept.
+    pub async fn reload(&self) -> ReloadResult {
+        match self.rebuild().await {
+            Ok(app_ctx) => {
+                let schema_hash = schema_hash(&app_ctx);
+                if let Some(usage) = &app_ctx.usage {
+                    let usage = Arc::downgrade(usage);
+                    let http = app_ctx.runtime.http.clone();
+                    tokio::spawn(UsageCollector::report_periodically(usage, http));
+                }
+                // the statistics collected since the last report would be
+                // dropped with the previous context
+                let previous = self.app_ctx.load();
+                if let Some(usage) = &previous.usage {
+                    if let Err(error) = usage.flush(previous.runtime.http.as_ref()).await {
+                        tracing::warn!("Failed to send the Apollo usage report: {}", error);
+                    }
+                }
+                self.app_ctx.swap(app_ctx);
+                tracing::info!(
+                    "Reloaded {}, schema {}",
+                    self.file_paths.join(", "),
+                    schema_hash
+                );
+                ReloadResult::Ok { schema_hash }
+            }
+            Err(err) => {
+                let errors = Errata::from(err);
+                tracing::error!(
+                    "Reload failed, the previous schema is still served: {}",
+                    errors
+                );
+                ReloadResult::Error { errors }
+            }
+        }
+    }
+
+    async fn rebuild(&self) -> anyhow::Result<Arc<AppContext>> {
+        // The reader caches the files it reads, a new one is needed to see the
+        // changes made since the last read.
+        let config_reader = ConfigReader::init(self.runtime.clone());
+        let config_module = config_reader
+            .read_all_with_profile(&self.file_paths, self.profile.as_deref())
+            .await?;
+        let blueprint = to_blueprint(&config_module, self.mock)?;
+        let endpoints = config_module.extensions().endpoint_set.clone();
+
+        ServerConfig::app_context(blueprint, endpoints).await
+    }
+}
+
+/// Reloads the config every time the process receives `SIGHUP`.
+#[cfg(unix)]
+pub async fn reload_on_hangup(reloader: Arc<Reloader>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            tracing::warn!(
+                "Can't listen to SIGHUP, the config won't be reloaded on it: {}",
+                err
+            );
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        tracing::info!("Received SIGHUP, reloading the config");
+        reloader.reload().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use async_graphql::Request;
+    use async_graphql_value::ConstValue;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::core::blueprint::Blueprint;
+
+    const QUERY: &str = r#"{ __type(name: "Query") { fields { name } } }"#;
+
+    fn write_config(dir: &TempDir, fields: &str) -> String {
+        let path = dir.path().join("tailcall.graphql");
+        let sdl = format!("schema @server @upstream {{ query: Query }}\ntype Query {{ {fields} }}");
+        fs::write(&path, sdl).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    async fn fields(handle: &AppContextHandle) -> Vec<String> {
+        let data = handle
+            .load()
+            .execute(Request::new(QUERY))
+            .await
+            .data
+            .into_json()
+            .unwrap();
+        data["__type"]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    async fn reloader(dir: &TempDir) -> Reloader {
+        let path = write_config(dir, "a: Int @expr(body: 1)");
+        let runtime = crate::cli::runtime::init(&Blueprint::default());
+        let config_module = ConfigReader::init(runtime.clone())
+            .read(&path)
+            .await
+            .unwrap();
+        let blueprint = to_blueprint(&config_module, None).unwrap();
+        let app_ctx = ServerConfig::app_context(blueprint, Default::default())
+            .await
+            .unwrap();
+
+        Reloader::new(
+            vec![path],
+            None,
+            None,
+            runtime,
+            AppContextHandle::new(app_ctx),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let dir = TempDir::new().unwrap();
+        let reloader = reloader(&dir).await;
+        let previous = reloader.app_ctx.load();
+        assert_eq!(fields(&reloader.app_ctx).await, vec!["a"]);
+
+        write_config(&dir, "a: Int @expr(body: 1)\nb: Int @expr(body: 2)");
+        let result = reloader.reload().await;
+
+        assert!(result.is_ok());
+        assert_eq!(fields(&reloader.app_ctx).await, vec!["a", "b"]);
+        assert_eq!(reloader.app_ctx.swaps(), 1);
+
+        let current = reloader.app_ctx.load();
+        assert!(!Arc::ptr_eq(&previous, &current));
+        assert!(current.operation_plans.is_empty());
+        assert!(current.const_execution_cache.is_empty());
+        assert!(
+            matches!(result, ReloadResult::Ok { schema_hash } if schema_hash != self::schema_hash(&previous))
+        );
+
+        let response = reloader.app_ctx.load().execute(Request::new("{ b }")).await;
+        assert_eq!(
+            response.data,
+            ConstValue::from_json(serde_json::json!({"b": 2})).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload() {
+        let dir = TempDir::new().unwrap();
+        let reloader = reloader(&dir).await;
+
+        write_config(&dir, "a: Unknown @expr(body: 1)");
+        let result = reloader.reload().await;
+
+        let ReloadResult::Error { errors } = result else {
+            panic!("the reload should fail");
+        };
+        let json = serde_json::to_value(&errors).unwrap();
+        assert!(json.to_string().contains("Unknown"));
+        assert_eq!(reloader.app_ctx.swaps(), 0);
+        assert_eq!(fields(&reloader.app_ctx).await, vec!["a"]);
+    }
+}
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use async_graphql_extension_apollo_tracing::ApolloTracing;
 
 use crate::cli::runtime::init;
-use crate::core::app_context::AppContext;
+use crate::core::app_context::{AppContext, AppContextHandle};
 use crate::core::blueprint::telemetry::TelemetryExporter;
 use crate::core::blueprint::{Blueprint, Http};
 use crate::core::rest::{EndpointSet, Unchecked};
@@ -12,7 +12,7 @@ use crate::core::schema_extension::SchemaExtension;
 
 pub struct ServerConfig {
     pub blueprint: Blueprint,
-    pub app_ctx: Arc<AppContext>,
+    pub app_ctx: AppContextHandle,
 }
 
 impl ServerConfig {
@@ -20,6 +20,17 @@ impl ServerConfig {
         blueprint: Blueprint,
         endpoints: EndpointSet<Unchecked>,
     ) -> anyhow::Result<Self> {
+        let app_ctx = Self::app_context(blueprint.clone(), endpoints).await?;
+
+        Ok(Self { app_ctx: AppContextHandle::new(app_ctx), blueprint })
+    }
+
+    /// Builds the context the requests are served with, along with its
+    /// runtime. Used on startup and to rebuild it when the config is reloaded.
+    pub async fn app_context(
+        blueprint: Blueprint,
+        endpoints: EndpointSet<Unchecked>,
+    ) -> anyhow::Result<Arc<AppContext>> {
         let mut rt = init(&blueprint);
 
         let mut extensions = vec![];
@@ -37,9 +48,8 @@ impl ServerConfig {
         rt.add_extensions(extensions);
 
         let endpoints = endpoints.into_checked(&blueprint, rt.clone()).await?;
-        let app_context = Arc::new(AppContext::new(blueprint.clone(), rt, endpoints));
 
-        Ok(Self { app_ctx: app_context, blueprint })
+        Ok(Arc::new(AppContext::new(blueprint, rt, endpoints)))
     }
 
     pub fn addr(&self) -> SocketAddr {
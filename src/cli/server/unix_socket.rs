@@ -0,0 +1,84 @@
+//! Serves the requests over a unix domain socket instead of a TCP port.
+
+use std::fs::{self, Permissions};
+use std::io;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use futures_util::stream;
+use hyper::server::accept::{self, Accept};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::core::blueprint::UnixSocket;
+
+/// Removes the socket file when dropped, i.e. once the server stops listening
+/// on it.
+pub struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.0) {
+            tracing::warn!("Failed to remove {}: {}", self.0.display(), err);
+        }
+    }
+}
+
+/// Binds the socket, replacing the file that a previous run might have left
+/// behind.
+pub fn bind(socket: &UnixSocket) -> anyhow::Result<(UnixListener, Option<SocketFile>)> {
+    match socket {
+        UnixSocket::Path { path, mode } => {
+            remove_stale(path)?;
+            let listener = UnixListener::bind(path)?;
+            let file = SocketFile(path.clone());
+
+            if let Some(mode) = mode {
+                fs::set_permissions(path, Permissions::from_mode(*mode))?;
+            }
+
+            Ok((listener, Some(file)))
+        }
+        UnixSocket::Abstract(name) => Ok((bind_abstract(name)?, None)),
+    }
+}
+
+fn remove_stale(path: &Path) -> anyhow::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => Ok(fs::remove_file(path)?),
+        Ok(_) => Err(anyhow::anyhow!(
+            "{} already exists and is not a socket",
+            path.display()
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn bind_abstract(name: &str) -> io::Result<UnixListener> {
+    #[cfg(target_os = "android")]
+    use std::os::android::net::SocketAddrExt;
+    #[cfg(target_os = "linux")]
+    use std::os::linux::net::SocketAddrExt;
+
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+    let listener = std::os::unix::net::UnixListener::bind_addr(&addr)?;
+    listener.set_nonblocking(true)?;
+    UnixListener::from_std(listener)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn bind_abstract(_: &str) -> io::Result<UnixListener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Abstract unix sockets are only supported on Linux",
+    ))
+}
+
+/// Accepts the connections of the listener for hyper.
+pub fn incoming(listener: UnixListener) -> impl Accept<Conn = UnixStream, Error = io::Error> {
+    accept::from_stream(stream::unfold(listener, |listener| async move {
+        let stream = listener.accept().await.map(|(stream, _)| stream);
+        Some((stream, listener))
+    }))
+}
@@ -0,0 +1,225 @@
+use std::fmt::{self, Display};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use futures_util::future::join_all;
+use hyper::body::Bytes;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, Method, Request};
+
+use crate::cli::fmt::Fmt;
+use crate::cli::server::server_config::ServerConfig;
+use crate::core::app_context::AppContext;
+use crate::core::async_graphql_hyper::GraphQLRequest;
+use crate::core::blueprint::Blueprint;
+use crate::core::config::reader::ConfigReader;
+use crate::core::http::handle_request;
+use crate::core::Errata;
+
+pub(super) struct BenchParams {
+    pub file_paths: Vec<String>,
+    pub query: String,
+    pub concurrency: usize,
+    pub duration: Duration,
+}
+
+pub(super) async fn bench_command(params: BenchParams, config_reader: &ConfigReader) -> Result<()> {
+    let config_module = config_reader.read_all(&params.file_paths).await?;
+    let blueprint = Blueprint::try_from(&config_module).map_err(Errata::from)?;
+    let endpoints = config_module.extensions().endpoint_set.clone();
+    let app_ctx = ServerConfig::app_context(blueprint, endpoints).await?;
+
+    tracing::info!(
+        "Sending the query with {} in flight for {:?}",
+        params.concurrency,
+        params.duration
+    );
+    let report = bench(app_ctx, &params.query, params.concurrency, params.duration).await?;
+    Fmt::display(report.to_string());
+    Ok(())
+}
+
+/// The latencies of the queries sent by a benchmark and how many failed.
+struct Report {
+    /// Sorted from the fastest.
+    latencies: Vec<Duration>,
+    errors: usize,
+    elapsed: Duration,
+}
+
+impl Report {
+    /// The latency under which `percent` of the queries were answered.
+    fn percentile(&self, percent: f64) -> Duration {
+        let rank = (percent / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies
+            .get(rank.saturating_sub(1))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn throughput(&self) -> f64 {
+        self.latencies.len() as f64 / self.elapsed.as_secs_f64()
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.latencies.is_empty() {
+            0.0
+        } else {
+            self.errors as f64 * 100.0 / self.latencies.len() as f64
+        }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", Fmt::heading("Queries"))?;
+        writeln!(
+            f,
+            "  {} in {:.2?}, {:.1} queries/sec",
+            self.latencies.len(),
+            self.elapsed,
+            self.throughput()
+        )?;
+        writeln!(f, "{}", Fmt::heading("Errors"))?;
+        writeln!(f, "  {} ({:.2}%)", self.errors, self.error_rate())?;
+        writeln!(f, "{}", Fmt::heading("Latency"))?;
+        for percent in [50.0, 95.0, 99.0] {
+            writeln!(f, "  p{percent}: {:.2?}", self.percentile(percent))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends the query through the executor, with `concurrency` of them in flight,
+/// until `duration` is elapsed.
+async fn bench(
+    app_ctx: Arc<AppContext>,
+    query: &str,
+    concurrency: usize,
+    duration: Duration,
+) -> Result<Report> {
+    let body = Bytes::from(serde_json::to_vec(&serde_json::json!({ "query": query }))?);
+    let started = Instant::now();
+    let deadline = started + duration;
+
+    let workers = (0..concurrency.max(1)).map(|_| {
+        let app_ctx = app_ctx.clone();
+        let body = body.clone();
+        tokio::spawn(async move {
+            let mut samples = vec![];
+            while Instant::now() < deadline {
+                let start = Instant::now();
+                let ok = send(&app_ctx, body.clone()).await;
+                samples.push((start.elapsed(), ok));
+            }
+            samples
+        })
+    });
+    let samples = join_all(workers).await;
+    let elapsed = started.elapsed();
+
+    let mut report = Report { latencies: vec![], errors: 0, elapsed };
+    for (latency, ok) in samples
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+    {
+        report.latencies.push(latency);
+        report.errors += usize::from(!ok);
+    }
+    report.latencies.sort();
+    Ok(report)
+}
+
+/// Sends the query, telling whether it was answered without errors.
+async fn send(app_ctx: &Arc<AppContext>, body: Bytes) -> bool {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(format!(
+            "http://localhost{}",
+            app_ctx.blueprint.server.routes.graphql()
+        ))
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body));
+    let Ok(request) = request else {
+        return false;
+    };
+
+    match handle_request::<GraphQLRequest>(request, app_ctx.clone()).await {
+        Ok(response) if response.status().is_success() => {
+            hyper::body::to_bytes(response.into_body())
+                .await
+                .ok()
+                .and_then(|body| serde_json::from_slice::<serde_json::Value>(&body).ok())
+                .is_some_and(|body| body.get("errors").is_none())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tailcall_valid::Validator;
+
+    use super::{bench, Report};
+    use crate::core::app_context::AppContext;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::rest::EndpointSet;
+    use crate::core::runtime::test::init;
+
+    fn app_ctx() -> Arc<AppContext> {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query { user: User @expr(body: {id: 1, name: "Ann"}) }
+            type User { id: Int name: String }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        Arc::new(AppContext::new(
+            blueprint,
+            init(None),
+            EndpointSet::default(),
+        ))
+    }
+
+    #[test]
+    fn test_percentile() {
+        let report = Report {
+            latencies: (1..=100).map(Duration::from_millis).collect(),
+            errors: 5,
+            elapsed: Duration::from_secs(2),
+        };
+
+        assert_eq!(report.percentile(50.0), Duration::from_millis(50));
+        assert_eq!(report.percentile(95.0), Duration::from_millis(95));
+        assert_eq!(report.percentile(99.0), Duration::from_millis(99));
+        assert_eq!(report.throughput(), 50.0);
+        assert_eq!(report.error_rate(), 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_bench() {
+        let duration = Duration::from_millis(50);
+
+        let report = bench(app_ctx(), "{ user { id name } }", 4, duration)
+            .await
+            .unwrap();
+        assert!(!report.latencies.is_empty());
+        assert_eq!(report.errors, 0);
+        assert!(report.elapsed >= duration);
+
+        let report = bench(app_ctx(), "{ user { email } }", 4, duration)
+            .await
+            .unwrap();
+        assert_eq!(report.errors, report.latencies.len());
+    }
+}
@@ -5,26 +5,52 @@ use crate::cli::fmt::Fmt;
 use crate::core::blueprint::Blueprint;
 use crate::core::config::reader::ConfigReader;
 use crate::core::runtime::TargetRuntime;
+use crate::core::timing::Phase;
 use crate::core::Errata;
 
 pub(super) struct CheckParams {
     pub(super) file_paths: Vec<String>,
     pub(super) n_plus_one_queries: bool,
     pub(super) schema: bool,
+    pub(super) strict: bool,
+    pub(super) profile: Option<String>,
+    pub(super) timing: bool,
+    pub(super) warnings: Vec<String>,
     pub(super) runtime: TargetRuntime,
 }
 
 pub(super) async fn check_command(params: CheckParams, config_reader: &ConfigReader) -> Result<()> {
-    let CheckParams { file_paths, n_plus_one_queries, schema, runtime } = params;
+    let CheckParams {
+        file_paths,
+        n_plus_one_queries,
+        schema,
+        strict,
+        profile,
+        timing,
+        mut warnings,
+        runtime,
+    } = params;
 
-    let config_module = (config_reader.read_all(&file_paths)).await?;
+    let config_module = config_reader
+        .read_all_with_profile(&file_paths, profile.as_deref())
+        .await?;
     log_endpoint_set(&config_module.extensions().endpoint_set);
-    let blueprint = Blueprint::try_from(&config_module).map_err(Errata::from);
+    let timings = config_reader.timings();
+    let blueprint = timings.time(Phase::Blueprint, || {
+        Blueprint::try_from(&config_module).map_err(Errata::from)
+    });
+    if timing {
+        tracing::info!("{}", timings.summary());
+    }
 
     match blueprint {
         Ok(blueprint) => {
             tracing::info!("Config {} ... ok", file_paths.join(", "));
-            Fmt::log_n_plus_one(n_plus_one_queries, config_module.config());
+            let n_plus_one = Fmt::log_n_plus_one(n_plus_one_queries, config_module.config());
+            if n_plus_one > 0 {
+                warnings.push(format!("N + 1 detected: {}", n_plus_one));
+            }
+            warnings.extend(Fmt::warn_non_null_delete(config_module.config()));
             // Check the endpoints' schema
             let _ = config_module
                 .extensions()
@@ -36,6 +62,12 @@ pub(super) async fn check_command(params: CheckParams, config_reader: &ConfigRea
                 display_schema(&blueprint);
             }
 
+            if strict && !warnings.is_empty() {
+                return Err(Errata::new("Strict mode: warnings are treated as errors")
+                    .caused_by(warnings.iter().map(|w| Errata::new(w)).collect())
+                    .into());
+            }
+
             Ok(())
         }
         Err(e) => Err(e.into()),
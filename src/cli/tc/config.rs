@@ -0,0 +1,16 @@
+use anyhow::Result;
+
+use crate::cli::fmt::Fmt;
+use crate::core::config::reader::ConfigReader;
+
+pub(super) async fn show_command(
+    file_paths: &[String],
+    profile: Option<&str>,
+    config_reader: &ConfigReader,
+) -> Result<()> {
+    let config_module = config_reader
+        .read_all_with_profile(file_paths, profile)
+        .await?;
+    Fmt::display(config_module.config().to_sdl());
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use std::io::Read;
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+use crate::cli::command::{ConvertFrom, ConvertTo};
+use crate::cli::fmt::Fmt;
+use crate::core::blueprint::{Blueprint, ClientTypes};
+use crate::core::config::reader::ConfigReader;
+use crate::core::config::{Config, ConfigModule, Link, LinkType};
+use crate::core::generator::{from_json_schema, from_openapi, resolve_file_descriptor_set};
+use crate::core::print_schema::print_schema;
+use crate::core::proto_reader::ProtoReader;
+use crate::core::resource_reader::ResourceReader;
+use crate::core::runtime::TargetRuntime;
+use crate::core::Errata;
+
+/// The path that reads the schema from stdin.
+const STDIN: &str = "-";
+
+/// The upstream of the fields generated from proto, to be set in the env.
+const GRPC_URL: &str = "{{.env.GRPC_URL}}";
+
+pub(super) struct ConvertParams {
+    pub file_path: String,
+    pub from: ConvertFrom,
+    pub to: ConvertTo,
+    pub out: Option<String>,
+}
+
+pub(super) async fn convert_command(
+    params: ConvertParams,
+    config_reader: &ConfigReader,
+    runtime: TargetRuntime,
+) -> Result<()> {
+    let config_module = read(&params.file_path, params.from, config_reader, &runtime).await?;
+    let output = convert(&config_module, params.to)?;
+    match params.out {
+        Some(out) => {
+            runtime.file.write(&out, output.as_bytes()).await?;
+            tracing::info!("Converted schema written to {}", out);
+        }
+        None => Fmt::display(output),
+    }
+    Ok(())
+}
+
+/// Reads the schema as a configuration, with its links resolved.
+async fn read(
+    file_path: &str,
+    from: ConvertFrom,
+    config_reader: &ConfigReader,
+    runtime: &TargetRuntime,
+) -> Result<ConfigModule> {
+    let config = match from {
+        ConvertFrom::OpenApi => from_openapi(&read_document(file_path, runtime).await?, "Query")?,
+        ConvertFrom::JsonSchema => from_json_schema(&read_document(file_path, runtime).await?)?,
+        ConvertFrom::Proto if file_path == STDIN => {
+            return Err(anyhow!("The proto files can't be read from stdin"));
+        }
+        ConvertFrom::Proto => {
+            let reader = ResourceReader::cached(runtime.clone());
+            let metadata = ProtoReader::init(reader, runtime.clone())
+                .read(file_path, None)
+                .await?;
+            // the file read comes after the files it imports
+            let name = metadata
+                .descriptor_set
+                .file
+                .last()
+                .map(|file| file.name().to_string());
+            let descriptor_set = resolve_file_descriptor_set(metadata.descriptor_set)?;
+            let (files, dependencies): (Vec<_>, Vec<_>) = descriptor_set
+                .file
+                .into_iter()
+                .partition(|file| Some(file.name()) == name.as_deref());
+            let file = files
+                .first()
+                .ok_or_else(|| anyhow!("Couldn't read the proto file {file_path}"))?;
+            let mut config = Config::from_proto(file, &dependencies, GRPC_URL)?;
+            config.links.push(Link {
+                id: None,
+                src: metadata.path,
+                type_of: LinkType::Protobuf,
+                headers: None,
+                meta: None,
+                proto_paths: None,
+                optional: false,
+            });
+            config
+        }
+        ConvertFrom::GraphQL if file_path != STDIN => {
+            return config_reader.read(file_path).await;
+        }
+        ConvertFrom::GraphQL => Config::from_sdl(&read_stdin()?).to_result()?,
+        ConvertFrom::Introspection if file_path == STDIN => {
+            Config::from_introspection(&read_stdin()?)?
+        }
+        ConvertFrom::Introspection => {
+            let reader = ResourceReader::cached(runtime.clone());
+            Config::from_introspection(&reader.read_file(file_path).await?.content)?
+        }
+    };
+
+    config_reader.resolve(config, None).await
+}
+
+/// Reads a JSON or YAML document, from stdin or from a path or a URL.
+async fn read_document(file_path: &str, runtime: &TargetRuntime) -> Result<Value> {
+    let content = if file_path == STDIN {
+        read_stdin()?
+    } else {
+        let reader = ResourceReader::cached(runtime.clone());
+        reader.read_file(file_path).await?.content
+    };
+    // YAML is a superset of JSON, so it reads both
+    Ok(serde_yaml_ng::from_str(&content)?)
+}
+
+fn read_stdin() -> Result<String> {
+    let mut content = String::new();
+    std::io::stdin().read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Writes the configuration in the format, the ones other than `graphql` and
+/// `json-schema` are written after its blueprint.
+fn convert(config_module: &ConfigModule, to: ConvertTo) -> Result<String> {
+    let blueprint = || Blueprint::try_from(config_module).map_err(Errata::from);
+    match to {
+        ConvertTo::GraphQL => Ok(config_module.config().to_sdl()),
+        ConvertTo::Sdl => Ok(print_schema(blueprint()?.to_schema())),
+        ConvertTo::JsonSchema => {
+            let schema = args_json_schema(config_module.config());
+            Ok(serde_json::to_string_pretty(&schema)?)
+        }
+        ConvertTo::Types(lang) => Ok(ClientTypes::new(lang).generate(&blueprint()?)),
+    }
+}
+
+/// The JSON Schema of the arguments of the root fields, with a definition for
+/// each field, eg. `Query.user`, that describes its arguments as an object,
+/// and one for each recursive input object, eg. `UserFilter`.
+fn args_json_schema(config: &Config) -> Value {
+    let roots = [
+        &config.schema.query,
+        &config.schema.mutation,
+        &config.schema.subscription,
+    ];
+    let mut definitions = serde_json::Map::new();
+
+    for type_name in roots.into_iter().flatten() {
+        let Some(type_) = config.find_type(type_name) else {
+            continue;
+        };
+
+        for (field_name, field) in type_.fields.iter().filter(|(_, f)| !f.is_omitted()) {
+            let mut properties = serde_json::Map::new();
+            let mut required = Vec::new();
+            for (arg_name, arg) in field.args.iter() {
+                if arg.default_value.is_none() && !arg.type_of.is_nullable() {
+                    required.push(Value::from(arg_name.as_str()));
+                }
+                properties.insert(
+                    arg_name.clone(),
+                    arg.to_json_schema(config, &mut definitions),
+                );
+            }
+
+            let mut schema = json!({ "type": "object", "properties": properties });
+            if !required.is_empty() {
+                schema["required"] = Value::Array(required);
+            }
+            definitions.insert(format!("{type_name}.{field_name}"), schema);
+        }
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$defs": definitions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::blueprint::TypeLang;
+
+    const CONFIG: &str = r#"
+        schema @server @upstream {
+          query: Query
+        }
+
+        type Query {
+          user(id: Int!): User @http(url: "http://jsonplaceholder.typicode.com/users/{{.args.id}}")
+        }
+
+        type User {
+          id: Int!
+          name: String
+        }
+    "#;
+
+    fn config_module() -> ConfigModule {
+        ConfigModule::from(Config::from_sdl(CONFIG).to_result().unwrap())
+    }
+
+    #[test]
+    fn test_convert_to_graphql() {
+        let output = convert(&config_module(), ConvertTo::GraphQL).unwrap();
+
+        assert!(output.contains("@http"));
+    }
+
+    #[test]
+    fn test_convert_to_sdl() {
+        let output = convert(&config_module(), ConvertTo::Sdl).unwrap();
+
+        assert!(output.contains("user(id: Int!): User"));
+        assert!(!output.contains("@http"));
+    }
+
+    #[test]
+    fn test_convert_to_json_schema() {
+        let output = convert(&config_module(), ConvertTo::JsonSchema).unwrap();
+        let schema: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(
+            schema["$defs"]["Query.user"],
+            json!({
+                "type": "object",
+                "properties": { "id": { "type": "integer", "format": "int32" } },
+                "required": ["id"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_convert_to_types() {
+        let output = convert(&config_module(), ConvertTo::Types(TypeLang::TypeScript)).unwrap();
+
+        assert!(output.contains("export interface User"));
+    }
+}
@@ -1,10 +1,22 @@
 use anyhow::Result;
 
 use crate::cli::generator::Generator;
+use crate::core::generator::ProtoOptions;
 use crate::core::runtime::TargetRuntime;
 
-pub(super) async fn gen_command(file_path: &str, runtime: TargetRuntime) -> Result<()> {
+pub(super) async fn gen_command(
+    file_path: &str,
+    strict: bool,
+    dry_run: bool,
+    resume: bool,
+    proto_options: ProtoOptions,
+    runtime: TargetRuntime,
+) -> Result<()> {
     Generator::new(file_path, runtime.clone())
+        .strict(strict)
+        .dry_run(dry_run)
+        .resume(resume)
+        .proto_options(proto_options)
         .generate()
         .await?;
     Ok(())
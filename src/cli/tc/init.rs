@@ -1,20 +1,51 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use convert_case::{Case, Casing};
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use inquire::{Confirm, CustomType, Text};
+use reqwest::StatusCode;
+use url::{Position, Url};
 
 use super::helpers::{GRAPHQL_RC, TAILCALL_RC, TAILCALL_RC_SCHEMA};
 use crate::cli::runtime::{confirm_and_write, create_directory, select_prompt};
+use crate::core::config::transformer::Preset;
 use crate::core::config::{
-    Config, Expr, Field, Link, LinkType, Resolver, RootSchema, RuntimeConfig, Source,
+    Config, Expr, Field, Link, LinkType, Playground, Resolver, RootSchema, RuntimeConfig, Server,
+    Source,
 };
+use crate::core::generator::{Generator, Input};
+use crate::core::http::Method;
 use crate::core::merge_right::MergeRight;
 use crate::core::runtime::TargetRuntime;
 use crate::core::{config, Type};
 
 const SCHEMA_FILENAME: &str = "main.graphql";
+const DEFAULT_PORT: u16 = 8000;
 
-pub(super) async fn init_command(runtime: TargetRuntime, folder_path: &str) -> Result<()> {
+/// Options of `tailcall init`, the ones that are not set are asked for.
+pub(super) struct InitParams {
+    pub(super) folder_path: String,
+    pub(super) probe: Option<String>,
+    pub(super) headers: Vec<String>,
+    pub(super) port: Option<u16>,
+    pub(super) playground: Option<bool>,
+    pub(super) base_url: Option<String>,
+}
+
+/// A REST endpoint the starter query is generated from.
+struct Probe {
+    url: Url,
+    /// Replaces the origin of `url` in the generated config, eg. to probe a
+    /// staging endpoint while serving from production.
+    base_url: Url,
+    headers: HeaderMap,
+}
+
+pub(super) async fn init_command(runtime: TargetRuntime, params: InitParams) -> Result<()> {
+    let InitParams { folder_path, probe, headers, port, playground, base_url } = params;
+    let folder_path = folder_path.as_str();
     create_directory(folder_path).await?;
 
     let selection = select_prompt(
@@ -22,6 +53,40 @@ pub(super) async fn init_command(runtime: TargetRuntime, folder_path: &str) -> R
         vec![Source::Json, Source::Yml],
     )?;
 
+    let port = match port {
+        Some(port) => port,
+        None => CustomType::<u16>::new("Which port should the server listen on?")
+            .with_default(DEFAULT_PORT)
+            .prompt()?,
+    };
+    let playground = match playground {
+        Some(playground) => playground,
+        None => Confirm::new("Do you want to enable the playground?")
+            .with_default(true)
+            .prompt()?,
+    };
+
+    let config = match probe {
+        Some(url) => {
+            let url = Url::parse(&url).with_context(|| format!("invalid probe url `{url}`"))?;
+            let base_url = match base_url {
+                Some(base_url) => base_url,
+                None => Text::new("What is the base URL of the upstream?")
+                    .with_default(&url.origin().ascii_serialization())
+                    .prompt()?,
+            };
+            let base_url =
+                Url::parse(&base_url).with_context(|| format!("invalid base url `{base_url}`"))?;
+            let headers = headers
+                .iter()
+                .map(|header| parse_header(header))
+                .collect::<Result<HeaderMap>>()?;
+
+            probe_config(&runtime, &Probe { url, base_url, headers }).await?
+        }
+        None => main_config(),
+    };
+
     let tailcallrc = include_str!("../../../generated/.tailcallrc.graphql");
     let tailcallrc_json: &str = include_str!("../../../generated/.tailcallrc.schema.json");
 
@@ -48,11 +113,29 @@ pub(super) async fn init_command(runtime: TargetRuntime, folder_path: &str) -> R
     )
     .await?;
 
-    create_main(runtime.clone(), folder_path, selection).await?;
+    create_main(
+        runtime.clone(),
+        folder_path,
+        selection,
+        config,
+        runtime_config(port, playground),
+    )
+    .await?;
 
     Ok(())
 }
 
+/// Parses a `--header`, eg. `Authorization: Bearer <token>`.
+fn parse_header(header: &str) -> Result<(HeaderName, HeaderValue)> {
+    let (name, value) = header
+        .split_once(':')
+        .with_context(|| format!("invalid header `{header}`, expected `name: value`"))?;
+    Ok((
+        HeaderName::try_from(name.trim())?,
+        HeaderValue::try_from(value.trim())?,
+    ))
+}
+
 fn default_graphqlrc() -> serde_yaml_ng::Value {
     serde_yaml_ng::Value::Mapping(serde_yaml_ng::mapping::Mapping::from_iter([(
         "schema".into(),
@@ -101,8 +184,84 @@ fn main_config() -> Config {
     }
 }
 
-fn runtime_config() -> RuntimeConfig {
-    let config = RuntimeConfig::default();
+/// Generates a config with one query field resolved by the probed endpoint,
+/// typed after its response. The probe headers are forwarded from the
+/// incoming requests instead of being written to the config.
+async fn probe_config(runtime: &TargetRuntime, probe: &Probe) -> Result<Config> {
+    let mut request = reqwest::Request::new(reqwest::Method::GET, probe.url.clone());
+    *request.headers_mut() = probe.headers.clone();
+    let response = runtime.http.execute(request).await?;
+    match response.status {
+        status if status.is_success() => {}
+        status @ (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => bail!(
+            "{} responded with {status}, pass the credentials with `--header`",
+            probe.url
+        ),
+        status => bail!("{} responded with {status}", probe.url),
+    }
+    let res_body: serde_json::Value = serde_json::from_slice(&response.body)
+        .with_context(|| format!("{} didn't respond with JSON", probe.url))?;
+
+    let headers = (!probe.headers.is_empty()).then(|| {
+        probe
+            .headers
+            .keys()
+            .map(|name| (name.to_string(), String::new()))
+            .collect::<BTreeMap<_, _>>()
+    });
+    let input = Input::Json {
+        url: rebase(&probe.url, &probe.base_url)?,
+        method: Method::GET,
+        req_body: serde_json::Value::Null,
+        res_body,
+        field_name: field_name(&probe.url),
+        is_mutation: false,
+        headers,
+    };
+
+    let config = Generator::default()
+        .inputs(vec![input])
+        .transformers(vec![Box::new(Preset::default())])
+        .generate(true)?;
+    Ok(config.config().clone())
+}
+
+/// The url with its origin replaced by the base url.
+fn rebase(url: &Url, base_url: &Url) -> Result<Url> {
+    let base_url = base_url.as_str().trim_end_matches('/');
+    Ok(Url::parse(&format!(
+        "{base_url}{}",
+        &url[Position::BeforePath..]
+    ))?)
+}
+
+/// Names the query field after the last segment of the path that isn't an
+/// id, eg. `users` for `/api/users` and `/users/1`.
+fn field_name(url: &Url) -> String {
+    url.path_segments()
+        .into_iter()
+        .flatten()
+        .rev()
+        .map(|segment| segment.replace(|c: char| !c.is_ascii_alphanumeric(), " "))
+        .find(|segment| {
+            segment
+                .trim_start()
+                .starts_with(|c: char| c.is_ascii_alphabetic())
+        })
+        .map(|segment| segment.to_case(Case::Camel))
+        .unwrap_or_else(|| "data".to_string())
+}
+
+fn runtime_config(port: u16, playground: bool) -> RuntimeConfig {
+    let config = RuntimeConfig {
+        server: Server {
+            port: (port != DEFAULT_PORT).then_some(port),
+            playground: playground
+                .then(|| Playground { enabled: Some(true), ..Default::default() }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
 
     config.links(vec![Link {
         id: Some("main".to_string()),
@@ -116,10 +275,9 @@ async fn create_main(
     runtime: TargetRuntime,
     folder_path: impl AsRef<Path>,
     source: Source,
+    config: Config,
+    runtime_config: RuntimeConfig,
 ) -> Result<()> {
-    let config = main_config();
-    let runtime_config = runtime_config();
-
     let runtime_config = match source {
         Source::Json => runtime_config.to_json(true)?,
         Source::Yml => runtime_config.to_yaml()?,
@@ -152,3 +310,126 @@ async fn create_main(
     confirm_and_write(runtime.clone(), &schema_path, schema.as_bytes()).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use http::header::HeaderMap;
+    use hyper::body::Bytes;
+    use serde_json::json;
+    use tailcall_valid::Validator;
+    use url::Url;
+
+    use super::{field_name, parse_header, probe_config, Probe};
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{ConfigModule, Resolver};
+    use crate::core::http::Response;
+    use crate::core::runtime::TargetRuntime;
+    use crate::core::HttpIO;
+
+    struct Api;
+
+    #[async_trait::async_trait]
+    impl HttpIO for Api {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let body = match request.url().path() {
+                "/users" if request.headers().contains_key("authorization") => {
+                    json!([{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bob"}])
+                }
+                "/users" => return Ok(Response::empty().status(reqwest::StatusCode::UNAUTHORIZED)),
+                "/users/1" => json!({"id": 1, "name": "Ann", "address": {"city": "Oslo"}}),
+                _ => return Ok(Response::empty().status(reqwest::StatusCode::NOT_FOUND)),
+            };
+            Ok(Response::empty().body(Bytes::from(serde_json::to_vec(&body)?)))
+        }
+    }
+
+    fn runtime() -> TargetRuntime {
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.http = Arc::new(Api);
+        runtime
+    }
+
+    fn probe(url: &str, base_url: &str, headers: &[&str]) -> Probe {
+        Probe {
+            url: Url::parse(url).unwrap(),
+            base_url: Url::parse(base_url).unwrap(),
+            headers: headers
+                .iter()
+                .map(|header| parse_header(header).unwrap())
+                .collect::<HeaderMap>(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_list() {
+        let probe = probe(
+            "http://staging.example.com/users",
+            "https://api.example.com",
+            &["Authorization: Bearer secret"],
+        );
+
+        let config = probe_config(&runtime(), &probe).await.unwrap();
+
+        let users = &config.types["Query"].fields["users"];
+        assert!(users.type_of.is_list());
+        let Some(Resolver::Http(http)) = users.resolvers.0.first() else {
+            panic!("users should be resolved with @http");
+        };
+        assert_eq!(http.url, "https://api.example.com/users");
+        assert!(config
+            .upstream
+            .allowed_headers
+            .as_ref()
+            .is_some_and(|headers| headers.contains("authorization")));
+        assert!(!config.to_sdl().contains("secret"));
+        assert!(Blueprint::try_from(&ConfigModule::from(config)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_probe_object() {
+        let probe = probe(
+            "http://api.example.com/users/1",
+            "http://api.example.com",
+            &[],
+        );
+
+        let config = probe_config(&runtime(), &probe).await.unwrap();
+
+        let user = &config.types["Query"].fields["users"];
+        assert!(!user.type_of.is_list());
+        let Some(Resolver::Http(http)) = user.resolvers.0.first() else {
+            panic!("users should be resolved with @http");
+        };
+        assert!(http
+            .url
+            .starts_with("http://api.example.com/users/{{.args."));
+        assert!(Blueprint::try_from(&ConfigModule::from(config)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_probe_unauthorized() {
+        let probe = probe(
+            "http://api.example.com/users",
+            "http://api.example.com",
+            &[],
+        );
+
+        let error = probe_config(&runtime(), &probe).await.unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "http://api.example.com/users responded with 401 Unauthorized, pass the credentials with `--header`"
+        );
+    }
+
+    #[test]
+    fn test_field_name() {
+        let name = |url: &str| field_name(&Url::parse(url).unwrap());
+
+        assert_eq!(name("http://example.com/api/v1/user-posts"), "userPosts");
+        assert_eq!(name("http://example.com/users/42"), "users");
+        assert_eq!(name("http://example.com/"), "data");
+    }
+}
@@ -1,7 +1,12 @@
+mod bench;
 mod check;
+mod config;
+mod convert;
 mod gen;
 mod helpers;
 mod init;
 pub mod run;
+mod snapshot;
 mod start;
+mod types;
 mod validate_rc;
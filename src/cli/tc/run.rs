@@ -4,18 +4,26 @@ use dotenvy::dotenv;
 
 use super::helpers::TRACKER;
 use super::validate_rc::validate_rc_config_files;
-use super::{check, gen, init, start};
-use crate::cli::command::{Cli, Command};
+use super::{bench, check, config, convert, gen, init, snapshot, start, types};
+use crate::cli::command::{Cli, Command, ConfigCommand, GenCommand};
 use crate::cli::{self, update_checker};
 use crate::core::blueprint::Blueprint;
 use crate::core::config::reader::ConfigReader;
+use crate::core::generator::{ProtoOptions, TypeNaming};
 use crate::core::runtime::TargetRuntime;
+use crate::core::tracing::{default_tracing_tailcall, get_log_format, set_log_format};
 
 pub async fn run() -> Result<()> {
     if let Ok(path) = dotenv() {
         tracing::info!("Env file: {:?} loaded", path);
     }
     let cli = Cli::parse();
+    if let Some(format) = cli.log_format {
+        set_log_format(format);
+    }
+    // the guard of `main` is set before the flag and the env file are read
+    let _guard =
+        get_log_format().map(|_| tracing::subscriber::set_default(default_tracing_tailcall()));
     tokio::task::spawn(update_checker::check_for_update());
     // Initialize ping event every 60 seconds
     let _ = TRACKER
@@ -29,7 +37,13 @@ pub async fn run() -> Result<()> {
         ))
         .await;
 
-    run_command(cli).await
+    cli::runtime::init_cassette()?;
+
+    let result = run_command(cli).await;
+    // the server returns once it's shut down, the cassette is written before
+    // the process exits
+    cli::runtime::flush_cassette().await;
+    result
 }
 
 fn get_runtime_and_config_reader(verify_ssl: bool) -> (TargetRuntime, ConfigReader) {
@@ -41,27 +55,115 @@ fn get_runtime_and_config_reader(verify_ssl: bool) -> (TargetRuntime, ConfigRead
 
 async fn run_command(cli: Cli) -> Result<()> {
     match cli.command {
-        Command::Start { file_paths, verify_ssl } => {
+        Command::Start { file_paths, config_env, verify_ssl, mock, profile } => {
             let (runtime, config_reader) = get_runtime_and_config_reader(verify_ssl);
             validate_rc_config_files(runtime, &file_paths).await;
-            start::start_command(file_paths, &config_reader).await?;
+            start::start_command(file_paths, config_env, mock, profile, &config_reader).await?;
         }
-        Command::Check { file_paths, n_plus_one_queries, schema, verify_ssl } => {
+        Command::Check {
+            file_paths,
+            n_plus_one_queries,
+            schema,
+            verify_ssl,
+            strict,
+            profile,
+            timing,
+        } => {
             let (runtime, config_reader) = get_runtime_and_config_reader(verify_ssl);
-            validate_rc_config_files(runtime.clone(), &file_paths).await;
+            let warnings = validate_rc_config_files(runtime.clone(), &file_paths).await;
             check::check_command(
-                check::CheckParams { file_paths, n_plus_one_queries, schema, runtime },
+                check::CheckParams {
+                    file_paths,
+                    n_plus_one_queries,
+                    schema,
+                    strict,
+                    profile,
+                    timing,
+                    warnings,
+                    runtime,
+                },
                 &config_reader,
             )
             .await?;
         }
-        Command::Init { folder_path } => {
+        Command::Init { folder_path, probe, headers, port, playground, base_url } => {
             let (runtime, _) = get_runtime_and_config_reader(true);
-            init::init_command(runtime, &folder_path).await?;
+            init::init_command(
+                runtime,
+                init::InitParams { folder_path, probe, headers, port, playground, base_url },
+            )
+            .await?;
         }
-        Command::Gen { file_path } => {
+        Command::Gen {
+            command: Some(GenCommand::Types { file_paths, lang, out, scalars, watch }),
+            ..
+        } => {
+            let (runtime, config_reader) = get_runtime_and_config_reader(true);
+            types::types_command(
+                types::TypesParams { file_paths, lang, out, scalars, watch },
+                &config_reader,
+                runtime,
+            )
+            .await?;
+        }
+        Command::Gen {
+            command: None,
+            file_path: Some(file_path),
+            strict,
+            include_services,
+            type_prefix,
+            strip_package_prefix,
+            dry_run,
+            no_resume,
+        } => {
             let (runtime, _) = get_runtime_and_config_reader(true);
-            gen::gen_command(&file_path, runtime).await?;
+            let type_naming = match type_prefix {
+                Some(prefix) => TypeNaming::Prefix(prefix),
+                None if strip_package_prefix => TypeNaming::StripPackage,
+                None => TypeNaming::Qualified,
+            };
+            let proto_options = ProtoOptions::default()
+                .include_services(include_services)
+                .type_naming(type_naming);
+            gen::gen_command(
+                &file_path,
+                strict,
+                dry_run,
+                !no_resume,
+                proto_options,
+                runtime,
+            )
+            .await?;
+        }
+        Command::Gen { .. } => unreachable!("the file path is required without a subcommand"),
+        Command::Snapshot { schema, output } => {
+            let (runtime, config_reader) = get_runtime_and_config_reader(true);
+            snapshot::snapshot_command(&schema, &output, &config_reader, runtime).await?;
+        }
+        Command::Diff { snapshot, file_paths } => {
+            let (runtime, config_reader) = get_runtime_and_config_reader(true);
+            snapshot::diff_command(&snapshot, &file_paths, &config_reader, runtime).await?;
+        }
+        Command::Bench { schema, query, concurrency, duration } => {
+            let (_, config_reader) = get_runtime_and_config_reader(true);
+            bench::bench_command(
+                bench::BenchParams { file_paths: schema, query, concurrency, duration },
+                &config_reader,
+            )
+            .await?;
+        }
+        Command::Convert { file_path, from, to, out } => {
+            let (runtime, config_reader) = get_runtime_and_config_reader(true);
+            convert::convert_command(
+                convert::ConvertParams { file_path, from, to, out },
+                &config_reader,
+                runtime,
+            )
+            .await?;
+        }
+        Command::Config { command: ConfigCommand::Show { file_paths, profile } } => {
+            let (_, config_reader) = get_runtime_and_config_reader(true);
+            config::show_command(&file_paths, profile.as_deref(), &config_reader).await?;
         }
     }
     Ok(())
@@ -0,0 +1,186 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tailcall_valid::Validator;
+
+use crate::cli::fmt::Fmt;
+use crate::core::blueprint::Blueprint;
+use crate::core::config::reader::ConfigReader;
+use crate::core::config::{Config, ConfigModule};
+use crate::core::runtime::TargetRuntime;
+use crate::core::{EnvIO, Errata};
+
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// A baseline of a schema that was verified to work, with its types, fields
+/// and resolvers.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// The config printed as SDL. The types and their fields are sorted, so
+    /// the snapshot only changes when the schema does.
+    schema: String,
+}
+
+/// An environment without variables, the configs read with it keep their
+/// `{{.env.*}}` templates instead of the values, eg. the secrets.
+struct NoEnv;
+
+impl EnvIO for NoEnv {
+    fn get(&self, _: &str) -> Option<Cow<'_, str>> {
+        None
+    }
+}
+
+/// Reads the configs and checks that they compile, so only working schemas
+/// are used as a baseline or compared with one.
+async fn read_verified(
+    file_paths: &[String],
+    config_reader: &ConfigReader,
+) -> Result<ConfigModule> {
+    let config_module = config_reader.read_all(file_paths).await?;
+    Blueprint::try_from(&config_module).map_err(Errata::from)?;
+    Ok(config_module)
+}
+
+pub(super) async fn snapshot_command(
+    file_paths: &[String],
+    output: &str,
+    config_reader: &ConfigReader,
+    runtime: TargetRuntime,
+) -> Result<()> {
+    read_verified(file_paths, config_reader).await?;
+    // the configs are read again without the environment, so that the values
+    // of the variables aren't written to the snapshot
+    let runtime_without_env = TargetRuntime { env: Arc::new(NoEnv), ..runtime.clone() };
+    let config_module = ConfigReader::init(runtime_without_env)
+        .read_all(file_paths)
+        .await?;
+    let snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        schema: config_module.config().to_sdl(),
+    };
+
+    let content = serde_json::to_string_pretty(&snapshot)?;
+    runtime.file.write(output, content.as_bytes()).await?;
+    tracing::info!(
+        "Snapshot of {} written to {}",
+        file_paths.join(", "),
+        output
+    );
+    Ok(())
+}
+
+pub(super) async fn diff_command(
+    snapshot_path: &str,
+    file_paths: &[String],
+    config_reader: &ConfigReader,
+    runtime: TargetRuntime,
+) -> Result<()> {
+    let snapshot: Snapshot = serde_json::from_str(&runtime.file.read(snapshot_path).await?)?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(anyhow!(
+            "Unsupported snapshot version {} in {}, expected {}",
+            snapshot.version,
+            snapshot_path,
+            SNAPSHOT_VERSION
+        ));
+    }
+    let baseline = Config::from_sdl(&snapshot.schema).to_result()?;
+    let config_module = read_verified(file_paths, config_reader).await?;
+
+    let changes = baseline.diff_schema_changes(config_module.config());
+    if changes.is_empty() {
+        tracing::info!("No changes since the snapshot {}", snapshot_path);
+        return Ok(());
+    }
+
+    Fmt::display(Fmt::heading(&format!(
+        "{} changes since the snapshot {}:\n",
+        changes.len(),
+        snapshot_path
+    )));
+    let breaking = changes.iter().filter(|change| change.is_breaking()).count();
+    for change in changes {
+        Fmt::display(change.to_string());
+    }
+    if breaking > 0 {
+        return Err(anyhow!(
+            "Breaking changes since the snapshot {}: {}",
+            snapshot_path,
+            breaking
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tests::TestEnvIO;
+
+    const SCHEMA: &str = r#"
+        schema { query: Query }
+        type Query {
+          user: User @http(url: "http://api.test/users/1", headers: [{key: "Authorization", value: "{{.env.TOKEN}}"}])
+        }
+        type User { id: Int name: String }
+    "#;
+
+    fn runtime() -> TargetRuntime {
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.env = Arc::new(TestEnvIO::from_iter([(
+            "TOKEN".to_string(),
+            "secret".to_string(),
+        )]));
+        runtime
+    }
+
+    async fn snapshot(dir: &std::path::Path) -> String {
+        let path = dir.join("schema.graphql").to_string_lossy().to_string();
+        let output = dir.join("snapshot.json").to_string_lossy().to_string();
+        std::fs::write(&path, SCHEMA).unwrap();
+        let config_reader = ConfigReader::init(runtime());
+        snapshot_command(&[path], &output, &config_reader, runtime())
+            .await
+            .unwrap();
+        output
+    }
+
+    async fn diff(dir: &std::path::Path, snapshot: &str, schema: &str) -> Result<()> {
+        let path = dir.join("changed.graphql").to_string_lossy().to_string();
+        std::fs::write(&path, schema).unwrap();
+        let config_reader = ConfigReader::init(runtime());
+        diff_command(snapshot, &[path], &config_reader, runtime()).await
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_without_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = snapshot(dir.path()).await;
+
+        let content = std::fs::read_to_string(output).unwrap();
+        assert!(content.contains("{{.env.TOKEN}}"));
+        assert!(!content.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = snapshot(dir.path()).await;
+
+        assert!(diff(dir.path(), &output, SCHEMA).await.is_ok());
+
+        let added = SCHEMA.replace("name: String", "name: String email: String");
+        assert!(diff(dir.path(), &output, &added).await.is_ok());
+
+        let removed = SCHEMA.replace(" name: String", "");
+        let error = diff(dir.path(), &output, &removed).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Breaking changes since the snapshot {output}: 1")
+        );
+    }
+}
@@ -4,15 +4,38 @@ use super::helpers::log_endpoint_set;
 use crate::cli::fmt::Fmt;
 use crate::cli::server::Server;
 use crate::core::config::reader::ConfigReader;
+use crate::core::http::MockMode;
 
 pub(super) async fn start_command(
     file_paths: Vec<String>,
+    config_env: Option<String>,
+    mock: Option<MockMode>,
+    profile: Option<String>,
     config_reader: &ConfigReader,
 ) -> Result<()> {
-    let config_module = config_reader.read_all(&file_paths).await?;
+    // the config of an environment variable can't change, so it isn't reloaded
+    let (config_module, reload_source) = match config_env {
+        Some(var_name) => {
+            let config = config_reader.from_env(&var_name)?;
+            (config_reader.resolve(config, None).await?, None)
+        }
+        None => {
+            let config_module = config_reader
+                .read_all_with_profile(&file_paths, profile.as_deref())
+                .await?;
+            (config_module, Some((file_paths, profile)))
+        }
+    };
     log_endpoint_set(&config_module.extensions().endpoint_set);
     Fmt::log_n_plus_one(false, config_module.config());
-    let server = Server::new(config_module);
+    Fmt::warn_non_null_delete(config_module.config());
+    let server = Server::new(config_module)
+        .mock(mock)
+        .timings(config_reader.timings());
+    let server = match reload_source {
+        Some((file_paths, profile)) => server.reload_source(file_paths, profile),
+        None => server,
+    };
     server.fork_start().await?;
     Ok(())
 }
@@ -0,0 +1,129 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+
+use crate::core::blueprint::{Blueprint, ClientTypes, TypeLang};
+use crate::core::config::reader::ConfigReader;
+use crate::core::runtime::TargetRuntime;
+use crate::core::Errata;
+
+/// How often the configuration files are checked for changes with `--watch`.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub(super) struct TypesParams {
+    pub file_paths: Vec<String>,
+    pub lang: TypeLang,
+    pub out: String,
+    pub scalars: Vec<(String, String)>,
+    pub watch: bool,
+}
+
+pub(super) async fn types_command(
+    params: TypesParams,
+    config_reader: &ConfigReader,
+    runtime: TargetRuntime,
+) -> Result<()> {
+    let client_types = params
+        .scalars
+        .iter()
+        .fold(ClientTypes::new(params.lang), |types, (name, type_of)| {
+            types.scalar(name, type_of)
+        });
+
+    if !params.watch {
+        return write_types(&params, &client_types, config_reader, &runtime).await;
+    }
+
+    if let Err(err) = write_types(&params, &client_types, config_reader, &runtime).await {
+        tracing::error!("{}", err);
+    }
+    let mut modified = modified_times(&params.file_paths, &config_reader.globs());
+    tracing::info!("Watching {} for changes", params.file_paths.join(", "));
+    loop {
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        let current = modified_times(&params.file_paths, &config_reader.globs());
+        if current == modified {
+            continue;
+        }
+        modified = current;
+        if let Err(err) = write_types(&params, &client_types, config_reader, &runtime).await {
+            tracing::error!("{}", err);
+        }
+    }
+}
+
+async fn write_types(
+    params: &TypesParams,
+    client_types: &ClientTypes,
+    config_reader: &ConfigReader,
+    runtime: &TargetRuntime,
+) -> Result<()> {
+    let config_module = config_reader.read_all(&params.file_paths).await?;
+    let blueprint = Blueprint::try_from(&config_module).map_err(Errata::from)?;
+    let types = client_types.generate(&blueprint);
+    runtime.file.write(&params.out, types.as_bytes()).await?;
+    tracing::info!("Types written to {}", params.out);
+    Ok(())
+}
+
+/// The time the local files were last modified, the remote ones are not
+/// watched. For the globs of the links, the directories they match in are
+/// watched too, so that adding or removing a file is noticed.
+fn modified_times(file_paths: &[String], globs: &[String]) -> Vec<Option<SystemTime>> {
+    let globbed = globs.iter().flat_map(|pattern| {
+        let matches = glob::glob(pattern)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|path| path.to_string_lossy().to_string());
+        std::iter::once(glob_dir(pattern)).chain(matches)
+    });
+
+    file_paths
+        .iter()
+        .cloned()
+        .chain(globbed)
+        .map(|path| {
+            std::fs::metadata(Path::new(&path))
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
+/// The directory a glob matches in, the part of it before its first pattern.
+fn glob_dir(pattern: &str) -> String {
+    let end = pattern.find(['*', '?', '[']).unwrap_or(pattern.len());
+    match pattern[..end].rfind(['/', '\\']) {
+        Some(separator) => pattern[..separator].to_string(),
+        None => ".".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_dir() {
+        assert_eq!(glob_dir("types/*.graphql"), "types");
+        assert_eq!(glob_dir("./config/types/**/*.graphql"), "./config/types");
+        assert_eq!(glob_dir("*.graphql"), ".");
+    }
+
+    #[test]
+    fn test_modified_times_with_new_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.graphql"), "type A { id: Int }").unwrap();
+        let glob = dir.path().join("*.graphql").to_string_lossy().to_string();
+
+        let before = modified_times(&[], &[glob.clone()]);
+        std::fs::write(dir.path().join("b.graphql"), "type B { id: Int }").unwrap();
+        let after = modified_times(&[], &[glob]);
+
+        // the directory and the two files
+        assert_eq!(after.len(), 3);
+        assert_ne!(before, after);
+    }
+}
@@ -3,7 +3,14 @@ use std::path::Path;
 use super::helpers::{TAILCALL_RC, TAILCALL_RC_SCHEMA};
 use crate::core::runtime::TargetRuntime;
 
-pub async fn validate_rc_config_files(runtime: TargetRuntime, file_paths: &[String]) {
+/// Warns about outdated `.tailcallrc` files next to the given config files.
+/// Returns the emitted warnings.
+pub async fn validate_rc_config_files(
+    runtime: TargetRuntime,
+    file_paths: &[String],
+) -> Vec<String> {
+    let mut warnings = vec![];
+
     // base config files.
     let tailcallrc = include_str!("../../../generated/.tailcallrc.graphql");
     let tailcallrc_json = include_str!("../../../generated/.tailcallrc.schema.json");
@@ -39,11 +46,15 @@ pub async fn validate_rc_config_files(runtime: TargetRuntime, file_paths: &[Stri
 
         if !outdated_files.is_empty() {
             let outdated_files = outdated_files.join(", ");
-            tracing::warn!(
+            let warning = format!(
                 "[{}] {} outdated, reinitialize using tailcall init.",
                 outdated_files,
                 pluralizer::pluralize("is", outdated_files.len() as isize, false)
             );
+            tracing::warn!("{}", warning);
+            warnings.push(warning);
         }
     }
+
+    warnings
 }
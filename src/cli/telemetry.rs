@@ -16,18 +16,18 @@ use opentelemetry_sdk::trace::{Tracer, TracerProvider};
 use opentelemetry_sdk::{runtime, Resource};
 use serde::Serialize;
 use tonic::metadata::MetadataMap;
-use tracing::level_filters::LevelFilter;
 use tracing::Subscriber;
 use tracing_opentelemetry::OpenTelemetryLayer;
 use tracing_subscriber::filter::dynamic_filter_fn;
 use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::{Layer, Registry};
+use tracing_subscriber::{reload, Layer, Registry};
 
 use super::metrics::init_metrics;
+use crate::core::app_context::AppContextHandle;
 use crate::core::blueprint::telemetry::{OtlpExporter, Telemetry, TelemetryExporter};
 use crate::core::runtime::TargetRuntime;
 use crate::core::tracing::{
-    default_tracing, default_tracing_tailcall, get_log_level, tailcall_filter_target,
+    default_tracing_tailcall, get_log_format, log_filter, tracing_with_format, LogFilterHandle,
 };
 use crate::core::Errata;
 
@@ -192,7 +192,13 @@ fn set_tracing_subscriber(subscriber: impl Subscriber + Send + Sync) {
     let _ = tracing::subscriber::set_global_default(subscriber);
 }
 
-pub fn init_opentelemetry(config: Telemetry, runtime: &TargetRuntime) -> anyhow::Result<()> {
+pub fn init_opentelemetry(
+    config: Telemetry,
+    runtime: &TargetRuntime,
+    app_ctx: AppContextHandle,
+) -> anyhow::Result<LogFilterHandle> {
+    let log_format = get_log_format().unwrap_or(config.log_format);
+    let log_filter = log_filter(config.log_filter.as_deref())?;
     if let Some(export) = &config.export {
         global::set_error_handler(|error| {
             if !matches!(
@@ -220,9 +226,10 @@ pub fn init_opentelemetry(config: Telemetry, runtime: &TargetRuntime) -> anyhow:
 
         global::set_text_map_propagator(TraceContextPropagator::new());
 
+        let (log_filter, handle) = reload::Layer::new(log_filter);
         let subscriber = tracing_subscriber::registry()
             .with(trace_layer)
-            .with(default_tracing())
+            .with(tracing_with_format(log_format))
             .with(
                 log_layer.with_filter(dynamic_filter_fn(|_metatada, context| {
                     // ignore logs that are generated inside tracing::Span since they will be logged
@@ -231,17 +238,19 @@ pub fn init_opentelemetry(config: Telemetry, runtime: &TargetRuntime) -> anyhow:
                     context.lookup_current().is_none()
                 })),
             )
-            .with(tailcall_filter_target())
-            .with(LevelFilter::from_level(
-                get_log_level().unwrap_or(tracing::Level::INFO),
-            ));
+            .with(log_filter);
 
-        init_metrics(runtime)?;
+        init_metrics(runtime, app_ctx)?;
 
         set_tracing_subscriber(subscriber);
+        Ok(LogFilterHandle::new(handle))
     } else {
-        set_tracing_subscriber(default_tracing_tailcall());
+        let (log_filter, handle) = reload::Layer::new(log_filter);
+        set_tracing_subscriber(
+            tracing_subscriber::registry()
+                .with(log_filter)
+                .with(tracing_with_format(log_format)),
+        );
+        Ok(LogFilterHandle::new(handle))
     }
-
-    Ok(())
 }
@@ -0,0 +1,460 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use dashmap::DashMap;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_timer::Delay;
+use http::header::{HeaderValue, ACCEPT, CONTENT_ENCODING, CONTENT_TYPE};
+use prost::Message;
+use prost_reflect::prost_types::Timestamp;
+use tailcall_hasher::TailcallHasher;
+
+use super::histogram::DurationHistogram;
+use super::report::{
+    ContextualizedStats, FieldStat, QueryLatencyStats, ReferencedFieldsForType, Report,
+    ReportHeader, StatsContext, TracesAndStats, TypeStat,
+};
+use super::signature::stats_key;
+use crate::core::config::Apollo;
+use crate::core::jit::{Field, FieldId, FieldTiming, OperationPlan};
+use crate::core::HttpIO;
+
+/// Endpoint the reports are sent to when none is configured.
+pub const DEFAULT_ENDPOINT: &str =
+    "https://usage-reporting.api.apollographql.com/api/ingress/traces";
+
+/// Interval between two reports.
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Maximum number of distinct operations kept in a single report. The
+/// statistics of any other operation are dropped until the next report.
+const MAX_OPERATIONS: usize = 512;
+
+/// Maximum number of cached operation signatures.
+const MAX_SIGNATURES: usize = 4096;
+
+type ReferencedFields = BTreeMap<String, BTreeSet<String>>;
+
+/// Stats key of an operation along with the fields it references.
+struct Operation {
+    stats_key: String,
+    referenced_fields: OnceLock<ReferencedFields>,
+}
+
+#[derive(Default)]
+struct FieldStats {
+    return_type: String,
+    errors_count: u64,
+    observed_execution_count: u64,
+    requests_with_errors_count: u64,
+    latency: DurationHistogram,
+}
+
+struct OperationStats {
+    operation: Arc<Operation>,
+    request_count: u64,
+    requests_with_errors_count: u64,
+    latency: DurationHistogram,
+    per_type: BTreeMap<String, BTreeMap<String, FieldStats>>,
+}
+
+impl OperationStats {
+    fn new(operation: Arc<Operation>) -> Self {
+        Self {
+            operation,
+            request_count: 0,
+            requests_with_errors_count: 0,
+            latency: DurationHistogram::default(),
+            per_type: BTreeMap::new(),
+        }
+    }
+
+    fn into_traces_and_stats(self) -> TracesAndStats {
+        let per_type_stat = self
+            .per_type
+            .into_iter()
+            .map(|(type_name, fields)| {
+                let per_field_stat = fields
+                    .into_iter()
+                    .map(|(field_name, stats)| {
+                        let stat = FieldStat {
+                            return_type: stats.return_type,
+                            errors_count: stats.errors_count,
+                            observed_execution_count: stats.observed_execution_count,
+                            estimated_execution_count: stats.observed_execution_count as f64,
+                            requests_with_errors_count: stats.requests_with_errors_count,
+                            latency_count: stats.latency.to_vec(),
+                        };
+                        (field_name, stat)
+                    })
+                    .collect();
+                (type_name, TypeStat { per_field_stat })
+            })
+            .collect();
+
+        let referenced_fields_by_type = self
+            .operation
+            .referenced_fields
+            .get()
+            .into_iter()
+            .flatten()
+            .map(|(type_name, fields)| {
+                let fields = ReferencedFieldsForType {
+                    field_names: fields.iter().cloned().collect(),
+                    is_interface: false,
+                };
+                (type_name.clone(), fields)
+            })
+            .collect();
+
+        TracesAndStats {
+            stats_with_context: vec![ContextualizedStats {
+                context: Some(StatsContext::default()),
+                query_latency_stats: Some(QueryLatencyStats {
+                    latency_count: self.latency.to_vec(),
+                    request_count: self.request_count,
+                    requests_with_errors_count: self.requests_with_errors_count,
+                }),
+                per_type_stat,
+            }],
+            referenced_fields_by_type,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    operations: HashMap<String, OperationStats>,
+    operation_count: u64,
+    dropped: u64,
+}
+
+impl Stats {
+    /// Returns the statistics of the operation, or `None` if the report is
+    /// already full.
+    fn entry(&mut self, operation: &Arc<Operation>) -> Option<&mut OperationStats> {
+        if !self.operations.contains_key(&operation.stats_key) {
+            if self.operations.len() >= MAX_OPERATIONS {
+                self.dropped += 1;
+                return None;
+            }
+            self.operations.insert(
+                operation.stats_key.clone(),
+                OperationStats::new(operation.clone()),
+            );
+        }
+
+        self.operations.get_mut(&operation.stats_key)
+    }
+}
+
+/// Aggregates the usage statistics of the operations executed by the server
+/// and periodically sends them to Apollo Studio.
+pub struct UsageCollector {
+    api_key: String,
+    graph_ref: String,
+    endpoint: String,
+    service_version: String,
+    operations: DashMap<u64, Arc<Operation>>,
+    stats: Mutex<Stats>,
+}
+
+impl UsageCollector {
+    pub fn new(apollo: &Apollo) -> Self {
+        Self {
+            api_key: apollo.api_key.clone(),
+            graph_ref: apollo.graph_ref.clone(),
+            endpoint: apollo
+                .endpoint
+                .clone()
+                .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            service_version: apollo.user_version.clone().unwrap_or_default(),
+            operations: DashMap::new(),
+            stats: Mutex::new(Stats::default()),
+        }
+    }
+
+    fn operation(&self, query: &str, operation_name: Option<&str>) -> Arc<Operation> {
+        let mut hasher = TailcallHasher::default();
+        query.hash(&mut hasher);
+        operation_name.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if let Some(operation) = self.operations.get(&hash) {
+            return operation.clone();
+        }
+
+        let operation = Arc::new(Operation {
+            stats_key: stats_key(query, operation_name),
+            referenced_fields: OnceLock::new(),
+        });
+        if self.operations.len() < MAX_SIGNATURES {
+            self.operations.insert(hash, operation.clone());
+        }
+
+        operation
+    }
+
+    /// Records the latency and the outcome of a GraphQL request.
+    pub fn record_operation(
+        &self,
+        query: &str,
+        operation_name: Option<&str>,
+        duration: Duration,
+        has_errors: bool,
+    ) {
+        let operation = self.operation(query, operation_name);
+        let mut stats = self.stats.lock().unwrap();
+        stats.operation_count += 1;
+
+        if let Some(stats) = stats.entry(&operation) {
+            stats.request_count += 1;
+            if has_errors {
+                stats.requests_with_errors_count += 1;
+            }
+            stats.latency.record(duration);
+        }
+    }
+
+    /// Records the fields referenced by the operation and the time taken by
+    /// their resolvers.
+    pub fn record_fields<Input>(
+        &self,
+        query: &str,
+        operation_name: Option<&str>,
+        plan: &OperationPlan<Input>,
+        timings: &[FieldTiming],
+    ) {
+        let operation = self.operation(query, operation_name);
+        let fields = parent_types(plan);
+        operation
+            .referenced_fields
+            .get_or_init(|| referenced_fields(&fields));
+
+        if timings.is_empty() {
+            return;
+        }
+
+        let mut stats = self.stats.lock().unwrap();
+        let Some(stats) = stats.entry(&operation) else {
+            return;
+        };
+
+        let mut failed = HashSet::new();
+        for timing in timings {
+            let Some((parent, field)) = fields.get(&timing.id) else {
+                continue;
+            };
+
+            let field_stats = stats
+                .per_type
+                .entry(parent.to_string())
+                .or_default()
+                .entry(field.name.clone())
+                .or_insert_with(|| FieldStats {
+                    return_type: format!("{:?}", field.type_of),
+                    ..Default::default()
+                });
+
+            field_stats.observed_execution_count += 1;
+            field_stats.latency.record(timing.duration);
+            if timing.is_err {
+                field_stats.errors_count += 1;
+                if failed.insert(&timing.id) {
+                    field_stats.requests_with_errors_count += 1;
+                }
+            }
+        }
+    }
+
+    /// Takes the statistics collected since the last report. Returns `None`
+    /// when nothing was collected.
+    pub fn take_report(&self) -> Option<Report> {
+        let stats = std::mem::take(&mut *self.stats.lock().unwrap());
+        if stats.operation_count == 0 && stats.operations.is_empty() {
+            return None;
+        }
+
+        if stats.dropped > 0 {
+            tracing::warn!(
+                "Dropped the usage statistics of {} requests, a report is limited to {} operations",
+                stats.dropped,
+                MAX_OPERATIONS
+            );
+        }
+
+        let now = Utc::now();
+        let traces_per_query = stats
+            .operations
+            .into_iter()
+            .map(|(key, stats)| (key, stats.into_traces_and_stats()))
+            .collect();
+
+        Some(Report {
+            header: Some(ReportHeader {
+                graph_ref: self.graph_ref.clone(),
+                agent_version: format!("tailcall@{}", env!("CARGO_PKG_VERSION")),
+                service_version: self.service_version.clone(),
+                runtime_version: "rust".to_string(),
+                uname: std::env::consts::OS.to_string(),
+                ..Default::default()
+            }),
+            traces_per_query,
+            end_time: Some(Timestamp {
+                seconds: now.timestamp(),
+                nanos: now.timestamp_subsec_nanos() as i32,
+            }),
+            operation_count: stats.operation_count,
+            traces_pre_aggregated: true,
+        })
+    }
+
+    /// Sends the statistics collected since the last report. The statistics
+    /// are dropped if the report can't be delivered.
+    pub async fn flush(&self, http: &dyn HttpIO) -> Result<()> {
+        let Some(report) = self.take_report() else {
+            return Ok(());
+        };
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&report.encode_to_vec())?;
+        let body = encoder.finish()?;
+
+        let mut request = reqwest::Request::new(reqwest::Method::POST, self.endpoint.parse()?);
+        let headers = request.headers_mut();
+        headers.insert("X-Api-Key", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/protobuf"),
+        );
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        request.body_mut().replace(body.into());
+
+        let response = http.execute(request).await?;
+        if !response.status.is_success() {
+            return Err(anyhow!(
+                "Apollo usage report was rejected with status {}",
+                response.status
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Sends a report every [REPORT_INTERVAL], until the collector is dropped
+    /// with its context, eg. when the config is reloaded.
+    pub async fn report_periodically(collector: Weak<Self>, http: Arc<dyn HttpIO>) {
+        loop {
+            Delay::new(REPORT_INTERVAL).await;
+            let Some(collector) = collector.upgrade() else {
+                break;
+            };
+            if let Err(error) = collector.flush(http.as_ref()).await {
+                tracing::warn!("Failed to send the Apollo usage report: {}", error);
+            }
+        }
+    }
+}
+
+/// Maps every field of the plan to the name of the type it belongs to.
+fn parent_types<Input>(plan: &OperationPlan<Input>) -> HashMap<FieldId, (&str, &Field<Input>)> {
+    let mut fields = HashMap::new();
+    let mut stack = plan
+        .selection
+        .iter()
+        .map(|field| (plan.root_name(), field))
+        .collect::<Vec<_>>();
+
+    while let Some((parent, field)) = stack.pop() {
+        let type_name = field.type_of.name().as_str();
+        for child in field.selection.iter() {
+            stack.push((child.type_condition.as_deref().unwrap_or(type_name), child));
+        }
+        fields.insert(field.id.clone(), (parent, field));
+    }
+
+    fields
+}
+
+fn referenced_fields<Input>(fields: &HashMap<FieldId, (&str, &Field<Input>)>) -> ReferencedFields {
+    let mut referenced = ReferencedFields::new();
+    for (parent, field) in fields.values() {
+        if field.name.starts_with("__") {
+            continue;
+        }
+        referenced
+            .entry(parent.to_string())
+            .or_default()
+            .insert(field.name.clone());
+    }
+
+    referenced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collector() -> UsageCollector {
+        UsageCollector::new(&Apollo {
+            api_key: "key".to_string(),
+            graph_ref: "graph@current".to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_record_operation() {
+        let collector = collector();
+        let query = "query Users { users { id } }";
+
+        collector.record_operation(query, None, Duration::from_millis(1), false);
+        collector.record_operation(query, None, Duration::from_millis(1), true);
+
+        let report = collector.take_report().unwrap();
+        let stats = &report.traces_per_query["# Users\nquery Users{users{id}}"];
+        let latency = stats.stats_with_context[0]
+            .query_latency_stats
+            .as_ref()
+            .unwrap();
+
+        assert_eq!(report.operation_count, 2);
+        assert_eq!(report.header.unwrap().graph_ref, "graph@current");
+        assert_eq!(latency.request_count, 2);
+        assert_eq!(latency.requests_with_errors_count, 1);
+        assert_eq!(latency.latency_count, vec![-73, 2]);
+    }
+
+    #[test]
+    fn test_take_report_resets_stats() {
+        let collector = collector();
+
+        assert!(collector.take_report().is_none());
+
+        collector.record_operation("{ a }", None, Duration::ZERO, false);
+        assert!(collector.take_report().is_some());
+        assert!(collector.take_report().is_none());
+    }
+
+    #[test]
+    fn test_drops_operations_over_limit() {
+        let collector = collector();
+
+        for i in 0..=MAX_OPERATIONS {
+            let query = format!("query Q{i} {{ a }}");
+            collector.record_operation(&query, None, Duration::ZERO, false);
+        }
+
+        let report = collector.take_report().unwrap();
+
+        assert_eq!(report.operation_count as usize, MAX_OPERATIONS + 1);
+        assert_eq!(report.traces_per_query.len(), MAX_OPERATIONS);
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+const BUCKET_COUNT: usize = 384;
+const EXPONENT_LOG: f64 = 0.09531017980432493; // ln(1.1)
+
+/// Latency histogram in the format expected by the usage reporting protocol.
+/// Bucket `n` counts the durations between `1.1^(n-1)` and `1.1^n`
+/// microseconds, so the memory used is bounded by the number of buckets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DurationHistogram {
+    buckets: Vec<i64>,
+}
+
+impl DurationHistogram {
+    pub fn record(&mut self, duration: Duration) {
+        let bucket = bucket(duration);
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+
+    /// Encodes the buckets, replacing runs of empty buckets with their
+    /// negated length.
+    pub fn to_vec(&self) -> Vec<i64> {
+        let mut output = Vec::new();
+        let mut zeros = 0;
+
+        for count in self.buckets.iter().copied() {
+            if count == 0 {
+                zeros += 1;
+                continue;
+            }
+
+            match zeros {
+                0 => {}
+                1 => output.push(0),
+                zeros => output.push(-zeros),
+            }
+            output.push(count);
+            zeros = 0;
+        }
+
+        output
+    }
+}
+
+fn bucket(duration: Duration) -> usize {
+    let micros = duration.as_nanos() as f64 / 1000.0;
+    let bucket = (micros.ln() / EXPONENT_LOG).ceil();
+
+    if bucket.is_nan() || bucket <= 0.0 {
+        0
+    } else if bucket >= BUCKET_COUNT as f64 {
+        BUCKET_COUNT - 1
+    } else {
+        bucket as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket() {
+        assert_eq!(bucket(Duration::ZERO), 0);
+        assert_eq!(bucket(Duration::from_micros(1)), 0);
+        assert_eq!(bucket(Duration::from_nanos(1050)), 1);
+        assert_eq!(bucket(Duration::from_millis(1)), 73);
+        assert_eq!(bucket(Duration::from_secs(100_000)), BUCKET_COUNT - 1);
+    }
+
+    #[test]
+    fn test_to_vec() {
+        let mut histogram = DurationHistogram::default();
+        histogram.record(Duration::ZERO);
+        histogram.record(Duration::from_nanos(1050));
+        histogram.record(Duration::from_nanos(1050));
+        histogram.record(Duration::from_micros(2));
+
+        // buckets: [1, 2, 0, 0, 0, 0, 0, 0, 1]
+        assert_eq!(histogram.to_vec(), vec![1, 2, -6, 1]);
+    }
+
+    #[test]
+    fn test_single_empty_bucket() {
+        let mut histogram = DurationHistogram::default();
+        histogram.record(Duration::from_nanos(1050));
+        histogram.record(Duration::from_nanos(1200));
+
+        assert_eq!(histogram.to_vec(), vec![0, 1, 1]);
+    }
+}
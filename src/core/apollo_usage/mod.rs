@@ -0,0 +1,11 @@
+//! Usage reporting for Apollo Studio. The statistics of every operation and
+//! of the fields it resolves are aggregated in memory and periodically sent
+//! to Apollo using the usage reporting protocol.
+
+mod collector;
+mod histogram;
+pub mod report;
+mod signature;
+
+pub use collector::*;
+pub use signature::{stats_key, PARSE_FAILURE, UNKNOWN_OPERATION};
@@ -0,0 +1,105 @@
+//! Subset of the messages of the Apollo usage reporting protocol
+//! (`reports.proto`) that are required to report operation and field
+//! statistics.
+
+use std::collections::HashMap;
+
+use prost_reflect::prost_types::Timestamp;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Report {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<ReportHeader>,
+    #[prost(map = "string, message", tag = "5")]
+    pub traces_per_query: HashMap<String, TracesAndStats>,
+    #[prost(message, optional, tag = "2")]
+    pub end_time: Option<Timestamp>,
+    #[prost(uint64, tag = "6")]
+    pub operation_count: u64,
+    #[prost(bool, tag = "7")]
+    pub traces_pre_aggregated: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ReportHeader {
+    #[prost(string, tag = "12")]
+    pub graph_ref: String,
+    #[prost(string, tag = "5")]
+    pub hostname: String,
+    #[prost(string, tag = "6")]
+    pub agent_version: String,
+    #[prost(string, tag = "7")]
+    pub service_version: String,
+    #[prost(string, tag = "8")]
+    pub runtime_version: String,
+    #[prost(string, tag = "9")]
+    pub uname: String,
+    #[prost(string, tag = "11")]
+    pub executable_schema_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TracesAndStats {
+    #[prost(message, repeated, tag = "2")]
+    pub stats_with_context: Vec<ContextualizedStats>,
+    #[prost(map = "string, message", tag = "4")]
+    pub referenced_fields_by_type: HashMap<String, ReferencedFieldsForType>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ReferencedFieldsForType {
+    #[prost(string, repeated, tag = "1")]
+    pub field_names: Vec<String>,
+    #[prost(bool, tag = "2")]
+    pub is_interface: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ContextualizedStats {
+    #[prost(message, optional, tag = "1")]
+    pub context: Option<StatsContext>,
+    #[prost(message, optional, tag = "2")]
+    pub query_latency_stats: Option<QueryLatencyStats>,
+    #[prost(map = "string, message", tag = "3")]
+    pub per_type_stat: HashMap<String, TypeStat>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StatsContext {
+    #[prost(string, tag = "2")]
+    pub client_name: String,
+    #[prost(string, tag = "3")]
+    pub client_version: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryLatencyStats {
+    #[prost(sint64, repeated, tag = "13")]
+    pub latency_count: Vec<i64>,
+    #[prost(uint64, tag = "2")]
+    pub request_count: u64,
+    #[prost(uint64, tag = "8")]
+    pub requests_with_errors_count: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TypeStat {
+    #[prost(map = "string, message", tag = "3")]
+    pub per_field_stat: HashMap<String, FieldStat>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FieldStat {
+    #[prost(string, tag = "3")]
+    pub return_type: String,
+    #[prost(uint64, tag = "4")]
+    pub errors_count: u64,
+    #[prost(uint64, tag = "5")]
+    pub observed_execution_count: u64,
+    #[prost(double, tag = "10")]
+    pub estimated_execution_count: f64,
+    #[prost(uint64, tag = "6")]
+    pub requests_with_errors_count: u64,
+    #[prost(sint64, repeated, tag = "9")]
+    pub latency_count: Vec<i64>,
+}
@@ -0,0 +1,361 @@
+use std::collections::{BTreeSet, HashMap};
+
+use async_graphql::parser::types::{
+    Directive, DocumentOperations, ExecutableDocument, FragmentDefinition, OperationDefinition,
+    OperationType, Selection, SelectionSet,
+};
+use async_graphql::{Name, Positioned};
+use async_graphql_value::Value;
+
+/// Stats key used for requests that can't be parsed.
+pub const PARSE_FAILURE: &str = "## GraphQLParseFailure\n";
+
+/// Stats key used for requests with an operation name that doesn't exist in
+/// the document.
+pub const UNKNOWN_OPERATION: &str = "## GraphQLUnknownOperationName\n";
+
+/// Computes the key under which the statistics of the operation are reported,
+/// in the `# <operationName>\n<signature>` format.
+pub fn stats_key(query: &str, operation_name: Option<&str>) -> String {
+    let Ok(doc) = async_graphql::parser::parse_query(query) else {
+        return PARSE_FAILURE.to_string();
+    };
+
+    match signature(&doc, operation_name) {
+        Some((name, signature)) => format!("# {}\n{}", name.unwrap_or("-"), signature),
+        None => UNKNOWN_OPERATION.to_string(),
+    }
+}
+
+/// Computes the usage reporting signature of an operation. The signature
+/// normalizes the operation so that requests that only differ in literals,
+/// aliases, formatting or the order of the fields are reported together:
+/// - unused fragments are dropped
+/// - literals are replaced with empty values, eg. `0`, `""`, `[]` and `{}`
+/// - aliases are removed
+/// - fields, fragments, arguments and directives are sorted by name
+/// - whitespace is reduced to the minimum
+///
+/// Returns the name of the operation along with its signature.
+pub fn signature<'a>(
+    doc: &'a ExecutableDocument,
+    operation_name: Option<&'a str>,
+) -> Option<(Option<&'a str>, String)> {
+    let (name, operation) = find_operation(doc, operation_name)?;
+
+    let mut fragments = BTreeSet::new();
+    collect_fragments(
+        &operation.selection_set.node,
+        &doc.fragments,
+        &mut fragments,
+    );
+
+    let mut printer = Printer::default();
+    for fragment in fragments {
+        if let Some(definition) = doc.fragments.get(fragment) {
+            printer.fragment(fragment, &definition.node);
+        }
+    }
+    printer.operation(name, operation);
+
+    Some((name, printer.output))
+}
+
+fn find_operation<'a>(
+    doc: &'a ExecutableDocument,
+    operation_name: Option<&'a str>,
+) -> Option<(Option<&'a str>, &'a OperationDefinition)> {
+    match (&doc.operations, operation_name) {
+        (DocumentOperations::Single(operation), None) => Some((None, &operation.node)),
+        (DocumentOperations::Single(_), Some(_)) => None,
+        (DocumentOperations::Multiple(operations), Some(name)) => operations
+            .get(name)
+            .map(|operation| (Some(name), &operation.node)),
+        (DocumentOperations::Multiple(operations), None) if operations.len() == 1 => operations
+            .iter()
+            .next()
+            .map(|(name, operation)| (Some(name.as_str()), &operation.node)),
+        (DocumentOperations::Multiple(_), None) => None,
+    }
+}
+
+fn collect_fragments<'a>(
+    selection_set: &'a SelectionSet,
+    fragments: &'a HashMap<Name, Positioned<FragmentDefinition>>,
+    used: &mut BTreeSet<&'a str>,
+) {
+    for selection in selection_set.items.iter() {
+        match &selection.node {
+            Selection::Field(field) => {
+                collect_fragments(&field.node.selection_set.node, fragments, used)
+            }
+            Selection::InlineFragment(fragment) => {
+                collect_fragments(&fragment.node.selection_set.node, fragments, used)
+            }
+            Selection::FragmentSpread(spread) => {
+                let name = spread.node.fragment_name.node.as_str();
+                if used.insert(name) {
+                    if let Some(fragment) = fragments.get(name) {
+                        collect_fragments(&fragment.node.selection_set.node, fragments, used);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn sort_key(selection: &Positioned<Selection>) -> (u8, Option<&str>) {
+    match &selection.node {
+        Selection::Field(field) => (0, Some(field.node.name.node.as_str())),
+        Selection::FragmentSpread(spread) => (1, Some(spread.node.fragment_name.node.as_str())),
+        Selection::InlineFragment(_) => (2, None),
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Prints the normalized document keeping a space only between two tokens
+/// that would otherwise merge into one.
+#[derive(Default)]
+struct Printer {
+    output: String,
+}
+
+impl Printer {
+    fn token(&mut self, token: &str) {
+        let needs_space = matches!(
+            (self.output.chars().last(), token.chars().next()),
+            (Some(last), Some(first)) if is_word(last) && is_word(first)
+        );
+        if needs_space {
+            self.output.push(' ');
+        }
+        self.output.push_str(token);
+    }
+
+    fn operation(&mut self, name: Option<&str>, operation: &OperationDefinition) {
+        let is_shorthand = operation.ty == OperationType::Query
+            && name.is_none()
+            && operation.variable_definitions.is_empty()
+            && operation.directives.is_empty();
+
+        if !is_shorthand {
+            self.token(&operation.ty.to_string());
+            if let Some(name) = name {
+                self.token(name);
+            }
+
+            let mut variables = operation.variable_definitions.iter().collect::<Vec<_>>();
+            variables.sort_by(|a, b| a.node.name.node.as_str().cmp(b.node.name.node.as_str()));
+            if !variables.is_empty() {
+                self.token("(");
+                for (i, variable) in variables.into_iter().enumerate() {
+                    if i > 0 {
+                        self.token(",");
+                    }
+                    let variable = &variable.node;
+                    self.token("$");
+                    self.token(&variable.name.node);
+                    self.token(":");
+                    self.token(&variable.var_type.node.to_string());
+                    if let Some(default_value) = &variable.default_value {
+                        self.token("=");
+                        self.value(&default_value.node.clone().into_value());
+                    }
+                    self.directives(&variable.directives);
+                }
+                self.token(")");
+            }
+            self.directives(&operation.directives);
+        }
+
+        self.selection_set(&operation.selection_set.node);
+    }
+
+    fn fragment(&mut self, name: &str, fragment: &FragmentDefinition) {
+        self.token("fragment");
+        self.token(name);
+        self.token("on");
+        self.token(&fragment.type_condition.node.on.node);
+        self.directives(&fragment.directives);
+        self.selection_set(&fragment.selection_set.node);
+    }
+
+    fn selection_set(&mut self, selection_set: &SelectionSet) {
+        if selection_set.items.is_empty() {
+            return;
+        }
+
+        // fields come first, then fragment spreads and then inline fragments that
+        // keep their original order
+        let mut items = selection_set.items.iter().collect::<Vec<_>>();
+        items.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+
+        self.token("{");
+        for item in items {
+            match &item.node {
+                Selection::Field(field) => {
+                    let field = &field.node;
+                    self.token(&field.name.node);
+                    self.arguments(&field.arguments);
+                    self.directives(&field.directives);
+                    self.selection_set(&field.selection_set.node);
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.token("...");
+                    self.token(&spread.node.fragment_name.node);
+                    self.directives(&spread.node.directives);
+                }
+                Selection::InlineFragment(fragment) => {
+                    let fragment = &fragment.node;
+                    self.token("...");
+                    if let Some(type_condition) = &fragment.type_condition {
+                        self.token("on");
+                        self.token(&type_condition.node.on.node);
+                    }
+                    self.directives(&fragment.directives);
+                    self.selection_set(&fragment.selection_set.node);
+                }
+            }
+        }
+        self.token("}");
+    }
+
+    fn arguments(&mut self, arguments: &[(Positioned<Name>, Positioned<Value>)]) {
+        if arguments.is_empty() {
+            return;
+        }
+
+        let mut arguments = arguments.iter().collect::<Vec<_>>();
+        arguments.sort_by(|a, b| a.0.node.as_str().cmp(b.0.node.as_str()));
+
+        self.token("(");
+        for (i, (name, value)) in arguments.into_iter().enumerate() {
+            if i > 0 {
+                self.token(",");
+            }
+            self.token(&name.node);
+            self.token(":");
+            self.value(&value.node);
+        }
+        self.token(")");
+    }
+
+    fn directives(&mut self, directives: &[Positioned<Directive>]) {
+        let mut directives = directives.iter().collect::<Vec<_>>();
+        directives.sort_by(|a, b| a.node.name.node.as_str().cmp(b.node.name.node.as_str()));
+
+        for directive in directives {
+            self.token("@");
+            self.token(&directive.node.name.node);
+            self.arguments(&directive.node.arguments);
+        }
+    }
+
+    /// Prints the value hiding the literals that could contain sensitive data.
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Variable(name) => {
+                self.token("$");
+                self.token(name);
+            }
+            Value::Null => self.token("null"),
+            Value::Number(_) => self.token("0"),
+            Value::String(_) | Value::Binary(_) => self.token("\"\""),
+            Value::Boolean(value) => self.token(&value.to_string()),
+            Value::Enum(name) => self.token(name),
+            Value::List(_) => self.token("[]"),
+            Value::Object(_) => self.token("{}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorts_fields_and_removes_aliases() {
+        let actual = stats_key("query Users { users { name first: id } }", None);
+
+        assert_eq!(actual, "# Users\nquery Users{users{id name}}");
+    }
+
+    #[test]
+    fn test_hides_literals() {
+        let query = r#"
+            query Search($limit: Int = 10) {
+                search(text: "secret", limit: $limit, page: 2, filter: {active: true}, ids: [1, 2], sort: ASC, exact: true) {
+                    id
+                }
+            }
+        "#;
+
+        let actual = stats_key(query, None);
+
+        assert_eq!(
+            actual,
+            "# Search\nquery Search($limit:Int=0){search(exact:true,filter:{},ids:[],limit:$limit,page:0,sort:ASC,text:\"\"){id}}"
+        );
+    }
+
+    #[test]
+    fn test_anonymous_operation() {
+        let actual = stats_key("{ posts { title id } }", None);
+
+        assert_eq!(actual, "# -\n{posts{id title}}");
+    }
+
+    #[test]
+    fn test_fragments() {
+        let query = r#"
+            query Posts {
+                posts {
+                    ... on Post { title }
+                    ...PostUser
+                    id
+                }
+            }
+
+            fragment Unused on Post { id }
+            fragment PostUser on Post { user { name @include(if: true) } }
+        "#;
+
+        let actual = stats_key(query, None);
+
+        assert_eq!(
+            actual,
+            "# Posts\nfragment PostUser on Post{user{name@include(if:true)}}query Posts{posts{id...PostUser...on Post{title}}}"
+        );
+    }
+
+    #[test]
+    fn test_selects_operation_by_name() {
+        let query = "query A { a } mutation B($id: ID!) { b(id: $id) }";
+
+        assert_eq!(
+            stats_key(query, Some("B")),
+            "# B\nmutation B($id:ID!){b(id:$id)}"
+        );
+        assert_eq!(stats_key(query, None), UNKNOWN_OPERATION);
+        assert_eq!(stats_key(query, Some("C")), UNKNOWN_OPERATION);
+    }
+
+    #[test]
+    fn test_parse_failure() {
+        assert_eq!(stats_key("{ posts {", None), PARSE_FAILURE);
+    }
+
+    #[test]
+    fn test_same_signature_for_equivalent_queries() {
+        let a = stats_key("query Q { user(id: 1) { name id } }", None);
+        let b = stats_key(
+            "query Q {\n  user(id: 42) {\n    id\n    alias: name\n  }\n}",
+            None,
+        );
+
+        assert_eq!(a, b);
+    }
+}
@@ -1,20 +1,25 @@
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 
 use async_graphql::dynamic::{self, DynamicRequest};
 use async_graphql_value::ConstValue;
 use dashmap::DashMap;
 
 use super::jit::AnyResponse;
+use crate::core::apollo_usage::UsageCollector;
 use crate::core::async_graphql_hyper::OperationId;
+use crate::core::blueprint::telemetry::TelemetryExporter;
 use crate::core::blueprint::{Blueprint, Definition, SchemaModifiers};
 use crate::core::data_loader::{DataLoader, DedupeResult};
-use crate::core::graphql::GraphqlDataLoader;
+use crate::core::graphql::{GraphqlDataLoader, GraphqlMergeLoader, RootFieldRequest};
 use crate::core::grpc;
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::{DataLoaderRequest, HttpDataLoader};
+use crate::core::http::{CircuitBreakers, DataLoaderRequest, HttpDataLoader};
 use crate::core::ir::model::{DataLoaderId, IoId, IO, IR};
 use crate::core::ir::Error;
-use crate::core::jit::{OPHash, OperationPlan};
+use crate::core::jit::{
+    AsyncRule, AsyncRuleChain, ExecutionMiddleware, Middleware, OPHash, OperationPlan,
+};
 use crate::core::rest::{Checked, EndpointSet};
 use crate::core::runtime::TargetRuntime;
 
@@ -25,11 +30,23 @@ pub struct AppContext {
     pub http_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, HttpDataLoader>>>,
     pub gql_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, GraphqlDataLoader>>>,
     pub grpc_data_loaders: Arc<Vec<DataLoader<grpc::DataLoaderRequest, GrpcDataLoader>>>,
+    /// Merges the `@graphQL` queries into aliased root fields of one
+    /// operation, when `graphqlBatching` is enabled on the upstream.
+    pub gql_merge_loader: Option<Arc<DataLoader<RootFieldRequest, GraphqlMergeLoader>>>,
     pub endpoints: EndpointSet<Checked>,
     pub dedupe_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
     pub dedupe_operation_handler: DedupeResult<OperationId, AnyResponse<Vec<u8>>, Error>,
     pub operation_plans: DashMap<OPHash, OperationPlan<async_graphql_value::Value>>,
     pub const_execution_cache: DashMap<OPHash, AnyResponse<Vec<u8>>>,
+    pub circuit_breakers: Arc<CircuitBreakers>,
+    pub usage: Option<Arc<UsageCollector>>,
+    /// Run around the resolver of every field, see [ExecutionMiddleware].
+    pub middlewares: Vec<Arc<dyn ExecutionMiddleware>>,
+    /// Run around the execution of every request, see [Middleware].
+    pub request_middlewares: Vec<Arc<dyn Middleware>>,
+    /// Checked against every operation before it's executed, see
+    /// [AsyncRule].
+    pub async_rules: AsyncRuleChain,
 }
 
 impl AppContext {
@@ -50,15 +67,34 @@ impl AppContext {
                         expr.modify(&mut |expr| match expr {
                             IR::IO(io) => match io {
                                 IO::Http {
-                                    req_template, group_by, is_list, dedupe, hook, ..
+                                    req_template,
+                                    group_by,
+                                    is_list,
+                                    dedupe,
+                                    hook,
+                                    circuit_breaker,
+                                    verify_ssl,
+                                    signer,
+                                    timeout,
+                                    batch_body,
+                                    paginate,
+                                    map_response_body,
+                                    status_codes,
+                                    ..
                                 } => {
                                     let is_list = *is_list;
                                     let dedupe = *dedupe;
+                                    let verify_ssl = *verify_ssl;
                                     let data_loader = HttpDataLoader::new(
                                         runtime.clone(),
                                         group_by.clone(),
                                         is_list,
                                     )
+                                    .verify_ssl(verify_ssl)
+                                    .signer(signer.clone())
+                                    .batch_body(batch_body.clone())
+                                    .parse_response(req_template.parse_response)
+                                    .no_content(req_template.no_content)
                                     .to_data_loader(upstream_batch.clone().unwrap_or_default());
 
                                     let result = Some(IR::IO(IO::Http {
@@ -68,6 +104,14 @@ impl AppContext {
                                         hook: hook.clone(),
                                         is_list,
                                         dedupe,
+                                        circuit_breaker: circuit_breaker.clone(),
+                                        verify_ssl,
+                                        signer: signer.clone(),
+                                        timeout: *timeout,
+                                        batch_body: batch_body.clone(),
+                                        paginate: paginate.clone(),
+                                        map_response_body: map_response_body.clone(),
+                                        status_codes: status_codes.clone(),
                                     }));
 
                                     http_data_loaders.push(data_loader);
@@ -130,9 +174,27 @@ impl AppContext {
             }
         }
 
+        let gql_merge_loader = blueprint
+            .upstream
+            .graphql_batching
+            .as_ref()
+            .map(|batching| {
+                Arc::new(
+                    GraphqlMergeLoader::new(runtime.clone(), batching.get_max_size())
+                        .into_data_loader(batching),
+                )
+            });
+
         let schema = blueprint
             .to_schema_with(SchemaModifiers::default().extensions(runtime.extensions.clone()));
 
+        let usage = match &blueprint.telemetry.export {
+            Some(TelemetryExporter::Apollo(apollo)) if apollo.enable_usage_reporting() => {
+                Some(Arc::new(UsageCollector::new(apollo)))
+            }
+            _ => None,
+        };
+
         AppContext {
             schema,
             runtime,
@@ -140,16 +202,75 @@ impl AppContext {
             http_data_loaders: Arc::new(http_data_loaders),
             gql_data_loaders: Arc::new(gql_data_loaders),
             grpc_data_loaders: Arc::new(grpc_data_loaders),
+            gql_merge_loader,
             endpoints,
 
             dedupe_handler: Arc::new(DedupeResult::new(false)),
             dedupe_operation_handler: DedupeResult::new(false),
             operation_plans: DashMap::new(),
             const_execution_cache: DashMap::default(),
+            circuit_breakers: Arc::new(CircuitBreakers::default()),
+            usage,
+            middlewares: vec![],
+            request_middlewares: vec![],
+            async_rules: AsyncRuleChain::default(),
         }
     }
 
+    /// Adds a middleware to the end of the chain.
+    pub fn middleware(mut self, middleware: impl ExecutionMiddleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Adds a middleware to the end of the chain run around the requests.
+    pub fn request_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.request_middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Adds a rule that the operations have to pass before being executed.
+    pub fn async_rule(mut self, rule: impl AsyncRule + 'static) -> Self {
+        self.async_rules = self.async_rules.rule(rule);
+        self
+    }
+
     pub async fn execute(&self, request: impl Into<DynamicRequest>) -> async_graphql::Response {
         self.schema.execute(request).await
     }
 }
+
+/// Holds the [AppContext] that serves the requests and swaps it atomically
+/// when the config is reloaded. The requests in flight finish with the context
+/// they started with.
+#[derive(Clone)]
+pub struct AppContextHandle {
+    current: Arc<RwLock<Arc<AppContext>>>,
+    swaps: Arc<AtomicUsize>,
+}
+
+impl AppContextHandle {
+    pub fn new(app_ctx: Arc<AppContext>) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(app_ctx)),
+            swaps: Default::default(),
+        }
+    }
+
+    /// The context the new requests are served with.
+    pub fn load(&self) -> Arc<AppContext> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Serves the new requests with `app_ctx`. Its plan and execution caches
+    /// start empty, so nothing planned for the previous schema is reused.
+    pub fn swap(&self, app_ctx: Arc<AppContext>) {
+        *self.current.write().unwrap() = app_ctx;
+        self.swaps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of times the context was swapped.
+    pub fn swaps(&self) -> usize {
+        self.swaps.load(Ordering::Relaxed)
+    }
+}
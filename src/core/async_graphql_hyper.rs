@@ -2,8 +2,10 @@ use std::any::Any;
 use std::hash::{Hash, Hasher};
 
 use anyhow::Result;
-use async_graphql::parser::types::{ExecutableDocument, OperationType};
-use async_graphql::{BatchResponse, Executor, Value};
+use async_graphql::parser::types::{DocumentOperations, ExecutableDocument, OperationType};
+use async_graphql::{BatchRequest, BatchResponse, Executor, Value};
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
 use http::header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
 use http::{Response, StatusCode};
 use hyper::Body;
@@ -11,7 +13,7 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use tailcall_hasher::TailcallHasher;
 
-use super::jit::{BatchResponse as JITBatchResponse, JITExecutor};
+use super::jit::{AnyResponse, BatchResponse as JITBatchResponse, JITExecutor};
 
 #[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub struct OperationId(u64);
@@ -25,6 +27,13 @@ pub trait GraphQLRequestLike: Hash + Send {
 
     async fn execute_with_jit(self, executor: JITExecutor) -> GraphQLArcResponse;
 
+    /// Executes the request as a stream of responses, which is required to
+    /// serve subscriptions.
+    fn execute_stream_with_jit(
+        self,
+        executor: JITExecutor,
+    ) -> BoxStream<'static, AnyResponse<Vec<u8>>>;
+
     fn parse_query(&mut self) -> Option<&ExecutableDocument>;
 
     fn is_query(&mut self) -> bool {
@@ -39,6 +48,10 @@ pub trait GraphQLRequestLike: Hash + Send {
             .unwrap_or(false)
     }
 
+    /// The name of the operation to execute, as sent by the client or, when
+    /// it isn't, as found in the document.
+    fn operation_name(&mut self) -> Option<String>;
+
     fn operation_id(&self, headers: &HeaderMap) -> OperationId {
         let mut hasher = TailcallHasher::default();
         let state = &mut hasher;
@@ -51,6 +64,18 @@ pub trait GraphQLRequestLike: Hash + Send {
     }
 }
 
+fn operation_name(request: &mut async_graphql::Request) -> Option<String> {
+    if request.operation_name.is_some() {
+        return request.operation_name.clone();
+    }
+    match &request.parsed_query().ok()?.operations {
+        DocumentOperations::Multiple(operations) if operations.len() == 1 => {
+            operations.keys().next().map(|name| name.to_string())
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GraphQLBatchRequest(pub async_graphql::BatchRequest);
 impl GraphQLBatchRequest {}
@@ -82,6 +107,22 @@ impl GraphQLRequestLike for GraphQLBatchRequest {
         GraphQLArcResponse::new(executor.execute_batch(self.0).await)
     }
 
+    fn execute_stream_with_jit(
+        self,
+        executor: JITExecutor,
+    ) -> BoxStream<'static, AnyResponse<Vec<u8>>> {
+        match self.0 {
+            BatchRequest::Single(request) => executor.execute_stream(request),
+            // every request of the batch is delivered as a single response
+            BatchRequest::Batch(requests) => stream::iter(requests)
+                .then(move |request| {
+                    let executor = executor.clone();
+                    async move { executor.execute(request).await }
+                })
+                .boxed(),
+        }
+    }
+
     /// Shortcut method to execute the request on the executor.
     async fn execute<E>(self, executor: &E) -> GraphQLResponse
     where
@@ -93,6 +134,16 @@ impl GraphQLRequestLike for GraphQLBatchRequest {
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         None
     }
+
+    /// The names of the named operations of the batch, comma separated.
+    fn operation_name(&mut self) -> Option<String> {
+        let names = self
+            .0
+            .iter_mut()
+            .filter_map(operation_name)
+            .collect::<Vec<_>>();
+        (!names.is_empty()).then(|| names.join(","))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -121,6 +172,13 @@ impl GraphQLRequestLike for GraphQLRequest {
         GraphQLArcResponse::new(JITBatchResponse::Single(response))
     }
 
+    fn execute_stream_with_jit(
+        self,
+        executor: JITExecutor,
+    ) -> BoxStream<'static, AnyResponse<Vec<u8>>> {
+        executor.execute_stream(self.0)
+    }
+
     /// Shortcut method to execute the request on the schema.
     async fn execute<E>(self, executor: &E) -> GraphQLResponse
     where
@@ -132,6 +190,10 @@ impl GraphQLRequestLike for GraphQLRequest {
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         self.0.parsed_query().ok()
     }
+
+    fn operation_name(&mut self) -> Option<String> {
+        operation_name(&mut self.0)
+    }
 }
 
 // TODO: drop this type since we can use jit::response?
@@ -349,6 +411,11 @@ impl GraphQLArcResponse {
         Self { response, cache_control: None }
     }
 
+    /// Whether none of the responses have errors.
+    pub fn is_ok(&self) -> bool {
+        self.response.is_ok()
+    }
+
     pub fn set_cache_control(self, enable_cache_header: bool, max_age: i32, public: bool) -> Self {
         Self {
             response: self.response,
@@ -1,19 +1,26 @@
 use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_graphql::dynamic::{Schema, SchemaBuilder};
 use async_graphql::extensions::ApolloTracing;
+use async_graphql::parser::types::DirectiveLocation;
 use async_graphql::ValidationMode;
 use derive_setters::Setters;
 
 use super::directive::Directive;
+use super::introspection::add_directives;
 use super::telemetry::Telemetry;
 use super::{GlobalTimeout, Index};
 use crate::core::blueprint::{Server, Upstream};
+use crate::core::http::MockTable;
 use crate::core::ir::model::IR;
 use crate::core::schema_extension::SchemaExtension;
 use crate::core::{scalar, Type};
 
+/// The introspection query used by GraphQL tooling to read the full schema.
+const INTROSPECTION_QUERY: &str = include_str!("introspection.graphql");
+
 /// Blueprint is an intermediary representation that allows us to generate
 /// graphQL APIs. It can only be generated from a valid Config.
 /// It allows us to choose a different GraphQL Backend, without re-writing all
@@ -25,6 +32,10 @@ pub struct Blueprint {
     pub server: Server,
     pub upstream: Upstream,
     pub telemetry: Telemetry,
+    /// Mocked upstream responses served instead of the real upstreams.
+    pub mocks: Option<MockTable>,
+    /// Directives declared in the schema, listed by the introspection.
+    pub directives: Vec<DirectiveDefinition>,
 }
 
 #[derive(Clone, Debug)]
@@ -108,15 +119,26 @@ pub struct EnumValueDefinition {
 pub struct SchemaDefinition {
     pub query: String,
     pub mutation: Option<String>,
+    pub subscription: Option<String>,
     pub directives: Vec<Directive>,
 }
 
+#[derive(Clone, Debug)]
+pub struct DirectiveDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    pub args: Vec<InputFieldDefinition>,
+    pub locations: Vec<DirectiveLocation>,
+    pub repeatable: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct InputFieldDefinition {
     pub name: String,
     pub of_type: Type,
     pub default_value: Option<serde_json::Value>,
     pub description: Option<String>,
+    pub directives: Vec<Directive>,
 }
 
 #[derive(Clone, Debug, Setters, Default)]
@@ -128,6 +150,21 @@ pub struct FieldDefinition {
     pub directives: Vec<Directive>,
     pub description: Option<String>,
     pub default_value: Option<serde_json::Value>,
+    pub poll: Option<Poll>,
+    /// Drop the field from the response, instead of setting it to `null`,
+    /// when it doesn't resolve to a value.
+    pub omit_if_null: bool,
+    /// The key the field is read from when the parent value has none named
+    /// after the field, see `@server(upstreamKeyCase:)`.
+    pub fallback_key: Option<String>,
+}
+
+/// Settings of a subscription field that is resolved by polling its resolver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Poll {
+    pub interval: Duration,
+    /// Emit an event after every poll instead of only when the result changes.
+    pub emit_unchanged: bool,
 }
 
 impl FieldDefinition {
@@ -156,6 +193,21 @@ pub struct UnionTypeDefinition {
     pub types: BTreeSet<String>,
 }
 
+/// The size of a [Blueprint], see [Blueprint::metrics].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlueprintMetrics {
+    /// Every type of the schema, including the unions and enums.
+    pub type_count: usize,
+    /// Fields of the object, interface and input types.
+    pub field_count: usize,
+    /// Fields with a resolver, eg. `@http`.
+    pub resolver_count: usize,
+    pub union_count: usize,
+    pub enum_count: usize,
+    /// Directives on the schema, types, fields and enum values.
+    pub directive_count: usize,
+}
+
 ///
 /// Controls the kind of blueprint that is generated.
 #[derive(Clone, Default, Setters)]
@@ -242,7 +294,254 @@ impl Blueprint {
         schema.finish().unwrap()
     }
 
+    /// Runs the introspection query against the schema of the blueprint,
+    /// without its resolvers, and returns the result in the
+    /// `{"data": {"__schema": {...}}}` format that tooling expects.
+    pub async fn to_introspection_json(&self) -> serde_json::Value {
+        let blueprint = self.clone().drop_resolvers();
+
+        // We should safely assume the blueprint is correct and,
+        // generation of schema cannot fail.
+        let schema = SchemaBuilder::from(&blueprint).finish().unwrap();
+        let mut response = schema.execute(INTROSPECTION_QUERY).await;
+        add_directives(&mut response.data, INTROSPECTION_QUERY, None, &blueprint);
+
+        serde_json::to_value(response).unwrap_or_default()
+    }
+
     pub fn index(&self) -> Index {
         Index::from(self)
     }
+
+    /// Counts the types, fields and directives of the schema.
+    pub fn metrics(&self) -> BlueprintMetrics {
+        let mut metrics = BlueprintMetrics {
+            type_count: self.definitions.len(),
+            directive_count: self.schema.directives.len(),
+            ..Default::default()
+        };
+
+        for def in self.definitions.iter() {
+            metrics.directive_count += def.directives().len();
+            let fields = match def {
+                Definition::Object(def) => def.fields.as_slice(),
+                Definition::Interface(def) => def.fields.as_slice(),
+                Definition::InputObject(def) => {
+                    metrics.field_count += def.fields.len();
+                    &[]
+                }
+                Definition::Union(_) => {
+                    metrics.union_count += 1;
+                    &[]
+                }
+                Definition::Enum(def) => {
+                    metrics.enum_count += 1;
+                    metrics.directive_count += def
+                        .enum_values
+                        .iter()
+                        .map(|value| value.directives.len())
+                        .sum::<usize>();
+                    &[]
+                }
+                Definition::Scalar(_) => &[],
+            };
+
+            for field in fields {
+                metrics.field_count += 1;
+                metrics.directive_count += field.directives.len();
+                if field.resolver.is_some() {
+                    metrics.resolver_count += 1;
+                }
+            }
+        }
+
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::Validator;
+
+    use super::*;
+    use crate::core::config::{Config, ConfigModule};
+
+    #[tokio::test]
+    async fn test_to_introspection_json() {
+        let config = Config::from_sdl(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { user(id: Int!): User @http(url: "http://localhost/users/{{.args.id}}") }
+            type User { id: Int! name: String }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+
+        let json = blueprint.to_introspection_json().await;
+        let schema = &json["data"]["__schema"];
+
+        assert!(json.get("errors").is_none());
+        assert_eq!(schema["queryType"]["name"], "Query");
+        assert!(schema["mutationType"].is_null());
+
+        let user = schema["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|ty| ty["name"] == "User")
+            .unwrap();
+        assert_eq!(user["kind"], "OBJECT");
+        assert_eq!(user["fields"][0]["name"], "id");
+        assert_eq!(user["fields"][0]["type"]["kind"], "NON_NULL");
+        assert_eq!(user["fields"][0]["type"]["ofType"]["name"], "Int");
+    }
+
+    #[tokio::test]
+    async fn test_to_introspection_json_custom_directives() {
+        let config = Config::from_sdl(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { user: User @http(url: "http://localhost/user") }
+            type User { id: Int! name: String }
+            "Logs the field"
+            directive @log(level: Int! = 1) repeatable on FIELD | FRAGMENT_SPREAD
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+
+        let json = blueprint.to_introspection_json().await;
+        let directives = json["data"]["__schema"]["directives"].as_array().unwrap();
+        let log = directives
+            .iter()
+            .find(|directive| directive["name"] == "log")
+            .unwrap();
+
+        assert!(directives
+            .iter()
+            .any(|directive| directive["name"] == "skip"));
+        assert_eq!(
+            log,
+            &serde_json::json!({
+                "name": "log",
+                "description": "Logs the field",
+                "locations": ["FIELD", "FRAGMENT_SPREAD"],
+                "args": [{
+                    "name": "level",
+                    "description": null,
+                    "type": {
+                        "kind": "NON_NULL",
+                        "name": null,
+                        "ofType": {"kind": "SCALAR", "name": "Int", "ofType": null}
+                    },
+                    "defaultValue": "1"
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let config = Config::from_sdl(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query {
+              user: User @http(url: "http://localhost/user")
+              search: [SearchResult] @expr(body: [])
+              legacy: String @expr(body: "old") @deprecated(reason: "Use user")
+            }
+            union SearchResult = User | Post
+            type User { id: Int name: String status: Status }
+            type Post { title: String }
+            enum Status { ACTIVE LEGACY @deprecated(reason: "Gone") }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+
+        assert_eq!(
+            blueprint.metrics(),
+            BlueprintMetrics {
+                type_count: 5,
+                field_count: 7,
+                resolver_count: 3,
+                union_count: 1,
+                enum_count: 1,
+                directive_count: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_introspection() {
+        let config = Config::from_sdl(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query {
+              user: User @expr(body: {id: 1, status: "ACTIVE"})
+              legacyUser: User @expr(body: {id: 1}) @deprecated(reason: "Use user")
+            }
+            type User { id: Int login: String @deprecated status: Status }
+            enum Status { ACTIVE LEGACY @deprecated(reason: "Gone") }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let schema = SchemaBuilder::from(&blueprint).finish().unwrap();
+
+        let query = |include_deprecated: bool| {
+            format!(
+                r#"{{
+                  query: __type(name: "Query") {{
+                    fields(includeDeprecated: {include_deprecated}) {{
+                      name isDeprecated deprecationReason
+                    }}
+                  }}
+                  user: __type(name: "User") {{
+                    fields(includeDeprecated: {include_deprecated}) {{ name }}
+                  }}
+                  status: __type(name: "Status") {{
+                    enumValues(includeDeprecated: {include_deprecated}) {{
+                      name isDeprecated deprecationReason
+                    }}
+                  }}
+                }}"#
+            )
+        };
+
+        let response = schema.execute(query(false)).await.into_result().unwrap();
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({
+                "query": {"fields": [
+                    {"name": "user", "isDeprecated": false, "deprecationReason": null},
+                ]},
+                "user": {"fields": [{"name": "id"}, {"name": "status"}]},
+                "status": {"enumValues": [
+                    {"name": "ACTIVE", "isDeprecated": false, "deprecationReason": null},
+                ]},
+            })
+        );
+
+        let response = schema.execute(query(true)).await.into_result().unwrap();
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({
+                "query": {"fields": [
+                    {"name": "legacyUser", "isDeprecated": true, "deprecationReason": "Use user"},
+                    {"name": "user", "isDeprecated": false, "deprecationReason": null},
+                ]},
+                "user": {"fields": [{"name": "id"}, {"name": "login"}, {"name": "status"}]},
+                "status": {"enumValues": [
+                    {"name": "ACTIVE", "isDeprecated": false, "deprecationReason": null},
+                    {"name": "LEGACY", "isDeprecated": true, "deprecationReason": "Gone"},
+                ]},
+            })
+        );
+    }
 }
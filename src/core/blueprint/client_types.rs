@@ -0,0 +1,369 @@
+//! Generates the TypeScript or Flow types of a schema for its clients, see
+//! `tailcall gen types`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+use super::{Blueprint, Definition, FieldDefinition, InputFieldDefinition};
+use crate::core::{scalar, Type};
+
+/// The scalars written as their type instead of an alias.
+const BUILT_IN_SCALARS: &[&str] = &["Boolean", "Float", "ID", "Int", "String"];
+
+/// The language the types are written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TypeLang {
+    #[default]
+    TypeScript,
+    Flow,
+}
+
+impl TypeLang {
+    /// The type of the values that can be anything.
+    fn unknown(self) -> &'static str {
+        match self {
+            TypeLang::TypeScript => "unknown",
+            TypeLang::Flow => "mixed",
+        }
+    }
+}
+
+impl FromStr for TypeLang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ts" | "typescript" => Ok(TypeLang::TypeScript),
+            "flow" => Ok(TypeLang::Flow),
+            _ => Err(format!("unknown language `{s}`, use `ts` or `flow`")),
+        }
+    }
+}
+
+/// Writes an interface for each object, interface and input type, a union of
+/// string literals for each enum and a union discriminated by `__typename`
+/// for each union, followed by the arguments of the root fields. The types
+/// are sorted by name, so the output only changes when the schema does.
+#[derive(Clone, Debug, Default)]
+pub struct ClientTypes {
+    lang: TypeLang,
+    /// Types of the scalars set by the user, by the name of the scalar.
+    scalars: BTreeMap<String, String>,
+}
+
+impl ClientTypes {
+    pub fn new(lang: TypeLang) -> Self {
+        Self { lang, scalars: BTreeMap::new() }
+    }
+
+    /// Writes the scalar as `type_of` instead of its default type, eg.
+    /// `DateTime` as `Date`.
+    pub fn scalar(mut self, name: impl Into<String>, type_of: impl Into<String>) -> Self {
+        self.scalars.insert(name.into(), type_of.into());
+        self
+    }
+
+    pub fn generate(&self, blueprint: &Blueprint) -> String {
+        let mut definitions = blueprint.definitions.iter().collect::<Vec<_>>();
+        definitions.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let mut out = String::new();
+        if self.lang == TypeLang::Flow {
+            out.push_str("// @flow\n");
+        }
+        out.push_str("// Generated by `tailcall gen types`, don't edit it by hand.\n");
+
+        for (name, type_of) in self.scalar_aliases(&definitions) {
+            let _ = write!(out, "\nexport type {name} = {type_of};\n");
+        }
+
+        for definition in definitions {
+            match definition {
+                Definition::Object(def) => {
+                    let typename = format!("  __typename?: \"{}\";\n", def.name);
+                    let fields = typename + &self.output_fields(&def.fields);
+                    self.object(&mut out, &def.name, def.description.as_deref(), &fields);
+                }
+                Definition::Interface(def) => {
+                    let fields = self.output_fields(&def.fields);
+                    self.object(&mut out, &def.name, def.description.as_deref(), &fields);
+                }
+                Definition::InputObject(def) => {
+                    let fields = self.input_fields(&def.fields);
+                    self.object(&mut out, &def.name, def.description.as_deref(), &fields);
+                }
+                Definition::Enum(def) => {
+                    let values = def
+                        .enum_values
+                        .iter()
+                        .map(|value| format!("\"{}\"", value.name))
+                        .collect::<Vec<_>>();
+                    let values = join_union(values);
+                    out.push('\n');
+                    write_doc(&mut out, def.description.as_deref(), "");
+                    let _ = writeln!(out, "export type {} = {values};", def.name);
+                }
+                Definition::Union(def) => {
+                    let members = def
+                        .types
+                        .iter()
+                        .map(|name| format!("({{ __typename: \"{name}\" }} & {name})"))
+                        .collect::<Vec<_>>();
+                    let members = join_union(members);
+                    out.push('\n');
+                    write_doc(&mut out, def.description.as_deref(), "");
+                    let _ = writeln!(out, "export type {} = {members};", def.name);
+                }
+                Definition::Scalar(_) => {}
+            }
+        }
+
+        let roots = [
+            Some(&blueprint.schema.query),
+            blueprint.schema.mutation.as_ref(),
+            blueprint.schema.subscription.as_ref(),
+        ];
+        for root in roots.into_iter().flatten() {
+            let fields = blueprint.definitions.iter().find_map(|def| match def {
+                Definition::Object(def) if &def.name == root => Some(&def.fields),
+                _ => None,
+            });
+            for field in fields.into_iter().flatten() {
+                if field.args.is_empty() {
+                    continue;
+                }
+                let name = format!("{root}{}Args", pascal_case(&field.name));
+                let fields = self.input_fields(&field.args);
+                self.object(&mut out, &name, None, &fields);
+            }
+        }
+
+        out
+    }
+
+    /// The aliases of the scalars other than the built-in ones, eg.
+    /// `export type DateTime = string;`.
+    fn scalar_aliases(&self, definitions: &[&Definition]) -> BTreeMap<String, String> {
+        let mut names = BTreeSet::new();
+        for definition in definitions {
+            let fields = match definition {
+                Definition::Object(def) => def.fields.as_slice(),
+                Definition::Interface(def) => def.fields.as_slice(),
+                Definition::InputObject(def) => {
+                    names.extend(def.fields.iter().map(|field| field.of_type.name()));
+                    continue;
+                }
+                Definition::Scalar(def) => {
+                    names.insert(&def.name);
+                    continue;
+                }
+                _ => continue,
+            };
+            for field in fields {
+                names.insert(field.of_type.name());
+                names.extend(field.args.iter().map(|arg| arg.of_type.name()));
+            }
+        }
+
+        names
+            .into_iter()
+            .filter(|name| {
+                scalar::Scalar::find(name).is_some()
+                    || definitions
+                        .iter()
+                        .any(|def| matches!(def, Definition::Scalar(def) if &def.name == *name))
+            })
+            .map(|name| (name.clone(), self.scalar_type(name)))
+            .collect()
+    }
+
+    /// The type a scalar is written as, the one set by the user or else a
+    /// `string` or a `number` after the values it accepts.
+    fn scalar_type(&self, name: &str) -> String {
+        if let Some(type_of) = self.scalars.get(name) {
+            return type_of.clone();
+        }
+        let type_of = match name {
+            "Int" | "Float" => "number",
+            "String" | "ID" => "string",
+            "Boolean" => "boolean",
+            _ => match scalar::Scalar::find(name) {
+                Some(
+                    scalar::Scalar::Int8
+                    | scalar::Scalar::Int16
+                    | scalar::Scalar::Int32
+                    | scalar::Scalar::UInt8
+                    | scalar::Scalar::UInt16
+                    | scalar::Scalar::UInt32,
+                ) => "number",
+                // the 64 and 128 bit integers are sent as strings
+                Some(
+                    scalar::Scalar::Email
+                    | scalar::Scalar::PhoneNumber
+                    | scalar::Scalar::Date
+                    | scalar::Scalar::DateTime
+                    | scalar::Scalar::Url
+                    | scalar::Scalar::Bytes
+                    | scalar::Scalar::Int64
+                    | scalar::Scalar::UInt64
+                    | scalar::Scalar::Int128
+                    | scalar::Scalar::UInt128,
+                ) => "string",
+                _ => self.lang.unknown(),
+            },
+        };
+        type_of.to_string()
+    }
+
+    fn type_of(&self, type_of: &Type) -> String {
+        let inner = match type_of {
+            Type::Named { name, .. } if BUILT_IN_SCALARS.contains(&name.as_str()) => {
+                self.scalar_type(name)
+            }
+            Type::Named { name, .. } => name.clone(),
+            Type::List { of_type, .. } => format!("Array<{}>", self.type_of(of_type)),
+        };
+        if type_of.is_nullable() {
+            format!("{inner} | null")
+        } else {
+            inner
+        }
+    }
+
+    fn output_fields(&self, fields: &[FieldDefinition]) -> String {
+        let mut out = String::new();
+        for field in fields {
+            write_doc(&mut out, field.description.as_deref(), "  ");
+            let _ = writeln!(out, "  {}: {};", field.name, self.type_of(&field.of_type));
+        }
+        out
+    }
+
+    /// The fields of an input, which can be left out when they are nullable
+    /// or have a default value.
+    fn input_fields(&self, fields: &[InputFieldDefinition]) -> String {
+        let mut out = String::new();
+        for field in fields {
+            let optional = field.of_type.is_nullable() || field.default_value.is_some();
+            let optional = if optional { "?" } else { "" };
+            write_doc(&mut out, field.description.as_deref(), "  ");
+            let _ = writeln!(
+                out,
+                "  {}{optional}: {};",
+                field.name,
+                self.type_of(&field.of_type)
+            );
+        }
+        out
+    }
+
+    fn object(&self, out: &mut String, name: &str, description: Option<&str>, fields: &str) {
+        out.push('\n');
+        write_doc(out, description, "");
+        let _ = match self.lang {
+            TypeLang::TypeScript => write!(out, "export interface {name} {{\n{fields}}}\n"),
+            TypeLang::Flow => write!(out, "export type {name} = {{\n{fields}}};\n"),
+        };
+    }
+}
+
+/// A union of the types, `never` when there are none.
+fn join_union(types: Vec<String>) -> String {
+    if types.is_empty() {
+        "never".to_string()
+    } else {
+        types.join(" | ")
+    }
+}
+
+fn write_doc(out: &mut String, description: Option<&str>, indent: &str) {
+    let Some(description) = description.map(str::trim).filter(|d| !d.is_empty()) else {
+        return;
+    };
+    let description = description.replace("*/", "*\\/");
+    if description.contains('\n') {
+        let _ = writeln!(out, "{indent}/**");
+        for line in description.lines() {
+            let _ = writeln!(out, "{indent} * {line}");
+        }
+        let _ = writeln!(out, "{indent} */");
+    } else {
+        let _ = writeln!(out, "{indent}/** {description} */");
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::Validator;
+
+    use super::{ClientTypes, TypeLang};
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+
+    fn blueprint(sdl: &str) -> Blueprint {
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        Blueprint::try_from(&ConfigModule::from(config)).unwrap()
+    }
+
+    const SDL: &str = r#"
+        schema { query: Query mutation: Mutation }
+        scalar Cursor
+        enum Role { ADMIN USER }
+        union Result = User | Post
+        type User { id: ID! role: Role createdAt: DateTime }
+        type Post { id: ID! cursor: Cursor }
+        input UserInput { name: String! role: Role = USER tags: [String!] }
+        type Query { search(term: String!): [Result!]! @http(url: "http://api.test/search") }
+        type Mutation { createUser(input: UserInput!): User @http(url: "http://api.test/users", method: POST) }
+    "#;
+
+    #[test]
+    fn test_jsonplaceholder() {
+        let sdl = std::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER).unwrap();
+        let types = ClientTypes::new(TypeLang::TypeScript).generate(&blueprint(&sdl));
+        insta::assert_snapshot!(types);
+    }
+
+    #[test]
+    fn test_typescript() {
+        let types = ClientTypes::new(TypeLang::TypeScript)
+            .scalar("DateTime", "Date")
+            .generate(&blueprint(SDL));
+
+        assert!(types.contains("export type Cursor = unknown;"));
+        assert!(types.contains("export type DateTime = Date;"));
+        assert!(types.contains("export type Role = \"ADMIN\" | \"USER\";"));
+        assert!(types.contains(
+            "export type Result = ({ __typename: \"Post\" } & Post) | ({ __typename: \"User\" } & User);"
+        ));
+        assert!(types.contains(
+            "export interface User {\n  __typename?: \"User\";\n  createdAt: DateTime | null;\n  id: string;\n  role: Role | null;\n}"
+        ));
+        assert!(types.contains(
+            "export interface UserInput {\n  name: string;\n  role?: Role | null;\n  tags?: Array<string> | null;\n}"
+        ));
+        assert!(types.contains("  search: Array<Result>;"));
+        assert!(types.contains("export interface QuerySearchArgs {\n  term: string;\n}"));
+        assert!(types.contains("export interface MutationCreateUserArgs {\n  input: UserInput;\n}"));
+    }
+
+    #[test]
+    fn test_flow() {
+        let types = ClientTypes::new(TypeLang::Flow).generate(&blueprint(SDL));
+
+        assert!(types.starts_with("// @flow\n"));
+        assert!(types.contains("export type Cursor = mixed;"));
+        assert!(types.contains("export type DateTime = string;"));
+        assert!(types.contains("export type QuerySearchArgs = {\n  term: string;\n};"));
+        assert!(!types.contains("export interface"));
+    }
+}
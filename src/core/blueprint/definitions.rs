@@ -51,6 +51,7 @@ pub fn to_input_object_type_definition(
                 description: field.description.clone(),
                 default_value: field.default_value.clone(),
                 of_type: field.of_type.clone(),
+                directives: field.directives.clone(),
             })
             .collect(),
         description: definition.description,
@@ -252,7 +253,7 @@ fn to_enum_type_definition((name, eu): (&String, &Enum)) -> Definition {
             .map(|variant| EnumValueDefinition {
                 description: None,
                 name: variant.name.clone(),
-                directives: vec![],
+                directives: variant.deprecated.iter().map(Directive::from).collect(),
                 alias: variant.alias.clone().unwrap_or_default().options,
             })
             .collect(),
@@ -275,6 +276,28 @@ fn to_object_type_definition(
     })
 }
 
+/// Fails when an output field is an `Upload`, which is only sent by clients.
+fn update_upload<'a>(
+    object_name: &'a str,
+) -> TryFold<
+    'a,
+    (&'a ConfigModule, &'a Field, &'a config::Type, &'a str),
+    FieldDefinition,
+    BlueprintError,
+> {
+    TryFold::<(&ConfigModule, &Field, &config::Type, &str), FieldDefinition, BlueprintError>::new(
+        move |(config_module, field, _, _), b_field| {
+            let is_output = config_module.output_types().contains(object_name)
+                || config_module.schema.subscription.as_deref() == Some(object_name);
+            if is_output && *field.type_of.name() == scalar::Scalar::Upload.name() {
+                Valid::fail(BlueprintError::UploadInOutputPosition)
+            } else {
+                Valid::succeed(b_field)
+            }
+        },
+    )
+}
+
 fn update_args<'a>() -> TryFold<
     'a,
     (&'a ConfigModule, &'a Field, &'a config::Type, &'a str),
@@ -285,21 +308,22 @@ fn update_args<'a>() -> TryFold<
         move |(_, field, _typ, name), _| {
             // TODO: assert type name
             Valid::from_iter(field.args.iter(), |(name, arg)| {
-                Valid::succeed(InputFieldDefinition {
-                    name: name.clone(),
-                    description: arg.doc.clone(),
-                    of_type: arg.type_of.clone(),
-                    default_value: arg.default_value.clone(),
-                })
+                Valid::succeed(to_input_field_definition(name, arg))
             })
             .map(|args| FieldDefinition {
                 name: name.to_string(),
                 description: field.doc.clone(),
                 args,
                 of_type: field.type_of.clone(),
-                directives: to_directives(&field.directives),
+                directives: to_directives(&field.directives)
+                    .into_iter()
+                    .chain(field.deprecated.iter().map(Directive::from))
+                    .collect(),
                 resolver: None,
                 default_value: field.default_value.clone(),
+                poll: None,
+                omit_if_null: false,
+                fallback_key: None,
             })
         },
     )
@@ -362,6 +386,29 @@ pub fn fix_dangling_resolvers<'a>() -> TryFold<
     )
 }
 
+/// Sets the key the field is read from when the parent value has no key
+/// named after it, see `@server(upstreamKeyCase:)`. The fields with a resolver,
+/// eg. renamed with `@modify(name:)`, don't read the parent value by name.
+fn update_key_case<'a>() -> TryFold<
+    'a,
+    (&'a ConfigModule, &'a Field, &'a config::Type, &'a str),
+    FieldDefinition,
+    BlueprintError,
+> {
+    TryFold::<(&ConfigModule, &Field, &config::Type, &str), FieldDefinition, BlueprintError>::new(
+        move |(config, _, _, _), b_field| {
+            let fallback_key = match (config.server.upstream_key_case, &b_field.resolver) {
+                (Some(key_case), None) => {
+                    Some(key_case.key(&b_field.name)).filter(|key| *key != b_field.name)
+                }
+                _ => None,
+            };
+
+            Valid::succeed(b_field.fallback_key(fallback_key))
+        },
+    )
+}
+
 /// Wraps the IO Expression with Expression::Cached
 /// if `Field::cache` is present for that field
 pub fn update_cache_resolvers<'a>() -> TryFold<
@@ -544,8 +591,12 @@ pub fn to_field_definition(
 ) -> Valid<FieldDefinition, BlueprintError> {
     update_args()
         .and(update_resolver(operation_type, object_name))
+        .and(update_poll(object_name))
+        .and(update_auth())
+        .and(update_upload(object_name))
         .and(update_modify().trace(config::Modify::trace_name().as_str()))
         .and(fix_dangling_resolvers())
+        .and(update_key_case())
         .and(update_cache_resolvers())
         .and(update_protected(object_name).trace(Protected::trace_name().as_str()))
         .and(update_enum_alias())
@@ -601,6 +652,39 @@ pub fn to_definitions<'a>() -> TryFold<'a, ConfigModule, Vec<Definition>, Bluepr
     })
 }
 
+fn to_input_field_definition(name: &str, arg: &config::Arg) -> InputFieldDefinition {
+    InputFieldDefinition {
+        name: name.to_string(),
+        description: arg.doc.clone(),
+        of_type: arg.type_of.clone(),
+        default_value: arg.default_value.clone(),
+        directives: arg.deprecated.iter().map(Directive::from).collect(),
+    }
+}
+
+pub fn to_directive_definitions<'a>(
+) -> TryFold<'a, ConfigModule, Vec<DirectiveDefinition>, BlueprintError> {
+    TryFold::<ConfigModule, Vec<DirectiveDefinition>, BlueprintError>::new(|config_module, _| {
+        Valid::succeed(
+            config_module
+                .directives
+                .iter()
+                .map(|directive| DirectiveDefinition {
+                    name: directive.name.clone(),
+                    description: directive.doc.clone(),
+                    args: directive
+                        .args
+                        .iter()
+                        .map(|(name, arg)| to_input_field_definition(name, arg))
+                        .collect(),
+                    locations: directive.locations.clone(),
+                    repeatable: directive.repeatable,
+                })
+                .collect(),
+        )
+    })
+}
+
 fn to_directives(directives: &[config::Directive]) -> Vec<Directive> {
     directives.iter().cloned().map(Directive::from).collect()
 }
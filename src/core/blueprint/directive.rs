@@ -43,6 +43,34 @@ pub fn to_const_directive(directive: &Directive) -> Valid<ConstDirective, String
     .map(|arguments| ConstDirective { name: pos(Name::new(&directive.name)), arguments })
 }
 
+impl From<&config::Deprecated> for Directive {
+    fn from(value: &config::Deprecated) -> Self {
+        Self {
+            name: config::Deprecated::NAME.to_string(),
+            arguments: value
+                .reason
+                .iter()
+                .map(|reason| ("reason".to_string(), Value::String(reason.clone())))
+                .collect(),
+        }
+    }
+}
+
+/// Reads the `@deprecated` directive back from the directives of a field or
+/// an enum value.
+pub fn to_deprecated(directives: &[Directive]) -> Option<config::Deprecated> {
+    directives
+        .iter()
+        .find(|directive| directive.name == config::Deprecated::NAME)
+        .map(|directive| config::Deprecated {
+            reason: directive
+                .arguments
+                .get("reason")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+}
+
 impl From<config::Directive> for Directive {
     fn from(value: config::Directive) -> Self {
         Self {
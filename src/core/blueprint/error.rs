@@ -58,15 +58,33 @@ pub enum BlueprintError {
     #[error("batchKey requires either body or query parameters")]
     BatchKeyRequiresEitherBodyOrQuery,
 
+    #[error("batchKey and batchKeys can't be used together")]
+    BatchKeyAndBatchKeys,
+
+    #[error("batchKeys can only be used with GET requests")]
+    BatchKeysRequireGet,
+
+    #[error("batchKeys requires a query parameter that references `{{{{.value}}}}` for each key")]
+    BatchKeysRequireQueryKeys,
+
+    #[error("compress can't be used with batchKey or batchKeys, the batched body is rebuilt uncompressed")]
+    CompressWithBatching,
+
     #[error("script is required")]
     ScriptIsRequired,
 
     #[error("Field is already implemented from interface")]
     FieldExistsInInterface,
 
+    #[error("omitIfNull can only be used on nullable fields")]
+    OmitIfNullOnNonNullField,
+
     #[error("Input types can not be protected")]
     InputTypesCannotBeProtected,
 
+    #[error("Upload can only be used in input positions")]
+    UploadInOutputPosition,
+
     #[error("@protected operator is used but there is no @link definitions for auth providers")]
     ProtectedOperatorNoAuthProviders,
 
@@ -100,6 +118,9 @@ pub enum BlueprintError {
     #[error("Link src cannot be empty")]
     LinkSrcCannotBeEmpty,
 
+    #[error("Only the links to local files can use glob patterns")]
+    RemoteLinkGlob,
+
     #[error("Duplicated id: {0}")]
     Duplicated(String),
 
@@ -148,6 +169,36 @@ pub enum BlueprintError {
     #[error("Mutation type is not defined")]
     MutationTypeNotDefined,
 
+    #[error("Subscription type is not defined")]
+    SubscriptionTypeNotDefined,
+
+    #[error("pollIntervalMs can only be used on the fields of the Subscription type")]
+    PollOutsideSubscription,
+
+    #[error("pollIntervalMs must be greater than 0")]
+    InvalidPollInterval,
+
+    #[error("Subscription fields must be resolved by an @http with pollIntervalMs")]
+    SubscriptionFieldWithoutPoll,
+
+    #[error("paginate can only be used on list fields")]
+    PaginateRequiresList,
+
+    #[error("paginate can't be used with batchKey or batchKeys")]
+    PaginateWithBatching,
+
+    #[error("paginate with CURSOR mode requires cursorPath")]
+    PaginateRequiresCursorPath,
+
+    #[error("maxPages must be greater than 0")]
+    InvalidMaxPages,
+
+    #[error("mapResponseBody is not a valid JSONPath: {0}")]
+    InvalidMapResponseBody(String),
+
+    #[error("mapResponseBody can't be used with paginate, batchKey or batchKeys")]
+    MapResponseBodyWithBatching,
+
     #[error("Certificate is required for HTTP2")]
     CertificateIsRequiredForHTTP2,
 
@@ -160,9 +211,104 @@ pub enum BlueprintError {
     #[error("`graph_ref` should be in the format <graph_id>@<variant> where `graph_id` and `variant` can only contain letters, numbers, '-' and '_'. Found {0}")]
     InvalidGraphRef(String),
 
+    #[error("Only the error status codes can be mapped, found {0}")]
+    StatusCodeNotError(String),
+
+    #[error("The status {0} can only be mapped with a `when`")]
+    StatusCodeWithoutWhen(String),
+
+    #[error("Only the requests of the idempotent methods can be retried, found {0}")]
+    RetryNotIdempotent(String),
+
+    #[error("The status {0} is mapped more than once")]
+    StatusCodeMappedTwice(String),
+
+    #[error("The environment variable `{0}` is not set")]
+    EnvVarNotSet(String),
+
+    #[error("@auth can only be used on the fields resolved with @http")]
+    AuthWithoutHttp,
+
+    #[error("Invalid log filter: {0}")]
+    InvalidLogFilter(String),
+
     #[error("Invalid CORS configuration: Cannot combine `Access-Control-Allow-Credentials: true` with `{0}: *`")]
     InvalidCORSConfiguration(String),
 
+    #[error("Playground path must start with '/'. Found '{0}'")]
+    InvalidPlaygroundPath(String),
+
+    #[error("Playground path '{0}' conflicts with another route of the server")]
+    PlaygroundPathConflict(String),
+
+    #[error(
+        "listen must be a unix socket address like `unix:///var/run/tailcall.sock`. Found '{0}'"
+    )]
+    InvalidListenAddress(String),
+
+    #[error("Unix domain sockets are not supported on this platform")]
+    UnixSocketNotSupported,
+
+    #[error("Abstract unix sockets are only supported on Linux")]
+    AbstractSocketNotSupported,
+
+    #[error("Unix domain sockets can only be served over HTTP1")]
+    UnixSocketRequiresHTTP1,
+
+    #[error("socketMode requires `listen` to be a unix socket file")]
+    SocketModeWithoutUnixSocket,
+
+    #[error("socketMode must be an octal permission like `0660`. Found '{0}'")]
+    InvalidSocketMode(String),
+
+    #[error("The admin token can't be empty")]
+    EmptyAdminToken,
+
+    #[error("The admin token `{0}` isn't resolved, set the variables it reads")]
+    UnresolvedAdminToken(String),
+
+    #[error("The admin token must be at least {0} characters long")]
+    AdminTokenTooShort(usize),
+
+    #[error("The admin port {0} is already used by the server")]
+    AdminPortConflict(u16),
+
+    #[error("The tenant id can't be empty")]
+    EmptyTenantId,
+
+    #[error("The tenant id can't use {{{{.tenant.id}}}}")]
+    RecursiveTenantId,
+
+    #[error("The tenant needs allowedHosts for its upstreams")]
+    TenantWithoutAllowedHosts,
+
+    #[error("tenant.id can only be used in the url of @http")]
+    TenantOutsideUrl,
+
+    #[error("tenant.{0} is not available, set @server(tenant:) to use tenant.id")]
+    TenantNotAvailable(String),
+
+    #[error("The context key can't be empty")]
+    EmptyContextKey,
+
+    #[error("The context key `{0}` is already used")]
+    DuplicateContextKey(String),
+
+    #[error("Set either `value` or `http` for the context value")]
+    ContextEntryWithoutSource,
+
+    #[error("ctx.{0} is not available, add it to @server(context:) before it's used")]
+    ContextKeyNotAvailable(String),
+
+    #[error("`http2Only` conflicts with `httpVersion`, set only one of them")]
+    ConflictingUpstreamHttpVersion,
+
+    #[error("{0} must be greater than 0")]
+    InvalidUpstreamDuration(String),
+
+    #[error("DNS override of {0} is not an IP address")]
+    InvalidDnsOverride(String),
+
     #[error("{0}")]
     Cause(String),
 
@@ -181,6 +327,9 @@ pub enum BlueprintError {
     #[error("Parsing failed because of {0}")]
     InvalidHeaderName(#[from] http::header::InvalidHeaderName),
 
+    #[error("Invalid cookie name: {0}")]
+    InvalidCookieName(String),
+
     #[error("Parsing failed because of {0}")]
     InvalidHeaderValue(#[from] http::header::InvalidHeaderValue),
 
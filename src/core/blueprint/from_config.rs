@@ -30,6 +30,11 @@ pub fn config_blueprint<'a>() -> TryFold<'a, ConfigModule, Blueprint, BlueprintE
         |blueprint| blueprint.definitions,
     );
 
+    let directives = to_directive_definitions().transform::<Blueprint>(
+        |directives, blueprint| blueprint.directives(directives),
+        |blueprint| blueprint.directives,
+    );
+
     let upstream = TryFoldConfig::<Blueprint>::new(|config_module, blueprint| {
         Valid::from(Upstream::try_from(config_module)).map(|upstream| blueprint.upstream(upstream))
     });
@@ -46,6 +51,7 @@ pub fn config_blueprint<'a>() -> TryFold<'a, ConfigModule, Blueprint, BlueprintE
     server
         .and(schema)
         .and(definitions)
+        .and(directives)
         .and(upstream)
         .and(links)
         .and(opentelemetry)
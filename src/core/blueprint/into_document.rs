@@ -35,7 +35,7 @@ fn to_args(args: &[blueprint::InputFieldDefinition]) -> Vec<Positioned<InputValu
                     .clone()
                     .and_then(|value| ConstValue::from_json(value).ok())
                     .map(pos),
-                directives: Vec::new(),
+                directives: to_directives(&input.directives),
             })
         })
         .collect()
@@ -107,7 +107,7 @@ fn to_definition(def: &Definition) -> TypeSystemDefinition {
                     pos(EnumValueDefinition {
                         description: None,
                         value: pos(Name::new(&variant.name)),
-                        directives: Vec::new(),
+                        directives: to_directives(&variant.directives),
                     })
                 })
                 .collect(),
@@ -139,7 +139,11 @@ impl From<&Blueprint> for ServiceDocument {
                 .mutation
                 .as_ref()
                 .map(|mutation| pos(Name::new(mutation))),
-            subscription: None,
+            subscription: blueprint
+                .schema
+                .subscription
+                .as_ref()
+                .map(|subscription| pos(Name::new(subscription))),
         })));
 
         for def in &blueprint.definitions {
@@ -5,7 +5,8 @@ use async_graphql_value::ConstValue;
 use futures_util::TryFutureExt;
 use tracing::Instrument;
 
-use crate::core::blueprint::{Blueprint, Definition};
+use super::directive::to_deprecated;
+use crate::core::blueprint::{Blueprint, Definition, InputFieldDefinition};
 use crate::core::http::RequestContext;
 use crate::core::ir::{EvalContext, ResolverContext, TypedValue};
 use crate::core::jit::graphql_error::ErrorExtensions;
@@ -34,6 +35,17 @@ fn set_default_value(
     }
 }
 
+fn to_input_value(arg: &InputFieldDefinition) -> dynamic::InputValue {
+    let mut input_value = dynamic::InputValue::new(arg.name.clone(), TypeRef::from(&arg.of_type));
+    if let Some(description) = &arg.description {
+        input_value = input_value.description(description);
+    }
+    if let Some(deprecated) = to_deprecated(&arg.directives) {
+        input_value = input_value.deprecation(deprecated.reason.as_deref());
+    }
+    set_default_value(input_value, arg.default_value.clone())
+}
+
 fn to_field_value(value: async_graphql::Value) -> FieldValue<'static> {
     match value {
         ConstValue::List(vec) => FieldValue::list(vec.into_iter().map(to_field_value)),
@@ -77,7 +89,10 @@ fn to_type(def: &Definition) -> dynamic::Type {
                                 let ctx: ResolverContext = ctx.into();
                                 let ctx = EvalContext::new(req_ctx, &ctx);
 
-                                match ctx.path_value(&[field_name]).map(|a| a.into_owned()) {
+                                let value = ctx
+                                    .path_value(&[field_name])
+                                    .or_else(|| ctx.path_value(&[field.fallback_key.as_ref()?]));
+                                match value.map(|a| a.into_owned()) {
                                     Some(ConstValue::Null) => FieldFuture::Value(FieldValue::NONE),
                                     a => FieldFuture::from_value(a),
                                 }
@@ -118,11 +133,11 @@ fn to_type(def: &Definition) -> dynamic::Type {
                 if let Some(description) = &field.description {
                     dyn_schema_field = dyn_schema_field.description(description);
                 }
+                if let Some(deprecated) = to_deprecated(&field.directives) {
+                    dyn_schema_field = dyn_schema_field.deprecation(deprecated.reason.as_deref());
+                }
                 for arg in field.args.iter() {
-                    dyn_schema_field = dyn_schema_field.argument(set_default_value(
-                        dynamic::InputValue::new(arg.name.clone(), TypeRef::from(&arg.of_type)),
-                        arg.default_value.clone(),
-                    ));
+                    dyn_schema_field = dyn_schema_field.argument(to_input_value(arg));
                 }
                 object = object.field(dyn_schema_field);
             }
@@ -138,10 +153,24 @@ fn to_type(def: &Definition) -> dynamic::Type {
         Definition::Interface(def) => {
             let mut interface = dynamic::Interface::new(def.name.clone());
             for field in def.fields.iter() {
-                interface = interface.field(dynamic::InterfaceField::new(
-                    field.name.clone(),
-                    TypeRef::from(&field.of_type),
-                ));
+                let mut interface_field =
+                    dynamic::InterfaceField::new(field.name.clone(), TypeRef::from(&field.of_type));
+                if let Some(description) = &field.description {
+                    interface_field = interface_field.description(description);
+                }
+                if let Some(deprecated) = to_deprecated(&field.directives) {
+                    interface_field = interface_field.deprecation(deprecated.reason.as_deref());
+                }
+                for arg in field.args.iter() {
+                    interface_field = interface_field.argument(to_input_value(arg));
+                }
+                interface = interface.field(interface_field);
+            }
+            if let Some(description) = &def.description {
+                interface = interface.description(description);
+            }
+            for implements in def.implements.iter() {
+                interface = interface.implement(implements.clone());
             }
 
             dynamic::Type::Interface(interface)
@@ -149,13 +178,7 @@ fn to_type(def: &Definition) -> dynamic::Type {
         Definition::InputObject(def) => {
             let mut input_object = dynamic::InputObject::new(def.name.clone());
             for field in def.fields.iter() {
-                let mut input_field =
-                    dynamic::InputValue::new(field.name.clone(), TypeRef::from(&field.of_type));
-                if let Some(description) = &field.description {
-                    input_field = input_field.description(description);
-                }
-                let input_field = set_default_value(input_field, field.default_value.clone());
-                input_object = input_object.field(input_field);
+                input_object = input_object.field(to_input_value(field));
             }
             if let Some(description) = &def.description {
                 input_object = input_object.description(description);
@@ -168,6 +191,9 @@ fn to_type(def: &Definition) -> dynamic::Type {
             if let Some(description) = &def.description {
                 scalar = scalar.description(description);
             }
+            if let Some(url) = def.scalar.specified_by_url() {
+                scalar = scalar.specified_by_url(url);
+            }
             let name = def.scalar.clone();
             scalar = scalar.validator(move |v| name.validate(v));
             dynamic::Type::Scalar(scalar)
@@ -175,7 +201,14 @@ fn to_type(def: &Definition) -> dynamic::Type {
         Definition::Enum(def) => {
             let mut enum_type = dynamic::Enum::new(def.name.clone());
             for value in def.enum_values.iter() {
-                enum_type = enum_type.item(dynamic::EnumItem::new(value.name.clone()));
+                let mut item = dynamic::EnumItem::new(value.name.clone());
+                if let Some(description) = &value.description {
+                    item = item.description(description);
+                }
+                if let Some(deprecated) = to_deprecated(&value.directives) {
+                    item = item.deprecation(deprecated.reason.as_deref());
+                }
+                enum_type = enum_type.item(item);
             }
             if let Some(desc) = def.description.clone() {
                 enum_type = enum_type.description(desc);
@@ -187,6 +220,9 @@ fn to_type(def: &Definition) -> dynamic::Type {
             for type_ in def.types.iter() {
                 union = union.possible_type(type_.clone());
             }
+            if let Some(description) = &def.description {
+                union = union.description(description);
+            }
             dynamic::Type::Union(union)
         }
     }
@@ -211,9 +247,13 @@ impl From<&Blueprint> for SchemaBuilder {
 fn inject_custom_scalars(mut schema: SchemaBuilder, blueprint: &Blueprint) -> SchemaBuilder {
     fn inject_scalar(schema: SchemaBuilder, type_name: &str) -> SchemaBuilder {
         if let Some(scalar) = Scalar::find(type_name) {
+            let mut definition = dynamic::Scalar::new(scalar.name());
+            if let Some(url) = scalar.specified_by_url() {
+                definition = definition.specified_by_url(url);
+            }
             let scalar = scalar.clone();
             schema.register(dynamic::Type::Scalar(
-                dynamic::Scalar::new(scalar.name()).validator(move |val| scalar.validate(val)),
+                definition.validator(move |val| scalar.validate(val)),
             ))
         } else {
             schema
@@ -226,12 +266,18 @@ fn inject_custom_scalars(mut schema: SchemaBuilder, blueprint: &Blueprint) -> Sc
                 for field in interface_type_definition.fields {
                     let type_name = field.of_type.name();
                     schema = inject_scalar(schema, type_name);
+                    for arg in field.args {
+                        schema = inject_scalar(schema, arg.of_type.name());
+                    }
                 }
             }
             Definition::Object(object_type_definition) => {
                 for field in object_type_definition.fields {
                     let type_name = field.of_type.name();
                     schema = inject_scalar(schema, type_name);
+                    for arg in field.args {
+                        schema = inject_scalar(schema, arg.of_type.name());
+                    }
                 }
             }
             Definition::InputObject(input_object_type_definition) => {
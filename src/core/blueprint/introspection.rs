@@ -0,0 +1,254 @@
+use async_graphql::parser::parse_query;
+use async_graphql::parser::types::{
+    DocumentOperations, ExecutableDocument, Field, Selection, SelectionSet,
+};
+use async_graphql::Name;
+use async_graphql_value::ConstValue;
+use indexmap::IndexMap;
+use tailcall_typedefs_common::directive_definition::from_directive_location;
+
+use super::directive::to_deprecated;
+use super::{Blueprint, Definition, DirectiveDefinition, InputFieldDefinition};
+use crate::core::Type;
+
+/// Adds the directives declared in the schema to the `__schema.directives`
+/// lists of the introspection `data`, since the dynamic schema only knows
+/// about the built-in ones. The `query` is the one that produced the `data`,
+/// the directives are shaped after its selection.
+pub fn add_directives(
+    data: &mut ConstValue,
+    query: &str,
+    operation_name: Option<&str>,
+    blueprint: &Blueprint,
+) {
+    if blueprint.directives.is_empty() {
+        return;
+    }
+    let Ok(document) = parse_query(query) else {
+        return;
+    };
+    let operation = match (&document.operations, operation_name) {
+        (DocumentOperations::Single(operation), _) => operation,
+        (DocumentOperations::Multiple(operations), Some(name)) => match operations.get(name) {
+            Some(operation) => operation,
+            None => return,
+        },
+        (DocumentOperations::Multiple(_), None) => return,
+    };
+    let ConstValue::Object(data) = data else {
+        return;
+    };
+
+    let directives = blueprint
+        .directives
+        .iter()
+        .map(|directive| to_directive_value(directive, blueprint))
+        .collect::<Vec<_>>();
+
+    let query_type = blueprint.query();
+    for schema_field in fields(&operation.node.selection_set.node, &document, &query_type) {
+        if schema_field.name.node.as_str() != "__schema" {
+            continue;
+        }
+        let Some(ConstValue::Object(schema)) =
+            data.get_mut(schema_field.response_key().node.as_str())
+        else {
+            continue;
+        };
+        for directives_field in fields(&schema_field.selection_set.node, &document, "__Schema") {
+            if directives_field.name.node.as_str() != "directives" {
+                continue;
+            }
+            if let Some(ConstValue::List(list)) =
+                schema.get_mut(directives_field.response_key().node.as_str())
+            {
+                list.extend(directives.iter().map(|directive| {
+                    select(directive, &directives_field.selection_set.node, &document)
+                }));
+            }
+        }
+    }
+}
+
+/// The fields of the selection set that apply to the type, with the
+/// fragments expanded.
+fn fields<'a>(
+    selection_set: &'a SelectionSet,
+    document: &'a ExecutableDocument,
+    type_name: &str,
+) -> Vec<&'a Field> {
+    let mut selected = Vec::new();
+    for selection in selection_set.items.iter() {
+        match &selection.node {
+            Selection::Field(field) => selected.push(&field.node),
+            Selection::FragmentSpread(spread) => {
+                if let Some(fragment) = document.fragments.get(&spread.node.fragment_name.node) {
+                    if fragment.node.type_condition.node.on.node.as_str() == type_name {
+                        selected.extend(fields(
+                            &fragment.node.selection_set.node,
+                            document,
+                            type_name,
+                        ));
+                    }
+                }
+            }
+            Selection::InlineFragment(fragment) => {
+                let applies = fragment
+                    .node
+                    .type_condition
+                    .as_ref()
+                    .map_or(true, |condition| {
+                        condition.node.on.node.as_str() == type_name
+                    });
+                if applies {
+                    selected.extend(fields(
+                        &fragment.node.selection_set.node,
+                        document,
+                        type_name,
+                    ));
+                }
+            }
+        }
+    }
+    selected
+}
+
+/// Picks the selected fields out of an introspection value, whose objects
+/// carry their type in `__typename`.
+fn select(
+    value: &ConstValue,
+    selection_set: &SelectionSet,
+    document: &ExecutableDocument,
+) -> ConstValue {
+    match value {
+        ConstValue::List(list) => ConstValue::List(
+            list.iter()
+                .map(|value| select(value, selection_set, document))
+                .collect(),
+        ),
+        ConstValue::Object(object) => {
+            let type_name = match object.get("__typename") {
+                Some(ConstValue::String(type_name)) => type_name.as_str(),
+                _ => "",
+            };
+            let mut selected = IndexMap::new();
+            for field in fields(selection_set, document, type_name) {
+                let value = object
+                    .get(field.name.node.as_str())
+                    .map(|value| select(value, &field.selection_set.node, document))
+                    .unwrap_or_default();
+                selected
+                    .entry(field.response_key().node.clone())
+                    .or_insert(value);
+            }
+            ConstValue::Object(selected)
+        }
+        value => value.clone(),
+    }
+}
+
+fn to_object<const N: usize>(fields: [(&str, ConstValue); N]) -> ConstValue {
+    ConstValue::Object(
+        fields
+            .into_iter()
+            .map(|(name, value)| (Name::new(name), value))
+            .collect(),
+    )
+}
+
+fn to_string_value(value: Option<&str>) -> ConstValue {
+    value
+        .map(|value| ConstValue::String(value.to_string()))
+        .unwrap_or_default()
+}
+
+fn to_directive_value(directive: &DirectiveDefinition, blueprint: &Blueprint) -> ConstValue {
+    let locations = directive
+        .locations
+        .iter()
+        .map(|location| ConstValue::Enum(Name::new(from_directive_location(*location))))
+        .collect();
+    let args = directive
+        .args
+        .iter()
+        .map(|arg| to_input_value(arg, blueprint))
+        .collect();
+    to_object([
+        ("__typename", ConstValue::String("__Directive".to_string())),
+        ("name", ConstValue::String(directive.name.clone())),
+        (
+            "description",
+            to_string_value(directive.description.as_deref()),
+        ),
+        ("locations", ConstValue::List(locations)),
+        ("args", ConstValue::List(args)),
+        ("isRepeatable", ConstValue::Boolean(directive.repeatable)),
+    ])
+}
+
+fn to_input_value(arg: &InputFieldDefinition, blueprint: &Blueprint) -> ConstValue {
+    let deprecated = to_deprecated(&arg.directives);
+    let default_value = arg
+        .default_value
+        .clone()
+        .and_then(|value| ConstValue::from_json(value).ok())
+        .map(|value| value.to_string());
+    to_object([
+        ("__typename", ConstValue::String("__InputValue".to_string())),
+        ("name", ConstValue::String(arg.name.clone())),
+        ("description", to_string_value(arg.description.as_deref())),
+        ("type", to_type_value(&arg.of_type, blueprint)),
+        ("defaultValue", to_string_value(default_value.as_deref())),
+        ("isDeprecated", ConstValue::Boolean(deprecated.is_some())),
+        (
+            "deprecationReason",
+            to_string_value(
+                deprecated
+                    .as_ref()
+                    .and_then(|deprecated| deprecated.reason.as_deref()),
+            ),
+        ),
+    ])
+}
+
+fn to_type_value(type_of: &Type, blueprint: &Blueprint) -> ConstValue {
+    if !type_of.is_nullable() {
+        return to_object([
+            ("__typename", ConstValue::String("__Type".to_string())),
+            ("kind", ConstValue::Enum(Name::new("NON_NULL"))),
+            ("name", ConstValue::Null),
+            (
+                "ofType",
+                to_type_value(&type_of.clone().into_nullable(), blueprint),
+            ),
+        ]);
+    }
+    match type_of {
+        Type::List { of_type, .. } => to_object([
+            ("__typename", ConstValue::String("__Type".to_string())),
+            ("kind", ConstValue::Enum(Name::new("LIST"))),
+            ("name", ConstValue::Null),
+            ("ofType", to_type_value(of_type, blueprint)),
+        ]),
+        Type::Named { name, .. } => {
+            let kind = blueprint
+                .definitions
+                .iter()
+                .find(|definition| definition.name() == name.as_str())
+                .map_or("SCALAR", |definition| match definition {
+                    Definition::Interface(_) => "INTERFACE",
+                    Definition::Object(_) => "OBJECT",
+                    Definition::InputObject(_) => "INPUT_OBJECT",
+                    Definition::Scalar(_) => "SCALAR",
+                    Definition::Enum(_) => "ENUM",
+                    Definition::Union(_) => "UNION",
+                });
+            to_object([
+                ("__typename", ConstValue::String("__Type".to_string())),
+                ("kind", ConstValue::Enum(Name::new(kind))),
+                ("name", ConstValue::String(name.clone())),
+                ("ofType", ConstValue::Null),
+            ])
+        }
+    }
+}
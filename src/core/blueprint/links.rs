@@ -15,6 +15,8 @@ impl TryFrom<Vec<Link>> for Links {
                 .and_then(|link| {
                     if link.src.is_empty() {
                         Valid::fail(BlueprintError::LinkSrcCannotBeEmpty)
+                    } else if link.is_remote() && link.is_glob() {
+                        Valid::fail(BlueprintError::RemoteLinkGlob)
                     } else {
                         Valid::succeed(link)
                     }
@@ -49,6 +49,11 @@ impl<'a> MustachePartsValidator<'a> {
         let config = self.config;
         let args = &self.field.args;
 
+        // `{{.fields}}` is written from the selection of the query
+        if parts == ["fields"] {
+            return Valid::succeed(());
+        }
+
         if parts.len() < 2 {
             return Valid::fail(BlueprintError::TooFewPartsInTemplate);
         }
@@ -89,6 +94,15 @@ impl<'a> MustachePartsValidator<'a> {
                     return Valid::fail(BlueprintError::VarNotSetInServerConfig(tail.to_string()));
                 }
             }
+            "tenant" => {
+                // only the host of an @http URL is checked, see `validate_url`
+                return Valid::fail(BlueprintError::TenantOutsideUrl);
+            }
+            "ctx" => {
+                if !config.server.context.iter().any(|entry| entry.key == tail) {
+                    return Valid::fail(BlueprintError::ContextKeyNotAvailable(tail.to_string()));
+                }
+            }
             "headers" | "env" => {
                 // "headers" and "env" refers to values known at runtime, which
                 // we can't validate here
@@ -101,14 +115,42 @@ impl<'a> MustachePartsValidator<'a> {
         Valid::succeed(())
     }
 
+    /// Validates a part of the URL of an @http resolver, the only template
+    /// that can use the tenant id, since its host is checked against the
+    /// `allowedHosts` of the tenant.
+    fn validate_url(&self, parts: &[String]) -> Valid<(), BlueprintError> {
+        match parts {
+            [head, tail] if head == "tenant" => {
+                if tail != "id" || self.config.server.tenant.is_none() {
+                    Valid::fail(BlueprintError::TenantNotAvailable(tail.to_string()))
+                } else {
+                    Valid::succeed(())
+                }
+            }
+            _ => self.validate(parts, false),
+        }
+    }
+
     fn validate_resolver(&self, resolver: &IR) -> Valid<(), BlueprintError> {
         match resolver {
             IR::Merge(resolvers) => {
                 Valid::from_iter(resolvers, |resolver| self.validate_resolver(resolver)).unit()
             }
+            IR::ByOperation { query, mutation, subscription } => self
+                .validate_resolver(query)
+                .and(self.validate_resolver(mutation))
+                .and(self.validate_resolver(subscription)),
+            IR::When(condition, resolver) => {
+                Valid::from_iter(condition.expression_segments(), |parts| {
+                    self.validate(parts, true)
+                        .trace("when")
+                        .trace(config::Http::trace_name().as_str())
+                })
+                .and(self.validate_resolver(resolver))
+            }
             IR::IO(IO::Http { req_template, .. }) => {
                 Valid::from_iter(req_template.root_url.expression_segments(), |parts| {
-                    self.validate(parts, false).trace("path")
+                    self.validate_url(parts).trace("path")
                 })
                 .and(Valid::from_iter(req_template.query.clone(), |query| {
                     let mustache = &query.value;
@@ -117,6 +159,30 @@ impl<'a> MustachePartsValidator<'a> {
                         self.validate(parts, true).trace("query")
                     })
                 }))
+                .and(Valid::from_iter(
+                    req_template.headers.clone(),
+                    |(_, mustache)| {
+                        // a header that resolves to nothing is left out, so nullable
+                        // values can be used
+                        Valid::from_iter(mustache.expression_segments(), |parts| {
+                            self.validate(parts, true).trace("headers")
+                        })
+                    },
+                ))
+                .and(Valid::from_iter(
+                    req_template
+                        .body_path
+                        .iter()
+                        .flat_map(|body| body.expression_segments()),
+                    |parts| {
+                        // the other parts of the body aren't validated yet
+                        if parts.first().is_some_and(|head| head == "tenant") {
+                            Valid::fail(BlueprintError::TenantOutsideUrl).trace("body")
+                        } else {
+                            Valid::succeed(())
+                        }
+                    },
+                ))
                 .unit()
                 .trace(config::Http::trace_name().as_str())
             }
@@ -230,12 +296,16 @@ mod test {
                 of_type: type_,
                 default_value: None,
                 description: None,
+                directives: vec![],
             }],
             of_type: Type::Named { name: "T1".to_string(), non_null: false },
             resolver: None,
             directives: vec![],
             description: None,
             default_value: None,
+            poll: None,
+            omit_if_null: false,
+            fallback_key: None,
         };
 
         (config, fld)
@@ -264,4 +334,47 @@ mod test {
 
         assert!(validation_result.to_result().is_err())
     }
+
+    #[test]
+    fn test_tenant_only_in_url() {
+        let (mut config, field_def) = initialize_test_config_and_field();
+        config.server.tenant = Some(config::Tenant {
+            id: "{{.headers.x-tenant-id}}".to_string(),
+            allowed_hosts: vec!["*.api.example.com".to_string()],
+        });
+        let tenant = ["tenant".to_string(), "id".to_string()];
+
+        let parts_validator =
+            MustachePartsValidator::new(config.types.get("T1").unwrap(), &config, &field_def);
+
+        assert!(parts_validator.validate_url(&tenant).is_succeed());
+        assert!(parts_validator.validate(&tenant, true).to_result().is_err());
+    }
+
+    #[test]
+    fn test_http_header_references_parent_value() {
+        use crate::core::blueprint::Blueprint;
+        use crate::core::config::ConfigModule;
+
+        let blueprint = |header: &str| {
+            let sdl = format!(
+                r#"
+                schema @server @upstream {{ query: Query }}
+                type Query {{ resource: Resource @http(url: "http://upstream.test/resource") }}
+                type Resource {{
+                  version: Int
+                  history: [Int] @http(
+                    url: "http://upstream.test/history"
+                    headers: [{{key: "X-Resource-Version", value: "{header}"}}]
+                  )
+                }}
+                "#
+            );
+            let config = Config::from_sdl(&sdl).to_result().unwrap();
+            Blueprint::try_from(&ConfigModule::from(config))
+        };
+
+        assert!(blueprint("{{.value.version}}").is_ok());
+        assert!(blueprint("{{.value.revision}}").is_err());
+    }
 }
@@ -7,7 +7,7 @@ use crate::core::config::Expr;
 use crate::core::ir::model::IR;
 use crate::core::ir::model::IR::Dynamic;
 
-fn validate_data_with_schema(
+pub(super) fn validate_data_with_schema(
     config: &config::Config,
     field: &config::Field,
     gql_value: ConstValue,
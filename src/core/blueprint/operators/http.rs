@@ -1,12 +1,23 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use http::header::HeaderName;
 use tailcall_valid::{Valid, Validator};
 use template_validation::validate_argument;
 
 use crate::core::blueprint::*;
-use crate::core::config::group_by::GroupBy;
-use crate::core::config::Field;
+use crate::core::config::group_by::{CompositeKey, GroupBy};
+use crate::core::config::{AuthProvider, ConfigModule, Field, Resolver};
+use crate::core::directive::DirectiveCodec;
 use crate::core::endpoint::Endpoint;
-use crate::core::http::{Method, RequestTemplate};
+use crate::core::http::{
+    BasicCredentials, CircuitBreaker, Method, NoContent, Paginate, Query, QueryEncoder,
+    RequestTemplate, Signer, StatusCodes,
+};
 use crate::core::ir::model::{IO, IR};
+use crate::core::json::JsonPath;
+use crate::core::mustache::Segment;
+use crate::core::try_fold::TryFold;
 use crate::core::worker_hooks::WorkerHooks;
 use crate::core::{config, helpers, Mustache};
 
@@ -17,15 +28,63 @@ pub fn compile_http(
 ) -> Valid<IR, BlueprintError> {
     let is_list = field.type_of.is_list();
     let dedupe = http.dedupe.unwrap_or_default();
+    let verify_ssl = http.ssl.as_ref().map_or(true, |ssl| ssl.verify);
+    let no_content = NoContent::new(&field.type_of, &http.method);
     let mustache_headers = match helpers::headers::to_mustache_headers(&http.headers).to_result() {
         Ok(mustache_headers) => Valid::succeed(mustache_headers),
         Err(e) => Valid::from_validation_err(BlueprintError::from_validation_string(e)),
     };
+    let signer = match http
+        .signing
+        .as_ref()
+        .or(config_module.upstream.signing.as_ref())
+        .map(Signer::try_from)
+        .transpose()
+    {
+        Ok(signer) => Valid::succeed(signer),
+        Err(e) => Valid::fail(BlueprintError::InvalidHeaderName(e)).trace("signing"),
+    };
+    let request_id_headers = Valid::from_iter(http.request_id.iter(), |name| {
+        match HeaderName::try_from(name.as_str()) {
+            Ok(name) => Valid::succeed(name),
+            Err(e) => Valid::fail(BlueprintError::InvalidHeaderName(e)).trace(name),
+        }
+    })
+    .trace("requestId");
+    // The keys of all the batched requests are sent together in the body
+    let keys_in_body = !http.batch_key.is_empty() && http.body.as_ref().is_some_and(has_batch_keys);
+    let batch_body = match http.body.as_ref().filter(|_| keys_in_body) {
+        Some(body) => match DynamicValue::try_from(body) {
+            Ok(body) => Valid::succeed(Some(body)),
+            Err(e) => Valid::fail(BlueprintError::Error(e)).trace("body"),
+        },
+        None => Valid::succeed(None),
+    };
+    let value_query_keys = http
+        .query
+        .iter()
+        .filter(|q| Mustache::parse(&q.value).expression_contains("value"))
+        .map(|q| q.key.clone())
+        .collect::<Vec<_>>();
+    let value_query_key = value_query_keys.first().cloned();
+    let is_batched = !http.batch_key.is_empty() || !http.batch_keys.is_empty();
+    let map_response_body = match &http.map_response_body {
+        Some(_) if is_batched || http.paginate.is_some() => {
+            Valid::fail(BlueprintError::MapResponseBodyWithBatching).trace("mapResponseBody")
+        }
+        Some(path) => match JsonPath::try_new(path, is_list) {
+            Ok(path) => Valid::succeed(Some(path)),
+            Err(e) => {
+                Valid::fail(BlueprintError::InvalidMapResponseBody(e)).trace("mapResponseBody")
+            }
+        },
+        None => Valid::succeed(None),
+    };
 
     Valid::<(), BlueprintError>::fail(BlueprintError::IncorrectBatchingUsage)
         .when(|| {
             (config_module.upstream.get_delay() < 1 || config_module.upstream.get_max_size() < 1)
-                && !http.batch_key.is_empty()
+                && is_batched
         })
         .and(
             Valid::from_iter(http.query.iter(), |query| {
@@ -34,15 +93,40 @@ pub fn compile_http(
             .unit()
             .trace("query"),
         )
+        .and(
+            Valid::from_iter(http.cookies.iter(), |cookie| {
+                Valid::<(), BlueprintError>::fail(BlueprintError::InvalidCookieName(
+                    cookie.key.clone(),
+                ))
+                .when(|| !is_cookie_name(&cookie.key))
+                .and(validate_argument(
+                    config_module,
+                    Mustache::parse(cookie.value.as_str()),
+                    field,
+                ))
+                .trace(&cookie.key)
+            })
+            .unit()
+            .trace("cookies"),
+        )
         .and(
             Valid::<(), BlueprintError>::fail(BlueprintError::BatchKeyRequiresEitherBodyOrQuery)
                 .when(|| {
                     !http.batch_key.is_empty() && (http.body.is_none() && http.query.is_empty())
                 }),
         )
+        .and(validate_batch_keys(http, &value_query_keys).trace("batchKeys"))
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::CompressWithBatching)
+                .when(|| http.compress && is_batched)
+                .trace("compress"),
+        )
+        .and(validate_paginate(http, is_list, is_batched).trace("paginate"))
+        .and(validate_status_codes(http).trace("onStatus"))
         .and(Valid::succeed(http.url.as_str()))
         .zip(mustache_headers)
-        .and_then(|(base_url, headers)| {
+        .zip(request_id_headers)
+        .and_then(|((base_url, headers), request_id_headers)| {
             let query = http
                 .query
                 .clone()
@@ -60,17 +144,102 @@ pub fn compile_http(
                 Endpoint::new(base_url.to_string())
                     .method(http.method.clone())
                     .query(query)
-                    .body(http.body.clone())
+                    .body(if keys_in_body {
+                        None
+                    } else {
+                        http.body.clone()
+                    })
                     .encoding(http.encoding.clone()),
             )
-            .map(|req_tmpl| req_tmpl.headers(headers))
-            {
+            .map(|req_tmpl| {
+                let cookies = http
+                    .cookies
+                    .iter()
+                    .map(|cookie| (cookie.key.clone(), Mustache::parse(cookie.value.as_str())))
+                    .collect();
+                let query = req_tmpl
+                    .query
+                    .iter()
+                    .zip(http.query.iter())
+                    .map(|(query, key_value)| {
+                        query
+                            .clone()
+                            .encoder(key_value.encoding.map(QueryEncoder::from))
+                    })
+                    .collect();
+                let query_encoder = http
+                    .array_format
+                    .map(QueryEncoder::from)
+                    .unwrap_or_default();
+                req_tmpl
+                    .query(query)
+                    .query_encoder(query_encoder)
+                    .headers(headers)
+                    .cookies(cookies)
+                    .null_query_params(http.null_query_params)
+                    .parse_response(http.parse_response)
+                    .no_content(no_content)
+                    .request_id_headers(request_id_headers)
+                    .compress(http.compress)
+                    .encode_path(http.encode_path.unwrap_or(true))
+                    .encode_query_params(http.encode_query_params.unwrap_or_default())
+            }) {
                 Ok(data) => Valid::succeed(data),
                 Err(e) => Valid::fail(BlueprintError::Error(e)),
             }
         })
         .and_then(|request_template| {
-            if !http.batch_key.is_empty() && (http.body.is_some() || http.method != Method::GET) {
+            let providers = config_module
+                .auth
+                .iter()
+                .chain(field.auth.iter())
+                .flat_map(|auth| auth.providers())
+                .collect::<Vec<_>>();
+            let env = &config_module.extensions().env_vars;
+
+            validate_env_vars(env, &providers)
+                .and_then(|_| apply_upstream_auth(request_template, &providers, env))
+                .trace("auth")
+        })
+        .and_then(|mut request_template| {
+            let Some(basic_auth) = &http.basic_auth else {
+                return Valid::succeed(request_template);
+            };
+            let provider =
+                AuthProvider { basic_auth: Some(basic_auth.clone()), ..Default::default() };
+
+            let env = &config_module.extensions().env_vars;
+
+            validate_env_vars(env, &[provider])
+                .map(|_| {
+                    request_template.basic_auth = Some(BasicCredentials {
+                        user: env_value(env, "", &basic_auth.user_env),
+                        pass: env_value(env, "", &basic_auth.pass_env),
+                    });
+                    request_template
+                })
+                .trace("basicAuth")
+        })
+        .and_then(|request_template| {
+            if keys_in_body {
+                // The key of each request is the query parameter that references
+                // `{{.value}}`, or else the field of the value named like the
+                // last component of the batchKey.
+                let batch_key = match http
+                    .query
+                    .iter()
+                    .find(|q| Some(&q.key) == value_query_key.as_ref())
+                {
+                    Some(query) => Mustache::parse(&query.value),
+                    None => {
+                        let key = http.batch_key.last().cloned().unwrap_or_default();
+                        Mustache::parse(&format!("{{{{.value.{key}}}}}"))
+                    }
+                };
+                Valid::succeed(request_template.batch_key(Some(batch_key)))
+            } else if !http.batch_key.is_empty()
+                && (http.body.is_some() || http.method != Method::GET)
+            {
                 if let Some(body) = http.body.as_ref() {
                     let dynamic_paths = count_dynamic_paths(body);
                     if dynamic_paths != 1 {
@@ -85,7 +254,9 @@ pub fn compile_http(
                 Valid::succeed(request_template)
             }
         })
-        .map(|req_template| {
+        .zip(signer)
+        .zip(batch_body.zip(map_response_body))
+        .map(|((req_template, signer), (batch_body, body_path))| {
             // marge http and upstream on_request
             let on_request = http
                 .on_request
@@ -93,15 +264,39 @@ pub fn compile_http(
                 .or(config_module.upstream.on_request.clone());
             let on_response_body = http.on_response_body.clone();
             let hook = WorkerHooks::try_new(on_request, on_response_body).ok();
+            let circuit_breaker = http.circuit_breaker.as_ref().map(CircuitBreaker::from);
+            let timeout = http.timeout.map(Duration::from_millis);
+            let paginate = http.paginate.as_ref().map(Paginate::from);
+            let status_codes = StatusCodes::from(http.on_status.as_slice());
+
+            let io = if !http.batch_keys.is_empty() {
+                let keys = http
+                    .batch_keys
+                    .iter()
+                    .zip(value_query_keys)
+                    .map(|(path, query)| CompositeKey { path: path.clone(), query })
+                    .collect();
 
-            let io = if !http.batch_key.is_empty() {
+                IR::IO(IO::Http {
+                    req_template,
+                    group_by: Some(GroupBy::composite(keys)),
+                    dl_id: None,
+                    is_list,
+                    dedupe,
+                    hook,
+                    circuit_breaker,
+                    verify_ssl,
+                    signer,
+                    timeout,
+                    batch_body: None,
+                    paginate: None,
+                    map_response_body: None,
+                    status_codes,
+                })
+            } else if !http.batch_key.is_empty() {
                 // Find a query parameter that contains a reference to the {{.value}} key
-                let key = if http.method == Method::GET {
-                    http.query.iter().find_map(|q| {
-                        Mustache::parse(&q.value)
-                            .expression_contains("value")
-                            .then(|| q.key.clone())
-                    })
+                let key = if http.method == Method::GET || keys_in_body {
+                    value_query_key
                 } else {
                     None
                 };
@@ -113,6 +308,14 @@ pub fn compile_http(
                     is_list,
                     dedupe,
                     hook,
+                    circuit_breaker,
+                    verify_ssl,
+                    signer,
+                    timeout,
+                    batch_body,
+                    paginate: None,
+                    map_response_body: None,
+                    status_codes,
                 })
             } else {
                 IR::IO(IO::Http {
@@ -122,14 +325,256 @@ pub fn compile_http(
                     is_list,
                     dedupe,
                     hook,
+                    circuit_breaker,
+                    verify_ssl,
+                    signer,
+                    timeout,
+                    batch_body: None,
+                    paginate,
+                    map_response_body: body_path,
+                    status_codes,
                 })
             };
             (io, &http.select)
         })
         .and_then(apply_select)
+        .map(|ir| match &http.when {
+            Some(condition) => IR::When(Mustache::parse(condition), Box::new(ir)),
+            None => ir,
+        })
+}
+
+/// Checks that the cookie name is a token as defined by RFC 6265.
+fn is_cookie_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b))
+}
+
+/// Reads the polling settings of the `@http` resolver of a field. Upstreams
+/// can't push updates, so every field of the Subscription type has to be
+/// polled and polling isn't allowed anywhere else.
+pub fn update_poll<'a>(
+    object_name: &'a str,
+) -> TryFold<
+    'a,
+    (&'a ConfigModule, &'a Field, &'a config::Type, &'a str),
+    FieldDefinition,
+    BlueprintError,
+> {
+    TryFold::<(&ConfigModule, &Field, &config::Type, &str), FieldDefinition, BlueprintError>::new(
+        move |(config_module, field, _, _), b_field| {
+            let is_subscription = config_module.schema.subscription.as_deref() == Some(object_name);
+            let http = field.resolvers.iter().find_map(|resolver| match resolver {
+                Resolver::Http(http) if http.get_poll_interval_ms().is_some() => Some(http),
+                _ => None,
+            });
+
+            match http {
+                None if is_subscription => {
+                    Valid::fail(BlueprintError::SubscriptionFieldWithoutPoll)
+                }
+                None => Valid::succeed(b_field),
+                Some(_) if !is_subscription => Valid::fail(BlueprintError::PollOutsideSubscription)
+                    .trace(config::Http::trace_name().as_str()),
+                Some(http) => match http.get_poll_interval_ms().unwrap_or_default() {
+                    0 => Valid::fail(BlueprintError::InvalidPollInterval)
+                        .trace(config::Http::trace_name().as_str()),
+                    interval => Valid::succeed(b_field.poll(Some(Poll {
+                        interval: Duration::from_millis(interval),
+                        emit_unchanged: http.poll_emit_unchanged.unwrap_or_default(),
+                    }))),
+                },
+            }
+        },
+    )
+}
+
+/// Default header used to send an API key when no location is specified.
+const DEFAULT_API_KEY_HEADER: &str = "X-Api-Key";
+
+/// A template that renders to the value of an environment variable read when
+/// the config was loaded, as is.
+fn env_value(env: &HashMap<String, String>, prefix: &str, name: &str) -> Mustache {
+    let value = env.get(name).map(String::as_str).unwrap_or_default();
+    Mustache::from([Segment::Literal(format!("{prefix}{value}"))])
+}
+
+/// Fails when an `@auth` is on a field that isn't resolved with `@http`, the
+/// only resolver it attaches credentials to.
+pub fn update_auth<'a>() -> TryFold<
+    'a,
+    (&'a ConfigModule, &'a Field, &'a config::Type, &'a str),
+    FieldDefinition,
+    BlueprintError,
+> {
+    TryFold::<(&ConfigModule, &Field, &config::Type, &str), FieldDefinition, BlueprintError>::new(
+        |(_, field, _, _), b_field| {
+            let has_http = field
+                .resolvers
+                .iter()
+                .any(|resolver| matches!(resolver, Resolver::Http(_)));
+            if field.auth.is_some() && !has_http {
+                Valid::fail(BlueprintError::AuthWithoutHttp)
+                    .trace(config::Auth::trace_name().as_str())
+            } else {
+                Valid::succeed(b_field)
+            }
+        },
+    )
+}
+
+/// Checks that the environment variables the credentials are read from were
+/// set when the config was loaded, since the missing ones would be sent as
+/// empty credentials.
+fn validate_env_vars(
+    env: &HashMap<String, String>,
+    providers: &[AuthProvider],
+) -> Valid<(), BlueprintError> {
+    let env_vars = providers.iter().flat_map(|provider| provider.env_vars());
+    Valid::from_iter(env_vars, |name| {
+        if env.contains_key(&name) {
+            Valid::succeed(())
+        } else {
+            Valid::fail(BlueprintError::EnvVarNotSet(name))
+        }
+    })
+    .unit()
+}
+
+/// Attaches the credentials of the upstream auth providers to the request
+/// template, with the values of the environment variables read at startup.
+fn apply_upstream_auth(
+    mut request_template: RequestTemplate,
+    providers: &[AuthProvider],
+    env: &HashMap<String, String>,
+) -> Valid<RequestTemplate, BlueprintError> {
+    for provider in providers {
+        if let Some(api_key) = &provider.api_key {
+            let value = env_value(env, "", &api_key.env);
+
+            if let Some(query) = &api_key.query {
+                request_template.query.push(Query {
+                    key: query.clone(),
+                    value: value.clone(),
+                    skip_empty: true,
+                    encoder: None,
+                });
+            }
+
+            let header = match (&api_key.header, &api_key.query) {
+                (Some(header), _) => Some(header.as_str()),
+                (None, None) => Some(DEFAULT_API_KEY_HEADER),
+                (None, Some(_)) => None,
+            };
+
+            if let Some(header) = header {
+                match HeaderName::from_bytes(header.as_bytes()) {
+                    Ok(name) => request_template.headers.push((name, value)),
+                    Err(e) => return Valid::fail(BlueprintError::InvalidHeaderName(e)),
+                }
+            }
+        }
+
+        if let Some(jwt) = &provider.jwt {
+            request_template.headers.push((
+                http::header::AUTHORIZATION,
+                env_value(env, "Bearer ", &jwt.env),
+            ));
+        }
+
+        if let Some(basic_auth) = &provider.basic_auth {
+            request_template.basic_auth = Some(BasicCredentials {
+                user: env_value(env, "", &basic_auth.user_env),
+                pass: env_value(env, "", &basic_auth.pass_env),
+            });
+        }
+    }
+
+    Valid::succeed(request_template)
 }
 
 /// Count the number of dynamic expressions in the JSON value.
+/// Checks that each component of a composite key has a query parameter to
+/// send it in.
+fn validate_batch_keys(
+    http: &config::Http,
+    value_query_keys: &[String],
+) -> Valid<(), BlueprintError> {
+    if http.batch_keys.is_empty() {
+        Valid::succeed(())
+    } else if !http.batch_key.is_empty() {
+        Valid::fail(BlueprintError::BatchKeyAndBatchKeys)
+    } else if http.method != Method::GET {
+        Valid::fail(BlueprintError::BatchKeysRequireGet)
+    } else if value_query_keys.len() != http.batch_keys.len() {
+        Valid::fail(BlueprintError::BatchKeysRequireQueryKeys)
+    } else {
+        Valid::succeed(())
+    }
+}
+
+/// Checks that the pages can be followed and their items concatenated.
+fn validate_paginate(
+    http: &config::Http,
+    is_list: bool,
+    is_batched: bool,
+) -> Valid<(), BlueprintError> {
+    match &http.paginate {
+        None => Valid::succeed(()),
+        Some(_) if !is_list => Valid::fail(BlueprintError::PaginateRequiresList),
+        Some(_) if is_batched => Valid::fail(BlueprintError::PaginateWithBatching),
+        Some(paginate)
+            if paginate.mode == config::PaginateMode::Cursor && paginate.cursor_path.is_none() =>
+        {
+            Valid::fail(BlueprintError::PaginateRequiresCursorPath)
+        }
+        Some(paginate) if paginate.max_pages == Some(0) => {
+            Valid::fail(BlueprintError::InvalidMaxPages)
+        }
+        Some(_) => Valid::succeed(()),
+    }
+}
+
+/// Checks that only the error statuses and classes are mapped, and the
+/// successful ones with a `when`, once each, and that only the idempotent
+/// requests are retried.
+fn validate_status_codes(http: &config::Http) -> Valid<(), BlueprintError> {
+    let mut seen = HashSet::new();
+    Valid::from_iter(http.on_status.iter(), |on_status| {
+        let (is_error, is_success) = match on_status.code {
+            config::StatusPattern::Code(status) => {
+                ((400..600).contains(&status), (200..300).contains(&status))
+            }
+            config::StatusPattern::Class(class) => ((4..6).contains(&class), class == 2),
+        };
+        let code = on_status.code.to_string();
+        if is_success && on_status.when.is_none() {
+            Valid::fail(BlueprintError::StatusCodeWithoutWhen(code))
+        } else if !is_error && !is_success {
+            Valid::fail(BlueprintError::StatusCodeNotError(code))
+        } else if !seen.insert((on_status.code, on_status.when.as_deref())) {
+            Valid::fail(BlueprintError::StatusCodeMappedTwice(code))
+        } else if on_status.behavior == config::StatusBehavior::Retry
+            && !http.method.is_idempotent()
+        {
+            Valid::fail(BlueprintError::RetryNotIdempotent(http.method.to_string()))
+        } else {
+            Valid::succeed(())
+        }
+    })
+    .unit()
+}
+
+/// Checks if the body references `{{.batch.keys}}`.
+fn has_batch_keys(body: &serde_json::Value) -> bool {
+    Mustache::parse(&body.to_string())
+        .segments()
+        .iter()
+        .any(|segment| matches!(segment, Segment::Expression(parts) if parts == &["batch", "keys"]))
+}
+
 fn count_dynamic_paths(json: &serde_json::Value) -> usize {
     let mut count = 0;
     match json {
@@ -158,6 +603,7 @@ mod test {
     use serde_json::json;
 
     use super::*;
+    use crate::core::http::{NextPage, UpstreamResponse, DEFAULT_MAX_PAGES};
 
     #[test]
     fn test_extract_expression_keys_from_nested_objects() {
@@ -181,4 +627,629 @@ mod test {
         let keys = count_dynamic_paths(&json);
         assert_eq!(keys, 1);
     }
+
+    fn env() -> HashMap<String, String> {
+        HashMap::from([
+            ("API_KEY".to_string(), "secret".to_string()),
+            ("TOKEN".to_string(), "{{.env.TOKEN}}".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_apply_upstream_auth_api_key() {
+        let providers = vec![AuthProvider {
+            api_key: Some(config::ApiKey {
+                env: "API_KEY".to_string(),
+                header: None,
+                query: Some("api_key".to_string()),
+            }),
+            ..Default::default()
+        }];
+        let template = RequestTemplate::new("http://localhost:3000").unwrap();
+        let template = apply_upstream_auth(template, &providers, &env())
+            .to_result()
+            .unwrap();
+
+        assert!(template.headers.is_empty());
+        assert_eq!(template.query.len(), 1);
+        assert_eq!(template.query[0].key, "api_key");
+        assert_eq!(template.query[0].value, Mustache::parse("secret"));
+    }
+
+    #[test]
+    fn test_apply_upstream_auth_default_header() {
+        let providers = vec![AuthProvider {
+            api_key: Some(config::ApiKey { env: "API_KEY".to_string(), header: None, query: None }),
+            jwt: Some(config::JwtAuth { env: "TOKEN".to_string() }),
+            ..Default::default()
+        }];
+        let template = RequestTemplate::new("http://localhost:3000").unwrap();
+        let template = apply_upstream_auth(template, &providers, &env())
+            .to_result()
+            .unwrap();
+
+        assert_eq!(
+            template.headers,
+            vec![
+                (
+                    HeaderName::from_static("x-api-key"),
+                    Mustache::parse("secret")
+                ),
+                (
+                    http::header::AUTHORIZATION,
+                    Mustache::from([Segment::Literal("Bearer {{.env.TOKEN}}".to_string())])
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auth_env_vars() {
+        let compile = |env_vars: &[&str]| {
+            let extensions = config::Extensions {
+                env_vars: env_vars
+                    .iter()
+                    .map(|name| (name.to_string(), "value".to_string()))
+                    .collect(),
+                ..Default::default()
+            };
+            let config_module = ConfigModule::new(Default::default(), extensions);
+            let http = config::Http {
+                url: "https://localhost:3000/users".to_string(),
+                ..Default::default()
+            };
+            let field = Field {
+                auth: Some(config::Auth {
+                    basic_auth: Some(config::BasicAuth {
+                        user_env: "API_USER".to_string(),
+                        pass_env: "API_PASS".to_string(),
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            compile_http(&config_module, &http, &field).to_result()
+        };
+
+        assert!(compile(&["API_USER", "API_PASS"]).is_ok());
+
+        let error = compile(&["API_USER"]).unwrap_err().to_string();
+        assert!(error.contains("The environment variable `API_PASS` is not set"));
+    }
+
+    #[test]
+    fn test_basic_auth_env_vars() {
+        let extensions = config::Extensions {
+            env_vars: HashMap::from([("API_USER".to_string(), "admin".to_string())]),
+            ..Default::default()
+        };
+        let config_module = ConfigModule::new(Default::default(), extensions);
+        let http = config::Http {
+            url: "https://localhost:3000/users".to_string(),
+            basic_auth: Some(config::BasicAuth {
+                user_env: "API_USER".to_string(),
+                pass_env: "API_PASS".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        let error = compile_http(&config_module, &http, &Field::default())
+            .to_result()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("The environment variable `API_PASS` is not set"));
+
+        let extensions = config::Extensions {
+            env_vars: HashMap::from([
+                ("API_USER".to_string(), "admin".to_string()),
+                ("API_PASS".to_string(), "{{.env.SECRET}}".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let config_module = ConfigModule::new(Default::default(), extensions);
+        let Ok(IR::IO(IO::Http { req_template, .. })) =
+            compile_http(&config_module, &http, &Field::default()).to_result()
+        else {
+            panic!("expected an http resolver");
+        };
+        let credentials = req_template.basic_auth.unwrap();
+
+        // the values are used as they were read, not as templates
+        assert_eq!(
+            credentials.user,
+            Mustache::from([Segment::Literal("admin".to_string())])
+        );
+        assert_eq!(
+            credentials.pass,
+            Mustache::from([Segment::Literal("{{.env.SECRET}}".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_is_cookie_name() {
+        assert!(is_cookie_name("session"));
+        assert!(is_cookie_name("__Host-id"));
+        assert!(!is_cookie_name(""));
+        assert!(!is_cookie_name("session id"));
+        assert!(!is_cookie_name("a=b"));
+        assert!(!is_cookie_name("a;b"));
+    }
+
+    #[test]
+    fn test_ssl_verify() {
+        let verify_ssl = |ssl: Option<config::HttpSslConfig>| {
+            let http = config::Http {
+                url: "https://localhost:3000/users".to_string(),
+                ssl,
+                ..Default::default()
+            };
+            match compile_http(&ConfigModule::default(), &http, &Field::default()).to_result() {
+                Ok(IR::IO(IO::Http { verify_ssl, .. })) => verify_ssl,
+                _ => panic!("expected an http resolver"),
+            }
+        };
+
+        assert!(verify_ssl(None));
+        assert!(verify_ssl(Some(config::HttpSslConfig { verify: true })));
+        assert!(!verify_ssl(Some(config::HttpSslConfig { verify: false })));
+    }
+
+    #[test]
+    fn test_array_format() {
+        let compile = |array_format: Option<config::ArrayFormat>| {
+            let http = config::Http {
+                url: "https://localhost:3000/users".to_string(),
+                array_format,
+                query: vec![
+                    config::URLQuery {
+                        key: "ids".to_string(),
+                        value: "{{.value.ids}}".to_string(),
+                        ..Default::default()
+                    },
+                    config::URLQuery {
+                        key: "tags".to_string(),
+                        value: "{{.value.tags}}".to_string(),
+                        encoding: Some(config::QueryEncoding::PipeDelimited),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            };
+            match compile_http(&ConfigModule::default(), &http, &Field::default()).to_result() {
+                Ok(IR::IO(IO::Http { req_template, .. })) => req_template,
+                _ => panic!("expected an http resolver"),
+            }
+        };
+
+        let req_template = compile(None);
+        assert!(matches!(
+            req_template.query_encoder,
+            QueryEncoder::RepeatedKey
+        ));
+        assert!(req_template.query[0].encoder.is_none());
+
+        let req_template = compile(Some(config::ArrayFormat::Bracket));
+        assert!(matches!(req_template.query_encoder, QueryEncoder::Bracket));
+        assert!(matches!(
+            req_template.query[1].encoder,
+            Some(QueryEncoder::PipeDelimited)
+        ));
+
+        let req_template = compile(Some(config::ArrayFormat::Comma));
+        assert!(matches!(
+            req_template.query_encoder,
+            QueryEncoder::CommaSeparated
+        ));
+    }
+
+    #[test]
+    fn test_paginate() {
+        let compile = |paginate: config::Paginate, type_of: crate::core::Type| {
+            let http = config::Http {
+                url: "https://localhost:3000/users".to_string(),
+                paginate: Some(paginate),
+                ..Default::default()
+            };
+            let field = Field { type_of, ..Default::default() };
+            compile_http(&ConfigModule::default(), &http, &field).to_result()
+        };
+        let cursor = config::Paginate {
+            mode: config::PaginateMode::Cursor,
+            cursor_path: Some(".meta.next".to_string()),
+            cursor_param: None,
+            items_path: Some(".data".to_string()),
+            max_pages: None,
+        };
+        let list = crate::core::Type::from("User".to_string()).into_list();
+
+        match compile(cursor.clone(), list.clone()).unwrap() {
+            IR::IO(IO::Http { paginate: Some(paginate), .. }) => {
+                assert_eq!(
+                    paginate.next,
+                    NextPage::Cursor {
+                        path: vec!["meta".to_string(), "next".to_string()],
+                        param: None
+                    }
+                );
+                assert_eq!(paginate.items_path, vec!["data".to_string()]);
+                assert_eq!(paginate.max_pages, DEFAULT_MAX_PAGES);
+            }
+            _ => panic!("expected a paginated http resolver"),
+        }
+
+        let error = compile(cursor.clone(), crate::core::Type::from("User".to_string()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("paginate can only be used on list fields"));
+
+        let without_path = config::Paginate { cursor_path: None, ..cursor.clone() };
+        let error = compile(without_path, list.clone()).unwrap_err().to_string();
+        assert!(error.contains("requires cursorPath"));
+
+        let no_pages = config::Paginate { max_pages: Some(0), ..cursor };
+        let error = compile(no_pages, list).unwrap_err().to_string();
+        assert!(error.contains("maxPages must be greater than 0"));
+    }
+
+    #[test]
+    fn test_when() {
+        let http = config::Http {
+            url: "https://localhost:3000/users".to_string(),
+            when: Some("{{.args.includeDetails}}".to_string()),
+            ..Default::default()
+        };
+        let ir = compile_http(&ConfigModule::default(), &http, &Field::default()).to_result();
+        assert!(matches!(ir, Ok(IR::When(_, ir)) if matches!(*ir, IR::IO(IO::Http { .. }))));
+    }
+
+    #[test]
+    fn test_on_status() {
+        let compile = |codes: serde_json::Value| {
+            let http = config::Http {
+                url: "https://localhost:3000/users".to_string(),
+                on_status: serde_json::from_value(codes).unwrap(),
+                ..Default::default()
+            };
+            compile_http(&ConfigModule::default(), &http, &Field::default()).to_result()
+        };
+
+        match compile(json!([
+            {"code": 404, "behavior": "NULL"},
+            {"code": "5xx", "behavior": "ERROR"},
+            {"code": 503, "behavior": "RETRY"},
+            {"code": 200, "behavior": "ERROR", "when": "{{.response.body.error}}"}
+        ])) {
+            Ok(IR::IO(IO::Http { status_codes, .. })) => {
+                let find = |status, body| {
+                    let response = UpstreamResponse { status, body };
+                    status_codes.find(&response).map(|code| code.behavior)
+                };
+                assert_eq!(find(404, json!({})), Some(config::StatusBehavior::Null));
+                assert_eq!(find(502, json!({})), Some(config::StatusBehavior::Error));
+                assert_eq!(find(503, json!({})), Some(config::StatusBehavior::Retry));
+                assert_eq!(find(400, json!({})), None);
+                assert_eq!(
+                    find(200, json!({"error": "no such user"})),
+                    Some(config::StatusBehavior::Error)
+                );
+                assert_eq!(find(200, json!({"id": 1})), None);
+            }
+            _ => panic!("expected an http resolver"),
+        }
+
+        let error = compile(json!([{"code": 200, "behavior": "NULL"}]))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("The status 200 can only be mapped with a `when`"));
+
+        let error = compile(json!([{"code": "3xx", "behavior": "NULL"}]))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Only the error status codes can be mapped, found 3xx"));
+
+        let error = compile(json!([
+            {"code": 429, "behavior": "RETRY"},
+            {"code": 429, "behavior": "RATE_LIMIT_ERROR"}
+        ]))
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("The status 429 is mapped more than once"));
+
+        let error = compile(json!([
+            {"code": "5xx", "behavior": "NULL"},
+            {"code": "5XX", "behavior": "ERROR"}
+        ]))
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("The status 5xx is mapped more than once"));
+
+        let http = config::Http {
+            url: "https://localhost:3000/users".to_string(),
+            method: Method::POST,
+            on_status: serde_json::from_value(json!([{"code": 503, "behavior": "RETRY"}])).unwrap(),
+            ..Default::default()
+        };
+        let error = compile_http(&ConfigModule::default(), &http, &Field::default())
+            .to_result()
+            .unwrap_err()
+            .to_string();
+        assert!(error
+            .contains("Only the requests of the idempotent methods can be retried, found POST"));
+
+        let ir = compile(json!([
+            {"code": "4xx", "behavior": "ERROR"},
+            {"code": "4xx", "behavior": "NULL", "when": "{{.response.body.missing}}"}
+        ]));
+        assert!(ir.is_ok());
+    }
+
+    #[test]
+    fn test_signing() {
+        let hmac = |header: &str| {
+            config::Signing::Hmac(config::HmacSigning {
+                algorithm: Default::default(),
+                secret: "secret".to_string(),
+                header: header.to_string(),
+            })
+        };
+        let signer = |upstream: Option<config::Signing>, signing: Option<config::Signing>| {
+            let config = config::Config {
+                upstream: config::Upstream { signing: upstream, ..Default::default() },
+                ..Default::default()
+            };
+            let config_module = ConfigModule::from(config);
+            let http = config::Http {
+                url: "https://localhost:3000/users".to_string(),
+                signing,
+                ..Default::default()
+            };
+            compile_http(&config_module, &http, &Field::default())
+                .to_result()
+                .map(|ir| match ir {
+                    IR::IO(IO::Http { signer, .. }) => signer,
+                    _ => panic!("expected an http resolver"),
+                })
+        };
+
+        assert_eq!(signer(None, None).unwrap(), None);
+
+        let upstream = signer(Some(hmac("X-Upstream")), None).unwrap();
+        assert!(matches!(upstream, Some(Signer::Hmac { header, .. }) if header == "x-upstream"));
+
+        let field = signer(Some(hmac("X-Upstream")), Some(hmac("X-Field"))).unwrap();
+        assert!(matches!(field, Some(Signer::Hmac { header, .. }) if header == "x-field"));
+
+        assert!(signer(None, Some(hmac("X Field"))).is_err());
+    }
+
+    #[test]
+    fn test_batch_keys_in_body() {
+        let compile = |query: Vec<config::URLQuery>| {
+            let config = config::Config::from_sdl(
+                "schema @upstream(batch: {delay: 1, maxSize: 10}) { query: Query } type Query { id: Int }",
+            )
+            .to_result()
+            .unwrap();
+            let http = config::Http {
+                url: "http://localhost:3000/users:batch".to_string(),
+                method: Method::POST,
+                body: Some(json!({"ids": "{{.batch.keys}}"})),
+                batch_key: vec!["id".to_string()],
+                query,
+                ..Default::default()
+            };
+            compile_http(&ConfigModule::from(config), &http, &Field::default()).to_result()
+        };
+
+        let ir = compile(vec![config::URLQuery {
+            key: "id".to_string(),
+            value: "{{.value.userId}}".to_string(),
+            skip_empty: None,
+            encoding: Default::default(),
+        }])
+        .unwrap();
+        match ir {
+            IR::IO(IO::Http { req_template, group_by, batch_body, .. }) => {
+                assert!(req_template.body_path.is_none());
+                assert_eq!(
+                    req_template.batch_key,
+                    Some(Mustache::parse("{{.value.userId}}"))
+                );
+                assert_eq!(group_by.unwrap().key(), "id");
+                assert!(batch_body.is_some());
+            }
+            _ => panic!("expected an http resolver"),
+        }
+
+        match compile(vec![]).unwrap() {
+            IR::IO(IO::Http { req_template, group_by, batch_body, .. }) => {
+                assert_eq!(
+                    req_template.batch_key,
+                    Some(Mustache::parse("{{.value.id}}"))
+                );
+                assert_eq!(group_by.unwrap().query_key(), None);
+                assert!(batch_body.is_some());
+            }
+            _ => panic!("expected an http resolver"),
+        }
+    }
+
+    #[test]
+    fn test_compress_with_batching() {
+        let http = config::Http {
+            url: "http://localhost:3000/users".to_string(),
+            method: Method::POST,
+            body: Some(json!({"id": "{{.value.userId}}"})),
+            batch_key: vec!["id".to_string()],
+            compress: true,
+            ..Default::default()
+        };
+        let error = compile_http(&ConfigModule::default(), &http, &Field::default())
+            .to_result()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("compress can't be used with batchKey or batchKeys"));
+    }
+
+    #[test]
+    fn test_batch_keys() {
+        let compile = |method: Method, batch_key: Vec<String>| {
+            let config = config::Config::from_sdl(
+                "schema @upstream(batch: {delay: 1, maxSize: 10}) { query: Query } type Query { id: Int }",
+            )
+            .to_result()
+            .unwrap();
+            let query = |key: &str, value: &str| config::URLQuery {
+                key: key.to_string(),
+                value: value.to_string(),
+                skip_empty: None,
+                encoding: Default::default(),
+            };
+            let http = config::Http {
+                url: "http://localhost:3000/users".to_string(),
+                method,
+                batch_key,
+                batch_keys: vec!["tenantId".to_string(), "user.id".to_string()],
+                query: vec![
+                    query("tenant", "{{.value.tenantId}}"),
+                    query("id", "{{.value.userId}}"),
+                ],
+                ..Default::default()
+            };
+            compile_http(&ConfigModule::from(config), &http, &Field::default()).to_result()
+        };
+
+        match compile(Method::GET, vec![]).unwrap() {
+            IR::IO(IO::Http { group_by: Some(group_by), .. }) => {
+                assert_eq!(group_by.query_keys(), vec!["tenant", "id"]);
+            }
+            _ => panic!("expected a batched http resolver"),
+        }
+
+        let error = compile(Method::POST, vec![]).unwrap_err().to_string();
+        assert!(error.contains("batchKeys can only be used with GET requests"));
+
+        let error = compile(Method::GET, vec!["id".to_string()])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("batchKey and batchKeys can't be used together"));
+    }
+
+    fn subscription_field(sdl: &str) -> Option<FieldDefinition> {
+        let config = config::Config::from_sdl(sdl).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).ok()?;
+
+        blueprint
+            .definitions
+            .into_iter()
+            .find_map(|definition| match definition {
+                Definition::Object(object) if object.name == "Subscription" => {
+                    object.fields.into_iter().next()
+                }
+                _ => None,
+            })
+    }
+
+    const QUERY: &str = r#"
+        type Query {
+          user: User @http(url: "http://localhost:3000/user")
+        }
+
+        type User {
+          id: Int
+        }
+    "#;
+
+    #[test]
+    fn test_poll() {
+        let sdl = format!(
+            r#"
+            schema {{ query: Query subscription: Subscription }}
+            {QUERY}
+            type Subscription {{
+              user: User @http(url: "http://localhost:3000/user", pollIntervalMs: 2000)
+            }}
+            "#
+        );
+        let field = subscription_field(&sdl).unwrap();
+
+        assert_eq!(
+            field.poll,
+            Some(Poll { interval: Duration::from_secs(2), emit_unchanged: false })
+        );
+    }
+
+    #[test]
+    fn test_poll_interval() {
+        let sdl = format!(
+            r#"
+            schema {{ query: Query subscription: Subscription }}
+            {QUERY}
+            type Subscription {{
+              user: User @http(url: "http://localhost:3000/user", pollInterval: 5000)
+            }}
+            "#
+        );
+        let field = subscription_field(&sdl).unwrap();
+
+        assert_eq!(field.poll.unwrap().interval, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_poll_emit_unchanged() {
+        let sdl = format!(
+            r#"
+            schema {{ query: Query subscription: Subscription }}
+            {QUERY}
+            type Subscription {{
+              user: User
+                @http(url: "http://localhost:3000/user", pollIntervalMs: 10, pollEmitUnchanged: true)
+            }}
+            "#
+        );
+        let field = subscription_field(&sdl).unwrap();
+
+        assert!(field.poll.unwrap().emit_unchanged);
+    }
+
+    #[test]
+    fn test_poll_invalid() {
+        let zero_interval = format!(
+            r#"
+            schema {{ query: Query subscription: Subscription }}
+            {QUERY}
+            type Subscription {{
+              user: User @http(url: "http://localhost:3000/user", pollIntervalMs: 0)
+            }}
+            "#
+        );
+        assert!(subscription_field(&zero_interval).is_none());
+
+        let without_poll = format!(
+            r#"
+            schema {{ query: Query subscription: Subscription }}
+            {QUERY}
+            type Subscription {{
+              user: User @http(url: "http://localhost:3000/user")
+            }}
+            "#
+        );
+        assert!(subscription_field(&without_poll).is_none());
+
+        let outside_subscription = r#"
+            schema { query: Query }
+
+            type Query {
+              user: User @http(url: "http://localhost:3000/user", pollIntervalMs: 2000)
+            }
+
+            type User {
+              id: Int
+            }
+        "#;
+        let config = config::Config::from_sdl(outside_subscription)
+            .to_result()
+            .unwrap();
+        assert!(Blueprint::try_from(&ConfigModule::from(config)).is_err());
+    }
 }
@@ -1,11 +1,40 @@
-use tailcall_valid::Valid;
+use std::collections::BTreeMap;
 
+use async_graphql_value::ConstValue;
+use tailcall_valid::{Valid, Validator};
+
+use super::expr::validate_data_with_schema;
 use crate::core::blueprint::*;
 use crate::core::config;
 use crate::core::config::Field;
 use crate::core::ir::model::IR;
+use crate::core::mustache::Mustache;
 use crate::core::try_fold::TryFold;
 
+/// Renders the `value` of @modify with the server's vars, the env is already
+/// rendered when the config is read, and parses it as the field's type.
+fn compile_value(config: &ConfigModule, field: &Field, value: &str) -> Valid<IR, BlueprintError> {
+    let vars = config
+        .server
+        .vars
+        .iter()
+        .map(|var| (var.key.clone(), var.value.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let rendered = Mustache::parse(value).render(&serde_json::json!({ "vars": vars }));
+
+    let value = match field.type_of.name().as_str() {
+        "String" | "ID" => ConstValue::String(rendered),
+        _ => serde_json::from_str(&rendered)
+            .ok()
+            .and_then(|value| ConstValue::from_json(value).ok())
+            .unwrap_or(ConstValue::String(rendered)),
+    };
+
+    validate_data_with_schema(config, field, value.clone())
+        .map(|_| IR::Dynamic(DynamicValue::Value(value)))
+        .trace("value")
+}
+
 pub fn update_modify<'a>() -> TryFold<
     'a,
     (&'a ConfigModule, &'a Field, &'a config::Type, &'a str),
@@ -15,6 +44,19 @@ pub fn update_modify<'a>() -> TryFold<
     TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, BlueprintError>::new(
         |(config, field, type_of, _), mut b_field| {
             if let Some(modify) = field.modify.as_ref() {
+                if modify.omit_if_null.unwrap_or_default() {
+                    if !field.type_of.is_nullable() {
+                        return Valid::fail(BlueprintError::OmitIfNullOnNonNullField)
+                            .trace("omitIfNull");
+                    }
+                    b_field.omit_if_null = true;
+                }
+                if let Some(value) = &modify.value {
+                    match compile_value(config, field, value).to_result() {
+                        Ok(resolver) => b_field.resolver = Some(resolver),
+                        Err(err) => return Valid::from_validation_err(err),
+                    }
+                }
                 if let Some(new_name) = &modify.name {
                     for name in type_of.implements.iter() {
                         let interface = config.find_type(name);
@@ -36,3 +78,86 @@ pub fn update_modify<'a>() -> TryFold<
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::ConstValue;
+    use tailcall_valid::Validator;
+
+    use crate::core::blueprint::{Blueprint, Definition, DynamicValue};
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::ir::model::IR;
+
+    fn blueprint(sdl: &str) -> Result<Blueprint, String> {
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        Blueprint::try_from(&ConfigModule::from(config)).map_err(|err| err.to_string())
+    }
+
+    fn field_resolver<'a>(blueprint: &'a Blueprint, type_name: &str, field: &str) -> &'a IR {
+        blueprint
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Object(object) if object.name == type_name => object
+                    .fields
+                    .iter()
+                    .find(|f| f.name == field)
+                    .and_then(|f| f.resolver.as_ref()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_omit_if_null_on_non_null_field() {
+        let error = blueprint(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { foo: Foo @http(url: "http://localhost/foo") }
+            type Foo { bar: Int! @modify(omitIfNull: true) }
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(error.contains("omitIfNull can only be used on nullable fields"));
+    }
+
+    #[test]
+    fn test_value() {
+        let blueprint = blueprint(
+            r#"
+            schema @server(vars: [{key: "REGION", value: "eu-west-1"}]) @upstream {
+                query: Query
+            }
+            type Query { foo: Foo @http(url: "http://localhost/foo") }
+            type Foo {
+                region: String @modify(value: "{{.vars.REGION}}")
+                limit: Int @modify(value: "10")
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            field_resolver(&blueprint, "Foo", "region"),
+            IR::Dynamic(DynamicValue::Value(value)) if *value == ConstValue::from("eu-west-1")
+        ));
+        assert!(matches!(
+            field_resolver(&blueprint, "Foo", "limit"),
+            IR::Dynamic(DynamicValue::Value(value)) if *value == ConstValue::from(10)
+        ));
+    }
+
+    #[test]
+    fn test_value_type_mismatch() {
+        let result = blueprint(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { foo: Foo @http(url: "http://localhost/foo") }
+            type Foo { limit: Int @modify(value: "ten") }
+            "#,
+        );
+
+        assert!(result.is_err());
+    }
+}
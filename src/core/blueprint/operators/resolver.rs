@@ -55,6 +55,37 @@ pub fn compile_resolver(
     .map(Some)
 }
 
+/// Wraps the field's resolver with the ones of `@operationOverrides`, picking
+/// one of them depending on the operation the field is resolved in. The
+/// operations without an override use the field's resolver, or its value in
+/// the parent when it has none.
+fn compile_operation_overrides(
+    inputs: &CompileResolver,
+    resolver: Option<IR>,
+    field_name: &str,
+) -> Valid<Option<IR>, BlueprintError> {
+    let Some(overrides) = &inputs.field.operation_overrides else {
+        return Valid::succeed(resolver);
+    };
+
+    let compile = |resolver: &Option<Resolver>, name: &str| match resolver {
+        Some(resolver) => compile_resolver(inputs, resolver).trace(name),
+        None => Valid::succeed(None),
+    };
+
+    compile(&overrides.query, "query")
+        .zip(compile(&overrides.mutation, "mutation"))
+        .zip(compile(&overrides.subscription, "subscription"))
+        .map(|((query, mutation), subscription)| {
+            let default = resolver.unwrap_or(IR::ContextPath(vec![field_name.to_string()]));
+            Some(IR::ByOperation {
+                query: Box::new(query.unwrap_or(default.clone())),
+                mutation: Box::new(mutation.unwrap_or(default.clone())),
+                subscription: Box::new(subscription.unwrap_or(default)),
+            })
+        })
+}
+
 pub fn update_resolver<'a>(
     operation_type: &'a GraphQLOperationType,
     object_name: &'a str,
@@ -76,6 +107,10 @@ pub fn update_resolver<'a>(
                 1 => resolvers.pop().unwrap(),
                 _ => Some(IR::Merge(resolvers.into_iter().flatten().collect())),
             })
+            .and_then(|resolver| {
+                compile_operation_overrides(&inputs, resolver, &b_field.name)
+                    .trace(config::OperationOverrides::trace_name().as_str())
+            })
             .map(|resolver| b_field.resolver(resolver))
             .and_then(|b_field| {
                 b_field
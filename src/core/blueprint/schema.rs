@@ -79,10 +79,20 @@ fn validate_mutation(config: &Config) -> Valid<(), BlueprintError> {
     }
 }
 
+fn validate_subscription(config: &Config) -> Valid<(), BlueprintError> {
+    match config.schema.subscription.as_ref() {
+        Some(subscription_type_name) if config.find_type(subscription_type_name).is_none() => {
+            Valid::fail(BlueprintError::SubscriptionTypeNotDefined).trace(subscription_type_name)
+        }
+        _ => Valid::succeed(()),
+    }
+}
+
 pub fn to_schema<'a>() -> TryFoldConfig<'a, SchemaDefinition> {
     TryFoldConfig::new(|config, _| {
         validate_query(config)
             .and(validate_mutation(config))
+            .and(validate_subscription(config))
             .and(Valid::from_option(
                 config.schema.query.as_ref(),
                 BlueprintError::QueryRootIsMissing,
@@ -91,6 +101,7 @@ pub fn to_schema<'a>() -> TryFoldConfig<'a, SchemaDefinition> {
             .map(|(query_type_name, directive)| SchemaDefinition {
                 query: query_type_name.to_owned(),
                 mutation: config.schema.mutation.clone(),
+                subscription: config.schema.subscription.clone(),
                 directives: vec![directive],
             })
     })
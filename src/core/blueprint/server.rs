@@ -1,16 +1,22 @@
 use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::{Display, Formatter};
 use std::net::{AddrParseError, IpAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
+use async_graphql_value::ConstValue;
 use derive_setters::Setters;
 use http::header::{HeaderMap, HeaderName, HeaderValue};
 use rustls_pki_types::CertificateDer;
 use tailcall_valid::{Valid, ValidationError, Validator};
+use url::Url;
 
 use super::BlueprintError;
 use crate::core::blueprint::Cors;
-use crate::core::config::{self, ConfigModule, HttpVersion, PrivateKey, Routes};
+use crate::core::config::{self, ConfigModule, ContextTransform, HttpVersion, PrivateKey, Routes};
+use crate::core::http::API_URL_PREFIX;
+use crate::core::mustache::Mustache;
 
 #[derive(Clone, Debug, Setters)]
 pub struct Server {
@@ -18,6 +24,7 @@ pub struct Server {
     pub enable_cache_control_header: bool,
     pub enable_set_cookie_header: bool,
     pub enable_introspection: bool,
+    pub enable_lenient_coercion: bool,
     pub enable_query_validation: bool,
     pub enable_response_validation: bool,
     pub enable_batch_requests: bool,
@@ -34,6 +41,161 @@ pub struct Server {
     pub cors: Option<Cors>,
     pub experimental_headers: HashSet<HeaderName>,
     pub routes: Routes,
+    pub playground: Option<Playground>,
+    pub compression: Option<Compression>,
+    pub max_fetch_count: Option<usize>,
+    pub max_complexity: Option<usize>,
+    pub unix_socket: Option<UnixSocket>,
+    pub admin: Option<Admin>,
+    pub tenant: Option<Tenant>,
+    pub context: Vec<ContextEntry>,
+    pub upload: UploadLimits,
+    pub etag: Option<Etag>,
+}
+
+/// The admin endpoints, present only when they're configured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Admin {
+    pub port: u16,
+    pub token: String,
+}
+
+/// Identifies the tenant of each request, present only when it's configured.
+#[derive(Clone, Debug)]
+pub struct Tenant {
+    pub id: Mustache,
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Tenant {
+    /// Whether the id can be used in a URL, only a DNS label is, so that the
+    /// id can't change more than the host, eg. with `a.com:1234/x#`.
+    pub fn is_valid_id(id: &str) -> bool {
+        (1..=63).contains(&id.len())
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !id.starts_with('-')
+            && !id.ends_with('-')
+    }
+
+    /// Whether the URLs of the tenants can be on the scheme, host and port
+    /// of the URL. Only `http` and `https` are, and on their default port
+    /// unless the pattern names one, eg. `*.api.example.com:8443`.
+    pub fn is_allowed(&self, url: &Url) -> bool {
+        let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+            return false;
+        };
+        if !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+
+        self.allowed_hosts.iter().any(|pattern| {
+            let (pattern, pattern_port) = match pattern.rsplit_once(':') {
+                Some((pattern, port)) => (pattern, port.parse().ok()),
+                None => (pattern.as_str(), url.port().is_none().then_some(port)),
+            };
+            let host_matches = match pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .and_then(|subdomain| subdomain.strip_suffix('.'))
+                    .is_some_and(|subdomain| !subdomain.is_empty()),
+                None => host == pattern,
+            };
+            host_matches && pattern_port == Some(port)
+        })
+    }
+}
+
+/// A value of the request context, computed once per request.
+#[derive(Clone, Debug)]
+pub struct ContextEntry {
+    pub key: String,
+    pub source: ContextSource,
+    pub transform: Option<ContextTransform>,
+    /// Used when the value can't be computed, the request fails without it.
+    pub default: Option<ConstValue>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ContextSource {
+    Value(Mustache),
+    Http {
+        url: Mustache,
+        headers: Vec<(HeaderName, Mustache)>,
+    },
+}
+
+/// Settings of the embedded GraphQL IDE, present only when it's enabled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Playground {
+    pub path: String,
+    pub default_query: Option<String>,
+    pub headers: Vec<(String, String)>,
+}
+
+/// Encodings the responses can be compressed with, present only when
+/// compression is configured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compression {
+    pub gzip: bool,
+    pub brotli: bool,
+    pub min_size: usize,
+}
+
+impl From<config::Compression> for Compression {
+    fn from(compression: config::Compression) -> Self {
+        Compression {
+            gzip: compression.enable_gzip(),
+            brotli: compression.enable_brotli(),
+            min_size: compression.get_min_size(),
+        }
+    }
+}
+
+/// The responses tagged with an `ETag`, present only when it's configured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Etag {
+    pub post: bool,
+}
+
+impl From<config::Etag> for Etag {
+    fn from(etag: config::Etag) -> Self {
+        Etag { post: etag.enable_post() }
+    }
+}
+
+/// The limits of the files sent with the multipart requests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadLimits {
+    pub max_file_size: usize,
+    pub max_files: usize,
+}
+
+impl From<config::UploadLimits> for UploadLimits {
+    fn from(limits: config::UploadLimits) -> Self {
+        UploadLimits {
+            max_file_size: limits.get_max_file_size(),
+            max_files: limits.get_max_files(),
+        }
+    }
+}
+
+/// A unix domain socket the server listens on instead of the TCP address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnixSocket {
+    /// A socket file that is created with the given permissions and removed
+    /// when the server shuts down.
+    Path { path: PathBuf, mode: Option<u32> },
+    /// A socket in the abstract namespace of Linux, which has no file.
+    Abstract(String),
+}
+
+impl Display for UnixSocket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnixSocket::Path { path, .. } => write!(f, "unix://{}", path.display()),
+            UnixSocket::Abstract(name) => write!(f, "unix://@{}", name),
+        }
+    }
 }
 
 /// Mimic of mini_v8::Script that's wasm compatible
@@ -121,12 +283,32 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
                     .as_ref()
                     .and_then(|headers| headers.get_cors()),
             ))
+            .fuse(validate_playground(
+                config_server.get_playground(),
+                &config_server.get_routes(),
+            ))
+            .fuse(
+                validate_listen(&config_server)
+                    .zip(validate_admin(&config_server))
+                    .zip(validate_tenant(&config_server))
+                    .zip(validate_context(&config_server)),
+            )
             .map(
-                |(hostname, http, response_headers, script, experimental_headers, cors)| Server {
+                |(
+                    hostname,
+                    http,
+                    response_headers,
+                    script,
+                    experimental_headers,
+                    cors,
+                    playground,
+                    (((unix_socket, admin), tenant), context),
+                )| Server {
                     enable_apollo_tracing: (config_server).enable_apollo_tracing(),
                     enable_cache_control_header: (config_server).enable_cache_control(),
                     enable_set_cookie_header: (config_server).enable_set_cookies(),
                     enable_introspection: (config_server).enable_introspection(),
+                    enable_lenient_coercion: (config_server).enable_lenient_coercion(),
                     enable_query_validation: (config_server).enable_query_validation(),
                     enable_response_validation: (config_server).enable_http_validation(),
                     enable_batch_requests: (config_server).enable_batch_requests(),
@@ -143,6 +325,16 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
                     script,
                     cors,
                     routes: config_server.get_routes(),
+                    playground,
+                    compression: config_server.compression.clone().map(Compression::from),
+                    max_fetch_count: config_server.get_max_fetch_count(),
+                    max_complexity: config_server.get_max_complexity(),
+                    unix_socket,
+                    admin,
+                    tenant,
+                    context,
+                    upload: UploadLimits::from(config_server.upload.clone().unwrap_or_default()),
+                    etag: config_server.etag.clone().map(Etag::from),
                 },
             )
             .to_result()
@@ -176,6 +368,229 @@ fn validate_cors(cors: Option<config::cors::Cors>) -> Valid<Option<Cors>, Bluepr
         .trace("schema")
 }
 
+fn validate_playground(
+    playground: config::Playground,
+    routes: &Routes,
+) -> Valid<Option<Playground>, BlueprintError> {
+    if !playground.is_enabled() {
+        return Valid::succeed(None);
+    }
+
+    let path = playground.get_path();
+    let path_valid = if !path.starts_with('/') {
+        Valid::fail(BlueprintError::InvalidPlaygroundPath(path.clone()))
+    } else if &path == routes.graphql()
+        || &path == routes.status()
+        || path.starts_with(API_URL_PREFIX)
+    {
+        Valid::fail(BlueprintError::PlaygroundPathConflict(path.clone()))
+    } else {
+        Valid::succeed(())
+    };
+
+    let headers = Valid::from_iter(playground.headers.iter(), |kv| {
+        let name = match HeaderName::from_bytes(kv.key.as_bytes()) {
+            Ok(_) => Valid::succeed(kv.key.clone()),
+            Err(e) => Valid::fail(BlueprintError::InvalidHeaderName(e)),
+        };
+        let value = match HeaderValue::from_str(&kv.value) {
+            Ok(_) => Valid::succeed(kv.value.clone()),
+            Err(e) => Valid::fail(BlueprintError::InvalidHeaderValue(e)),
+        };
+
+        name.zip(value)
+    })
+    .trace("headers");
+
+    path_valid
+        .trace("path")
+        .and(headers)
+        .map(|headers| Some(Playground { path, default_query: playground.default_query, headers }))
+        .trace("playground")
+        .trace("@server")
+        .trace("schema")
+}
+
+/// The minimum length of the admin token, so that it can't be guessed.
+const MIN_ADMIN_TOKEN_LEN: usize = 16;
+
+fn validate_admin(server: &config::Server) -> Valid<Option<Admin>, BlueprintError> {
+    let Some(admin) = server.admin.as_ref() else {
+        return Valid::succeed(None);
+    };
+
+    if admin.token.is_empty() {
+        Valid::fail(BlueprintError::EmptyAdminToken).trace("token")
+    } else if !Mustache::parse(&admin.token).is_const() {
+        // the templates the reader couldn't render are kept as they're written
+        Valid::fail(BlueprintError::UnresolvedAdminToken(admin.token.clone())).trace("token")
+    } else if admin.token.chars().count() < MIN_ADMIN_TOKEN_LEN {
+        Valid::fail(BlueprintError::AdminTokenTooShort(MIN_ADMIN_TOKEN_LEN)).trace("token")
+    } else if server.listen.is_none() && admin.port == server.get_port() {
+        Valid::fail(BlueprintError::AdminPortConflict(admin.port)).trace("port")
+    } else {
+        Valid::succeed(Some(Admin { port: admin.port, token: admin.token.clone() }))
+    }
+    .trace("admin")
+    .trace("@server")
+    .trace("schema")
+}
+
+fn validate_tenant(server: &config::Server) -> Valid<Option<Tenant>, BlueprintError> {
+    let Some(tenant) = server.tenant.as_ref() else {
+        return Valid::succeed(None);
+    };
+
+    let id = Mustache::parse(&tenant.id);
+    let id_valid = if tenant.id.is_empty() {
+        Valid::fail(BlueprintError::EmptyTenantId)
+    } else if id
+        .expression_segments()
+        .iter()
+        .any(|parts| parts.first().is_some_and(|head| head == "tenant"))
+    {
+        Valid::fail(BlueprintError::RecursiveTenantId)
+    } else {
+        Valid::succeed(id)
+    };
+    let allowed_hosts = if tenant.allowed_hosts.is_empty() {
+        Valid::fail(BlueprintError::TenantWithoutAllowedHosts)
+    } else {
+        Valid::succeed(
+            tenant
+                .allowed_hosts
+                .iter()
+                .map(|host| host.to_lowercase())
+                .collect(),
+        )
+    };
+
+    id_valid
+        .trace("id")
+        .zip(allowed_hosts.trace("allowedHosts"))
+        .map(|(id, allowed_hosts)| Some(Tenant { id, allowed_hosts }))
+        .trace("tenant")
+        .trace("@server")
+        .trace("schema")
+}
+
+fn validate_context(server: &config::Server) -> Valid<Vec<ContextEntry>, BlueprintError> {
+    Valid::from_iter(server.context.iter().enumerate(), |(i, entry)| {
+        let earlier = &server.context[..i];
+        let key = if entry.key.is_empty() {
+            Valid::fail(BlueprintError::EmptyContextKey)
+        } else if earlier.iter().any(|earlier| earlier.key == entry.key) {
+            Valid::fail(BlueprintError::DuplicateContextKey(entry.key.clone()))
+        } else {
+            Valid::succeed(entry.key.clone())
+        }
+        .trace("key");
+
+        // A value can read the ones computed before it
+        let template = |template: &str| {
+            let mustache = Mustache::parse(template);
+            Valid::from_iter(mustache.expression_segments(), |parts| {
+                match parts.as_slice() {
+                    [head, key, ..]
+                        if head == "ctx" && !earlier.iter().any(|earlier| &earlier.key == key) =>
+                    {
+                        Valid::fail(BlueprintError::ContextKeyNotAvailable(key.clone()))
+                    }
+                    _ => Valid::succeed(()),
+                }
+            })
+            .map(|_| mustache)
+        };
+
+        let source = match (&entry.value, &entry.http) {
+            (Some(value), None) => template(value).map(ContextSource::Value).trace("value"),
+            (None, Some(http)) => template(&http.url)
+                .trace("url")
+                .zip(
+                    Valid::from_iter(http.headers.iter(), |header| {
+                        let name = match HeaderName::from_bytes(header.key.as_bytes()) {
+                            Ok(name) => Valid::succeed(name),
+                            Err(e) => Valid::fail(BlueprintError::InvalidHeaderName(e)),
+                        };
+                        name.zip(template(&header.value)).trace(&header.key)
+                    })
+                    .trace("headers"),
+                )
+                .map(|(url, headers)| ContextSource::Http { url, headers })
+                .trace("http"),
+            _ => Valid::fail(BlueprintError::ContextEntryWithoutSource),
+        };
+
+        let default = match entry.default.clone().map(ConstValue::from_json).transpose() {
+            Ok(default) => Valid::succeed(default),
+            Err(e) => Valid::fail(BlueprintError::InvalidJson(e.into())),
+        }
+        .trace("default");
+
+        key.zip(source)
+            .zip(default)
+            .map(|((key, source), default)| ContextEntry {
+                key,
+                source,
+                transform: entry.transform,
+                default,
+            })
+            .trace(&i.to_string())
+    })
+    .trace("context")
+    .trace("@server")
+    .trace("schema")
+}
+
+fn validate_listen(server: &config::Server) -> Valid<Option<UnixSocket>, BlueprintError> {
+    let Some(listen) = server.listen.as_ref() else {
+        return match server.socket_mode {
+            Some(_) => Valid::fail(BlueprintError::SocketModeWithoutUnixSocket)
+                .trace("socketMode")
+                .trace("@server")
+                .trace("schema"),
+            None => Valid::succeed(None),
+        };
+    };
+
+    let socket = match listen.strip_prefix("unix://") {
+        Some(_) if !cfg!(unix) => Valid::fail(BlueprintError::UnixSocketNotSupported),
+        Some(_) if server.clone().get_version() == HttpVersion::HTTP2 => {
+            Valid::fail(BlueprintError::UnixSocketRequiresHTTP1)
+        }
+        Some(name) if name.starts_with('@') => {
+            if !cfg!(any(target_os = "linux", target_os = "android")) {
+                Valid::fail(BlueprintError::AbstractSocketNotSupported)
+            } else if server.socket_mode.is_some() {
+                Valid::fail(BlueprintError::SocketModeWithoutUnixSocket)
+            } else {
+                Valid::succeed(UnixSocket::Abstract(name[1..].to_string()))
+            }
+        }
+        Some(path) if !path.is_empty() => validate_socket_mode(server.socket_mode.as_deref())
+            .map(|mode| UnixSocket::Path { path: PathBuf::from(path), mode }),
+        _ => Valid::fail(BlueprintError::InvalidListenAddress(listen.clone())),
+    };
+
+    socket
+        .map(Some)
+        .trace("listen")
+        .trace("@server")
+        .trace("schema")
+}
+
+fn validate_socket_mode(mode: Option<&str>) -> Valid<Option<u32>, BlueprintError> {
+    match mode {
+        None => Valid::succeed(None),
+        Some(mode) => match u32::from_str_radix(mode.trim_start_matches("0o"), 8) {
+            Ok(bits) if bits <= 0o777 => Valid::succeed(Some(bits)),
+            _ => {
+                Valid::fail(BlueprintError::InvalidSocketMode(mode.to_string())).trace("socketMode")
+            }
+        },
+    }
+}
+
 fn validate_hostname(hostname: String) -> Valid<IpAddr, BlueprintError> {
     if hostname == "localhost" {
         Valid::succeed(IpAddr::from([127, 0, 0, 1]))
@@ -236,11 +651,295 @@ fn handle_experimental_headers(
 
 #[cfg(test)]
 mod tests {
-    use crate::core::config::ConfigModule;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use tailcall_valid::Validator;
+
+    use super::UnixSocket;
+    use crate::core::config::{self, ConfigModule, ConfigReaderContext, KeyValue};
+    use crate::core::resource_reader::FileRead;
+    use crate::core::tests::TestEnvIO;
 
     #[test]
     fn test_try_from_default() {
         let actual = super::Server::try_from(ConfigModule::default());
         assert!(actual.is_ok())
     }
+
+    fn unix_socket(
+        listen: &str,
+        socket_mode: Option<&str>,
+    ) -> Result<Option<UnixSocket>, super::ValidationError<super::BlueprintError>> {
+        let mut config = config::Config::default();
+        config.server.listen = Some(listen.to_string());
+        config.server.socket_mode = socket_mode.map(str::to_string);
+        super::Server::try_from(ConfigModule::from(config)).map(|server| server.unix_socket)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_listen_unix_socket() {
+        assert_eq!(
+            unix_socket("unix:///var/run/tailcall.sock", Some("0660")).unwrap(),
+            Some(UnixSocket::Path {
+                path: PathBuf::from("/var/run/tailcall.sock"),
+                mode: Some(0o660)
+            })
+        );
+        assert!(unix_socket("unix:///var/run/tailcall.sock", Some("0999")).is_err());
+        assert!(unix_socket("unix://", None).is_err());
+        assert!(unix_socket("tcp://127.0.0.1:8000", None).is_err());
+
+        let mut config = config::Config::default();
+        config.server.socket_mode = Some("0660".to_string());
+        assert!(super::Server::try_from(ConfigModule::from(config)).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_listen_abstract_socket() {
+        assert_eq!(
+            unix_socket("unix://@tailcall", None).unwrap(),
+            Some(UnixSocket::Abstract("tailcall".to_string()))
+        );
+        assert!(unix_socket("unix://@tailcall", Some("0660")).is_err());
+    }
+
+    #[cfg(not(unix))]
+    #[test]
+    fn test_listen_unix_socket_unsupported() {
+        assert!(unix_socket("unix:///var/run/tailcall.sock", None).is_err());
+    }
+
+    fn admin(port: u16, token: &str) -> Result<Option<super::Admin>, String> {
+        let mut config = config::Config::default();
+        config.server.admin = Some(config::Admin { port, token: token.to_string() });
+        super::Server::try_from(ConfigModule::from(config))
+            .map(|server| server.admin)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_admin() {
+        let token = "2c1f3e4a5b6d7e8f";
+        assert_eq!(
+            admin(9000, token).unwrap(),
+            Some(super::Admin { port: 9000, token: token.to_string() })
+        );
+        assert!(admin(9000, "").unwrap_err().contains("admin token"));
+        assert!(admin(9000, "secret")
+            .unwrap_err()
+            .contains("at least 16 characters"));
+        assert!(admin(8000, token)
+            .unwrap_err()
+            .contains("already used by the server"));
+    }
+
+    #[test]
+    fn test_admin_token_from_unset_env() {
+        let sdl = r#"
+            schema @server(admin: {port: 9000, token: "{{.env.ADMIN_TOKEN}}"}) { query: Query }
+            type Query { hello: String @expr(body: "hello") }
+        "#;
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.env = Arc::new(TestEnvIO::from_iter([]));
+        let file = FileRead { content: sdl.to_string(), path: "main.graphql".to_string() }
+            .render(&ConfigReaderContext::new(&runtime));
+        let config = config::Config::from_sdl(&file.content).to_result().unwrap();
+
+        let error = super::Server::try_from(ConfigModule::from(config))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("The admin token `{{.env.ADMIN_TOKEN}}` isn't resolved"));
+    }
+
+    fn tenant(id: &str, allowed_hosts: &[&str]) -> Result<Option<super::Tenant>, String> {
+        let mut config = config::Config::default();
+        config.server.tenant = Some(config::Tenant {
+            id: id.to_string(),
+            allowed_hosts: allowed_hosts.iter().map(|host| host.to_string()).collect(),
+        });
+        super::Server::try_from(ConfigModule::from(config))
+            .map(|server| server.tenant)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_tenant() {
+        let tenant = tenant(
+            "{{.headers.x-tenant-id}}",
+            &["acme.api.example.com", "*.eu.Example.com"],
+        )
+        .unwrap()
+        .unwrap();
+
+        let is_allowed = |url: &str| tenant.is_allowed(&url::Url::parse(url).unwrap());
+        assert!(is_allowed("http://acme.api.example.com/users"));
+        assert!(is_allowed("https://acme.api.example.com:443/users"));
+        assert!(is_allowed("https://globex.eu.example.com/users"));
+        assert!(!is_allowed("http://eu.example.com/users"));
+        assert!(!is_allowed("http://globex.api.example.com/users"));
+        assert!(!is_allowed("http://attacker.com/users"));
+        assert!(!is_allowed("http://globexeu.example.com/users"));
+        assert!(!is_allowed("http://acme.api.example.com:1234/users"));
+        assert!(!is_allowed("ftp://acme.api.example.com/users"));
+
+        let tenant = tenant("{{.headers.x-tenant-id}}", &["*.svc:8080"])
+            .unwrap()
+            .unwrap();
+        assert!(tenant.is_allowed(&url::Url::parse("http://acme.svc:8080/").unwrap()));
+        assert!(!tenant.is_allowed(&url::Url::parse("http://acme.svc/").unwrap()));
+    }
+
+    #[test]
+    fn test_tenant_id() {
+        assert!(super::Tenant::is_valid_id("acme"));
+        assert!(super::Tenant::is_valid_id("acme-eu-1"));
+        assert!(!super::Tenant::is_valid_id(""));
+        assert!(!super::Tenant::is_valid_id("-acme"));
+        assert!(!super::Tenant::is_valid_id("a.com"));
+        assert!(!super::Tenant::is_valid_id("a.com:1234/x#"));
+        assert!(!super::Tenant::is_valid_id(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn test_invalid_tenant() {
+        assert!(tenant("", &["api.example.com"])
+            .unwrap_err()
+            .contains("tenant id can't be empty"));
+        assert!(tenant("{{.tenant.id}}", &["api.example.com"])
+            .unwrap_err()
+            .contains("can't use {{.tenant.id}}"));
+        assert!(tenant("{{.headers.x-tenant-id}}", &[])
+            .unwrap_err()
+            .contains("allowedHosts"));
+    }
+
+    fn context(context: serde_json::Value) -> Result<Vec<super::ContextEntry>, String> {
+        let mut config = config::Config::default();
+        config.server.context = serde_json::from_value(context).unwrap();
+        super::Server::try_from(ConfigModule::from(config))
+            .map(|server| server.context)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_context() {
+        let context = context(serde_json::json!([
+            {"key": "locale", "value": "{{.headers.accept-language}}", "default": "en"},
+            {"key": "flags", "http": {"url": "http://flags/{{.ctx.locale}}"}},
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            context.iter().map(|entry| &entry.key).collect::<Vec<_>>(),
+            ["locale", "flags"]
+        );
+        assert!(matches!(
+            context[1].source,
+            super::ContextSource::Http { .. }
+        ));
+    }
+
+    #[test]
+    fn test_invalid_context() {
+        assert!(context(serde_json::json!([{"key": "", "value": "en"}]))
+            .unwrap_err()
+            .contains("context key can't be empty"));
+        assert!(context(serde_json::json!([
+            {"key": "locale", "value": "en"},
+            {"key": "locale", "value": "fr"},
+        ]))
+        .unwrap_err()
+        .contains("`locale` is already used"));
+        assert!(context(serde_json::json!([{"key": "locale"}]))
+            .unwrap_err()
+            .contains("either `value` or `http`"));
+        assert!(context(serde_json::json!([
+            {"key": "path", "value": "/{{.ctx.locale}}"},
+            {"key": "locale", "value": "en"},
+        ]))
+        .unwrap_err()
+        .contains("ctx.locale is not available"));
+    }
+
+    fn server_with_playground(playground: config::Playground) -> ConfigModule {
+        let mut config = config::Config::default();
+        config.server.playground = Some(playground);
+        ConfigModule::from(config)
+    }
+
+    #[test]
+    fn test_playground_disabled_by_default() {
+        let server = super::Server::try_from(ConfigModule::default()).unwrap();
+        assert_eq!(server.playground, None);
+
+        let config = server_with_playground(config::Playground {
+            path: Some("/ide".to_string()),
+            ..Default::default()
+        });
+        let server = super::Server::try_from(config).unwrap();
+        assert_eq!(server.playground, None);
+    }
+
+    #[test]
+    fn test_playground_enabled() {
+        let config = server_with_playground(config::Playground {
+            enabled: Some(true),
+            ..Default::default()
+        });
+        let server = super::Server::try_from(config).unwrap();
+        let playground = server.playground.unwrap();
+
+        assert_eq!(playground.path, "/playground");
+        assert!(playground.headers.is_empty());
+    }
+
+    #[test]
+    fn test_playground_path_override() {
+        let config = server_with_playground(config::Playground {
+            enabled: Some(true),
+            path: Some("/ide".to_string()),
+            default_query: Some("{ users { id } }".to_string()),
+            headers: vec![KeyValue {
+                key: "Authorization".to_string(),
+                value: "Bearer <token>".to_string(),
+            }],
+        });
+        let server = super::Server::try_from(config).unwrap();
+
+        assert_eq!(
+            server.playground,
+            Some(super::Playground {
+                path: "/ide".to_string(),
+                default_query: Some("{ users { id } }".to_string()),
+                headers: vec![("Authorization".to_string(), "Bearer <token>".to_string())],
+            })
+        );
+    }
+
+    #[test]
+    fn test_playground_invalid_path() {
+        for path in ["ide", "/graphql", "/status", "/api/ide"] {
+            let config = server_with_playground(config::Playground {
+                enabled: Some(true),
+                path: Some(path.to_string()),
+                ..Default::default()
+            });
+
+            assert!(super::Server::try_from(config).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_playground_invalid_header() {
+        let config = server_with_playground(config::Playground {
+            enabled: Some(true),
+            headers: vec![KeyValue { key: "in valid".to_string(), value: "a".to_string() }],
+            ..Default::default()
+        });
+
+        assert!(super::Server::try_from(config).is_err());
+    }
 }
@@ -6,9 +6,10 @@ use url::Url;
 
 use super::{BlueprintError, TryFoldConfig};
 use crate::core::config::{
-    self, Apollo, ConfigModule, KeyValue, PrometheusExporter, StdoutExporter,
+    self, Apollo, ConfigModule, KeyValue, LogFormat, PrometheusExporter, StdoutExporter,
 };
 use crate::core::directive::DirectiveCodec;
+use crate::core::tracing::log_filter as log_filter_of;
 use crate::core::try_fold::TryFold;
 
 #[derive(Debug, Clone)]
@@ -29,6 +30,10 @@ pub enum TelemetryExporter {
 pub struct Telemetry {
     pub export: Option<TelemetryExporter>,
     pub request_headers: Vec<String>,
+    pub client_name_headers: Vec<String>,
+    pub client_version_headers: Vec<String>,
+    pub log_format: LogFormat,
+    pub log_filter: Option<String>,
 }
 
 fn to_url(url: &str) -> Valid<Url, BlueprintError> {
@@ -60,6 +65,14 @@ fn to_headers(headers: Vec<KeyValue>) -> Valid<HeaderMap, BlueprintError> {
 
 pub fn to_opentelemetry<'a>() -> TryFold<'a, ConfigModule, Telemetry, BlueprintError> {
     TryFoldConfig::<Telemetry>::new(|config, up| {
+        // The client is identified in the logs and spans whether the telemetry
+        // is exported or not.
+        let up = Telemetry {
+            client_name_headers: config.telemetry.client_name_headers.clone(),
+            client_version_headers: config.telemetry.client_version_headers.clone(),
+            log_format: config.telemetry.log_format,
+            ..up
+        };
         if let Some(export) = config.telemetry.export.as_ref() {
             let export: Valid<TelemetryExporter, BlueprintError> = match export {
                 config::TelemetryExporter::Stdout(config) => {
@@ -80,12 +93,35 @@ pub fn to_opentelemetry<'a>() -> TryFold<'a, ConfigModule, Telemetry, BlueprintE
                 .map(|export| Telemetry {
                     export: Some(export),
                     request_headers: config.telemetry.request_headers.clone(),
+                    ..up
                 })
                 .trace(config::Telemetry::trace_name().as_str())
         } else {
             Valid::succeed(up)
         }
     })
+    .and(to_log_filter())
+}
+
+fn to_log_filter<'a>() -> TryFold<'a, ConfigModule, Telemetry, BlueprintError> {
+    TryFoldConfig::<Telemetry>::new(|config, up| {
+        validate_log_filter(config.telemetry.log_filter.as_deref())
+            .map(|log_filter| Telemetry { log_filter, ..up })
+            .trace(config::Telemetry::trace_name().as_str())
+    })
+}
+
+/// Parses the filter like the server does, so that it is rejected with the
+/// parse error before the server starts.
+fn validate_log_filter(log_filter: Option<&str>) -> Valid<Option<String>, BlueprintError> {
+    match log_filter {
+        Some(directives) => match log_filter_of(Some(directives)) {
+            Ok(_) => Valid::succeed(Some(directives.to_string())),
+            Err(err) => Valid::fail(BlueprintError::InvalidLogFilter(err.to_string())),
+        }
+        .trace("logFilter"),
+        None => Valid::succeed(None),
+    }
 }
 
 fn validate_apollo(apollo: Apollo) -> Valid<Apollo, BlueprintError> {
@@ -107,9 +143,9 @@ fn validate_graph_ref(graph_ref: &str) -> Valid<(), BlueprintError> {
 
 #[cfg(test)]
 mod tests {
-    use tailcall_valid::Valid;
+    use tailcall_valid::{Valid, Validator};
 
-    use super::validate_graph_ref;
+    use super::{validate_graph_ref, validate_log_filter};
     use crate::core::blueprint::BlueprintError;
 
     #[test]
@@ -133,4 +169,14 @@ mod tests {
             failure("gr@ph_id@variant")
         );
     }
+
+    #[test]
+    fn test_validate_log_filter() {
+        assert_eq!(validate_log_filter(None), Valid::succeed(None));
+        assert_eq!(
+            validate_log_filter(Some("tailcall::core::http=debug")),
+            Valid::succeed(Some("tailcall::core::http=debug".to_string()))
+        );
+        assert!(!validate_log_filter(Some("tailcall=loud")).is_succeed());
+    }
 }
@@ -1,26 +1,46 @@
+use std::collections::BTreeSet;
+
 use tailcall_valid::{Valid, Validator};
 
 use super::BlueprintError;
 use crate::core::blueprint::FieldDefinition;
-use crate::core::config::{ConfigModule, Discriminate, Field, Type, Union};
+use crate::core::config::{ConfigModule, Discriminate, Field, Type};
 use crate::core::ir::model::IR;
 use crate::core::ir::Discriminator;
 use crate::core::try_fold::TryFold;
 
-fn compile_union_resolver(
-    union_name: &str,
-    union_definition: &Union,
+/// Builds the discriminator of the values of a union or interface, from the
+/// field's `@discriminate` or, without it, the keys and the required fields of
+/// the `types`.
+pub(super) fn compile_discriminator(
+    config: &ConfigModule,
+    type_name: &str,
+    types: &BTreeSet<String>,
     discriminate: &Option<Discriminate>,
 ) -> Valid<Discriminator, BlueprintError> {
     let typename_field = discriminate.as_ref().map(|d| d.get_field());
+    let required_fields = types
+        .iter()
+        .filter_map(|name| config.find_type(name).map(|type_of| (name, type_of)))
+        .map(|(name, type_of)| {
+            let fields = type_of
+                .fields
+                .iter()
+                .filter(|(_, field)| !field.type_of.is_nullable())
+                .map(|(field_name, _)| field_name.clone())
+                .collect::<BTreeSet<_>>();
+            (name.clone(), fields)
+        })
+        .collect();
 
-    match Discriminator::new(
-        union_name.to_string(),
-        union_definition.types.clone(),
-        typename_field,
-    )
-    .to_result()
-    {
+    let discriminator = Discriminator::new(type_name.to_string(), types.clone(), typename_field)
+        .and_then(|discriminator| match discriminate {
+            Some(discriminate) => discriminator
+                .with_mapping(discriminate.mapping.clone(), discriminate.default.clone()),
+            None => Valid::succeed(discriminator.with_required_fields(required_fields)),
+        });
+
+    match discriminator.to_result() {
         Ok(discriminator) => Valid::succeed(discriminator),
         Err(e) => Valid::from_validation_err(BlueprintError::from_validation_string(e)),
     }
@@ -35,17 +55,54 @@ pub fn update_union_resolver<'a>(
                 return Valid::succeed(b_field);
             };
 
-            compile_union_resolver(field.type_of.name(), union_definition, &field.discriminate).map(
-                |discriminator| {
-                    b_field.resolver = Some(
-                        b_field
-                            .resolver
-                            .unwrap_or(IR::ContextPath(vec![b_field.name.clone()])),
-                    );
-                    b_field.map_expr(move |expr| IR::Discriminate(discriminator, expr.into()));
-                    b_field
-                },
+            compile_discriminator(
+                config,
+                field.type_of.name(),
+                &union_definition.types,
+                &field.discriminate,
             )
+            .map(|discriminator| {
+                b_field.resolver = Some(
+                    b_field
+                        .resolver
+                        .unwrap_or(IR::ContextPath(vec![b_field.name.clone()])),
+                );
+                b_field.map_expr(move |expr| IR::Discriminate(discriminator, expr.into()));
+                b_field
+            })
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::Validator;
+
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+
+    fn blueprint(mapping: &str) -> Result<Blueprint, String> {
+        let sdl = format!(
+            r#"
+            schema @server @upstream {{ query: Query }}
+            type Query {{
+                pet: Pet @http(url: "http://localhost/pet") @discriminate(mapping: {mapping})
+            }}
+            union Pet = Cat | Dog
+            type Cat {{ meows: Boolean }}
+            type Dog {{ barks: Boolean }}
+            type Fish {{ swims: Boolean }}
+            "#
+        );
+        let config = Config::from_sdl(&sdl).to_result().unwrap();
+        Blueprint::try_from(&ConfigModule::from(config)).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_mapping_targets_are_members() {
+        assert!(blueprint(r#"{cat: "Cat", dog: "Dog"}"#).is_ok());
+
+        let error = blueprint(r#"{cat: "Cat", fish: "Fish"}"#).unwrap_err();
+        assert!(error.contains("The type `Fish` of the `@discriminate` is not a member of Pet"));
+    }
+}
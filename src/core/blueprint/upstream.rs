@@ -1,16 +1,25 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
 
 use derive_setters::Setters;
 use tailcall_valid::{Valid, ValidationError, Validator};
 
 use super::BlueprintError;
-use crate::core::config::{self, Batch, ConfigModule};
+use crate::core::config::{
+    self, Batch, ConfigModule, GraphQLBatching, HttpVersion, SelectedFields,
+};
 
 #[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
 pub struct Proxy {
     pub url: String,
 }
 
+#[derive(PartialEq, Eq, Clone, Debug, Hash, schemars::JsonSchema)]
+pub struct DnsCache {
+    pub max_ttl: u64,
+    pub negative_ttl: u64,
+}
+
 #[derive(PartialEq, Eq, Clone, Debug, Setters, schemars::JsonSchema)]
 pub struct Upstream {
     pub pool_idle_timeout: u64,
@@ -20,15 +29,20 @@ pub struct Upstream {
     pub keep_alive_while_idle: bool,
     pub proxy: Option<Proxy>,
     pub connect_timeout: u64,
+    pub dns_cache: Option<DnsCache>,
+    pub dns_overrides: BTreeMap<String, IpAddr>,
     pub timeout: u64,
     pub tcp_keep_alive: u64,
     pub user_agent: String,
     pub allowed_headers: BTreeSet<String>,
     pub http_cache: u64,
     pub batch: Option<Batch>,
+    pub graphql_batching: Option<GraphQLBatching>,
     pub http2_only: bool,
     pub on_request: Option<String>,
     pub verify_ssl: bool,
+    pub streaming_threshold: Option<usize>,
+    pub selected_fields: SelectedFields,
 }
 
 impl Upstream {
@@ -65,7 +79,10 @@ impl TryFrom<&ConfigModule> for Upstream {
 
         get_batch(&config_upstream)
             .fuse(get_proxy(&config_upstream))
-            .map(|(batch, proxy)| Upstream {
+            .fuse(validate_http_version(&config_upstream))
+            .fuse(validate_durations(&config_upstream))
+            .fuse(get_dns_overrides(&config_upstream))
+            .map(|(batch, proxy, _, _, dns_overrides)| Upstream {
                 pool_idle_timeout: (config_upstream).get_pool_idle_timeout(),
                 pool_max_idle_per_host: (config_upstream).get_pool_max_idle_per_host(),
                 keep_alive_interval: (config_upstream).get_keep_alive_interval(),
@@ -73,15 +90,26 @@ impl TryFrom<&ConfigModule> for Upstream {
                 keep_alive_while_idle: (config_upstream).get_keep_alive_while_idle(),
                 proxy,
                 connect_timeout: (config_upstream).get_connect_timeout(),
+                dns_cache: config_upstream
+                    .dns_cache
+                    .as_ref()
+                    .map(|dns_cache| DnsCache {
+                        max_ttl: dns_cache.get_max_ttl(),
+                        negative_ttl: dns_cache.get_negative_ttl(),
+                    }),
+                dns_overrides,
                 timeout: (config_upstream).get_timeout(),
                 tcp_keep_alive: (config_upstream).get_tcp_keep_alive(),
                 user_agent: (config_upstream).get_user_agent(),
                 allowed_headers,
                 http_cache: (config_upstream).get_http_cache_size(),
                 batch,
+                graphql_batching: config_upstream.graphql_batching.clone(),
                 http2_only: (config_upstream).get_http_2_only(),
                 on_request: (config_upstream).get_on_request(),
                 verify_ssl: (config_upstream).get_verify_ssl(),
+                streaming_threshold: config_upstream.streaming_threshold,
+                selected_fields: config_upstream.selected_fields.clone().unwrap_or_default(),
             })
             .to_result()
     }
@@ -107,3 +135,122 @@ fn get_proxy(upstream: &config::Upstream) -> Valid<Option<Proxy>, BlueprintError
         Valid::succeed(None)
     }
 }
+
+fn get_dns_overrides(
+    upstream: &config::Upstream,
+) -> Valid<BTreeMap<String, IpAddr>, BlueprintError> {
+    Valid::from_iter(
+        upstream.dns_overrides.iter().flatten(),
+        |(host, address)| match address.parse::<IpAddr>() {
+            Ok(address) => Valid::succeed((host.to_lowercase(), address)),
+            Err(_) => Valid::fail(BlueprintError::InvalidDnsOverride(host.clone()))
+                .trace("dnsOverrides")
+                .trace("@upstream"),
+        },
+    )
+    .map(BTreeMap::from_iter)
+}
+
+fn validate_http_version(upstream: &config::Upstream) -> Valid<(), BlueprintError> {
+    match (upstream.http2_only, &upstream.http_version) {
+        (Some(http2_only), Some(version)) if http2_only != (*version == HttpVersion::HTTP2) => {
+            Valid::fail(BlueprintError::ConflictingUpstreamHttpVersion).trace("@upstream")
+        }
+        _ => Valid::succeed(()),
+    }
+}
+
+fn validate_durations(upstream: &config::Upstream) -> Valid<(), BlueprintError> {
+    let durations = [
+        ("connectTimeout", upstream.connect_timeout),
+        (
+            "dnsCache.maxTtl",
+            upstream
+                .dns_cache
+                .as_ref()
+                .and_then(|dns_cache| dns_cache.max_ttl),
+        ),
+        ("keepAliveInterval", upstream.keep_alive_interval),
+        ("keepAliveTimeout", upstream.keep_alive_timeout),
+        ("tcpKeepAlive", upstream.tcp_keep_alive),
+        ("timeout", upstream.timeout),
+    ];
+
+    Valid::from_iter(durations, |(name, duration)| {
+        if duration == Some(0) {
+            Valid::fail(BlueprintError::InvalidUpstreamDuration(name.to_string()))
+                .trace(name)
+                .trace("@upstream")
+        } else {
+            Valid::succeed(())
+        }
+    })
+    .unit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upstream(config: config::Upstream) -> Result<Upstream, ValidationError<BlueprintError>> {
+        Upstream::try_from(&ConfigModule::from(
+            config::Config::default().upstream(config),
+        ))
+    }
+
+    #[test]
+    fn test_http_version() {
+        let http2 = upstream(config::Upstream::default().http_version(Some(HttpVersion::HTTP2)));
+        assert!(http2.unwrap().http2_only);
+
+        let http1 = upstream(config::Upstream::default().http_version(Some(HttpVersion::HTTP1)));
+        assert!(!http1.unwrap().http2_only);
+
+        let conflict = upstream(
+            config::Upstream::default()
+                .http_version(Some(HttpVersion::HTTP1))
+                .http2_only(true),
+        );
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn test_zero_durations() {
+        assert!(upstream(config::Upstream::default().keep_alive_interval(Some(0))).is_err());
+        assert!(upstream(config::Upstream::default().tcp_keep_alive(Some(0))).is_err());
+        assert!(upstream(config::Upstream::default().timeout(Some(0))).is_err());
+        assert!(upstream(config::Upstream::default().pool_max_idle_per_host(Some(0))).is_ok());
+        assert!(upstream(
+            config::Upstream::default().dns_cache(Some(config::DnsCache {
+                max_ttl: Some(0),
+                negative_ttl: None
+            }))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_dns_overrides() {
+        let overrides = |overrides: &[(&str, &str)]| {
+            upstream(
+                config::Upstream::default().dns_overrides(Some(
+                    overrides
+                        .iter()
+                        .map(|(host, address)| (host.to_string(), address.to_string()))
+                        .collect(),
+                )),
+            )
+        };
+
+        let upstream = overrides(&[("API.internal", "10.0.0.5"), ("v6.internal", "::1")]).unwrap();
+        assert_eq!(
+            upstream.dns_overrides,
+            BTreeMap::from([
+                ("api.internal".to_string(), "10.0.0.5".parse().unwrap()),
+                ("v6.internal".to_string(), "::1".parse().unwrap()),
+            ])
+        );
+
+        assert!(overrides(&[("api.internal", "not-an-ip")]).is_err());
+    }
+}
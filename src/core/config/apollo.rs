@@ -25,11 +25,20 @@ pub struct Apollo {
     /// Setting `version` for Apollo.
     #[serde(default, skip_serializing_if = "is_default")]
     pub version: Option<String>,
+    ///
+    /// Setting `usageReporting` to send the operation and field usage
+    /// statistics to Apollo Studio. @default `false`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub usage_reporting: Option<bool>,
+    ///
+    /// Setting `endpoint` for the Apollo usage reports.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub endpoint: Option<String>,
 }
 
 impl Apollo {
     pub fn render_mustache(&mut self, reader_ctx: &ConfigReaderContext) -> anyhow::Result<()> {
-        let Apollo { api_key, graph_ref, user_version, platform, version } = self;
+        let Apollo { api_key, graph_ref, user_version, platform, version, .. } = self;
 
         let api_key_tmpl = Mustache::parse(api_key);
         *api_key = api_key_tmpl.render(reader_ctx);
@@ -48,4 +57,8 @@ impl Apollo {
 
         Ok(())
     }
+
+    pub fn enable_usage_reporting(&self) -> bool {
+        self.usage_reporting.unwrap_or(false)
+    }
 }
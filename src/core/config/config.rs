@@ -2,11 +2,15 @@ use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::{self, Display};
 
 use anyhow::{anyhow, Result};
-use async_graphql::parser::types::ServiceDocument;
+use async_graphql::parser::types::{ConstDirective, DirectiveLocation, ServiceDocument};
+use async_graphql::parser::Positioned;
+use async_graphql::Name;
+use async_graphql_value::ConstValue;
 use derive_setters::Setters;
 use indexmap::IndexMap;
+use prost_reflect::prost_types::FileDescriptorProto;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use strum::IntoEnumIterator;
 use tailcall_typedefs_common::directive_definition::DirectiveDefinition;
 use tailcall_typedefs_common::input_definition::InputDefinition;
@@ -16,15 +20,17 @@ use tailcall_valid::{Valid, Validator};
 use super::directive::Directive;
 use super::from_document::from_document;
 use super::{
-    AddField, Alias, Cache, Call, Discriminate, Expr, GraphQL, Grpc, Http, Link, Modify, Omit,
-    Protected, ResolverSet, Server, Telemetry, Upstream, JS,
+    AddField, Alias, Auth, Cache, Call, CircuitBreaker, Discriminate, Expr, GraphQL, Grpc, Http,
+    KeyValue, Link, Modify, Omit, OperationOverrides, Protected, Resolver, ResolverSet, Server,
+    Telemetry, Upstream, JS,
 };
 use crate::core::config::npo::QueryPath;
 use crate::core::config::source::Source;
-use crate::core::is_default;
+use crate::core::http::{Method, NoContent};
 use crate::core::macros::MergeRight;
 use crate::core::merge_right::MergeRight;
 use crate::core::scalar::Scalar;
+use crate::core::{generator, is_default, pos};
 
 #[derive(
     Serialize,
@@ -85,6 +91,11 @@ pub struct Config {
     #[setters(skip)]
     pub types: BTreeMap<String, Type>,
 
+    ///
+    /// The types that are only extended with `extend type`, and are expected
+    /// to be defined in another file.
+    pub extended_types: BTreeSet<String>,
+
     ///
     /// A map of all the union types in the schema.
     pub unions: BTreeMap<String, Union>,
@@ -99,6 +110,15 @@ pub struct Config {
 
     /// Enable [opentelemetry](https://opentelemetry.io) support
     pub telemetry: Telemetry,
+
+    ///
+    /// Credentials attached to every upstream request made by `@http`
+    /// resolvers.
+    pub auth: Option<Auth>,
+
+    ///
+    /// The directives declared in the schema, eg. `directive @log on FIELD`.
+    pub directives: Vec<CustomDirective>,
 }
 
 ///
@@ -216,10 +236,22 @@ pub struct Field {
     /// Used to overwrite the default discrimination strategy
     pub discriminate: Option<Discriminate>,
 
+    ///
+    /// Credentials attached to the upstream requests of the field
+    pub auth: Option<Auth>,
+
+    ///
+    /// Resolvers that replace the field's one depending on the operation
+    pub operation_overrides: Option<OperationOverrides>,
+
     ///
     /// Resolver for the field
     pub resolvers: ResolverSet,
 
+    ///
+    /// Marks the field as deprecated
+    pub deprecated: Option<Deprecated>,
+
     ///
     /// Any additional directives
     pub directives: Vec<Directive>,
@@ -276,12 +308,179 @@ pub struct Inline {
     pub path: Vec<String>,
 }
 
+///
+/// A directive declared in the schema with `directive @name on ...`, listed by
+/// the introspection next to the built-in ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CustomDirective {
+    pub name: String,
+    pub doc: Option<String>,
+    pub args: IndexMap<String, Arg>,
+    pub locations: Vec<DirectiveLocation>,
+    pub repeatable: bool,
+}
+
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct Arg {
     pub type_of: crate::core::Type,
     pub doc: Option<String>,
     pub modify: Option<Modify>,
     pub default_value: Option<Value>,
+    pub deprecated: Option<Deprecated>,
+}
+
+impl Arg {
+    /// The JSON Schema of the values the argument accepts, with its input
+    /// objects and enums resolved from the config. The recursive input objects
+    /// are added to `defs`, which is expected at `#/$defs`, and referenced.
+    pub fn to_json_schema(&self, config: &Config, defs: &mut Map<String, Value>) -> Value {
+        let mut schema = type_json_schema(&self.type_of, config, &mut Vec::new(), defs);
+        if let Some(doc) = &self.doc {
+            schema["description"] = Value::from(doc.as_str());
+        }
+        if let Some(default_value) = &self.default_value {
+            schema["default"] = default_value.clone();
+        }
+        schema
+    }
+}
+
+/// The schema of a type, the input objects being resolved are kept in `path`
+/// so that a recursive one is defined in `defs` and referenced where it
+/// repeats.
+fn type_json_schema(
+    type_of: &crate::core::Type,
+    config: &Config,
+    path: &mut Vec<String>,
+    defs: &mut Map<String, Value>,
+) -> Value {
+    let schema = match type_of {
+        crate::core::Type::List { of_type, .. } => serde_json::json!({
+            "type": "array",
+            "items": type_json_schema(of_type, config, path, defs),
+        }),
+        crate::core::Type::Named { name, .. } => named_json_schema(name, config, path, defs),
+    };
+
+    if type_of.is_nullable() {
+        nullable_json_schema(schema)
+    } else {
+        schema
+    }
+}
+
+fn named_json_schema(
+    name: &str,
+    config: &Config,
+    path: &mut Vec<String>,
+    defs: &mut Map<String, Value>,
+) -> Value {
+    if let Some(enum_) = config.find_enum(name) {
+        let variants = enum_
+            .variants
+            .iter()
+            .map(|variant| Value::from(variant.name.as_str()))
+            .collect::<Vec<_>>();
+        return serde_json::json!({ "type": "string", "enum": variants });
+    }
+
+    match config.find_type(name) {
+        Some(type_) if !type_.scalar() => {
+            let reference = serde_json::json!({ "$ref": format!("#/$defs/{name}") });
+            if defs.get(name).is_some_and(|def| !def.is_null()) {
+                return reference;
+            }
+            if path.iter().any(|visited| visited == name) {
+                // defined once the type is resolved
+                defs.insert(name.to_string(), Value::Null);
+                return reference;
+            }
+            path.push(name.to_string());
+
+            let mut properties = serde_json::Map::new();
+            let mut required = Vec::new();
+            for (field_name, field) in type_.fields.iter().filter(|(_, f)| !f.is_omitted()) {
+                let mut schema = type_json_schema(&field.type_of, config, path, defs);
+                if let Some(doc) = &field.doc {
+                    schema["description"] = Value::from(doc.as_str());
+                }
+                if let Some(default_value) = &field.default_value {
+                    schema["default"] = default_value.clone();
+                } else if !field.type_of.is_nullable() {
+                    required.push(Value::from(field_name.as_str()));
+                }
+                properties.insert(field_name.clone(), schema);
+            }
+            path.pop();
+
+            let mut schema = serde_json::json!({ "type": "object", "properties": properties });
+            if !required.is_empty() {
+                schema["required"] = Value::Array(required);
+            }
+            if defs.contains_key(name) {
+                defs.insert(name.to_string(), schema);
+                reference
+            } else {
+                schema
+            }
+        }
+        Some(_) => serde_json::json!({}),
+        None => scalar_json_schema(name),
+    }
+}
+
+fn scalar_json_schema(name: &str) -> Value {
+    let Some(scalar) = Scalar::find(name) else {
+        return match name {
+            "Int" => serde_json::json!({ "type": "integer", "format": "int32" }),
+            "Float" => serde_json::json!({ "type": "number" }),
+            "String" | "ID" => serde_json::json!({ "type": "string" }),
+            "Boolean" => serde_json::json!({ "type": "boolean" }),
+            _ => serde_json::json!({}),
+        };
+    };
+
+    match scalar {
+        Scalar::Empty => serde_json::json!({ "type": "null" }),
+        Scalar::Email => serde_json::json!({ "type": "string", "format": "email" }),
+        Scalar::Date => serde_json::json!({ "type": "string", "format": "date" }),
+        Scalar::DateTime => serde_json::json!({ "type": "string", "format": "date-time" }),
+        Scalar::Url => serde_json::json!({ "type": "string", "format": "uri" }),
+        Scalar::PhoneNumber | Scalar::Bytes => serde_json::json!({ "type": "string" }),
+        Scalar::JSON => serde_json::json!({}),
+        Scalar::Upload => serde_json::json!({ "type": "object" }),
+        Scalar::Int8
+        | Scalar::Int16
+        | Scalar::Int32
+        | Scalar::UInt8
+        | Scalar::UInt16
+        | Scalar::UInt32 => {
+            serde_json::json!({ "type": "integer", "format": scalar.name().to_lowercase() })
+        }
+        // sent as strings, as they don't fit in the numbers of JavaScript
+        Scalar::Int64 | Scalar::Int128 | Scalar::UInt64 | Scalar::UInt128 => {
+            serde_json::json!({ "type": "string", "pattern": "^-?\\d+$" })
+        }
+    }
+}
+
+/// Adds `null` to the values a schema accepts, a schema without a `type`
+/// already accepts it.
+fn nullable_json_schema(mut schema: Value) -> Value {
+    if schema.get("$ref").is_some() {
+        return serde_json::json!({ "anyOf": [schema, { "type": "null" }] });
+    }
+    let Some(type_of) = schema.get("type").and_then(Value::as_str) else {
+        return schema;
+    };
+    if type_of == "null" {
+        return schema;
+    }
+    schema["type"] = serde_json::json!([type_of, "null"]);
+    if let Some(variants) = schema.get_mut("enum").and_then(Value::as_array_mut) {
+        variants.push(Value::Null);
+    }
+    schema
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, MergeRight)]
@@ -303,6 +502,57 @@ pub struct Variant {
     pub name: String,
     // directive: alias
     pub alias: Option<Alias>,
+    // directive: deprecated
+    pub deprecated: Option<Deprecated>,
+}
+
+/// The built-in `@deprecated` directive, for the fields, arguments and enum
+/// values that shouldn't be used anymore.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deprecated {
+    pub reason: Option<String>,
+}
+
+// It's a terminal implementation of MergeRight
+impl MergeRight for Deprecated {
+    fn merge_right(self, other: Self) -> Self {
+        other
+    }
+}
+
+impl Deprecated {
+    pub const NAME: &'static str = "deprecated";
+
+    pub fn from_directives<'a>(
+        mut directives: impl Iterator<Item = &'a Positioned<ConstDirective>>,
+    ) -> Option<Self> {
+        let directive = directives.find(|directive| directive.node.name.node == Self::NAME)?;
+        let reason = match directive
+            .node
+            .get_argument("reason")
+            .map(|reason| &reason.node)
+        {
+            Some(ConstValue::String(reason)) => Some(reason.clone()),
+            _ => None,
+        };
+        Some(Self { reason })
+    }
+
+    pub fn to_directive(&self) -> ConstDirective {
+        ConstDirective {
+            name: pos(Name::new(Self::NAME)),
+            arguments: self
+                .reason
+                .iter()
+                .map(|reason| {
+                    (
+                        pos(Name::new("reason")),
+                        pos(ConstValue::String(reason.clone())),
+                    )
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -424,6 +674,26 @@ impl Config {
         }
     }
 
+    /// Builds the types of a config from the response of the introspection
+    /// query of a GraphQL API, with no resolvers.
+    pub fn from_introspection(json: &str) -> Result<Self> {
+        super::from_introspection::from_introspection(json)
+    }
+
+    /// Builds a config from a protobuf file: a `Query` field resolved with
+    /// `@grpc` for each method of its services, and a type or an enum for each
+    /// of its messages and enums. The messages of the files it imports, in
+    /// `dependencies`, get a type when the file uses them. The fields call the
+    /// service at `url`, and the file has to be linked with
+    /// `@link(type: Protobuf)` for them to be valid.
+    pub fn from_proto(
+        descriptor: &FileDescriptorProto,
+        dependencies: &[FileDescriptorProto],
+        url: &str,
+    ) -> Result<Self> {
+        generator::from_proto_file(descriptor, dependencies, "Query", url)
+    }
+
     pub fn from_source(source: Source, content: &str) -> Result<Self> {
         match source {
             Source::GraphQL => Ok(Config::from_sdl(content).to_result()?),
@@ -435,6 +705,54 @@ impl Config {
         super::npo::PathTracker::new(self).find()
     }
 
+    /// The non-null fields of the mutation that call a `DELETE` endpoint, eg.
+    /// `Mutation.deleteUser`, which fail when it answers with `204 No
+    /// Content`, unless they're `Boolean`s.
+    pub fn non_null_delete_fields(&self) -> Vec<String> {
+        let Some(mutation) = self.schema.mutation.as_ref() else {
+            return vec![];
+        };
+        let Some(type_) = self.types.get(mutation) else {
+            return vec![];
+        };
+
+        type_
+            .fields
+            .iter()
+            .filter(|(_, field)| {
+                NoContent::new(&field.type_of, &Method::DELETE) == NoContent::Error
+            })
+            .filter(|(_, field)| {
+                field.resolvers.iter().any(|resolver| {
+                    matches!(resolver, Resolver::Http(http) if http.method == Method::DELETE)
+                })
+            })
+            .map(|(name, _)| format!("{mutation}.{name}"))
+            .collect()
+    }
+
+    /// Names of the environment variables the upstream credentials are read
+    /// from, by `@auth` and `@http(basicAuth:)`.
+    pub fn credential_env_vars(&self) -> BTreeSet<String> {
+        let fields = self.types.values().flat_map(|type_| type_.fields.values());
+        let field_auth = fields.clone().filter_map(|field| field.auth.as_ref());
+        let basic_auth = fields
+            .flat_map(|field| field.resolvers.iter())
+            .filter_map(|resolver| match resolver {
+                Resolver::Http(http) => http.basic_auth.as_ref(),
+                _ => None,
+            })
+            .flat_map(|basic_auth| [basic_auth.user_env.clone(), basic_auth.pass_env.clone()]);
+
+        self.auth
+            .iter()
+            .chain(field_auth)
+            .flat_map(|auth| auth.providers())
+            .flat_map(|provider| provider.env_vars())
+            .chain(basic_auth)
+            .collect()
+    }
+
     ///
     /// Given a starting type, this function searches for all the unique types
     /// that this type can be connected to via it's fields
@@ -579,6 +897,40 @@ impl Config {
         self
     }
 
+    /// Applies the defaults to the `@http` resolvers of all the fields, in a
+    /// single pass over the types. An option the resolver already sets is
+    /// kept, and so is a header it already sends, whatever its case.
+    pub fn merge_defaults(mut self, defaults: FieldDefaults) -> Self {
+        let resolvers = self
+            .types
+            .values_mut()
+            .flat_map(|type_| type_.fields.values_mut())
+            .flat_map(|field| field.resolvers.0.iter_mut());
+
+        for resolver in resolvers {
+            let Resolver::Http(http) = resolver else {
+                continue;
+            };
+            if http.timeout.is_none() {
+                http.timeout = defaults.timeout;
+            }
+            if http.circuit_breaker.is_none() {
+                http.circuit_breaker = defaults.circuit_breaker.clone();
+            }
+            for header in defaults.headers.iter() {
+                if !http
+                    .headers
+                    .iter()
+                    .any(|current| current.key.eq_ignore_ascii_case(&header.key))
+                {
+                    http.headers.push(header.clone());
+                }
+            }
+        }
+
+        self
+    }
+
     pub fn unused_types(&self) -> HashSet<String> {
         let used_types = self.get_all_used_type_names();
         let all_types: HashSet<String> = self
@@ -590,6 +942,14 @@ impl Config {
         all_types.difference(&used_types).cloned().collect()
     }
 
+    /// Gets the types connected to the given ones via their fields, the given
+    /// ones included.
+    pub fn reachable_types<'a>(&self, types: impl IntoIterator<Item = &'a str>) -> HashSet<String> {
+        types.into_iter().fold(HashSet::new(), |found, type_of| {
+            self.find_connections(type_of, found)
+        })
+    }
+
     /// Gets all the type names used in the schema.
     pub fn get_all_used_type_names(&self) -> HashSet<String> {
         let mut set = HashSet::new();
@@ -647,6 +1007,8 @@ impl Config {
             .add_directive(Omit::directive_definition(generated_types))
             .add_directive(Protected::directive_definition(generated_types))
             .add_directive(Discriminate::directive_definition(generated_types))
+            .add_directive(Auth::directive_definition(generated_types))
+            .add_directive(OperationOverrides::directive_definition(generated_types))
             .add_input(GraphQL::input_definition())
             .add_input(Grpc::input_definition())
             .add_input(Http::input_definition())
@@ -663,6 +1025,18 @@ impl Config {
     }
 }
 
+/// Options given to every `@http` field that doesn't set them, see
+/// [Config::merge_defaults].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Setters)]
+pub struct FieldDefaults {
+    /// See `@http(timeout:)`.
+    pub timeout: Option<u64>,
+    /// See `@http(circuitBreaker:)`.
+    pub circuit_breaker: Option<CircuitBreaker>,
+    /// Headers added to the ones of `@http(headers:)`.
+    pub headers: Vec<KeyValue>,
+}
+
 impl From<RuntimeConfig> for Config {
     fn from(config: RuntimeConfig) -> Self {
         Self {
@@ -682,14 +1056,15 @@ pub enum Encoding {
     #[default]
     ApplicationJson,
     ApplicationXWwwFormUrlencoded,
+    MultipartFormData,
 }
 
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use tailcall_fixtures::protobuf;
 
     use super::*;
-    use crate::core::config::Resolver;
     use crate::core::directive::DirectiveCodec;
 
     #[test]
@@ -731,6 +1106,34 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_from_sdl_extend_type() {
+        let actual = Config::from_sdl(
+            "
+            extend type Query {b: Int}
+            type Query {a: Int}
+            extend type Query {c: Int}
+            extend type User {id: Int}
+            ",
+        )
+        .to_result()
+        .unwrap();
+
+        assert_eq!(
+            actual.types["Query"].fields.keys().collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+        assert_eq!(actual.extended_types.iter().collect::<Vec<_>>(), ["User"]);
+
+        let error = Config::from_sdl("type Query {a: Int}\nextend type Query {a: String}")
+            .to_result()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation Error\n• Field `a` is already defined [Query, a]\n"
+        );
+    }
+
     #[test]
     fn test_unused_types_with_cyclic_types() {
         let config = Config::from_sdl(
@@ -858,4 +1261,299 @@ mod tests {
 
         assert_eq!(interfaces_types_map, expected_union_types);
     }
+
+    #[test]
+    fn test_to_sdl_dependencies_first() {
+        let sdl = r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              a: A @http(url: "http://localhost:3000/a")
+              b: B @http(url: "http://localhost:3000/b")
+            }
+
+            type A {
+              b: B
+            }
+
+            type B {
+              a: A
+              c: C
+            }
+
+            type C {
+              id: Int
+            }
+        "#;
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        let sdl = config.to_sdl();
+        let position = |name: &str| sdl.find(&format!("type {} {{", name)).unwrap();
+
+        // `A` and `B` reference each other, the cycle is entered from `A`
+        assert!(position("C") < position("B"));
+        assert!(position("B") < position("A"));
+        assert!(position("A") < position("Query"));
+    }
+
+    #[test]
+    fn test_merge_defaults() {
+        let sdl = r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              a: Int @http(url: "http://localhost:3000/a")
+              b: Int
+                @http(
+                  url: "http://localhost:3000/b"
+                  timeout: 100
+                  headers: [{key: "X-Api-Key", value: "b"}]
+                )
+              c: Int @expr(body: 1)
+            }
+        "#;
+        let circuit_breaker = CircuitBreaker { failure_threshold: 5, timeout_secs: 30 };
+        let defaults = FieldDefaults::default()
+            .timeout(Some(2000))
+            .circuit_breaker(Some(circuit_breaker.clone()))
+            .headers(vec![
+                KeyValue { key: "x-api-key".to_string(), value: "default".to_string() },
+                KeyValue { key: "x-tenant".to_string(), value: "acme".to_string() },
+            ]);
+
+        let config = Config::from_sdl(sdl)
+            .to_result()
+            .unwrap()
+            .merge_defaults(defaults);
+
+        let http = |name: &str| match &config.types["Query"].fields[name].resolvers.0[..] {
+            [Resolver::Http(http)] => http.clone(),
+            resolvers => panic!("unexpected resolvers {resolvers:?}"),
+        };
+        let header_keys = |http: &Http| {
+            http.headers
+                .iter()
+                .map(|header| header.key.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let a = http("a");
+        assert_eq!(a.timeout, Some(2000));
+        assert_eq!(a.circuit_breaker, Some(circuit_breaker.clone()));
+        assert_eq!(header_keys(&a), vec!["x-api-key", "x-tenant"]);
+
+        let b = http("b");
+        assert_eq!(b.timeout, Some(100));
+        assert_eq!(b.circuit_breaker, Some(circuit_breaker));
+        assert_eq!(header_keys(&b), vec!["X-Api-Key", "x-tenant"]);
+        assert_eq!(b.headers[0].value, "b");
+
+        assert!(matches!(
+            &config.types["Query"].fields["c"].resolvers.0[..],
+            [Resolver::Expr(_)]
+        ));
+    }
+
+    #[test]
+    fn test_arg_to_json_schema() {
+        let sdl = r#"
+            schema {
+              query: Query
+            }
+
+            enum Role {
+              ADMIN
+              USER
+            }
+
+            input UserFilter {
+              "Only the users with this name"
+              name: String!
+              roles: [Role!]
+              limit: Int! = 10
+              createdAfter: DateTime
+              parent: UserFilter
+              externalId: Int64
+            }
+
+            type Query {
+              users("The users to find" filter: UserFilter!, ids: [ID!]!): [Int]
+                @expr(body: [1])
+            }
+        "#;
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        let args = &config.types["Query"].fields["users"].args;
+        let mut defs = Map::new();
+
+        assert_eq!(
+            args["filter"].to_json_schema(&config, &mut defs),
+            serde_json::json!({
+                "$ref": "#/$defs/UserFilter",
+                "description": "The users to find"
+            })
+        );
+        assert_eq!(
+            Value::Object(defs.clone()),
+            serde_json::json!({
+                "UserFilter": {
+                    "type": "object",
+                    "properties": {
+                        "createdAfter": { "type": ["string", "null"], "format": "date-time" },
+                        "externalId": { "type": ["string", "null"], "pattern": "^-?\\d+$" },
+                        "limit": { "type": "integer", "format": "int32", "default": 10 },
+                        "name": { "type": "string", "description": "Only the users with this name" },
+                        "parent": {
+                            "anyOf": [{ "$ref": "#/$defs/UserFilter" }, { "type": "null" }]
+                        },
+                        "roles": {
+                            "type": ["array", "null"],
+                            "items": { "type": "string", "enum": ["ADMIN", "USER"] }
+                        }
+                    },
+                    "required": ["name"]
+                }
+            })
+        );
+        assert_eq!(
+            args["ids"].to_json_schema(&config, &mut defs),
+            serde_json::json!({ "type": "array", "items": { "type": "string" } })
+        );
+    }
+
+    #[test]
+    fn test_nullable_json_schema() {
+        assert_eq!(
+            nullable_json_schema(serde_json::json!({ "type": "string", "enum": ["A"] })),
+            serde_json::json!({ "type": ["string", "null"], "enum": ["A", null] })
+        );
+        assert_eq!(
+            nullable_json_schema(serde_json::json!({})),
+            serde_json::json!({})
+        );
+    }
+
+    #[test]
+    fn test_from_proto() {
+        let descriptor_set = protox::compile([protobuf::DEPRECATED], [protobuf::SELF]).unwrap();
+        let descriptor = descriptor_set
+            .file
+            .iter()
+            .find(|file| file.name() == "deprecated.proto")
+            .unwrap();
+
+        let config = Config::from_proto(descriptor, &[], "{{.env.GRPC_URL}}").unwrap();
+        let query = &config.types["Query"];
+        let field = &query.fields["GEN__deprecated__UserService__GetUser"];
+
+        assert_eq!(config.schema.query.as_deref(), Some("Query"));
+        assert_eq!(
+            field.args["getUserRequest"].type_of.name(),
+            "GEN__deprecated__GetUserRequest"
+        );
+        match &field.resolvers.0[..] {
+            [Resolver::Grpc(grpc)] => {
+                assert_eq!(grpc.url, "{{.env.GRPC_URL}}");
+                assert_eq!(grpc.method, "deprecated.UserService.GetUser");
+            }
+            resolvers => panic!("unexpected resolvers {resolvers:?}"),
+        }
+        assert_eq!(
+            config.types["GEN__deprecated__User"]
+                .fields
+                .keys()
+                .collect::<Vec<_>>(),
+            ["id", "login", "name", "status"]
+        );
+        assert_eq!(
+            config.enums["GEN__deprecated__Status"]
+                .variants
+                .iter()
+                .map(|variant| variant.name.as_str())
+                .collect::<Vec<_>>(),
+            ["ACTIVE", "LEGACY", "UNSPECIFIED"]
+        );
+    }
+
+    #[test]
+    fn test_from_proto_imports() {
+        let descriptor_set = protox::compile([protobuf::GREETINGS], [protobuf::SELF]).unwrap();
+        let (descriptor, dependencies) = descriptor_set.file.split_last().unwrap();
+
+        let config =
+            Config::from_proto(descriptor, dependencies, "http://localhost:50051").unwrap();
+        let field = &config.types["Query"].fields["GEN__greetings__Greeter__SayHello"];
+
+        assert_eq!(field.type_of.name(), "GEN__greetings__HelloReply");
+        assert!(config.types.contains_key("GEN__greetings__HelloReply"));
+        assert!(config.types.contains_key("GEN__greetings__HelloRequest"));
+    }
+
+    #[test]
+    fn test_from_proto_unused_messages() {
+        let descriptor_set =
+            protox::compile([protobuf::NEWS_NO_SERVICE], [protobuf::SELF]).unwrap();
+        let (descriptor, dependencies) = descriptor_set.file.split_last().unwrap();
+
+        let config =
+            Config::from_proto(descriptor, dependencies, "http://localhost:50051").unwrap();
+
+        assert_eq!(
+            config.types["GEN__NewsList"].fields["news"].type_of.name(),
+            "GEN__News"
+        );
+        assert!(config.types.contains_key("GEN__News"));
+    }
+
+    #[test]
+    fn test_non_null_delete_fields() {
+        let config = Config::from_sdl(
+            r#"
+            schema {
+                query: Query
+                mutation: Mutation
+            }
+            type Query { user: User @http(url: "http://api/user") }
+            type Mutation {
+                deleteUser: User! @http(url: "http://api/user", method: DELETE)
+                deletePost: Post @http(url: "http://api/post", method: DELETE)
+                deleteComment: Boolean! @http(url: "http://api/comment", method: DELETE)
+                createUser: User! @http(url: "http://api/user", method: POST)
+            }
+            type User { id: Int }
+            type Post { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        assert_eq!(config.non_null_delete_fields(), vec!["Mutation.deleteUser"]);
+    }
+
+    #[test]
+    fn test_credential_env_vars() {
+        let config = Config::from_sdl(
+            r#"
+            schema @auth(apiKey: {env: "API_KEY"}) {
+                query: Query
+            }
+            type Query {
+                user: User @http(url: "http://api/user") @auth(jwt: {env: "TOKEN"})
+                post: Post
+                    @http(url: "http://api/post", basicAuth: {userEnv: "USER", passEnv: "PASS"})
+            }
+            type User { id: Int }
+            type Post { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        assert_eq!(
+            config.credential_env_vars(),
+            BTreeSet::from(["API_KEY", "PASS", "TOKEN", "USER"].map(String::from))
+        );
+    }
 }
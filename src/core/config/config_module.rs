@@ -6,7 +6,8 @@ use prost_reflect::prost_types::{FileDescriptorProto, FileDescriptorSet};
 use rustls_pki_types::{CertificateDer, PrivateKeyDer};
 use tailcall_valid::{Valid, Validator};
 
-use crate::core::config::Config;
+use crate::core::config::{Config, LinkType, Resolver};
+use crate::core::http::Mock;
 use crate::core::macros::MergeRight;
 use crate::core::merge_right::MergeRight;
 use crate::core::proto_reader::ProtoMetadata;
@@ -71,6 +72,52 @@ impl ConfigModule {
         &self.extensions
     }
 
+    /// Lists the linked files that contain external code used to resolve
+    /// fields, together with the fields that depend on them. The `@grpc`
+    /// fields are listed under the links that provide the service of their
+    /// method.
+    pub fn list_extensions(&self) -> Vec<ExtensionInfo> {
+        self.links
+            .iter()
+            .filter_map(|link| {
+                let kind = match link.type_of {
+                    LinkType::Script => ExtensionKind::Script,
+                    LinkType::Protobuf => ExtensionKind::Protobuf,
+                    LinkType::Grpc => ExtensionKind::Reflection,
+                    _ => return None,
+                };
+                let services = self.extensions.grpc_services.get(&link.src);
+                let fields = self.fields_resolved_by(|resolver| match resolver {
+                    Resolver::Js(_) => kind == ExtensionKind::Script,
+                    // the method is `<package>.<service>.<method>`
+                    Resolver::Grpc(grpc) => grpc
+                        .method
+                        .rsplit_once('.')
+                        .zip(services)
+                        .is_some_and(|((service, _), services)| services.contains(service)),
+                    _ => false,
+                });
+
+                Some(ExtensionInfo { kind, path: link.src.clone(), fields })
+            })
+            .collect()
+    }
+
+    /// Returns the `Type.field` names of all the fields that have a resolver
+    /// matching the predicate.
+    fn fields_resolved_by(&self, predicate: impl Fn(&Resolver) -> bool) -> Vec<String> {
+        self.types
+            .iter()
+            .flat_map(|(type_name, type_)| {
+                type_
+                    .fields
+                    .iter()
+                    .filter(|(_, field)| field.resolvers.0.iter().any(&predicate))
+                    .map(move |(field_name, _)| format!("{type_name}.{field_name}"))
+            })
+            .collect()
+    }
+
     pub fn input_types(&self) -> &HashSet<String> {
         &self.cache.input_types
     }
@@ -90,6 +137,27 @@ impl ConfigModule {
     }
 }
 
+/// Kind of external code a linked file provides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::Display)]
+pub enum ExtensionKind {
+    /// A JS file used by the `@js` resolvers.
+    Script,
+    /// A Protobuf file used by the `@grpc` resolvers.
+    Protobuf,
+    /// A reflection endpoint used by the `@grpc` resolvers.
+    Reflection,
+}
+
+/// Describes a linked extension and the fields that are resolved with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionInfo {
+    pub kind: ExtensionKind,
+    /// Path or URL of the linked file, as written in the `@link` directive.
+    pub path: String,
+    /// Fields in the `Type.field` format that depend on the extension.
+    pub fields: Vec<String>,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Content<A> {
     pub id: Option<String>,
@@ -132,6 +200,10 @@ pub struct Extensions {
     /// Contains the file descriptor set resolved from the links to proto files
     pub grpc_file_descriptors: HashMap<String, FileDescriptorProto>,
 
+    /// Contains the services, eg. `news.NewsService`, provided by each link
+    /// to proto files or to a reflection endpoint, by its `src`
+    pub grpc_services: HashMap<String, BTreeSet<String>>,
+
     /// Contains the contents of the JS file
     pub script: Option<String>,
 
@@ -147,10 +219,29 @@ pub struct Extensions {
     pub htpasswd: Vec<Content<String>>,
 
     pub jwks: Vec<Content<JwkSet>>,
+
+    /// Contains the mocked upstream responses
+    pub mocks: Vec<Mock>,
+
+    /// Contains the values of the environment variables read by the upstream
+    /// credentials, read once when the config is loaded
+    pub env_vars: HashMap<String, String>,
 }
 
 impl Extensions {
-    pub fn add_proto(&mut self, metadata: ProtoMetadata) {
+    /// Adds the proto files read from the link `src`, remembering the services
+    /// they provide.
+    pub fn add_proto(&mut self, src: &str, metadata: ProtoMetadata) {
+        let services = metadata.descriptor_set.file.iter().flat_map(|file| {
+            file.service
+                .iter()
+                .map(move |service| format!("{}.{}", file.package(), service.name()))
+        });
+        self.grpc_services
+            .entry(src.to_string())
+            .or_default()
+            .extend(services);
+
         for file in metadata.descriptor_set.file {
             self.grpc_file_descriptors
                 .insert(file.name().to_string(), file);
@@ -186,3 +277,63 @@ impl From<Config> for ConfigModule {
         ConfigModule { cache: Cache::from(config), ..Default::default() }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_extensions() {
+        let sdl = r#"
+            schema
+                @server
+                @link(type: Script, src: "scripts/echo.js")
+                @link(type: Protobuf, src: "news.proto")
+                @link(type: Grpc, src: "http://localhost:50052")
+                @link(type: Operation, src: "operation.graphql") {
+                query: Query
+            }
+
+            type Query {
+                hello: String @js(name: "hello")
+                news: [String] @grpc(url: "http://localhost:50051", method: "news.NewsService.GetAllNews")
+                posts: [String] @grpc(url: "http://localhost:50052", method: "posts.PostService.ListPosts")
+                users: [String] @http(url: "http://localhost:3000/users")
+            }
+        "#;
+        let services =
+            |src: &str, service: &str| (src.to_string(), BTreeSet::from([service.to_string()]));
+        let extensions = Extensions {
+            grpc_services: HashMap::from([
+                services("news.proto", "news.NewsService"),
+                services("http://localhost:50052", "posts.PostService"),
+            ]),
+            ..Default::default()
+        };
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        let config_module = ConfigModule::new(config, extensions);
+
+        let actual = config_module.list_extensions();
+
+        assert_eq!(
+            actual,
+            vec![
+                ExtensionInfo {
+                    kind: ExtensionKind::Script,
+                    path: "scripts/echo.js".to_string(),
+                    fields: vec!["Query.hello".to_string()],
+                },
+                ExtensionInfo {
+                    kind: ExtensionKind::Protobuf,
+                    path: "news.proto".to_string(),
+                    fields: vec!["Query.news".to_string()],
+                },
+                ExtensionInfo {
+                    kind: ExtensionKind::Reflection,
+                    path: "http://localhost:50052".to_string(),
+                    fields: vec!["Query.posts".to_string()],
+                },
+            ]
+        );
+    }
+}
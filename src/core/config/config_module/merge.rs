@@ -82,6 +82,7 @@ impl Contravariant for Arg {
             doc: self.doc.merge_right(other.doc),
             modify: self.modify.merge_right(other.modify),
             default_value: self.default_value.or(other.default_value),
+            deprecated: self.deprecated.merge_right(other.deprecated),
         })
     }
 }
@@ -101,7 +102,12 @@ impl Contravariant for Field {
                 default_value: self.default_value.or(other.default_value),
                 protected: self.protected.merge_right(other.protected),
                 discriminate: self.discriminate.merge_right(other.discriminate),
+                auth: self.auth.merge_right(other.auth),
+                operation_overrides: self
+                    .operation_overrides
+                    .merge_right(other.operation_overrides),
                 resolvers: self.resolvers.merge_right(other.resolvers),
+                deprecated: self.deprecated.merge_right(other.deprecated),
                 directives: self.directives.merge_right(other.directives),
             })
     }
@@ -123,7 +129,12 @@ impl Covariant for Field {
                 default_value: self.default_value.or(other.default_value),
                 protected: self.protected.merge_right(other.protected),
                 discriminate: self.discriminate.merge_right(other.discriminate),
+                auth: self.auth.merge_right(other.auth),
+                operation_overrides: self
+                    .operation_overrides
+                    .merge_right(other.operation_overrides),
                 resolvers: self.resolvers.merge_right(other.resolvers),
+                deprecated: self.deprecated.merge_right(other.deprecated),
                 directives: self.directives.merge_right(other.directives),
             })
     }
@@ -203,6 +214,22 @@ impl Invariant for RootSchema {
 
 impl Invariant for Cache {
     fn unify(self, other: Self) -> Valid<Self, String> {
+        // A type extended on one side stays only extended while none of the
+        // sides defines it.
+        let is_defined = |config: &Config, type_name: &String| {
+            config.types.contains_key(type_name) && !config.extended_types.contains(type_name)
+        };
+        let extended_types = self
+            .config
+            .extended_types
+            .iter()
+            .chain(&other.config.extended_types)
+            .filter(|type_name| {
+                !is_defined(&self.config, type_name) && !is_defined(&other.config, type_name)
+            })
+            .cloned()
+            .collect();
+
         let mut types = self.config.types;
         let mut enums = self.config.enums;
 
@@ -295,6 +322,7 @@ impl Invariant for Cache {
 
             let config = Config {
                 types,
+                extended_types,
                 enums,
                 unions: self.config.unions.merge_right(other.config.unions),
                 schema,
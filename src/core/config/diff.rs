@@ -0,0 +1,581 @@
+//! Semantic changes between two versions of a config's schema, classified so
+//! that tooling can build changelogs from them.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{self, Display};
+
+use super::{Arg, Config, Enum, Field, Type, Union};
+
+/// How much a change can affect the existing clients of the schema.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Safe for every client.
+    NonBreaking,
+    /// Valid for existing queries, but clients that match the possible values
+    /// exhaustively might not handle the new ones.
+    Dangerous,
+    /// Existing queries or variables can stop working.
+    Breaking,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::NonBreaking => "NON_BREAKING",
+            Severity::Dangerous => "DANGEROUS",
+            Severity::Breaking => "BREAKING",
+        })
+    }
+}
+
+/// The part of the schema a change belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    Type,
+    Field,
+    Argument,
+    EnumValue,
+    UnionMember,
+    Description,
+    Resolver,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    TypeAdded,
+    TypeRemoved,
+    TypeKindChanged,
+    FieldAdded { required: bool },
+    FieldRemoved,
+    FieldTypeChanged { from: String, to: String },
+    ArgumentAdded { required: bool },
+    ArgumentRemoved,
+    ArgumentTypeChanged { from: String, to: String },
+    EnumValueAdded,
+    EnumValueRemoved,
+    UnionMemberAdded,
+    UnionMemberRemoved,
+    DescriptionChanged,
+    ResolverChanged,
+}
+
+impl ChangeKind {
+    pub fn category(&self) -> Category {
+        match self {
+            ChangeKind::TypeAdded | ChangeKind::TypeRemoved | ChangeKind::TypeKindChanged => {
+                Category::Type
+            }
+            ChangeKind::FieldAdded { .. }
+            | ChangeKind::FieldRemoved
+            | ChangeKind::FieldTypeChanged { .. } => Category::Field,
+            ChangeKind::ArgumentAdded { .. }
+            | ChangeKind::ArgumentRemoved
+            | ChangeKind::ArgumentTypeChanged { .. } => Category::Argument,
+            ChangeKind::EnumValueAdded | ChangeKind::EnumValueRemoved => Category::EnumValue,
+            ChangeKind::UnionMemberAdded | ChangeKind::UnionMemberRemoved => Category::UnionMember,
+            ChangeKind::DescriptionChanged => Category::Description,
+            ChangeKind::ResolverChanged => Category::Resolver,
+        }
+    }
+}
+
+/// A change of the schema, at `path`, i.e. `Type`, `Type.field`,
+/// `Type.field(arg)` or `Enum.VALUE`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub severity: Severity,
+}
+
+impl SchemaChange {
+    fn new(path: impl Into<String>, kind: ChangeKind, severity: Severity) -> Self {
+        Self { path: path.into(), kind, severity }
+    }
+
+    pub fn category(&self) -> Category {
+        self.kind.category()
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.severity == Severity::Breaking
+    }
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = &self.path;
+        write!(f, "[{}] ", self.severity)?;
+        match &self.kind {
+            ChangeKind::TypeAdded => write!(f, "Type `{path}` was added"),
+            ChangeKind::TypeRemoved => write!(f, "Type `{path}` was removed"),
+            ChangeKind::TypeKindChanged => write!(f, "Type `{path}` changed its kind"),
+            ChangeKind::FieldAdded { .. } => write!(f, "Field `{path}` was added"),
+            ChangeKind::FieldRemoved => write!(f, "Field `{path}` was removed"),
+            ChangeKind::FieldTypeChanged { from, to } => {
+                write!(f, "Field `{path}` changed type from `{from}` to `{to}`")
+            }
+            ChangeKind::ArgumentAdded { .. } => write!(f, "Argument `{path}` was added"),
+            ChangeKind::ArgumentRemoved => write!(f, "Argument `{path}` was removed"),
+            ChangeKind::ArgumentTypeChanged { from, to } => {
+                write!(f, "Argument `{path}` changed type from `{from}` to `{to}`")
+            }
+            ChangeKind::EnumValueAdded => write!(f, "Enum value `{path}` was added"),
+            ChangeKind::EnumValueRemoved => write!(f, "Enum value `{path}` was removed"),
+            ChangeKind::UnionMemberAdded => write!(f, "Union member `{path}` was added"),
+            ChangeKind::UnionMemberRemoved => write!(f, "Union member `{path}` was removed"),
+            ChangeKind::DescriptionChanged => write!(f, "Description of `{path}` changed"),
+            ChangeKind::ResolverChanged => write!(f, "Resolver of `{path}` changed"),
+        }
+    }
+}
+
+enum Definition<'a> {
+    Type(&'a Type),
+    Enum(&'a Enum),
+    Union(&'a Union),
+}
+
+impl Config {
+    fn find_definition(&self, name: &str) -> Option<Definition<'_>> {
+        self.find_type(name)
+            .map(Definition::Type)
+            .or_else(|| self.find_enum(name).map(Definition::Enum))
+            .or_else(|| self.find_union(name).map(Definition::Union))
+    }
+
+    /// Lists the changes of the schema from `self` to `new`, classified by
+    /// category and severity.
+    pub fn diff_schema_changes(&self, new: &Config) -> Vec<SchemaChange> {
+        let names = [self, new]
+            .into_iter()
+            .flat_map(|config| {
+                config
+                    .types
+                    .keys()
+                    .chain(config.enums.keys())
+                    .chain(config.unions.keys())
+            })
+            .collect::<BTreeSet<_>>();
+        let input_types = self
+            .input_types()
+            .union(&new.input_types())
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        let mut changes = Vec::new();
+        for name in names {
+            match (self.find_definition(name), new.find_definition(name)) {
+                (None, Some(_)) => changes.push(SchemaChange::new(
+                    name,
+                    ChangeKind::TypeAdded,
+                    Severity::NonBreaking,
+                )),
+                (Some(_), None) => changes.push(SchemaChange::new(
+                    name,
+                    ChangeKind::TypeRemoved,
+                    Severity::Breaking,
+                )),
+                (Some(Definition::Type(old)), Some(Definition::Type(new))) => {
+                    let is_input = input_types.contains(name);
+                    diff_type(name, old, new, is_input, &mut changes)
+                }
+                (Some(Definition::Enum(old)), Some(Definition::Enum(new))) => {
+                    diff_doc(name, &old.doc, &new.doc, &mut changes);
+                    let old_values = old.variants.iter().map(|v| &v.name).collect();
+                    let new_values = new.variants.iter().map(|v| &v.name).collect();
+                    diff_members(
+                        name,
+                        &old_values,
+                        &new_values,
+                        (ChangeKind::EnumValueAdded, ChangeKind::EnumValueRemoved),
+                        &mut changes,
+                    );
+                }
+                (Some(Definition::Union(old)), Some(Definition::Union(new))) => {
+                    diff_doc(name, &old.doc, &new.doc, &mut changes);
+                    diff_members(
+                        name,
+                        &old.types.iter().collect(),
+                        &new.types.iter().collect(),
+                        (ChangeKind::UnionMemberAdded, ChangeKind::UnionMemberRemoved),
+                        &mut changes,
+                    );
+                }
+                (Some(_), Some(_)) => changes.push(SchemaChange::new(
+                    name,
+                    ChangeKind::TypeKindChanged,
+                    Severity::Breaking,
+                )),
+                (None, None) => {}
+            }
+        }
+
+        changes
+    }
+}
+
+fn diff_type(name: &str, old: &Type, new: &Type, is_input: bool, changes: &mut Vec<SchemaChange>) {
+    diff_doc(name, &old.doc, &new.doc, changes);
+
+    let fields = old
+        .fields
+        .keys()
+        .chain(new.fields.keys())
+        .collect::<BTreeSet<_>>();
+    for field_name in fields {
+        let path = format!("{name}.{field_name}");
+        match (old.fields.get(field_name), new.fields.get(field_name)) {
+            (Some(_), None) => changes.push(SchemaChange::new(
+                path,
+                ChangeKind::FieldRemoved,
+                Severity::Breaking,
+            )),
+            (None, Some(field)) => {
+                // Only the fields of input types have to be sent by the clients.
+                let required = is_input && is_required(&field.type_of, &field.default_value);
+                let severity = if required {
+                    Severity::Breaking
+                } else {
+                    Severity::NonBreaking
+                };
+                changes.push(SchemaChange::new(
+                    path,
+                    ChangeKind::FieldAdded { required },
+                    severity,
+                ))
+            }
+            (Some(old), Some(new)) => diff_field(&path, old, new, is_input, changes),
+            (None, None) => {}
+        }
+    }
+}
+
+fn diff_field(
+    path: &str,
+    old: &Field,
+    new: &Field,
+    is_input: bool,
+    changes: &mut Vec<SchemaChange>,
+) {
+    diff_doc(path, &old.doc, &new.doc, changes);
+
+    // The shape of the schema stays the same, but the data can change.
+    if old.resolvers != new.resolvers {
+        changes.push(SchemaChange::new(
+            path,
+            ChangeKind::ResolverChanged,
+            Severity::Dangerous,
+        ));
+    }
+
+    if old.type_of != new.type_of {
+        let safe = if is_input {
+            is_safe_output_change(&new.type_of, &old.type_of)
+        } else {
+            is_safe_output_change(&old.type_of, &new.type_of)
+        };
+        changes.push(SchemaChange::new(
+            path,
+            ChangeKind::FieldTypeChanged {
+                from: format!("{:?}", old.type_of),
+                to: format!("{:?}", new.type_of),
+            },
+            severity(safe),
+        ));
+    }
+
+    let args = old
+        .args
+        .keys()
+        .chain(new.args.keys())
+        .collect::<BTreeSet<_>>();
+    for arg_name in args {
+        let arg_path = format!("{path}({arg_name})");
+        match (old.args.get(arg_name), new.args.get(arg_name)) {
+            (Some(_), None) => changes.push(SchemaChange::new(
+                arg_path,
+                ChangeKind::ArgumentRemoved,
+                Severity::Breaking,
+            )),
+            (None, Some(arg)) => {
+                let required = is_required(&arg.type_of, &arg.default_value);
+                changes.push(SchemaChange::new(
+                    arg_path,
+                    ChangeKind::ArgumentAdded { required },
+                    severity(!required),
+                ))
+            }
+            (Some(old), Some(new)) => diff_arg(&arg_path, old, new, changes),
+            (None, None) => {}
+        }
+    }
+}
+
+fn diff_arg(path: &str, old: &Arg, new: &Arg, changes: &mut Vec<SchemaChange>) {
+    diff_doc(path, &old.doc, &new.doc, changes);
+
+    if old.type_of != new.type_of {
+        changes.push(SchemaChange::new(
+            path,
+            ChangeKind::ArgumentTypeChanged {
+                from: format!("{:?}", old.type_of),
+                to: format!("{:?}", new.type_of),
+            },
+            severity(is_safe_output_change(&new.type_of, &old.type_of)),
+        ));
+    }
+}
+
+fn diff_doc(
+    path: &str,
+    old: &Option<String>,
+    new: &Option<String>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    if old != new {
+        changes.push(SchemaChange::new(
+            path,
+            ChangeKind::DescriptionChanged,
+            Severity::NonBreaking,
+        ));
+    }
+}
+
+fn diff_members(
+    name: &str,
+    old: &BTreeSet<&String>,
+    new: &BTreeSet<&String>,
+    (added, removed): (ChangeKind, ChangeKind),
+    changes: &mut Vec<SchemaChange>,
+) {
+    for member in old.difference(new) {
+        changes.push(SchemaChange::new(
+            format!("{name}.{member}"),
+            removed.clone(),
+            Severity::Breaking,
+        ));
+    }
+    for member in new.difference(old) {
+        changes.push(SchemaChange::new(
+            format!("{name}.{member}"),
+            added.clone(),
+            Severity::Dangerous,
+        ));
+    }
+}
+
+fn severity(safe: bool) -> Severity {
+    if safe {
+        Severity::NonBreaking
+    } else {
+        Severity::Breaking
+    }
+}
+
+fn is_required(type_of: &crate::core::Type, default_value: &Option<serde_json::Value>) -> bool {
+    !type_of.is_nullable() && default_value.is_none()
+}
+
+/// Checks if the values of `new` are always valid values of `old`, i.e. it's
+/// the same type with nullable positions made non-null.
+fn is_safe_output_change(old: &crate::core::Type, new: &crate::core::Type) -> bool {
+    use crate::core::Type::{List, Named};
+
+    match (old, new) {
+        (Named { name: old, non_null: old_nn }, Named { name: new, non_null: new_nn }) => {
+            old == new && (!old_nn || *new_nn)
+        }
+        (List { of_type: old, non_null: old_nn }, List { of_type: new, non_null: new_nn }) => {
+            (!old_nn || *new_nn) && is_safe_output_change(old, new)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::Validator;
+
+    use super::*;
+
+    fn changes(old: &str, new: &str) -> Vec<(String, Category, Severity)> {
+        let old = Config::from_sdl(old).to_result().unwrap();
+        let new = Config::from_sdl(new).to_result().unwrap();
+        old.diff_schema_changes(&new)
+            .into_iter()
+            .map(|change| (change.path.clone(), change.category(), change.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_fields_and_types() {
+        let changes = changes(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { user: User @http(url: "http://localhost/user") }
+            type User { id: Int name: String email: String }
+            type Post { id: Int }
+            "#,
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { user: User @http(url: "http://localhost/user") }
+            """
+            A user
+            """
+            type User { id: Int! name: Int age: Int }
+            type Comment { id: Int }
+            "#,
+        );
+
+        assert_eq!(
+            changes,
+            vec![
+                ("Comment".to_string(), Category::Type, Severity::NonBreaking),
+                ("Post".to_string(), Category::Type, Severity::Breaking),
+                (
+                    "User".to_string(),
+                    Category::Description,
+                    Severity::NonBreaking
+                ),
+                (
+                    "User.age".to_string(),
+                    Category::Field,
+                    Severity::NonBreaking
+                ),
+                (
+                    "User.email".to_string(),
+                    Category::Field,
+                    Severity::Breaking
+                ),
+                (
+                    "User.id".to_string(),
+                    Category::Field,
+                    Severity::NonBreaking
+                ),
+                ("User.name".to_string(), Category::Field, Severity::Breaking),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arguments_and_inputs() {
+        let changes = changes(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query {
+                users(filter: Filter, limit: Int!): [Int] @http(url: "http://localhost/users")
+            }
+            input Filter { name: String }
+            "#,
+            r#"
+            schema @server @upstream { query: Query }
+            type Query {
+                users(filter: Filter, limit: Int, offset: Int, page: Int!): [Int]
+                    @http(url: "http://localhost/users")
+            }
+            input Filter { name: String age: Int! }
+            "#,
+        );
+
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "Filter.age".to_string(),
+                    Category::Field,
+                    Severity::Breaking
+                ),
+                (
+                    "Query.users(limit)".to_string(),
+                    Category::Argument,
+                    Severity::NonBreaking
+                ),
+                (
+                    "Query.users(offset)".to_string(),
+                    Category::Argument,
+                    Severity::NonBreaking
+                ),
+                (
+                    "Query.users(page)".to_string(),
+                    Category::Argument,
+                    Severity::Breaking
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enums_and_unions() {
+        let changes = changes(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { status: Status @http(url: "http://localhost/status") }
+            enum Status { ACTIVE INACTIVE }
+            type A { id: Int }
+            type B { id: Int }
+            union Item = A
+            "#,
+            r#"
+            schema @server @upstream { query: Query }
+            type Query { status: Status @http(url: "http://localhost/status") }
+            enum Status { ACTIVE PENDING }
+            type A { id: Int }
+            type B { id: Int }
+            union Item = A | B
+            "#,
+        );
+
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "Item.B".to_string(),
+                    Category::UnionMember,
+                    Severity::Dangerous
+                ),
+                (
+                    "Status.INACTIVE".to_string(),
+                    Category::EnumValue,
+                    Severity::Breaking
+                ),
+                (
+                    "Status.PENDING".to_string(),
+                    Category::EnumValue,
+                    Severity::Dangerous
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolvers() {
+        let changes = changes(
+            r#"
+            schema @server @upstream { query: Query }
+            type Query {
+                user: User @http(url: "http://localhost/user")
+                posts: [Int] @http(url: "http://localhost/posts")
+            }
+            type User { id: Int }
+            "#,
+            r#"
+            schema @server @upstream { query: Query }
+            type Query {
+                user: User @http(url: "http://localhost/users/1")
+                posts: [Int] @http(url: "http://localhost/posts")
+            }
+            type User { id: Int }
+            "#,
+        );
+
+        assert_eq!(
+            changes,
+            vec![(
+                "Query.user".to_string(),
+                Category::Resolver,
+                Severity::Dangerous
+            )]
+        );
+    }
+}
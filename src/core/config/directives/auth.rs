@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use tailcall_macros::{DirectiveDefinition, InputDefinition};
+
+use crate::core::is_default;
+use crate::core::macros::MergeRight;
+
+/// Reads an API key from an environment variable and sends it along with every
+/// upstream request, either as a header or as a query parameter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ApiKey {
+    /// Name of the environment variable that holds the key.
+    pub env: String,
+
+    /// Name of the header the key is sent in. Defaults to `X-Api-Key` when
+    /// neither `header` nor `query` is set.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub header: Option<String>,
+
+    /// Name of the query parameter the key is sent in.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub query: Option<String>,
+}
+
+/// Reads a bearer token from an environment variable and sends it in the
+/// `Authorization` header of every upstream request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct JwtAuth {
+    /// Name of the environment variable that holds the token.
+    pub env: String,
+}
+
+/// Reads the username and password from environment variables and sends them
+/// as HTTP Basic credentials with every upstream request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BasicAuth {
+    /// Name of the environment variable that holds the username.
+    pub user_env: String,
+
+    /// Name of the environment variable that holds the password.
+    pub pass_env: String,
+}
+
+/// A single upstream authentication strategy. Exactly one of the strategies
+/// is expected to be set.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AuthProvider {
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub api_key: Option<ApiKey>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub jwt: Option<JwtAuth>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub basic_auth: Option<BasicAuth>,
+}
+
+impl AuthProvider {
+    pub fn is_empty(&self) -> bool {
+        self.api_key.is_none() && self.jwt.is_none() && self.basic_auth.is_none()
+    }
+
+    /// Names of the environment variables the credentials are read from.
+    pub fn env_vars(&self) -> Vec<String> {
+        let api_key = self.api_key.iter().map(|api_key| api_key.env.clone());
+        let jwt = self.jwt.iter().map(|jwt| jwt.env.clone());
+        let basic_auth = self
+            .basic_auth
+            .iter()
+            .flat_map(|basic_auth| [basic_auth.user_env.clone(), basic_auth.pass_env.clone()]);
+
+        api_key.chain(jwt).chain(basic_auth).collect()
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+    InputDefinition,
+)]
+#[directive_definition(locations = "Schema, FieldDefinition")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+/// The @auth directive attaches credentials to the upstream requests made by
+/// the `@http` resolvers. When applied on the schema, the listed `providers`
+/// are used for every `@http` field. When applied on a field, the strategies
+/// are added on top of the ones defined on the schema.
+pub struct Auth {
+    /// Sends an API key read from an environment variable.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub api_key: Option<ApiKey>,
+
+    /// Sends a bearer token read from an environment variable.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub jwt: Option<JwtAuth>,
+
+    /// Sends HTTP Basic credentials read from environment variables.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub basic_auth: Option<BasicAuth>,
+
+    /// A list of strategies that are applied together.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub providers: Vec<AuthProvider>,
+}
+
+impl Auth {
+    /// Returns all the strategies defined by the directive, including the
+    /// shorthand ones, as a flat list of providers.
+    pub fn providers(&self) -> Vec<AuthProvider> {
+        let shorthand = AuthProvider {
+            api_key: self.api_key.clone(),
+            jwt: self.jwt.clone(),
+            basic_auth: self.basic_auth.clone(),
+        };
+
+        std::iter::once(shorthand)
+            .chain(self.providers.iter().cloned())
+            .filter(|provider| !provider.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_providers_include_shorthand() {
+        let auth = Auth {
+            api_key: Some(ApiKey {
+                env: "API_KEY".to_string(),
+                header: Some("X-Api-Key".to_string()),
+                query: None,
+            }),
+            providers: vec![AuthProvider {
+                jwt: Some(JwtAuth { env: "TOKEN".to_string() }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let providers = auth.providers();
+
+        assert_eq!(providers.len(), 2);
+        assert!(providers[0].api_key.is_some());
+        assert!(providers[1].jwt.is_some());
+    }
+
+    #[test]
+    fn test_providers_skip_empty() {
+        let auth = Auth::default();
+
+        assert!(auth.providers().is_empty());
+    }
+}
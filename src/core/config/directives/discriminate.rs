@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use tailcall_macros::{DirectiveDefinition, InputDefinition, MergeRight};
 
@@ -25,10 +27,16 @@ use crate::core::is_default;
 /// be `Buzz`. If `field` is not applied it defaults to "type". The `field` does
 /// not have to be part of the GraphQL Schema, but it is required to be part of
 /// the JSON response. In case this field is missing from the response an
-/// appropriate error message will be displayed.
+/// appropriate error message will be displayed. The values of the field can be
+/// mapped to the types with `mapping`, eg. `{"cat": "Cat"}`, and the objects
+/// that don't resolve to any of the types get the `default` type when it's set.
 pub struct Discriminate {
     #[serde(default, skip_serializing_if = "is_default")]
     pub field: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mapping: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub default: Option<String>,
 }
 
 impl Discriminate {
@@ -1,11 +1,288 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
 
-use crate::core::config::{Encoding, KeyValue, URLQuery};
+use crate::core::config::{BasicAuth, Encoding, KeyValue, URLQuery};
 use crate::core::http::Method;
 use crate::core::is_default;
 use crate::core::json::JsonSchema;
+use crate::core::macros::MergeRight;
+
+/// Stops calling an upstream host that keeps failing. After
+/// `failureThreshold` consecutive failures requests to the host fail fast
+/// for `timeoutSecs` seconds, after which a single trial request is allowed
+/// through.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CircuitBreaker {
+    /// Number of consecutive failures after which the circuit opens.
+    pub failure_threshold: u32,
+    /// Number of seconds the circuit stays open before a trial request is
+    /// allowed.
+    pub timeout_secs: u64,
+}
+
+/// Follows the next pages of a paginated API, resolving the field to the items
+/// of all the pages. The next pages must be on the origin of the first one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Paginate {
+    /// Where the next page is read from.
+    pub mode: PaginateMode,
+    /// With `CURSOR`, the dot-path of the next page in the body, eg.
+    /// `.meta.next`. The pagination ends when it's missing, `null` or empty.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub cursor_path: Option<String>,
+    /// With `CURSOR`, the query parameter the cursor is sent in. When it's not
+    /// set the cursor is the URL of the next page, which can be relative.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub cursor_param: Option<String>,
+    /// The dot-path of the list of items in the body of each page, eg.
+    /// `.data`. The body itself is the list when it's not set.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub items_path: Option<String>,
+    /// The maximum number of pages fetched. When there are more, the items
+    /// fetched so far are returned with an error. @default `10`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub max_pages: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaginateMode {
+    /// The URL of the next page is the `rel="next"` link of the `Link`
+    /// header.
+    LinkHeader,
+    /// The next page is read from the body at `cursorPath`.
+    Cursor,
+}
+
+/// The format of the body of the upstream responses.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Xml,
+}
+
+/// What is done when the upstream answers with a status code.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StatusBehavior {
+    /// The field resolves to `null` without an error. In a non-null position
+    /// the `null` bubbles up to the nearest nullable parent.
+    Null,
+    /// The field fails with the `message` of the mapping.
+    Error,
+    /// The field fails with an error of code `RATE_LIMITED`, which clients
+    /// can tell apart from the other upstream errors.
+    RateLimitError,
+    /// The request is sent again, a few times, before failing. Only the
+    /// requests of the idempotent methods, eg. `GET`, are retried, and the
+    /// `Retry-After` of the response is waited for.
+    Retry,
+}
+
+/// Maps a status code of the upstream, or a class of them, to what the field
+/// resolves to. It can also be written `{status, action}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct OnStatus {
+    /// The status code, eg. `404`, or a class of them, eg. `"5xx"`.
+    #[schemars(with = "Value")]
+    #[serde(alias = "status")]
+    pub code: StatusPattern,
+    #[serde(alias = "action")]
+    pub behavior: StatusBehavior,
+    /// With `ERROR`, the message of the error. It's a mustache template that
+    /// can read the `status` and the `body` of the response, eg.
+    /// `"Conflict: {{.response.body.detail}}"`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub message: Option<String>,
+    /// A mustache template that reads the response like `message`, the
+    /// mapping only applies when it renders to something other than an empty
+    /// string, `false` or `null`. It's required to map a `2xx` status, eg.
+    /// `{code: 200, behavior: ERROR, when: "{{.response.body.error}}"}` for
+    /// the APIs that answer errors with a success status.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub when: Option<String>,
+}
+
+/// A status code, eg. `404`, or a class of them, eg. `"5xx"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusPattern {
+    Code(u16),
+    /// The first digit of the codes of the class, eg. `5` for `"5xx"`.
+    Class(u16),
+}
+
+impl StatusPattern {
+    pub fn matches(&self, status: u16) -> bool {
+        match self {
+            StatusPattern::Code(code) => *code == status,
+            StatusPattern::Class(class) => status / 100 == *class,
+        }
+    }
+
+    pub fn is_class(&self) -> bool {
+        matches!(self, StatusPattern::Class(_))
+    }
+}
+
+impl Display for StatusPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusPattern::Code(code) => write!(f, "{code}"),
+            StatusPattern::Class(class) => write!(f, "{class}xx"),
+        }
+    }
+}
+
+impl FromStr for StatusPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let class = s
+            .to_ascii_lowercase()
+            .strip_suffix("xx")
+            .filter(|digit| digit.len() == 1)
+            .and_then(|digit| digit.parse().ok());
+
+        match class {
+            Some(class) => Ok(StatusPattern::Class(class)),
+            None => s.parse().map(StatusPattern::Code).map_err(|_| {
+                format!("`{s}` is neither a status code, eg. `404`, nor a class, eg. `5xx`")
+            }),
+        }
+    }
+}
+
+impl Serialize for StatusPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StatusPattern::Code(code) => serializer.serialize_u16(*code),
+            StatusPattern::Class(_) => serializer.serialize_str(&self.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for StatusPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum CodeOrText {
+            Code(u16),
+            Text(String),
+        }
+
+        match CodeOrText::deserialize(deserializer)? {
+            CodeOrText::Code(code) => Ok(StatusPattern::Code(code)),
+            CodeOrText::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// How the pages of a paginated API are exposed in the schema.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaginationStrategy {
+    /// A Relay connection, the API takes the `first`, `after`, `last` and
+    /// `before` arguments as query parameters and returns
+    /// `{edges: [{node, cursor}], pageInfo}`.
+    Relay,
+}
+
+/// TLS settings of the requests made by a single `@http` field.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HttpSslConfig {
+    /// Verifies the certificate of the API. Setting this as `false` accepts
+    /// self-signed certificates for this field only, while the other fields
+    /// keep following `verifySSL` of `@upstream`.
+    pub verify: bool,
+}
+
+/// Signs the upstream requests once they are fully rendered, so that the
+/// signature covers the bytes that are actually sent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase")]
+pub enum Signing {
+    /// AWS Signature Version 4, as expected by API Gateway, S3 and the other
+    /// AWS services.
+    SigV4(SigV4Signing),
+    /// An HMAC of the method, the path and the body sent in a header.
+    Hmac(HmacSigning),
+}
+
+/// Credentials and scope of an AWS Signature Version 4. The values can refer
+/// to environment variables, eg. `"{{.env.AWS_SECRET_ACCESS_KEY}}"`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SigV4Signing {
+    /// Region of the service, eg. `us-east-1`.
+    pub region: String,
+    /// Name of the service, eg. `execute-api` or `s3`.
+    pub service: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Sent as `X-Amz-Security-Token` when using temporary credentials.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub session_token: Option<String>,
+}
+
+/// Sends the HMAC of `METHOD\npath?query\nbody` as a hex string in a header.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HmacSigning {
+    /// The hash function of the HMAC. @default `SHA256`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub algorithm: HmacAlgorithm,
+    /// The key of the HMAC, eg. `"{{.env.SIGNING_SECRET}}"`.
+    pub secret: String,
+    /// Name of the header the signature is sent in.
+    pub header: String,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+pub enum HmacAlgorithm {
+    #[default]
+    SHA256,
+    SHA512,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NullQueryParams {
+    /// Sends the parameter without a value.
+    #[default]
+    Empty,
+    /// Leaves the parameter out of the URL.
+    Omit,
+}
+
+/// How the list values of the query parameters are sent.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ArrayFormat {
+    /// Joins the items with commas, `ids=1,2,3`.
+    Comma,
+    /// Repeats the key for each item, `ids=1&ids=2&ids=3`.
+    Repeat,
+    /// Repeats the key followed by brackets for each item,
+    /// `ids[]=1&ids[]=2&ids[]=3`.
+    Bracket,
+}
 
 #[derive(
     Serialize,
@@ -34,6 +311,14 @@ pub struct Http {
     /// request interception handler.
     pub on_request: Option<String>,
 
+    #[serde(rename = "arrayFormat", default, skip_serializing_if = "is_default")]
+    /// How the list values of the query parameters are sent. `COMMA` joins the
+    /// items, `ids=1,2`, `REPEAT` repeats the key, `ids=1&ids=2`, and `BRACKET`
+    /// repeats the key followed by brackets, `ids[]=1&ids[]=2`. The `encoding`
+    /// of a query parameter takes precedence over it. When it's not set, the
+    /// key is repeated.
+    pub array_format: Option<ArrayFormat>,
+
     /// This refers to URL of the API.
     pub url: String,
 
@@ -41,25 +326,88 @@ pub struct Http {
     /// The body of the API call. It's used for methods like POST or PUT that
     /// send data to the server. You can pass it as a static object or use a
     /// Mustache template with object to substitute variables from the GraphQL
-    /// variables.
+    /// variables. With `batchKey`, `{{.batch.keys}}` is replaced with the keys
+    /// of all the batched requests, read from the query parameter that
+    /// references `{{.value}}`, or else from the field of the value named like
+    /// the last component of `batchKey`, eg. `{{.value.id}}`.
     pub body: Option<Value>,
 
     #[serde(default, skip_serializing_if = "is_default")]
     /// The `encoding` parameter specifies the encoding of the request body. It
-    /// can be `ApplicationJson` or `ApplicationXWwwFormUrlEncoded`. @default
-    /// `ApplicationJson`.
+    /// can be `ApplicationJson`, `ApplicationXWwwFormUrlencoded`, which
+    /// encodes nested objects and lists with brackets, eg.
+    /// `metadata[key]=value`, or `MultipartFormData`, which sends each key of
+    /// the body as a part and the `Upload` values as files, eg. with `body:
+    /// "{{.args}}"`. A body that is only an `Upload`, eg. `"{{.args.file}}"`,
+    /// is sent as the file itself. @default `ApplicationJson`.
     pub encoding: Encoding,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Gzip-compresses the body of the requests, which are sent with
+    /// `Content-Encoding: gzip`. The API must accept compressed bodies. It
+    /// can't be used with `batchKey` or `batchKeys`.
+    pub compress: bool,
+
+    #[serde(rename = "encodePath", default, skip_serializing_if = "is_default")]
+    /// Percent-encodes the values substituted in the path of the `url`, eg.
+    /// `/users/{{.args.name}}` with `a/b c` requests `/users/a%2Fb%20c`. The
+    /// letters, digits and `-._~` are kept, and so are the values substituted
+    /// before the path, eg. in the host. @default `true`.
+    pub encode_path: Option<bool>,
+
+    #[serde(
+        rename = "encodeQueryParams",
+        default,
+        skip_serializing_if = "is_default"
+    )]
+    /// Percent-encodes the values of the query parameters, eg. `a&b` is sent
+    /// as `a%26b`, instead of only the characters a URL can't have. The
+    /// letters, digits and `-._~` are kept. @default `false`.
+    pub encode_query_params: Option<bool>,
+
     #[serde(rename = "batchKey", default, skip_serializing_if = "is_default")]
     /// The `batchKey` dictates the path Tailcall will follow to group the returned items from the batch request. For more details please refer out [n + 1 guide](https://tailcall.run/docs/guides/n+1#solving-using-batching).
+    /// The last element can be a dot-path to a nested field of each item, eg.
+    /// `["users", "attributes.id"]`.
     pub batch_key: Vec<String>,
 
+    #[serde(rename = "batchKeys", default, skip_serializing_if = "is_default")]
+    /// Batches the requests with a composite key, used in place of `batchKey`.
+    /// The items at the root of the response are grouped by the fields at
+    /// these dot-paths, eg. `["tenantId", "user.id"]`, which are matched in
+    /// order with the query parameters that reference `{{.value}}`.
+    pub batch_keys: Vec<String>,
+
+    #[serde(rename = "basicAuth", default, skip_serializing_if = "is_default")]
+    /// Sends HTTP Basic credentials read from the environment variables
+    /// `userEnv` and `passEnv` with every request of the field. It takes
+    /// precedence over the `basicAuth` of `@auth`.
+    pub basic_auth: Option<BasicAuth>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The `headers` parameter allows you to customize the headers of the HTTP
     /// request made by the `@http` operator. It is used by specifying a
     /// key-value map of header names and their values.
     pub headers: Vec<KeyValue>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The `cookies` parameter sets the cookies of the HTTP request made by the
+    /// `@http` operator. They're added to the `Cookie` header of the request
+    /// and the values can use Mustache templates, which are percent-encoded.
+    pub cookies: Vec<KeyValue>,
+
+    #[serde(
+        rename = "requestId",
+        default,
+        skip_serializing_if = "is_default",
+        deserialize_with = "one_or_many"
+    )]
+    /// Sends the id of the incoming request, which every upstream request
+    /// already gets in `x-request-id`, in these headers as well, eg.
+    /// `["X-Correlation-Id", "X-B3-TraceId"]`, for the upstreams that correlate
+    /// their logs with other names. A single name can be given as a string.
+    pub request_id: Vec<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Schema of the input of the API call. It is automatically inferred in
     /// most cases.
@@ -67,9 +415,21 @@ pub struct Http {
 
     #[serde(default, skip_serializing_if = "is_default")]
     /// This refers to the HTTP method of the API call. Commonly used methods
-    /// include `GET`, `POST`, `PUT`, `DELETE` etc. @default `GET`.
+    /// include `GET`, `POST`, `PUT`, `DELETE` etc. With `HEAD`, the value of
+    /// the field is `{headers: {...}}`, the headers of the response.
+    /// @default `GET`.
     pub method: Method,
 
+    #[serde(
+        rename = "nullQueryParams",
+        default,
+        skip_serializing_if = "is_default"
+    )]
+    /// Decides how the query parameters that resolve to `null` are sent.
+    /// `EMPTY` sends them without a value and `OMIT` leaves them out of the
+    /// URL. @default `EMPTY`.
+    pub null_query_params: NullQueryParams,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Schema of the output of the API call. It is automatically inferred in
     /// most cases.
@@ -108,4 +468,160 @@ pub struct Http {
     /// body before it's sent back to the client.
     #[serde(rename = "onResponseBody", default, skip_serializing_if = "is_default")]
     pub on_response_body: Option<String>,
+
+    #[serde(
+        rename = "mapResponseBody",
+        default,
+        skip_serializing_if = "is_default"
+    )]
+    /// A JSONPath expression, eg. `$.data.items[*]`, that picks the part of the
+    /// response body the field resolves to. A list field resolves to all the
+    /// matches and the other fields to the first one. It can't be used with
+    /// `paginate`, `batchKey` or `batchKeys`.
+    pub map_response_body: Option<String>,
+
+    #[serde(rename = "circuitBreaker", default, skip_serializing_if = "is_default")]
+    /// Enables a circuit breaker for the upstream `host:port` of the API. When
+    /// it keeps failing, with server errors, timeouts or IO errors, requests
+    /// are rejected without making a network call until the breaker times
+    /// out.
+    pub circuit_breaker: Option<CircuitBreaker>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Fetches all the pages of a paginated API, following the `Link` header or
+    /// a cursor in the body, and resolves the list field to the items of all
+    /// of them. Each page is a regular request of the field and `timeout`
+    /// applies to all of them together.
+    pub paginate: Option<Paginate>,
+
+    #[serde(
+        rename = "paginationStrategy",
+        default,
+        skip_serializing_if = "is_default"
+    )]
+    /// Exposes a list field `[T]` as a paginated connection. With `RELAY` the
+    /// field gets the `first`, `after`, `last` and `before` arguments, which
+    /// are sent as query parameters when they're set, and returns a
+    /// `TConnection` read from the `edges` and `pageInfo` of the response.
+    pub pagination_strategy: Option<PaginationStrategy>,
+
+    #[serde(rename = "parseResponse", default, skip_serializing_if = "is_default")]
+    /// The format the response body is parsed from. With `XML` each element
+    /// is an object keyed by its name, the attributes are `@name` keys, the
+    /// repeated children are lists and the text of an element with
+    /// attributes or children is kept under `$text`, eg. `<user id="1">
+    /// <name>Ann</name></user>` is `{"user": {"@id": "1", "name": "Ann"}}`.
+    /// The values are strings, see `lenientCoercion` of `@server`. @default
+    /// `JSON`.
+    pub parse_response: ResponseFormat,
+
+    #[serde(
+        rename = "onStatus",
+        alias = "responseStatusCodes",
+        default,
+        skip_serializing_if = "is_default",
+        deserialize_with = "status_codes"
+    )]
+    /// What the field resolves to when the API answers with these status
+    /// codes, eg. `[{code: 404, behavior: NULL}, {code: "5xx", behavior:
+    /// RETRY}]`, instead of failing it. A code can be a class, eg. `"5xx"`,
+    /// and the exact codes take precedence over the classes. `NULL` resolves
+    /// the field to `null`, which bubbles up from a non-null position,
+    /// `ERROR` fails it with `message`, eg. `{code: 409, behavior: ERROR,
+    /// message: "Conflict: {{.response.body.detail}}"}`, `RATE_LIMIT_ERROR`
+    /// fails it with the `RATE_LIMITED` code and `RETRY` sends the request of
+    /// an idempotent method again, after the `Retry-After` of the response.
+    /// A success code is only mapped with a `when`, which reads the response
+    /// like `message`, eg. `{code: 200, behavior: ERROR, when:
+    /// "{{.response.body.error}}"}`. It can also be written
+    /// `responseStatusCodes`, and the JSON and YAML configs can use a map,
+    /// eg. `{"404": "NULL", "5xx": "RETRY"}`.
+    pub on_status: Vec<OnStatus>,
+
+    #[serde(rename = "pollIntervalMs", default, skip_serializing_if = "is_default")]
+    /// Turns a field of the `Subscription` root into a polling subscription.
+    /// The API is called every `pollIntervalMs` milliseconds and a new event
+    /// is delivered to the client whenever the result changes.
+    pub poll_interval_ms: Option<u64>,
+
+    #[serde(rename = "pollInterval", default, skip_serializing_if = "is_default")]
+    /// Same as `pollIntervalMs`, which takes precedence when both are set.
+    pub poll_interval: Option<u64>,
+
+    #[serde(
+        rename = "pollEmitUnchanged",
+        default,
+        skip_serializing_if = "is_default"
+    )]
+    /// Delivers an event after every poll even if the result didn't change
+    /// since the previous one. @default `false`.
+    pub poll_emit_unchanged: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Overrides the TLS settings of `@upstream` for the requests of this
+    /// field, eg. `ssl: {verify: false}` to call an internal service that uses
+    /// a self-signed certificate. NOTE: use this *only* during development or
+    /// testing.
+    pub ssl: Option<HttpSslConfig>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Signs the requests of this field, overriding `signing` of `@upstream`.
+    pub signing: Option<Signing>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The maximum time in milliseconds a request of this field can take. The
+    /// requests that take longer fail with a `TIMEOUT` error, which tells them
+    /// apart from the errors returned by the API.
+    pub timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// A mustache expression, eg. `"{{.args.includeDetails}}"`, that decides
+    /// whether the upstream is called. When it doesn't render to `true` the
+    /// field resolves to `null` without making the request.
+    pub when: Option<String>,
+}
+
+impl Http {
+    /// The polling interval in milliseconds, from `pollIntervalMs` or
+    /// `pollInterval`.
+    pub fn get_poll_interval_ms(&self) -> Option<u64> {
+        self.poll_interval_ms.or(self.poll_interval)
+    }
+}
+
+/// Reads the mappings of `onStatus` from a list or from a map keyed by status
+/// code or class.
+fn status_codes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<OnStatus>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ListOrMap {
+        List(Vec<OnStatus>),
+        Map(BTreeMap<String, StatusBehavior>),
+    }
+
+    match ListOrMap::deserialize(deserializer)? {
+        ListOrMap::List(codes) => Ok(codes),
+        ListOrMap::Map(codes) => codes
+            .into_iter()
+            .map(|(code, behavior)| match code.parse() {
+                Ok(code) => Ok(OnStatus { code, behavior, message: None, when: None }),
+                Err(error) => Err(serde::de::Error::custom(error)),
+            })
+            .collect(),
+    }
+}
+
+/// Reads a single name as a list of one.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(name) => vec![name],
+        OneOrMany::Many(names) => names,
+    })
 }
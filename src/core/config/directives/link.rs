@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use tailcall_macros::DirectiveDefinition;
+use url::Url;
 
 use crate::core::config::KeyValue;
 use crate::core::is_default;
@@ -53,6 +54,11 @@ pub enum LinkType {
     /// Points to a reflection endpoint. The imported reflection endpoint will
     /// be used by the `@grpc` directive to resolve data from gRPC services.
     Grpc,
+
+    /// Points to a JSON file with a list of mocked upstream responses. The
+    /// mocks are served instead of the real upstreams when the server is
+    /// started with the `--mock` flag.
+    Mocks,
 }
 
 /// The @link directive allows you to import external resources, such as
@@ -79,6 +85,8 @@ pub struct Link {
     ///
     /// The source of the link. It can be a URL or a path to a file.
     /// If a path is provided, it is relative to the file that imports the link.
+    /// The path can be a glob, eg. `./types/*.graphql`, to link all the files
+    /// it matches, in lexicographic order.
     #[serde(default, skip_serializing_if = "is_default")]
     pub src: String,
     ///
@@ -98,4 +106,41 @@ pub struct Link {
     /// Only valid when [`Link::type_of`] is [`LinkType::Protobuf`]
     #[serde(default, skip_serializing_if = "is_default")]
     pub proto_paths: Option<Vec<String>>,
+    ///
+    /// Allows the glob in `src` to match no file. By default, it's an error.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub optional: bool,
+}
+
+impl Link {
+    /// Whether `src` is a glob pattern, the query of a URL isn't part of it.
+    pub fn is_glob(&self) -> bool {
+        let path = match Url::parse(&self.src) {
+            Ok(url) => url.path().to_string(),
+            Err(_) => self.src.clone(),
+        };
+        path.contains(['*', '?', '['])
+    }
+
+    pub fn is_remote(&self) -> bool {
+        Url::parse(&self.src).is_ok_and(|url| url.scheme().starts_with("http"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(src: &str) -> Link {
+        Link { src: src.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_is_glob() {
+        assert!(link("./types/*.graphql").is_glob());
+        assert!(link("./types/user-[ab].graphql").is_glob());
+        assert!(link("http://example.com/*.graphql").is_glob());
+        assert!(!link("./types/user.graphql").is_glob());
+        assert!(!link("http://example.com/user.graphql?version=1").is_glob());
+    }
 }
@@ -1,5 +1,6 @@
 mod add_field;
 mod alias;
+mod auth;
 mod cache;
 mod call;
 mod discriminate;
@@ -12,6 +13,7 @@ mod js;
 mod link;
 mod modify;
 mod omit;
+mod operation_overrides;
 mod protected;
 mod server;
 mod telemetry;
@@ -19,6 +21,7 @@ mod upstream;
 
 pub use add_field::*;
 pub use alias::*;
+pub use auth::*;
 pub use cache::*;
 pub use call::*;
 pub use discriminate::*;
@@ -31,6 +34,7 @@ pub use js::*;
 pub use link::*;
 pub use modify::*;
 pub use omit::*;
+pub use operation_overrides::*;
 pub use protected::*;
 pub use server::*;
 pub use telemetry::*;
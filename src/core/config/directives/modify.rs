@@ -16,10 +16,16 @@ use crate::core::is_default;
     MergeRight,
 )]
 #[directive_definition(locations = "FieldDefinition")]
-#[serde(deny_unknown_fields)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Modify {
     #[serde(default, skip_serializing_if = "is_default")]
     pub name: Option<String>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub omit: Option<bool>,
+    /// Drops the field from the response when it resolves to `null`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub omit_if_null: Option<bool>,
+    /// Resolves the field to this constant, rendered once at startup.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub value: Option<String>,
 }
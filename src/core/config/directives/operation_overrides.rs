@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use tailcall_macros::{DirectiveDefinition, MergeRight};
+
+use crate::core::config::Resolver;
+use crate::core::is_default;
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    schemars::JsonSchema,
+    DirectiveDefinition,
+    MergeRight,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+#[serde(deny_unknown_fields)]
+/// The `@operationOverrides` directive replaces the resolver of a field when
+/// it's resolved as part of a query, a mutation or a subscription. For example
+/// `@operationOverrides(mutation: {http: {url: "...", method: POST}})` lets a
+/// field fetched with a GET in queries be sent with a POST in mutations.
+pub struct OperationOverrides {
+    /// The resolver used when the field is resolved as part of a query.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub query: Option<Resolver>,
+    /// The resolver used when the field is resolved as part of a mutation.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mutation: Option<Resolver>,
+    /// The resolver used when the field is resolved as part of a
+    /// subscription.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub subscription: Option<Resolver>,
+}
@@ -1,8 +1,10 @@
 use std::collections::{BTreeMap, BTreeSet};
 
+use convert_case::{Case, Casing};
 use derive_getters::Getters;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tailcall_macros::DirectiveDefinition;
 
 use crate::core::config::headers::Headers;
@@ -34,6 +36,12 @@ pub struct Server {
     #[schemars(skip)]
     pub enable_jit: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `admin` serves the admin endpoints, `POST /admin/reload`, `GET
+    /// /admin/complexity?query=...` and `PUT /admin/log-level`, on a separate
+    /// port.
+    pub admin: Option<Admin>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `apolloTracing` exposes GraphQL query performance data, including
     /// execution time of queries and individual resolvers.
@@ -45,6 +53,23 @@ pub struct Server {
     /// debugging. Use judiciously. @default `false`.
     pub batch_requests: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `compression` encodes the responses with gzip or brotli when the
+    /// client accepts it, trading CPU time for smaller payloads.
+    pub compression: Option<Compression>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    #[merge_right(merge_right_fn = "merge_right_context")]
+    /// `context` lists the values computed once per request, in order, that
+    /// every template can read as `{{.ctx.<key>}}`, eg. a locale read from
+    /// the `Accept-Language` header.
+    pub context: Vec<ContextEntry>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `etag` tags the successful query responses with a hash of their body
+    /// and answers `If-None-Match` with `304 Not Modified` when it matches.
+    pub etag: Option<Etag>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `headers` contains key-value pairs that are included as default headers
     /// in server responses, allowing for consistent header management across
@@ -66,6 +91,36 @@ pub struct Server {
     /// and operations. @default `true`.
     pub introspection: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `lenientCoercion` allows upstream strings to be parsed into numbers
+    /// and booleans when the schema declares an `Int`, `Float` or `Boolean`
+    /// field, eg. `"42"` is accepted for an `Int` field. @default `false`.
+    pub lenient_coercion: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `listen` sets a unix domain socket for the server to listen on instead
+    /// of `hostname` and `port`, eg. `unix:///var/run/tailcall.sock`, or
+    /// `unix://@tailcall` for an abstract socket on Linux.
+    pub listen: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `socketMode` sets the permissions of the socket file created for
+    /// `listen` as an octal string, eg. `"0660"`. @default depends on the
+    /// umask of the process.
+    pub socket_mode: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxFetchCount` rejects the operations that are estimated to call the
+    /// upstreams more than this many times. Every field resolved by `@http`
+    /// or `@grpc` counts once.
+    pub max_fetch_count: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxComplexity` rejects the operations whose complexity is over this
+    /// limit. A field counts for one plus the complexity of its selection,
+    /// which counts ten times for every level of list the field returns.
+    pub max_complexity: Option<usize>,
+
     /// `enableFederation` enables functionality to Tailcall server to act
     /// as a federation subgraph.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -76,6 +131,11 @@ pub struct Server {
     /// pipeline.
     pub pipeline_flush: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `playground` serves a GraphQL IDE that is embedded in the server and
+    /// doesn't require access to the internet.
+    pub playground: Option<Playground>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `port` sets the Tailcall running port. @default `8000`.
     pub port: Option<u16>,
@@ -100,6 +160,24 @@ pub struct Server {
     /// `showcase` enables the /showcase/graphql endpoint.
     pub showcase: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `tenant` identifies the tenant of each request, so that the upstreams
+    /// can be picked per tenant with `{{.tenant.id}}`, eg. `url:
+    /// "http://{{.tenant.id}}.api.example.com/users"`.
+    pub tenant: Option<Tenant>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `upload` limits the files sent with multipart requests, which are
+    /// rejected when they go over.
+    pub upload: Option<UploadLimits>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `upstreamKeyCase` is the case of the keys in the upstream values. A
+    /// field missing from the value is read from its name in that case, eg.
+    /// `userId` from `user_id` with `SNAKE`. The fields renamed with
+    /// `@modify(name:)` keep their mapping.
+    pub upstream_key_case: Option<KeyCase>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     #[merge_right(merge_right_fn = "merge_right_vars")]
     /// This configuration defines local variables for server operations. Useful
@@ -126,6 +204,175 @@ pub struct Server {
     pub routes: Option<Routes>,
 }
 
+/// The admin endpoints, served on their own port so they can be kept off the
+/// public network. The requests must send the token as `Authorization: Bearer
+/// <token>`.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Admin {
+    /// `port` the admin endpoints are served on.
+    pub port: u16,
+
+    /// `token` that authorizes the admin requests, at least 16 characters
+    /// long, eg. `"{{.env.ADMIN_TOKEN}}"`.
+    pub token: String,
+}
+
+/// How the tenant of a request is identified and the hosts its upstreams can
+/// be on. Only the URLs that use `{{.tenant.id}}` are checked against
+/// `allowedHosts`, so that a header can't send the requests to any host.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Tenant {
+    /// `id` of the tenant, rendered for each request, eg.
+    /// `"{{.headers.x-tenant-id}}"`. The header must be in `allowedHeaders`
+    /// of `@upstream`.
+    pub id: String,
+
+    /// `allowedHosts` the URLs of the tenants can resolve to. A pattern that
+    /// starts with `*.` allows the subdomains of a host, eg.
+    /// `*.api.example.com`, and a port other than the default one of the
+    /// scheme must be named, eg. `*.api.example.com:8443`.
+    pub allowed_hosts: Vec<String>,
+}
+
+/// The limits of the files sent with the GraphQL multipart requests.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadLimits {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxFileSize` is the size in bytes a file can't go over. @default
+    /// `10485760`, ie. 10 MiB.
+    pub max_file_size: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxFiles` is the number of files a request can't go over. @default
+    /// `10`.
+    pub max_files: Option<usize>,
+}
+
+impl UploadLimits {
+    pub fn get_max_file_size(&self) -> usize {
+        self.max_file_size.unwrap_or(10 * 1024 * 1024)
+    }
+
+    pub fn get_max_files(&self) -> usize {
+        self.max_files.unwrap_or(10)
+    }
+}
+
+/// Which responses get an `ETag`. The REST endpoints served with `GET` always
+/// do, the GraphQL queries sent with `POST` only when `post` is set.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Etag {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `post` also tags the queries sent with `POST` to the GraphQL endpoint,
+    /// which the HTTP caches don't store. @default `false`.
+    pub post: Option<bool>,
+}
+
+impl Etag {
+    pub fn enable_post(&self) -> bool {
+        self.post.unwrap_or(false)
+    }
+}
+
+/// A value of the request context, rendered from `value` or fetched from
+/// `http`, then transformed. When it can't be computed, eg. the header is
+/// missing, the request fails unless a `default` is set.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextEntry {
+    /// `key` the value is read with, eg. `locale` for `{{.ctx.locale}}`.
+    pub key: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `value` rendered for each request, eg.
+    /// `"{{.headers.accept-language}}"`. An empty value is a failure.
+    pub value: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `http` fetches the value with a `GET` request, whose JSON response is
+    /// the value.
+    pub http: Option<ContextHttp>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `transform` applied to the value, which must then be a string.
+    pub transform: Option<ContextTransform>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `default` value used when the value can't be computed.
+    pub default: Option<Value>,
+}
+
+/// The request that fetches a value of the request context.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextHttp {
+    /// `url` of the request, eg. `"http://flags/{{.headers.x-user-id}}"`.
+    pub url: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `headers` sent with the request.
+    pub headers: Vec<KeyValue>,
+}
+
+/// The transforms of the request context values.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ContextTransform {
+    /// The language tag with the highest quality of an `Accept-Language`
+    /// header, eg. `fr-CH` for `fr-CH, fr;q=0.9, en;q=0.8`.
+    FirstLanguageTag,
+    Lowercase,
+    Uppercase,
+    Trim,
+}
+
+impl ContextTransform {
+    /// The transformed value, `None` when there's nothing left of it.
+    pub fn apply(&self, value: &str) -> Option<String> {
+        let value = match self {
+            ContextTransform::FirstLanguageTag => first_language_tag(value)?.to_string(),
+            ContextTransform::Lowercase => value.to_lowercase(),
+            ContextTransform::Uppercase => value.to_uppercase(),
+            ContextTransform::Trim => value.trim().to_string(),
+        };
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+/// The first of the tags with the highest quality, the wildcard is skipped.
+fn first_language_tag(header: &str) -> Option<&str> {
+    let mut first: Option<(&str, f32)> = None;
+    for item in header.split(',') {
+        let mut params = item.split(';');
+        let tag = params.next().unwrap_or_default().trim();
+        let quality = params
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .map_or(Some(1.0), |quality| quality.trim().parse::<f32>().ok());
+        let Some(quality) = quality else {
+            continue;
+        };
+        if tag.is_empty() || tag == "*" || quality <= 0.0 {
+            continue;
+        }
+        if first.map_or(true, |(_, highest)| quality > highest) {
+            first = Some((tag, quality));
+        }
+    }
+    first.map(|(tag, _)| tag)
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, MergeRight, JsonSchema, Getters)]
 pub struct Routes {
     #[serde(default = "default_status")]
@@ -158,11 +405,91 @@ impl Routes {
     }
 }
 
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Playground {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `enabled` serves the playground. @default `false`.
+    pub enabled: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `path` the playground is served at. @default `/playground`.
+    pub path: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `defaultQuery` is the query shown in the new tabs of the playground.
+    pub default_query: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `headers` prefills the request headers of the new tabs of the
+    /// playground, eg. to hint that an `Authorization` header is required.
+    pub headers: Vec<KeyValue>,
+}
+
+impl Playground {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    pub fn get_path(&self) -> String {
+        self.path.clone().unwrap_or("/playground".to_string())
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Compression {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `gzip` enables the gzip encoding, of the responses and of the request
+    /// bodies. @default `true`.
+    pub gzip: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `brotli` enables the brotli encoding, which is preferred over gzip
+    /// when the client accepts both. @default `true`.
+    pub brotli: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `minSize` is the size in bytes below which the responses are sent
+    /// as is. @default `1024`.
+    pub min_size: Option<usize>,
+}
+
+impl Compression {
+    pub fn enable_gzip(&self) -> bool {
+        self.gzip.unwrap_or(true)
+    }
+
+    pub fn enable_brotli(&self) -> bool {
+        self.brotli.unwrap_or(true)
+    }
+
+    pub fn get_min_size(&self) -> usize {
+        self.min_size.unwrap_or(1024)
+    }
+}
+
 fn merge_right_vars(mut left: Vec<KeyValue>, right: Vec<KeyValue>) -> Vec<KeyValue> {
     left = merge_key_value_vecs(&left, &right);
     left
 }
 
+/// Keeps the order of the entries, the ones on the right replace the entries
+/// with the same key.
+fn merge_right_context(mut left: Vec<ContextEntry>, right: Vec<ContextEntry>) -> Vec<ContextEntry> {
+    for entry in right {
+        match left.iter_mut().find(|current| current.key == entry.key) {
+            Some(current) => *current = entry,
+            None => left.push(entry),
+        }
+    }
+    left
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight)]
 #[serde(rename_all = "camelCase")]
 pub struct ScriptOptions {
@@ -178,6 +505,28 @@ pub enum HttpVersion {
     HTTP2,
 }
 
+/// The case of the keys in the upstream values.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum KeyCase {
+    /// Keys like `user_id`.
+    Snake,
+    /// Keys like `userId`.
+    Camel,
+}
+
+impl KeyCase {
+    /// The key a field named `name` is read from.
+    pub fn key(&self, name: &str) -> String {
+        match self {
+            KeyCase::Snake => name.to_case(Case::Snake),
+            KeyCase::Camel => name.to_case(Case::Camel),
+        }
+    }
+}
+
 impl Server {
     pub fn enable_apollo_tracing(&self) -> bool {
         self.apollo_tracing.unwrap_or(false)
@@ -212,6 +561,9 @@ impl Server {
     pub fn enable_introspection(&self) -> bool {
         self.introspection.unwrap_or(true)
     }
+    pub fn enable_lenient_coercion(&self) -> bool {
+        self.lenient_coercion.unwrap_or(false)
+    }
     pub fn enable_query_validation(&self) -> bool {
         self.query_validation.unwrap_or(false)
     }
@@ -268,6 +620,18 @@ impl Server {
     pub fn get_enable_federation(&self) -> bool {
         self.enable_federation.unwrap_or(false)
     }
+
+    pub fn get_max_fetch_count(&self) -> Option<usize> {
+        self.max_fetch_count
+    }
+
+    pub fn get_max_complexity(&self) -> Option<usize> {
+        self.max_complexity
+    }
+
+    pub fn get_playground(&self) -> Playground {
+        self.playground.clone().unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -379,4 +743,49 @@ mod tests {
 
         assert_eq!(merge_vec, expected_vec)
     }
+
+    #[test]
+    fn test_merge_right_context() {
+        let entry = |key: &str, value: &str| ContextEntry {
+            key: key.to_string(),
+            value: Some(value.to_string()),
+            ..Default::default()
+        };
+        let left = vec![entry("locale", "en"), entry("region", "eu")];
+        let right = vec![entry("tier", "free"), entry("locale", "fr")];
+
+        let merged = merge_right_context(left, right);
+
+        assert_eq!(
+            merged,
+            vec![
+                entry("locale", "fr"),
+                entry("region", "eu"),
+                entry("tier", "free")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_transform() {
+        let tag = |header: &str| ContextTransform::FirstLanguageTag.apply(header);
+        assert_eq!(tag("fr-CH, fr;q=0.9, en;q=0.8"), Some("fr-CH".to_string()));
+        assert_eq!(tag("en;q=0.5, de;q=0.7, *"), Some("de".to_string()));
+        assert_eq!(tag("*, es"), Some("es".to_string()));
+        assert_eq!(tag("*;q=0.5"), None);
+        assert_eq!(tag(""), None);
+        assert_eq!(
+            ContextTransform::Lowercase.apply("EN-us"),
+            Some("en-us".to_string())
+        );
+        assert_eq!(ContextTransform::Trim.apply("  "), None);
+    }
+
+    #[test]
+    fn test_key_case() {
+        assert_eq!(KeyCase::Snake.key("userId"), "user_id");
+        assert_eq!(KeyCase::Snake.key("createdAt"), "created_at");
+        assert_eq!(KeyCase::Camel.key("user_id"), "userId");
+        assert_eq!(KeyCase::Camel.key("id"), "id");
+    }
 }
@@ -1,4 +1,6 @@
-use anyhow::Result;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
 use tailcall_valid::Validator;
@@ -45,6 +47,33 @@ pub enum PrometheusFormat {
     Protobuf,
 }
 
+/// Format of the logs printed by the server
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// A JSON object per line, with the fields of the event and its spans
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow!(
+                "Unknown log format `{s}`, expected `text` or `json`"
+            )),
+        }
+    }
+}
+
 /// Output the telemetry metrics data to prometheus server
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -94,6 +123,23 @@ pub struct Telemetry {
     /// contain sensitive data
     #[serde(default, skip_serializing_if = "is_default")]
     pub request_headers: Vec<String>,
+    /// The headers the name of the client is read from when it doesn't send
+    /// `apollographql-client-name`, in order of preference.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub client_name_headers: Vec<String>,
+    /// The headers the version of the client is read from when it doesn't
+    /// send `apollographql-client-version`, in order of preference.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub client_version_headers: Vec<String>,
+    /// Format of the logs, overridden by the `--log-format` flag and the
+    /// `TAILCALL_LOG_FORMAT` env variable.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub log_format: LogFormat,
+    /// Filter of the logs by module, eg.
+    /// `"tailcall::core::http=debug,tailcall=info"`. It can be changed on the
+    /// running server with `PUT /admin/log-level`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub log_filter: Option<String>,
 }
 
 impl Telemetry {
@@ -105,6 +151,13 @@ impl Telemetry {
             (Some(left), Some(right)) => Some(left.clone().merge_right(right.clone())),
         };
         self.request_headers.extend(other.request_headers);
+        self.client_name_headers.extend(other.client_name_headers);
+        self.client_version_headers
+            .extend(other.client_version_headers);
+        if other.log_format != LogFormat::default() {
+            self.log_format = other.log_format;
+        }
+        self.log_filter = other.log_filter.or(self.log_filter);
 
         self
     }
@@ -147,6 +200,8 @@ mod tests {
                 headers: vec![KeyValue { key: "header_a".to_owned(), value: "a".to_owned() }],
             })),
             request_headers: vec!["Api-Key-A".to_owned()],
+            client_name_headers: vec!["X-Client-Name".to_owned()],
+            ..Default::default()
         };
         let exporter_otlp_2 = Telemetry {
             export: Some(TelemetryExporter::Otlp(OtlpExporter {
@@ -154,6 +209,8 @@ mod tests {
                 headers: vec![KeyValue { key: "header_b".to_owned(), value: "b".to_owned() }],
             })),
             request_headers: vec!["Api-Key-B".to_owned()],
+            client_version_headers: vec!["X-Client-Version".to_owned()],
+            ..Default::default()
         };
         let exporter_prometheus_1 = Telemetry {
             export: Some(TelemetryExporter::Prometheus(PrometheusExporter {
@@ -202,7 +259,10 @@ mod tests {
                     url: "test-url-2".to_owned(),
                     headers: vec![KeyValue { key: "header_b".to_owned(), value: "b".to_owned() }]
                 })),
-                request_headers: vec!["Api-Key-A".to_string(), "Api-Key-B".to_string(),]
+                request_headers: vec!["Api-Key-A".to_string(), "Api-Key-B".to_string(),],
+                client_name_headers: vec!["X-Client-Name".to_string()],
+                client_version_headers: vec!["X-Client-Version".to_string()],
+                ..Default::default()
             }
         );
 
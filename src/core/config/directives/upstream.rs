@@ -1,9 +1,11 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
 
+use crate::core::config::{HttpVersion, Signing};
+use crate::core::json::Projection;
 use crate::core::macros::MergeRight;
 use crate::core::{default_verify_ssl, is_default, verify_ssl_is_default};
 
@@ -34,6 +36,132 @@ pub struct Proxy {
     pub url: String,
 }
 
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsCache {
+    /// The longest time in seconds an address is cached for, even when its TTL
+    /// is longer. @default `300`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub max_ttl: Option<u64>,
+    /// The time in seconds a host that doesn't exist is cached for.
+    /// @default `30`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub negative_ttl: Option<u64>,
+}
+
+impl DnsCache {
+    pub fn get_max_ttl(&self) -> u64 {
+        self.max_ttl.unwrap_or(300)
+    }
+    pub fn get_negative_ttl(&self) -> u64 {
+        self.negative_ttl.unwrap_or(30)
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLBatching {
+    /// The time in milliseconds to wait for the sibling fields before the
+    /// operation is sent. @default `1`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub delay: Option<usize>,
+    /// The most root fields merged into one upstream operation.
+    /// @default `100`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub max_size: Option<usize>,
+}
+
+impl GraphQLBatching {
+    pub fn get_delay(&self) -> usize {
+        self.delay.unwrap_or(1)
+    }
+    pub fn get_max_size(&self) -> usize {
+        self.max_size.unwrap_or(DEFAULT_MAX_SIZE)
+    }
+}
+
+/// How `{{.fields}}` writes the fields that the query selects on the value of
+/// an `@http` field, eg. `query: [{key: "fields", value: "{{.fields}}"}]`
+/// sends `?fields=id,name`.
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedFields {
+    /// Written between the fields. @default `,`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub separator: Option<String>,
+    /// How the fields of the nested objects are written. @default `IGNORE`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub nesting: Option<FieldNesting>,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    schemars::JsonSchema,
+    MergeRight,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FieldNesting {
+    /// Leaves the nested objects out, only their scalar siblings are written.
+    #[default]
+    Ignore,
+    /// Writes each field of a nested object after its path, `address.city`.
+    Dot,
+    /// Writes the fields of a nested object in parentheses, `address(city)`.
+    Parens,
+}
+
+impl SelectedFields {
+    /// Writes the keys of the selection, eg. `id,address(city,zip)`. It's
+    /// empty when the value is a scalar.
+    pub fn format(&self, selection: &Projection) -> String {
+        self.fields(selection).join(self.separator())
+    }
+
+    fn separator(&self) -> &str {
+        self.separator.as_deref().unwrap_or(",")
+    }
+
+    fn fields(&self, selection: &Projection) -> Vec<String> {
+        let Projection::Fields(fields) = selection else {
+            return vec![];
+        };
+        let nesting = self.nesting.unwrap_or_default();
+        fields
+            .iter()
+            .flat_map(|(name, selection)| match (selection, nesting) {
+                (Projection::All, _) => vec![name.clone()],
+                (_, FieldNesting::Ignore) => vec![],
+                (_, FieldNesting::Dot) => self
+                    .fields(selection)
+                    .into_iter()
+                    .map(|field| format!("{name}.{field}"))
+                    .collect(),
+                (_, FieldNesting::Parens) => {
+                    let fields = self.fields(selection);
+                    if fields.is_empty() {
+                        vec![]
+                    } else {
+                        vec![format!("{name}({})", fields.join(self.separator()))]
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -78,6 +206,23 @@ pub struct Upstream {
     /// timing out.
     pub connect_timeout: Option<u64>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Caches the DNS lookups of the upstream hosts in-process. Addresses are
+    /// kept for their TTL, up to `maxTtl`, and hosts that don't exist are
+    /// remembered for `negativeTtl`.
+    pub dns_cache: Option<DnsCache>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Static addresses of upstream hosts, eg. `{"api.internal": "10.0.0.5"}`,
+    /// that are used instead of resolving the hosts through DNS.
+    pub dns_overrides: Option<BTreeMap<String, String>>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Merges the `@graphQL` queries resolved together for the same upstream,
+    /// eg. the fields of the items of a list, into a single operation whose
+    /// root fields are aliased, and splits the response back. Off by default.
+    pub graphql_batching: Option<GraphQLBatching>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Providing httpCache size enables Tailcall's HTTP caching, adhering to the [HTTP Caching RFC](https://tools.ietf.org/html/rfc7234), to enhance performance by minimizing redundant data fetches. Defaults to `0` if unspecified.
     pub http_cache: Option<u64>,
@@ -90,6 +235,11 @@ pub struct Upstream {
     /// the server, but is automatically set to true for GRPC.
     pub http2_only: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The `httpVersion` setting is the HTTP version of the upstream requests,
+    /// `HTTP2` is the same as enabling `http2Only`. @default `HTTP1`.
+    pub http_version: Option<HttpVersion>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds between each keep-alive message sent to maintain the
     /// connection.
@@ -121,6 +271,27 @@ pub struct Upstream {
     /// enabling custom routing and security policies.
     pub proxy: Option<Proxy>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// How `{{.fields}}` writes the fields a query selects on the value of an
+    /// `@http` field, to request only those from the APIs that support sparse
+    /// fieldsets. `{{.fields}}` is missing when a nested field has its own
+    /// resolver, which can read any key, so `skipEmpty` sends the request
+    /// without the parameter.
+    pub selected_fields: Option<SelectedFields>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Signs the requests made by the `@http` fields with AWS SigV4 or with an
+    /// HMAC of the request, after all the templates are rendered and the
+    /// headers are forwarded.
+    pub signing: Option<Signing>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The size in bytes above which the JSON responses of the `@http` fields
+    /// are decoded keeping only the keys the query selects, instead of being
+    /// parsed whole. Fields whose selection needs the whole value are still
+    /// parsed whole. Off by default.
+    pub streaming_threshold: Option<usize>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds between each TCP keep-alive message sent to maintain
     /// the connection.
@@ -195,7 +366,8 @@ impl Upstream {
             .map_or(DEFAULT_MAX_SIZE, |b| b.max_size.unwrap_or(DEFAULT_MAX_SIZE))
     }
     pub fn get_http_2_only(&self) -> bool {
-        self.http2_only.unwrap_or(false)
+        self.http2_only
+            .unwrap_or(self.http_version == Some(HttpVersion::HTTP2))
     }
 
     pub fn get_on_request(&self) -> Option<String> {
@@ -257,4 +429,35 @@ mod tests {
             Some(["a", "b", "c"].iter().map(|s| s.to_string()).collect())
         );
     }
+
+    #[test]
+    fn test_selected_fields_format() {
+        let field = |name: &str, selection| (name.to_string(), selection);
+        let selection = Projection::Fields(
+            [
+                field("id", Projection::All),
+                field(
+                    "address",
+                    Projection::Fields([field("city", Projection::All)].into_iter().collect()),
+                ),
+                field("name", Projection::All),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let format = |separator: Option<&str>, nesting| {
+            SelectedFields { separator: separator.map(str::to_string), nesting }.format(&selection)
+        };
+
+        assert_eq!(format(None, None), "id,name");
+        assert_eq!(
+            format(Some(" "), Some(FieldNesting::Dot)),
+            "id address.city name"
+        );
+        assert_eq!(
+            format(None, Some(FieldNesting::Parens)),
+            "id,address(city),name"
+        );
+        assert_eq!(SelectedFields::default().format(&Projection::All), "");
+    }
 }
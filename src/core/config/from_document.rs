@@ -1,9 +1,9 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use async_graphql::parser::types::{
-    ConstDirective, EnumType, FieldDefinition, InputObjectType, InputValueDefinition,
-    InterfaceType, ObjectType, SchemaDefinition, ServiceDocument, Type, TypeDefinition, TypeKind,
-    TypeSystemDefinition, UnionType,
+    ConstDirective, DirectiveDefinition, EnumType, FieldDefinition, InputObjectType,
+    InputValueDefinition, InterfaceType, ObjectType, SchemaDefinition, ServiceDocument, Type,
+    TypeDefinition, TypeKind, TypeSystemDefinition, UnionType,
 };
 use async_graphql::parser::Positioned;
 use async_graphql::Name;
@@ -12,10 +12,13 @@ use indexmap::IndexMap;
 use tailcall_valid::{Valid, ValidationError, Validator};
 
 use super::directive::{to_directive, Directive};
-use super::{Alias, Discriminate, Resolver, RuntimeConfig, Telemetry, FEDERATION_DIRECTIVES};
+use super::{
+    Alias, Auth, Discriminate, OperationOverrides, Resolver, RuntimeConfig, Telemetry,
+    FEDERATION_DIRECTIVES,
+};
 use crate::core::config::{
-    self, Cache, Config, Enum, Link, Modify, Omit, Protected, RootSchema, Server, Union, Upstream,
-    Variant,
+    self, Cache, Config, CustomDirective, Deprecated, Enum, Link, Modify, Omit, Protected,
+    RootSchema, Server, Union, Upstream, Variant,
 };
 use crate::core::directive::DirectiveCodec;
 
@@ -37,10 +40,22 @@ pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
         })
         .collect();
 
-    let types = to_types(&type_definitions);
+    // The extensions of the types are merged into them once they are read.
+    let (type_extensions, type_definitions): (Vec<_>, Vec<_>) =
+        type_definitions.into_iter().partition(|td| {
+            td.node.extend
+                && matches!(
+                    td.node.kind,
+                    TypeKind::Object(_) | TypeKind::Interface(_) | TypeKind::InputObject(_)
+                )
+        });
+
+    let types =
+        to_types(&type_definitions).and_then(|types| to_extended_types(types, &type_extensions));
     let unions = to_union_types(&type_definitions);
     let enums = to_enum_types(&type_definitions);
     let schema = schema_definition(&doc).map(to_root_schema);
+    let directives = to_custom_directives(&doc);
     schema_definition(&doc).and_then(|sd| {
         server(sd)
             .fuse(upstream(sd))
@@ -49,11 +64,29 @@ pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
             .fuse(enums)
             .fuse(schema)
             .fuse(links(sd))
-            .fuse(telemetry(sd))
+            .fuse(telemetry(sd).zip(auth(sd)))
             .map(
-                |(server, upstream, types, unions, enums, schema, links, telemetry)| {
+                |(
+                    server,
+                    upstream,
+                    (types, extended_types),
+                    unions,
+                    enums,
+                    schema,
+                    links,
+                    (telemetry, auth),
+                )| {
                     let runtime_config = RuntimeConfig { server, upstream, links, telemetry };
-                    let config = Config { types, unions, enums, schema, ..Default::default() };
+                    let config = Config {
+                        types,
+                        extended_types,
+                        unions,
+                        enums,
+                        schema,
+                        auth,
+                        directives,
+                        ..Default::default()
+                    };
 
                     config.with_runtime_config(runtime_config)
                 },
@@ -125,6 +158,10 @@ fn telemetry(schema_definition: &SchemaDefinition) -> Valid<Telemetry, String> {
     )
 }
 
+fn auth(schema_definition: &SchemaDefinition) -> Valid<Option<Auth>, String> {
+    Auth::from_directives(schema_definition.directives.iter())
+}
+
 fn to_root_schema(schema_definition: &SchemaDefinition) -> RootSchema {
     let query = schema_definition.query.as_ref().map(pos_name_to_string);
     let mutation = schema_definition.mutation.as_ref().map(pos_name_to_string);
@@ -178,6 +215,51 @@ fn to_types(
         )
     })
 }
+
+/// Merges the `extend type` definitions into the types they extend. The
+/// extensions of the types that aren't defined in the document are kept as
+/// types, to be merged with the types of the other files.
+fn to_extended_types(
+    mut types: BTreeMap<String, config::Type>,
+    type_extensions: &[&Positioned<TypeDefinition>],
+) -> Valid<(BTreeMap<String, config::Type>, BTreeSet<String>), String> {
+    let mut extended_types = BTreeSet::new();
+
+    Valid::from_iter(type_extensions, |type_extension| {
+        to_types(&vec![*type_extension])
+    })
+    .and_then(|extensions| {
+        Valid::from_iter(
+            extensions.into_iter().flatten(),
+            |(type_name, extension)| match types.get_mut(&type_name) {
+                Some(ty) => extend_type(ty, extension).trace(&type_name),
+                None => {
+                    extended_types.insert(type_name.clone());
+                    types.insert(type_name, extension);
+                    Valid::succeed(())
+                }
+            },
+        )
+    })
+    .map(|_| (types, extended_types))
+}
+
+fn extend_type(ty: &mut config::Type, extension: config::Type) -> Valid<(), String> {
+    Valid::from_iter(extension.fields, |(field_name, field)| {
+        if ty.fields.contains_key(&field_name) {
+            Valid::fail(format!("Field `{field_name}` is already defined")).trace(&field_name)
+        } else {
+            ty.fields.insert(field_name, field);
+            Valid::succeed(())
+        }
+    })
+    .map(|_| {
+        ty.added_fields.extend(extension.added_fields);
+        ty.implements.extend(extension.implements);
+        ty.directives.extend(extension.directives);
+    })
+}
+
 fn to_scalar_type() -> config::Type {
     config::Type { ..Default::default() }
 }
@@ -324,13 +406,18 @@ where
         .map_err(|err| ValidationError::new(err.to_string()))
         .into();
     let doc = description.to_owned().map(|pos| pos.node);
+    let deprecated = Deprecated::from_directives(directives.iter());
 
     config::Resolver::from_directives(directives)
         .fuse(Cache::from_directives(directives.iter()))
         .fuse(Omit::from_directives(directives.iter()))
         .fuse(Modify::from_directives(directives.iter()))
         .fuse(Protected::from_directives(directives.iter()))
-        .fuse(Discriminate::from_directives(directives.iter()))
+        .fuse(
+            Discriminate::from_directives(directives.iter())
+                .zip(Auth::from_directives(directives.iter()))
+                .zip(OperationOverrides::from_directives(directives.iter())),
+        )
         .fuse(default_value)
         .fuse(to_federation_directives(directives))
         .map(
@@ -340,7 +427,7 @@ where
                 omit,
                 modify,
                 protected,
-                discriminate,
+                ((discriminate, auth), operation_overrides),
                 default_value,
                 directives,
             )| config::Field {
@@ -352,14 +439,43 @@ where
                 cache,
                 protected,
                 discriminate,
+                auth,
+                operation_overrides,
                 default_value,
                 resolvers,
+                deprecated,
                 directives,
             },
         )
         .trace(pos_name_to_string(field.name()).as_str())
 }
 
+fn to_custom_directives(doc: &ServiceDocument) -> Vec<CustomDirective> {
+    doc.definitions
+        .iter()
+        .filter_map(|def| match def {
+            TypeSystemDefinition::Directive(directive) => {
+                Some(to_custom_directive(&directive.node))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn to_custom_directive(directive: &DirectiveDefinition) -> CustomDirective {
+    CustomDirective {
+        name: pos_name_to_string(&directive.name),
+        doc: directive.description.to_owned().map(|pos| pos.node),
+        args: directive
+            .arguments
+            .iter()
+            .map(|arg| (pos_name_to_string(&arg.node.name), to_arg(&arg.node)))
+            .collect(),
+        locations: directive.locations.iter().map(|pos| pos.node).collect(),
+        repeatable: directive.is_repeatable,
+    }
+}
+
 fn to_args(field_definition: &FieldDefinition) -> IndexMap<String, config::Arg> {
     let mut args = IndexMap::new();
 
@@ -387,7 +503,14 @@ fn to_arg(input_value_definition: &InputValueDefinition) -> config::Arg {
     } else {
         None
     };
-    config::Arg { type_of: type_of.into(), doc, modify, default_value }
+    let deprecated = Deprecated::from_directives(input_value_definition.directives.iter());
+    config::Arg {
+        type_of: type_of.into(),
+        doc,
+        modify,
+        default_value,
+        deprecated,
+    }
 }
 
 fn to_union(union_type: UnionType, doc: &Option<String>) -> Valid<Union, String> {
@@ -403,15 +526,20 @@ fn to_union(union_type: UnionType, doc: &Option<String>) -> Valid<Union, String>
 fn to_enum(enum_type: EnumType, doc: Option<String>) -> Valid<Enum, String> {
     let variants = Valid::from_iter(enum_type.values.iter(), |member| {
         let name = member.node.value.node.as_str().to_owned();
+        let deprecated = Deprecated::from_directives(member.node.directives.iter());
         let alias = member
             .node
             .directives
             .iter()
             .find(|d| d.node.name.node.as_str() == Alias::directive_name());
         if let Some(alias) = alias {
-            Alias::from_directive(&alias.node).map(|alias| Variant { name, alias: Some(alias) })
+            Alias::from_directive(&alias.node).map(|alias| Variant {
+                name,
+                alias: Some(alias),
+                deprecated,
+            })
         } else {
-            Valid::succeed(Variant { name, alias: None })
+            Valid::succeed(Variant { name, alias: None, deprecated })
         }
     });
     variants.map(|v| Enum { variants: v.into_iter().collect::<BTreeSet<Variant>>(), doc })
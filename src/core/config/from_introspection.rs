@@ -0,0 +1,408 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use async_graphql_value::ConstValue;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::core::config::{self, Config, Deprecated, Enum, RootSchema, Union, Variant};
+use crate::core::scalar::Scalar;
+
+/// The response of the standard introspection query, with or without its
+/// `data` envelope.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Introspection {
+    Response { data: Data },
+    Data(Data),
+}
+
+#[derive(Deserialize)]
+struct Data {
+    #[serde(rename = "__schema")]
+    schema: Schema,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Schema {
+    query_type: Option<NamedType>,
+    mutation_type: Option<NamedType>,
+    subscription_type: Option<NamedType>,
+    types: Vec<FullType>,
+}
+
+#[derive(Deserialize)]
+struct NamedType {
+    name: String,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    List,
+    NonNull,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FullType {
+    kind: TypeKind,
+    name: String,
+    description: Option<String>,
+    fields: Option<Vec<Field>>,
+    input_fields: Option<Vec<InputValue>>,
+    interfaces: Option<Vec<NamedType>>,
+    enum_values: Option<Vec<EnumValue>>,
+    possible_types: Option<Vec<NamedType>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Field {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    type_of: TypeRef,
+    #[serde(default)]
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InputValue {
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "type")]
+    type_of: TypeRef,
+    default_value: Option<String>,
+    #[serde(default)]
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnumValue {
+    name: String,
+    #[serde(default)]
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypeRef {
+    kind: TypeKind,
+    name: Option<String>,
+    of_type: Option<Box<TypeRef>>,
+}
+
+/// Builds the types of a config from the introspection of a GraphQL API. The
+/// fields have no resolvers, they're left to be annotated.
+pub fn from_introspection(json: &str) -> Result<Config> {
+    let schema = match serde_json::from_str(json)? {
+        Introspection::Response { data } => data.schema,
+        Introspection::Data(data) => data.schema,
+    };
+
+    let mut config = Config {
+        schema: RootSchema {
+            query: schema.query_type.map(|t| t.name),
+            mutation: schema.mutation_type.map(|t| t.name),
+            subscription: schema.subscription_type.map(|t| t.name),
+        },
+        ..Default::default()
+    };
+
+    // The introspection lists the types of the introspection itself and the
+    // built-in scalars, which every config already has
+    let user_types = schema
+        .types
+        .into_iter()
+        .filter(|t| !t.name.starts_with("__") && !Scalar::is_predefined(&t.name));
+    for full_type in user_types {
+        let name = full_type.name.clone();
+        match full_type.kind {
+            TypeKind::Scalar => {
+                let doc = full_type.description;
+                config
+                    .types
+                    .insert(name, config::Type { doc, ..Default::default() });
+            }
+            TypeKind::Object | TypeKind::Interface => {
+                let type_of = to_object_type(full_type).with_context(|| in_type(&name))?;
+                config.types.insert(name, type_of);
+            }
+            TypeKind::InputObject => {
+                let type_of = to_input_object_type(full_type).with_context(|| in_type(&name))?;
+                config.types.insert(name, type_of);
+            }
+            TypeKind::Union => {
+                let union = Union {
+                    types: full_type
+                        .possible_types
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|t| t.name)
+                        .collect(),
+                    doc: full_type.description,
+                };
+                config.unions.insert(name, union);
+            }
+            TypeKind::Enum => {
+                let variants = full_type
+                    .enum_values
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|value| Variant {
+                        name: value.name,
+                        alias: None,
+                        deprecated: deprecated(value.is_deprecated, value.deprecation_reason),
+                    })
+                    .collect();
+                let doc = full_type.description;
+                config.enums.insert(name, Enum { variants, doc });
+            }
+            TypeKind::List | TypeKind::NonNull => {
+                return Err(anyhow!("`{name}` is a wrapping type"));
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+fn to_object_type(full_type: FullType) -> Result<config::Type> {
+    let mut fields = BTreeMap::new();
+    for field in full_type.fields.unwrap_or_default() {
+        let mut args = IndexMap::new();
+        for arg in field.args {
+            let name = arg.name.clone();
+            let arg = to_arg(arg).with_context(|| format!("in argument `{name}`"))?;
+            args.insert(name, arg);
+        }
+        let type_of =
+            to_type(&field.type_of).with_context(|| format!("in field `{}`", field.name))?;
+        let config_field = config::Field {
+            type_of,
+            args,
+            doc: field.description,
+            deprecated: deprecated(field.is_deprecated, field.deprecation_reason),
+            ..Default::default()
+        };
+        fields.insert(field.name, config_field);
+    }
+
+    Ok(config::Type {
+        fields,
+        doc: full_type.description,
+        implements: full_type
+            .interfaces
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| t.name)
+            .collect(),
+        ..Default::default()
+    })
+}
+
+fn to_input_object_type(full_type: FullType) -> Result<config::Type> {
+    let mut fields = BTreeMap::new();
+    for input_field in full_type.input_fields.unwrap_or_default() {
+        let name = input_field.name.clone();
+        let arg = to_arg(input_field).with_context(|| format!("in field `{name}`"))?;
+        let field = config::Field {
+            type_of: arg.type_of,
+            doc: arg.doc,
+            default_value: arg.default_value,
+            deprecated: arg.deprecated,
+            ..Default::default()
+        };
+        fields.insert(name, field);
+    }
+
+    Ok(config::Type { fields, doc: full_type.description, ..Default::default() })
+}
+
+fn to_arg(input_value: InputValue) -> Result<config::Arg> {
+    Ok(config::Arg {
+        type_of: to_type(&input_value.type_of)?,
+        doc: input_value.description,
+        modify: None,
+        default_value: input_value
+            .default_value
+            .as_deref()
+            .map(to_default_value)
+            .transpose()?,
+        deprecated: deprecated(input_value.is_deprecated, input_value.deprecation_reason),
+    })
+}
+
+fn to_type(type_ref: &TypeRef) -> Result<crate::core::Type> {
+    match type_ref.kind {
+        TypeKind::NonNull => Ok(to_type(of_type(type_ref)?)?.into_required()),
+        TypeKind::List => Ok(to_type(of_type(type_ref)?)?.into_list()),
+        _ => {
+            let name = type_ref.name.clone().context("a named type has no name")?;
+            Ok(name.into())
+        }
+    }
+}
+
+fn of_type(type_ref: &TypeRef) -> Result<&TypeRef> {
+    type_ref
+        .of_type
+        .as_deref()
+        .context("a wrapping type has no `ofType`")
+}
+
+/// The default values are GraphQL literals, eg. `{limit: 10}` or `ACTIVE`,
+/// which are read as the default value of a variable.
+fn to_default_value(literal: &str) -> Result<serde_json::Value> {
+    let query = format!("query($value: Int = {literal}) {{ __typename }}");
+    let document = async_graphql::parser::parse_query(query)
+        .map_err(|e| anyhow!("invalid default value `{literal}`: {e}"))?;
+    let value = document
+        .operations
+        .iter()
+        .next()
+        .and_then(|(_, operation)| {
+            let variable = operation.node.variable_definitions.first()?;
+            variable.node.default_value.clone()
+        })
+        .map(|value| value.node)
+        .unwrap_or(ConstValue::Null);
+    Ok(value.into_json()?)
+}
+
+fn in_type(name: &str) -> String {
+    format!("in type `{name}`")
+}
+
+fn deprecated(is_deprecated: bool, reason: Option<String>) -> Option<Deprecated> {
+    is_deprecated.then_some(Deprecated { reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tailcall_valid::Validator;
+
+    use super::*;
+
+    #[test]
+    fn test_from_introspection() {
+        let named = |kind: &str, name: &str| json!({"kind": kind, "name": name, "ofType": null});
+        let non_null = |of_type| json!({"kind": "NON_NULL", "name": null, "ofType": of_type});
+        let list = |of_type| json!({"kind": "LIST", "name": null, "ofType": of_type});
+        let field = |name: &str, type_of| json!({"name": name, "args": [], "type": type_of, "isDeprecated": false});
+        let introspection = json!({"data": {"__schema": {
+            "queryType": {"name": "Query"},
+            "mutationType": null,
+            "subscriptionType": null,
+            "types": [
+                {"kind": "SCALAR", "name": "String"},
+                {"kind": "SCALAR", "name": "Int"},
+                {"kind": "SCALAR", "name": "Cursor"},
+                {"kind": "OBJECT", "name": "__Type", "fields": []},
+                {"kind": "OBJECT", "name": "Query", "interfaces": [], "fields": [
+                    {
+                        "name": "users",
+                        "args": [
+                            {"name": "filter", "type": named("INPUT_OBJECT", "UserFilter"), "defaultValue": null},
+                            {"name": "limit", "type": named("SCALAR", "Int"), "defaultValue": "10"},
+                        ],
+                        "type": non_null(list(non_null(named("OBJECT", "User")))),
+                        "isDeprecated": false,
+                    },
+                    field("search", list(named("UNION", "SearchResult"))),
+                ]},
+                {"kind": "INTERFACE", "name": "Node", "fields": [
+                    field("id", non_null(named("SCALAR", "ID"))),
+                ]},
+                {"kind": "OBJECT", "name": "User", "description": "A user", "interfaces": [{"name": "Node"}], "fields": [
+                    field("id", non_null(named("SCALAR", "ID"))),
+                    field("status", named("ENUM", "Status")),
+                    field("cursor", named("SCALAR", "Cursor")),
+                    {"name": "login", "args": [], "type": named("SCALAR", "String"), "isDeprecated": true, "deprecationReason": "Use `id`"},
+                ]},
+                {"kind": "OBJECT", "name": "Post", "interfaces": [{"name": "Node"}], "fields": [
+                    field("id", non_null(named("SCALAR", "ID"))),
+                ]},
+                {"kind": "UNION", "name": "SearchResult", "possibleTypes": [{"name": "User"}, {"name": "Post"}]},
+                {"kind": "ENUM", "name": "Status", "enumValues": [
+                    {"name": "ACTIVE", "isDeprecated": false},
+                    {"name": "BANNED", "isDeprecated": true, "deprecationReason": null},
+                ]},
+                {"kind": "INPUT_OBJECT", "name": "UserFilter", "inputFields": [
+                    {"name": "status", "type": named("ENUM", "Status"), "defaultValue": "ACTIVE"},
+                    {"name": "ids", "type": list(named("SCALAR", "ID")), "defaultValue": "[\"1\", \"2\"]"},
+                ]},
+            ],
+        }}});
+
+        let actual = Config::from_introspection(&introspection.to_string()).unwrap();
+
+        let expected = Config::from_sdl(
+            r#"
+            schema {
+              query: Query
+            }
+
+            scalar Cursor
+
+            type Query {
+              users(filter: UserFilter, limit: Int = 10): [User!]!
+              search: [SearchResult]
+            }
+
+            interface Node {
+              id: ID!
+            }
+
+            "A user"
+            type User implements Node {
+              id: ID!
+              status: Status
+              cursor: Cursor
+              login: String @deprecated(reason: "Use `id`")
+            }
+
+            type Post implements Node {
+              id: ID!
+            }
+
+            union SearchResult = User | Post
+
+            enum Status {
+              ACTIVE
+              BANNED @deprecated
+            }
+
+            input UserFilter {
+              status: Status = ACTIVE
+              ids: [ID] = ["1", "2"]
+            }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        assert_eq!(actual.to_sdl(), expected.to_sdl());
+    }
+
+    #[test]
+    fn test_invalid_introspection() {
+        let error = Config::from_introspection(r#"{"data": {"types": []}}"#).unwrap_err();
+        assert!(error.to_string().contains("did not match any variant"));
+    }
+}
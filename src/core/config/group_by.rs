@@ -1,6 +1,10 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use url::Url;
 
 use crate::core::is_default;
+use crate::core::json::{group_by_keys, join_key, JsonLike};
 
 /// The `groupBy` parameter groups multiple data requests into a single call. For more details please refer out [n + 1 guide](https://tailcall.run/docs/guides/n+1#solving-using-batching).
 #[derive(Clone, Debug, Eq, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
@@ -9,11 +13,27 @@ pub struct GroupBy {
     path: Vec<String>,
     #[serde(default, skip_serializing_if = "is_default")]
     key: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    composite: Vec<CompositeKey>,
+}
+
+/// A component of a composite key, the dot-path of the field in each item of
+/// the response and the query parameter of the request that holds its value.
+#[derive(Clone, Debug, Eq, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CompositeKey {
+    pub path: String,
+    pub query: String,
 }
 
 impl GroupBy {
     pub fn new(path: Vec<String>, key: Option<String>) -> Self {
-        Self { path, key }
+        Self { path, key, composite: Vec::new() }
+    }
+
+    /// Groups the items at the root of the response by all the components of
+    /// the key.
+    pub fn composite(composite: Vec<CompositeKey>) -> Self {
+        Self { path: Vec::new(), key: None, composite }
     }
 
     pub fn path(&self) -> Vec<String> {
@@ -34,12 +54,136 @@ impl GroupBy {
             }
         }
     }
+
+    /// The query parameter that holds the key of each request, when it's sent
+    /// in one.
+    pub fn query_key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// The query parameters that hold the key of each request.
+    pub fn query_keys(&self) -> Vec<&str> {
+        if self.composite.is_empty() {
+            vec![self.key()]
+        } else {
+            self.composite
+                .iter()
+                .map(|key| key.query.as_str())
+                .collect()
+        }
+    }
+
+    /// Reads the key of a request from its query parameters.
+    pub fn request_key(&self, url: &Url) -> Option<String> {
+        let query: HashMap<_, _> = url.query_pairs().collect();
+        self.query_keys()
+            .iter()
+            .map(|key| query.get(*key).map(|value| value.to_string()))
+            .collect::<Option<Vec<_>>>()
+            .map(join_key)
+    }
+
+    /// Groups the items of the response by their keys. The last element of the
+    /// path can be a dot-path to a nested field of each item, eg.
+    /// `attributes.id`.
+    pub fn group<'json, J: JsonLike<'json>>(
+        &self,
+        body: &'json J,
+    ) -> HashMap<String, Vec<&'json J>> {
+        if !self.composite.is_empty() {
+            let keys = self
+                .composite
+                .iter()
+                .map(|key| split_path(&key.path))
+                .collect::<Vec<_>>();
+            return group_by_keys(body, &[], &keys);
+        }
+
+        let path = self.path();
+        match path.split_last() {
+            Some((key, items)) if key.contains('.') => {
+                group_by_keys(body, items, &[split_path(key)])
+            }
+            _ => body.group_by(&path),
+        }
+    }
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.split('.').map(String::from).collect()
 }
 
 const ID: &str = "id";
 
 impl Default for GroupBy {
     fn default() -> Self {
-        Self { path: vec![ID.to_string()], key: None }
+        Self { path: vec![ID.to_string()], key: None, composite: Vec::new() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_group_dot_path() {
+        let body = json!({
+            "users": [
+                {"attributes": {"id": 1}, "name": "foo"},
+                {"attributes": {"id": 2}, "name": "bar"}
+            ]
+        });
+        let group_by = GroupBy::new(vec!["users".into(), "attributes.id".into()], None);
+
+        let groups = group_by.group(&body);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["1"], vec![&body["users"][0]]);
+        assert_eq!(groups["2"], vec![&body["users"][1]]);
+    }
+
+    #[test]
+    fn test_group_composite() {
+        let body = json!([
+            {"tenantId": "a", "user": {"id": 1}},
+            {"tenantId": "b", "user": {"id": 1}},
+            {"tenantId": "a", "user": {"id": 2}}
+        ]);
+        let group_by = GroupBy::composite(vec![
+            CompositeKey { path: "tenantId".into(), query: "tenant".into() },
+            CompositeKey { path: "user.id".into(), query: "user".into() },
+        ]);
+
+        let groups = group_by.group(&body);
+        let key = |url: &str| group_by.request_key(&url.parse().unwrap()).unwrap();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(group_by.query_keys(), vec!["tenant", "user"]);
+        assert_eq!(groups[&key("http://x/?tenant=a&user=1")], vec![&body[0]]);
+        assert_eq!(groups[&key("http://x/?user=1&tenant=b")], vec![&body[1]]);
+        assert_eq!(groups[&key("http://x/?tenant=a&user=2")], vec![&body[2]]);
+    }
+
+    #[test]
+    fn test_group_missing_key() {
+        let body = json!([
+            {"attributes": {"id": 1}},
+            {"attributes": {}},
+            {"name": "foo"}
+        ]);
+        let group_by = GroupBy::new(vec!["attributes.id".into()], None);
+
+        let groups = group_by.group(&body);
+
+        assert_eq!(groups.len(), 1);
+        assert!(groups.contains_key("1"));
+        assert!(!groups.contains_key("2"));
+        assert_eq!(
+            GroupBy::composite(vec![CompositeKey { path: "id".into(), query: "id".into() }])
+                .request_key(&"http://x/?user=1".parse().unwrap()),
+            None
+        );
     }
 }
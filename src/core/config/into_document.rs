@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
 use async_graphql::parser::types::*;
 use async_graphql::Positioned;
 use async_graphql_value::{ConstValue, Name};
@@ -19,6 +21,8 @@ fn config_document(config: &Config) -> ServiceDocument {
         pos(config.upstream.to_directive()),
     ];
 
+    directives.extend(config.auth.as_ref().map(|auth| pos(auth.to_directive())));
+
     directives.extend(config.links.iter().map(|link| {
         let mut directive = link.to_directive();
 
@@ -56,6 +60,31 @@ fn config_document(config: &Config) -> ServiceDocument {
             .map(|name| pos(Name::new(name))),
     };
     definitions.push(TypeSystemDefinition::Schema(pos(schema_definition)));
+    for directive in config.directives.iter() {
+        definitions.push(TypeSystemDefinition::Directive(pos(DirectiveDefinition {
+            description: directive.doc.clone().map(pos),
+            name: pos(Name::new(&directive.name)),
+            arguments: directive
+                .args
+                .iter()
+                .map(|(name, arg)| {
+                    pos(InputValueDefinition {
+                        description: arg.doc.clone().map(pos),
+                        name: pos(Name::new(name)),
+                        ty: pos((&arg.type_of).into()),
+                        default_value: transform_default_value(arg.default_value.clone()).map(pos),
+                        directives: arg
+                            .deprecated
+                            .iter()
+                            .map(|deprecated| pos(deprecated.to_directive()))
+                            .collect(),
+                    })
+                })
+                .collect(),
+            is_repeatable: directive.repeatable,
+            locations: directive.locations.iter().copied().map(pos).collect(),
+        })));
+    }
     let interface_types = config.interfaces_types_map();
     let input_types = config.input_types();
     for (type_name, type_def) in config.types.iter() {
@@ -132,7 +161,11 @@ fn config_document(config: &Config) -> ServiceDocument {
                                         arg.default_value.clone(),
                                     )
                                     .map(pos),
-                                    directives: Vec::new(),
+                                    directives: arg
+                                        .deprecated
+                                        .iter()
+                                        .map(|deprecated| pos(deprecated.to_directive()))
+                                        .collect(),
                                 })
                             })
                             .collect::<Vec<Positioned<InputValueDefinition>>>();
@@ -191,8 +224,15 @@ fn config_document(config: &Config) -> ServiceDocument {
                             value: pos(Name::new(&variant.name)),
                             directives: variant
                                 .alias
-                                .clone()
-                                .map_or(vec![], |v| vec![pos(v.to_directive())]),
+                                .iter()
+                                .map(|alias| pos(alias.to_directive()))
+                                .chain(
+                                    variant
+                                        .deprecated
+                                        .iter()
+                                        .map(|deprecated| pos(deprecated.to_directive())),
+                                )
+                                .collect(),
                         })
                     })
                     .collect(),
@@ -200,7 +240,119 @@ fn config_document(config: &Config) -> ServiceDocument {
         })));
     }
 
-    ServiceDocument { definitions }
+    ServiceDocument { definitions: sort_by_dependencies(definitions) }
+}
+
+/// Orders the type definitions so that every type comes after the types it
+/// references. Types are visited in alphabetical order, which keeps the output
+/// stable and decides the order within cycles.
+fn sort_by_dependencies(definitions: Vec<TypeSystemDefinition>) -> Vec<TypeSystemDefinition> {
+    let names = definitions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, definition)| match definition {
+            TypeSystemDefinition::Type(type_def) => Some((type_def.node.name.node.as_str(), index)),
+            _ => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let mut visited = HashSet::with_capacity(definitions.len());
+    let mut order = Vec::with_capacity(definitions.len());
+
+    // the schema and directive definitions stay in front
+    for (index, definition) in definitions.iter().enumerate() {
+        if !matches!(definition, TypeSystemDefinition::Type(_)) {
+            visited.insert(index);
+            order.push(index);
+        }
+    }
+
+    for index in names.values() {
+        visit(*index, &definitions, &names, &mut visited, &mut order);
+    }
+
+    // definitions that share a name with another one weren't reached
+    for index in 0..definitions.len() {
+        if visited.insert(index) {
+            order.push(index);
+        }
+    }
+
+    let mut definitions = definitions.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .into_iter()
+        .filter_map(|index| definitions[index].take())
+        .collect()
+}
+
+fn visit(
+    index: usize,
+    definitions: &[TypeSystemDefinition],
+    names: &BTreeMap<&str, usize>,
+    visited: &mut HashSet<usize>,
+    order: &mut Vec<usize>,
+) {
+    if !visited.insert(index) {
+        return;
+    }
+
+    if let TypeSystemDefinition::Type(type_def) = &definitions[index] {
+        for dependency in dependencies(&type_def.node) {
+            if let Some(dependency) = names.get(dependency) {
+                visit(*dependency, definitions, names, visited, order);
+            }
+        }
+    }
+
+    order.push(index);
+}
+
+/// Names of the types referenced by the definition.
+fn dependencies(type_def: &TypeDefinition) -> BTreeSet<&str> {
+    fn field_types(fields: &[Positioned<FieldDefinition>]) -> impl Iterator<Item = &str> {
+        fields.iter().flat_map(|field| {
+            std::iter::once(base_name(&field.node.ty.node.base)).chain(
+                field
+                    .node
+                    .arguments
+                    .iter()
+                    .map(|arg| base_name(&arg.node.ty.node.base)),
+            )
+        })
+    }
+
+    match &type_def.kind {
+        TypeKind::Object(object) => object
+            .implements
+            .iter()
+            .map(|name| name.node.as_str())
+            .chain(field_types(&object.fields))
+            .collect(),
+        TypeKind::Interface(interface) => interface
+            .implements
+            .iter()
+            .map(|name| name.node.as_str())
+            .chain(field_types(&interface.fields))
+            .collect(),
+        TypeKind::InputObject(input) => input
+            .fields
+            .iter()
+            .map(|field| base_name(&field.node.ty.node.base))
+            .collect(),
+        TypeKind::Union(union) => union
+            .members
+            .iter()
+            .map(|name| name.node.as_str())
+            .collect(),
+        TypeKind::Scalar | TypeKind::Enum(_) => BTreeSet::new(),
+    }
+}
+
+fn base_name(base: &BaseType) -> &str {
+    match base {
+        BaseType::Named(name) => name.as_str(),
+        BaseType::List(ty) => base_name(&ty.base),
+    }
 }
 
 fn into_directives(
@@ -221,6 +373,14 @@ fn field_directives(field: &crate::core::config::Field) -> Vec<Positioned<ConstD
         .chain(field.omit.as_ref().map(|d| pos(d.to_directive())))
         .chain(field.cache.as_ref().map(|d| pos(d.to_directive())))
         .chain(field.protected.as_ref().map(|d| pos(d.to_directive())))
+        .chain(field.auth.as_ref().map(|d| pos(d.to_directive())))
+        .chain(
+            field
+                .operation_overrides
+                .as_ref()
+                .map(|d| pos(d.to_directive())),
+        )
+        .chain(field.deprecated.as_ref().map(|d| pos(d.to_directive())))
         .chain(into_directives(&field.directives))
         .collect()
 }
@@ -1,10 +1,12 @@
 pub use apollo::*;
 pub use config::*;
 pub use config_module::*;
+pub use diff::*;
 pub use directive::Directive;
 pub use directives::*;
 pub use key_values::*;
 pub use npo::QueryPath;
+pub use profile::*;
 pub use reader_context::*;
 pub use resolver::*;
 pub use source::*;
@@ -13,14 +15,17 @@ mod apollo;
 mod config;
 mod config_module;
 pub mod cors;
+mod diff;
 mod directive;
 pub mod directives;
 mod from_document;
+mod from_introspection;
 pub mod group_by;
 mod headers;
 mod into_document;
 mod key_values;
 mod npo;
+mod profile;
 pub mod reader;
 pub mod reader_context;
 mod resolver;
@@ -0,0 +1,170 @@
+//! Profiles overlay the runtime part of a config, i.e. `server`, `upstream`,
+//! `links` and `telemetry`, with the settings of an environment, so that
+//! dev, staging and prod can share the same base config.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{Config, RuntimeConfig, Source};
+
+/// Deep merges `overlay` into `base`. Objects are merged key by key, any other
+/// value, lists included, replaces the one of `base` and an explicit `null`
+/// removes the key.
+fn merge_overlay(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                if value.is_null() {
+                    base.remove(&key);
+                } else {
+                    merge_overlay(base.entry(key).or_insert(Value::Null), value);
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// The path of the overlay of `profile` for the config at `path`, placed next
+/// to it, eg. `tailcall.prod.yml` for `tailcall.graphql`. The overlays of
+/// GraphQL configs are written in YAML.
+pub fn overlay_path(path: &str, profile: &str) -> String {
+    let ext = match Source::detect(path) {
+        Ok(Source::Json) => Source::Json.ext(),
+        _ => Source::Yml.ext(),
+    };
+    let stem = Path::new(path)
+        .extension()
+        .and_then(|ext| path.strip_suffix(ext.to_str()?))
+        .and_then(|stem| stem.strip_suffix('.'))
+        .unwrap_or(path);
+
+    format!("{stem}.{profile}.{ext}")
+}
+
+impl Config {
+    /// Merges the overlay of a profile over the runtime part of the config,
+    /// see [merge_overlay]. The merge is done on the parsed config, so the
+    /// errors of the validation refer to the merged result.
+    pub fn with_overlay(self, overlay: Value) -> anyhow::Result<Self> {
+        let runtime_config = RuntimeConfig {
+            server: self.server.clone(),
+            upstream: self.upstream.clone(),
+            links: self.links.clone(),
+            telemetry: self.telemetry.clone(),
+        };
+        let mut value = serde_json::to_value(runtime_config)?;
+        merge_overlay(&mut value, overlay);
+        let runtime_config: RuntimeConfig = serde_path_to_error::deserialize(value)?;
+
+        Ok(self.with_runtime_config(runtime_config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tailcall_valid::Validator;
+
+    use super::*;
+    use crate::core::config::KeyValue;
+
+    fn config() -> Config {
+        Config::from_sdl(
+            r#"
+            schema
+                @server(
+                    vars: [{key: "BASE_URL", value: "http://localhost:3000"}]
+                    headers: {custom: [{key: "X-Env", value: "dev"}]}
+                )
+                @upstream(timeout: 10) {
+                query: Query
+            }
+            type Query { users: [Int] @http(url: "{{.vars.BASE_URL}}/users") }
+            "#,
+        )
+        .to_result()
+        .unwrap()
+    }
+
+    fn key_value(key: &str, value: &str) -> KeyValue {
+        KeyValue { key: key.to_string(), value: value.to_string() }
+    }
+
+    #[test]
+    fn test_override_base_url() {
+        let config = config()
+            .with_overlay(json!({
+                "server": {"vars": [{"key": "BASE_URL", "value": "https://api.example.com"}]},
+                "upstream": {"timeout": 30}
+            }))
+            .unwrap();
+
+        assert_eq!(
+            config.server.vars,
+            vec![key_value("BASE_URL", "https://api.example.com")]
+        );
+        assert_eq!(config.upstream.timeout, Some(30));
+        assert_eq!(
+            config.server.headers.unwrap().custom,
+            vec![key_value("X-Env", "dev")]
+        );
+        assert!(config.types.contains_key("Query"));
+    }
+
+    #[test]
+    fn test_add_header() {
+        let config = config()
+            .with_overlay(json!({
+                "server": {"headers": {"custom": [
+                    {"key": "X-Env", "value": "prod"},
+                    {"key": "Strict-Transport-Security", "value": "max-age=31536000"}
+                ]}}
+            }))
+            .unwrap();
+
+        assert_eq!(
+            config.server.headers.unwrap().custom,
+            vec![
+                key_value("X-Env", "prod"),
+                key_value("Strict-Transport-Security", "max-age=31536000")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_with_null() {
+        let config = config()
+            .with_overlay(json!({"upstream": {"timeout": null}}))
+            .unwrap();
+
+        assert_eq!(config.upstream.timeout, None);
+        assert_eq!(
+            config.server.vars,
+            vec![key_value("BASE_URL", "http://localhost:3000")]
+        );
+    }
+
+    #[test]
+    fn test_invalid_overlay() {
+        let error = config()
+            .with_overlay(json!({"upstream": {"timeout": "slow"}}))
+            .unwrap_err();
+
+        assert!(error.to_string().contains("upstream.timeout"));
+    }
+
+    #[test]
+    fn test_overlay_path() {
+        assert_eq!(
+            overlay_path("tailcall.graphql", "prod"),
+            "tailcall.prod.yml"
+        );
+        assert_eq!(
+            overlay_path("configs/tailcall.json", "prod"),
+            "configs/tailcall.prod.json"
+        );
+        assert_eq!(overlay_path("tailcall.yaml", "dev"), "tailcall.dev.yml");
+    }
+}
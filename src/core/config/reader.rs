@@ -1,19 +1,26 @@
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use futures_util::future::join_all;
+use futures_util::future::{join_all, BoxFuture};
+use futures_util::{stream, StreamExt};
+use jsonwebtoken::jwk::JwkSet;
 use rustls_pemfile;
 use rustls_pki_types::{
     CertificateDer, PrivateKeyDer, PrivatePkcs1KeyDer, PrivatePkcs8KeyDer, PrivateSec1KeyDer,
 };
 use tailcall_valid::{Valid, ValidationError, Validator};
+use tracing::Instrument;
 use url::Url;
 
-use super::{ConfigModule, Content, Link, LinkType, PrivateKey};
+use super::{overlay_path, ConfigModule, Content, Extensions, Link, LinkType, PrivateKey};
 use crate::core::config::{Config, ConfigReaderContext, Source};
-use crate::core::proto_reader::ProtoReader;
-use crate::core::resource_reader::{Cached, Resource, ResourceReader};
-use crate::core::rest::EndpointSet;
+use crate::core::http::Mock;
+use crate::core::proto_reader::{ProtoMetadata, ProtoReader};
+use crate::core::resource_reader::{Cached, FileRead, Resource, ResourceReader};
+use crate::core::rest::{EndpointSet, Unchecked};
 use crate::core::runtime::TargetRuntime;
+use crate::core::timing::{Phase, Timings};
 use crate::core::variance::Invariant;
 
 /// Reads the configuration from a file or from an HTTP URL and resolves all
@@ -22,6 +29,8 @@ pub struct ConfigReader {
     runtime: TargetRuntime,
     resource_reader: ResourceReader<Cached>,
     proto_reader: ProtoReader,
+    timings: Timings,
+    globs: Arc<Mutex<BTreeSet<String>>>,
 }
 
 impl ConfigReader {
@@ -31,24 +40,41 @@ impl ConfigReader {
             runtime: runtime.clone(),
             resource_reader: resource_reader.clone(),
             proto_reader: ProtoReader::init(resource_reader, runtime),
+            timings: Timings::default(),
+            globs: Default::default(),
         }
     }
 
-    /// Reads the links in a Config and fill the content
+    /// The time spent reading each file and link.
+    pub fn timings(&self) -> Timings {
+        self.timings.clone()
+    }
+
+    /// The globs of the links read so far, relative to the current directory,
+    /// so that the files added later can be watched for.
+    pub fn globs(&self) -> Vec<String> {
+        self.globs.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Reads the links in a Config and fill the content. The links are read
+    /// concurrently, at most [MAX_CONCURRENT_LINKS] at a time, and applied in
+    /// the order they're declared, so the result doesn't depend on which
+    /// finishes first. The linked configs have their links read too, `chain`
+    /// is the paths of the configs that link to this one, to report cycles.
     async fn ext_links(
         &self,
         config_module: ConfigModule,
         parent_dir: Option<&Path>,
+        chain: &[String],
     ) -> anyhow::Result<ConfigModule> {
-        let reader_ctx = ConfigReaderContext::new(&self.runtime);
-
         let links: Vec<Link> = config_module
             .config()
             .links
             .clone()
             .iter()
             .filter_map(|link| {
-                if link.src.is_empty() {
+                // the remote globs are reported by the validation
+                if link.src.is_empty() || (link.is_remote() && link.is_glob()) {
                     return None;
                 }
                 Some(link.to_owned())
@@ -59,91 +85,234 @@ impl ConfigReader {
             return Ok(config_module);
         }
 
-        let mut extensions = config_module.extensions().clone();
-        let mut config_module = Valid::succeed(config_module);
-
-        for link in links.iter() {
-            let path = Self::resolve_path(&link.src, parent_dir);
-
-            match link.type_of {
-                LinkType::Config => {
-                    let source = self
-                        .resource_reader
-                        .read_file(path)
-                        .await?
-                        .render(&reader_ctx);
-                    let content = source.content;
-                    let config = Config::from_source(Source::detect(&source.path)?, &content)?;
-                    config_module = config_module.and_then(|config_module| {
-                        config_module.unify(ConfigModule::from(config.clone()))
-                    });
-                }
-                LinkType::Protobuf => {
-                    let proto_paths = link.proto_paths.as_ref().map(|paths| {
-                        paths
-                            .iter()
-                            .map(|p| Self::resolve_path(p, parent_dir))
-                            .collect::<Vec<_>>()
-                    });
-                    let meta = self.proto_reader.read(path, proto_paths.as_deref()).await?;
-                    extensions.add_proto(meta);
-                }
-                LinkType::Script => {
-                    let source = self.resource_reader.read_file(path).await?;
-                    let content = source.content;
-                    extensions.script = Some(content);
-                }
-                LinkType::Cert => {
-                    let source = self.resource_reader.read_file(path).await?;
-                    let content = source.content;
-                    extensions.cert.extend(self.load_cert(content).await?);
-                }
-                LinkType::Key => {
-                    let source = self.resource_reader.read_file(path).await?;
-                    let content = source.content;
-                    extensions.keys = self.load_private_key(content).await?
-                }
-                LinkType::Operation => {
-                    let source = self.resource_reader.read_file(path).await?;
-                    let content = source.content;
+        let paths = Valid::from_iter(links.iter(), |link| {
+            Valid::from(
+                self.link_paths(link, parent_dir, chain)
+                    .map_err(to_validation_error),
+            )
+            .and_then(|paths| check_cycle(link, paths, chain))
+            .trace(&link.src)
+        })
+        .to_result()?;
+        let sources = links
+            .iter()
+            .zip(paths)
+            .flat_map(|(link, paths)| paths.into_iter().map(move |path| (link, path)))
+            .collect::<Vec<_>>();
 
-                    extensions.endpoint_set = EndpointSet::try_new(&content)?;
+        let results = stream::iter(sources)
+            .map(|(link, path)| {
+                let mut timer = self.timings.start(Phase::Link, Some(&path));
+                let span = timer.span();
+                async move {
+                    let result = self.read_link(link, path.clone(), parent_dir, chain).await;
+                    if let Ok((_, bytes)) = &result {
+                        if let Some(bytes) = bytes {
+                            timer.bytes(*bytes);
+                        }
+                        timer.finish();
+                    }
+                    (link, path, result)
                 }
-                LinkType::Htpasswd => {
-                    let source = self.resource_reader.read_file(path).await?;
-                    let content = source.content;
+                .instrument(span)
+            })
+            .buffered(MAX_CONCURRENT_LINKS)
+            .collect::<Vec<_>>()
+            .await;
 
-                    extensions
-                        .htpasswd
-                        .push(Content { id: link.id.clone(), content });
-                }
-                LinkType::Jwks => {
-                    let source = self.resource_reader.read_file(path).await?;
-                    let content = source.content;
+        // every link is read before failing, so that all the broken ones are
+        // reported together
+        let linked = Valid::from_iter(results, |(link, path, result)| {
+            Valid::from(result.map_err(to_validation_error))
+                .map(|(linked, _)| (link, linked))
+                .trace(&path)
+        })
+        .to_result()?;
 
-                    let de = &mut serde_json::Deserializer::from_str(&content);
+        let mut extensions = config_module.extensions().clone();
+        let mut config_modules = vec![];
 
-                    extensions.jwks.push(Content {
-                        id: link.id.clone(),
-                        content: serde_path_to_error::deserialize(de)?,
-                    })
-                }
-                LinkType::Grpc => {
-                    let meta = self
-                        .proto_reader
-                        .fetch(link.src.as_str(), link.headers.clone())
-                        .await?;
-
-                    for m in meta {
-                        extensions.add_proto(m);
+        for (link, linked) in linked {
+            match linked {
+                Linked::Config(linked_module) => config_modules.push(linked_module),
+                Linked::Protos(protos) => {
+                    for meta in protos {
+                        extensions.add_proto(&link.src, meta);
                     }
                 }
+                Linked::Script(script) => extensions.script = Some(script),
+                Linked::Cert(cert) => extensions.cert.extend(cert),
+                Linked::Keys(keys) => extensions.keys = keys,
+                Linked::Operations(endpoint_set) => extensions.endpoint_set = endpoint_set,
+                Linked::Htpasswd(htpasswd) => extensions.htpasswd.push(htpasswd),
+                Linked::Jwks(jwks) => extensions.jwks.push(jwks),
+                Linked::Mocks(mocks) => extensions.mocks.extend(mocks),
             }
         }
 
-        Ok(config_module
-            .map(|config_module| config_module.set_extensions(extensions))
-            .to_result()?)
+        // the extensions of the linked configs come from their own links
+        let config_module = config_modules.into_iter().fold(
+            Valid::succeed(config_module.set_extensions(extensions)),
+            |config_module, linked_module| {
+                config_module.and_then(|config_module| config_module.unify(linked_module))
+            },
+        );
+
+        Ok(config_module.to_result()?)
+    }
+
+    /// The paths a link reads, the files matching its glob in lexicographic
+    /// order or its single source. A glob doesn't match the config that
+    /// declares it, the last one of the `chain`.
+    fn link_paths(
+        &self,
+        link: &Link,
+        parent_dir: Option<&Path>,
+        chain: &[String],
+    ) -> anyhow::Result<Vec<String>> {
+        let path = Self::resolve_path(&link.src, parent_dir);
+        if !link.is_glob() {
+            return Ok(vec![path]);
+        }
+
+        let parent = chain.last();
+        let mut paths = glob::glob(&path)?
+            .filter(|entry| entry.as_ref().map_or(true, |path| path.is_file()))
+            .map(|entry| Ok(entry?.to_string_lossy().to_string()))
+            .filter(|path| {
+                path.as_ref()
+                    .map_or(true, |path| Some(&normalize_path(path)) != parent)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        paths.sort();
+        self.globs.lock().unwrap().insert(path);
+
+        if paths.is_empty() && !link.optional {
+            return Err(anyhow::anyhow!(
+                "The glob doesn't match any file, set `optional: true` to allow it"
+            ));
+        }
+        Ok(paths)
+    }
+
+    /// Reads and parses a single link, with the number of bytes read when
+    /// it's a single file.
+    async fn read_link(
+        &self,
+        link: &Link,
+        path: String,
+        parent_dir: Option<&Path>,
+        chain: &[String],
+    ) -> anyhow::Result<(Linked, Option<usize>)> {
+        let reader_ctx = ConfigReaderContext::new(&self.runtime);
+
+        let linked = match link.type_of {
+            LinkType::Config => {
+                let source = self
+                    .resource_reader
+                    .read_file(path.clone())
+                    .await?
+                    .render(&reader_ctx);
+                let bytes = source.content.len();
+                let config = parse_blocking(move || {
+                    Config::from_source(Source::detect(&source.path)?, &source.content)
+                })
+                .await?;
+
+                let chain = chain
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(normalize_path(&path)))
+                    .collect::<Vec<_>>();
+                // boxed, since the links of the linked config are read with
+                // this same function
+                let linked_module: BoxFuture<'_, anyhow::Result<ConfigModule>> =
+                    Box::pin(self.ext_links(
+                        ConfigModule::from(config),
+                        Path::new(&path).parent(),
+                        &chain,
+                    ));
+                (Linked::Config(linked_module.await?), Some(bytes))
+            }
+            LinkType::Protobuf => {
+                let proto_paths = link.proto_paths.as_ref().map(|paths| {
+                    paths
+                        .iter()
+                        .map(|p| Self::resolve_path(p, parent_dir))
+                        .collect::<Vec<_>>()
+                });
+                let meta = self.proto_reader.read(path, proto_paths.as_deref()).await?;
+                (Linked::Protos(vec![meta]), None)
+            }
+            LinkType::Script => {
+                let source = self.resource_reader.read_file(path).await?;
+                let bytes = source.content.len();
+                (Linked::Script(source.content), Some(bytes))
+            }
+            LinkType::Cert => {
+                let source = self.resource_reader.read_file(path).await?;
+                let bytes = source.content.len();
+                (
+                    Linked::Cert(self.load_cert(source.content).await?),
+                    Some(bytes),
+                )
+            }
+            LinkType::Key => {
+                let source = self.resource_reader.read_file(path).await?;
+                let bytes = source.content.len();
+                let keys = self.load_private_key(source.content).await?;
+                (Linked::Keys(keys), Some(bytes))
+            }
+            LinkType::Operation => {
+                let source = self.resource_reader.read_file(path).await?;
+                let content = source.content;
+                let bytes = content.len();
+                let endpoint_set =
+                    parse_blocking(move || Ok(EndpointSet::try_new(&content)?)).await?;
+                (Linked::Operations(endpoint_set), Some(bytes))
+            }
+            LinkType::Htpasswd => {
+                let source = self.resource_reader.read_file(path).await?;
+                let content = source.content;
+                let bytes = content.len();
+                let htpasswd = Content { id: link.id.clone(), content };
+                (Linked::Htpasswd(htpasswd), Some(bytes))
+            }
+            LinkType::Jwks => {
+                let source = self.resource_reader.read_file(path).await?;
+                let content = source.content;
+                let bytes = content.len();
+                let jwks = parse_blocking(move || {
+                    let de = &mut serde_json::Deserializer::from_str(&content);
+                    Ok(serde_path_to_error::deserialize(de)?)
+                })
+                .await?;
+                (
+                    Linked::Jwks(Content { id: link.id.clone(), content: jwks }),
+                    Some(bytes),
+                )
+            }
+            LinkType::Mocks => {
+                let source = self.resource_reader.read_file(path).await?;
+                let content = source.content;
+                let bytes = content.len();
+                let mocks = parse_blocking(move || {
+                    let de = &mut serde_json::Deserializer::from_str(&content);
+                    Ok(serde_path_to_error::deserialize::<_, Vec<Mock>>(de)?)
+                })
+                .await?;
+                (Linked::Mocks(mocks), Some(bytes))
+            }
+            LinkType::Grpc => {
+                let meta = self
+                    .proto_reader
+                    .fetch(link.src.as_str(), link.headers.clone())
+                    .await?;
+                (Linked::Protos(meta), None)
+            }
+        };
+
+        Ok(linked)
     }
 
     /// Reads the certificate from a given file
@@ -188,26 +357,45 @@ impl ConfigReader {
         &self,
         files: &[T],
     ) -> anyhow::Result<ConfigModule> {
-        let reader_ctx = ConfigReaderContext::new(&self.runtime);
+        let reader_ctx = &ConfigReaderContext::new(&self.runtime);
 
-        let files = self
-            .resource_reader
-            .read_files(files)
-            .await?
-            .into_iter()
-            .map(|file| file.render(&reader_ctx))
-            .collect::<Vec<_>>();
+        // each file is timed from its read until its links are resolved
+        let files = join_all(files.iter().map(|path| {
+            let mut timer = self.timings.start(Phase::File, Some(&path.to_string()));
+            let span = timer.span();
+            async move {
+                let file = self
+                    .resource_reader
+                    .read_file(path.clone())
+                    .await
+                    .map_err(|e| e.context(path.to_string()))?
+                    .render(reader_ctx);
+                timer.bytes(file.content.len());
+                anyhow::Ok((file, timer))
+            }
+            .instrument(span)
+        }))
+        .await
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
-        let mut config_modules = join_all(files.iter().map(|file| async {
-            let source = Source::detect(&file.path)?;
-            let schema = &file.content;
+        let mut config_modules = join_all(files.into_iter().map(|(file, timer)| {
+            let span = timer.span();
+            async move {
+                let source = Source::detect(&file.path)?;
 
-            // Create initial config module
-            self.resolve(
-                Config::from_source(source, schema)?,
-                Path::new(&file.path).parent(),
-            )
-            .await
+                // Create initial config module
+                let config_module = self
+                    .resolve_linked(
+                        Config::from_source(source, &file.content)?,
+                        Path::new(&file.path).parent(),
+                        &[normalize_path(&file.path)],
+                    )
+                    .await?;
+                timer.finish();
+                anyhow::Ok(config_module)
+            }
+            .instrument(span)
         }))
         .await
         .into_iter();
@@ -225,14 +413,110 @@ impl ConfigReader {
             })
         });
 
+        // The types extended in a file must be defined in one of the others.
+        let config_module = config_module.and_then(|config_module| {
+            Valid::from_iter(&config_module.extended_types, |type_name| {
+                Valid::<(), String>::fail("The extended type isn't defined".to_string())
+                    .trace(type_name)
+            })
+            .map_to(config_module)
+        });
+
         Ok(config_module.to_result()?)
     }
 
+    /// Reads the config stored in the environment variable `var_name`. The
+    /// format isn't known, so it's parsed as GraphQL, then YAML, then JSON,
+    /// and the first that succeeds is returned. When none does, the error of
+    /// every format is reported.
+    pub fn from_env(&self, var_name: &str) -> anyhow::Result<Config> {
+        let content = self
+            .runtime
+            .env
+            .get(var_name)
+            .ok_or_else(|| anyhow::anyhow!("The environment variable `{var_name}` is not set"))?
+            .to_string();
+        let reader_ctx = ConfigReaderContext::new(&self.runtime);
+        let file = FileRead { content, path: var_name.to_string() }.render(&reader_ctx);
+
+        let mut errors = vec![];
+        for source in [Source::GraphQL, Source::Yml, Source::Json] {
+            match Config::from_source(source, &file.content) {
+                Ok(config) => return Ok(config),
+                Err(error) => errors.push(format!("{source}: {error}")),
+            }
+        }
+        Err(anyhow::anyhow!(
+            "The environment variable `{var_name}` isn't a GraphQL, YAML or JSON config\n{}",
+            errors.join("\n")
+        ))
+    }
+
+    /// Reads all the files and merges the overlay of the `profile` over the
+    /// result. The overlay is read from next to the first file, see
+    /// [overlay_path], and its links are resolved relative to it.
+    pub async fn read_all_with_profile<T: Into<Resource> + Clone + ToString + Send + Sync>(
+        &self,
+        files: &[T],
+        profile: Option<&str>,
+    ) -> anyhow::Result<ConfigModule> {
+        let config_module = self.read_all(files).await?;
+        let (Some(profile), Some(file)) = (profile, files.first()) else {
+            return Ok(config_module);
+        };
+
+        let reader_ctx = ConfigReaderContext::new(&self.runtime);
+        let path = overlay_path(&file.to_string(), profile);
+        let source = self
+            .resource_reader
+            .read_file(path.clone())
+            .await?
+            .render(&reader_ctx);
+        let overlay = match Source::detect(&source.path)? {
+            Source::Json => serde_json::from_str(&source.content)?,
+            _ => serde_yaml_ng::from_str(&source.content)?,
+        };
+        let config = config_module.config().clone().with_overlay(overlay)?;
+
+        let links = config
+            .links
+            .iter()
+            .filter(|link| !config_module.config().links.contains(link))
+            .cloned()
+            .collect::<Vec<_>>();
+        let linked = self
+            .ext_links(
+                ConfigModule::from(Config::default().links(links)),
+                Path::new(&path).parent(),
+                &[],
+            )
+            .await?;
+
+        // The settings of the base config are kept when unifying, so only the
+        // types and the extensions of the links are added.
+        let config_module = ConfigModule::new(config, config_module.extensions().clone())
+            .unify(linked)
+            .to_result()?;
+        Ok(self.read_env_vars(config_module))
+    }
+
     /// Resolves all the links in a Config to create a ConfigModule
     pub async fn resolve(
+        &self,
+        config: Config,
+        parent_dir: Option<&Path>,
+    ) -> anyhow::Result<ConfigModule> {
+        self.resolve_linked(config, parent_dir, &[]).await
+    }
+
+    /// Resolves the links of a Config read from a file, `chain` is the path
+    /// of the file when it's known, so that a link back to it is reported as
+    /// a cycle.
+    async fn resolve_linked(
         &self,
         mut config: Config,
         parent_dir: Option<&Path>,
+        chain: &[String],
     ) -> anyhow::Result<ConfigModule> {
         // Setup telemetry in Config
         let vars = &config
@@ -245,7 +529,25 @@ impl ConfigReader {
         config.telemetry.render_mustache(&reader_ctx)?;
 
         // Create initial config set & extend it with the links
-        self.ext_links(ConfigModule::from(config), parent_dir).await
+        let config_module = self
+            .ext_links(ConfigModule::from(config), parent_dir, chain)
+            .await?;
+        Ok(self.read_env_vars(config_module))
+    }
+
+    /// Reads the environment variables of the upstream credentials once, so
+    /// that they aren't read again on every request and the missing ones fail
+    /// the validation instead of sending empty credentials.
+    fn read_env_vars(&self, config_module: ConfigModule) -> ConfigModule {
+        let env_vars = config_module
+            .credential_env_vars()
+            .into_iter()
+            .filter_map(|name| {
+                let value = self.runtime.env.get(&name)?.into_owned();
+                Some((name, value))
+            })
+            .collect();
+        config_module.merge_extensions(Extensions { env_vars, ..Default::default() })
     }
 
     /// Checks if path is a URL or absolute path, returns directly if so.
@@ -262,6 +564,76 @@ impl ConfigReader {
     }
 }
 
+/// The number of links of a config that are read at the same time.
+const MAX_CONCURRENT_LINKS: usize = 16;
+
+/// What a link resolved to, kept until all the links are read to be applied
+/// in their order.
+enum Linked {
+    Config(ConfigModule),
+    Protos(Vec<ProtoMetadata>),
+    Script(String),
+    Cert(Vec<CertificateDer<'static>>),
+    Keys(Vec<PrivateKey>),
+    Operations(EndpointSet<Unchecked>),
+    Htpasswd(Content<String>),
+    Jwks(Content<JwkSet>),
+    Mocks(Vec<Mock>),
+}
+
+/// Parses a source on the blocking pool, so that a large one doesn't hold
+/// the thread that reads the other links.
+#[cfg(not(target_arch = "wasm32"))]
+async fn parse_blocking<T: Send + 'static>(
+    parse: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    tokio::task::spawn_blocking(parse).await?
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn parse_blocking<T: Send + 'static>(
+    parse: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    parse()
+}
+
+/// Fails when one of the paths of a config link is a config that links to
+/// it, directly or through other configs, with the cycle as the message.
+fn check_cycle(link: &Link, paths: Vec<String>, chain: &[String]) -> Valid<Vec<String>, String> {
+    if link.type_of != LinkType::Config {
+        return Valid::succeed(paths);
+    }
+    Valid::from_iter(paths, |path| {
+        let path = normalize_path(&path);
+        match chain.iter().position(|linking| linking == &path) {
+            Some(index) => {
+                let cycle = chain[index..]
+                    .iter()
+                    .chain(std::iter::once(&path))
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                Valid::fail(format!("The links form a cycle: {cycle}"))
+            }
+            None => Valid::succeed(path),
+        }
+    })
+}
+
+/// The path without its `.` components, so that the same file linked as
+/// `./a.graphql` and `a.graphql` is recognized.
+fn normalize_path(path: &str) -> String {
+    if Url::parse(path).is_ok() {
+        return path.to_string();
+    }
+    Path::new(path)
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect::<PathBuf>()
+        .to_string_lossy()
+        .to_string()
+}
+
 fn to_validation_error(error: anyhow::Error) -> ValidationError<String> {
     match error.downcast::<ValidationError<String>>() {
         Ok(err) => err,
@@ -271,12 +643,19 @@ fn to_validation_error(error: anyhow::Error) -> ValidationError<String> {
 
 #[cfg(test)]
 mod reader_tests {
+    use std::collections::HashMap;
     use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
 
     use pretty_assertions::assert_eq;
+    use tailcall_valid::ValidationError;
 
     use crate::core::config::reader::ConfigReader;
     use crate::core::config::{Config, Type};
+    use crate::core::tests::TestEnvIO;
+    use crate::core::FileIO;
 
     fn start_mock_server() -> httpmock::MockServer {
         httpmock::MockServer::start()
@@ -325,6 +704,66 @@ mod reader_tests {
         bar_mock.assert();
     }
 
+    fn env_reader(value: &str) -> ConfigReader {
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.env = Arc::new(TestEnvIO::from_iter([(
+            "CONFIG".to_owned(),
+            value.to_owned(),
+        )]));
+        ConfigReader::init(runtime)
+    }
+
+    #[test]
+    fn test_from_env() {
+        let sdl = "schema @server(port: 8000) { query: Query }\ntype Query { hello: String }";
+        let yaml = "server:\n  port: 8000\nlinks:\n  - src: ./jsonplaceholder.graphql\n";
+        let json = r#"{"server": {"port": 8000}, "links": [{"src": "./jsonplaceholder.graphql"}]}"#;
+
+        let config = env_reader(sdl).from_env("CONFIG").unwrap();
+        assert_eq!(config.server.port, Some(8000));
+        assert!(config.types.contains_key("Query"));
+
+        for value in [yaml, json] {
+            let config = env_reader(value).from_env("CONFIG").unwrap();
+            assert_eq!(config.server.port, Some(8000));
+            assert_eq!(config.links[0].src, "./jsonplaceholder.graphql");
+        }
+    }
+
+    #[test]
+    fn test_from_env_errors() {
+        let reader = env_reader("type Query {");
+
+        assert_eq!(
+            reader.from_env("MISSING").unwrap_err().to_string(),
+            "The environment variable `MISSING` is not set"
+        );
+        let error = reader.from_env("CONFIG").unwrap_err().to_string();
+        assert!(error.starts_with(
+            "The environment variable `CONFIG` isn't a GraphQL, YAML or JSON config\n"
+        ));
+        for source in ["GraphQL", "YML", "JSON"] {
+            assert!(error.contains(&format!("\n{source}: ")));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_credential_env_vars() {
+        let sdl = r#"
+            schema @auth(basicAuth: {userEnv: "CONFIG", passEnv: "MISSING"}) { query: Query }
+            type Query { hello: String }
+        "#;
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        let config_module = env_reader("admin").resolve(config, None).await.unwrap();
+
+        assert_eq!(
+            config_module.extensions().env_vars,
+            [("CONFIG".to_string(), "admin".to_string())]
+                .into_iter()
+                .collect()
+        );
+    }
+
     #[tokio::test]
     async fn test_local_files() {
         let runtime = crate::core::runtime::test::init(None);
@@ -372,6 +811,363 @@ mod reader_tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_extend_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, sdl: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, sdl).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let users = write(
+            "users.graphql",
+            "schema @server @upstream { query: Query }\ntype Query { users: [Int] @expr(body: [1]) }",
+        );
+        let posts = write(
+            "posts.graphql",
+            "extend type Query { posts: [Int] @expr(body: [2]) }",
+        );
+        let reader = ConfigReader::init(crate::core::runtime::test::init(None));
+
+        let config = reader.read_all(&[users, posts.clone()]).await.unwrap();
+        assert_eq!(
+            config.types["Query"].fields.keys().collect::<Vec<_>>(),
+            ["posts", "users"]
+        );
+        assert!(config.extended_types.is_empty());
+
+        let error = reader.read_all(&[posts]).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation Error\n• The extended type isn't defined [Query]\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timing_spans() {
+        use std::sync::Mutex;
+
+        use tracing::span::{Attributes, Id};
+        use tracing::Subscriber;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::registry::LookupSpan;
+        use tracing_subscriber::Layer;
+
+        use crate::core::timing::Phase;
+
+        /// Captures the names of the spans and of their parents.
+        #[derive(Clone, Default)]
+        struct Spans(Arc<Mutex<Vec<(String, Option<String>)>>>);
+
+        impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Spans {
+            fn on_new_span(&self, _: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+                let span = ctx.span(id).unwrap();
+                let parent = span.parent().map(|parent| parent.name().to_string());
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((span.name().to_string(), parent));
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, sdl: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, sdl).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let users = write("users.graphql", "type User { id: Int }");
+        let posts = write("posts.graphql", "type Post { id: Int }");
+        let app = write(
+            "app.graphql",
+            r#"schema @server @upstream @link(src: "users.graphql", type: Config) @link(src: "posts.graphql", type: Config) { query: Query }
+            type Query { users: [User] @expr(body: []) posts: [Post] @expr(body: []) }"#,
+        );
+        let reader = ConfigReader::init(crate::core::runtime::test::init(None));
+        let spans = Spans::default();
+        let subscriber = tracing_subscriber::registry().with(spans.clone());
+
+        {
+            let _guard = tracing::subscriber::set_default(subscriber);
+            reader.read(app.clone()).await.unwrap();
+        }
+
+        let file = ("config.file".to_string(), None);
+        let link = ("config.link".to_string(), Some("config.file".to_string()));
+        let spans = spans.0.lock().unwrap().clone();
+        assert_eq!(
+            spans
+                .into_iter()
+                .filter(|(name, _)| name.starts_with("config."))
+                .collect::<Vec<_>>(),
+            [file, link.clone(), link]
+        );
+
+        let phases = reader.timings().phases();
+        assert_eq!(
+            phases
+                .iter()
+                .map(|timing| (timing.phase, timing.source.clone().unwrap()))
+                .collect::<Vec<_>>(),
+            [
+                (Phase::File, app),
+                (Phase::Link, users),
+                (Phase::Link, posts)
+            ]
+        );
+        assert_eq!(phases[1].bytes, Some("type User { id: Int }".len()));
+    }
+
+    /// Files kept in memory, each read after its delay.
+    /// Files that take a while to read, counting how many are read at once.
+    #[derive(Default)]
+    struct SlowFiles {
+        files: HashMap<&'static str, (u64, &'static str)>,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl FileIO for SlowFiles {
+        async fn write<'a>(&'a self, path: &'a str, _: &'a [u8]) -> anyhow::Result<()> {
+            Err(anyhow::anyhow!(
+                "Can't write {path}, the files are read-only"
+            ))
+        }
+
+        async fn read<'a>(&'a self, path: &'a str) -> anyhow::Result<String> {
+            let (delay, content) = self
+                .files
+                .get(path)
+                .ok_or(anyhow::anyhow!("File not found"))?;
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(*delay)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(content.to_string())
+        }
+    }
+
+    fn slow_reader(
+        files: impl IntoIterator<Item = (&'static str, (u64, &'static str))>,
+    ) -> (ConfigReader, Arc<SlowFiles>) {
+        let files =
+            Arc::new(SlowFiles { files: files.into_iter().collect(), ..Default::default() });
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.file = files.clone();
+        (ConfigReader::init(runtime), files)
+    }
+
+    #[tokio::test]
+    async fn test_links_are_read_concurrently() {
+        let app = r#"
+            schema @server @upstream
+              @link(src: "users.graphql", type: Config)
+              @link(src: "posts.graphql", type: Config)
+              @link(src: "first.js", type: Script)
+              @link(src: "last.js", type: Script) {
+              query: Query
+            }
+            type Query { users: [User] @expr(body: []) posts: [Post] @expr(body: []) }
+        "#;
+        // the links declared first finish last
+        let (reader, files) = slow_reader([
+            ("app.graphql", (0, app)),
+            ("users.graphql", (300, "type User { id: Int }")),
+            ("posts.graphql", (200, "type Post { id: Int }")),
+            ("first.js", (200, "function first() {}")),
+            ("last.js", (50, "function last() {}")),
+        ]);
+
+        let config = reader.read("app.graphql").await.unwrap();
+
+        assert_eq!(files.max_in_flight.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            config.types.keys().collect::<Vec<_>>(),
+            ["Post", "Query", "User"]
+        );
+        assert_eq!(
+            config.extensions().script.as_deref(),
+            Some("function last() {}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_link_errors() {
+        let app = r#"
+            schema @server @upstream
+              @link(src: "users.graphql", type: Config)
+              @link(src: "missing.graphql", type: Config)
+              @link(src: "invalid.graphql", type: Config) {
+              query: Query
+            }
+            type Query { users: [User] @expr(body: []) }
+        "#;
+        let (reader, _) = slow_reader([
+            ("app.graphql", (0, app)),
+            ("users.graphql", (0, "type User { id: Int }")),
+            ("invalid.graphql", (0, "type Post {")),
+        ]);
+
+        let error = reader.read("app.graphql").await.unwrap_err();
+        let error = error.downcast::<ValidationError<String>>().unwrap();
+        let sources = error
+            .as_vec()
+            .iter()
+            .map(|cause| cause.trace.front().cloned().unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        assert_eq!(sources, ["missing.graphql", "invalid.graphql"]);
+    }
+
+    #[tokio::test]
+    async fn test_nested_links() {
+        let app = r#"
+            schema @server @upstream @link(src: "types/users.graphql", type: Config) {
+              query: Query
+            }
+            type Query { users: [User] @expr(body: []) }
+        "#;
+        let users = r#"
+            schema @link(src: "posts.graphql", type: Config) { query: Query }
+            type User { id: Int posts: [Post] }
+        "#;
+        let (reader, _) = slow_reader([
+            ("app.graphql", (0, app)),
+            ("types/users.graphql", (0, users)),
+            ("types/posts.graphql", (0, "type Post { id: Int }")),
+        ]);
+
+        let config = reader.read("app.graphql").await.unwrap();
+
+        assert_eq!(
+            config.types.keys().collect::<Vec<_>>(),
+            ["Post", "Query", "User"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_link_cycle() {
+        let app = r#"
+            schema @server @upstream @link(src: "./users.graphql", type: Config) {
+              query: Query
+            }
+            type Query { users: [User] @expr(body: []) }
+        "#;
+        let users = r#"
+            schema @link(src: "app.graphql", type: Config) { query: Query }
+            type User { id: Int }
+        "#;
+        let (reader, _) = slow_reader([("app.graphql", (0, app)), ("users.graphql", (0, users))]);
+
+        let error = reader.read("app.graphql").await.unwrap_err();
+        let error = error.downcast::<ValidationError<String>>().unwrap();
+        let messages = error
+            .as_vec()
+            .iter()
+            .map(|cause| cause.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            ["The links form a cycle: app.graphql -> users.graphql -> app.graphql"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_glob_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let users = write("types/users.graphql", "type User { id: Int }");
+        let posts = write("types/posts.graphql", "type Post { id: Int }");
+        write("types/notes.txt", "type Note { id: Int }");
+        write("types/old/comments.graphql", "type Comment { id: Int }");
+        let app = write(
+            "app.graphql",
+            r#"schema @server @upstream @link(src: "./types/*.graphql", type: Config) { query: Query }
+            type Query { users: [User] @expr(body: []) posts: [Post] @expr(body: []) }"#,
+        );
+        let reader = ConfigReader::init(crate::core::runtime::test::init(None));
+
+        let config = reader.read(app).await.unwrap();
+        assert_eq!(
+            config.types.keys().collect::<Vec<_>>(),
+            ["Post", "Query", "User"]
+        );
+        let links = reader
+            .timings()
+            .phases()
+            .into_iter()
+            .filter_map(|timing| timing.source)
+            .skip(1)
+            .map(|source| Path::new(&source).file_name().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            [
+                Path::new(&posts).file_name().unwrap(),
+                Path::new(&users).file_name().unwrap()
+            ]
+        );
+        assert_eq!(reader.globs().len(), 1);
+        assert!(reader.globs()[0].ends_with("*.graphql"));
+    }
+
+    #[tokio::test]
+    async fn test_glob_links_skip_the_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        write("users.graphql", "type User { id: Int }");
+        let app = write(
+            "app.graphql",
+            r#"schema @server @upstream @link(src: "./*.graphql", type: Config) { query: Query }
+            type Query { users: [User] @expr(body: []) }"#,
+        );
+        let reader = ConfigReader::init(crate::core::runtime::test::init(None));
+
+        let config = reader.read(app).await.unwrap();
+        assert_eq!(config.types.keys().collect::<Vec<_>>(), ["Query", "User"]);
+    }
+
+    #[tokio::test]
+    async fn test_glob_links_without_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let required = write(
+            "required.graphql",
+            r#"schema @server @upstream @link(src: "./types/*.graphql", type: Config) { query: Query }
+            type Query { hello: String @expr(body: "world") }"#,
+        );
+        let optional = write(
+            "optional.graphql",
+            r#"schema @server @upstream @link(src: "./types/*.graphql", type: Config, optional: true) { query: Query }
+            type Query { hello: String @expr(body: "world") }"#,
+        );
+        let reader = ConfigReader::init(crate::core::runtime::test::init(None));
+
+        let error = reader.read(required).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation Error\n• The glob doesn't match any file, set `optional: true` to allow it [./types/*.graphql]\n"
+        );
+
+        let config = reader.read(optional).await.unwrap();
+        assert_eq!(config.types.keys().collect::<Vec<_>>(), ["Query"]);
+    }
+
     #[test]
     fn test_relative_path() {
         let path_dir = Path::new("abc/xyz");
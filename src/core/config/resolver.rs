@@ -43,7 +43,7 @@ pub enum Resolver {
 impl Resolver {
     pub fn is_batched(&self) -> bool {
         match self {
-            Resolver::Http(http) => !http.batch_key.is_empty(),
+            Resolver::Http(http) => !http.batch_key.is_empty() || !http.batch_keys.is_empty(),
             Resolver::Grpc(grpc) => !grpc.batch_key.is_empty(),
             Resolver::Graphql(graphql) => graphql.batch,
             Resolver::ApolloFederation(ApolloFederation::EntityResolver(entity_resolver)) => {
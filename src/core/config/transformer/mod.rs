@@ -4,6 +4,8 @@ mod improve_type_names;
 mod merge_types;
 mod nested_unions;
 mod preset;
+mod relay_pagination;
+mod rename_fields;
 mod rename_types;
 mod required;
 mod subgraph;
@@ -16,6 +18,8 @@ pub use improve_type_names::ImproveTypeNames;
 pub use merge_types::TypeMerger;
 pub use nested_unions::NestedUnions;
 pub use preset::Preset;
+pub use relay_pagination::RelayPagination;
+pub use rename_fields::RenameFields;
 pub use rename_types::RenameTypes;
 pub use required::Required;
 pub use subgraph::Subgraph;
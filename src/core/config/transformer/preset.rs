@@ -10,6 +10,9 @@ pub struct Preset {
     pub merge_type: f32,
     pub tree_shake: bool,
     pub infer_type_names: bool,
+    /// Asks the LLM for the names of the root fields with an auto-generated
+    /// name, see `InferFieldNames`.
+    pub infer_field_names: bool,
     pub unwrap_single_field_types: bool,
 }
 
@@ -19,6 +22,7 @@ impl Preset {
             merge_type: 0.0,
             tree_shake: false,
             infer_type_names: true,
+            infer_field_names: false,
             unwrap_single_field_types: true,
         }
     }
@@ -47,6 +51,7 @@ impl Default for Preset {
         Self {
             merge_type: 1.0,
             infer_type_names: true,
+            infer_field_names: false,
             tree_shake: true,
             unwrap_single_field_types: false,
         }
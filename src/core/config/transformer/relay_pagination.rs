@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use tailcall_valid::{Valid, Validator};
+
+use crate::core::config::{self, Arg, Config, Field, PaginationStrategy, Resolver, URLQuery};
+use crate::core::{Transform, Type};
+
+const PAGE_INFO_TYPE_NAME: &str = "PageInfo";
+
+/// The arguments of a connection with their types, sent as the query
+/// parameters of the same name.
+const CURSOR_ARGS: [(&str, &str); 4] = [
+    ("first", "Int"),
+    ("after", "String"),
+    ("last", "Int"),
+    ("before", "String"),
+];
+
+/// Turns the list fields with `@http(paginationStrategy: RELAY)` into Relay
+/// connections. The fields get the cursor arguments, which are sent as query
+/// parameters, and return a `Connection` type that is read as is from the
+/// response. Followed by [spec](https://relay.dev/graphql/connections.htm)
+pub struct RelayPagination;
+
+impl Transform for RelayPagination {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, mut config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        // The item type of each connection, by the name of the connection
+        let mut connections = BTreeMap::new();
+
+        let valid = Valid::from_iter(config.types.iter_mut(), |(type_name, ty)| {
+            Valid::from_iter(ty.fields.iter_mut(), |(field_name, field)| {
+                into_connection(field)
+                    .map(|connection| connections.extend(connection))
+                    .trace(field_name)
+            })
+            .trace(type_name)
+        });
+
+        if valid.is_fail() {
+            return valid.map_to(config);
+        }
+
+        if !connections.is_empty() {
+            config
+                .types
+                .entry(PAGE_INFO_TYPE_NAME.to_string())
+                .or_insert_with(page_info_type);
+        }
+        for (connection_name, node) in connections {
+            let edge_name = format!("{}Edge", node.name());
+            config
+                .types
+                .entry(connection_name)
+                .or_insert_with(|| connection_type(&edge_name));
+            config
+                .types
+                .entry(edge_name)
+                .or_insert_with(|| edge_type(node));
+        }
+
+        Valid::succeed(config)
+    }
+}
+
+/// Rewrites the field into a connection, returning the name of the connection
+/// and the type of its nodes.
+fn into_connection(field: &mut Field) -> Valid<Option<(String, Type)>, String> {
+    let Some(http) = field
+        .resolvers
+        .0
+        .iter_mut()
+        .find_map(|resolver| match resolver {
+            Resolver::Http(http) if http.pagination_strategy == Some(PaginationStrategy::Relay) => {
+                Some(http)
+            }
+            _ => None,
+        })
+    else {
+        return Valid::succeed(None);
+    };
+
+    if http.paginate.is_some() {
+        return Valid::fail("paginationStrategy can't be used with paginate".to_string());
+    }
+    let Type::List { of_type, non_null } = &field.type_of else {
+        return Valid::fail(format!(
+            "paginationStrategy RELAY needs a list type, found {:?}",
+            field.type_of
+        ));
+    };
+
+    // The field is now a connection, transforming it again would fail
+    http.pagination_strategy = None;
+    for (name, type_name) in CURSOR_ARGS {
+        if !http.query.iter().any(|query| query.key == name) {
+            http.query.push(URLQuery {
+                key: name.to_string(),
+                value: format!("{{{{.args.{name}}}}}"),
+                skip_empty: Some(true),
+                ..Default::default()
+            });
+        }
+        field.args.entry(name.to_string()).or_insert_with(|| Arg {
+            type_of: Type::from(type_name.to_string()),
+            ..Default::default()
+        });
+    }
+
+    let node = of_type.as_ref().clone();
+    let connection_name = format!("{}Connection", node.name());
+    let mut type_of = Type::from(connection_name.clone());
+    if *non_null {
+        type_of = type_of.into_required();
+    }
+    field.type_of = type_of;
+
+    Valid::succeed(Some((connection_name, node)))
+}
+
+fn connection_type(edge_name: &str) -> config::Type {
+    config::Type {
+        fields: [
+            (
+                "edges".to_string(),
+                Field {
+                    type_of: Type::from(edge_name.to_string()).into_list(),
+                    ..Default::default()
+                },
+            ),
+            (
+                "pageInfo".to_string(),
+                Field {
+                    type_of: Type::from(PAGE_INFO_TYPE_NAME.to_string()).into_required(),
+                    ..Default::default()
+                },
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+}
+
+fn edge_type(node: Type) -> config::Type {
+    config::Type {
+        fields: [
+            (
+                "cursor".to_string(),
+                Field {
+                    type_of: Type::from("String".to_string()).into_required(),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node".to_string(),
+                Field { type_of: node, ..Default::default() },
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+}
+
+fn page_info_type() -> config::Type {
+    let field = |type_name: &str, non_null: bool| Field {
+        type_of: Type::Named { name: type_name.to_string(), non_null },
+        ..Default::default()
+    };
+
+    config::Type {
+        fields: [
+            ("hasNextPage".to_string(), field("Boolean", true)),
+            ("hasPreviousPage".to_string(), field("Boolean", true)),
+            ("startCursor".to_string(), field("String", false)),
+            ("endCursor".to_string(), field("String", false)),
+        ]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::Validator;
+
+    use super::RelayPagination;
+    use crate::core::config::{Config, Resolver};
+    use crate::core::transform::Transform;
+
+    #[test]
+    fn test_relay_pagination() {
+        let config = Config::from_sdl(
+            r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              users: [User!]! @http(url: "http://upstream/users", paginationStrategy: RELAY)
+            }
+
+            type User {
+              id: Int
+            }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let config = RelayPagination.transform(config).to_result().unwrap();
+
+        let users = &config.types["Query"].fields["users"];
+        assert_eq!(format!("{:?}", users.type_of), "UserConnection!");
+        assert_eq!(
+            users.args.keys().collect::<Vec<_>>(),
+            ["first", "after", "last", "before"]
+        );
+        let Some(Resolver::Http(http)) = users.resolvers.0.first() else {
+            panic!("users should be resolved with @http");
+        };
+        assert_eq!(
+            http.query
+                .iter()
+                .map(|query| format!("{}={}", query.key, query.value))
+                .collect::<Vec<_>>(),
+            [
+                "first={{.args.first}}",
+                "after={{.args.after}}",
+                "last={{.args.last}}",
+                "before={{.args.before}}"
+            ]
+        );
+
+        let connection = &config.types["UserConnection"].fields;
+        assert_eq!(format!("{:?}", connection["edges"].type_of), "[UserEdge]");
+        assert_eq!(format!("{:?}", connection["pageInfo"].type_of), "PageInfo!");
+        let edge = &config.types["UserEdge"].fields;
+        assert_eq!(format!("{:?}", edge["node"].type_of), "User!");
+        assert_eq!(format!("{:?}", edge["cursor"].type_of), "String!");
+        assert!(config.types.contains_key("PageInfo"));
+    }
+
+    #[test]
+    fn test_relay_pagination_not_list() {
+        let config = Config::from_sdl(
+            r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              user: User @http(url: "http://upstream/user", paginationStrategy: RELAY)
+            }
+
+            type User {
+              id: Int
+            }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let error = RelayPagination.transform(config).to_result().unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Validation Error\n• paginationStrategy RELAY needs a list type, found User [Query, user]\n"
+        );
+    }
+}
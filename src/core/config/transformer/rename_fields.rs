@@ -0,0 +1,178 @@
+use indexmap::IndexMap;
+use tailcall_valid::{Valid, Validator};
+
+use crate::core::config::{Config, Resolver};
+use crate::core::Transform;
+
+/// A transformer that renames fields of existing types with suggested names.
+/// The `@call` steps that call a renamed root field are renamed with it.
+pub struct RenameFields(IndexMap<(String, String), String>);
+
+impl RenameFields {
+    /// Takes the type, the field and its new name.
+    pub fn new<I: Iterator<Item = (S, S, S)>, S: ToString>(suggested_names: I) -> Self {
+        Self(
+            suggested_names
+                .map(|(type_name, field_name, name)| {
+                    (
+                        (type_name.to_string(), field_name.to_string()),
+                        name.to_string(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Transform for RenameFields {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        let mut config = config;
+
+        Valid::from_iter(self.0.iter(), |((type_name, field_name), name)| {
+            let Some(type_) = config.types.get_mut(type_name) else {
+                return Valid::fail(format!("Type '{}' not found in configuration.", type_name));
+            };
+            if type_.fields.contains_key(name) {
+                return Valid::fail(format!(
+                    "Field '{}' already exists on type '{}'.",
+                    name, type_name
+                ));
+            }
+            match type_.fields.remove(field_name) {
+                Some(field) => {
+                    type_.fields.insert(name.to_owned(), field);
+                    Valid::succeed(())
+                }
+                None => Valid::fail(format!(
+                    "Field '{}' not found on type '{}'.",
+                    field_name, type_name
+                )),
+            }
+        })
+        .map(|_| self.rename_steps(config))
+    }
+}
+
+impl RenameFields {
+    /// Points the `@call` steps at the new names of the query and mutation
+    /// fields they call.
+    fn rename_steps(&self, mut config: Config) -> Config {
+        let query = config.schema.query.clone();
+        let mutation = config.schema.mutation.clone();
+        let rename = |root: &Option<String>, field: &mut Option<String>| {
+            if let (Some(root), Some(field)) = (root, field) {
+                if let Some(name) = self.0.get(&(root.clone(), field.clone())) {
+                    *field = name.clone();
+                }
+            }
+        };
+
+        let resolvers = config.types.values_mut().flat_map(|type_| {
+            type_.resolvers.0.iter_mut().chain(
+                type_
+                    .fields
+                    .values_mut()
+                    .flat_map(|field| field.resolvers.0.iter_mut()),
+            )
+        });
+        for resolver in resolvers {
+            let Resolver::Call(call) = resolver else {
+                continue;
+            };
+            for step in call.steps.iter_mut() {
+                rename(&query, &mut step.query);
+                rename(&mutation, &mut step.mutation);
+            }
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailcall_valid::{ValidationError, Validator};
+
+    use super::RenameFields;
+    use crate::core::config::{Config, Resolver};
+    use crate::core::transform::Transform;
+
+    fn config() -> Config {
+        Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query {
+              users: [User] @http(url: "http://upstream/users")
+              GEN__getUsersId(id: Int!): User @http(url: "http://upstream/users/{{.args.id}}")
+            }
+            type User { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rename_fields() {
+        let config = RenameFields::new([("Query", "GEN__getUsersId", "user")].into_iter())
+            .transform(config())
+            .to_result()
+            .unwrap();
+
+        let query = &config.types["Query"];
+        assert!(query.fields.contains_key("user"));
+        assert!(!query.fields.contains_key("GEN__getUsersId"));
+        assert_eq!(query.fields["user"].args.len(), 1);
+    }
+
+    #[test]
+    fn test_rename_fields_call_steps() {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query {
+              GEN__getUsersId(id: Int!): User @http(url: "http://upstream/users/{{.args.id}}")
+              admin: User @call(steps: [{query: "GEN__getUsersId", args: {id: 1}}])
+            }
+            type User { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let config = RenameFields::new([("Query", "GEN__getUsersId", "user")].into_iter())
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        let Some(Resolver::Call(call)) = config.types["Query"].fields["admin"].resolvers.0.first()
+        else {
+            panic!("expected a @call resolver");
+        };
+        assert_eq!(call.steps[0].query.as_deref(), Some("user"));
+    }
+
+    #[test]
+    fn test_rename_fields_errors() {
+        let actual = RenameFields::new(
+            [
+                ("Query", "GEN__getUsersId", "users"),
+                ("Query", "posts", "allPosts"),
+                ("Mutation", "createUser", "newUser"),
+            ]
+            .into_iter(),
+        )
+        .transform(config())
+        .to_result();
+
+        let taken =
+            ValidationError::new("Field 'users' already exists on type 'Query'.".to_string());
+        let missing = ValidationError::new("Field 'posts' not found on type 'Query'.".to_string());
+        let no_type =
+            ValidationError::new("Type 'Mutation' not found in configuration.".to_string());
+        let expected = Err(taken.combine(missing).combine(no_type));
+        assert_eq!(actual, expected);
+    }
+}
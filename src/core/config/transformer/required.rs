@@ -12,6 +12,7 @@ impl Transform for Required {
 
     fn transform(&self, config: Self::Value) -> tailcall_valid::Valid<Self::Value, Self::Error> {
         transform::default()
+            .pipe(super::RelayPagination)
             .pipe(super::Subgraph)
             .pipe(super::NestedUnions)
             .pipe(super::UnionInputType)
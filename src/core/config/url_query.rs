@@ -15,4 +15,26 @@ pub struct URLQuery {
     #[serde(default, skip_serializing_if = "is_default")]
     /// Determines whether to ignore query parameters with empty values.
     pub skip_empty: Option<bool>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// How the items of a list value are sent, overriding `arrayFormat` of
+    /// `@http`.
+    pub encoding: Option<QueryEncoding>,
+}
+
+/// How the items of a list query parameter are sent, matching the `style` and
+/// `explode` options of OpenAPI parameters.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QueryEncoding {
+    /// Repeats the key for each item, `key=1&key=2` (`form`, exploded).
+    #[default]
+    RepeatedKey,
+    /// Joins the items with commas, `key=1,2` (`form`, not exploded).
+    CommaSeparated,
+    /// Joins the items with spaces, `key=1%202` (`spaceDelimited`).
+    SpaceDelimited,
+    /// Joins the items with pipes, `key=1|2` (`pipeDelimited`).
+    PipeDelimited,
 }
@@ -246,7 +246,7 @@ fn print_enum_value(value: &async_graphql::parser::types::EnumValueDefinition) -
     let variant_def = if directives_str.is_empty() {
         format!("  {}", value.value)
     } else {
-        format!("  {} {}", value.value, directives_str)
+        format!("  {} {}", value.value, directives_str.trim_end())
     };
 
     if let Some(desc) = &value.description {
@@ -268,13 +268,15 @@ fn print_field(field: &async_graphql::parser::types::FieldDefinition) -> String
             .iter()
             .map(|arg| {
                 let nullable = if arg.node.ty.node.nullable { "" } else { "!" };
-                format!(
-                    "{}: {}{}{}",
+                let arg = format!(
+                    "{}: {}{}{} {}",
                     arg.node.name,
                     arg.node.ty.node.base,
                     nullable,
-                    print_default_value(arg.node.default_value.as_ref())
-                )
+                    print_default_value(arg.node.default_value.as_ref()),
+                    print_pos_directives(&arg.node.directives)
+                );
+                arg.trim_end().to_string()
             })
             .collect::<Vec<String>>()
             .join(", ");
@@ -302,14 +304,14 @@ fn print_default_value(value: Option<&Positioned<ConstValue>>) -> String {
 fn print_input_value(field: &async_graphql::parser::types::InputValueDefinition) -> String {
     let directives_str = print_pos_directives(&field.directives);
     let doc = get_formatted_docs(field.description.as_ref().map(|d| d.node.clone()), 2);
-    format!(
-        "{}  {}: {}{}{}",
-        doc,
+    let node = format!(
+        "  {}: {}{} {}",
         field.name.node,
         field.ty.node,
-        directives_str,
-        print_default_value(field.default_value.as_ref())
-    )
+        print_default_value(field.default_value.as_ref()),
+        directives_str
+    );
+    doc + node.trim_end()
 }
 
 pub fn print_directive<'a, T>(directive: &'a T) -> String
@@ -337,7 +339,13 @@ fn print_directive_type_def(directive: &DirectiveDefinition) -> String {
         .iter()
         .map(|arg| {
             let doc = get_formatted_docs(arg.node.description.as_ref().map(|d| d.node.clone()), 2);
-            format!("{}  {}: {}", doc, arg.node.name.node, arg.node.ty.node)
+            format!(
+                "{}  {}: {}{}",
+                doc,
+                arg.node.name.node,
+                arg.node.ty.node,
+                print_default_value(arg.node.default_value.as_ref())
+            )
         })
         .collect::<Vec<String>>()
         .join("\n");
@@ -2,6 +2,7 @@ use std::fmt::{Debug, Display};
 
 use colored::Colorize;
 use derive_setters::Setters;
+use serde::Serialize;
 use tailcall_valid::ValidationError;
 
 use crate::core::error::Error as CoreError;
@@ -9,17 +10,23 @@ use crate::core::error::Error as CoreError;
 /// The moral equivalent of a serde_json::Value but for errors.
 /// It's a data structure like Value that can hold any error in an untyped
 /// manner.
-#[derive(Debug, thiserror::Error, Setters, PartialEq, Clone)]
+#[derive(Debug, thiserror::Error, Setters, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Errata {
+    #[serde(skip)]
     is_root: bool,
     #[setters(skip)]
+    #[serde(skip)]
     color: bool,
     message: String,
     #[setters(strip_option)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     trace: Vec<String>,
 
     #[setters(skip)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     caused_by: Vec<Errata>,
 }
 
@@ -0,0 +1,618 @@
+//! Generates the configuration from an OpenAPI 3 spec, with a field calling
+//! each operation, or from the definitions of a JSON Schema. The object
+//! schemas become types and the enums of strings become enums, while the
+//! `oneOf` and `anyOf` schemas, which GraphQL has no equivalent for, become
+//! `JSON`.
+
+use std::collections::BTreeSet;
+
+use anyhow::{anyhow, bail, Result};
+use convert_case::{Case, Casing};
+use serde_json::Value;
+
+use crate::core::config::{
+    self, Arg, Config, Enum, Field, Http, KeyValue, Resolver, URLQuery, Variant,
+};
+use crate::core::http::Method;
+use crate::core::Type;
+
+/// The methods of the operations of a path, in the order they're generated.
+const METHODS: [(&str, Method); 5] = [
+    ("get", Method::GET),
+    ("post", Method::POST),
+    ("put", Method::PUT),
+    ("patch", Method::PATCH),
+    ("delete", Method::DELETE),
+];
+
+/// Generates a field for each operation of the spec, the `GET` ones on the
+/// query and the others on `Mutation`, which call the first server of the
+/// spec.
+pub fn from_openapi(spec: &Value, query: &str) -> Result<Config> {
+    let version = spec.get("openapi").and_then(Value::as_str);
+    if !version.is_some_and(|version| version.starts_with('3')) {
+        bail!("Only the OpenAPI 3 specs can be converted");
+    }
+    let base_url = base_url(spec)?;
+
+    let mut context = Context::new(spec);
+    let paths = spec.get("paths").and_then(Value::as_object);
+    for (path, item) in paths.into_iter().flatten() {
+        let parameters = item.get("parameters").and_then(Value::as_array);
+        for (key, method) in METHODS {
+            let Some(operation) = item.get(key) else {
+                continue;
+            };
+            let root = if method == Method::GET {
+                query
+            } else {
+                "Mutation"
+            };
+            let (name, field) = context.operation(
+                &base_url,
+                path,
+                method,
+                operation,
+                parameters.into_iter().flatten(),
+            )?;
+            context
+                .config
+                .types
+                .entry(root.to_string())
+                .or_default()
+                .fields
+                .insert(name, field);
+        }
+    }
+
+    let mut config = context.config;
+    if config.types.contains_key(query) {
+        config.schema.query = Some(query.to_string());
+    }
+    if config.types.contains_key("Mutation") {
+        config.schema.mutation = Some("Mutation".to_string());
+    }
+    Ok(config)
+}
+
+/// Generates a type for each definition of the schema, in `$defs` or
+/// `definitions`, and for the schema itself when it has a `title`.
+pub fn from_json_schema(schema: &Value) -> Result<Config> {
+    let mut context = Context::new(schema);
+    let definitions = ["$defs", "definitions"]
+        .into_iter()
+        .filter_map(|key| schema.get(key).and_then(Value::as_object))
+        .flatten();
+    for (name, definition) in definitions {
+        context.type_of(definition, &type_name(name), Position::Output)?;
+    }
+    if let Some(title) = schema.get("title").and_then(Value::as_str) {
+        context.type_of(schema, &type_name(title), Position::Output)?;
+    }
+    Ok(context.config)
+}
+
+/// The URL of the first server, with its variables set to their defaults.
+fn base_url(spec: &Value) -> Result<String> {
+    let server = spec
+        .get("servers")
+        .and_then(|servers| servers.get(0))
+        .ok_or_else(|| anyhow!("The spec has no servers to send the requests to"))?;
+    let mut url = server
+        .get("url")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let variables = server.get("variables").and_then(Value::as_object);
+    for (name, variable) in variables.into_iter().flatten() {
+        let default = variable.get("default").and_then(Value::as_str);
+        url = url.replace(&format!("{{{name}}}"), default.unwrap_or_default());
+    }
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        bail!("The server `{url}` isn't an absolute URL");
+    }
+    Ok(url.trim_end_matches('/').to_string())
+}
+
+/// Where the type of a schema is used. The input types are named after the
+/// output ones with an `Input` suffix, since GraphQL doesn't share them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Output,
+    Input,
+}
+
+struct Context<'a> {
+    /// The document the `$ref`s point into.
+    document: &'a Value,
+
+    /// Final configuration that's being built up.
+    config: Config,
+}
+
+impl<'a> Context<'a> {
+    fn new(document: &'a Value) -> Self {
+        Self { document, config: Config::default() }
+    }
+
+    /// The name and the field of an operation, with an argument for each of
+    /// its path, query and header parameters and an `input` one for its JSON
+    /// body.
+    fn operation(
+        &mut self,
+        base_url: &str,
+        path: &str,
+        method: Method,
+        operation: &'a Value,
+        path_parameters: impl Iterator<Item = &'a Value>,
+    ) -> Result<(String, Field)> {
+        let name = match operation.get("operationId").and_then(Value::as_str) {
+            Some(id) => field_name(id),
+            None => field_name(&format!("{method:?} {path}").to_lowercase()),
+        };
+        let mut field = Field { doc: description(operation), ..Default::default() };
+        let mut http = Http { method, ..Default::default() };
+        let mut url = format!("{base_url}{path}");
+
+        let parameters = operation.get("parameters").and_then(Value::as_array);
+        for parameter in path_parameters.chain(parameters.into_iter().flatten()) {
+            let parameter = self.dereference(parameter)?;
+            let key = parameter
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("A parameter of `{path}` has no name"))?;
+            let arg_name = field_name(key);
+            let value = format!("{{{{.args.{arg_name}}}}}");
+            match parameter.get("in").and_then(Value::as_str) {
+                Some("path") => url = url.replace(&format!("{{{key}}}"), &value),
+                Some("query") => {
+                    http.query
+                        .push(URLQuery { key: key.to_string(), value, ..Default::default() })
+                }
+                Some("header") => http.headers.push(KeyValue { key: key.to_string(), value }),
+                _ => continue,
+            }
+
+            let schema = parameter.get("schema").unwrap_or(&Value::Null);
+            let inline_name = type_name(&format!("{name} {key}"));
+            let mut type_of = self.type_of(schema, &inline_name, Position::Input)?;
+            if parameter.get("required").and_then(Value::as_bool) == Some(true) {
+                type_of = type_of.into_required();
+            }
+            field.args.insert(
+                arg_name,
+                Arg { type_of, doc: description(parameter), ..Default::default() },
+            );
+        }
+
+        if let Some(body) = operation.get("requestBody") {
+            let body = self.dereference(body)?;
+            if let Some(schema) = json_schema(body) {
+                let mut type_of = self.type_of(schema, &type_name(&name), Position::Input)?;
+                if body.get("required").and_then(Value::as_bool) == Some(true) {
+                    type_of = type_of.into_required();
+                }
+                field.args.insert(
+                    "input".to_string(),
+                    Arg { type_of, doc: description(body), ..Default::default() },
+                );
+                http.body = Some(Value::String("{{.args.input}}".to_string()));
+            }
+        }
+
+        field.type_of = match self.response(operation)? {
+            Some(schema) => self.type_of(schema, &type_name(&name), Position::Output)?,
+            None => Type::from("Empty".to_string()),
+        };
+        http.url = url;
+        field.resolvers = Resolver::Http(http).into();
+        Ok((name, field))
+    }
+
+    /// The JSON schema of the first successful response, or of the default
+    /// one.
+    fn response(&self, operation: &'a Value) -> Result<Option<&'a Value>> {
+        let Some(responses) = operation.get("responses").and_then(Value::as_object) else {
+            return Ok(None);
+        };
+        let mut statuses = responses
+            .keys()
+            .filter(|status| status.starts_with('2'))
+            .collect::<Vec<_>>();
+        statuses.sort();
+        let response = statuses
+            .first()
+            .map(|status| &responses[status.as_str()])
+            .or_else(|| responses.get("default"));
+        match response {
+            Some(response) => Ok(json_schema(self.dereference(response)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The value a `$ref` points to, eg. `#/components/schemas/User`, or the
+    /// value itself when it isn't a reference.
+    fn dereference(&self, value: &'a Value) -> Result<&'a Value> {
+        match value.get("$ref").and_then(Value::as_str) {
+            Some(reference) => self.dereference(self.resolve(reference)?.1),
+            None => Ok(value),
+        }
+    }
+
+    /// The name of the type of the schema a `$ref` points to and the schema.
+    fn resolve(&self, reference: &str) -> Result<(String, &'a Value)> {
+        let pointer = reference
+            .strip_prefix('#')
+            .ok_or_else(|| anyhow!("The reference `{reference}` isn't in the document"))?;
+        let schema = self
+            .document
+            .pointer(pointer)
+            .ok_or_else(|| anyhow!("The reference `{reference}` doesn't point to a schema"))?;
+        let name = pointer.rsplit('/').next().unwrap_or_default();
+        Ok((type_name(name), schema))
+    }
+
+    /// The GraphQL type of a schema, generating the types and enums it needs.
+    /// The inline objects and enums are named `name`, the referenced ones after
+    /// their definition.
+    fn type_of(&mut self, schema: &'a Value, name: &str, position: Position) -> Result<Type> {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let (name, schema) = self.resolve(reference)?;
+            return self.type_of(schema, &name, position);
+        }
+
+        if let Some(variants) = enum_variants(schema) {
+            self.config
+                .enums
+                .entry(name.to_string())
+                .or_insert_with(|| Enum { variants, doc: description(schema) });
+            return Ok(Type::from(name.to_string()));
+        }
+
+        let type_of = match schema_type(schema) {
+            Some("array") => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                let item_name = format!("{name}Item");
+                return Ok(self.type_of(items, &item_name, position)?.into_list());
+            }
+            Some("object") | None if is_object(schema) => {
+                return self.object(schema, name, position)
+            }
+            Some("string") => match schema.get("format").and_then(Value::as_str) {
+                Some("date-time") => "DateTime",
+                Some("date") => "Date",
+                Some("email") => "Email",
+                Some("uri" | "url") => "Url",
+                _ => "String",
+            },
+            Some("integer") => match schema.get("format").and_then(Value::as_str) {
+                Some("int64") => "Int64",
+                _ => "Int",
+            },
+            Some("number") => "Float",
+            Some("boolean") => "Boolean",
+            _ => "JSON",
+        };
+        Ok(Type::from(type_of.to_string()))
+    }
+
+    /// The type of an object schema, with a field for each of its properties
+    /// and the ones of its `allOf` schemas.
+    fn object(&mut self, schema: &'a Value, name: &str, position: Position) -> Result<Type> {
+        let type_name_of = match position {
+            Position::Output => name.to_string(),
+            Position::Input => format!("{name}Input"),
+        };
+        // the type is added before its fields, so that the recursive ones stop
+        if self.config.types.contains_key(&type_name_of) {
+            return Ok(Type::from(type_name_of));
+        }
+        self.config
+            .types
+            .insert(type_name_of.clone(), config::Type::default());
+
+        let mut properties = vec![];
+        let mut required = vec![];
+        self.properties(schema, &mut properties, &mut required)?;
+
+        let mut type_ = config::Type { doc: description(schema), ..Default::default() };
+        for (property, schema) in properties {
+            let field_name = field_name(&property);
+            let inline_name = type_name(&format!("{name} {property}"));
+            let mut type_of = self.type_of(schema, &inline_name, position)?;
+            if required.contains(&property) {
+                type_of = type_of.into_required();
+            }
+            let field = Field { type_of, doc: description(schema), ..Default::default() };
+            type_.fields.insert(field_name, field);
+        }
+        self.config.types.insert(type_name_of.clone(), type_);
+        Ok(Type::from(type_name_of))
+    }
+
+    /// Collects the properties and the required ones of an object schema,
+    /// following its `allOf` schemas.
+    fn properties(
+        &self,
+        schema: &'a Value,
+        properties: &mut Vec<(String, &'a Value)>,
+        required: &mut Vec<String>,
+    ) -> Result<()> {
+        let schema = self.dereference(schema)?;
+        let all_of = schema.get("allOf").and_then(Value::as_array);
+        for schema in all_of.into_iter().flatten() {
+            self.properties(schema, properties, required)?;
+        }
+        let own = schema.get("properties").and_then(Value::as_object);
+        properties.extend(
+            own.into_iter()
+                .flatten()
+                .map(|(name, schema)| (name.clone(), schema)),
+        );
+        let own_required = schema.get("required").and_then(Value::as_array);
+        required.extend(
+            own_required
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string),
+        );
+        Ok(())
+    }
+}
+
+/// The schema of the JSON content of a request body or a response.
+fn json_schema(body: &Value) -> Option<&Value> {
+    let content = body.get("content")?.as_object()?;
+    content
+        .iter()
+        .find(|(media_type, _)| media_type.starts_with("application/json"))
+        .and_then(|(_, media_type)| media_type.get("schema"))
+}
+
+/// The `type` of a schema, the one other than `null` when it's a list.
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(type_of) => Some(type_of),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|type_of| *type_of != "null"),
+        _ => None,
+    }
+}
+
+/// Whether the schema is an object with properties. The ones without, eg.
+/// maps with `additionalProperties`, and the `oneOf` and `anyOf` ones are
+/// left as `JSON`.
+fn is_object(schema: &Value) -> bool {
+    schema.get("properties").is_some() || schema.get("allOf").is_some()
+}
+
+/// The variants of an enum of strings, when they're all valid GraphQL names.
+fn enum_variants(schema: &Value) -> Option<BTreeSet<Variant>> {
+    schema
+        .get("enum")?
+        .as_array()?
+        .iter()
+        .map(|value| {
+            let name = value.as_str().filter(|name| is_name(name))?;
+            Some(Variant { name: name.to_string(), alias: None, deprecated: None })
+        })
+        .collect()
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn description(value: &Value) -> Option<String> {
+    value
+        .get("description")
+        .or_else(|| value.get("summary"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// The words of a name, with the characters GraphQL doesn't allow dropped.
+fn words(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect()
+}
+
+/// A type name, eg. `UserProfile` for `user_profile`.
+fn type_name(name: &str) -> String {
+    let name = words(name).to_case(Case::Pascal);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+/// A field or an argument name, eg. `getUser` for `get-user`.
+fn field_name(name: &str) -> String {
+    let name = words(name).to_case(Case::Camel);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn spec() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "servers": [{"url": "https://{region}.example.com/v1/", "variables": {"region": {"default": "eu"}}}],
+            "paths": {
+                "/users/{user-id}": {
+                    "parameters": [{"name": "user-id", "in": "path", "required": true, "schema": {"type": "integer"}}],
+                    "get": {
+                        "operationId": "get_user",
+                        "parameters": [{"$ref": "#/components/parameters/Fields"}],
+                        "responses": {
+                            "200": {"content": {"application/json": {"schema": {"$ref": "#/components/schemas/User"}}}}
+                        }
+                    },
+                    "put": {
+                        "requestBody": {
+                            "required": true,
+                            "content": {"application/json": {"schema": {"$ref": "#/components/schemas/User"}}}
+                        },
+                        "responses": {"204": {"description": "Updated"}}
+                    }
+                }
+            },
+            "components": {
+                "parameters": {
+                    "Fields": {"name": "fields", "in": "query", "schema": {"type": "array", "items": {"type": "string"}}}
+                },
+                "schemas": {
+                    "User": {
+                        "description": "A user",
+                        "required": ["id"],
+                        "properties": {
+                            "id": {"type": "integer", "format": "int64"},
+                            "status": {"type": "string", "enum": ["ACTIVE", "BANNED"]},
+                            "address": {"type": "object", "properties": {"city": {"type": "string"}}},
+                            "friends": {"type": "array", "items": {"$ref": "#/components/schemas/User"}},
+                            "meta": {"type": "object", "additionalProperties": true}
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_from_openapi() {
+        let config = from_openapi(&spec(), "Query").unwrap();
+
+        assert_eq!(config.schema.query.as_deref(), Some("Query"));
+        assert_eq!(config.schema.mutation.as_deref(), Some("Mutation"));
+
+        let field = &config.types["Query"].fields["getUser"];
+        assert_eq!(field.type_of, Type::from("User".to_string()));
+        assert_eq!(
+            field.args["userId"].type_of,
+            Type::from("Int".to_string()).into_required()
+        );
+        assert_eq!(
+            field.args["fields"].type_of,
+            Type::from("String".to_string()).into_list()
+        );
+        let Some(Resolver::Http(http)) = field.resolvers.first() else {
+            panic!("the field has no @http");
+        };
+        assert_eq!(http.url, "https://eu.example.com/v1/users/{{.args.userId}}");
+        assert_eq!(http.query[0].key, "fields");
+        assert_eq!(http.query[0].value, "{{.args.fields}}");
+
+        let field = &config.types["Mutation"].fields["putUsersUserId"];
+        assert_eq!(field.type_of, Type::from("Empty".to_string()));
+        assert_eq!(
+            field.args["input"].type_of,
+            Type::from("UserInput".to_string()).into_required()
+        );
+        let Some(Resolver::Http(http)) = field.resolvers.first() else {
+            panic!("the field has no @http");
+        };
+        assert_eq!(http.method, Method::PUT);
+        assert_eq!(http.body, Some(json!("{{.args.input}}")));
+
+        let user = &config.types["User"];
+        assert_eq!(user.doc.as_deref(), Some("A user"));
+        assert_eq!(
+            user.fields["id"].type_of,
+            Type::from("Int64".to_string()).into_required()
+        );
+        assert_eq!(
+            user.fields["status"].type_of,
+            Type::from("UserStatus".to_string())
+        );
+        assert_eq!(
+            user.fields["address"].type_of,
+            Type::from("UserAddress".to_string())
+        );
+        assert_eq!(
+            user.fields["friends"].type_of,
+            Type::from("User".to_string()).into_list()
+        );
+        assert_eq!(user.fields["meta"].type_of, Type::from("JSON".to_string()));
+        assert!(config.enums.contains_key("UserStatus"));
+
+        let user_input = &config.types["UserInput"];
+        assert_eq!(
+            user_input.fields["address"].type_of,
+            Type::from("UserAddressInput".to_string())
+        );
+        assert_eq!(
+            user_input.fields["friends"].type_of,
+            Type::from("UserInput".to_string()).into_list()
+        );
+    }
+
+    #[test]
+    fn test_from_openapi_requires_servers() {
+        let mut spec = spec();
+        spec["servers"] = json!([]);
+        let error = from_openapi(&spec, "Query").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The spec has no servers to send the requests to"
+        );
+
+        spec["openapi"] = json!("2.0");
+        let error = from_openapi(&spec, "Query").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Only the OpenAPI 3 specs can be converted"
+        );
+    }
+
+    #[test]
+    fn test_from_json_schema() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "order",
+            "properties": {
+                "id": {"type": "string"},
+                "lines": {"type": "array", "items": {"$ref": "#/$defs/line"}}
+            },
+            "$defs": {
+                "line": {
+                    "required": ["sku"],
+                    "properties": {"sku": {"type": "string"}, "quantity": {"type": ["integer", "null"]}}
+                }
+            }
+        });
+
+        let config = from_json_schema(&schema).unwrap();
+
+        let order = &config.types["Order"];
+        assert_eq!(
+            order.fields["lines"].type_of,
+            Type::from("Line".to_string()).into_list()
+        );
+        let line = &config.types["Line"];
+        assert_eq!(
+            line.fields["sku"].type_of,
+            Type::from("String".to_string()).into_required()
+        );
+        assert_eq!(
+            line.fields["quantity"].type_of,
+            Type::from("Int".to_string())
+        );
+    }
+}
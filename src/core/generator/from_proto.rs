@@ -1,23 +1,52 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use anyhow::{bail, Result};
 use derive_setters::Setters;
 use prost_reflect::prost_types::field_descriptor_proto::Label;
 use prost_reflect::prost_types::{
-    DescriptorProto, EnumDescriptorProto, FileDescriptorSet, ServiceDescriptorProto, SourceCodeInfo,
+    DescriptorProto, EnumDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    ServiceDescriptorProto, SourceCodeInfo,
 };
 use serde_json::Value;
 use tailcall_valid::Validator;
 
-use super::graphql_type::{GraphQLType, Unparsed};
+use super::graphql_type::{GraphQLType, Parsed, Unparsed};
 use super::proto::comments_builder::CommentsBuilder;
 use super::proto::path_builder::PathBuilder;
 use super::proto::path_field::PathField;
 use crate::core::config::transformer::{AmbiguousType, TreeShake};
-use crate::core::config::{self, Arg, Config, Enum, Field, Grpc, Resolver, Union, Variant};
+use crate::core::config::{
+    self, Arg, Config, Deprecated, Enum, Field, Grpc, Resolver, Union, Variant,
+};
 use crate::core::transform::{Transform, TransformerOps};
 use crate::core::Type;
 
+/// How the names of the GraphQL types are made from the names of the proto
+/// messages and enums.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TypeNaming {
+    /// The package is kept, eg. `GEN__shop__v1__Order`.
+    #[default]
+    Qualified,
+    /// The package is dropped, eg. `Order`.
+    StripPackage,
+    /// The package is replaced by the prefix, eg. `ShopOrder`.
+    Prefix(String),
+}
+
+/// Options of the generation from protobuf.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Setters)]
+pub struct ProtoOptions {
+    /// Patterns matched against the fully qualified names of the services,
+    /// eg. `shop.v1.UserService` or `shop.v1.*`. Only the matching services
+    /// and the types they use are generated. Every service is generated when
+    /// empty.
+    pub include_services: Vec<String>,
+
+    /// How the names of the types are made.
+    pub type_naming: TypeNaming,
+}
+
 /// Assists in the mapping and retrieval of proto type names to custom formatted
 /// strings based on the descriptor type.
 #[derive(Setters)]
@@ -37,25 +66,92 @@ struct Context {
     /// Optional field to store source code information, including comments, for
     /// each entity.
     comments_builder: CommentsBuilder,
+
+    options: ProtoOptions,
+
+    /// Packages of all the files, stripped from the type names.
+    packages: Vec<String>,
+
+    /// Fully qualified names of the proto types defining each GraphQL type.
+    definitions: BTreeMap<String, BTreeSet<String>>,
+
+    /// Number of services matching the included ones.
+    included_services: usize,
 }
 
 impl Context {
-    fn new(query: &str) -> Self {
+    fn new(query: &str, options: ProtoOptions, packages: Vec<String>) -> Self {
         Self {
             query: query.to_string(),
             namespace: Default::default(),
             config: Default::default(),
             map_types: Default::default(),
             comments_builder: CommentsBuilder::new(None),
+            options,
+            packages,
+            definitions: Default::default(),
+            included_services: 0,
         }
     }
 
+    /// The name of the GraphQL type of a message or an enum.
+    fn type_name(&self, graphql_type: GraphQLType<Parsed>) -> String {
+        match &self.options.type_naming {
+            TypeNaming::Qualified => graphql_type.to_string(),
+            TypeNaming::StripPackage => graphql_type
+                .strip_package(&self.packages)
+                .to_string_with_prefix(""),
+            TypeNaming::Prefix(prefix) => graphql_type
+                .strip_package(&self.packages)
+                .to_string_with_prefix(prefix),
+        }
+    }
+
+    /// Records the proto type the GraphQL type is generated for, to find the
+    /// names given to more than one type.
+    fn define(&mut self, graphql_type: &GraphQLType<Parsed>) -> String {
+        let name = self.type_name(graphql_type.clone());
+        self.definitions
+            .entry(name.clone())
+            .or_default()
+            .insert(graphql_type.id());
+        name
+    }
+
+    fn is_included(&self, service_id: &str) -> bool {
+        let patterns = &self.options.include_services;
+        patterns.is_empty()
+            || patterns
+                .iter()
+                .any(|pattern| matches_glob(pattern, service_id))
+    }
+
     /// Sets source code information for preservation of comments.
     fn with_source_code_info(mut self, source_code_info: SourceCodeInfo) -> Self {
         self.comments_builder = CommentsBuilder::new(Some(source_code_info));
         self
     }
 
+    /// Processes the enums and the messages of a file, and its services when
+    /// they're called at a url.
+    fn append_file(mut self, file: &FileDescriptorProto, url: Option<&str>) -> Result<Self> {
+        self.namespace = vec![file.package().to_string()];
+
+        if let Some(source_code_info) = &file.source_code_info {
+            self = self.with_source_code_info(source_code_info.clone());
+        }
+
+        let root_path = PathBuilder::new(&[]);
+
+        self = self
+            .append_enums(&file.enum_type, &root_path, false)
+            .append_msg_type(&file.message_type, &root_path, false)?;
+        match url {
+            Some(url) => self.append_query_service(&file.service, &root_path, url),
+            None => Ok(self),
+        }
+    }
+
     /// Resolves the actual name and inserts the type.
     fn insert_type(mut self, name: String, ty: config::Type) -> Self {
         self.config.types.insert(name.to_string(), ty);
@@ -185,31 +281,27 @@ impl Context {
 
                 // Get comments for the enum value
                 let comment = self.comments_builder.get_comments(&value_path);
+                let deprecated =
+                    to_deprecated(v.options.as_ref().and_then(|options| options.deprecated));
 
                 // Format the variant with its comment as description
-                if let Some(comment) = comment {
+                let name = if let Some(comment) = comment {
                     // TODO: better support for enum variant descriptions [There is no way to define
                     // description for enum variant in current config structure]
-                    let variant_with_comment =
-                        format!("\"\"\n  {}\n  \"\"\n  {}", comment, variant_name);
-                    variants_with_comments.insert(variant_with_comment);
+                    format!("\"\"\n  {}\n  \"\"\n  {}", comment, variant_name)
                 } else {
-                    variants_with_comments.insert(variant_name);
-                }
+                    variant_name
+                };
+                variants_with_comments.insert(Variant { name, alias: None, deprecated });
             }
 
-            let type_name = GraphQLType::new(enum_name)
+            let enum_type = GraphQLType::new(enum_name)
                 .extend(self.namespace.as_slice())
-                .into_enum()
-                .to_string();
+                .into_enum();
+            let type_name = self.define(&enum_type);
 
             let doc = self.comments_builder.get_comments(&enum_type_path);
 
-            let variants_with_comments = variants_with_comments
-                .into_iter()
-                .map(|v| Variant { name: v, alias: None })
-                .collect();
-
             self.config
                 .enums
                 .insert(type_name, Enum { variants: variants_with_comments, doc });
@@ -290,9 +382,8 @@ impl Context {
                         cfg_field.type_of = "JSON".to_string().into();
                     } else {
                         // for non-primitive types
-                        let type_of = graphql_type_from_ref(type_name)?
-                            .into_object_type()
-                            .to_string();
+                        let type_of =
+                            self.type_name(graphql_type_from_ref(type_name)?.into_object_type());
 
                         cfg_field.type_of = cfg_field.type_of.with_name(type_of);
                     }
@@ -305,6 +396,12 @@ impl Context {
                 let field_path =
                     PathBuilder::new(&msg_path).extend(PathField::Field, field_index as i32);
                 cfg_field.doc = self.comments_builder.get_comments(&field_path);
+                cfg_field.deprecated = to_deprecated(
+                    field
+                        .options
+                        .as_ref()
+                        .and_then(|options| options.deprecated),
+                );
 
                 if let Some(oneof_index) = field.oneof_index {
                     oneof_fields[oneof_index as usize].push((field_name.to_string(), cfg_field));
@@ -313,10 +410,11 @@ impl Context {
                 }
             }
 
+            let type_name = self.define(&msg_type);
             if message.oneof_decl.is_empty() {
-                self = self.insert_type(msg_type.to_string(), ty);
+                self = self.insert_type(type_name, ty);
             } else {
-                self = self.insert_oneofs(msg_type.to_string(), ty, oneof_fields);
+                self = self.insert_oneofs(type_name, ty, oneof_fields);
             }
         }
         Ok(self)
@@ -335,6 +433,14 @@ impl Context {
 
         for (index, service) in services.iter().enumerate() {
             let service_name = service.name();
+            let service_id = GraphQLType::new(service_name)
+                .extend(self.namespace.as_slice())
+                .into_object_type()
+                .id();
+            if !self.is_included(&service_id) {
+                continue;
+            }
+            self.included_services += 1;
             let path = parent_path.extend(PathField::Service, index as i32);
 
             for (method_index, method) in service.method.iter().enumerate() {
@@ -348,7 +454,7 @@ impl Context {
 
                 if let Some(graphql_type) = get_input_type(method.input_type())? {
                     let key = graphql_type.clone().into_field().to_string();
-                    let type_of = graphql_type.into_object_type().to_string();
+                    let type_of = self.type_name(graphql_type.into_object_type());
                     let val = Arg {
                         type_of: Type::from(type_of).into_required(),
                         /* Setting it not null by default. There's no way to infer this
@@ -356,15 +462,15 @@ impl Context {
                         doc: None,
                         modify: None,
                         default_value: None,
+                        deprecated: None,
                     };
 
                     body = Some(Value::String(format!("{{{{.args.{key}}}}}")));
                     cfg_field.args.insert(key, val);
                 }
 
-                let output_ty = get_output_type(method.output_type())?
-                    .into_object_type()
-                    .to_string();
+                let output_ty =
+                    self.type_name(get_output_type(method.output_type())?.into_object_type());
                 cfg_field.type_of = cfg_field.type_of.with_name(output_ty);
 
                 cfg_field.resolvers = Resolver::Grpc(Grpc {
@@ -382,6 +488,12 @@ impl Context {
                 let method_path =
                     PathBuilder::new(&path).extend(PathField::Method, method_index as i32);
                 cfg_field.doc = self.comments_builder.get_comments(&method_path);
+                cfg_field.deprecated = to_deprecated(
+                    method
+                        .options
+                        .as_ref()
+                        .and_then(|options| options.deprecated),
+                );
 
                 let ty = self
                     .config
@@ -413,6 +525,14 @@ fn graphql_type_from_ref(name: &str) -> Result<GraphQLType<Unparsed>> {
     }
 }
 
+/// Maps the `deprecated` option of a proto field, enum value or method to
+/// `@deprecated`. Proto has no deprecation reason.
+fn to_deprecated(deprecated: Option<bool>) -> Option<Deprecated> {
+    deprecated
+        .filter(|deprecated| *deprecated)
+        .map(|_| Deprecated::default())
+}
+
 /// Converts proto field types to a custom format.
 fn convert_primitive_type(proto_ty: &str) -> String {
     let binding = proto_ty.to_lowercase();
@@ -456,31 +576,120 @@ fn get_input_type(input_ty: &str) -> Result<Option<GraphQLType<Unparsed>>> {
     }
 }
 
+/// Matches a name against a pattern where `*` stands for any run of characters.
+fn matches_glob(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => name.strip_prefix(prefix).is_some_and(|name| {
+            name.char_indices()
+                .map(|(index, _)| index)
+                .chain(std::iter::once(name.len()))
+                .any(|index| matches_glob(rest, &name[index..]))
+        }),
+    }
+}
+
 /// The main entry point that builds a Config object from proto descriptor sets.
-pub fn from_proto(descriptor_sets: &[FileDescriptorSet], query: &str, url: &str) -> Result<Config> {
-    let mut ctx = Context::new(query);
+pub fn from_proto(
+    descriptor_sets: &[FileDescriptorSet],
+    query: &str,
+    url: &str,
+    options: &ProtoOptions,
+) -> Result<Config> {
+    let packages = descriptor_sets
+        .iter()
+        .flat_map(|descriptor_set| descriptor_set.file.iter())
+        .map(|file_descriptor| file_descriptor.package().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let mut ctx = Context::new(query, options.clone(), packages);
     for descriptor_set in descriptor_sets.iter() {
         for file_descriptor in descriptor_set.file.iter() {
-            ctx.namespace = vec![file_descriptor.package().to_string()];
-
-            if let Some(source_code_info) = &file_descriptor.source_code_info {
-                ctx = ctx.with_source_code_info(source_code_info.clone());
-            }
-
-            let root_path = PathBuilder::new(&[]);
-
-            ctx = ctx
-                .append_enums(&file_descriptor.enum_type, &root_path, false)
-                .append_msg_type(&file_descriptor.message_type, &root_path, false)?
-                .append_query_service(&file_descriptor.service, &root_path, url)?;
+            ctx = ctx.append_file(file_descriptor, Some(url))?;
         }
     }
 
+    if !options.include_services.is_empty() && ctx.included_services == 0 {
+        bail!("No service matches {}", options.include_services.join(", "));
+    }
+
     let config = AmbiguousType::default()
         .pipe(TreeShake)
         .transform(ctx.config)
         .to_result()?;
 
+    check_collisions(&ctx.definitions, config)
+}
+
+/// Builds a Config from a single proto file, with all its messages and enums
+/// whether its methods use them or not. The messages of the files it imports,
+/// which have to be in `dependencies`, are generated when the file uses them.
+pub fn from_proto_file(
+    file: &FileDescriptorProto,
+    dependencies: &[FileDescriptorProto],
+    query: &str,
+    url: &str,
+) -> Result<Config> {
+    let packages = dependencies
+        .iter()
+        .chain(std::iter::once(file))
+        .map(|file_descriptor| file_descriptor.package().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let mut ctx = Context::new(query, ProtoOptions::default(), packages);
+    for dependency in dependencies {
+        ctx = ctx.append_file(dependency, None)?;
+    }
+    let imported = ctx.config.types.keys().cloned().collect::<HashSet<_>>();
+    ctx = ctx.append_file(file, Some(url))?;
+    let defined = ctx
+        .config
+        .types
+        .keys()
+        .filter(|name| !imported.contains(*name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut config = AmbiguousType::default().transform(ctx.config).to_result()?;
+    let used = config.reachable_types(defined.iter().map(String::as_str));
+    let unused = config
+        .unused_types()
+        .into_iter()
+        .filter(|name| !used.contains(name))
+        .collect();
+    config = config.remove_types(unused);
+
+    check_collisions(&ctx.definitions, config)
+}
+
+/// Fails when a name is given to more than one proto type. Only the types left
+/// in the config matter, the others aren't generated.
+fn check_collisions(
+    definitions: &BTreeMap<String, BTreeSet<String>>,
+    config: Config,
+) -> Result<Config> {
+    let collisions = definitions
+        .iter()
+        .filter(|(name, ids)| {
+            ids.len() > 1
+                && (config.types.contains_key(*name)
+                    || config.enums.contains_key(*name)
+                    || config.unions.contains_key(*name))
+        })
+        .map(|(name, ids)| {
+            let ids = ids.iter().cloned().collect::<Vec<_>>().join(", ");
+            format!("{name} for {ids}")
+        })
+        .collect::<Vec<_>>();
+    if !collisions.is_empty() {
+        bail!(
+            "The same type name is generated for different proto types: {}",
+            collisions.join("; ")
+        );
+    }
+
     Ok(config)
 }
 
@@ -490,19 +699,22 @@ mod test {
     use prost_reflect::prost_types::FileDescriptorSet;
     use tailcall_fixtures::protobuf;
 
-    use super::from_proto;
+    use super::{from_proto, matches_glob, ProtoOptions, TypeNaming};
     use crate::core::config::ConfigModule;
 
     fn compile_protobuf(files: &[&str]) -> Result<FileDescriptorSet> {
         Ok(protox::compile(files, [protobuf::SELF])?)
     }
 
+    fn generate(files: &[&str], options: &ProtoOptions) -> Result<String> {
+        let set = compile_protobuf(files)?;
+        let config = from_proto(&[set], "Query", "http://localhost:50051", options)?;
+        Ok(ConfigModule::from(config).to_sdl())
+    }
+
     macro_rules! assert_gen {
         ($( $set:expr ), +) => {
-            let set = compile_protobuf(&[$( $set ),+]).unwrap();
-            let config = from_proto(&[set], "Query", "http://localhost:50051").unwrap();
-            let config_module = ConfigModule::from(config);
-            let result = config_module.to_sdl();
+            let result = generate(&[$( $set ),+], &ProtoOptions::default()).unwrap();
             insta::assert_snapshot!(result);
         };
     }
@@ -546,8 +758,10 @@ mod test {
         let set3 = compile_protobuf(&[protobuf::GREETINGS_B])?;
         let url = "http://localhost:50051";
 
-        let actual = from_proto(&[set.clone()], "Query", url)?.to_sdl();
-        let expected = from_proto(&[set1, set2, set3], "Query", url)?.to_sdl();
+        let options = ProtoOptions::default();
+
+        let actual = from_proto(&[set.clone()], "Query", url, &options)?.to_sdl();
+        let expected = from_proto(&[set1, set2, set3], "Query", url, &options)?.to_sdl();
 
         pretty_assertions::assert_eq!(actual, expected);
         Ok(())
@@ -584,6 +798,11 @@ mod test {
         assert_gen!(protobuf::OPTIONAL);
     }
 
+    #[test]
+    fn test_deprecated() {
+        assert_gen!(protobuf::DEPRECATED);
+    }
+
     #[test]
     fn test_scalar_types() {
         assert_gen!(protobuf::SCALARS);
@@ -593,4 +812,62 @@ mod test {
     fn test_oneof_types() {
         assert_gen!(protobuf::ONEOF);
     }
+
+    #[test]
+    fn test_include_services() {
+        let options = ProtoOptions::default().include_services(vec![
+            "shop.v1.UserService".to_string(),
+            "*.OrderService".to_string(),
+        ]);
+        let result = generate(&[protobuf::MULTI_SERVICE], &options).unwrap();
+        insta::assert_snapshot!(result);
+    }
+
+    #[test]
+    fn test_strip_package_prefix() {
+        let options = ProtoOptions::default().type_naming(TypeNaming::StripPackage);
+        let result = generate(&[protobuf::MULTI_SERVICE], &options).unwrap();
+        insta::assert_snapshot!(result);
+    }
+
+    #[test]
+    fn test_type_prefix() {
+        let options = ProtoOptions::default().type_naming(TypeNaming::Prefix("Shop".to_string()));
+        let result = generate(&[protobuf::MULTI_SERVICE], &options).unwrap();
+        insta::assert_snapshot!(result);
+    }
+
+    #[test]
+    fn test_no_matching_service() {
+        let options =
+            ProtoOptions::default().include_services(vec!["shop.v1.Payment*".to_string()]);
+        let error = generate(&[protobuf::MULTI_SERVICE], &options).unwrap_err();
+        assert_eq!(error.to_string(), "No service matches shop.v1.Payment*");
+    }
+
+    #[test]
+    fn test_type_name_collision() {
+        let options = ProtoOptions::default().type_naming(TypeNaming::StripPackage);
+        let error = generate(&[protobuf::GREETINGS_A, protobuf::GREETINGS_B], &options)
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.starts_with("The same type name is generated for different proto types"));
+        assert!(
+            error.contains("HelloRequest for greetings.HelloRequest, greetings_a.b.HelloRequest")
+        );
+        assert!(error.contains("HelloReply for greetings.HelloReply, greetings_a.b.HelloReply"));
+    }
+
+    #[test]
+    fn test_matches_glob() {
+        assert!(matches_glob("shop.v1.UserService", "shop.v1.UserService"));
+        assert!(matches_glob("shop.v1.*", "shop.v1.UserService"));
+        assert!(matches_glob("*.UserService", "shop.v1.UserService"));
+        assert!(matches_glob("shop.*.User*", "shop.v1.UserService"));
+        assert!(matches_glob("*", "shop.v1.UserService"));
+        assert!(!matches_glob("shop.v1.User", "shop.v1.UserService"));
+        assert!(!matches_glob("shop.v2.*", "shop.v1.UserService"));
+        assert!(!matches_glob("*.OrderService", "shop.v1.UserService"));
+    }
 }
@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::fmt::{self, Display};
 
 use derive_setters::Setters;
 use prost_reflect::prost_types::FileDescriptorSet;
@@ -7,7 +8,7 @@ use serde_json::Value;
 use tailcall_valid::Validator;
 use url::Url;
 
-use super::from_proto::from_proto;
+use super::from_proto::{from_proto, ProtoOptions};
 use super::proto::connect_rpc::ConnectRPC;
 use super::{FromJsonGenerator, NameGenerator, RequestSample, PREFIX};
 use crate::core::config::{self, Config, ConfigModule, Link, LinkType};
@@ -25,6 +26,7 @@ pub struct Generator {
     mutation: Option<String>,
     inputs: Vec<Input>,
     type_name_prefix: String,
+    proto_options: ProtoOptions,
     transformers: Vec<Box<dyn Transform<Value = Config, Error = String>>>,
 }
 
@@ -51,6 +53,49 @@ pub enum Input {
     },
 }
 
+/// An input that couldn't be turned into config and was left out of the
+/// generated one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Skipped {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Summary of a generation, with the number of root fields generated and the
+/// inputs that were skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    pub generated: usize,
+    pub skipped: Vec<Skipped>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "generated {} fields", self.generated)?;
+        if !self.skipped.is_empty() {
+            let skipped = self
+                .skipped
+                .iter()
+                .map(|skipped| format!("{}: {}", skipped.path, skipped.reason))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, ", skipped {}: {}", self.skipped.len(), skipped)?;
+        }
+        Ok(())
+    }
+}
+
+impl Input {
+    /// Identifies the input in the generation report.
+    fn path(&self) -> String {
+        match self {
+            Input::Json { url, .. } => url.to_string(),
+            Input::Proto { metadata, .. } => metadata.path.clone(),
+            Input::Config { source, .. } => format!("{} config", source.ext()),
+        }
+    }
+}
+
 impl Default for Generator {
     fn default() -> Self {
         Self::new()
@@ -64,6 +109,7 @@ impl Generator {
             mutation: None,
             inputs: Vec::new(),
             type_name_prefix: PREFIX.into(),
+            proto_options: Default::default(),
             transformers: Default::default(),
         }
     }
@@ -92,7 +138,7 @@ impl Generator {
         url: &str,
     ) -> anyhow::Result<Config> {
         let descriptor_set = resolve_file_descriptor_set(metadata.descriptor_set.clone())?;
-        let mut config = from_proto(&[descriptor_set], operation_name, url)?;
+        let mut config = from_proto(&[descriptor_set], operation_name, url, &self.proto_options)?;
         config.links.push(Link {
             id: None,
             src: metadata.path.to_owned(),
@@ -100,61 +146,93 @@ impl Generator {
             headers: None,
             meta: None,
             proto_paths: None,
+            optional: false,
         });
         Ok(config)
     }
 
-    /// Generated the actual configuratio from provided samples.
+    /// Generated the actual configuratio from provided samples. Fails if any
+    /// of the inputs can't be generated.
     pub fn generate(&self, use_transformers: bool) -> anyhow::Result<ConfigModule> {
+        let (config, report) = self.generate_with_report(use_transformers)?;
+        if !report.skipped.is_empty() {
+            return Err(anyhow::anyhow!("{}", report));
+        }
+        Ok(config)
+    }
+
+    /// Generates the configuration from the inputs that can be generated,
+    /// skipping the rest and listing them in the returned [Report].
+    pub fn generate_with_report(
+        &self,
+        use_transformers: bool,
+    ) -> anyhow::Result<(ConfigModule, Report)> {
         let mut config: Config = Config::default();
+        let mut report = Report::default();
         let type_name_generator = NameGenerator::new(&self.type_name_prefix);
 
         for input in self.inputs.iter() {
-            match input {
-                Input::Config { source, schema } => {
-                    config = config.merge_right(Config::from_source(source.clone(), schema)?);
-                }
-                Input::Json {
-                    url,
-                    method,
-                    req_body,
-                    res_body,
-                    field_name,
-                    is_mutation,
-                    headers,
-                } => {
-                    let req_sample = RequestSample::new(
-                        url.to_owned(),
-                        res_body.to_owned(),
-                        field_name.to_owned(),
-                    )
-                    .with_method(method.to_owned())
-                    .with_headers(headers.to_owned())
-                    .with_is_mutation(is_mutation.to_owned())
-                    .with_req_body(req_body.to_owned());
-
-                    config = config
-                        .merge_right(self.generate_from_json(&type_name_generator, &[req_sample])?);
-                }
-                Input::Proto { metadata, url, connect_rpc } => {
-                    let proto_config = self.generate_from_proto(metadata, &self.query, url)?;
-                    let proto_config = if connect_rpc == &Some(true) {
-                        ConnectRPC.transform(proto_config).to_result()?
-                    } else {
-                        proto_config
-                    };
-                    config = config.merge_right(proto_config);
-                }
+            match self.generate_input(&type_name_generator, input) {
+                Ok(generated) => config = config.merge_right(generated),
+                Err(err) => report
+                    .skipped
+                    .push(Skipped { path: input.path(), reason: err.to_string() }),
             }
         }
 
+        report.generated = [
+            config.schema.query.as_ref(),
+            config.schema.mutation.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|name| config.find_type(name))
+        .map(|type_of| type_of.fields.len())
+        .sum();
+
         if use_transformers {
             for t in &self.transformers {
                 config = t.transform(config).to_result()?;
             }
         }
 
-        Ok(ConfigModule::from(config))
+        Ok((ConfigModule::from(config), report))
+    }
+
+    fn generate_input(
+        &self,
+        type_name_generator: &NameGenerator,
+        input: &Input,
+    ) -> anyhow::Result<Config> {
+        match input {
+            Input::Config { source, schema } => Ok(Config::from_source(source.clone(), schema)?),
+            Input::Json {
+                url,
+                method,
+                req_body,
+                res_body,
+                field_name,
+                is_mutation,
+                headers,
+            } => {
+                let req_sample =
+                    RequestSample::new(url.to_owned(), res_body.to_owned(), field_name.to_owned())
+                        .with_method(method.to_owned())
+                        .with_headers(headers.to_owned())
+                        .with_is_mutation(is_mutation.to_owned())
+                        .with_req_body(req_body.to_owned());
+
+                self.generate_from_json(type_name_generator, &[req_sample])
+            }
+            Input::Proto { metadata, url, connect_rpc } => {
+                let proto_config = self.generate_from_proto(metadata, &self.query, url)?;
+                if connect_rpc == &Some(true) {
+                    Ok(ConnectRPC.transform(proto_config).to_result()?)
+                } else {
+                    Ok(proto_config)
+                }
+            }
+        }
     }
 }
 
@@ -162,7 +240,7 @@ impl Generator {
 // that is important for generation to work
 // TODO: probably we can drop this in case the config_reader will use
 // protox::compile instead of more low-level protox_parse::parse
-fn resolve_file_descriptor_set(
+pub fn resolve_file_descriptor_set(
     descriptor_set: FileDescriptorSet,
 ) -> anyhow::Result<FileDescriptorSet> {
     let descriptor_set = DescriptorPool::from_file_descriptor_set(descriptor_set)?;
@@ -180,7 +258,7 @@ fn resolve_file_descriptor_set(
 pub mod test {
     use std::collections::BTreeMap;
 
-    use prost_reflect::prost_types::FileDescriptorSet;
+    use prost_reflect::prost_types::{FileDescriptorProto, FileDescriptorSet};
     use serde::{Deserialize, Deserializer};
     use serde_json::Value;
     use url::Url;
@@ -280,6 +358,45 @@ pub mod test {
         Ok(())
     }
 
+    #[test]
+    fn should_report_skipped_inputs() -> anyhow::Result<()> {
+        let news_proto = tailcall_fixtures::protobuf::NEWS;
+        let set = compile_protobuf(&[news_proto])?;
+        let broken = FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("broken.proto".to_string()),
+                dependency: vec!["missing.proto".to_string()],
+                ..Default::default()
+            }],
+        };
+        let generator = Generator::default().inputs(vec![
+            Input::Proto {
+                metadata: ProtoMetadata { descriptor_set: set, path: "news.proto".to_string() },
+                url: "http://localhost:50051".to_string(),
+                connect_rpc: None,
+            },
+            Input::Proto {
+                metadata: ProtoMetadata {
+                    descriptor_set: broken,
+                    path: "broken.proto".to_string(),
+                },
+                url: "http://localhost:50051".to_string(),
+                connect_rpc: None,
+            },
+        ]);
+
+        let (_, report) = generator.generate_with_report(false)?;
+
+        assert_eq!(report.generated, 6);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].path, "broken.proto");
+        assert!(report
+            .to_string()
+            .starts_with("generated 6 fields, skipped 1: broken.proto: "));
+        assert!(generator.generate(false).is_err());
+        Ok(())
+    }
+
     #[test]
     fn should_generate_config_from_configs() -> anyhow::Result<()> {
         let cfg_module = Generator::default()
@@ -133,6 +133,44 @@ impl GraphQLType<Parsed> {
             self.0.name.clone()
         }
     }
+
+    /// Drops the longest of the packages the namespace starts with, keeping
+    /// the names of the messages the type is nested in.
+    pub fn strip_package<S: AsRef<str>>(mut self, packages: &[S]) -> Self {
+        let namespace = &self.0.namespace.0;
+        let package_len = packages
+            .iter()
+            .map(|package| {
+                package
+                    .as_ref()
+                    .split(PACKAGE_SEPARATOR)
+                    .filter(|a| !a.is_empty())
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|package| namespace.starts_with(package))
+            .map(|package| package.len())
+            .max()
+            .unwrap_or_default();
+        self.0.namespace.0.drain(..package_len);
+        self
+    }
+
+    /// Formats the name of an object type or an enum with `prefix` in place
+    /// of the prefix of the generated names.
+    pub fn to_string_with_prefix(&self, prefix: &str) -> String {
+        let parsed = &self.0;
+        let mut name = String::new();
+        if !Scalar::is_predefined(&parsed.name) {
+            name.push_str(prefix);
+        }
+        if !parsed.namespace.is_empty() {
+            name.push_str(parsed.namespace.to_string().as_str());
+            name.push_str(DEFAULT_SEPARATOR);
+        }
+        name.push_str(parsed.name.as_str());
+        name
+    }
 }
 
 /// Used to convert proto type names to GraphQL formatted names.
@@ -308,6 +346,39 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_strip_package() {
+        let packages = ["shop", "shop.v1"];
+        let name = |name: &str, namespace: &str| {
+            GraphQLType::new(name)
+                .push(namespace)
+                .into_object_type()
+                .strip_package(&packages)
+                .to_string_with_prefix("")
+        };
+
+        assert_eq!(name("User", "shop.v1"), "User");
+        assert_eq!(name("Item", "shop.v1.Order"), "Order__Item");
+        assert_eq!(name("User", "shop"), "User");
+        assert_eq!(name("User", "billing"), "billing__User");
+        assert_eq!(name("User", "shopping"), "shopping__User");
+        assert_eq!(name("Empty", ""), "Empty");
+    }
+
+    #[test]
+    fn test_to_string_with_prefix() {
+        let actual = GraphQLType::new("Order")
+            .push("shop.v1")
+            .into_object_type()
+            .to_string_with_prefix("Shop");
+        assert_eq!(actual, "Shopshop__v1__Order");
+
+        let actual = GraphQLType::new("Empty")
+            .into_object_type()
+            .to_string_with_prefix("Shop");
+        assert_eq!(actual, "Empty");
+    }
+
     fn assert_type_names(input: Vec<TestParams>) {
         for ((entity, namespaces, name), expected) in input {
             let prefix = match entity {
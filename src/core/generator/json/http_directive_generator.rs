@@ -121,9 +121,12 @@ impl<'a> HttpDirectiveGenerator<'a> {
             let query_key = query.key.to_case(Case::Camel);
             let value: String = format!("{{{{.args.{}}}}}", query_key);
 
-            self.http
-                .query
-                .push(URLQuery { key: query.key.clone(), value, skip_empty: None });
+            self.http.query.push(URLQuery {
+                key: query.key.clone(),
+                value,
+                skip_empty: None,
+                encoding: Default::default(),
+            });
             field.args.insert(query_key, arg);
         }
     }
@@ -1,4 +1,5 @@
 mod from_json;
+mod from_openapi;
 mod from_proto;
 mod generator;
 mod graphql_type;
@@ -6,7 +7,9 @@ mod json;
 mod proto;
 
 pub use from_json::{FromJsonGenerator, RequestSample};
-pub use generator::{Generator, Input};
+pub use from_openapi::{from_json_schema, from_openapi};
+pub use from_proto::{from_proto, from_proto_file, ProtoOptions, TypeNaming};
+pub use generator::{resolve_file_descriptor_set, Generator, Input, Report, Skipped};
 
 use crate::core::counter::{Count, Counter};
 
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_graphql::async_trait;
+use async_graphql::futures_util::future::join_all;
+use async_graphql_value::{ConstValue, Name};
+use http::header::{HeaderName, HeaderValue};
+use indexmap::IndexMap;
+use reqwest::Url;
+
+use crate::core::config::GraphQLBatching;
+use crate::core::data_loader::{DataLoader, Loader};
+use crate::core::http::Method::POST;
+use crate::core::http::Response;
+use crate::core::runtime::TargetRuntime;
+
+/// A root field of a `@graphQL` query. The fields sent to the same url with
+/// the same headers are merged into one operation.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RootFieldRequest {
+    url: Url,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    field_name: String,
+    root_field: String,
+}
+
+impl RootFieldRequest {
+    /// `root_field` is the field with its arguments and selection set, as
+    /// rendered by [crate::core::graphql::RequestTemplate::render_root_field].
+    pub fn new(request: &reqwest::Request, field_name: &str, root_field: String) -> Self {
+        let mut headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        headers.sort_by(|(a, a_value), (b, b_value)| {
+            (a.as_str(), a_value.as_bytes()).cmp(&(b.as_str(), b_value.as_bytes()))
+        });
+
+        Self {
+            url: request.url().clone(),
+            headers,
+            field_name: field_name.to_string(),
+            root_field,
+        }
+    }
+
+    fn to_request(&self, body: String) -> reqwest::Request {
+        let mut request = reqwest::Request::new(POST.to_hyper(), self.url.clone());
+        request.headers_mut().extend(self.headers.iter().cloned());
+        request.body_mut().replace(body.into());
+        request
+    }
+}
+
+pub struct GraphqlMergeLoader {
+    pub runtime: TargetRuntime,
+    pub max_size: usize,
+}
+
+impl GraphqlMergeLoader {
+    pub fn new(runtime: TargetRuntime, max_size: usize) -> Self {
+        GraphqlMergeLoader { runtime, max_size: max_size.max(1) }
+    }
+
+    pub fn into_data_loader(
+        self,
+        batching: &GraphQLBatching,
+    ) -> DataLoader<RootFieldRequest, GraphqlMergeLoader> {
+        DataLoader::new(self).delay(Duration::from_millis(batching.get_delay() as u64))
+    }
+
+    /// Sends the fields as the aliased root fields of a single query.
+    async fn load_merged(
+        &self,
+        keys: &[&RootFieldRequest],
+    ) -> anyhow::Result<Vec<(RootFieldRequest, Response<ConstValue>)>> {
+        let fields = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| format!("{}: {}", alias(i), key.root_field))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let query = format!(r#"{{ "query": "query {{ {fields} }}" }}"#);
+
+        let response = self
+            .runtime
+            .http
+            .execute(keys[0].to_request(query))
+            .await?
+            .to_json::<ConstValue>()?;
+
+        Ok(split_response(response, keys))
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<RootFieldRequest> for GraphqlMergeLoader {
+    type Value = Response<ConstValue>;
+    type Error = Arc<anyhow::Error>;
+
+    #[allow(clippy::mutable_key_type)]
+    async fn load(
+        &self,
+        keys: &[RootFieldRequest],
+    ) -> async_graphql::Result<HashMap<RootFieldRequest, Self::Value>, Self::Error> {
+        let mut groups: IndexMap<_, Vec<_>> = IndexMap::new();
+        for key in keys {
+            groups
+                .entry((&key.url, &key.headers))
+                .or_default()
+                .push(key);
+        }
+
+        let mut operations = vec![];
+        for (_, mut keys) in groups {
+            // The keys come in any order, sorting them sends the same
+            // operations for the same fields.
+            keys.sort_by(|a, b| a.root_field.cmp(&b.root_field));
+            for chunk in keys.chunks(self.max_size) {
+                operations.push(self.load_merged(chunk));
+            }
+        }
+
+        let mut hashmap = HashMap::with_capacity(keys.len());
+        for responses in join_all(operations).await {
+            hashmap.extend(responses?);
+        }
+
+        Ok(hashmap)
+    }
+}
+
+fn alias(i: usize) -> String {
+    format!("f{i}")
+}
+
+/// Splits the response of the merged query into the responses each field
+/// would have gotten on its own. The errors are routed to the field whose
+/// alias starts their path, the ones without a path go to every field.
+fn split_response(
+    response: Response<ConstValue>,
+    keys: &[&RootFieldRequest],
+) -> Vec<(RootFieldRequest, Response<ConstValue>)> {
+    let (mut data, errors) = match response.body {
+        ConstValue::Object(mut body) => {
+            let data = match body.shift_remove("data") {
+                Some(ConstValue::Object(data)) => data,
+                _ => IndexMap::new(),
+            };
+            let errors = match body.shift_remove("errors") {
+                Some(ConstValue::List(errors)) => errors,
+                _ => vec![],
+            };
+            (data, errors)
+        }
+        _ => (IndexMap::new(), vec![]),
+    };
+
+    let mut field_errors = vec![vec![]; keys.len()];
+    for mut error in errors {
+        let index = route_error(&mut error, keys);
+        match index {
+            Some(index) => field_errors[index].push(error),
+            None => field_errors
+                .iter_mut()
+                .for_each(|errors| errors.push(error.clone())),
+        }
+    }
+
+    keys.iter()
+        .zip(field_errors)
+        .enumerate()
+        .map(|(i, (key, errors))| {
+            let value = data.shift_remove(alias(i).as_str()).unwrap_or_default();
+            let mut body = IndexMap::new();
+            body.insert(
+                Name::new("data"),
+                ConstValue::Object(IndexMap::from([(Name::new(&key.field_name), value)])),
+            );
+            if !errors.is_empty() {
+                body.insert(Name::new("errors"), ConstValue::List(errors));
+            }
+
+            let response = Response {
+                status: response.status,
+                headers: response.headers.clone(),
+                body: ConstValue::Object(body),
+            };
+            ((*key).clone(), response)
+        })
+        .collect()
+}
+
+/// The index of the field the error belongs to, its path then starts with the
+/// name of the field instead of the alias.
+fn route_error(error: &mut ConstValue, keys: &[&RootFieldRequest]) -> Option<usize> {
+    let ConstValue::Object(error) = error else {
+        return None;
+    };
+    let Some(ConstValue::List(path)) = error.get_mut("path") else {
+        return None;
+    };
+    let Some(ConstValue::String(segment)) = path.first_mut() else {
+        return None;
+    };
+    let index = segment.strip_prefix('f')?.parse::<usize>().ok()?;
+    let key = keys.get(index)?;
+    *segment = key.field_name.clone();
+    Some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use hyper::body::Bytes;
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::HttpIO;
+
+    #[derive(Default)]
+    struct Upstream {
+        queries: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for Upstream {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let body = request.body().and_then(|b| b.as_bytes()).unwrap();
+            let query = String::from_utf8(body.to_vec())?;
+            // The second field of an operation is never found
+            let response = if query.contains("f1:") {
+                json!({
+                    "data": {"f0": {"name": "a"}, "f1": null},
+                    "errors": [{"message": "not found", "path": ["f1"]}]
+                })
+            } else {
+                json!({"data": {"f0": {"name": "a"}}})
+            };
+            self.queries.lock().unwrap().push(query);
+
+            Ok(Response::empty().body(Bytes::from(serde_json::to_vec(&response)?)))
+        }
+    }
+
+    fn key(id: usize) -> RootFieldRequest {
+        let request = reqwest::Request::new(
+            reqwest::Method::POST,
+            Url::parse("http://upstream/graphql").unwrap(),
+        );
+        RootFieldRequest::new(&request, "user", format!("user(id: {id}) {{ name }}"))
+    }
+
+    fn body(value: serde_json::Value) -> ConstValue {
+        ConstValue::from_json(value).unwrap()
+    }
+
+    #[tokio::test]
+    #[allow(clippy::mutable_key_type)]
+    async fn test_merge_fields() {
+        let http = Arc::new(Upstream::default());
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.http = http.clone();
+        let loader = GraphqlMergeLoader::new(runtime, 2);
+
+        let keys = [key(3), key(1), key(2)];
+        let responses = loader.load(&keys).await.unwrap();
+
+        assert_eq!(
+            *http.queries.lock().unwrap(),
+            vec![
+                r#"{ "query": "query { f0: user(id: 1) { name } f1: user(id: 2) { name } }" }"#,
+                r#"{ "query": "query { f0: user(id: 3) { name } }" }"#,
+            ]
+        );
+        assert_eq!(
+            responses[&key(1)].body,
+            body(json!({"data": {"user": {"name": "a"}}}))
+        );
+        assert_eq!(
+            responses[&key(2)].body,
+            body(json!({
+                "data": {"user": null},
+                "errors": [{"message": "not found", "path": ["user"]}]
+            }))
+        );
+        assert_eq!(
+            responses[&key(3)].body,
+            body(json!({"data": {"user": {"name": "a"}}}))
+        );
+    }
+
+    #[test]
+    fn test_split_errors_without_path() {
+        let response = Response {
+            body: body(json!({"data": null, "errors": [{"message": "invalid query"}]})),
+            ..Default::default()
+        };
+        let keys = [key(1), key(2)];
+
+        let responses = split_response(response, &keys.iter().collect::<Vec<_>>());
+
+        for (_, response) in responses {
+            assert_eq!(
+                response.body,
+                body(json!({
+                    "data": {"user": null},
+                    "errors": [{"message": "invalid query"}]
+                }))
+            );
+        }
+    }
+}
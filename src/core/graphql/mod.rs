@@ -1,5 +1,7 @@
 mod data_loader;
+mod merge_loader;
 mod request_template;
 
 pub use data_loader::*;
+pub use merge_loader::*;
 pub use request_template::*;
@@ -115,7 +115,19 @@ impl RequestTemplate {
         ctx: &C,
     ) -> String {
         let operation_type = &self.operation_type;
+        let root_field = self.render_root_field(ctx);
 
+        let query = format!(r#"{{ "query": "{operation_type} {{ {root_field} }}" }}"#);
+        info!("Query {} ", query);
+        query
+    }
+
+    /// The root field the operation selects, with its arguments, directives
+    /// and selection set, escaped to be embedded in the JSON body.
+    pub fn render_root_field<C: PathGraphql + HasHeaders + GraphQLOperationContext>(
+        &self,
+        ctx: &C,
+    ) -> String {
         let selection_set = match &self.selection {
             Some(Selection::Resolved(s)) => Cow::Borrowed(s),
             Some(Selection::UnResolved(u)) => Cow::Owned(u.to_string()),
@@ -156,10 +168,7 @@ impl RequestTemplate {
             }
         }
 
-        let query =
-            format!(r#"{{ "query": "{operation_type} {{ {operation} {selection_set} }}" }}"#);
-        info!("Query {} ", query);
-        query
+        format!("{operation} {selection_set}")
     }
 
     pub fn new(
@@ -75,6 +75,7 @@ mod tests {
             headers: None,
             meta: None,
             proto_paths: None,
+            optional: false,
         }]);
         let method = GrpcMethod {
             package: "greetings".to_string(),
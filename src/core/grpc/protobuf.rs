@@ -281,6 +281,7 @@ pub mod tests {
             headers: None,
             meta: None,
             proto_paths: link_config.proto_paths,
+            optional: false,
         }]);
 
         let method = GrpcMethod { package: id, service: "a".to_owned(), name: "b".to_owned() };
@@ -161,6 +161,7 @@ mod tests {
             headers: None,
             meta: None,
             proto_paths: None,
+            optional: false,
         }]);
         let method = GrpcMethod {
             package: id.to_string(),
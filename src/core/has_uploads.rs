@@ -0,0 +1,12 @@
+use crate::core::http::Uploads;
+use crate::core::ir::{EvalContext, ResolverContextLike};
+
+pub trait HasUploads {
+    fn uploads(&self) -> &Uploads;
+}
+
+impl<Ctx: ResolverContextLike> HasUploads for EvalContext<'_, Ctx> {
+    fn uploads(&self) -> &Uploads {
+        &self.request_ctx.uploads
+    }
+}
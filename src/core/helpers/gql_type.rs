@@ -6,6 +6,8 @@ pub fn detect_gql_data_type(value: &str) -> String {
 
     if trimmed_value.parse::<i32>().is_ok() {
         "Int".to_string()
+    } else if trimmed_value.parse::<i64>().is_ok() {
+        "Int64".to_string()
     } else if trimmed_value.parse::<f64>().is_ok() {
         "Float".to_string()
     } else if trimmed_value.parse::<bool>().is_ok() {
@@ -31,6 +33,8 @@ pub fn to_gql_type(value: &Value) -> String {
             "JSON"
         }
         Value::Bool(_) => "Boolean",
+        // integers that don't fit an `Int` would be rejected or rounded by it
+        Value::Number(n) if n.as_i64().is_some_and(|n| i32::try_from(n).is_err()) => "Int64",
         Value::Number(_) => "Int",
         Value::String(_) => "String",
         Value::Array(_) => "List",
@@ -52,6 +56,7 @@ mod test {
     #[test]
     fn test_detect_gql_data_type() {
         assert_eq!(detect_gql_data_type("42"), "Int");
+        assert_eq!(detect_gql_data_type("1234567890123"), "Int64");
         assert_eq!(detect_gql_data_type("3.14"), "Float");
         assert_eq!(detect_gql_data_type("true"), "Boolean");
         assert_eq!(detect_gql_data_type("false"), "Boolean");
@@ -81,6 +86,7 @@ mod test {
         assert_eq!(to_gql_type(&json!(12.3)), "Int");
         assert_eq!(to_gql_type(&json!(-12)), "Int");
         assert_eq!(to_gql_type(&json!(-12.2)), "Int");
+        assert_eq!(to_gql_type(&json!(9007199254740993i64)), "Int64");
         assert_eq!(to_gql_type(&json!(true)), "Boolean");
         assert_eq!(to_gql_type(&json!(false)), "Boolean");
         assert_eq!(to_gql_type(&json!([1, 2, 3])), "List");
@@ -0,0 +1,491 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use http::header::{HeaderName, HeaderValue};
+use hyper::body::Bytes;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+use super::Response;
+use crate::core::{FileIO, HttpIO};
+
+/// Environment variable pointing to the cassette that upstream traffic is
+/// recorded into.
+pub const TAILCALL_RECORD: &str = "TAILCALL_RECORD";
+
+/// Environment variable pointing to the cassette that upstream traffic is
+/// replayed from.
+pub const TAILCALL_REPLAY: &str = "TAILCALL_REPLAY";
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Time the writer of a cassette waits for more interactions before writing
+/// the file, so that a burst of requests is written once.
+const WRITE_DELAY: Duration = Duration::from_millis(100);
+
+/// Query parameters and headers whose values are never written to a cassette.
+const SECRETS: &[&str] = &[
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "proxy-authorization",
+    "x-api-key",
+    "api_key",
+    "apikey",
+    "access_token",
+    "token",
+    "secret",
+    "password",
+];
+
+fn default_ignored_headers() -> Vec<String> {
+    vec![
+        "date".to_string(),
+        "x-request-id".to_string(),
+        "traceparent".to_string(),
+        "tracestate".to_string(),
+    ]
+}
+
+/// A request as it is stored in a cassette.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+/// A response as it is stored in a cassette.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// A file holding recorded upstream interactions.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cassette {
+    /// Headers that are left out while recording and matching, because their
+    /// values change between runs.
+    #[serde(default = "default_ignored_headers")]
+    pub ignored_headers: Vec<String>,
+    #[serde(default)]
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Self {
+            ignored_headers: default_ignored_headers(),
+            interactions: vec![],
+        }
+    }
+}
+
+impl Cassette {
+    fn is_ignored(&self, name: &str) -> bool {
+        self.ignored_headers
+            .iter()
+            .any(|ignored| ignored.eq_ignore_ascii_case(name))
+    }
+
+    fn normalize_headers(&self, headers: &http::HeaderMap) -> BTreeMap<String, String> {
+        headers
+            .iter()
+            .filter(|(name, _)| !self.is_ignored(name.as_str()))
+            .map(|(name, value)| {
+                let name = name.as_str().to_lowercase();
+                let value = if is_secret(&name) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).to_string()
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    fn to_recorded(&self, request: &reqwest::Request) -> RecordedRequest {
+        RecordedRequest {
+            method: request.method().to_string(),
+            url: redact_url(request.url()),
+            headers: self.normalize_headers(request.headers()),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| String::from_utf8_lossy(body).to_string()),
+        }
+    }
+
+    fn find(&self, request: &RecordedRequest) -> Option<&Interaction> {
+        self.interactions
+            .iter()
+            .find(|interaction| &interaction.request == request)
+    }
+}
+
+fn is_secret(name: &str) -> bool {
+    SECRETS
+        .iter()
+        .any(|secret| secret.eq_ignore_ascii_case(name))
+}
+
+fn redact_url(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    if url.query().is_some() {
+        let query = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if is_secret(&k) { REDACTED.into() } else { v };
+                (k.to_string(), v.to_string())
+            })
+            .collect::<Vec<_>>();
+        url.query_pairs_mut().clear().extend_pairs(query);
+    }
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    }
+    url.to_string()
+}
+
+enum Message {
+    Record(Interaction),
+    /// Answers once the interactions recorded before it are written.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Records interactions into a cassette file. A single task owns the
+/// interactions and writes the file with the ones received meanwhile, so
+/// that concurrent requests don't overwrite each other's.
+pub struct CassetteRecorder {
+    /// The settings of the cassette, its interactions are kept by the task.
+    cassette: Cassette,
+    sender: mpsc::UnboundedSender<Message>,
+}
+
+impl CassetteRecorder {
+    /// Appends the interactions to the ones of `cassette`, which is empty
+    /// unless the file at `path` already holds a cassette.
+    pub fn new(path: String, cassette: Cassette, file: Arc<dyn FileIO>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let settings = Cassette { interactions: vec![], ..cassette.clone() };
+        tokio::spawn(write_cassette(path, cassette, file, receiver));
+        Self { cassette: settings, sender }
+    }
+
+    /// Waits until the interactions recorded so far are written.
+    pub async fn flush(&self) {
+        let (sender, receiver) = oneshot::channel();
+        if self.sender.send(Message::Flush(sender)).is_ok() {
+            let _ = receiver.await;
+        }
+    }
+
+    fn record(&self, interaction: Interaction) -> Result<()> {
+        self.sender
+            .send(Message::Record(interaction))
+            .map_err(|_| anyhow!("The cassette is no longer recorded"))
+    }
+}
+
+async fn write_cassette(
+    path: String,
+    mut cassette: Cassette,
+    file: Arc<dyn FileIO>,
+    mut receiver: mpsc::UnboundedReceiver<Message>,
+) {
+    while let Some(message) = receiver.recv().await {
+        tokio::time::sleep(WRITE_DELAY).await;
+        let mut messages = vec![message];
+        while let Ok(message) = receiver.try_recv() {
+            messages.push(message);
+        }
+
+        let mut flushed = vec![];
+        for message in messages {
+            match message {
+                Message::Record(interaction) => cassette.interactions.push(interaction),
+                Message::Flush(sender) => flushed.push(sender),
+            }
+        }
+        let written = match serde_json::to_vec_pretty(&cassette) {
+            Ok(content) => file.write(&path, &content).await,
+            Err(err) => Err(err.into()),
+        };
+        if let Err(err) = written {
+            tracing::error!("Failed to write cassette {}: {}", path, err);
+        }
+        for sender in flushed {
+            let _ = sender.send(());
+        }
+    }
+}
+
+/// Wraps an [HttpIO] to record the upstream traffic into a cassette or to
+/// replay it from one.
+pub enum CassetteHttp {
+    /// Executes the requests and records every interaction.
+    Record {
+        recorder: Arc<CassetteRecorder>,
+        inner: Arc<dyn HttpIO>,
+    },
+    /// Serves the requests from the cassette without touching the network.
+    Replay { cassette: Arc<Cassette> },
+}
+
+impl CassetteHttp {
+    pub fn record(recorder: Arc<CassetteRecorder>, inner: Arc<dyn HttpIO>) -> Self {
+        CassetteHttp::Record { recorder, inner }
+    }
+
+    pub fn replay(cassette: Arc<Cassette>) -> Self {
+        CassetteHttp::Replay { cassette }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpIO for CassetteHttp {
+    async fn execute(&self, request: reqwest::Request) -> Result<Response<Bytes>> {
+        match self {
+            CassetteHttp::Record { recorder, inner } => {
+                let cassette = &recorder.cassette;
+                let recorded = cassette.to_recorded(&request);
+                let response = inner.execute(request).await?;
+
+                recorder.record(Interaction {
+                    request: recorded,
+                    response: RecordedResponse {
+                        status: response.status.as_u16(),
+                        headers: cassette.normalize_headers(&response.headers),
+                        body: String::from_utf8_lossy(&response.body).to_string(),
+                    },
+                })?;
+
+                Ok(response)
+            }
+            CassetteHttp::Replay { cassette } => {
+                let recorded = cassette.to_recorded(&request);
+                let interaction = cassette.find(&recorded).ok_or(anyhow!(
+                    "No recorded interaction for upstream request {} {}",
+                    recorded.method,
+                    recorded.url
+                ))?;
+
+                let mut headers = http::HeaderMap::new();
+                for (key, value) in interaction.response.headers.iter() {
+                    headers.insert(
+                        HeaderName::from_bytes(key.as_bytes())?,
+                        HeaderValue::from_str(value)?,
+                    );
+                }
+
+                Ok(Response {
+                    status: reqwest::StatusCode::from_u16(interaction.response.status)?,
+                    headers,
+                    body: Bytes::from(interaction.response.body.clone()),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use futures_util::future::join_all;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryFile(Mutex<HashMap<String, String>>);
+
+    #[async_trait::async_trait]
+    impl FileIO for InMemoryFile {
+        async fn write<'a>(&'a self, path: &'a str, content: &'a [u8]) -> Result<()> {
+            let content = String::from_utf8(content.to_vec())?;
+            self.0.lock().unwrap().insert(path.to_string(), content);
+            Ok(())
+        }
+
+        async fn read<'a>(&'a self, path: &'a str) -> Result<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .get(path)
+                .cloned()
+                .ok_or(anyhow!("File not found"))
+        }
+    }
+
+    struct Upstream;
+
+    #[async_trait::async_trait]
+    impl HttpIO for Upstream {
+        async fn execute(&self, request: reqwest::Request) -> Result<Response<Bytes>> {
+            let mut headers = http::HeaderMap::new();
+            headers.insert("content-type", HeaderValue::from_static("application/json"));
+            headers.insert(
+                "date",
+                HeaderValue::from_static("Tue, 01 Oct 2024 00:00:00 GMT"),
+            );
+            let body = format!("{{\"path\":\"{}\"}}", request.url().path());
+            Ok(Response {
+                status: reqwest::StatusCode::OK,
+                headers,
+                body: Bytes::from(body),
+            })
+        }
+    }
+
+    fn request(url: &str, request_id: &str) -> reqwest::Request {
+        let mut request = reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap());
+        request
+            .headers_mut()
+            .insert("x-request-id", HeaderValue::from_str(request_id).unwrap());
+        request
+            .headers_mut()
+            .insert("accept", HeaderValue::from_static("application/json"));
+        request
+    }
+
+    async fn record(file: Arc<InMemoryFile>) -> Cassette {
+        let recorder =
+            CassetteRecorder::new("cassette.json".into(), Cassette::default(), file.clone());
+        let recorder = Arc::new(recorder);
+        let http = CassetteHttp::record(recorder.clone(), Arc::new(Upstream));
+        http.execute(request("http://localhost/users/1?token=abc", "1"))
+            .await
+            .unwrap();
+        http.execute(request("http://localhost/posts", "2"))
+            .await
+            .unwrap();
+        recorder.flush().await;
+
+        serde_json::from_str(&file.read("cassette.json").await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_record() {
+        let cassette = record(Arc::new(InMemoryFile::default())).await;
+
+        assert_eq!(cassette.interactions.len(), 2);
+
+        let interaction = &cassette.interactions[0];
+        assert_eq!(
+            interaction.request.url,
+            "http://localhost/users/1?token=%5BREDACTED%5D"
+        );
+        assert!(!interaction.request.headers.contains_key("x-request-id"));
+        assert!(!interaction.response.headers.contains_key("date"));
+        assert_eq!(interaction.response.body, r#"{"path":"/users/1"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_record_concurrently() {
+        let file = Arc::new(InMemoryFile::default());
+        let recorder =
+            CassetteRecorder::new("cassette.json".into(), Cassette::default(), file.clone());
+        let recorder = Arc::new(recorder);
+        // each runtime wraps its own client with the same recorder
+        let clients = (0..2)
+            .map(|_| CassetteHttp::record(recorder.clone(), Arc::new(Upstream)))
+            .collect::<Vec<_>>();
+
+        join_all((0..20).map(|i| {
+            let url = format!("http://localhost/users/{i}");
+            clients[i % 2].execute(request(&url, "1"))
+        }))
+        .await;
+        recorder.flush().await;
+
+        let cassette: Cassette =
+            serde_json::from_str(&file.read("cassette.json").await.unwrap()).unwrap();
+        assert_eq!(cassette.interactions.len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_record_appends() {
+        let file = Arc::new(InMemoryFile::default());
+        let cassette = record(file.clone()).await;
+        let recorder = Arc::new(CassetteRecorder::new(
+            "cassette.json".into(),
+            cassette,
+            file.clone(),
+        ));
+        let http = CassetteHttp::record(recorder.clone(), Arc::new(Upstream));
+
+        http.execute(request("http://localhost/comments", "3"))
+            .await
+            .unwrap();
+        recorder.flush().await;
+
+        let cassette: Cassette =
+            serde_json::from_str(&file.read("cassette.json").await.unwrap()).unwrap();
+        let urls = cassette
+            .interactions
+            .iter()
+            .map(|interaction| interaction.request.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            [
+                "http://localhost/users/1?token=%5BREDACTED%5D",
+                "http://localhost/posts",
+                "http://localhost/comments"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let cassette = record(Arc::new(InMemoryFile::default())).await;
+        let http = CassetteHttp::replay(Arc::new(cassette));
+
+        // volatile headers don't take part in the match
+        let response = http
+            .execute(request("http://localhost/users/1?token=abc", "3"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.body,
+            Bytes::from_static(b"{\"path\":\"/users/1\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_unmatched() {
+        let cassette = record(Arc::new(InMemoryFile::default())).await;
+        let http = CassetteHttp::replay(Arc::new(cassette));
+
+        let error = http
+            .execute(request("http://localhost/comments", "1"))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "No recorded interaction for upstream request GET http://localhost/comments"
+        );
+    }
+}
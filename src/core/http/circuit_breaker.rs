@@ -0,0 +1,336 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+
+use super::Response;
+use crate::core::config;
+use crate::core::ir::Error;
+
+/// Settings of a circuit breaker attached to an upstream HTTP call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// Number of consecutive failures after which the circuit opens.
+    pub failure_threshold: u32,
+    /// Time the circuit stays open before a trial request is let through.
+    pub timeout: Duration,
+}
+
+impl From<&config::CircuitBreaker> for CircuitBreaker {
+    fn from(value: &config::CircuitBreaker) -> Self {
+        Self {
+            failure_threshold: value.failure_threshold.max(1),
+            timeout: Duration::from_secs(value.timeout_secs),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally, failures are being counted.
+    Closed { failures: u32 },
+    /// Requests fail fast until the timeout elapses.
+    Open { since: DateTime<Utc> },
+    /// A single trial request is in flight to probe the upstream, the circuit
+    /// was opened at `since`.
+    HalfOpen { since: DateTime<Utc> },
+}
+
+impl Default for CircuitState {
+    fn default() -> Self {
+        CircuitState::Closed { failures: 0 }
+    }
+}
+
+/// Keeps the state of the circuit breaker for every upstream, keyed by its
+/// `host:port`. The state is shared across all requests handled by the same
+/// `AppContext`.
+#[derive(Default, Debug)]
+pub struct CircuitBreakers {
+    states: DashMap<String, CircuitState>,
+}
+
+impl CircuitBreakers {
+    /// Checks if a request to the given `host:port` is allowed. Returns an
+    /// error without touching the network when the circuit is open.
+    pub fn acquire<'a>(
+        &'a self,
+        host: &str,
+        settings: &'a CircuitBreaker,
+    ) -> Result<CircuitPermit<'a>, Error> {
+        let is_trial = self.acquire_at(host, settings, Utc::now())?;
+        Ok(CircuitPermit {
+            breakers: self,
+            host: host.to_string(),
+            settings,
+            is_trial,
+            recorded: false,
+        })
+    }
+
+    /// Returns the current state of the circuit for the host.
+    pub fn state(&self, host: &str) -> CircuitState {
+        self.states.get(host).map(|s| *s).unwrap_or_default()
+    }
+
+    /// Lets a request through unless the circuit is open, and tells whether
+    /// it's the trial request of a half open circuit.
+    fn acquire_at(
+        &self,
+        host: &str,
+        settings: &CircuitBreaker,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        let mut state = self.states.entry(host.to_string()).or_default();
+
+        match *state {
+            CircuitState::Closed { .. } => Ok(false),
+            CircuitState::Open { since } => {
+                let elapsed = (now - since).to_std().unwrap_or_default();
+                if elapsed >= settings.timeout {
+                    *state = CircuitState::HalfOpen { since };
+                    Ok(true)
+                } else {
+                    Err(Error::CircuitOpen {
+                        host: host.to_string(),
+                        retry_after: settings.timeout.saturating_sub(elapsed),
+                    })
+                }
+            }
+            CircuitState::HalfOpen { .. } => {
+                Err(Error::CircuitOpen { host: host.to_string(), retry_after: Duration::ZERO })
+            }
+        }
+    }
+
+    fn record_at(&self, host: &str, settings: &CircuitBreaker, success: bool, now: DateTime<Utc>) {
+        let mut state = self.states.entry(host.to_string()).or_default();
+
+        *state = match (*state, success) {
+            (_, true) => CircuitState::Closed { failures: 0 },
+            (CircuitState::Closed { failures }, false) => {
+                let failures = failures + 1;
+                if failures >= settings.failure_threshold {
+                    CircuitState::Open { since: now }
+                } else {
+                    CircuitState::Closed { failures }
+                }
+            }
+            (CircuitState::HalfOpen { .. }, false) | (CircuitState::Open { .. }, false) => {
+                CircuitState::Open { since: now }
+            }
+        };
+    }
+
+    /// Gives the turn of a trial request that ended without an outcome back,
+    /// so that the next request probes the upstream instead.
+    fn release(&self, host: &str) {
+        if let Some(mut state) = self.states.get_mut(host) {
+            if let CircuitState::HalfOpen { since } = *state {
+                *state = CircuitState::Open { since };
+            }
+        }
+    }
+}
+
+/// A request let through by [CircuitBreakers::acquire]. When it's dropped
+/// without [CircuitPermit::record], eg. because the request was cancelled, a
+/// trial request gives its turn back, so that the circuit doesn't stay half
+/// open.
+#[must_use]
+pub struct CircuitPermit<'a> {
+    breakers: &'a CircuitBreakers,
+    host: String,
+    settings: &'a CircuitBreaker,
+    /// Whether it's the trial request of a half open circuit, the only one
+    /// that gives its turn back.
+    is_trial: bool,
+    recorded: bool,
+}
+
+impl CircuitPermit<'_> {
+    /// Records the outcome of the request.
+    pub fn record(mut self, success: bool) {
+        self.recorded = true;
+        self.breakers
+            .record_at(&self.host, self.settings, success, Utc::now());
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if !self.recorded && self.is_trial {
+            self.breakers.release(&self.host);
+        }
+    }
+}
+
+/// Whether the outcome of a request counts as a failure of the upstream: a
+/// server error, a timeout or an IO error. The other errors, eg. a `404` or a
+/// body that can't be read, come from an upstream that is up.
+pub fn is_failure<T>(response: &Result<Response<T>, Error>) -> bool {
+    match response {
+        Ok(res) => res.status.is_server_error(),
+        Err(error) => match error.upstream_status() {
+            Some(status) => status >= 500,
+            None => matches!(error, Error::IO(_) | Error::Timeout { .. }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> CircuitBreaker {
+        CircuitBreaker { failure_threshold: 2, timeout: Duration::from_secs(10) }
+    }
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breakers = CircuitBreakers::default();
+        let now = Utc::now();
+
+        breakers.record_at("a.com", &settings(), false, now);
+        assert_eq!(
+            breakers.state("a.com"),
+            CircuitState::Closed { failures: 1 }
+        );
+        assert!(breakers.acquire_at("a.com", &settings(), now).is_ok());
+
+        breakers.record_at("a.com", &settings(), false, now);
+        assert_eq!(breakers.state("a.com"), CircuitState::Open { since: now });
+        assert!(breakers.acquire_at("a.com", &settings(), now).is_err());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let breakers = CircuitBreakers::default();
+        let now = Utc::now();
+
+        breakers.record_at("a.com", &settings(), false, now);
+        breakers.record_at("a.com", &settings(), true, now);
+
+        assert_eq!(breakers.state("a.com"), CircuitState::default());
+    }
+
+    #[test]
+    fn test_half_open_after_timeout() {
+        let breakers = CircuitBreakers::default();
+        let now = Utc::now();
+        let later = now + chrono::Duration::seconds(11);
+
+        breakers.record_at("a.com", &settings(), false, now);
+        breakers.record_at("a.com", &settings(), false, now);
+
+        assert!(breakers.acquire_at("a.com", &settings(), later).is_ok());
+        assert_eq!(
+            breakers.state("a.com"),
+            CircuitState::HalfOpen { since: now }
+        );
+
+        // only one trial request is allowed while half open
+        assert!(breakers.acquire_at("a.com", &settings(), later).is_err());
+
+        breakers.record_at("a.com", &settings(), false, later);
+        assert_eq!(breakers.state("a.com"), CircuitState::Open { since: later });
+
+        let much_later = later + chrono::Duration::seconds(11);
+        assert!(breakers
+            .acquire_at("a.com", &settings(), much_later)
+            .is_ok());
+        breakers.record_at("a.com", &settings(), true, much_later);
+        assert_eq!(breakers.state("a.com"), CircuitState::default());
+    }
+
+    #[test]
+    fn test_dropped_trial_is_released() {
+        let breakers = CircuitBreakers::default();
+        let since = Utc::now() - chrono::Duration::seconds(11);
+
+        breakers.record_at("a.com", &settings(), false, since);
+        breakers.record_at("a.com", &settings(), false, since);
+
+        let settings = settings();
+        let permit = breakers.acquire("a.com", &settings).unwrap();
+        assert_eq!(breakers.state("a.com"), CircuitState::HalfOpen { since });
+        assert!(breakers.acquire("a.com", &settings).is_err());
+
+        // the trial was cancelled, so the next request probes the upstream
+        drop(permit);
+        assert_eq!(breakers.state("a.com"), CircuitState::Open { since });
+        breakers.acquire("a.com", &settings).unwrap().record(true);
+        assert_eq!(breakers.state("a.com"), CircuitState::default());
+    }
+
+    #[test]
+    fn test_dropped_request_is_not_released() {
+        let breakers = CircuitBreakers::default();
+        let settings = settings();
+
+        // a request let through while the circuit was closed
+        let permit = breakers.acquire("a.com:443", &settings).unwrap();
+
+        let since = Utc::now() - chrono::Duration::seconds(11);
+        breakers.record_at("a.com:443", &settings, false, since);
+        breakers.record_at("a.com:443", &settings, false, since);
+        let trial = breakers.acquire("a.com:443", &settings).unwrap();
+
+        // it doesn't give the turn of the trial in flight back
+        drop(permit);
+        assert_eq!(
+            breakers.state("a.com:443"),
+            CircuitState::HalfOpen { since }
+        );
+        assert!(breakers.acquire("a.com:443", &settings).is_err());
+        trial.record(true);
+    }
+
+    #[test]
+    fn test_is_failure() {
+        let response = |status| {
+            Ok(Response {
+                status: reqwest::StatusCode::from_u16(status).unwrap(),
+                headers: Default::default(),
+                body: (),
+            })
+        };
+        let upstream_status = |status| {
+            Err(Error::UpstreamStatus {
+                status,
+                message: String::new(),
+                body: Default::default(),
+                retry_after: None,
+            })
+        };
+        let timeout = Error::Timeout {
+            field: None,
+            url: String::new(),
+            timeout: Duration::from_secs(1),
+        };
+
+        assert!(!is_failure(&response(200)));
+        assert!(is_failure(&response(502)));
+        assert!(!is_failure::<()>(&upstream_status(404)));
+        assert!(!is_failure::<()>(&upstream_status(429)));
+        assert!(is_failure::<()>(&upstream_status(503)));
+        assert!(is_failure::<()>(&Err(timeout)));
+        assert!(is_failure::<()>(&Err(Error::IO(
+            "connection refused".into()
+        ))));
+        assert!(!is_failure::<()>(&Err(Error::Deserialize("eof".into()))));
+    }
+
+    #[test]
+    fn test_hosts_are_isolated() {
+        let breakers = CircuitBreakers::default();
+        let now = Utc::now();
+
+        breakers.record_at("a.com:443", &settings(), false, now);
+        breakers.record_at("a.com:443", &settings(), false, now);
+
+        assert!(breakers.acquire_at("a.com:443", &settings(), now).is_err());
+        assert!(breakers.acquire_at("a.com:8080", &settings(), now).is_ok());
+        assert!(breakers.acquire_at("b.com:443", &settings(), now).is_ok());
+    }
+}
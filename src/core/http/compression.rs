@@ -0,0 +1,404 @@
+//! Compresses the responses with the encoding negotiated from
+//! `Accept-Encoding` and decompresses gzip request bodies, when gzip is
+//! enabled.
+
+use std::io::{self, Read, Write};
+
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use futures_util::stream::{self, Stream};
+use futures_util::StreamExt;
+use hyper::body::{Bytes, HttpBody};
+use hyper::header::{
+    HeaderMap, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, VARY,
+};
+use hyper::{Body, Response};
+
+use super::etag;
+use super::sse::EVENT_STREAM;
+use crate::core::blueprint::Compression;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+}
+
+/// Picks the enabled encoding with the highest quality in `Accept-Encoding`,
+/// preferring brotli over gzip when both are equally acceptable.
+pub fn negotiate(headers: &HeaderMap, compression: &Compression) -> Option<Encoding> {
+    let accepted = headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| {
+            let mut params = item.split(';');
+            let name = params.next()?.trim().to_lowercase();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((name, quality))
+        })
+        .collect::<Vec<_>>();
+
+    let quality = |name: &str| {
+        accepted
+            .iter()
+            .find(|(accepted, _)| accepted == name)
+            .or_else(|| accepted.iter().find(|(accepted, _)| accepted == "*"))
+            .map_or(0.0, |(_, quality)| *quality)
+    };
+
+    [
+        (Encoding::Brotli, compression.brotli),
+        (Encoding::Gzip, compression.gzip),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(encoding, _)| (encoding, quality(encoding.as_str())))
+    .filter(|(_, quality)| *quality > 0.0)
+    .fold(
+        None,
+        |best: Option<(Encoding, f32)>, (encoding, quality)| match best {
+            Some((_, best_quality)) if best_quality >= quality => best,
+            _ => Some((encoding, quality)),
+        },
+    )
+    .map(|(encoding, _)| encoding)
+}
+
+fn is_compressible(headers: &HeaderMap) -> bool {
+    if headers.contains_key(CONTENT_ENCODING) {
+        return false;
+    }
+
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_lowercase())
+        .is_some_and(|value| {
+            !value.starts_with(EVENT_STREAM)
+                && (value.starts_with("text/")
+                    || ["json", "javascript", "xml", "graphql"]
+                        .iter()
+                        .any(|subtype| value.contains(subtype)))
+        })
+}
+
+/// Compresses the response body as it's streamed when its content type is
+/// compressible and it isn't known to be smaller than `min_size`.
+pub fn compress(
+    response: Response<Body>,
+    compression: &Compression,
+    encoding: Option<Encoding>,
+) -> Response<Body> {
+    let is_large = response
+        .body()
+        .size_hint()
+        .exact()
+        .map_or(true, |size| size >= compression.min_size as u64);
+
+    if !is_compressible(response.headers()) || !is_large {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("Accept-Encoding"));
+
+    match encoding {
+        Some(encoding) => {
+            parts.headers.remove(CONTENT_LENGTH);
+            etag::weaken(&mut parts.headers);
+            parts.headers.insert(
+                CONTENT_ENCODING,
+                HeaderValue::from_static(encoding.as_str()),
+            );
+            Response::from_parts(parts, Body::wrap_stream(encode(body, encoding)))
+        }
+        None => Response::from_parts(parts, body),
+    }
+}
+
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    Brotli(Box<brotli::CompressorWriter<Vec<u8>>>),
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip => Encoder::Gzip(GzEncoder::new(Vec::new(), Default::default())),
+            Encoding::Brotli => Encoder::Brotli(Box::new(brotli::CompressorWriter::new(
+                Vec::new(),
+                4096,
+                5,
+                22,
+            ))),
+        }
+    }
+
+    /// Feeds the chunk to the encoder and takes whatever it has produced so
+    /// far.
+    fn write(&mut self, chunk: &[u8]) -> io::Result<Bytes> {
+        let output = match self {
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.get_mut()
+            }
+            Encoder::Brotli(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.get_mut()
+            }
+        };
+        Ok(Bytes::from(std::mem::take(output)))
+    }
+
+    fn finish(self) -> io::Result<Bytes> {
+        let output = match self {
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            Encoder::Brotli(encoder) => encoder.into_inner(),
+        };
+        Ok(Bytes::from(output))
+    }
+}
+
+fn encode(body: Body, encoding: Encoding) -> impl Stream<Item = io::Result<Bytes>> + Send {
+    stream::unfold(Some((body, Encoder::new(encoding))), |state| async move {
+        let (mut body, mut encoder) = state?;
+        loop {
+            match body.next().await {
+                Some(Ok(chunk)) => match encoder.write(&chunk) {
+                    Ok(output) if output.is_empty() => continue,
+                    Ok(output) => return Some((Ok(output), Some((body, encoder)))),
+                    Err(err) => return Some((Err(err), None)),
+                },
+                Some(Err(err)) => return Some((Err(io::Error::other(err)), None)),
+                None => return Some((encoder.finish(), None)),
+            }
+        }
+    })
+}
+
+/// Gzip-encodes the body of an upstream request.
+pub fn gzip(body: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Default::default());
+    encoder.write_all(body)?;
+    encoder.finish()
+}
+
+/// The size the request bodies are decompressed up to, when they aren't
+/// limited otherwise.
+pub const MAX_DECOMPRESSED_SIZE: usize = 32 * 1024 * 1024;
+
+/// The request body can't be decompressed, which is answered with a 413 or a
+/// 415 rather than in the GraphQL response.
+#[derive(Debug, thiserror::Error)]
+pub enum DecompressError {
+    #[error("the decompressed body is larger than {0} bytes")]
+    TooLarge(usize),
+    #[error("the Content-Encoding `{0}` isn't supported")]
+    Unsupported(String),
+}
+
+/// Decodes the request body according to its `Content-Encoding`, stopping
+/// once it's larger than `max_size`. Gzip is only accepted when it's enabled
+/// for the responses.
+pub fn decompress(
+    headers: &HeaderMap,
+    body: Bytes,
+    compression: Option<&Compression>,
+    max_size: usize,
+) -> Result<Bytes> {
+    let encoding = headers
+        .get(CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_lowercase());
+
+    match encoding.as_deref() {
+        None | Some("identity") => Ok(body),
+        Some("gzip") if compression.is_some_and(|compression| compression.gzip) => {
+            let mut decoded = Vec::new();
+            GzDecoder::new(body.as_ref())
+                .take(max_size as u64 + 1)
+                .read_to_end(&mut decoded)?;
+            if decoded.len() > max_size {
+                return Err(DecompressError::TooLarge(max_size).into());
+            }
+            Ok(Bytes::from(decoded))
+        }
+        Some(encoding) => Err(DecompressError::Unsupported(encoding.to_string()).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compression() -> Compression {
+        Compression { gzip: true, brotli: true, min_size: 1024 }
+    }
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(value));
+        headers
+    }
+
+    fn json(size: usize) -> Response<Body> {
+        Response::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(vec![b'a'; size]))
+            .unwrap()
+    }
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(body).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let compression = compression();
+
+        assert_eq!(negotiate(&HeaderMap::new(), &compression), None);
+        assert_eq!(negotiate(&accept("identity"), &compression), None);
+        assert_eq!(
+            negotiate(&accept("gzip, deflate, br"), &compression),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(
+            negotiate(&accept("br;q=0.5, gzip"), &compression),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            negotiate(&accept("br;q=0, *"), &compression),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            negotiate(&accept("*"), &compression),
+            Some(Encoding::Brotli)
+        );
+
+        let gzip_only = Compression { brotli: false, ..compression };
+        assert_eq!(
+            negotiate(&accept("br, gzip;q=0.1"), &gzip_only),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(negotiate(&accept("br"), &gzip_only), None);
+    }
+
+    #[tokio::test]
+    async fn test_compress_threshold() {
+        let compression = compression();
+
+        let response = compress(json(1023), &compression, Some(Encoding::Gzip));
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+        assert!(!response.headers().contains_key(VARY));
+
+        let response = compress(json(1024), &compression, Some(Encoding::Gzip));
+        assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[VARY], "Accept-Encoding");
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let mut decoded = Vec::new();
+        GzDecoder::new(body.as_ref())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, vec![b'a'; 1024]);
+    }
+
+    #[tokio::test]
+    async fn test_compress_brotli() {
+        let response = compress(json(4096), &compression(), Some(Encoding::Brotli));
+        assert_eq!(response.headers()[CONTENT_ENCODING], "br");
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let mut decoded = Vec::new();
+        brotli::Decompressor::new(body.as_ref(), 4096)
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, vec![b'a'; 4096]);
+    }
+
+    #[test]
+    fn test_compress_skips_incompressible() {
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "image/png")
+            .body(Body::from(vec![0; 4096]))
+            .unwrap();
+        let response = compress(response, &compression(), Some(Encoding::Gzip));
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+
+        let response = Response::builder()
+            .header(CONTENT_TYPE, EVENT_STREAM)
+            .body(Body::from(vec![b'a'; 4096]))
+            .unwrap();
+        let response = compress(response, &compression(), Some(Encoding::Gzip));
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+
+        let response = compress(json(4096), &compression(), None);
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(response.headers()[VARY], "Accept-Encoding");
+    }
+
+    #[test]
+    fn test_decompress() {
+        let body = br#"{"query": "{ a }"}"#;
+        let compression = compression();
+
+        let headers = HeaderMap::new();
+        let decoded = decompress(&headers, Bytes::from_static(body), None, 1024).unwrap();
+        assert_eq!(decoded.as_ref(), body);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        let decoded =
+            decompress(&headers, Bytes::from(gzip(body)), Some(&compression), 1024).unwrap();
+        assert_eq!(decoded.as_ref(), body);
+
+        let error = decompress(&headers, Bytes::from(gzip(body)), None, 1024).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(DecompressError::Unsupported(_))
+        ));
+
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("zstd"));
+        let error =
+            decompress(&headers, Bytes::from_static(body), Some(&compression), 1024).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the Content-Encoding `zstd` isn't supported"
+        );
+    }
+
+    #[test]
+    fn test_decompress_max_size() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        let compression = compression();
+
+        let body = Bytes::from(gzip(&[b'a'; 1024]));
+        assert!(decompress(&headers, body.clone(), Some(&compression), 1024).is_ok());
+
+        let error = decompress(&headers, body, Some(&compression), 1023).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(DecompressError::TooLarge(1023))
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! Computes the values of `@server(context:)` once per request, before the
+//! query runs, so that every template can read them as `{{.ctx.<key>}}`.
+
+use anyhow::{anyhow, bail, Context};
+use async_graphql_value::ConstValue;
+use http::header::HeaderValue;
+use reqwest::Url;
+
+use crate::core::blueprint::{ContextEntry, ContextSource};
+use crate::core::http::RequestContext;
+use crate::core::ir::{EmptyResolverContext, EvalContext};
+
+/// Computes the values in order, so that a value can read the ones before it.
+/// A value that fails without a default fails the request.
+pub async fn compute(req_ctx: &mut RequestContext) -> anyhow::Result<()> {
+    let entries = req_ctx.server.context.clone();
+    for entry in entries {
+        let value = match compute_entry(&entry, req_ctx).await {
+            Ok(value) => value,
+            Err(err) => match &entry.default {
+                Some(default) => {
+                    tracing::warn!(
+                        "Using the default of the context value `{}`: {err}",
+                        entry.key
+                    );
+                    default.clone()
+                }
+                None => {
+                    return Err(err.context(format!(
+                        "The context value `{}` couldn't be computed",
+                        entry.key
+                    )))
+                }
+            },
+        };
+        req_ctx.context_values.insert(entry.key, value);
+    }
+    Ok(())
+}
+
+async fn compute_entry(
+    entry: &ContextEntry,
+    req_ctx: &RequestContext,
+) -> anyhow::Result<ConstValue> {
+    let value = match &entry.source {
+        ContextSource::Value(mustache) => {
+            let value = mustache.render(&EvalContext::new(req_ctx, &EmptyResolverContext {}));
+            if value.is_empty() {
+                bail!("the value is empty");
+            }
+            ConstValue::String(value)
+        }
+        ContextSource::Http { url, headers } => {
+            let request = {
+                let eval_ctx = EvalContext::new(req_ctx, &EmptyResolverContext {});
+                let url = url.render(&eval_ctx);
+                let url = Url::parse(&url).with_context(|| format!("invalid url `{url}`"))?;
+                let mut request = reqwest::Request::new(reqwest::Method::GET, url);
+                for (name, value) in headers {
+                    let value = HeaderValue::from_str(&value.render(&eval_ctx))?;
+                    request.headers_mut().insert(name.clone(), value);
+                }
+                request
+            };
+            let url = request.url().to_string();
+            let response = req_ctx.runtime.http.execute(request).await?;
+            if !response.status.is_success() {
+                bail!("{url} responded with {}", response.status);
+            }
+            response.to_json::<ConstValue>()?.body
+        }
+    };
+
+    match entry.transform {
+        Some(transform) => {
+            let ConstValue::String(value) = value else {
+                bail!("{transform:?} needs a string value");
+            };
+            transform
+                .apply(&value)
+                .map(ConstValue::String)
+                .ok_or_else(|| anyhow!("nothing is left of `{value}` after {transform:?}"))
+        }
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_graphql_value::ConstValue;
+    use http::header::{HeaderMap, HeaderValue};
+    use hyper::body::Bytes;
+    use serde_json::json;
+
+    use super::compute;
+    use crate::core::blueprint::Server;
+    use crate::core::config::{self, ConfigModule};
+    use crate::core::http::{RequestContext, Response};
+    use crate::core::HttpIO;
+
+    struct Flags;
+
+    #[async_trait::async_trait]
+    impl HttpIO for Flags {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let flags = match request.url().path() {
+                "/flags/42" => json!({"beta": true}),
+                _ => return Ok(Response::empty().status(reqwest::StatusCode::NOT_FOUND)),
+            };
+            Ok(Response::empty().body(Bytes::from(serde_json::to_vec(&flags)?)))
+        }
+    }
+
+    fn req_ctx(context: serde_json::Value, headers: &[(&str, &str)]) -> RequestContext {
+        let mut config = config::Config::default();
+        config.server.context = serde_json::from_value(context).unwrap();
+        let server = Server::try_from(ConfigModule::from(config)).unwrap();
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.http = Arc::new(Flags);
+        let allowed_headers = headers
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), HeaderValue::from_str(value).unwrap()))
+            .collect::<HeaderMap>();
+        RequestContext::new(runtime)
+            .server(server)
+            .allowed_headers(allowed_headers)
+    }
+
+    #[tokio::test]
+    async fn test_header_value() {
+        let mut req_ctx = req_ctx(
+            json!([
+                {"key": "locale", "value": "{{.headers.accept-language}}", "transform": "firstLanguageTag"},
+                {"key": "path", "value": "/{{.ctx.locale}}/home", "transform": "lowercase"},
+            ]),
+            &[("accept-language", "fr-CH, fr;q=0.9, en;q=0.8")],
+        );
+
+        compute(&mut req_ctx).await.unwrap();
+
+        assert_eq!(
+            req_ctx.context_values["locale"],
+            ConstValue::String("fr-CH".to_string())
+        );
+        assert_eq!(
+            req_ctx.context_values["path"],
+            ConstValue::String("/fr-ch/home".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_value() {
+        let mut req_ctx = req_ctx(
+            json!([{"key": "flags", "http": {"url": "http://flags/flags/{{.headers.x-user-id}}"}}]),
+            &[("x-user-id", "42")],
+        );
+
+        compute(&mut req_ctx).await.unwrap();
+
+        assert_eq!(
+            req_ctx.context_values["flags"],
+            ConstValue::from_json(json!({"beta": true})).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_value() {
+        let mut req_ctx = req_ctx(
+            json!([
+                {"key": "locale", "value": "{{.headers.accept-language}}", "default": "en"},
+                {"key": "flags", "http": {"url": "http://flags/flags/7"}, "default": {}},
+            ]),
+            &[],
+        );
+
+        compute(&mut req_ctx).await.unwrap();
+
+        assert_eq!(
+            req_ctx.context_values["locale"],
+            ConstValue::String("en".to_string())
+        );
+        assert_eq!(
+            req_ctx.context_values["flags"],
+            ConstValue::Object(Default::default())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fatal_value() {
+        let mut req_ctx = req_ctx(
+            json!([{"key": "flags", "http": {"url": "http://flags/flags/7"}}]),
+            &[],
+        );
+
+        let error = compute(&mut req_ctx).await.unwrap_err();
+
+        assert_eq!(
+            format!("{error:#}"),
+            "The context value `flags` couldn't be computed: http://flags/flags/7 responded with 404 Not Found"
+        );
+    }
+}
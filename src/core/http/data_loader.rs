@@ -5,18 +5,42 @@ use std::time::Duration;
 use async_graphql::async_trait;
 use async_graphql::futures_util::future::join_all;
 use async_graphql_value::ConstValue;
+use http::header::{HeaderValue, CONTENT_TYPE};
+use hyper::body::Bytes;
+use once_cell::sync::Lazy;
+use opentelemetry::metrics::Counter;
 use tailcall_valid::Validator;
 
 use super::transformations::{BodyBatching, QueryBatching};
+use crate::core::blueprint::DynamicValue;
 use crate::core::config::group_by::GroupBy;
-use crate::core::config::Batch;
+use crate::core::config::{Batch, ResponseFormat};
 use crate::core::data_loader::{DataLoader, Loader};
-use crate::core::http::{DataLoaderRequest, Response};
-use crate::core::json::JsonLike;
+use crate::core::http::{DataLoaderRequest, NoContent, Response, Signer};
+use crate::core::json::{key_string, JsonLike};
 use crate::core::runtime::TargetRuntime;
+use crate::core::serde_value_ext::ValueExt;
 use crate::core::transform::TransformerOps;
 use crate::core::Transform;
 
+static BATCH_KEY_MISS_COUNT: Lazy<Counter<u64>> = Lazy::new(|| {
+    let meter = opentelemetry::global::meter("http_data_loader");
+
+    meter
+        .u64_counter("http.batch.key.miss.count")
+        .with_description("Number of batched requests whose key is missing in the response")
+        .init()
+});
+
+/// Warns when the upstream didn't return an item for the key of a request,
+/// that resolves to `null` instead.
+fn check_key(body_value: &HashMap<String, Vec<&ConstValue>>, id: &str) {
+    if !body_value.contains_key(id) {
+        BATCH_KEY_MISS_COUNT.add(1, &[]);
+        tracing::warn!("Key {} is missing in the batch response", id);
+    }
+}
+
 fn get_body_value_single(body_value: &HashMap<String, Vec<&ConstValue>>, id: &str) -> ConstValue {
     body_value
         .get(id)
@@ -35,15 +59,108 @@ fn get_body_value_list(body_value: &HashMap<String, Vec<&ConstValue>>, id: &str)
     )
 }
 
+/// Builds a single request that sends the keys of all the requests in the
+/// body, in place of `{{.batch.keys}}`, as they were rendered for each request.
+/// The `key` query parameter that holds them, if any, is dropped from the
+/// batched request.
+fn batch_keys_request(
+    base_dl_request: &DataLoaderRequest,
+    dl_requests: &[DataLoaderRequest],
+    key: Option<&str>,
+    batch_body: &DynamicValue<ConstValue>,
+) -> anyhow::Result<reqwest::Request> {
+    let keys = dl_requests
+        .iter()
+        .filter_map(|dl_req| dl_req.batch_key().cloned())
+        .map(ConstValue::into_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    let body = batch_body.render_value(&serde_json::json!({ "batch": { "keys": keys } }));
+
+    let mut request = base_dl_request.to_request();
+    if let Some(key) = key {
+        let query = request
+            .url()
+            .query_pairs()
+            .filter(|(name, _)| name != key)
+            .map(|(name, value)| (name.into_owned(), value.into_owned()))
+            .collect::<Vec<_>>();
+        let url = request.url_mut();
+        url.set_query(None);
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+    }
+
+    request
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    *request.body_mut() = Some(serde_json::to_vec(&body)?.into());
+
+    Ok(request)
+}
+
 #[derive(Clone)]
 pub struct HttpDataLoader {
     pub runtime: TargetRuntime,
     pub group_by: Option<GroupBy>,
     is_list: bool,
+    verify_ssl: bool,
+    signer: Option<Signer>,
+    batch_body: Option<DynamicValue<ConstValue>>,
+    parse_response: ResponseFormat,
+    no_content: NoContent,
 }
 impl HttpDataLoader {
     pub fn new(runtime: TargetRuntime, group_by: Option<GroupBy>, is_list: bool) -> Self {
-        HttpDataLoader { runtime, group_by, is_list }
+        HttpDataLoader {
+            runtime,
+            group_by,
+            is_list,
+            verify_ssl: true,
+            signer: None,
+            batch_body: None,
+            parse_response: ResponseFormat::Json,
+            no_content: NoContent::Null,
+        }
+    }
+
+    /// Sends the requests without verifying the TLS certificates when `false`.
+    pub fn verify_ssl(self, verify_ssl: bool) -> Self {
+        Self { verify_ssl, ..self }
+    }
+
+    /// Signs the requests, including the batched ones, right before they're
+    /// sent.
+    pub fn signer(self, signer: Option<Signer>) -> Self {
+        Self { signer, ..self }
+    }
+
+    /// Sends the keys of the batched requests in this body, in place of
+    /// `{{.batch.keys}}`.
+    pub fn batch_body(self, batch_body: Option<DynamicValue<ConstValue>>) -> Self {
+        Self { batch_body, ..self }
+    }
+
+    /// Parses the responses in this format, see `@http(parseResponse:)`.
+    pub fn parse_response(self, parse_response: ResponseFormat) -> Self {
+        Self { parse_response, ..self }
+    }
+
+    /// Resolves the responses without content to this value, unless the
+    /// requests are batched.
+    pub fn no_content(self, no_content: NoContent) -> Self {
+        Self { no_content, ..self }
+    }
+
+    async fn execute(&self, mut request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+        if let Some(signer) = &self.signer {
+            signer.sign(&mut request)?;
+        }
+
+        self.runtime
+            .http_client(self.verify_ssl)
+            .execute(request)
+            .await
     }
 
     pub fn to_data_loader(self, batch: Batch) -> DataLoader<DataLoaderRequest, HttpDataLoader> {
@@ -63,18 +180,26 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
         keys: &[DataLoaderRequest],
     ) -> async_graphql::Result<HashMap<DataLoaderRequest, Self::Value>, Self::Error> {
         if let Some(group_by) = &self.group_by {
-            let query_name = group_by.key();
             let mut dl_requests = keys.to_vec();
             if cfg!(debug_assertions) {
                 // Sort keys to build consistent URLs only in Testing environment.
-                dl_requests.sort_by(|a, b| a.to_request().url().cmp(b.to_request().url()));
+                let batch_key = |req: &DataLoaderRequest| req.batch_key().map(ToString::to_string);
+                dl_requests.sort_by(|a, b| {
+                    a.to_request()
+                        .url()
+                        .cmp(b.to_request().url())
+                        .then_with(|| batch_key(a).cmp(&batch_key(b)))
+                });
             }
 
             if let Some(base_dl_request) = dl_requests.first().as_mut() {
-                let base_request = if base_dl_request.method() == http::Method::GET {
-                    QueryBatching::new(
+                let base_request = if let Some(batch_body) = &self.batch_body {
+                    let key = group_by.query_key();
+                    batch_keys_request(base_dl_request, &dl_requests, key, batch_body)?
+                } else if base_dl_request.method() == http::Method::GET {
+                    QueryBatching::with_keys(
                         &dl_requests.iter().skip(1).collect::<Vec<_>>(),
-                        Some(group_by.key()),
+                        &group_by.query_keys(),
                     )
                     .transform(base_dl_request.to_request())
                     .to_result()
@@ -89,21 +214,16 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
 
                 // Dispatch request
                 let res = self
-                    .runtime
-                    .http
                     .execute(base_request)
                     .await?
-                    .to_json::<ConstValue>()?;
+                    .to_value(self.parse_response)?;
 
                 // Create a response HashMap
                 #[allow(clippy::mutable_key_type)]
                 let mut hashmap = HashMap::with_capacity(dl_requests.len());
 
-                // Parse the response body and group it by batchKey
-                let path = &group_by.path();
-
                 // ResponseMap contains the response body grouped by the batchKey
-                let response_map = res.body.group_by(path);
+                let response_map = group_by.group(&res.body);
 
                 // depending on graphql type, it will extract the data out of the response.
                 let data_extractor = if self.is_list {
@@ -113,17 +233,32 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
                 };
 
                 // For each request and insert its corresponding value
-                if base_dl_request.method() == reqwest::Method::GET {
+                if self.batch_body.is_some() {
                     for dl_req in dl_requests.iter() {
-                        let url = dl_req.url();
-                        let query_set: HashMap<_, _> = url.query_pairs().collect();
-                        let id = query_set.get(query_name).ok_or(anyhow::anyhow!(
+                        let id = dl_req
+                            .batch_key()
+                            .and_then(key_string)
+                            .ok_or(anyhow::anyhow!(
+                                "Unable to find the batch key of the request {}",
+                                dl_req.url().as_str()
+                            ))?;
+                        check_key(&response_map, &id);
+
+                        let body = data_extractor(&response_map, &id);
+                        let res = res.clone().body(body);
+
+                        hashmap.insert(dl_req.clone(), res);
+                    }
+                } else if base_dl_request.method() == reqwest::Method::GET {
+                    for dl_req in dl_requests.iter() {
+                        let id = group_by.request_key(dl_req.url()).ok_or(anyhow::anyhow!(
                             "Unable to find key {} in query params",
-                            query_name
+                            group_by.query_keys().join(", ")
                         ))?;
+                        check_key(&response_map, &id);
 
                         // Clone the response and set the body
-                        let body = data_extractor(&response_map, id);
+                        let body = data_extractor(&response_map, &id);
                         let res = res.clone().body(body);
 
                         hashmap.insert(dl_req.clone(), res);
@@ -134,6 +269,7 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
                             "Unable to find batching value in the body for data loader request {}",
                             dl_req.url().as_str()
                         ))?;
+                        check_key(&response_map, body_key);
                         let extracted_value = data_extractor(&response_map, body_key);
                         let res = res.clone().body(extracted_value);
                         hashmap.insert(dl_req.clone(), res);
@@ -147,7 +283,7 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
             }
         } else {
             let results = keys.iter().map(|key| async {
-                let result = self.runtime.http.execute(key.to_request()).await;
+                let result = self.execute(key.to_request()).await;
                 (key.clone(), result)
             });
 
@@ -156,10 +292,207 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
             #[allow(clippy::mutable_key_type)]
             let mut hashmap = HashMap::with_capacity(results.len());
             for (key, value) in results {
-                hashmap.insert(key, value?.to_json()?);
+                let value = value?.to_field_value(self.parse_response, self.no_content)?;
+                hashmap.insert(key, value);
             }
 
             Ok(hashmap)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+    use std::sync::Mutex;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::config::group_by::CompositeKey;
+    use crate::core::HttpIO;
+
+    #[derive(Default)]
+    struct RecordingHttp {
+        requests: Mutex<Vec<reqwest::Request>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for RecordingHttp {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let body: serde_json::Value =
+                serde_json::from_slice(request.body().and_then(|b| b.as_bytes()).unwrap())?;
+            // Every user except the last one exists upstream
+            let users = body["ids"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|id| id.as_i64() != Some(10))
+                .map(|id| json!({"id": id, "name": format!("user {}", id)}))
+                .collect::<Vec<_>>();
+            self.requests.lock().unwrap().push(request);
+
+            Ok(Response::empty().body(Bytes::from(serde_json::to_vec(&users)?)))
+        }
+    }
+
+    struct TenantUsers {
+        requests: Mutex<Vec<reqwest::Request>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for TenantUsers {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            self.requests.lock().unwrap().push(request);
+            let users = json!([
+                {"tenantId": "a", "attributes": {"id": 1}, "name": "a1"},
+                {"tenantId": "b", "attributes": {"id": 1}, "name": "b1"},
+                {"tenantId": "a", "attributes": {"id": 2}, "name": "a2"}
+            ]);
+
+            Ok(Response::empty().body(Bytes::from(serde_json::to_vec(&users)?)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_composite_batch_keys() {
+        let http = Arc::new(TenantUsers { requests: Mutex::new(vec![]) });
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.http = http.clone();
+
+        let data_loader = HttpDataLoader::new(
+            runtime,
+            Some(GroupBy::composite(vec![
+                CompositeKey { path: "tenantId".to_string(), query: "tenant".to_string() },
+                CompositeKey { path: "attributes.id".to_string(), query: "id".to_string() },
+            ])),
+            false,
+        );
+
+        let requests = [("a", 1), ("b", 1), ("a", 2), ("b", 2)]
+            .iter()
+            .map(|(tenant, id)| {
+                let url = format!(
+                    "http://upstream/users?tenant={}&id={}&fields=all",
+                    tenant, id
+                );
+                let request = reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap());
+                DataLoaderRequest::new(request, BTreeSet::new())
+            })
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::mutable_key_type)]
+        let responses = data_loader.load(&requests).await.unwrap();
+
+        let sent = http.requests.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].url().as_str(),
+            "http://upstream/users?tenant=a&id=1&fields=all&tenant=a&id=2&tenant=b&id=1&tenant=b&id=2"
+        );
+
+        let user = |i: usize| responses[&requests[i]].body.clone();
+        let expected = |tenant: &str, id: i32, name: &str| {
+            ConstValue::from_json(
+                json!({"tenantId": tenant, "attributes": {"id": id}, "name": name}),
+            )
+            .unwrap()
+        };
+        assert_eq!(user(0), expected("a", 1, "a1"));
+        assert_eq!(user(1), expected("b", 1, "b1"));
+        assert_eq!(user(2), expected("a", 2, "a2"));
+        assert_eq!(user(3), ConstValue::Null);
+    }
+
+    #[tokio::test]
+    async fn test_batch_keys_in_body() {
+        let http = Arc::new(RecordingHttp::default());
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.http = http.clone();
+
+        let batch_body = DynamicValue::try_from(&json!({"ids": "{{.batch.keys}}"})).unwrap();
+        let data_loader = HttpDataLoader::new(
+            runtime,
+            Some(GroupBy::new(vec!["id".to_string()], Some("id".to_string()))),
+            false,
+        )
+        .batch_body(Some(batch_body));
+
+        let requests = (1..=10)
+            .map(|id| {
+                let url = format!("http://upstream/users:batch?id={}", id);
+                let request = reqwest::Request::new(reqwest::Method::POST, url.parse().unwrap());
+                DataLoaderRequest::new(request, BTreeSet::new())
+                    .with_batch_key(Some(ConstValue::Number(id.into())))
+            })
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::mutable_key_type)]
+        let responses = data_loader.load(&requests).await.unwrap();
+
+        let sent = http.requests.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].method(), reqwest::Method::POST);
+        assert_eq!(sent[0].url().as_str(), "http://upstream/users:batch");
+
+        let body: serde_json::Value =
+            serde_json::from_slice(sent[0].body().and_then(|b| b.as_bytes()).unwrap()).unwrap();
+        let ids = body["ids"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|id| id.as_i64().unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(ids, (1..=10).collect::<HashSet<_>>());
+
+        assert_eq!(
+            responses[&requests[0]].body,
+            ConstValue::from_json(json!({"id": 1, "name": "user 1"})).unwrap()
+        );
+        assert_eq!(responses[&requests[9]].body, ConstValue::Null);
+    }
+
+    #[tokio::test]
+    async fn test_batch_keys_in_body_only() {
+        let http = Arc::new(RecordingHttp::default());
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.http = http.clone();
+
+        let batch_body = DynamicValue::try_from(&json!({"ids": "{{.batch.keys}}"})).unwrap();
+        let data_loader = HttpDataLoader::new(
+            runtime,
+            Some(GroupBy::new(vec!["id".to_string()], None)),
+            false,
+        )
+        .batch_body(Some(batch_body));
+
+        let requests = ["007", "42"]
+            .into_iter()
+            .map(|id| {
+                let url = "http://upstream/users:batch?id=static".parse().unwrap();
+                let request = reqwest::Request::new(reqwest::Method::POST, url);
+                DataLoaderRequest::new(request, BTreeSet::new())
+                    .with_batch_key(Some(ConstValue::String(id.to_string())))
+            })
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::mutable_key_type)]
+        let responses = data_loader.load(&requests).await.unwrap();
+
+        let sent = http.requests.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].url().as_str(),
+            "http://upstream/users:batch?id=static"
+        );
+
+        let body: serde_json::Value =
+            serde_json::from_slice(sent[0].body().and_then(|b| b.as_bytes()).unwrap()).unwrap();
+        assert_eq!(body, json!({"ids": ["007", "42"]}));
+
+        assert_eq!(
+            responses[&requests[0]].body,
+            ConstValue::from_json(json!({"id": "007", "name": "user \"007\""})).unwrap()
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::BTreeSet;
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 
+use async_graphql_value::ConstValue;
 use tailcall_hasher::TailcallHasher;
 
 #[derive(Debug)]
@@ -10,12 +11,14 @@ pub struct DataLoaderRequest {
     headers: BTreeSet<String>,
     /// used for request body batching.
     batching_value: Option<String>,
+    /// The key of the request sent in `{{.batch.keys}}`, as it was rendered.
+    batch_key: Option<ConstValue>,
 }
 
 impl DataLoaderRequest {
     pub fn new(req: reqwest::Request, headers: BTreeSet<String>) -> Self {
         // TODO: req should already have headers builtin, no?
-        Self { request: req, headers, batching_value: None }
+        Self { request: req, headers, batching_value: None, batch_key: None }
     }
 
     pub fn with_batching_value(self, body: Option<String>) -> Self {
@@ -26,6 +29,14 @@ impl DataLoaderRequest {
         self.batching_value.as_ref()
     }
 
+    pub fn with_batch_key(self, batch_key: Option<ConstValue>) -> Self {
+        Self { batch_key, ..self }
+    }
+
+    pub fn batch_key(&self) -> Option<&ConstValue> {
+        self.batch_key.as_ref()
+    }
+
     pub fn to_request(&self) -> reqwest::Request {
         // TODO: excessive clone for the whole structure instead of cloning only part of
         // it check if we really need to clone anything at all or just pass
@@ -45,6 +56,10 @@ impl Hash for DataLoaderRequest {
         if let Some(body) = self.request.body() {
             body.as_bytes().hash(state);
         }
+        // the requests batched in the body can differ only by their keys
+        if let Some(batch_key) = &self.batch_key {
+            batch_key.to_string().hash(state);
+        }
         for name in &self.headers {
             if let Some(value) = self.request.headers().get(name) {
                 name.hash(state);
@@ -81,6 +96,7 @@ impl Clone for DataLoaderRequest {
 
         DataLoaderRequest::new(req, self.headers.clone())
             .with_batching_value(self.batching_value.clone())
+            .with_batch_key(self.batch_key.clone())
     }
 }
 
@@ -0,0 +1,169 @@
+//! Tags the responses with a hash of their body and answers the requests whose
+//! `If-None-Match` already has that tag with an empty `304 Not Modified`.
+
+use anyhow::Result;
+use hyper::header::{HeaderMap, HeaderValue, CONTENT_LENGTH, ETAG, IF_NONE_MATCH};
+use hyper::{Body, Response, StatusCode};
+use sha2::{Digest, Sha256};
+
+/// The strong tag of a body, the quoted hex of its SHA-256.
+pub fn tag(body: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(Sha256::digest(body)))
+}
+
+/// Whether `If-None-Match` has the tag, compared weakly as the spec requires
+/// for conditional `GET`s, or is `*`.
+pub fn matches(headers: &HeaderMap, tag: &str) -> bool {
+    let tag = weak(tag);
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || weak(candidate) == tag)
+}
+
+fn weak(tag: &str) -> &str {
+    tag.strip_prefix("W/").unwrap_or(tag)
+}
+
+/// Marks the tag of a response as weak, since the compressed body isn't the
+/// one it was computed from.
+pub fn weaken(headers: &mut HeaderMap) {
+    let weakened = headers
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.starts_with("W/"))
+        .and_then(|value| HeaderValue::from_str(&format!("W/{value}")).ok());
+
+    if let Some(value) = weakened {
+        headers.insert(ETAG, value);
+    }
+}
+
+/// Tags a successful response and, when the client already has its body,
+/// replaces it with an empty `304 Not Modified` that keeps the headers.
+pub async fn apply(
+    request_headers: &HeaderMap,
+    response: Response<Body>,
+) -> Result<Response<Body>> {
+    if response.status() != StatusCode::OK {
+        return Ok(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = hyper::body::to_bytes(body).await?;
+    let tag = tag(&body);
+    parts.headers.insert(ETAG, HeaderValue::from_str(&tag)?);
+
+    if matches(request_headers, &tag) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(CONTENT_LENGTH);
+        return Ok(Response::from_parts(parts, Body::empty()));
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::CONTENT_TYPE;
+
+    use super::*;
+
+    fn if_none_match(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_static(value));
+        headers
+    }
+
+    fn response(status: StatusCode, body: &'static str) -> Response<Body> {
+        Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tag() {
+        assert_eq!(tag(b"{}"), tag(b"{}"));
+        assert_ne!(tag(b"{}"), tag(b"[]"));
+        assert!(tag(b"{}").starts_with('"') && tag(b"{}").ends_with('"'));
+    }
+
+    #[test]
+    fn test_matches() {
+        let tag = tag(b"{}");
+        let list = format!("\"other\", W/{tag}");
+
+        assert!(matches(
+            &HeaderMap::from_iter([(IF_NONE_MATCH, HeaderValue::from_str(&tag).unwrap())]),
+            &tag
+        ));
+        assert!(matches(
+            &HeaderMap::from_iter([(IF_NONE_MATCH, HeaderValue::from_str(&list).unwrap())]),
+            &tag
+        ));
+        assert!(matches(&if_none_match("*"), &tag));
+        assert!(!matches(&if_none_match("\"other\""), &tag));
+        assert!(!matches(&HeaderMap::new(), &tag));
+    }
+
+    #[test]
+    fn test_weaken() {
+        let mut headers = HeaderMap::from_iter([(ETAG, HeaderValue::from_static("\"abc\""))]);
+        weaken(&mut headers);
+        weaken(&mut headers);
+
+        assert_eq!(headers.get(ETAG).unwrap(), "W/\"abc\"");
+    }
+
+    #[tokio::test]
+    async fn test_apply_tags_the_response() {
+        let response = apply(&HeaderMap::new(), response(StatusCode::OK, "{}"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(ETAG).unwrap(), tag(b"{}").as_str());
+        assert_eq!(
+            hyper::body::to_bytes(response.into_body()).await.unwrap(),
+            "{}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_not_modified() {
+        let tag = tag(b"{}");
+        let headers = HeaderMap::from_iter([(IF_NONE_MATCH, HeaderValue::from_str(&tag).unwrap())]);
+        let response = apply(&headers, response(StatusCode::OK, "{}"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(ETAG).unwrap(), tag.as_str());
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert!(hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_apply_skips_errors() {
+        let response = apply(
+            &if_none_match("*"),
+            response(StatusCode::INTERNAL_SERVER_ERROR, "{}"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.headers().get(ETAG).is_none());
+    }
+}
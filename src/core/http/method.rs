@@ -27,6 +27,20 @@ pub enum Method {
 }
 
 impl Method {
+    /// Whether sending the request more than once has the effect of sending
+    /// it once, so that it can be retried.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            Method::GET
+                | Method::PUT
+                | Method::DELETE
+                | Method::HEAD
+                | Method::OPTIONS
+                | Method::TRACE
+        )
+    }
+
     pub fn to_hyper(self) -> http::Method {
         match self {
             Method::GET => http::Method::GET,
@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use futures_timer::Delay;
+use http::header::{HeaderName, HeaderValue};
+use hyper::body::Bytes;
+use serde::{Deserialize, Serialize};
+
+use super::{Method, Response, StatusError};
+use crate::core::HttpIO;
+
+/// Describes how the mock table is consulted before performing network IO.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MockMode {
+    /// Every upstream request must be served by a mock. Requests without a
+    /// matching mock fail.
+    #[default]
+    Strict,
+    /// Requests without a matching mock are sent to the real upstream.
+    Partial,
+}
+
+impl FromStr for MockMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(MockMode::Strict),
+            "partial" => Ok(MockMode::Partial),
+            _ => Err(anyhow!(
+                "Unknown mock mode `{s}`, expected `strict` or `partial`"
+            )),
+        }
+    }
+}
+
+/// Matches an upstream request against a mock entry.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MockRequest {
+    /// HTTP method of the request. Any method matches when not set.
+    #[serde(default)]
+    pub method: Option<Method>,
+
+    /// Path of the request. Segments can be replaced with `*` or `:name` to
+    /// match any value, and a trailing `**` matches the remaining segments.
+    /// Patterns that contain `://` are matched against the whole URL.
+    pub path: String,
+
+    /// Query parameters that must be present in the request.
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+
+    /// JSON body the request must be equal to.
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+}
+
+/// Canned response returned for a matching request.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MockResponse {
+    #[serde(default = "default_status")]
+    pub status: u16,
+
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub body: serde_json::Value,
+
+    /// Delay in milliseconds before the response is returned.
+    #[serde(default)]
+    pub latency: Option<u64>,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+/// An entry of the mock table linked with `@link(type: Mocks)`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Mock {
+    pub request: MockRequest,
+    pub response: MockResponse,
+}
+
+impl Mock {
+    fn matches(&self, request: &reqwest::Request) -> bool {
+        let expected = &self.request;
+
+        if let Some(method) = &expected.method {
+            if method.clone().to_hyper() != *request.method() {
+                return false;
+            }
+        }
+
+        let url = request.url();
+        let target = if expected.path.contains("://") {
+            let mut url = url.clone();
+            url.set_query(None);
+            url.to_string()
+        } else {
+            url.path().to_string()
+        };
+
+        if !path_matches(&expected.path, &target) {
+            return false;
+        }
+
+        let query = url.query_pairs().collect::<Vec<_>>();
+        let query_matches = expected
+            .query
+            .iter()
+            .all(|(k, v)| query.iter().any(|(qk, qv)| qk == k && qv == v));
+        if !query_matches {
+            return false;
+        }
+
+        match &expected.body {
+            Some(expected_body) => request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<serde_json::Value>(body).ok())
+                .is_some_and(|body| &body == expected_body),
+            None => true,
+        }
+    }
+}
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.trim_end_matches('/').split('/');
+    let mut path = path.trim_end_matches('/').split('/');
+
+    loop {
+        match (pattern.next(), path.next()) {
+            (Some("**"), _) => return true,
+            (Some(expected), Some(actual)) => {
+                let is_wildcard = expected == "*" || expected.starts_with(':');
+                if !is_wildcard && expected != actual {
+                    return false;
+                }
+            }
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// The mocks loaded from the linked files together with the mode they are
+/// served in.
+#[derive(Clone, Debug, Default)]
+pub struct MockTable {
+    pub mode: MockMode,
+    pub mocks: Vec<Mock>,
+}
+
+/// Serves upstream requests from a mock table before (or instead of)
+/// performing real network IO.
+pub struct MockHttp {
+    table: MockTable,
+    inner: Arc<dyn HttpIO>,
+}
+
+impl MockHttp {
+    pub fn new(table: MockTable, inner: Arc<dyn HttpIO>) -> Self {
+        Self { table, inner }
+    }
+
+    fn find(&self, request: &reqwest::Request) -> Option<&Mock> {
+        self.table.mocks.iter().find(|mock| mock.matches(request))
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpIO for MockHttp {
+    async fn execute(&self, request: reqwest::Request) -> Result<Response<Bytes>> {
+        let Some(mock) = self.find(&request) else {
+            return match self.table.mode {
+                MockMode::Partial => self.inner.execute(request).await,
+                MockMode::Strict => Err(anyhow!(
+                    "No mock found for upstream request {} {}",
+                    request.method(),
+                    request.url()
+                )),
+            };
+        };
+
+        let response = &mock.response;
+        if let Some(latency) = response.latency {
+            Delay::new(Duration::from_millis(latency)).await;
+        }
+
+        let mut headers = http::HeaderMap::new();
+        for (key, value) in response.headers.iter() {
+            headers.insert(
+                HeaderName::from_bytes(key.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+        if !headers.contains_key(http::header::CONTENT_TYPE) {
+            headers.insert(
+                http::header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+        }
+
+        let status = reqwest::StatusCode::from_u16(response.status)?;
+        let body = Bytes::from(serde_json::to_vec(&response.body)?);
+        if status.is_client_error() || status.is_server_error() {
+            // Fails like the real client does, keeping the status and the body
+            // so that they can be mapped by `@http(onStatus:)`.
+            let mut response = http::Response::new(body);
+            *response.status_mut() = status;
+            *response.headers_mut() = headers;
+            return Response::from_reqwest(StatusError::check(response.into()).await?).await;
+        }
+
+        Ok(Response { status, headers, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    struct NetworkHttp;
+
+    #[async_trait::async_trait]
+    impl HttpIO for NetworkHttp {
+        async fn execute(&self, _: reqwest::Request) -> Result<Response<Bytes>> {
+            Ok(Response::empty().body(Bytes::from_static(b"\"network\"")))
+        }
+    }
+
+    fn mocks() -> Vec<Mock> {
+        serde_json::from_value(json!([
+            {
+                "request": {"method": "GET", "path": "/users/1"},
+                "response": {"body": {"id": 1}}
+            },
+            {
+                "request": {"method": "GET", "path": "/users/:id/posts"},
+                "response": {"status": 201, "body": [{"id": 2}]}
+            },
+            {
+                "request": {"path": "/search", "query": {"q": "tailcall"}},
+                "response": {"body": "found", "latency": 50}
+            },
+            {
+                "request": {"method": "POST", "path": "/users", "body": {"name": "foo"}},
+                "response": {"body": {"id": 3}}
+            },
+            {
+                "request": {"path": "/users/2"},
+                "response": {"status": 404, "body": {"message": "not found"}}
+            }
+        ]))
+        .unwrap()
+    }
+
+    fn get(url: &str) -> reqwest::Request {
+        reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap())
+    }
+
+    fn mock_http(mode: MockMode) -> MockHttp {
+        MockHttp::new(MockTable { mode, mocks: mocks() }, Arc::new(NetworkHttp))
+    }
+
+    #[tokio::test]
+    async fn test_exact_match() {
+        let response = mock_http(MockMode::Strict)
+            .execute(get("http://localhost/users/1"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, Bytes::from_static(b"{\"id\":1}"));
+    }
+
+    #[tokio::test]
+    async fn test_pattern_match() {
+        let response = mock_http(MockMode::Strict)
+            .execute(get("http://localhost/users/42/posts"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 201);
+        assert_eq!(response.body, Bytes::from_static(b"[{\"id\":2}]"));
+    }
+
+    #[tokio::test]
+    async fn test_body_match() {
+        let mut request = reqwest::Request::new(
+            reqwest::Method::POST,
+            "http://localhost/users".parse().unwrap(),
+        );
+        request.body_mut().replace(r#"{"name":"foo"}"#.into());
+
+        let response = mock_http(MockMode::Strict).execute(request).await.unwrap();
+
+        assert_eq!(response.body, Bytes::from_static(b"{\"id\":3}"));
+    }
+
+    #[tokio::test]
+    async fn test_latency_injection() {
+        let start = std::time::Instant::now();
+        let response = mock_http(MockMode::Strict)
+            .execute(get("http://localhost/search?q=tailcall&page=1"))
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(response.body, Bytes::from_static(b"\"found\""));
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let error = mock_http(MockMode::Strict)
+            .execute(get("http://localhost/users/2"))
+            .await
+            .unwrap_err();
+        let error = error.downcast_ref::<StatusError>().unwrap();
+
+        assert!(error.to_string().contains("404 Not Found"));
+        assert_eq!(
+            error.body,
+            Bytes::from_static(b"{\"message\":\"not found\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strict_miss() {
+        let error = mock_http(MockMode::Strict)
+            .execute(get("http://localhost/search?q=other"))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "No mock found for upstream request GET http://localhost/search?q=other"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partial_miss_falls_through() {
+        let response = mock_http(MockMode::Partial)
+            .execute(get("http://localhost/unknown"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.body, Bytes::from_static(b"\"network\""));
+    }
+
+    #[test]
+    fn test_mock_mode_from_str() {
+        assert_eq!(MockMode::from_str("strict").unwrap(), MockMode::Strict);
+        assert_eq!(MockMode::from_str("Partial").unwrap(), MockMode::Partial);
+        assert!(MockMode::from_str("other").is_err());
+    }
+}
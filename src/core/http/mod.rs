@@ -1,26 +1,52 @@
 pub use cache::*;
+pub use cassette::{
+    Cassette, CassetteHttp, CassetteRecorder, Interaction, TAILCALL_RECORD, TAILCALL_REPLAY,
+};
+pub use circuit_breaker::{
+    is_failure, CircuitBreaker, CircuitBreakers, CircuitPermit, CircuitState,
+};
 pub use data_loader::*;
 pub use data_loader_request::*;
 use http::HeaderValue;
 pub use method::Method;
+pub use mock::{Mock, MockHttp, MockMode, MockRequest, MockResponse, MockTable};
+pub use multipart::{Upload, Uploads};
+pub use paginate::{NextPage, Pages, Paginate, DEFAULT_MAX_PAGES};
 pub use query_encoder::QueryEncoder;
 pub use request_context::RequestContext;
 pub use request_handler::{handle_request, API_URL_PREFIX};
-pub use request_template::RequestTemplate;
+pub use request_id::{RequestId, X_REQUEST_ID};
+pub use request_template::{BasicCredentials, Query, RequestTemplate};
 pub use response::*;
+pub use signing::Signer;
+pub use status_codes::{StatusCodes, UpstreamResponse};
 
 mod cache;
+mod cassette;
+mod circuit_breaker;
+mod compression;
+mod context_values;
 mod data_loader;
 mod data_loader_request;
+mod etag;
 mod method;
+mod mock;
+mod multipart;
+mod operation_info;
+mod paginate;
+mod playground;
 mod query_encoder;
 mod request_context;
 mod request_handler;
+mod request_id;
 mod request_template;
 mod response;
 pub mod showcase;
+mod signing;
+mod sse;
 mod telemetry;
 mod transformations;
+mod xml;
 
 pub static TAILCALL_HTTPS_ORIGIN: HeaderValue = HeaderValue::from_static("https://tailcall.run");
 pub static TAILCALL_HTTP_ORIGIN: HeaderValue = HeaderValue::from_static("http://tailcall.run");
@@ -0,0 +1,575 @@
+//! Reads the requests of the [GraphQL multipart request spec](https://github.com/jaydenseric/graphql-multipart-request-spec),
+//! used to upload files. The body is made of an `operations` part with the
+//! JSON request, a `map` part that tells which variables hold which file, and
+//! a part for each file. The files are then sent to the upstreams as the
+//! body of a request or as the parts of a `multipart/form-data` body.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context};
+use hyper::body::{Bytes, HttpBody};
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, HeaderMap};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::core::blueprint::UploadLimits;
+use crate::core::json::JsonLike;
+
+/// A file sent with the request. Its body is a slice of the request body, so
+/// it's passed on to the upstreams without being copied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Upload {
+    pub filename: String,
+    pub mime_type: String,
+    pub body: Bytes,
+}
+
+/// The files sent with a request, by the `id` of their `Upload` value. The ids
+/// are random, so that the values a client writes itself, eg. an input with
+/// an `id` field, are never taken for a file.
+#[derive(Clone, Debug, Default)]
+pub struct Uploads(HashMap<String, Upload>);
+
+impl Uploads {
+    pub fn get(&self, id: &str) -> Option<&Upload> {
+        self.0.get(id)
+    }
+
+    /// The file an `Upload` value refers to.
+    pub fn find<'a, V: JsonLike<'a>>(&self, value: &'a V) -> Option<&Upload> {
+        self.get(value.get_key("id")?.as_str()?)
+    }
+}
+
+impl FromIterator<(String, Upload)> for Uploads {
+    fn from_iter<T: IntoIterator<Item = (String, Upload)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// The room left for the `operations` and `map` parts and the headers of the
+/// parts, on top of the files the limits allow.
+const MAX_OVERHEAD: usize = 1024 * 1024;
+
+/// The request is larger than the upload limits allow, which is answered
+/// with a 413.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct TooLarge(String);
+
+/// The largest multipart request the limits allow.
+pub fn max_size(limits: &UploadLimits) -> usize {
+    limits
+        .max_files
+        .saturating_mul(limits.max_file_size)
+        .saturating_add(MAX_OVERHEAD)
+}
+
+/// Reads the body of a multipart request, stopping as soon as it's larger
+/// than the files the limits allow, so that it's never buffered whole.
+pub async fn read_body(mut body: Body, limits: &UploadLimits) -> anyhow::Result<Bytes> {
+    let max_size = max_size(limits);
+    let too_large = || TooLarge(format!("the request is larger than {max_size} bytes"));
+    if body.size_hint().lower() > max_size as u64 {
+        return Err(too_large().into());
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > max_size {
+            return Err(too_large().into());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(bytes))
+}
+
+/// The boundary of the parts, when the request is `multipart/form-data`.
+pub fn boundary(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    let mime = content_type.parse::<mime::Mime>().ok()?;
+    if mime.type_() != mime::MULTIPART || mime.subtype() != mime::FORM_DATA {
+        return None;
+    }
+    Some(mime.get_param(mime::BOUNDARY)?.to_string())
+}
+
+/// The JSON `operations` of the request, with each file replaced by its
+/// metadata, eg. `{"id": "<uuid>", "filename": "a.png", "mimeType":
+/// "image/png", "size": 42}`, which is the value of the `Upload` scalar, and
+/// the files.
+pub fn operations(
+    body: &Bytes,
+    boundary: &str,
+    limits: &UploadLimits,
+) -> anyhow::Result<(Bytes, Uploads)> {
+    let mut parts = parts(body, boundary)?.into_iter();
+
+    let operations = parts
+        .next()
+        .filter(|part| part.name == "operations")
+        .context("the first part must be `operations`")?;
+    let mut operations: Value = serde_json::from_slice(operations.body)?;
+
+    let map = parts
+        .next()
+        .filter(|part| part.name == "map")
+        .context("the second part must be `map`")?;
+    let map: Map<String, Value> = serde_json::from_slice(map.body)?;
+
+    if parts.len() > limits.max_files {
+        return Err(TooLarge(format!(
+            "the request has more than {} files",
+            limits.max_files
+        ))
+        .into());
+    }
+
+    let mut uploads = HashMap::new();
+    for file in parts {
+        let paths = map
+            .get(file.name)
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("the file `{}` is missing from `map`", file.name))?;
+        if file.body.len() > limits.max_file_size {
+            return Err(TooLarge(format!(
+                "the file `{}` is larger than {} bytes",
+                file.name, limits.max_file_size
+            ))
+            .into());
+        }
+        let upload = Upload {
+            filename: file.filename.unwrap_or_default().to_string(),
+            mime_type: file
+                .content_type
+                .unwrap_or("application/octet-stream")
+                .to_string(),
+            body: body.slice_ref(file.body),
+        };
+        let id = Uuid::new_v4().to_string();
+        let value = json!({
+            "id": id,
+            "filename": upload.filename,
+            "mimeType": upload.mime_type,
+            "size": upload.body.len(),
+        });
+        for path in paths {
+            let path = path
+                .as_str()
+                .context("the paths of `map` must be strings")?;
+            *value_at(&mut operations, path)? = value.clone();
+        }
+        uploads.insert(id, upload);
+    }
+
+    Ok((
+        Bytes::from(serde_json::to_vec(&operations)?),
+        Uploads(uploads),
+    ))
+}
+
+/// Encodes the object as a `multipart/form-data` body with a part for each
+/// key. The `Upload` values are sent as their files, the strings as they are
+/// and the other values as JSON, while the nulls are left out. Returns the
+/// `Content-Type` with the boundary, which is picked so that no part has it.
+pub fn form_data(fields: &Map<String, Value>, uploads: &Uploads) -> (String, Vec<u8>) {
+    let parts = fields
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(name, value)| {
+            let name = name.replace('"', "%22");
+            match uploads.find(value) {
+                Some(upload) => (
+                    format!(
+                        "Content-Disposition: form-data; name=\"{name}\"; filename=\"{}\"\r\nContent-Type: {}",
+                        upload.filename.replace('"', "%22"),
+                        upload.mime_type
+                    ),
+                    upload.body.clone(),
+                ),
+                None => (
+                    format!("Content-Disposition: form-data; name=\"{name}\""),
+                    Bytes::from(match value {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    }),
+                ),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let boundary = (0..)
+        .map(|n| format!("tailcall-boundary-{n}"))
+        .find(|boundary| {
+            parts
+                .iter()
+                .all(|(_, body)| find(body, boundary.as_bytes()).is_none())
+        })
+        .unwrap_or_default();
+
+    let mut body = vec![];
+    for (headers, content) in parts {
+        body.extend_from_slice(format!("--{boundary}\r\n{headers}\r\n\r\n").as_bytes());
+        body.extend_from_slice(&content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+struct Part<'a> {
+    name: &'a str,
+    filename: Option<&'a str>,
+    content_type: Option<&'a str>,
+    body: &'a [u8],
+}
+
+impl<'a> Part<'a> {
+    fn parse(part: &'a [u8]) -> anyhow::Result<Self> {
+        let end = find(part, b"\r\n\r\n").context("a part has no headers")?;
+        let headers = std::str::from_utf8(&part[..end])?;
+        let body = &part[end + 4..];
+
+        let mut name = None;
+        let mut filename = None;
+        let mut content_type = None;
+        for header in headers.split("\r\n") {
+            let Some((key, value)) = header.split_once(':') else {
+                continue;
+            };
+            if key.eq_ignore_ascii_case("content-disposition") {
+                for param in params(value).into_iter().skip(1) {
+                    match param.trim().split_once('=') {
+                        Some(("name", value)) => name = Some(value.trim_matches('"')),
+                        Some(("filename", value)) => filename = Some(value.trim_matches('"')),
+                        _ => {}
+                    }
+                }
+            } else if key.eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.trim());
+            }
+        }
+
+        let name = name.context("a part has no name")?;
+        Ok(Self { name, filename, content_type, body })
+    }
+}
+
+/// The parameters of a header value separated by `;`, eg. `form-data;
+/// name="file"; filename="a;b.txt"`, leaving the `;` of the quoted strings
+/// alone.
+fn params(value: &str) -> Vec<&str> {
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut params = vec![];
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                params.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&value[start..]);
+    params
+}
+
+fn parts<'a>(body: &'a [u8], boundary: &str) -> anyhow::Result<Vec<Part<'a>>> {
+    let delimiter = format!("--{boundary}");
+    let start = find(body, delimiter.as_bytes()).context("the body has no parts")?;
+    let mut rest = &body[start + delimiter.len()..];
+    let delimiter = format!("\r\n{delimiter}");
+
+    let mut parts = vec![];
+    while !rest.starts_with(b"--") {
+        rest = rest
+            .strip_prefix(b"\r\n")
+            .context("a delimiter isn't followed by a new line")?;
+        let end = find(rest, delimiter.as_bytes()).context("the last part isn't closed")?;
+        parts.push(Part::parse(&rest[..end])?);
+        rest = &rest[end + delimiter.len()..];
+    }
+
+    Ok(parts)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// The value at a path of `map`, eg. `variables.files.0`, or
+/// `0.variables.file` in a batch.
+fn value_at<'a>(value: &'a mut Value, path: &str) -> anyhow::Result<&'a mut Value> {
+    let mut value = value;
+    for key in path.split('.') {
+        value = match value {
+            Value::Object(object) => object.get_mut(key),
+            Value::Array(list) => key.parse::<usize>().ok().and_then(|i| list.get_mut(i)),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("`{path}` of `map` isn't in `operations`"))?;
+    }
+    if !value.is_null() {
+        bail!("`{path}` of `map` must be null in `operations`");
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::HeaderValue;
+
+    use super::*;
+
+    fn body(parts: &[(&str, &str)]) -> Bytes {
+        let mut body = String::new();
+        for (headers, content) in parts {
+            body.push_str(&format!("--XYZ\r\n{headers}\r\n\r\n{content}\r\n"));
+        }
+        body.push_str("--XYZ--\r\n");
+        Bytes::from(body)
+    }
+
+    fn limits() -> UploadLimits {
+        UploadLimits { max_file_size: 16, max_files: 2 }
+    }
+
+    fn single_file(content: &str) -> Bytes {
+        body(&[
+            (
+                r#"Content-Disposition: form-data; name="operations""#,
+                r#"{"query": "q", "variables": {"file": null}}"#,
+            ),
+            (
+                r#"Content-Disposition: form-data; name="map""#,
+                r#"{"0": ["variables.file"]}"#,
+            ),
+            (
+                "Content-Disposition: form-data; name=\"0\"; filename=\"a.txt\"",
+                content,
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_boundary() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=XYZ"),
+        );
+        assert_eq!(boundary(&headers), Some("XYZ".to_string()));
+
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        assert_eq!(boundary(&headers), None);
+    }
+
+    #[test]
+    fn test_operations() {
+        let body = body(&[
+            (
+                r#"Content-Disposition: form-data; name="operations""#,
+                r#"{"query": "mutation ($files: [Upload!]!) { upload(files: $files) }", "variables": {"files": [null, null]}}"#,
+            ),
+            (
+                r#"Content-Disposition: form-data; name="map""#,
+                r#"{"0": ["variables.files.0"], "1": ["variables.files.1"]}"#,
+            ),
+            (
+                "Content-Disposition: form-data; name=\"0\"; filename=\"a.txt\"\r\nContent-Type: text/plain",
+                "Alpha file",
+            ),
+            (
+                "Content-Disposition: form-data; name=\"1\"; filename=\"b.png\"\r\nContent-Type: image/png",
+                "\u{89}PNG",
+            ),
+        ]);
+
+        let (actual, uploads) = operations(&body, "XYZ", &limits()).unwrap();
+        let actual: Value = serde_json::from_slice(&actual).unwrap();
+
+        let files = &actual["variables"]["files"];
+        let expected = json!({
+            "query": "mutation ($files: [Upload!]!) { upload(files: $files) }",
+            "variables": {"files": [
+                {"id": files[0]["id"], "filename": "a.txt", "mimeType": "text/plain", "size": 10},
+                {"id": files[1]["id"], "filename": "b.png", "mimeType": "image/png", "size": 5},
+            ]}
+        });
+        assert_eq!(actual, expected);
+        assert_ne!(files[0]["id"], files[1]["id"]);
+        assert!(uploads.find(&json!({"id": "0"})).is_none());
+
+        let upload = uploads.find(&actual["variables"]["files"][1]).unwrap();
+        assert_eq!(upload.filename, "b.png");
+        assert_eq!(upload.mime_type, "image/png");
+        assert_eq!(upload.body, Bytes::from("\u{89}PNG"));
+    }
+
+    #[test]
+    fn test_batch_operations() {
+        let body = body(&[
+            (
+                r#"Content-Disposition: form-data; name="operations""#,
+                r#"[{"query": "q", "variables": {"file": null}}, {"query": "q", "variables": {"file": null}}]"#,
+            ),
+            (
+                r#"Content-Disposition: form-data; name="map""#,
+                r#"{"0": ["0.variables.file", "1.variables.file"]}"#,
+            ),
+            (
+                "Content-Disposition: form-data; name=\"0\"; filename=\"a.txt\"",
+                "a",
+            ),
+        ]);
+
+        let (actual, _) = operations(&body, "XYZ", &limits()).unwrap();
+        let actual: Value = serde_json::from_slice(&actual).unwrap();
+
+        let id = &actual[0]["variables"]["file"]["id"];
+        let file = json!({"id": id, "filename": "a.txt", "mimeType": "application/octet-stream", "size": 1});
+        let expected = json!([
+            {"query": "q", "variables": {"file": file}},
+            {"query": "q", "variables": {"file": file}},
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_quoted_params() {
+        let part =
+            "Content-Disposition: form-data; name=\"0\"; filename=\"a;b \\\"c\\\".txt\"\r\n\r\na";
+
+        let part = Part::parse(part.as_bytes()).unwrap();
+
+        assert_eq!(part.name, "0");
+        assert_eq!(part.filename, Some(r#"a;b \"c\".txt"#));
+    }
+
+    #[test]
+    fn test_invalid_map() {
+        let body = body(&[
+            (
+                r#"Content-Disposition: form-data; name="operations""#,
+                r#"{"query": "q", "variables": {}}"#,
+            ),
+            (
+                r#"Content-Disposition: form-data; name="map""#,
+                r#"{"0": ["variables.file"]}"#,
+            ),
+            (
+                "Content-Disposition: form-data; name=\"0\"; filename=\"a.txt\"",
+                "a",
+            ),
+        ]);
+
+        let error = operations(&body, "XYZ", &limits()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "`variables.file` of `map` isn't in `operations`"
+        );
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        assert!(operations(&single_file("0123456789abcdef"), "XYZ", &limits()).is_ok());
+
+        let error = operations(&single_file("0123456789abcdefg"), "XYZ", &limits()).unwrap_err();
+
+        assert_eq!(error.to_string(), "the file `0` is larger than 16 bytes");
+        assert!(error.is::<TooLarge>());
+    }
+
+    #[test]
+    fn test_max_files() {
+        let file = |name: &str| format!("Content-Disposition: form-data; name=\"{name}\"");
+        let body = body(&[
+            (
+                r#"Content-Disposition: form-data; name="operations""#,
+                r#"{"query": "q", "variables": {"files": [null, null, null]}}"#,
+            ),
+            (
+                r#"Content-Disposition: form-data; name="map""#,
+                r#"{"0": ["variables.files.0"], "1": ["variables.files.1"], "2": ["variables.files.2"]}"#,
+            ),
+            (file("0").as_str(), "a"),
+            (file("1").as_str(), "b"),
+            (file("2").as_str(), "c"),
+        ]);
+
+        let error = operations(&body, "XYZ", &limits()).unwrap_err();
+
+        assert_eq!(error.to_string(), "the request has more than 2 files");
+        assert!(error.is::<TooLarge>());
+    }
+
+    #[tokio::test]
+    async fn test_read_body() {
+        let max_size = 2 * 16 + MAX_OVERHEAD;
+
+        let body = read_body(Body::from(vec![b'a'; max_size]), &limits()).await;
+        assert_eq!(body.unwrap().len(), max_size);
+
+        let chunks = vec![
+            Ok::<_, std::io::Error>(vec![b'a'; max_size]),
+            Ok(vec![b'a']),
+        ];
+        let body = Body::wrap_stream(futures_util::stream::iter(chunks));
+        let error = read_body(body, &limits()).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("the request is larger than {max_size} bytes")
+        );
+        assert!(error.is::<TooLarge>());
+    }
+
+    #[test]
+    fn test_form_data() {
+        let uploads = Uploads::from_iter([(
+            "0".to_string(),
+            Upload {
+                filename: "a.txt".to_string(),
+                mime_type: "text/plain".to_string(),
+                body: Bytes::from("tailcall-boundary-0"),
+            },
+        )]);
+        let fields = json!({
+            "file": {"id": "0", "filename": "a.txt", "mimeType": "text/plain", "size": 19},
+            "name": "a",
+            "tags": ["b", "c"],
+            "note": null,
+        });
+
+        let (content_type, body) = form_data(fields.as_object().unwrap(), &uploads);
+
+        assert_eq!(
+            content_type,
+            "multipart/form-data; boundary=tailcall-boundary-1"
+        );
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--tailcall-boundary-1\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+             Content-Type: text/plain\r\n\r\n\
+             tailcall-boundary-0\r\n\
+             --tailcall-boundary-1\r\n\
+             Content-Disposition: form-data; name=\"name\"\r\n\r\n\
+             a\r\n\
+             --tailcall-boundary-1\r\n\
+             Content-Disposition: form-data; name=\"tags\"\r\n\r\n\
+             [\"b\",\"c\"]\r\n\
+             --tailcall-boundary-1--\r\n"
+        );
+    }
+}
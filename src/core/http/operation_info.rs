@@ -0,0 +1,217 @@
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+use std::sync::Mutex;
+
+use hyper::HeaderMap;
+use once_cell::sync::Lazy;
+use opentelemetry::KeyValue;
+
+use crate::core::blueprint::telemetry::Telemetry;
+
+/// Headers the Apollo clients identify themselves with.
+pub const APOLLO_CLIENT_NAME: &str = "apollographql-client-name";
+pub const APOLLO_CLIENT_VERSION: &str = "apollographql-client-version";
+
+/// Name given to the operations sent without one.
+pub const ANONYMOUS: &str = "anonymous";
+
+/// Values are cut to this many characters, so that a client can't bloat the
+/// logs and spans.
+const MAX_LENGTH: usize = 128;
+
+/// Distinct values an attribute of the metrics takes, the next ones are
+/// counted as [OTHER], so that the clients can't create a time series per
+/// request.
+const MAX_METRIC_VALUES: usize = 100;
+
+/// The value of the metric attributes past [MAX_METRIC_VALUES].
+pub const OTHER: &str = "other";
+
+static OPERATION_NAMES: Lazy<MetricValues> = Lazy::new(|| MetricValues::new(MAX_METRIC_VALUES));
+static CLIENT_NAMES: Lazy<MetricValues> = Lazy::new(|| MetricValues::new(MAX_METRIC_VALUES));
+static CLIENT_VERSIONS: Lazy<MetricValues> = Lazy::new(|| MetricValues::new(MAX_METRIC_VALUES));
+
+pub const GRAPHQL_OPERATION_NAME: &str = "graphql.operation.name";
+pub const CLIENT_NAME: &str = "client.name";
+pub const CLIENT_VERSION: &str = "client.version";
+
+/// The root span of a request, stored in its extensions so that the
+/// attributes only known once the body is parsed can be recorded on it.
+#[derive(Clone)]
+pub struct RequestSpan(pub tracing::Span);
+
+/// The operation a request executes and the client that sent it, attached to
+/// the spans, logs and metrics of the request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperationInfo {
+    pub operation_name: String,
+    pub client_name: Option<String>,
+    pub client_version: Option<String>,
+}
+
+impl OperationInfo {
+    pub fn new(operation_name: Option<String>, headers: &HeaderMap, telemetry: &Telemetry) -> Self {
+        let operation_name = operation_name
+            .as_deref()
+            .map(truncate)
+            .unwrap_or_else(|| ANONYMOUS.to_string());
+        let client_name = find_header(headers, APOLLO_CLIENT_NAME, &telemetry.client_name_headers);
+        let client_version = find_header(
+            headers,
+            APOLLO_CLIENT_VERSION,
+            &telemetry.client_version_headers,
+        );
+
+        Self { operation_name, client_name, client_version }
+    }
+
+    /// Records the info on a span that declares the fields
+    /// [GRAPHQL_OPERATION_NAME], [CLIENT_NAME] and [CLIENT_VERSION].
+    pub fn record(&self, span: &tracing::Span) {
+        span.record(GRAPHQL_OPERATION_NAME, self.operation_name.as_str());
+        if let Some(client_name) = &self.client_name {
+            span.record(CLIENT_NAME, client_name.as_str());
+        }
+        if let Some(client_version) = &self.client_version {
+            span.record(CLIENT_VERSION, client_version.as_str());
+        }
+    }
+
+    /// The info as attributes of the metrics, with the values past the first
+    /// [MAX_METRIC_VALUES] of each attribute counted as [OTHER].
+    pub fn attributes(&self) -> Vec<KeyValue> {
+        let mut attributes = vec![KeyValue::new(
+            GRAPHQL_OPERATION_NAME,
+            OPERATION_NAMES.bucket(&self.operation_name),
+        )];
+        if let Some(client_name) = &self.client_name {
+            attributes.push(KeyValue::new(CLIENT_NAME, CLIENT_NAMES.bucket(client_name)));
+        }
+        if let Some(client_version) = &self.client_version {
+            attributes.push(KeyValue::new(
+                CLIENT_VERSION,
+                CLIENT_VERSIONS.bucket(client_version),
+            ));
+        }
+        attributes
+    }
+}
+
+/// The values an attribute of the metrics took so far, up to a maximum.
+struct MetricValues {
+    values: Mutex<HashSet<String>>,
+    max: usize,
+}
+
+impl MetricValues {
+    fn new(max: usize) -> Self {
+        Self { values: Default::default(), max }
+    }
+
+    /// The value, or [OTHER] once the maximum of distinct values is reached.
+    fn bucket(&self, value: &str) -> String {
+        let mut values = self.values.lock().unwrap();
+        if values.contains(value) || (values.len() < self.max && values.insert(value.to_string())) {
+            value.to_string()
+        } else {
+            OTHER.to_string()
+        }
+    }
+}
+
+impl Display for OperationInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "operation={}", self.operation_name)?;
+        if let Some(client_name) = &self.client_name {
+            write!(f, " client={}", client_name)?;
+        }
+        if let Some(client_version) = &self.client_version {
+            write!(f, " version={}", client_version)?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of the first of the headers sent with a printable value.
+fn find_header(headers: &HeaderMap, name: &str, alternatives: &[String]) -> Option<String> {
+    std::iter::once(name)
+        .chain(alternatives.iter().map(String::as_str))
+        .filter_map(|name| headers.get(name)?.to_str().ok())
+        .find(|value| !value.is_empty())
+        .map(truncate)
+}
+
+fn truncate(value: &str) -> String {
+    value.chars().take(MAX_LENGTH).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::HeaderValue;
+
+    use super::*;
+
+    fn telemetry() -> Telemetry {
+        Telemetry {
+            client_name_headers: vec!["x-client-name".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_operation_info() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-client-name", HeaderValue::from_static("mobile"));
+        headers.insert(APOLLO_CLIENT_VERSION, HeaderValue::from_static("1.2.0"));
+
+        let info = OperationInfo::new(Some("GetUser".to_string()), &headers, &telemetry());
+
+        assert_eq!(
+            info,
+            OperationInfo {
+                operation_name: "GetUser".to_string(),
+                client_name: Some("mobile".to_string()),
+                client_version: Some("1.2.0".to_string()),
+            }
+        );
+        assert_eq!(
+            info.to_string(),
+            "operation=GetUser client=mobile version=1.2.0"
+        );
+    }
+
+    #[test]
+    fn test_apollo_header_first() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-client-name", HeaderValue::from_static("mobile"));
+        headers.insert(APOLLO_CLIENT_NAME, HeaderValue::from_static("web"));
+
+        let info = OperationInfo::new(None, &headers, &telemetry());
+
+        assert_eq!(info.operation_name, ANONYMOUS);
+        assert_eq!(info.client_name, Some("web".to_string()));
+        assert_eq!(info.client_version, None);
+    }
+
+    #[test]
+    fn test_metric_values() {
+        let values = MetricValues::new(2);
+
+        assert_eq!(values.bucket("GetUser"), "GetUser");
+        assert_eq!(values.bucket("GetPosts"), "GetPosts");
+        assert_eq!(values.bucket("GetComments"), OTHER);
+        assert_eq!(values.bucket("GetUser"), "GetUser");
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut headers = HeaderMap::new();
+        let long = "a".repeat(1000);
+        headers.insert(APOLLO_CLIENT_NAME, HeaderValue::from_str(&long).unwrap());
+
+        let info = OperationInfo::new(Some("é".repeat(1000)), &headers, &telemetry());
+
+        assert_eq!(info.client_name.unwrap().len(), MAX_LENGTH);
+        assert_eq!(info.operation_name.chars().count(), MAX_LENGTH);
+    }
+}
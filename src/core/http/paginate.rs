@@ -0,0 +1,446 @@
+use std::future::Future;
+
+use async_graphql_value::ConstValue;
+use reqwest::header::LINK;
+use url::{form_urlencoded, Url};
+
+use super::Response;
+use crate::core::config;
+use crate::core::ir::{DynamicRequest, Error};
+use crate::core::json::JsonLike;
+
+/// Number of pages fetched when `maxPages` isn't set.
+pub const DEFAULT_MAX_PAGES: usize = 10;
+
+/// Settings of an `@http` field that follows the next pages of its API.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Paginate {
+    pub next: NextPage,
+    /// Path of the list of items in the body of each page.
+    pub items_path: Vec<String>,
+    pub max_pages: usize,
+}
+
+/// Where the next page is read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NextPage {
+    /// The `rel="next"` link of the `Link` header.
+    LinkHeader,
+    /// The value at `path` in the body, sent in the `param` query parameter or
+    /// used as the URL of the next page.
+    Cursor {
+        path: Vec<String>,
+        param: Option<String>,
+    },
+}
+
+impl From<&config::Paginate> for Paginate {
+    fn from(value: &config::Paginate) -> Self {
+        let next = match value.mode {
+            config::PaginateMode::LinkHeader => NextPage::LinkHeader,
+            config::PaginateMode::Cursor => NextPage::Cursor {
+                path: to_path(value.cursor_path.as_deref().unwrap_or_default()),
+                param: value.cursor_param.clone(),
+            },
+        };
+
+        Self {
+            next,
+            items_path: to_path(value.items_path.as_deref().unwrap_or_default()),
+            max_pages: value
+                .max_pages
+                .map_or(DEFAULT_MAX_PAGES, |max_pages| max_pages as usize),
+        }
+    }
+}
+
+/// The items of all the fetched pages.
+#[derive(Debug)]
+pub struct Pages {
+    /// The response of the last page, with the items of all the pages as its
+    /// body.
+    pub response: Response<ConstValue>,
+    /// Why the pagination stopped before the last page.
+    pub warning: Option<String>,
+}
+
+impl Paginate {
+    /// Sends the request and then the requests of the next pages with `send`,
+    /// until there's no next page or `max_pages` are fetched. An invalid next
+    /// page or too many pages end the pagination with a warning, keeping the
+    /// items fetched so far.
+    pub async fn execute<F, Fut>(
+        &self,
+        mut request: DynamicRequest<String>,
+        send: F,
+    ) -> Result<Pages, Error>
+    where
+        F: Fn(DynamicRequest<String>) -> Fut,
+        Fut: Future<Output = Result<Response<ConstValue>, Error>>,
+    {
+        let first_url = request.request().url().clone();
+        let mut items = Vec::new();
+        let mut pages = 0;
+
+        loop {
+            let url = request.request().url().clone();
+            let next_request = request.request().try_clone();
+            let response = send(request).await?;
+            pages += 1;
+
+            let next_url = self.next_url(&url, &response);
+            let Response { status, headers, body } = response;
+            items.extend(self.items(body)?);
+
+            let warning = match next_url {
+                Ok(None) => None,
+                Ok(Some(_)) if pages >= self.max_pages => Some(format!(
+                    "Pagination of `{}` stopped after maxPages ({}), the next pages are missing",
+                    first_url, pages
+                )),
+                Ok(Some(next_url)) => {
+                    let mut next_request = next_request.ok_or_else(|| {
+                        Error::IO(format!(
+                            "The request to `{}` can't be repeated for the next page",
+                            url
+                        ))
+                    })?;
+                    *next_request.url_mut() = next_url;
+                    request = DynamicRequest::new(next_request);
+                    continue;
+                }
+                Err(reason) => Some(format!(
+                    "Pagination of `{}` stopped at {}, the next pages are missing",
+                    first_url, reason
+                )),
+            };
+
+            let body = ConstValue::List(items);
+            return Ok(Pages { response: Response { status, headers, body }, warning });
+        }
+    }
+
+    /// The URL of the page after the one fetched from `url`, or the reason it
+    /// can't be read. The next page must be on the origin of `url`, since its
+    /// request is sent with the same headers, eg. the credentials.
+    fn next_url(&self, url: &Url, response: &Response<ConstValue>) -> Result<Option<Url>, String> {
+        let next_url = self.next_url_of(url, response)?;
+        match next_url {
+            Some(next_url) if next_url.origin() != url.origin() => Err(format!(
+                "a next page on another origin `{}`",
+                next_url.origin().ascii_serialization()
+            )),
+            next_url => Ok(next_url),
+        }
+    }
+
+    fn next_url_of(
+        &self,
+        url: &Url,
+        response: &Response<ConstValue>,
+    ) -> Result<Option<Url>, String> {
+        match &self.next {
+            NextPage::LinkHeader => {
+                let link = response
+                    .headers
+                    .get_all(LINK)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .find_map(next_link);
+                match link {
+                    Some(link) => url
+                        .join(link)
+                        .map(Some)
+                        .map_err(|_| format!("an invalid next link `{}`", link)),
+                    None => Ok(None),
+                }
+            }
+            NextPage::Cursor { path, param } => {
+                let cursor = match response.body.get_path(path) {
+                    None | Some(ConstValue::Null) => return Ok(None),
+                    Some(ConstValue::String(cursor)) => cursor.clone(),
+                    Some(ConstValue::Number(cursor)) => cursor.to_string(),
+                    Some(cursor) => return Err(format!("an invalid cursor `{}`", cursor)),
+                };
+                if cursor.is_empty() {
+                    return Ok(None);
+                }
+                match param {
+                    Some(param) => Ok(Some(with_query_param(url, param, &cursor))),
+                    None => url
+                        .join(&cursor)
+                        .map(Some)
+                        .map_err(|_| format!("an invalid cursor `{}`", cursor)),
+                }
+            }
+        }
+    }
+
+    fn items(&self, mut body: ConstValue) -> Result<Vec<ConstValue>, Error> {
+        for key in &self.items_path {
+            body = match body {
+                ConstValue::Object(mut object) => {
+                    object.swap_remove(key.as_str()).unwrap_or_default()
+                }
+                _ => ConstValue::Null,
+            };
+        }
+        match body {
+            ConstValue::List(items) => Ok(items),
+            ConstValue::Null => Ok(Vec::new()),
+            _ => Err(Error::Deserialize(format!(
+                "Expected a list of items at `.{}`",
+                self.items_path.join(".")
+            ))),
+        }
+    }
+}
+
+/// Splits a dot-path, eg. `.meta.next`.
+fn to_path(path: &str) -> Vec<String> {
+    path.split('.')
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The URL of the `rel="next"` link of a `Link` header, eg.
+/// `<https://api.example.com/items?page=2>; rel="next"`.
+fn next_link(header: &str) -> Option<&str> {
+    let mut rest = header;
+    while let Some(start) = rest.find('<') {
+        let end = start + rest[start..].find('>')?;
+        let link = &rest[start + 1..end];
+        let params_end = rest[end..].find('<').map_or(rest.len(), |i| end + i);
+        let is_next = rest[end + 1..params_end].split([';', ',']).any(|param| {
+            param.split_once('=').is_some_and(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("rel")
+                    && value
+                        .trim()
+                        .trim_matches('"')
+                        .split_whitespace()
+                        .any(|rel| rel.eq_ignore_ascii_case("next"))
+            })
+        });
+        if is_next {
+            return Some(link);
+        }
+        rest = &rest[params_end..];
+    }
+    None
+}
+
+/// Sets the query parameter, keeping the other parameters as they're
+/// encoded.
+fn with_query_param(url: &Url, name: &str, value: &str) -> Url {
+    let param = form_urlencoded::Serializer::new(String::new())
+        .append_pair(name, value)
+        .finish();
+    let key = param.split('=').next().unwrap_or_default();
+    let query = url
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some(key))
+        .chain(std::iter::once(param.as_str()))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut url = url.clone();
+    url.set_query(Some(&query));
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+
+    fn page(body: serde_json::Value, link: Option<&str>) -> Response<ConstValue> {
+        let body = ConstValue::from_json(body).unwrap();
+        let mut response = Response { body, ..Default::default() };
+        if let Some(link) = link {
+            response
+                .headers
+                .insert(LINK, HeaderValue::from_str(link).unwrap());
+        }
+        response
+    }
+
+    async fn execute(
+        paginate: &Paginate,
+        pages: HashMap<&str, Response<ConstValue>>,
+    ) -> Result<Pages, Error> {
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            "http://localhost/items?size=2".parse().unwrap(),
+        );
+        paginate
+            .execute(DynamicRequest::new(request), |request| {
+                let response = pages
+                    .get(request.request().url().as_str())
+                    .cloned()
+                    .ok_or_else(|| Error::IO(format!("no page {}", request.request().url())));
+                async move { response }
+            })
+            .await
+    }
+
+    fn paginate(next: NextPage, max_pages: usize) -> Paginate {
+        Paginate { next, items_path: vec!["data".to_string()], max_pages }
+    }
+
+    #[test]
+    fn test_next_link() {
+        let header = r#"<http://a/items?page=1>; rel="prev", <http://a/items?ids=1,2&page=3>; rel="next last""#;
+        assert_eq!(next_link(header), Some("http://a/items?ids=1,2&page=3"));
+        assert_eq!(next_link(r#"<http://a/items?page=1>; rel="prev""#), None);
+        assert_eq!(next_link("<http://a/items"), None);
+    }
+
+    #[test]
+    fn test_with_query_param() {
+        let url = Url::parse("http://a/items?ids=1,2&cursor=a&size=2").unwrap();
+        let actual = with_query_param(&url, "cursor", "b c");
+        assert_eq!(actual.as_str(), "http://a/items?ids=1,2&size=2&cursor=b+c");
+    }
+
+    #[tokio::test]
+    async fn test_link_header() {
+        let paginate = paginate(NextPage::LinkHeader, 10);
+        let pages = HashMap::from([
+            (
+                "http://localhost/items?size=2",
+                page(
+                    json!({"data": [1, 2]}),
+                    Some(r#"</items?size=2&page=2>; rel="next""#),
+                ),
+            ),
+            (
+                "http://localhost/items?size=2&page=2",
+                page(json!({"data": [3]}), None),
+            ),
+        ]);
+
+        let pages = execute(&paginate, pages).await.unwrap();
+
+        assert_eq!(
+            pages.response.body,
+            ConstValue::from_json(json!([1, 2, 3])).unwrap()
+        );
+        assert_eq!(pages.warning, None);
+    }
+
+    #[tokio::test]
+    async fn test_cursor() {
+        let next = NextPage::Cursor {
+            path: vec!["meta".to_string(), "next".to_string()],
+            param: Some("cursor".to_string()),
+        };
+        let paginate = paginate(next, 10);
+        let pages = HashMap::from([
+            (
+                "http://localhost/items?size=2",
+                page(json!({"data": [1, 2], "meta": {"next": "b"}}), None),
+            ),
+            (
+                "http://localhost/items?size=2&cursor=b",
+                page(json!({"data": [3], "meta": {"next": null}}), None),
+            ),
+        ]);
+
+        let pages = execute(&paginate, pages).await.unwrap();
+
+        assert_eq!(
+            pages.response.body,
+            ConstValue::from_json(json!([1, 2, 3])).unwrap()
+        );
+        assert_eq!(pages.warning, None);
+    }
+
+    #[tokio::test]
+    async fn test_max_pages() {
+        let paginate = paginate(NextPage::LinkHeader, 1);
+        let pages = HashMap::from([(
+            "http://localhost/items?size=2",
+            page(
+                json!({"data": [1, 2]}),
+                Some(r#"</items?size=2&page=2>; rel="next""#),
+            ),
+        )]);
+
+        let pages = execute(&paginate, pages).await.unwrap();
+
+        assert_eq!(
+            pages.response.body,
+            ConstValue::from_json(json!([1, 2])).unwrap()
+        );
+        assert_eq!(
+            pages.warning.unwrap(),
+            "Pagination of `http://localhost/items?size=2` stopped after maxPages (1), the next pages are missing"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_cursor() {
+        let next = NextPage::Cursor { path: vec!["next".to_string()], param: None };
+        let paginate = paginate(next, 10);
+        let pages = HashMap::from([(
+            "http://localhost/items?size=2",
+            page(json!({"data": [1, 2], "next": {"page": 2}}), None),
+        )]);
+
+        let pages = execute(&paginate, pages).await.unwrap();
+
+        assert_eq!(
+            pages.response.body,
+            ConstValue::from_json(json!([1, 2])).unwrap()
+        );
+        assert_eq!(
+            pages.warning.unwrap(),
+            "Pagination of `http://localhost/items?size=2` stopped at an invalid cursor `{page: 2}`, the next pages are missing"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cross_origin() {
+        let paginate = paginate(NextPage::LinkHeader, 10);
+        let pages = HashMap::from([(
+            "http://localhost/items?size=2",
+            page(
+                json!({"data": [1, 2]}),
+                Some(r#"<http://attacker.com/items?page=2>; rel="next""#),
+            ),
+        )]);
+
+        let pages = execute(&paginate, pages).await.unwrap();
+
+        assert_eq!(
+            pages.response.body,
+            ConstValue::from_json(json!([1, 2])).unwrap()
+        );
+        assert_eq!(
+            pages.warning.unwrap(),
+            "Pagination of `http://localhost/items?size=2` stopped at a next page on another origin `http://attacker.com`, the next pages are missing"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_items() {
+        let paginate = paginate(NextPage::LinkHeader, 10);
+        let pages = HashMap::from([(
+            "http://localhost/items?size=2",
+            page(json!({"data": {"id": 1}}), None),
+        )]);
+
+        let error = execute(&paginate, pages).await.unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected a list of items at `.data`"));
+    }
+}
@@ -0,0 +1,142 @@
+//! GraphQL IDE that is embedded in the binary so that it works without access
+//! to the internet.
+
+use anyhow::Result;
+use hyper::header::{CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Body, Response, StatusCode};
+use serde_json::json;
+
+use crate::core::blueprint::Playground;
+
+const INDEX: &str = include_str!("index.html");
+const SCRIPT: &[u8] = include_bytes!("playground.js");
+const STYLE: &[u8] = include_bytes!("playground.css");
+
+/// The page contains the settings of the server so it's always revalidated.
+const PAGE_CACHE_CONTROL: &str = "no-cache";
+const ASSET_CACHE_CONTROL: &str = "public, max-age=86400";
+
+/// Serves the playground page and its assets. Returns `None` if the path
+/// doesn't belong to the playground.
+pub fn serve(
+    playground: &Playground,
+    graphql_endpoint: &str,
+    path: &str,
+) -> Option<Result<Response<Body>>> {
+    let assets = playground.path.trim_end_matches('/');
+
+    if path == playground.path {
+        return Some(page(playground, graphql_endpoint, assets));
+    }
+
+    match path.strip_prefix(assets)? {
+        "/playground.js" => Some(asset(SCRIPT, "application/javascript; charset=utf-8")),
+        "/playground.css" => Some(asset(STYLE, "text/css; charset=utf-8")),
+        _ => None,
+    }
+}
+
+fn page(playground: &Playground, graphql_endpoint: &str, assets: &str) -> Result<Response<Body>> {
+    let headers = playground
+        .headers
+        .iter()
+        .map(|(key, value)| (key.clone(), json!(value)))
+        .collect::<serde_json::Map<_, _>>();
+    let config = json!({
+        "endpoint": graphql_endpoint,
+        "defaultQuery": playground.default_query,
+        "headers": headers,
+    });
+
+    // escape `<` so that the settings can't close the script tag they're
+    // embedded in
+    let config = serde_json::to_string(&config)?.replace('<', "\\u003c");
+    let html = INDEX
+        .replace("{{assets}}", assets)
+        .replace("{{config}}", &config);
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(CACHE_CONTROL, PAGE_CACHE_CONTROL)
+        .body(Body::from(html))?)
+}
+
+fn asset(content: &'static [u8], content_type: &str) -> Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, content_type)
+        .header(CACHE_CONTROL, ASSET_CACHE_CONTROL)
+        .body(Body::from(content))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playground(path: &str) -> Playground {
+        Playground {
+            path: path.to_string(),
+            default_query: Some("{ users { id } }".to_string()),
+            headers: vec![("Authorization".to_string(), "Bearer </script>".to_string())],
+        }
+    }
+
+    async fn body(response: Response<Body>) -> String {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_page_contains_defaults() {
+        let response = serve(&playground("/ide"), "/gql", "/ide").unwrap().unwrap();
+
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html; charset=utf-8");
+        assert_eq!(response.headers()[CACHE_CONTROL], PAGE_CACHE_CONTROL);
+
+        let html = body(response).await;
+        assert!(html.contains(r#""endpoint":"/gql""#));
+        assert!(html.contains(r#""defaultQuery":"{ users { id } }""#));
+        assert!(html.contains(r#""Authorization":"Bearer \u003c/script>""#));
+        assert_eq!(html.matches("</script>").count(), 2);
+        assert!(html.contains(r#"src="/ide/playground.js""#));
+        assert!(html.contains(r#"href="/ide/playground.css""#));
+    }
+
+    #[tokio::test]
+    async fn test_assets() {
+        let playground = playground("/ide");
+
+        let response = serve(&playground, "/graphql", "/ide/playground.js")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            response.headers()[CONTENT_TYPE],
+            "application/javascript; charset=utf-8"
+        );
+        assert_eq!(response.headers()[CACHE_CONTROL], ASSET_CACHE_CONTROL);
+        assert_eq!(body(response).await.as_bytes(), SCRIPT);
+
+        let response = serve(&playground, "/graphql", "/ide/playground.css")
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/css; charset=utf-8");
+    }
+
+    #[test]
+    fn test_root_path() {
+        let playground = playground("/");
+
+        assert!(serve(&playground, "/graphql", "/").is_some());
+        assert!(serve(&playground, "/graphql", "/playground.js").is_some());
+    }
+
+    #[test]
+    fn test_unknown_path() {
+        let playground = playground("/ide");
+
+        assert!(serve(&playground, "/graphql", "/graphql").is_none());
+        assert!(serve(&playground, "/graphql", "/ide/index.html").is_none());
+        assert!(serve(&playground, "/graphql", "/playground.js").is_none());
+    }
+}
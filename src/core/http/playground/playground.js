@@ -0,0 +1,210 @@
+(function () {
+  "use strict";
+
+  var config = JSON.parse(document.getElementById("playground-config").textContent);
+  var storageKey = "tailcall-playground:" + config.endpoint;
+
+  var elements = {
+    tabs: document.getElementById("tabs"),
+    newTab: document.getElementById("new-tab"),
+    endpoint: document.getElementById("endpoint"),
+    run: document.getElementById("run"),
+    query: document.getElementById("query"),
+    variables: document.getElementById("variables"),
+    headers: document.getElementById("headers"),
+    status: document.getElementById("status"),
+    response: document.getElementById("response"),
+  };
+
+  function createTab() {
+    return {
+      id: Date.now().toString(36) + Math.random().toString(36).slice(2, 6),
+      query: config.defaultQuery || "",
+      variables: "{}",
+      headers: JSON.stringify(config.headers || {}, null, 2),
+      response: "",
+    };
+  }
+
+  function load() {
+    try {
+      var state = JSON.parse(window.localStorage.getItem(storageKey));
+      if (state && Array.isArray(state.tabs) && state.tabs.length > 0) {
+        return state;
+      }
+    } catch (e) {
+      // ignore the broken state and start over
+    }
+
+    var tab = createTab();
+    return { tabs: [tab], active: tab.id };
+  }
+
+  function save() {
+    try {
+      window.localStorage.setItem(storageKey, JSON.stringify(state));
+    } catch (e) {
+      // the storage could be full or disabled, the tabs are kept in memory
+    }
+  }
+
+  var state = load();
+
+  function activeTab() {
+    var tab = state.tabs.find(function (tab) {
+      return tab.id === state.active;
+    });
+
+    return tab || state.tabs[0];
+  }
+
+  function title(tab) {
+    var match = /(query|mutation|subscription)\s+([_A-Za-z][_0-9A-Za-z]*)/.exec(tab.query);
+    return match ? match[2] : "Untitled";
+  }
+
+  function renderTabs() {
+    elements.tabs.innerHTML = "";
+
+    state.tabs.forEach(function (tab) {
+      var button = document.createElement("button");
+      button.className = "tab" + (tab.id === activeTab().id ? " active" : "");
+      button.textContent = title(tab);
+      button.addEventListener("click", function () {
+        state.active = tab.id;
+        render();
+        save();
+      });
+
+      if (state.tabs.length > 1) {
+        var close = document.createElement("button");
+        close.className = "close";
+        close.title = "Close tab";
+        close.textContent = "×";
+        close.addEventListener("click", function (event) {
+          event.stopPropagation();
+          closeTab(tab.id);
+        });
+        button.appendChild(close);
+      }
+
+      elements.tabs.appendChild(button);
+    });
+  }
+
+  function render() {
+    var tab = activeTab();
+    elements.query.value = tab.query;
+    elements.variables.value = tab.variables;
+    elements.headers.value = tab.headers;
+    elements.response.textContent = tab.response;
+    elements.status.textContent = "";
+    elements.status.className = "status";
+    renderTabs();
+  }
+
+  function closeTab(id) {
+    state.tabs = state.tabs.filter(function (tab) {
+      return tab.id !== id;
+    });
+    if (state.active === id) {
+      state.active = state.tabs[state.tabs.length - 1].id;
+    }
+    render();
+    save();
+  }
+
+  function parseJson(text, name) {
+    if (!text.trim()) {
+      return {};
+    }
+
+    try {
+      return JSON.parse(text);
+    } catch (e) {
+      throw new Error(name + " must be valid JSON: " + e.message);
+    }
+  }
+
+  function showError(message) {
+    elements.status.textContent = message;
+    elements.status.className = "status error";
+  }
+
+  function run() {
+    var tab = activeTab();
+    var variables;
+    var headers;
+
+    try {
+      variables = parseJson(tab.variables, "Variables");
+      headers = parseJson(tab.headers, "Headers");
+    } catch (e) {
+      showError(e.message);
+      return;
+    }
+
+    var started = Date.now();
+    elements.status.textContent = "Running...";
+    elements.status.className = "status";
+
+    fetch(config.endpoint, {
+      method: "POST",
+      headers: Object.assign({ "Content-Type": "application/json" }, headers),
+      body: JSON.stringify({ query: tab.query, variables: variables }),
+    })
+      .then(function (response) {
+        return response.text().then(function (body) {
+          try {
+            body = JSON.stringify(JSON.parse(body), null, 2);
+          } catch (e) {
+            // keep the body as is if it isn't JSON
+          }
+
+          tab.response = body;
+          elements.status.textContent = response.status + " " + response.statusText + " in " + (Date.now() - started) + "ms";
+          if (tab.id === activeTab().id) {
+            elements.response.textContent = body;
+          }
+          save();
+        });
+      })
+      .catch(function (e) {
+        showError("Request failed: " + e.message);
+      });
+  }
+
+  function bind(element, key) {
+    element.addEventListener("input", function () {
+      activeTab()[key] = element.value;
+      if (key === "query") {
+        renderTabs();
+      }
+      save();
+    });
+  }
+
+  bind(elements.query, "query");
+  bind(elements.variables, "variables");
+  bind(elements.headers, "headers");
+
+  elements.newTab.addEventListener("click", function () {
+    var tab = createTab();
+    state.tabs.push(tab);
+    state.active = tab.id;
+    render();
+    save();
+    elements.query.focus();
+  });
+
+  elements.run.addEventListener("click", run);
+  document.addEventListener("keydown", function (event) {
+    if ((event.ctrlKey || event.metaKey) && event.key === "Enter") {
+      event.preventDefault();
+      run();
+    }
+  });
+
+  elements.endpoint.textContent = config.endpoint;
+  render();
+})();
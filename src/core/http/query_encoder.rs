@@ -1,3 +1,4 @@
+use crate::core::config::{ArrayFormat, QueryEncoding};
 use crate::core::path::ValueString;
 
 /// Defines different strategies for encoding query parameters.
@@ -9,9 +10,48 @@ pub enum QueryEncoder {
     /// key=value1&key=value2&key=value3&...
     #[default]
     RepeatedKey,
+    /// Encodes the query list as key=value1%20value2%20value3...
+    SpaceDelimited,
+    /// Encodes the query list as key=value1|value2|value3...
+    PipeDelimited,
+    /// Encodes the query list by repeating the key followed by brackets for
+    /// each value: key[]=value1&key[]=value2&key[]=value3&...
+    Bracket,
+}
+
+impl From<QueryEncoding> for QueryEncoder {
+    fn from(encoding: QueryEncoding) -> Self {
+        match encoding {
+            QueryEncoding::RepeatedKey => QueryEncoder::RepeatedKey,
+            QueryEncoding::CommaSeparated => QueryEncoder::CommaSeparated,
+            QueryEncoding::SpaceDelimited => QueryEncoder::SpaceDelimited,
+            QueryEncoding::PipeDelimited => QueryEncoder::PipeDelimited,
+        }
+    }
+}
+
+impl From<ArrayFormat> for QueryEncoder {
+    fn from(format: ArrayFormat) -> Self {
+        match format {
+            ArrayFormat::Comma => QueryEncoder::CommaSeparated,
+            ArrayFormat::Repeat => QueryEncoder::RepeatedKey,
+            ArrayFormat::Bracket => QueryEncoder::Bracket,
+        }
+    }
 }
 
 impl QueryEncoder {
+    /// The separator of the items of a list, for the encoders that send it as
+    /// a single value.
+    fn delimiter(&self) -> Option<&'static str> {
+        match self {
+            QueryEncoder::CommaSeparated => Some(","),
+            QueryEncoder::SpaceDelimited => Some("%20"),
+            QueryEncoder::PipeDelimited => Some("|"),
+            QueryEncoder::RepeatedKey | QueryEncoder::Bracket => None,
+        }
+    }
+
     pub fn encode(&self, key: &str, raw_value: Option<ValueString>) -> String {
         if let Some(value) = raw_value {
             match &value {
@@ -23,8 +63,8 @@ impl QueryEncoder {
         }
     }
     fn encode_const_value(&self, key: &str, value: &async_graphql::Value) -> String {
-        match self {
-            QueryEncoder::CommaSeparated => match value {
+        match self.delimiter() {
+            Some(delimiter) => match value {
                 async_graphql::Value::List(list) if !list.is_empty() => {
                     let encoded_values: Vec<String> =
                         list.iter().filter_map(convert_value).collect();
@@ -32,18 +72,22 @@ impl QueryEncoder {
                     if encoded_values.is_empty() {
                         key.to_string()
                     } else {
-                        format!("{}={}", key, encoded_values.join(","))
+                        format!("{}={}", key, encoded_values.join(delimiter))
                     }
                 }
                 _ => convert_value(value)
                     .map(|val| format!("{}={}", key, val))
                     .unwrap_or(key.to_string()),
             },
-            QueryEncoder::RepeatedKey => match value {
+            None => match value {
                 async_graphql::Value::List(list) if !list.is_empty() => {
+                    let key = match self {
+                        QueryEncoder::Bracket => format!("{}[]", key),
+                        _ => key.to_string(),
+                    };
                     let encoded_values: Vec<String> = list
                         .iter()
-                        .map(|val| self.encode_const_value(key, val))
+                        .map(|val| self.encode_const_value(&key, val))
                         .collect();
                     if encoded_values.is_empty() {
                         key.to_string()
@@ -93,6 +137,33 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_encode_delimited_arg() {
+        let values = Value::List(vec![Value::Number(12.into()), Value::Number(42.into())]);
+
+        let actual = QueryEncoder::SpaceDelimited
+            .encode("key", Some(ValueString::Value(Cow::Borrowed(&values))));
+        assert_eq!(actual, "key=12%2042");
+
+        let actual = QueryEncoder::PipeDelimited
+            .encode("key", Some(ValueString::Value(Cow::Borrowed(&values))));
+        assert_eq!(actual, "key=12|42");
+    }
+
+    #[test]
+    fn test_encode_bracket_arg() {
+        let values = Value::List(vec![Value::Number(12.into()), Value::Number(42.into())]);
+
+        let actual =
+            QueryEncoder::Bracket.encode("ids", Some(ValueString::Value(Cow::Borrowed(&values))));
+        assert_eq!(actual, "ids[]=12&ids[]=42");
+
+        let value = Value::Number(12.into());
+        let actual =
+            QueryEncoder::Bracket.encode("ids", Some(ValueString::Value(Cow::Borrowed(&value))));
+        assert_eq!(actual, "ids=12");
+    }
+
     #[test]
     fn test_encode_repeated_key_value_arg() {
         let encoder = QueryEncoder::RepeatedKey;
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::num::NonZeroU64;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
@@ -10,9 +11,9 @@ use http::header::{HeaderMap, HeaderName, HeaderValue};
 use crate::core::app_context::AppContext;
 use crate::core::blueprint::{Server, Upstream};
 use crate::core::data_loader::{DataLoader, DedupeResult};
-use crate::core::graphql::GraphqlDataLoader;
+use crate::core::graphql::{GraphqlDataLoader, GraphqlMergeLoader, RootFieldRequest};
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::{DataLoaderRequest, HttpDataLoader};
+use crate::core::http::{CircuitBreakers, DataLoaderRequest, HttpDataLoader, RequestId, Uploads};
 use crate::core::ir::model::IoId;
 use crate::core::ir::Error;
 use crate::core::runtime::TargetRuntime;
@@ -30,11 +31,19 @@ pub struct RequestContext {
     pub http_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, HttpDataLoader>>>,
     pub gql_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, GraphqlDataLoader>>>,
     pub grpc_data_loaders: Arc<Vec<DataLoader<grpc::DataLoaderRequest, GrpcDataLoader>>>,
+    pub gql_merge_loader: Option<Arc<DataLoader<RootFieldRequest, GraphqlMergeLoader>>>,
     pub min_max_age: Arc<Mutex<Option<i32>>>,
     pub cache_public: Arc<Mutex<Option<bool>>>,
     pub runtime: TargetRuntime,
     pub cache: DedupeResult<IoId, ConstValue, Error>,
     pub dedupe_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
+    pub circuit_breakers: Arc<CircuitBreakers>,
+    /// Sent to the upstreams in the `X-Request-Id` header.
+    pub request_id: RequestId,
+    /// The values of `@server(context:)`, computed before the query runs.
+    pub context_values: BTreeMap<String, ConstValue>,
+    /// The files sent with a multipart request.
+    pub uploads: Uploads,
 }
 
 impl RequestContext {
@@ -47,12 +56,17 @@ impl RequestContext {
             http_data_loaders: Arc::new(vec![]),
             gql_data_loaders: Arc::new(vec![]),
             grpc_data_loaders: Arc::new(vec![]),
+            gql_merge_loader: None,
             min_max_age: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
             runtime: target_runtime,
             cache: DedupeResult::new(true),
             dedupe_handler: Arc::new(DedupeResult::new(false)),
             allowed_headers: HeaderMap::new(),
+            circuit_breakers: Arc::new(CircuitBreakers::default()),
+            request_id: RequestId::new(),
+            context_values: BTreeMap::new(),
+            uploads: Uploads::default(),
         }
     }
     fn set_min_max_age_conc(&self, min_max_age: i32) {
@@ -196,11 +210,16 @@ impl From<&AppContext> for RequestContext {
             http_data_loaders: app_ctx.http_data_loaders.clone(),
             gql_data_loaders: app_ctx.gql_data_loaders.clone(),
             grpc_data_loaders: app_ctx.grpc_data_loaders.clone(),
+            gql_merge_loader: app_ctx.gql_merge_loader.clone(),
             min_max_age: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
             runtime: app_ctx.runtime.clone(),
             cache: DedupeResult::new(true),
             dedupe_handler: app_ctx.dedupe_handler.clone(),
+            circuit_breakers: app_ctx.circuit_breakers.clone(),
+            request_id: RequestId::new(),
+            context_values: BTreeMap::new(),
+            uploads: Uploads::default(),
         }
     }
 }
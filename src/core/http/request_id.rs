@@ -0,0 +1,45 @@
+use std::fmt::{self, Display};
+
+use http::{HeaderMap, HeaderValue};
+use uuid::Uuid;
+
+/// Name of the header that carries the [RequestId] to the upstreams and back
+/// to the client.
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+/// Identifies an incoming request, to correlate the logs, spans and upstream
+/// calls made for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(Uuid);
+
+impl RequestId {
+    /// Generates a random (v4) id.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    /// The id sent by the client in [X_REQUEST_ID], so that its logs and ours
+    /// can be correlated. Only uuids are accepted, the other values could
+    /// forge the logs.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(X_REQUEST_ID)?.to_str().ok()?;
+        Uuid::parse_str(value.trim()).ok().map(Self)
+    }
+
+    pub fn header_value(&self) -> HeaderValue {
+        // The hyphenated form of a uuid is always a valid header value
+        HeaderValue::from_str(&self.0.to_string()).unwrap()
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -1,15 +1,20 @@
 use std::borrow::Cow;
 use std::hash::{Hash, Hasher};
 
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
 use derive_setters::Setters;
-use http::header::{HeaderMap, HeaderValue};
+use http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use tailcall_hasher::TailcallHasher;
 use url::Url;
 
 use super::query_encoder::QueryEncoder;
-use crate::core::config::Encoding;
+use super::{compression, multipart, NoContent, Upload};
+use crate::core::config::{Encoding, NullQueryParams, ResponseFormat};
 use crate::core::endpoint::Endpoint;
 use crate::core::has_headers::HasHeaders;
+use crate::core::has_uploads::HasUploads;
 use crate::core::helpers::headers::MustacheHeaders;
 use crate::core::ir::model::{CacheKey, IoId};
 use crate::core::ir::DynamicRequest;
@@ -30,6 +35,42 @@ pub struct RequestTemplate {
     pub endpoint: Endpoint,
     pub encoding: Encoding,
     pub query_encoder: QueryEncoder,
+    #[setters(strip_option)]
+    pub basic_auth: Option<BasicCredentials>,
+    /// Cookies sent with the request in a single `Cookie` header.
+    pub cookies: Vec<(String, Mustache)>,
+    /// How the query parameters that resolve to `null` are sent.
+    pub null_query_params: NullQueryParams,
+    /// The format the response body is parsed from.
+    pub parse_response: ResponseFormat,
+    /// What the field resolves to when the response has no content.
+    pub no_content: NoContent,
+    /// Headers that carry the id of the incoming request, besides
+    /// `x-request-id`.
+    pub request_id_headers: Vec<HeaderName>,
+    /// Gzip-compresses the body, sent with `Content-Encoding: gzip`.
+    pub compress: bool,
+    /// Percent-encodes the values substituted in the path of the URL.
+    pub encode_path: bool,
+    /// Percent-encodes the values of the query parameters.
+    pub encode_query_params: bool,
+    /// The key of the request among the batched ones, rendered as a value and
+    /// sent in `{{.batch.keys}}`.
+    pub batch_key: Option<Mustache>,
+}
+
+/// Username and password sent as HTTP Basic credentials with the request.
+#[derive(Debug, Clone)]
+pub struct BasicCredentials {
+    pub user: Mustache,
+    pub pass: Mustache,
+}
+
+impl BasicCredentials {
+    fn render<C: PathString>(&self, ctx: &C) -> String {
+        let credentials = format!("{}:{}", self.user.render(ctx), self.pass.render(ctx));
+        format!("Basic {}", BASE64_STANDARD.encode(credentials))
+    }
 }
 
 #[derive(Setters, Debug, Clone)]
@@ -37,13 +78,67 @@ pub struct Query {
     pub key: String,
     pub value: Mustache,
     pub skip_empty: bool,
+    /// Overrides the template's `query_encoder` for this parameter.
+    pub encoder: Option<QueryEncoder>,
 }
 
 impl RequestTemplate {
+    /// Whether the URL is picked per tenant with `{{.tenant.id}}`, the other
+    /// templates can't use it, see `MustachePartsValidator`.
+    pub fn uses_tenant(&self) -> bool {
+        self.root_url
+            .expression_segments()
+            .iter()
+            .any(|parts| parts.first().is_some_and(|head| head == "tenant"))
+    }
+
+    /// Renders the URL, percent-encoding the values substituted in its path
+    /// and query as `encode_path` and `encode_query_params` require.
+    fn render_url<C: PathString>(&self, ctx: &C) -> String {
+        if !self.encode_path && !self.encode_query_params {
+            return self.root_url.render(ctx);
+        }
+
+        let mut url = String::new();
+        for segment in self.root_url.segments() {
+            match segment {
+                Segment::Literal(text) => url.push_str(text),
+                Segment::Expression(parts) => {
+                    let value = ctx
+                        .path_string(parts)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default();
+                    if self.encodes_at(&url) {
+                        url.push_str(&percent_encode(&value));
+                    } else {
+                        url.push_str(&value);
+                    }
+                }
+            }
+        }
+        url
+    }
+
+    /// Whether a value appended to the URL rendered so far is encoded, it's
+    /// in the path when the authority is followed by a `/`, and in the query
+    /// after a `?`. The values in the scheme, host or fragment never are.
+    fn encodes_at(&self, url: &str) -> bool {
+        let Some((_, rest)) = url.split_once("://") else {
+            return false;
+        };
+        if rest.contains('#') {
+            false
+        } else if rest.contains('?') {
+            self.encode_query_params
+        } else {
+            self.encode_path && rest.contains('/')
+        }
+    }
+
     /// Creates a URL for the context
     /// Fills in all the mustache templates with required values.
     fn create_url<C: PathString + PathValue>(&self, ctx: &C) -> anyhow::Result<Url> {
-        let mut url = url::Url::parse(self.root_url.render(ctx).as_str())?;
+        let mut url = url::Url::parse(self.render_url(ctx).as_str())?;
         if self.query.is_empty() && self.root_url.is_const() {
             return Ok(url);
         }
@@ -51,16 +146,28 @@ impl RequestTemplate {
         // evaluates mustache template and returns the values evaluated by mustache
         // template.
         let mustache_eval = ValueStringEval::default();
+        let omit_null = self.null_query_params == NullQueryParams::Omit;
 
         let extra_qp = self.query.iter().filter_map(|query| {
             let key = &query.key;
             let value = &query.value;
             let skip = query.skip_empty;
-            let parsed_value = mustache_eval.eval(value, ctx);
-            if skip && parsed_value.is_none() {
+            let mut parsed_value = mustache_eval.eval(value, ctx);
+            if self.encode_query_params {
+                parsed_value = parsed_value.map(percent_encode_value);
+            }
+            let is_null = match &parsed_value {
+                None => true,
+                Some(ValueString::Value(value)) => {
+                    matches!(value.as_ref(), async_graphql::Value::Null)
+                }
+                Some(ValueString::String(_)) => false,
+            };
+            if (skip && parsed_value.is_none()) || (omit_null && is_null) {
                 None
             } else {
-                Some(self.query_encoder.encode(key, parsed_value))
+                let encoder = query.encoder.as_ref().unwrap_or(&self.query_encoder);
+                Some(encoder.encode(key, parsed_value))
             }
         });
 
@@ -68,7 +175,14 @@ impl RequestTemplate {
             .query_pairs()
             .filter_map(|(k, v)| if v.is_empty() { None } else { Some((k, v)) });
 
-        let qp_string = base_qp.map(|(k, v)| format!("{}={}", k, v));
+        // the pairs are decoded, so they're encoded again when required
+        let qp_string = base_qp.map(|(k, v)| {
+            if self.encode_query_params {
+                format!("{}={}", k, percent_encode(&v))
+            } else {
+                format!("{}={}", k, v)
+            }
+        });
         let qp_string = qp_string.chain(extra_qp).fold("".to_string(), |str, item| {
             if str.is_empty() {
                 item
@@ -95,6 +209,27 @@ impl RequestTemplate {
             && self.body_path.as_ref().map_or(true, |b| b.is_const())
             && self.query.iter().all(|query| query.value.is_const())
             && self.headers.iter().all(|(_, v)| v.is_const())
+            && self
+                .basic_auth
+                .as_ref()
+                .map_or(true, |auth| auth.user.is_const() && auth.pass.is_const())
+            && self.cookies.iter().all(|(_, v)| v.is_const())
+    }
+
+    /// Renders the cookies as the value of a `Cookie` header, with their
+    /// values percent-encoded so that eg. a `;` can't add another cookie
+    fn render_cookies<C: PathString>(&self, ctx: &C) -> Option<String> {
+        if self.cookies.is_empty() {
+            return None;
+        }
+
+        let cookies = self
+            .cookies
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, percent_encode(&v.render(ctx))))
+            .collect::<Vec<_>>();
+
+        Some(cookies.join("; "))
     }
 
     /// Creates a HeaderMap for the context
@@ -102,32 +237,68 @@ impl RequestTemplate {
         let mut header_map = HeaderMap::new();
 
         for (k, v) in &self.headers {
-            if let Ok(header_value) = HeaderValue::from_str(&v.render(ctx)) {
+            let value = v.render(ctx);
+            // a template that resolves to nothing, eg. `{{.value.version}}` on a
+            // parent without a version, leaves the header out
+            if value.is_empty() && !v.is_const() {
+                continue;
+            }
+            if let Ok(header_value) = HeaderValue::from_str(&value) {
                 header_map.insert(k, header_value);
             }
         }
 
+        if let Some(basic_auth) = &self.basic_auth {
+            if let Ok(header_value) = HeaderValue::from_str(&basic_auth.render(ctx)) {
+                header_map.insert(reqwest::header::AUTHORIZATION, header_value);
+            }
+        }
+
         header_map
     }
 
     /// Creates a Request for the given context
-    pub fn to_request<C: PathString + HasHeaders + PathValue>(
+    pub fn to_request<C: PathString + HasHeaders + PathValue + HasUploads>(
         &self,
         ctx: &C,
     ) -> anyhow::Result<DynamicRequest<String>> {
         let url = self.create_url(ctx)?;
         let method = self.method.clone();
         let req = reqwest::Request::new(method, url);
-        let req = self.set_headers(req, ctx);
-        self.set_body(req, ctx)
+        let req = self.set_headers(req, ctx)?;
+        let mut req = self.set_body(req, ctx)?;
+        if self.compress {
+            compress_body(req.request_mut())?;
+        }
+        let batch_key =
+            self.batch_key
+                .as_ref()
+                .map(|key| match ValueStringEval::default().eval(key, ctx) {
+                    Some(ValueString::Value(value)) => value.into_owned(),
+                    Some(ValueString::String(value)) => {
+                        async_graphql::Value::String(value.into_owned())
+                    }
+                    None => async_graphql::Value::Null,
+                });
+        Ok(req.with_batch_key(batch_key))
     }
 
     /// Sets the body for the request
-    fn set_body<C: PathString + HasHeaders>(
+    fn set_body<C: PathString + HasHeaders + PathValue + HasUploads>(
         &self,
         mut req: reqwest::Request,
         ctx: &C,
     ) -> anyhow::Result<DynamicRequest<String>> {
+        // a file is sent as is when it's the whole body, eg. `{{.args.file}}`
+        if let Some(upload) = self.body_path.as_ref().and_then(|body| upload(body, ctx)) {
+            if let Ok(mime_type) = HeaderValue::from_str(&upload.mime_type) {
+                req.headers_mut()
+                    .insert(reqwest::header::CONTENT_TYPE, mime_type);
+            }
+            req.body_mut().replace(upload.body.clone().into());
+            return Ok(DynamicRequest::new(req));
+        }
+
         let batching_value = if let Some(body_path) = &self.body_path {
             match &self.encoding {
                 Encoding::ApplicationJson => {
@@ -141,13 +312,28 @@ impl RequestTemplate {
                     // We first encode everything to string and then back to form-urlencoded
                     let body = body_path.render(ctx);
                     let form_data = match serde_json::from_str::<serde_json::Value>(&body) {
-                        Ok(deserialized_data) => serde_urlencoded::to_string(deserialized_data)?,
-                        Err(_) => body,
+                        Ok(serde_json::Value::Object(map)) => to_form_urlencoded(&map),
+                        _ => body,
                     };
 
                     req.body_mut().replace(form_data.into());
                     None
                 }
+                Encoding::MultipartFormData => {
+                    let body = body_path.render(ctx);
+                    let Ok(serde_json::Value::Object(fields)) = serde_json::from_str(&body) else {
+                        anyhow::bail!(
+                            "the body of a multipart/form-data request must be an object"
+                        );
+                    };
+                    let (content_type, form_data) = multipart::form_data(&fields, ctx.uploads());
+                    req.headers_mut().insert(
+                        reqwest::header::CONTENT_TYPE,
+                        HeaderValue::from_str(&content_type)?,
+                    );
+                    req.body_mut().replace(form_data.into());
+                    None
+                }
             }
         } else {
             None
@@ -160,12 +346,29 @@ impl RequestTemplate {
         &self,
         mut req: reqwest::Request,
         ctx: &C,
-    ) -> reqwest::Request {
+    ) -> anyhow::Result<reqwest::Request> {
+        // the headers of the template take precedence over the forwarded ones
+        req.headers_mut().extend(ctx.headers().to_owned());
         let headers = self.create_headers(ctx);
         if !headers.is_empty() {
             req.headers_mut().extend(headers);
         }
 
+        // the cookies are added to the ones of a `Cookie` header, if any
+        if let Some(cookies) = self.render_cookies(ctx) {
+            let cookies = req
+                .headers()
+                .get_all(reqwest::header::COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .chain([cookies.as_str()])
+                .collect::<Vec<_>>()
+                .join("; ");
+            let value = HeaderValue::from_str(&cookies)
+                .map_err(|_| anyhow::anyhow!("the cookies `{cookies}` aren't a valid header"))?;
+            req.headers_mut().insert(reqwest::header::COOKIE, value);
+        }
+
         let headers = req.headers_mut();
         // We want to set the header value based on encoding
         // TODO: potential of optimizations.
@@ -178,12 +381,12 @@ impl RequestTemplate {
                     Encoding::ApplicationXWwwFormUrlencoded => {
                         HeaderValue::from_static("application/x-www-form-urlencoded")
                     }
+                    Encoding::MultipartFormData => HeaderValue::from_static("multipart/form-data"),
                 },
             );
         }
 
-        headers.extend(ctx.headers().to_owned());
-        req
+        Ok(req)
     }
 
     pub fn new(root_url: &str) -> anyhow::Result<Self> {
@@ -196,6 +399,16 @@ impl RequestTemplate {
             endpoint: Endpoint::new(root_url.to_string()),
             encoding: Default::default(),
             query_encoder: Default::default(),
+            basic_auth: Default::default(),
+            cookies: Default::default(),
+            null_query_params: Default::default(),
+            parse_response: Default::default(),
+            no_content: Default::default(),
+            request_id_headers: Default::default(),
+            compress: Default::default(),
+            encode_path: true,
+            encode_query_params: Default::default(),
+            batch_key: Default::default(),
         })
     }
 
@@ -210,6 +423,105 @@ impl RequestTemplate {
     }
 }
 
+/// Replaces the body with its gzip encoding, the requests without a body are
+/// left as they are.
+fn compress_body(req: &mut reqwest::Request) -> anyhow::Result<()> {
+    let Some(body) = req.body().and_then(|body| body.as_bytes()) else {
+        return Ok(());
+    };
+    let body = compression::gzip(body)?;
+    req.headers_mut()
+        .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    // the client sets it from the compressed body
+    req.headers_mut().remove(CONTENT_LENGTH);
+    req.body_mut().replace(body.into());
+    Ok(())
+}
+
+/// The file the body is, when it's a single expression whose value is an
+/// `Upload`.
+fn upload<'a, C: PathValue + HasUploads>(body: &Mustache, ctx: &'a C) -> Option<&'a Upload> {
+    let [Segment::Expression(path)] = body.segments().as_slice() else {
+        return None;
+    };
+    match ctx.raw_value(path)? {
+        ValueString::Value(value) => ctx.uploads().find(&*value),
+        ValueString::String(_) => None,
+    }
+}
+
+/// The characters kept by `encodePath` and `encodeQueryParams`, the letters,
+/// digits and the other characters RFC 3986 leaves unreserved.
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+fn percent_encode(value: &str) -> String {
+    utf8_percent_encode(value, UNRESERVED).to_string()
+}
+
+/// Encodes the strings of a query parameter, the ones in a list too.
+fn percent_encode_value(value: ValueString<'_>) -> ValueString<'_> {
+    fn encode(value: &async_graphql::Value) -> async_graphql::Value {
+        match value {
+            async_graphql::Value::String(value) => {
+                async_graphql::Value::String(percent_encode(value))
+            }
+            async_graphql::Value::List(list) => {
+                async_graphql::Value::List(list.iter().map(encode).collect())
+            }
+            value => value.clone(),
+        }
+    }
+
+    match value {
+        ValueString::String(value) => ValueString::String(Cow::Owned(percent_encode(&value))),
+        ValueString::Value(value) => ValueString::Value(Cow::Owned(encode(&value))),
+    }
+}
+
+/// Encodes the object as an `application/x-www-form-urlencoded` body. Nested
+/// objects and lists use the bracket notation of Rails and Stripe, eg.
+/// `metadata[order]=1&items[0][sku]=a`, and the nulls are left out.
+fn to_form_urlencoded(map: &serde_json::Map<String, serde_json::Value>) -> String {
+    fn append(
+        serializer: &mut url::form_urlencoded::Serializer<'_, String>,
+        key: &str,
+        value: &serde_json::Value,
+    ) {
+        match value {
+            serde_json::Value::Null => {}
+            serde_json::Value::Bool(value) => {
+                serializer.append_pair(key, &value.to_string());
+            }
+            serde_json::Value::Number(value) => {
+                serializer.append_pair(key, &value.to_string());
+            }
+            serde_json::Value::String(value) => {
+                serializer.append_pair(key, value);
+            }
+            serde_json::Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    append(serializer, &format!("{key}[{index}]"), item);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                for (name, item) in map {
+                    append(serializer, &format!("{key}[{name}]"), item);
+                }
+            }
+        }
+    }
+
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in map {
+        append(&mut serializer, key, value);
+    }
+    serializer.finish()
+}
+
 impl TryFrom<Endpoint> for RequestTemplate {
     type Error = anyhow::Error;
     fn try_from(endpoint: Endpoint) -> anyhow::Result<Self> {
@@ -222,6 +534,7 @@ impl TryFrom<Endpoint> for RequestTemplate {
                     key: k.as_str().to_string(),
                     value: Mustache::parse(v.as_str()),
                     skip_empty: *skip,
+                    encoder: None,
                 })
             })
             .collect::<anyhow::Result<Vec<_>>>()?;
@@ -247,6 +560,16 @@ impl TryFrom<Endpoint> for RequestTemplate {
             endpoint,
             encoding,
             query_encoder: Default::default(),
+            basic_auth: Default::default(),
+            cookies: Default::default(),
+            null_query_params: Default::default(),
+            parse_response: Default::default(),
+            no_content: Default::default(),
+            request_id_headers: Default::default(),
+            compress: Default::default(),
+            encode_path: true,
+            encode_query_params: Default::default(),
+            batch_key: Default::default(),
         })
     }
 }
@@ -263,6 +586,12 @@ impl<Ctx: PathString + HasHeaders + PathValue> CacheKey<Ctx> for RequestTemplate
             mustache.render(ctx).hash(state);
         }
 
+        if let Some(basic_auth) = self.basic_auth.as_ref() {
+            basic_auth.render(ctx).hash(state);
+        }
+
+        self.render_cookies(ctx).hash(state);
+
         for (name, value) in ctx.headers().iter() {
             name.hash(state);
             value.hash(state);
@@ -272,7 +601,14 @@ impl<Ctx: PathString + HasHeaders + PathValue> CacheKey<Ctx> for RequestTemplate
             body.render(ctx).hash(state)
         }
 
-        let url = self.create_url(ctx).unwrap();
+        if let Some(batch_key) = self.batch_key.as_ref() {
+            batch_key.render(ctx).hash(state)
+        }
+
+        ctx.path_string(&["tenant", "id"]).hash(state);
+
+        // without a key, the request is made and fails with the URL's error
+        let url = self.create_url(ctx).ok()?;
         url.hash(state);
 
         Some(IoId::new(hasher.finish()))
@@ -355,12 +691,14 @@ mod tests {
     use std::borrow::Cow;
 
     use derive_setters::Setters;
-    use http::header::{HeaderMap, HeaderName};
+    use http::header::{HeaderMap, HeaderName, HeaderValue};
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
-    use super::{Query, RequestTemplate};
+    use super::{Query, QueryEncoder, RequestTemplate};
     use crate::core::has_headers::HasHeaders;
+    use crate::core::has_uploads::HasUploads;
+    use crate::core::http::{Upload, Uploads};
     use crate::core::json::JsonLike;
     use crate::core::mustache::Mustache;
     use crate::core::path::{PathString, PathValue, ValueString};
@@ -369,11 +707,16 @@ mod tests {
     struct Context {
         pub value: serde_json::Value,
         pub headers: HeaderMap,
+        pub uploads: Uploads,
     }
 
     impl Default for Context {
         fn default() -> Self {
-            Self { value: serde_json::Value::Null, headers: HeaderMap::new() }
+            Self {
+                value: serde_json::Value::Null,
+                headers: HeaderMap::new(),
+                uploads: Uploads::default(),
+            }
         }
     }
 
@@ -402,8 +745,14 @@ mod tests {
         }
     }
 
+    impl HasUploads for Context {
+        fn uploads(&self) -> &Uploads {
+            &self.uploads
+        }
+    }
+
     impl RequestTemplate {
-        fn to_body<C: PathString + HasHeaders + PathValue>(
+        fn to_body<C: PathString + HasHeaders + PathValue + HasUploads>(
             &self,
             ctx: &C,
         ) -> anyhow::Result<String> {
@@ -426,11 +775,13 @@ mod tests {
                 key: "baz".to_string(),
                 value: Mustache::parse("{{baz.id}}"),
                 skip_empty: false,
+                encoder: None,
             },
             Query {
                 key: "foo".to_string(),
                 value: Mustache::parse("{{foo.id}}"),
                 skip_empty: false,
+                encoder: None,
             },
         ];
 
@@ -514,16 +865,19 @@ mod tests {
                 key: "foo".to_string(),
                 value: Mustache::parse("0"),
                 skip_empty: false,
+                encoder: None,
             },
             Query {
                 key: "bar".to_string(),
                 value: Mustache::parse("1"),
                 skip_empty: false,
+                encoder: None,
             },
             Query {
                 key: "baz".to_string(),
                 value: Mustache::parse("2"),
                 skip_empty: false,
+                encoder: None,
             },
         ];
 
@@ -548,16 +902,19 @@ mod tests {
                 key: "foo".to_string(),
                 value: Mustache::parse("0"),
                 skip_empty: false,
+                encoder: None,
             },
             Query {
                 key: "bar".to_string(),
                 value: Mustache::parse("{{bar.id}}"),
                 skip_empty: false,
+                encoder: None,
             },
             Query {
                 key: "baz".to_string(),
                 value: Mustache::parse("{{baz.id}}"),
                 skip_empty: false,
+                encoder: None,
             },
         ];
         let tmpl = RequestTemplate::new("http://localhost:3000/")
@@ -579,6 +936,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_url_query_params_encoder() {
+        let query = vec![
+            Query {
+                key: "ids".to_string(),
+                value: Mustache::parse("{{ids}}"),
+                skip_empty: false,
+                encoder: Some(QueryEncoder::CommaSeparated),
+            },
+            Query {
+                key: "tags".to_string(),
+                value: Mustache::parse("{{tags}}"),
+                skip_empty: false,
+                encoder: None,
+            },
+        ];
+        let tmpl = RequestTemplate::new("http://localhost:3000/")
+            .unwrap()
+            .query(query);
+        let ctx = Context::default().value(json!({ "ids": [1, 2], "tags": ["a", "b"] }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(
+            req.url().to_string(),
+            "http://localhost:3000/?ids=1,2&tags=a&tags=b"
+        );
+    }
+
+    #[test]
+    fn test_url_query_params_bracket_encoder() {
+        let query = vec![Query {
+            key: "ids".to_string(),
+            value: Mustache::parse("{{ids}}"),
+            skip_empty: false,
+            encoder: None,
+        }];
+        let tmpl = RequestTemplate::new("http://localhost:3000/")
+            .unwrap()
+            .query(query)
+            .query_encoder(QueryEncoder::Bracket);
+        let ctx = Context::default().value(json!({ "ids": [1, 2] }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(
+            req.url().to_string(),
+            "http://localhost:3000/?ids[]=1&ids[]=2"
+        );
+    }
+
+    #[test]
+    fn test_url_encode_path() {
+        let tmpl = RequestTemplate::new("http://{{host}}/users/{{name}}?tag={{tag}}").unwrap();
+        let ctx = Context::default().value(json!({
+          "host": "localhost:3000",
+          "name": "a/b c?é",
+          "tag": "x y"
+        }));
+
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().url().to_string(),
+            "http://localhost:3000/users/a%2Fb%20c%3F%C3%A9?tag=x%20y"
+        );
+
+        let request_wrapper = tmpl.encode_path(false).to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().url().to_string(),
+            "http://localhost:3000/users/a/b%20c?%C3%A9?tag=x%20y"
+        );
+    }
+
+    #[test]
+    fn test_url_encode_query_params() {
+        let query = vec![
+            Query {
+                key: "name".to_string(),
+                value: Mustache::parse("{{name}}"),
+                skip_empty: false,
+                encoder: None,
+            },
+            Query {
+                key: "tags".to_string(),
+                value: Mustache::parse("{{tags}}"),
+                skip_empty: false,
+                encoder: Some(QueryEncoder::CommaSeparated),
+            },
+        ];
+        let tmpl = RequestTemplate::new("http://localhost:3000/users?q={{q}}")
+            .unwrap()
+            .query(query)
+            .encode_query_params(true);
+        let ctx = Context::default().value(json!({
+          "name": "a&b=c",
+          "tags": ["x,y", "z-1"],
+          "q": "1+1"
+        }));
+
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().url().to_string(),
+            "http://localhost:3000/users?q=1%2B1&name=a%26b%3Dc&tags=x%2Cy,z-1"
+        );
+    }
+
     #[test]
     fn test_headers() {
         let headers = vec![
@@ -628,6 +1089,128 @@ mod tests {
         assert_eq!(req.headers().get("baz").unwrap(), "2");
     }
 
+    #[test]
+    fn test_header_from_parent_value() {
+        let headers = vec![(
+            HeaderName::from_static("x-resource-version"),
+            Mustache::parse("{{value.version}}"),
+        )];
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .headers(headers);
+
+        let ctx = Context::default().value(json!({"value": {"version": 3}}));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().headers()["x-resource-version"],
+            "3"
+        );
+
+        let ctx = Context::default().value(json!({"value": {}}));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert!(!request_wrapper
+            .request()
+            .headers()
+            .contains_key("x-resource-version"));
+    }
+
+    #[test]
+    fn test_header_template_over_forwarded() {
+        let headers = vec![(
+            HeaderName::from_static("x-resource-version"),
+            Mustache::parse("{{value.version}}"),
+        )];
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .headers(headers);
+        let mut forwarded = HeaderMap::new();
+        forwarded.insert("x-resource-version", "1".parse().unwrap());
+        forwarded.insert("x-tenant", "acme".parse().unwrap());
+
+        let ctx = Context::default()
+            .value(json!({"value": {"version": 3}}))
+            .headers(forwarded.clone());
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(req.headers()["x-resource-version"], "3");
+        assert_eq!(req.headers()["x-tenant"], "acme");
+
+        // the forwarded value is kept when the template resolves to nothing
+        let ctx = Context::default().value(json!({})).headers(forwarded);
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().headers()["x-resource-version"],
+            "1"
+        );
+    }
+
+    #[test]
+    fn test_cookies() {
+        let cookies = vec![
+            ("session".to_string(), Mustache::parse("{{args.sessionId}}")),
+            ("theme".to_string(), Mustache::parse("dark")),
+        ];
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .cookies(cookies);
+        let ctx = Context::default().value(json!({
+          "args": {
+            "sessionId": "abc"
+          }
+        }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert!(!tmpl.is_const());
+        assert_eq!(
+            req.headers().get("cookie").unwrap(),
+            "session=abc; theme=dark"
+        );
+    }
+
+    #[test]
+    fn test_cookies_encoded() {
+        let cookies = vec![("session".to_string(), Mustache::parse("{{args.sessionId}}"))];
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .cookies(cookies);
+        let ctx = Context::default().value(json!({
+          "args": {
+            "sessionId": "abc; admin=true"
+          }
+        }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().headers()["cookie"],
+            "session=abc%3B%20admin%3Dtrue"
+        );
+    }
+
+    #[test]
+    fn test_cookies_appended() {
+        let cookies = vec![("theme".to_string(), Mustache::parse("dark"))];
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .cookies(cookies);
+        let mut forwarded = HeaderMap::new();
+        forwarded.insert("cookie", HeaderValue::from_static("session=abc"));
+        let ctx = Context::default().value(json!({})).headers(forwarded);
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().headers()["cookie"],
+            "session=abc; theme=dark"
+        );
+    }
+
+    #[test]
+    fn test_cookies_invalid() {
+        let cookies = vec![("the\nme".to_string(), Mustache::parse("dark"))];
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .cookies(cookies);
+        let ctx = Context::default().value(json!({}));
+        assert!(tmpl.to_request(&ctx).is_err());
+    }
+
     #[test]
     fn test_header_encoding_application_json() {
         let tmpl = RequestTemplate::new("http://localhost:3000")
@@ -708,10 +1291,115 @@ mod tests {
         assert_eq!(body, "baz");
     }
 
+    fn uploads() -> Uploads {
+        Uploads::from_iter([(
+            "0".to_string(),
+            Upload {
+                filename: "a.png".to_string(),
+                mime_type: "image/png".to_string(),
+                body: hyper::body::Bytes::from("\u{89}PNG"),
+            },
+        )])
+    }
+
+    #[test]
+    fn test_body_upload() {
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .method(reqwest::Method::PUT)
+            .body_path(Some(Mustache::parse("{{args.file}}")));
+        let ctx = Context::default().uploads(uploads()).value(json!({
+          "args": {
+            "file": {"id": "0", "filename": "a.png", "mimeType": "image/png", "size": 5}
+          }
+        }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(req.headers().get("Content-Type").unwrap(), "image/png");
+        assert_eq!(
+            req.body().and_then(|body| body.as_bytes()).unwrap(),
+            "\u{89}PNG".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_body_encoding_multipart_form_data() {
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .method(reqwest::Method::POST)
+            .encoding(crate::core::config::Encoding::MultipartFormData)
+            .body_path(Some(Mustache::parse("{{args}}")));
+        let ctx = Context::default().uploads(uploads()).value(json!({
+          "args": {
+            "avatar": {"id": "0", "filename": "a.png", "mimeType": "image/png", "size": 5},
+            "id": 1
+          }
+        }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(
+            req.headers().get("Content-Type").unwrap(),
+            "multipart/form-data; boundary=tailcall-boundary-0"
+        );
+        let body = tmpl.to_body(&ctx).unwrap();
+        assert_eq!(
+            body,
+            "--tailcall-boundary-0\r\n\
+             Content-Disposition: form-data; name=\"avatar\"; filename=\"a.png\"\r\n\
+             Content-Type: image/png\r\n\r\n\
+             \u{89}PNG\r\n\
+             --tailcall-boundary-0\r\n\
+             Content-Disposition: form-data; name=\"id\"\r\n\r\n\
+             1\r\n\
+             --tailcall-boundary-0--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_body_compress() {
+        use std::io::Read;
+
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .method(reqwest::Method::POST)
+            .headers(vec![(
+                HeaderName::from_static("content-length"),
+                Mustache::parse("13"),
+            )])
+            .compress(true)
+            .body_path(Some(Mustache::parse("{{foo}}")));
+        let ctx = Context::default().value(json!({"foo": {"bar": "baz"}}));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        let body = req.body().and_then(|body| body.as_bytes()).unwrap();
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(body)
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        assert_eq!(req.headers().get("Content-Encoding").unwrap(), "gzip");
+        assert!(req.headers().get("Content-Length").is_none());
+        assert_eq!(decoded, r#"{"bar":"baz"}"#);
+    }
+
+    #[test]
+    fn test_body_compress_without_body() {
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .compress(true);
+        let ctx = Context::default();
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+
+        assert!(req.headers().get("Content-Encoding").is_none());
+        assert!(req.body().is_none());
+    }
+
     mod endpoint {
         use http::header::HeaderMap;
         use serde_json::json;
 
+        use crate::core::config::NullQueryParams;
         use crate::core::http::request_template::tests::Context;
         use crate::core::http::RequestTemplate;
 
@@ -790,6 +1478,31 @@ mod tests {
             assert_eq!(req.url().to_string(), "http://localhost:3000/?q=1&b=1&c");
         }
 
+        #[test]
+        fn test_from_endpoint_template_omit_null_query_params() {
+            let endpoint = crate::core::endpoint::Endpoint::new(
+                "http://localhost:3000/".to_string(),
+            )
+            .query(vec![
+                ("b".to_string(), "1".to_string(), false),
+                ("c".to_string(), "{{args.c}}".to_string(), false),
+                ("d".to_string(), "{{args.d}}".to_string(), false),
+                ("e".to_string(), "{{args.e}}".to_string(), false),
+            ]);
+            let tmpl = RequestTemplate::try_from(endpoint)
+                .unwrap()
+                .null_query_params(NullQueryParams::Omit);
+            let ctx = Context::default().value(json!({
+              "args": {
+                "d": null,
+                "e": ""
+              }
+            }));
+            let request_wrapper = tmpl.to_request(&ctx).unwrap();
+            let req = request_wrapper.request();
+            assert_eq!(req.url().to_string(), "http://localhost:3000/?b=1&e=");
+        }
+
         #[test]
         fn test_from_endpoint_template_few_null_value() {
             let endpoint = crate::core::endpoint::Endpoint::new(
@@ -900,6 +1613,25 @@ mod tests {
             assert_eq!(a, e);
         }
 
+        #[test]
+        fn test_with_nested_json_body() {
+            let tmpl = RequestTemplate::form_encoded_url("http://localhost:3000")
+                .unwrap()
+                .body_path(Some(Mustache::parse("{{charge}}")));
+            let ctx = Context::default().value(json!({"charge": {
+                "amount": 2000,
+                "capture": true,
+                "description": null,
+                "metadata": {"order id": "6735"},
+                "items": [{"sku": "a"}, {"sku": "b"}],
+            }}));
+            let body = tmpl.to_body(&ctx).unwrap();
+            assert_eq!(
+                body,
+                "amount=2000&capture=true&metadata%5Border+id%5D=6735&items%5B0%5D%5Bsku%5D=a&items%5B1%5D%5Bsku%5D=b"
+            );
+        }
+
         #[test]
         fn test_with_mustache_literal() {
             let tmpl = RequestTemplate::form_encoded_url("http://localhost:3000")
@@ -971,6 +1703,23 @@ mod tests {
             ]);
         }
 
+        #[test]
+        fn test_cookies_diff() {
+            let tmpl = RequestTemplate::form_encoded_url("http://localhost:3000")
+                .unwrap()
+                .cookies(vec![(
+                    "session".to_string(),
+                    Mustache::parse("{{args.sessionId}}"),
+                )]);
+            let ctx_with_session =
+                |id| Context::default().value(json!({"args": {"sessionId": id}}));
+
+            assert_no_duplicate([
+                tmpl.cache_key(&ctx_with_session("a")),
+                tmpl.cache_key(&ctx_with_session("b")),
+            ]);
+        }
+
         #[test]
         fn test_body_diff() {
             let ctx_with_body = |value| Context::default().value(value);
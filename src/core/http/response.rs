@@ -1,5 +1,9 @@
+use std::fmt::{self, Display};
+use std::time::Duration;
+
 use anyhow::Result;
 use async_graphql_value::{ConstValue, Name};
+use chrono::{DateTime, Utc};
 use derive_setters::Setters;
 use hyper::body::Bytes;
 use hyper::Body;
@@ -8,8 +12,12 @@ use prost::Message;
 use tonic::Status;
 use tonic_types::Status as GrpcStatus;
 
+use super::{xml, Method};
+use crate::core::config::ResponseFormat;
 use crate::core::grpc::protobuf::ProtobufOperation;
 use crate::core::ir::Error;
+use crate::core::json::Projection;
+use crate::core::Type;
 
 #[derive(Clone, Debug, Default, Setters)]
 pub struct Response<Body> {
@@ -48,6 +56,108 @@ impl FromValue for ConstValue {
     }
 }
 
+/// The error of an upstream response with an error status. It keeps the body
+/// of the response, for the `ERROR` messages of `@http(onStatus:)`
+/// to read, and is displayed as the error of reqwest, which is its source.
+#[derive(Debug)]
+pub struct StatusError {
+    error: reqwest::Error,
+    pub body: Bytes,
+    /// The delay asked for with `Retry-After`, eg. with a `429` or a `503`.
+    pub retry_after: Option<Duration>,
+}
+
+impl StatusError {
+    /// Fails with a [StatusError] when the status of the response is an error.
+    pub async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+        let error = response
+            .error_for_status_ref()
+            .err()
+            .map(|error| error.without_url());
+
+        match error {
+            Some(error) => {
+                let retry_after = retry_after(response.headers(), Utc::now());
+                let body = response.bytes().await.unwrap_or_default();
+                Err(StatusError { error, body, retry_after }.into())
+            }
+            None => Ok(response),
+        }
+    }
+}
+
+/// The delay of a `Retry-After` header, given in seconds or as a date.
+fn retry_after(headers: &reqwest::header::HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    match value.parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value).ok()?;
+            Some(
+                (date.with_timezone(&Utc) - now)
+                    .to_std()
+                    .unwrap_or_default(),
+            )
+        }
+    }
+}
+
+impl Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for StatusError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// What a field resolves to when the upstream answers without content, eg.
+/// with `204 No Content`, which depends on the type of the field and on the
+/// method of the request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NoContent {
+    /// `true`, for the `Boolean` fields of the methods that change something,
+    /// eg. `DELETE`, since the request succeeded.
+    True,
+    /// `null`, for the other nullable fields.
+    #[default]
+    Null,
+    /// An error, for the other non-null fields, which can't be `null`.
+    Error,
+}
+
+impl NoContent {
+    pub fn new(type_of: &Type, method: &Method) -> Self {
+        if *method == Method::HEAD {
+            // the body of a HEAD response is replaced with its headers anyway
+            NoContent::Null
+        } else if *method != Method::GET && !type_of.is_list() && type_of.name() == "Boolean" {
+            // an empty answer to a GET doesn't tell whether the value is true
+            NoContent::True
+        } else if type_of.is_nullable() {
+            NoContent::Null
+        } else {
+            NoContent::Error
+        }
+    }
+
+    /// The value of a response without content with this status.
+    pub fn value(&self, status: reqwest::StatusCode) -> Result<ConstValue, Error> {
+        match self {
+            NoContent::True => Ok(ConstValue::Boolean(true)),
+            NoContent::Null => Ok(ConstValue::Null),
+            NoContent::Error => Err(Error::NoContent(status.as_u16())),
+        }
+    }
+}
+
 impl Response<Bytes> {
     pub async fn from_reqwest(resp: reqwest::Response) -> Result<Self> {
         let status = resp.status();
@@ -87,6 +197,65 @@ impl Response<Bytes> {
         Ok(Response { status: self.status, headers: self.headers, body })
     }
 
+    /// Parses the body in the format of `@http(parseResponse:)`.
+    pub fn to_value(self, format: ResponseFormat) -> Result<Response<ConstValue>> {
+        match format {
+            ResponseFormat::Json => self.to_json(),
+            ResponseFormat::Xml => self.to_xml(),
+        }
+    }
+
+    /// Whether the response has no content to parse, because its status is
+    /// `204 No Content` or `205 Reset Content`, or it's a JSON response with
+    /// an empty or blank body. The other formats parse their empty bodies.
+    pub fn has_no_content(&self, format: ResponseFormat) -> bool {
+        matches!(
+            self.status,
+            reqwest::StatusCode::NO_CONTENT | reqwest::StatusCode::RESET_CONTENT
+        ) || (format == ResponseFormat::Json && self.body.iter().all(u8::is_ascii_whitespace))
+    }
+
+    /// Same as [Response::to_value], but a response without content resolves
+    /// to the value of `no_content` instead of a JSON `null`.
+    pub fn to_field_value(
+        self,
+        format: ResponseFormat,
+        no_content: NoContent,
+    ) -> Result<Response<ConstValue>> {
+        if !self.has_no_content(format) {
+            return self.to_value(format);
+        }
+        let body = no_content.value(self.status)?;
+        Ok(Response { status: self.status, headers: self.headers, body })
+    }
+
+    /// Same as [Response::to_json] for an XML body, see [xml::to_value].
+    pub fn to_xml(self) -> Result<Response<ConstValue>> {
+        if self.body.is_empty() {
+            return Ok(Response {
+                status: self.status,
+                headers: self.headers,
+                body: Default::default(),
+            });
+        }
+        let body = xml::to_value(&self.body)?;
+        Ok(Response { status: self.status, headers: self.headers, body })
+    }
+
+    /// Same as [Response::to_json] but keeps only the keys of the body that
+    /// the projection selects, the others are skipped while parsing.
+    pub fn to_projected_json(self, projection: &Projection) -> Result<Response<ConstValue>> {
+        if self.body.is_empty() {
+            return Ok(Response {
+                status: self.status,
+                headers: self.headers,
+                body: Default::default(),
+            });
+        }
+        let body = projection.decode(&self.body)?;
+        Ok(Response { status: self.status, headers: self.headers, body })
+    }
+
     pub fn to_grpc_value(
         self,
         operation: &ProtobufOperation,
@@ -164,3 +333,102 @@ impl From<Response<Bytes>> for http::Response<Body> {
         response
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &'static str) -> Response<Bytes> {
+        Response {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            headers: Default::default(),
+            body: Bytes::from_static(body.as_bytes()),
+        }
+    }
+
+    fn named(name: &str, non_null: bool) -> Type {
+        Type::Named { name: name.to_string(), non_null }
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        let retry_after = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+            retry_after(&headers, now)
+        };
+
+        assert_eq!(retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            retry_after("Wed, 21 Oct 2015 07:28:30 GMT"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after("Wed, 21 Oct 2015 07:27:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after("soon"), None);
+        assert_eq!(super::retry_after(&Default::default(), now), None);
+    }
+
+    #[test]
+    fn test_has_no_content() {
+        let json = ResponseFormat::Json;
+        assert!(response(204, "").has_no_content(json));
+        assert!(response(205, "").has_no_content(json));
+        assert!(response(200, "").has_no_content(json));
+        assert!(response(200, " \n").has_no_content(json));
+        assert!(!response(200, "{}").has_no_content(json));
+
+        let xml = ResponseFormat::Xml;
+        assert!(response(204, "").has_no_content(xml));
+        assert!(!response(200, "").has_no_content(xml));
+    }
+
+    #[test]
+    fn test_no_content_new() {
+        let new = |type_of: &Type| NoContent::new(type_of, &Method::POST);
+        assert_eq!(new(&named("Boolean", true)), NoContent::True);
+        assert_eq!(new(&named("Boolean", false)), NoContent::True);
+        assert_eq!(new(&named("User", false)), NoContent::Null);
+        assert_eq!(new(&named("User", true)), NoContent::Error);
+
+        let list = Type::List { of_type: Box::new(named("Boolean", true)), non_null: true };
+        assert_eq!(new(&list), NoContent::Error);
+
+        let get = |type_of: &Type| NoContent::new(type_of, &Method::GET);
+        assert_eq!(get(&named("Boolean", false)), NoContent::Null);
+        assert_eq!(get(&named("Boolean", true)), NoContent::Error);
+        assert_eq!(
+            NoContent::new(&named("User", true), &Method::HEAD),
+            NoContent::Null
+        );
+    }
+
+    #[test]
+    fn test_to_field_value() {
+        let value = |no_content| {
+            response(204, "")
+                .to_field_value(ResponseFormat::Json, no_content)
+                .map(|response| response.body)
+        };
+
+        assert_eq!(value(NoContent::True).unwrap(), ConstValue::Boolean(true));
+        assert_eq!(value(NoContent::Null).unwrap(), ConstValue::Null);
+        assert!(matches!(
+            value(NoContent::Error).unwrap_err().downcast::<Error>(),
+            Ok(Error::NoContent(204))
+        ));
+
+        let response = response(200, r#"{"id": 1}"#)
+            .to_field_value(ResponseFormat::Json, NoContent::True)
+            .unwrap();
+        assert_eq!(
+            response.body,
+            ConstValue::from_json(serde_json::json!({"id": 1})).unwrap()
+        );
+    }
+}
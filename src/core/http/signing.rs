@@ -0,0 +1,371 @@
+//! Signs the upstream requests, either with AWS Signature Version 4 or with an
+//! HMAC of the method, the path and the body.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use http::header::{HeaderName, HeaderValue, InvalidHeaderName, AUTHORIZATION, HOST};
+use reqwest::Request;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::core::config::{self, HmacAlgorithm};
+
+const AMZ_DATE: &str = "x-amz-date";
+const AMZ_SECURITY_TOKEN: &str = "x-amz-security-token";
+const AMZ_CONTENT_SHA256: &str = "x-amz-content-sha256";
+
+/// Signs a request once it's fully rendered, right before it's sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Signer {
+    SigV4(config::SigV4Signing),
+    Hmac {
+        algorithm: HmacAlgorithm,
+        secret: String,
+        header: HeaderName,
+    },
+}
+
+impl TryFrom<&config::Signing> for Signer {
+    type Error = InvalidHeaderName;
+
+    fn try_from(value: &config::Signing) -> Result<Self, Self::Error> {
+        match value {
+            config::Signing::SigV4(sig_v4) => Ok(Signer::SigV4(sig_v4.clone())),
+            config::Signing::Hmac(hmac) => Ok(Signer::Hmac {
+                algorithm: hmac.algorithm,
+                secret: hmac.secret.clone(),
+                header: HeaderName::from_bytes(hmac.header.as_bytes())?,
+            }),
+        }
+    }
+}
+
+impl Signer {
+    pub fn sign(&self, request: &mut Request) -> anyhow::Result<()> {
+        self.sign_at(request, Utc::now())
+    }
+
+    fn sign_at(&self, request: &mut Request, now: DateTime<Utc>) -> anyhow::Result<()> {
+        match self {
+            Signer::SigV4(sig_v4) => sign_v4(sig_v4, request, now),
+            Signer::Hmac { algorithm, secret, header } => {
+                let url = request.url();
+                let path = match url.query() {
+                    Some(query) => format!("{}?{}", url.path(), query),
+                    None => url.path().to_owned(),
+                };
+                let mut message = format!("{}\n{}\n", request.method(), path).into_bytes();
+                message.extend_from_slice(body(request));
+
+                let signature = match algorithm {
+                    HmacAlgorithm::SHA256 => hmac_sha256(secret.as_bytes(), &message),
+                    HmacAlgorithm::SHA512 => hmac_sha512(secret.as_bytes(), &message),
+                };
+
+                request.headers_mut().insert(
+                    header.clone(),
+                    HeaderValue::from_str(&hex::encode(signature))?,
+                );
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn body(request: &Request) -> &[u8] {
+    request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes everything but the unreserved characters, as required by
+/// the canonical request.
+fn uri_encode(value: &[u8]) -> String {
+    urlencoding::encode_binary(value).into_owned()
+}
+
+fn sign_v4(
+    sig_v4: &config::SigV4Signing,
+    request: &mut Request,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+    let payload_hash = hex::encode(Sha256::digest(body(request)));
+
+    let headers = request.headers_mut();
+    headers.insert(AMZ_DATE, HeaderValue::from_str(&amz_date)?);
+    if let Some(token) = &sig_v4.session_token {
+        headers.insert(AMZ_SECURITY_TOKEN, HeaderValue::from_str(token)?);
+    }
+    if sig_v4.service == "s3" {
+        headers.insert(AMZ_CONTENT_SHA256, HeaderValue::from_str(&payload_hash)?);
+    }
+
+    let url = request.url();
+    let path = url
+        .path()
+        .split('/')
+        .map(|segment| uri_encode(&urlencoding::decode_binary(segment.as_bytes())))
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut query = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(key.as_bytes()), uri_encode(value.as_bytes())))
+        .collect::<Vec<_>>();
+    query.sort();
+    let query = query
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    // The Host header is only added by the client when the request is sent.
+    let mut canonical_headers = BTreeMap::new();
+    if !request.headers().contains_key(HOST) {
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (host, None) => host.unwrap_or_default().to_owned(),
+            (None, Some(_)) => String::new(),
+        };
+        canonical_headers.insert(HOST.as_str().to_owned(), host);
+    }
+    for (name, value) in request.headers() {
+        let value = value
+            .to_str()?
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        canonical_headers
+            .entry(name.as_str().to_owned())
+            .and_modify(|values: &mut String| {
+                values.push(',');
+                values.push_str(&value);
+            })
+            .or_insert_with(|| value.clone());
+    }
+
+    let signed_headers = canonical_headers
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers = canonical_headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect::<String>();
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method(),
+        path,
+        query,
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, sig_v4.region, sig_v4.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = format!("AWS4{}", sig_v4.secret_access_key);
+    let key = [date, &sig_v4.region, &sig_v4.service, "aws4_request"]
+        .iter()
+        .fold(key.into_bytes(), |key, part| {
+            hmac_sha256(&key, part.as_bytes())
+        });
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        sig_v4.access_key_id, scope, signed_headers, signature
+    );
+    request
+        .headers_mut()
+        .insert(AUTHORIZATION, HeaderValue::from_str(&authorization)?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use reqwest::Method;
+    use url::Url;
+
+    use super::*;
+
+    fn sig_v4() -> Signer {
+        Signer::SigV4(config::SigV4Signing {
+            region: "us-east-1".to_string(),
+            service: "service".to_string(),
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        })
+    }
+
+    fn request(method: Method, url: &str) -> Request {
+        Request::new(method, Url::parse(url).unwrap())
+    }
+
+    /// Signs the request at the time used by the AWS SigV4 test suite.
+    fn authorization(mut request: Request) -> String {
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        sig_v4().sign_at(&mut request, now).unwrap();
+
+        assert_eq!(request.headers()[AMZ_DATE], "20150830T123600Z");
+        request.headers()[AUTHORIZATION]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn expected(signed_headers: &str, signature: &str) -> String {
+        format!(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders={}, Signature={}",
+            signed_headers, signature
+        )
+    }
+
+    #[test]
+    fn test_sig_v4_get_vanilla() {
+        let request = request(Method::GET, "https://example.amazonaws.com/");
+        assert_eq!(
+            authorization(request),
+            expected(
+                "host;x-amz-date",
+                "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sig_v4_get_vanilla_query_order_key_case() {
+        let request = request(
+            Method::GET,
+            "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+        );
+        assert_eq!(
+            authorization(request),
+            expected(
+                "host;x-amz-date",
+                "b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sig_v4_get_utf8() {
+        let request = request(Method::GET, "https://example.amazonaws.com/ሴ");
+        assert_eq!(
+            authorization(request),
+            expected(
+                "host;x-amz-date",
+                "8318018e0b0f223aa2bbf98705b62bb787dc9c0e678f255a891fd03141be5d85"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sig_v4_get_space() {
+        let request = request(Method::GET, "https://example.amazonaws.com/example space/");
+        assert_eq!(
+            authorization(request),
+            expected(
+                "host;x-amz-date",
+                "652487583200325589f1fba4c7e578f72c47cb61beeca81406b39ddec1366741"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sig_v4_post_vanilla() {
+        let request = request(Method::POST, "https://example.amazonaws.com/");
+        assert_eq!(
+            authorization(request),
+            expected(
+                "host;x-amz-date",
+                "5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sig_v4_post_x_www_form_urlencoded() {
+        let mut request = request(Method::POST, "https://example.amazonaws.com/");
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        *request.body_mut() = Some("Param1=value1".into());
+
+        assert_eq!(
+            authorization(request),
+            expected(
+                "content-type;host;x-amz-date",
+                "ff11897932ad3f4e8b18135d722051e5ac45fc38421b1da7b9d196a0fe09473a"
+            )
+        );
+    }
+
+    #[test]
+    fn test_hmac() {
+        let sign = |algorithm| {
+            let signer = Signer::try_from(&config::Signing::Hmac(config::HmacSigning {
+                algorithm,
+                secret: "secret".to_string(),
+                header: "X-Signature".to_string(),
+            }))
+            .unwrap();
+            let mut request = request(Method::POST, "https://internal.example.com/orders?id=1");
+            *request.body_mut() = Some(r#"{"id":1}"#.into());
+            signer.sign(&mut request).unwrap();
+
+            request.headers()["x-signature"]
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            sign(HmacAlgorithm::SHA256),
+            "00222320adb4256d58de953ffc4094c9738bdf6636c6fe8f7ec88d592580372e"
+        );
+        assert_eq!(
+            sign(HmacAlgorithm::SHA512),
+            "fe22b290a7b9abf9bc240943d88192098ee15bcc8874886f8d4cafdc2a6964a8c838eed4f2d2ce8e310ab5e53836f73586bf8aeea3792bf97d8a76efec66372c"
+        );
+    }
+
+    #[test]
+    fn test_invalid_hmac_header() {
+        let signing = config::Signing::Hmac(config::HmacSigning {
+            algorithm: HmacAlgorithm::SHA256,
+            secret: "secret".to_string(),
+            header: "X Signature".to_string(),
+        });
+        assert!(Signer::try_from(&signing).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Delivers GraphQL responses as Server-Sent Events.
+
+use std::convert::Infallible;
+
+use anyhow::Result;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use hyper::header::{HeaderMap, ACCEPT, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Body, Response, StatusCode};
+
+use crate::core::jit::AnyResponse;
+
+pub const EVENT_STREAM: &str = "text/event-stream";
+
+/// Checks if the client asked for the responses to be streamed.
+pub fn is_accepted(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains(EVENT_STREAM))
+}
+
+fn event(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut event = format!("event: {name}\ndata: ").into_bytes();
+    event.extend_from_slice(data);
+    event.extend_from_slice(b"\n\n");
+    event
+}
+
+/// Sends every response as a `next` event followed by a single `complete`
+/// event once the responses end. Dropping the body, e.g. when the client
+/// disconnects, drops the stream of responses as well.
+pub fn response(responses: BoxStream<'static, AnyResponse<Vec<u8>>>) -> Result<Response<Body>> {
+    let events = responses
+        .map(|response| event("next", &response.body))
+        .chain(stream::once(async { event("complete", b"") }))
+        .map(Ok::<_, Infallible>);
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, EVENT_STREAM)
+        .header(CACHE_CONTROL, "no-cache")
+        .body(Body::wrap_stream(events))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_is_accepted() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_accepted(&headers));
+
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        assert!(!is_accepted(&headers));
+
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("text/event-stream, application/json"),
+        );
+        assert!(is_accepted(&headers));
+    }
+
+    #[tokio::test]
+    async fn test_events() {
+        let responses = stream::iter([br#"{"data":{"a":1}}"#, br#"{"data":{"a":2}}"#])
+            .map(|body| AnyResponse {
+                body: body.to_vec().into(),
+                cache_control: Default::default(),
+                is_ok: true,
+            })
+            .boxed();
+        let response = response(responses).unwrap();
+
+        assert_eq!(response.headers()[CONTENT_TYPE], EVENT_STREAM);
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            body,
+            "event: next\ndata: {\"data\":{\"a\":1}}\n\n\
+             event: next\ndata: {\"data\":{\"a\":2}}\n\n\
+             event: complete\ndata: \n\n"
+        );
+    }
+}
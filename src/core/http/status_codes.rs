@@ -0,0 +1,206 @@
+use hyper::body::Bytes;
+use serde_json::{json, Value};
+
+use crate::core::config::{self, StatusBehavior, StatusPattern};
+use crate::core::ir::Error;
+use crate::core::mustache::Mustache;
+
+/// Maps a status code of the upstream, or a class of them, to what the field
+/// resolves to.
+#[derive(Clone, Debug)]
+pub struct StatusCode {
+    pub code: StatusPattern,
+    pub behavior: StatusBehavior,
+    /// The message of the `ERROR`s, rendered with the `status` and the `body`
+    /// of the response under `response`.
+    pub message: Option<Mustache>,
+    /// The condition on the response, rendered like `message`.
+    pub when: Option<Mustache>,
+}
+
+impl From<&config::OnStatus> for StatusCode {
+    fn from(value: &config::OnStatus) -> Self {
+        Self {
+            code: value.code,
+            behavior: value.behavior,
+            message: value.message.as_deref().map(Mustache::parse),
+            when: value.when.as_deref().map(Mustache::parse),
+        }
+    }
+}
+
+impl StatusCode {
+    /// The error of the `ERROR` behavior, for the response the upstream
+    /// answered with.
+    pub fn error(&self, response: &UpstreamResponse) -> Error {
+        let status = response.status;
+        let message = match &self.message {
+            Some(message) => message.render(&response.to_value()),
+            None => format!("The upstream answered with the status {status}"),
+        };
+        Error::Status { status, message }
+    }
+
+    fn applies_to(&self, response: &UpstreamResponse) -> bool {
+        self.code.matches(response.status)
+            && self.when.as_ref().map_or(true, |when| {
+                !matches!(
+                    when.render(&response.to_value()).as_str(),
+                    "" | "false" | "null"
+                )
+            })
+    }
+}
+
+/// The status and the body of an upstream response, which the `message` and
+/// the `when` of the mappings read.
+#[derive(Clone, Debug)]
+pub struct UpstreamResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl UpstreamResponse {
+    /// The response of an error status, with the body the error kept.
+    pub fn from_error(error: &Error) -> Option<Self> {
+        match error {
+            Error::UpstreamStatus { status, body, .. } => {
+                Some(Self { status: *status, body: json_body(body) })
+            }
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        json!({"response": {"status": self.status, "body": self.body}})
+    }
+}
+
+/// The status codes mapped by a field, see `@http(onStatus:)`.
+#[derive(Clone, Debug, Default)]
+pub struct StatusCodes(Vec<StatusCode>);
+
+impl From<&[config::OnStatus]> for StatusCodes {
+    fn from(value: &[config::OnStatus]) -> Self {
+        Self(value.iter().map(StatusCode::from).collect())
+    }
+}
+
+impl StatusCodes {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether a mapping matches the status, before its `when` is read.
+    pub fn maps(&self, status: u16) -> bool {
+        self.0.iter().any(|code| code.code.matches(status))
+    }
+
+    /// The mapping of a response, the exact codes take precedence over the
+    /// classes.
+    pub fn find(&self, response: &UpstreamResponse) -> Option<&StatusCode> {
+        self.0
+            .iter()
+            .filter(|code| code.applies_to(response))
+            .min_by_key(|code| code.code.is_class())
+    }
+}
+
+/// The body of an error response, as a string when it isn't JSON.
+fn json_body(body: &Bytes) -> Value {
+    serde_json::from_slice(body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_codes(codes: Value) -> StatusCodes {
+        let codes: Vec<config::OnStatus> = serde_json::from_value(codes).unwrap();
+        StatusCodes::from(codes.as_slice())
+    }
+
+    fn response(status: u16, body: &'static str) -> UpstreamResponse {
+        let error = Error::UpstreamStatus {
+            status,
+            message: "Status code error".to_string(),
+            body: Bytes::from_static(body.as_bytes()),
+            retry_after: None,
+        };
+        UpstreamResponse::from_error(&error).unwrap()
+    }
+
+    #[test]
+    fn test_find() {
+        let status_codes = status_codes(json!([
+            {"code": "4xx", "behavior": "ERROR"},
+            {"code": 404, "behavior": "NULL"},
+            {"status": "5XX", "action": "RETRY"}
+        ]));
+        let find = |status| {
+            status_codes
+                .find(&response(status, "{}"))
+                .map(|code| code.behavior)
+        };
+
+        assert_eq!(find(404), Some(StatusBehavior::Null));
+        assert_eq!(find(409), Some(StatusBehavior::Error));
+        assert_eq!(find(503), Some(StatusBehavior::Retry));
+        assert_eq!(find(200), None);
+    }
+
+    #[test]
+    fn test_find_when() {
+        let status_codes = status_codes(json!([
+            {"code": 200, "behavior": "ERROR", "when": "{{.response.body.error}}"},
+            {"code": 404, "behavior": "NULL", "when": "{{.response.body.missing}}"},
+            {"code": "4xx", "behavior": "ERROR"}
+        ]));
+        let find = |status, body: Value| {
+            let response = UpstreamResponse { status, body };
+            status_codes.find(&response).map(|code| code.behavior)
+        };
+
+        assert_eq!(
+            find(200, json!({"error": {"message": "no such user"}})),
+            Some(StatusBehavior::Error)
+        );
+        assert_eq!(find(200, json!({"error": null})), None);
+        assert_eq!(find(200, json!({"id": 1})), None);
+        assert_eq!(
+            find(404, json!({"missing": true})),
+            Some(StatusBehavior::Null)
+        );
+        assert_eq!(
+            find(404, json!({"missing": false})),
+            Some(StatusBehavior::Error)
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let status_codes = status_codes(json!([
+            {"code": 409, "behavior": "ERROR", "message": "Conflict: {{.response.body.detail}}"},
+            {"code": "5xx", "behavior": "ERROR", "message": "{{.response.status}}: {{.response.body}}"},
+            {"code": 422, "behavior": "ERROR"}
+        ]));
+        let message = |status, body| {
+            let response = response(status, body);
+            match status_codes.find(&response).unwrap().error(&response) {
+                Error::Status { message, .. } => message,
+                error => panic!("unexpected error {error:?}"),
+            }
+        };
+
+        assert_eq!(
+            message(409, r#"{"detail": "the name is taken"}"#),
+            "Conflict: the name is taken"
+        );
+        assert_eq!(message(502, "Bad Gateway"), "502: Bad Gateway");
+        assert_eq!(
+            message(422, "{}"),
+            "The upstream answered with the status 422"
+        );
+    }
+}
@@ -10,6 +10,7 @@ use opentelemetry_semantic_conventions::trace::{
 };
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use super::operation_info::OperationInfo;
 use crate::core::blueprint::telemetry::Telemetry;
 
 static HTTP_SERVER_REQUEST_COUNT: Lazy<Counter<u64>> = Lazy::new(|| {
@@ -57,6 +58,12 @@ impl RequestCounter {
         }
     }
 
+    pub fn set_operation_info(&mut self, info: &OperationInfo) {
+        if let Some(ref mut attributes) = self.attributes {
+            attributes.extend(info.attributes());
+        }
+    }
+
     pub fn update(self, response: &Result<Response<Body>>) {
         if let Some(mut attributes) = self.attributes {
             if let Ok(response) = response {
@@ -0,0 +1,173 @@
+//! Reads the XML responses of `@http(parseResponse: XML)` as values, eg.
+//! `<user id="1"><name>Ann</name></user>` is
+//! `{"user": {"@id": "1", "name": "Ann"}}`.
+
+use anyhow::{bail, Context};
+use async_graphql_value::{ConstValue, Name};
+use indexmap::IndexMap;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// The key of the text of an element that has attributes or children.
+const TEXT_KEY: &str = "$text";
+
+struct Element {
+    name: Name,
+    fields: IndexMap<Name, ConstValue>,
+    text: String,
+}
+
+impl Element {
+    fn new(start: &BytesStart) -> anyhow::Result<Self> {
+        let name = Name::new(std::str::from_utf8(start.name().as_ref())?);
+        let mut fields = IndexMap::new();
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            let key = std::str::from_utf8(attribute.key.as_ref())?;
+            let value = attribute.unescape_value()?.into_owned();
+            fields.insert(Name::new(format!("@{key}")), ConstValue::String(value));
+        }
+        Ok(Self { name, fields, text: String::new() })
+    }
+
+    /// Adds a child, the repeated ones are turned into a list.
+    fn insert(&mut self, name: Name, value: ConstValue) {
+        match self.fields.get_mut(&name) {
+            Some(ConstValue::List(list)) => list.push(value),
+            Some(current) => {
+                let first = std::mem::take(current);
+                *current = ConstValue::List(vec![first, value]);
+            }
+            None => {
+                self.fields.insert(name, value);
+            }
+        }
+    }
+
+    fn into_value(self) -> (Name, ConstValue) {
+        let value = match (self.fields.is_empty(), self.text.is_empty()) {
+            (true, true) => ConstValue::Null,
+            (true, false) => ConstValue::String(self.text),
+            (false, text_is_empty) => {
+                let mut fields = self.fields;
+                if !text_is_empty {
+                    fields.insert(Name::new(TEXT_KEY), ConstValue::String(self.text));
+                }
+                ConstValue::Object(fields)
+            }
+        };
+        (self.name, value)
+    }
+}
+
+/// The value of an XML document, an object with its root element.
+pub fn to_value(body: &[u8]) -> anyhow::Result<ConstValue> {
+    let mut reader = Reader::from_reader(body);
+    reader.trim_text(true);
+
+    let mut open: Vec<Element> = vec![];
+    let mut root = None;
+    loop {
+        let closed = match reader.read_event()? {
+            Event::Start(start) => {
+                open.push(Element::new(&start)?);
+                None
+            }
+            Event::Empty(start) => Some(Element::new(&start)?),
+            Event::End(_) => Some(open.pop().context("an element is closed twice")?),
+            Event::Text(text) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(&text.unescape()?);
+                }
+                None
+            }
+            Event::CData(data) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(std::str::from_utf8(&data)?);
+                }
+                None
+            }
+            Event::Eof => break,
+            _ => None,
+        };
+
+        if let Some(element) = closed {
+            let (name, value) = element.into_value();
+            match open.last_mut() {
+                Some(parent) => parent.insert(name, value),
+                None if root.is_none() => {
+                    root = Some(ConstValue::Object(IndexMap::from([(name, value)])))
+                }
+                None => bail!("the document has more than one root element"),
+            }
+        }
+    }
+
+    if let Some(element) = open.last() {
+        bail!("the element `{}` isn't closed", element.name);
+    }
+    root.context("the document has no element")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn value(xml: &str) -> serde_json::Value {
+        to_value(xml.as_bytes()).unwrap().into_json().unwrap()
+    }
+
+    #[test]
+    fn test_to_value() {
+        let actual = value(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0">
+              <channel>
+                <title>News &amp; views</title>
+                <item id="1"><title>First</title></item>
+                <item id="2"><title><![CDATA[<b>Second</b>]]></title><draft/></item>
+                <link rel="self">http://example.com/rss</link>
+              </channel>
+            </rss>"#,
+        );
+
+        let expected = json!({"rss": {
+            "@version": "2.0",
+            "channel": {
+                "title": "News & views",
+                "item": [
+                    {"@id": "1", "title": "First"},
+                    {"@id": "2", "title": "<b>Second</b>", "draft": null},
+                ],
+                "link": {"@rel": "self", "$text": "http://example.com/rss"},
+            },
+        }});
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let actual = value(
+            r#"<soap:Envelope xmlns:soap="http://www.w3.org/2003/05/soap-envelope">
+              <soap:Body><m:Price xmlns:m="urn:prices">42</m:Price></soap:Body>
+            </soap:Envelope>"#,
+        );
+
+        let expected = json!({"soap:Envelope": {
+            "@xmlns:soap": "http://www.w3.org/2003/05/soap-envelope",
+            "soap:Body": {"m:Price": {"@xmlns:m": "urn:prices", "$text": "42"}},
+        }});
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_invalid_xml() {
+        let error = to_value(b"<users><user></users>").unwrap_err();
+        assert!(error.to_string().contains("users"));
+
+        let error = to_value(b"<user>").unwrap_err();
+        assert_eq!(error.to_string(), "the element `user` isn't closed");
+    }
+}
@@ -1,7 +1,7 @@
 mod keyed_discriminator;
 mod type_field_discriminator;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::{bail, Result};
 use async_graphql::Value;
@@ -18,7 +18,8 @@ use crate::core::json::{JsonLike, JsonObjectLike};
 ///
 /// There are two types of discriminators:
 ///
-/// * [KeyedDiscriminator]: Uses the keys of an object to determine its type.
+/// * [KeyedDiscriminator]: Uses the keys of an object, or the required fields
+///   of the types, to determine its type.
 /// * [TypeFieldDiscriminator]: Uses a specific field of an object to determine
 ///   its type.
 ///
@@ -64,6 +65,32 @@ impl Discriminator {
         }
     }
 
+    /// Maps the values of the type name field to the types, and sets the type
+    /// used when none matches. Only applies to [TypeFieldDiscriminator].
+    pub fn with_mapping(
+        self,
+        mapping: BTreeMap<String, String>,
+        default: Option<String>,
+    ) -> Valid<Self, String> {
+        match self {
+            Discriminator::TypeField(discriminator) => discriminator
+                .with_mapping(mapping, default)
+                .map(Self::TypeField),
+            discriminator => Valid::succeed(discriminator),
+        }
+    }
+
+    /// Enables the structural matching of the objects with the required fields
+    /// of each type. Only applies to [KeyedDiscriminator].
+    pub fn with_required_fields(self, required_fields: BTreeMap<String, BTreeSet<String>>) -> Self {
+        match self {
+            Discriminator::Keyed(discriminator) => {
+                Self::Keyed(discriminator.with_required_fields(required_fields))
+            }
+            discriminator => discriminator,
+        }
+    }
+
     /// Resolves the `__typename` for an object and inserts the value into the
     /// object.
     pub fn resolve_type(&self, value: Value) -> Result<Value> {
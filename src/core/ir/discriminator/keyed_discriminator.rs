@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::{bail, Result};
 use async_graphql::Value;
@@ -13,13 +13,17 @@ use super::TypedValue;
 /// "Foo".
 ///
 /// This discriminator is used when the type of an object can be determined by
-/// its keys.
+/// its keys. When the object isn't wrapped in a key, the type is the only one
+/// whose required fields are all present in the object, the types without
+/// required fields are never matched that way.
 #[derive(Debug, Clone, PartialEq)]
 pub struct KeyedDiscriminator {
     /// List of all types that are members of the union or interface.
     types: BTreeSet<String>,
     /// The name of `KeyedDiscriminator` is used for error reporting
     type_name: String,
+    /// The required fields of each type, used for the structural matching.
+    required_fields: BTreeMap<String, BTreeSet<String>>,
 }
 
 impl KeyedDiscriminator {
@@ -28,11 +32,47 @@ impl KeyedDiscriminator {
     /// `type_name`: The name of the type that this discriminator is applied at.
     /// `types`: The possible types that this discriminator can resolve.
     pub fn new(type_name: String, types: BTreeSet<String>) -> Valid<Self, String> {
-        let discriminator = Self { type_name, types };
+        let discriminator = Self { type_name, types, required_fields: Default::default() };
 
         Valid::succeed(discriminator)
     }
 
+    /// Enables the structural matching with the required fields of each type.
+    pub fn with_required_fields(self, required_fields: BTreeMap<String, BTreeSet<String>>) -> Self {
+        Self { required_fields, ..self }
+    }
+
+    /// Resolves the `__typename` of an object to the type whose required
+    /// fields are all present in it, skipping the types without any, which
+    /// would match every object. Returns `None` when the structural matching
+    /// is not enabled.
+    fn resolve_structural_type(&self, value: &Value) -> Result<Option<String>> {
+        let Value::Object(index_map) = value else {
+            return Ok(None);
+        };
+        if self.required_fields.is_empty() {
+            return Ok(None);
+        }
+
+        let matches: Vec<_> = self
+            .required_fields
+            .iter()
+            .filter(|(_, fields)| !fields.is_empty())
+            .filter(|(_, fields)| {
+                fields
+                    .iter()
+                    .all(|field| index_map.contains_key(field.as_str()))
+            })
+            .map(|(type_name, _)| type_name.clone())
+            .collect();
+
+        match matches.as_slice() {
+            [type_name] => Ok(Some(type_name.clone())),
+            [] => bail!("The KeyedDiscriminator(type=\"{}\") cannot discriminate the Value because it does not contain the required fields of any of the types {:?}", self.type_name, self.types),
+            _ => bail!("The KeyedDiscriminator(type=\"{}\") cannot discriminate the Value because it contains the required fields of more than one type {:?}", self.type_name, matches),
+        }
+    }
+
     /// Resolves the `__typename` for an object.
     /// If the object has more than one key, or if the key is not in the list of
     /// possible types, an error will be returned.
@@ -63,8 +103,17 @@ impl KeyedDiscriminator {
 
     /// Resolves the `__typename` for an object and inserts the value into the
     /// object.
-    pub fn resolve_and_set_type(&self, value: Value) -> Result<Value> {
-        let type_name = self.resolve_type(&value)?;
+    pub fn resolve_and_set_type(&self, mut value: Value) -> Result<Value> {
+        let type_name = match self.resolve_type(&value) {
+            Ok(type_name) => type_name,
+            Err(err) => {
+                let Some(type_name) = self.resolve_structural_type(&value)? else {
+                    return Err(err);
+                };
+                value.set_type_name(type_name)?;
+                return Ok(value);
+            }
+        };
         let mut value = match value {
             Value::Object(index_map) => {
                 // this is safe to unwrap because we already validated it in `resolve_type``
@@ -157,4 +206,72 @@ mod tests {
             "The KeyedDiscriminator(type=\"Test\") cannot discriminate the Value because it contains more than one keys."
         );
     }
+
+    #[test]
+    fn test_keyed_discriminator_structural() {
+        let types = vec![
+            "Cat".to_string(),
+            "Dog".to_string(),
+            "Bird".to_string(),
+            "Stray".to_string(),
+        ];
+        let required_fields = [
+            ("Cat", vec!["name", "meows"]),
+            ("Dog", vec!["name", "barks"]),
+            ("Bird", vec!["name", "wings"]),
+            ("Stray", vec![]),
+        ]
+        .into_iter()
+        .map(|(type_name, fields)| {
+            (
+                type_name.to_string(),
+                fields.into_iter().map(String::from).collect(),
+            )
+        })
+        .collect();
+        let discriminator =
+            KeyedDiscriminator::new("Animal".to_string(), types.into_iter().collect())
+                .to_result()
+                .unwrap()
+                .with_required_fields(required_fields);
+
+        let value = discriminator
+            .resolve_and_set_type(
+                Value::from_json(json!({ "name": "Rex", "barks": true })).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            value,
+            Value::from_json(json!({ "name": "Rex", "barks": true, "__typename": "Dog" })).unwrap()
+        );
+
+        let value = discriminator
+            .resolve_and_set_type(
+                Value::from_json(json!({ "Cat": { "name": "Tom", "meows": true } })).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            value,
+            Value::from_json(json!({ "name": "Tom", "meows": true, "__typename": "Cat" })).unwrap()
+        );
+
+        assert_eq!(
+            discriminator
+                .resolve_and_set_type(
+                    Value::from_json(json!({ "name": "Odd", "barks": true, "meows": true }))
+                        .unwrap()
+                )
+                .unwrap_err()
+                .to_string(),
+            "The KeyedDiscriminator(type=\"Animal\") cannot discriminate the Value because it contains the required fields of more than one type [\"Cat\", \"Dog\"]"
+        );
+
+        assert_eq!(
+            discriminator
+                .resolve_and_set_type(Value::from_json(json!({ "name": "Nemo" })).unwrap())
+                .unwrap_err()
+                .to_string(),
+            "The KeyedDiscriminator(type=\"Animal\") cannot discriminate the Value because it does not contain the required fields of any of the types [\"Bird\", \"Cat\", \"Dog\", \"Stray\"]"
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::{bail, Result};
 use async_graphql::Value;
@@ -14,7 +14,9 @@ use crate::core::json::JsonLike;
 /// `__typename` will resolve to "Buzz".
 ///
 /// This discriminator is used when the type of an object can be determined by
-/// a specific field.
+/// a specific field. The values of the field can be mapped to the types, i.e.
+/// `{"cat": "Cat"}`, and a default type can be used for the objects that don't
+/// resolve to any of the types.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TypeFieldDiscriminator {
     /// The field name that contains the type
@@ -23,6 +25,10 @@ pub struct TypeFieldDiscriminator {
     types: BTreeSet<String>,
     /// The name of TypeFieldDiscriminator is used for error reporting
     type_name: String,
+    /// Maps the values of the field to the types
+    mapping: BTreeMap<String, String>,
+    /// The type of the objects that don't resolve to any of the types
+    default: Option<String>,
 }
 
 impl TypeFieldDiscriminator {
@@ -36,11 +42,40 @@ impl TypeFieldDiscriminator {
         types: BTreeSet<String>,
         typename_field: String,
     ) -> Valid<Self, String> {
-        let discriminator = Self { type_name, types, typename_field };
+        let discriminator = Self {
+            type_name,
+            types,
+            typename_field,
+            mapping: Default::default(),
+            default: None,
+        };
 
         Valid::succeed(discriminator)
     }
 
+    /// Sets the mapping of the field's values to the types and the default
+    /// type. Validates that all of them are in the list of possible types.
+    pub fn with_mapping(
+        self,
+        mapping: BTreeMap<String, String>,
+        default: Option<String>,
+    ) -> Valid<Self, String> {
+        Valid::from_iter(
+            mapping.iter().chain(default.iter().map(|d| (d, d))),
+            |(_, type_name)| {
+                if self.types.contains(type_name) {
+                    Valid::succeed(())
+                } else {
+                    Valid::fail(format!(
+                        "The type `{}` of the `@discriminate` is not a member of {}",
+                        type_name, self.type_name
+                    ))
+                }
+            },
+        )
+        .map(|_| Self { mapping, default, ..self })
+    }
+
     /// Resolves the `__typename` for an object.
     pub fn resolve_type(&self, value: &Value) -> Result<String> {
         if value.is_null() {
@@ -52,6 +87,9 @@ impl TypeFieldDiscriminator {
         };
 
         let Some(value) = index_map.get(self.typename_field.as_str()) else {
+            if let Some(default) = &self.default {
+                return Ok(default.clone());
+            }
             bail!("The TypeFieldDiscriminator(type=\"{}\") cannot discriminate the Value because it does not contain the type name field `{}`", self.type_name, self.typename_field)
         };
 
@@ -59,8 +97,11 @@ impl TypeFieldDiscriminator {
             bail!("The TypeFieldDiscriminator(type=\"{}\") requires `{}` of type string, but received a different type.", self.type_name, self.typename_field)
         };
 
+        let type_name = self.mapping.get(type_name).unwrap_or(type_name);
         if self.types.contains(type_name) {
             Ok(type_name.to_string())
+        } else if let Some(default) = &self.default {
+            Ok(default.clone())
         } else {
             let types: Vec<_> = self.types.clone().into_iter().collect();
             bail!("The type `{}` is not in the list of acceptable types {:?} of TypeFieldDiscriminator(type=\"{}\")", type_name, types, self.type_name)
@@ -78,6 +119,8 @@ impl TypeFieldDiscriminator {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use async_graphql::Value;
     use serde_json::json;
     use tailcall_valid::Validator;
@@ -161,4 +204,69 @@ mod tests {
             "The type `Buzz` is not in the list of acceptable types [\"Bar\", \"Foo\"] of TypeFieldDiscriminator(type=\"Test\")"
         );
     }
+
+    #[test]
+    fn test_type_field_mapping() {
+        let types = vec!["Cat".to_string(), "Dog".to_string(), "Unknown".to_string()];
+        let discriminator = TypeFieldDiscriminator::new(
+            "Animal".to_string(),
+            types.into_iter().collect(),
+            "type".to_string(),
+        )
+        .to_result()
+        .unwrap();
+
+        let mapping = [("cat", "Cat"), ("dog", "Dog")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<_, _>>();
+
+        let mapped = discriminator
+            .clone()
+            .with_mapping(mapping.clone(), None)
+            .to_result()
+            .unwrap();
+        assert_eq!(
+            mapped
+                .resolve_type(&Value::from_json(json!({ "type": "cat" })).unwrap())
+                .unwrap(),
+            "Cat"
+        );
+        assert!(mapped
+            .resolve_type(&Value::from_json(json!({ "type": "fish" })).unwrap())
+            .is_err());
+
+        let with_default = discriminator
+            .clone()
+            .with_mapping(mapping.clone(), Some("Unknown".to_string()))
+            .to_result()
+            .unwrap();
+        assert_eq!(
+            with_default
+                .resolve_type(&Value::from_json(json!({ "type": "dog" })).unwrap())
+                .unwrap(),
+            "Dog"
+        );
+        assert_eq!(
+            with_default
+                .resolve_type(&Value::from_json(json!({ "type": "fish" })).unwrap())
+                .unwrap(),
+            "Unknown"
+        );
+        assert_eq!(
+            with_default
+                .resolve_type(&Value::from_json(json!({ "name": "Nemo" })).unwrap())
+                .unwrap(),
+            "Unknown"
+        );
+
+        let invalid = discriminator
+            .with_mapping(mapping, Some("Fish".to_string()))
+            .to_result()
+            .unwrap_err()
+            .to_string();
+        assert!(
+            invalid.contains("The type `Fish` of the `@discriminate` is not a member of Animal")
+        );
+    }
 }
@@ -1,10 +1,13 @@
 use std::fmt::Display;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_graphql::Value as ConstValue;
 use derive_more::From;
+use hyper::body::Bytes;
 use thiserror::Error;
 
+use crate::core::http::StatusError;
 use crate::core::jit::graphql_error::{Error as ExtensionError, ErrorExtensions};
 use crate::core::{auth, cache, worker, Errata};
 
@@ -12,6 +15,16 @@ use crate::core::{auth, cache, worker, Errata};
 pub enum Error {
     IO(String),
 
+    /// The upstream answered with an error status.
+    #[from(ignore)]
+    UpstreamStatus {
+        status: u16,
+        message: String,
+        body: Bytes,
+        /// The delay asked for with `Retry-After`.
+        retry_after: Option<Duration>,
+    },
+
     GRPC {
         grpc_code: i32,
         grpc_description: String,
@@ -35,6 +48,87 @@ pub enum Error {
 
     #[from(ignore)]
     Entity(String),
+
+    #[from(ignore)]
+    Tenant(String),
+
+    #[from(ignore)]
+    CircuitOpen {
+        host: String,
+        retry_after: Duration,
+    },
+
+    #[from(ignore)]
+    Timeout {
+        field: Option<String>,
+        url: String,
+        timeout: Duration,
+    },
+
+    /// The upstream answered without content to a non-null field that isn't
+    /// a `Boolean`.
+    #[from(ignore)]
+    NoContent(u16),
+
+    /// The upstream answered with a status mapped to `ERROR` in
+    /// `@http(onStatus:)`.
+    #[from(ignore)]
+    Status {
+        status: u16,
+        message: String,
+    },
+
+    /// The upstream answered with a status mapped to `RATE_LIMIT_ERROR` in
+    /// `@http(onStatus:)`.
+    #[from(ignore)]
+    RateLimited {
+        url: String,
+        status: u16,
+    },
+}
+
+impl Error {
+    /// The status of the upstream response that failed the request.
+    pub fn upstream_status(&self) -> Option<u16> {
+        match self {
+            Error::UpstreamStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// The delay the upstream asked for before the request is sent again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::UpstreamStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Same as [Error::IO], unless the error comes from an upstream response
+    /// with an error status, which is kept.
+    fn from_io(error: &anyhow::Error) -> Self {
+        let status = error.chain().find_map(|cause| {
+            cause
+                .downcast_ref::<reqwest::Error>()
+                .and_then(|error| error.status())
+        });
+        let status_error = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<StatusError>());
+        let body = status_error
+            .map(|error| error.body.clone())
+            .unwrap_or_default();
+        let retry_after = status_error.and_then(|error| error.retry_after);
+        match status {
+            Some(status) => Error::UpstreamStatus {
+                status: status.as_u16(),
+                message: error.to_string(),
+                body,
+                retry_after,
+            },
+            None => Error::IO(error.to_string()),
+        }
+    }
 }
 
 impl Display for Error {
@@ -46,7 +140,9 @@ impl Display for Error {
 impl From<Error> for Errata {
     fn from(value: Error) -> Self {
         match value {
-            Error::IO(message) => Errata::new("IOException").description(message),
+            Error::IO(message) | Error::UpstreamStatus { message, .. } => {
+                Errata::new("IOException").description(message)
+            }
             Error::GRPC {
                 grpc_code,
                 grpc_description,
@@ -67,7 +163,18 @@ impl From<Error> for Errata {
             }
             Error::Worker(err) => Errata::new("Worker Error").description(err.to_string()),
             Error::Cache(err) => Errata::new("Cache Error").description(err.to_string()),
-            Error::Entity(message) => Errata::new("Entity Resolver Error").description(message)
+            Error::Entity(message) => Errata::new("Entity Resolver Error").description(message),
+            Error::Tenant(message) => Errata::new("Tenant Error").description(message),
+            Error::CircuitOpen { host, retry_after } => Errata::new("Circuit Breaker Open")
+                .description(format!("Requests to `{host}` are rejected because the upstream keeps failing, retry in {}s", retry_after.as_secs())),
+            Error::Timeout { url, timeout, .. } => Errata::new("Upstream Timeout")
+                .description(format!("Request to `{url}` didn't complete within {}ms", timeout.as_millis())),
+            Error::NoContent(status) => Errata::new("No Content").description(format!(
+                "The upstream answered with the status {status} and no body, which a non-null field can't resolve to"
+            )),
+            Error::Status { message, .. } => Errata::new(&message),
+            Error::RateLimited { url, status } => Errata::new("Rate Limited")
+                .description(format!("Request to `{url}` was answered with the status {status}")),
         }
     }
 }
@@ -87,6 +194,22 @@ impl ErrorExtensions for Error {
                 e.set("grpcStatusMessage", grpc_status_message);
                 e.set("grpcStatusDetails", grpc_status_details.clone());
             }
+            if let Error::Timeout { field, url, .. } = self {
+                e.set("code", "TIMEOUT");
+                if let Some(field) = field {
+                    e.set("field", field);
+                }
+                e.set("upstreamUrl", url);
+            }
+            if let Error::Status { status, .. } = self {
+                e.set("code", "UPSTREAM_ERROR");
+                e.set("status", i32::from(*status));
+            }
+            if let Error::RateLimited { url, status } = self {
+                e.set("code", "RATE_LIMITED");
+                e.set("status", i32::from(*status));
+                e.set("upstreamUrl", url);
+            }
         })
     }
 }
@@ -107,7 +230,7 @@ impl From<Arc<anyhow::Error>> for Error {
     fn from(error: Arc<anyhow::Error>) -> Self {
         match error.downcast_ref::<Error>() {
             Some(err) => err.clone(),
-            None => Error::IO(error.to_string()),
+            None => Error::from_io(&error),
         }
     }
 }
@@ -119,7 +242,7 @@ impl From<anyhow::Error> for Error {
     fn from(value: anyhow::Error) -> Self {
         match value.downcast::<Error>() {
             Ok(err) => err,
-            Err(err) => Error::IO(err.to_string()),
+            Err(err) => Error::from_io(&err),
         }
     }
 }
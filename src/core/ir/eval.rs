@@ -118,6 +118,22 @@ impl IR {
                     .eval(ctx)
                     .await
                     .and_then(|value| Ok(discriminator.resolve_type(value)?)),
+                IR::ByOperation { query, mutation, subscription } => {
+                    if ctx.is_query() {
+                        query.eval(ctx).await
+                    } else if ctx.is_subscription() {
+                        subscription.eval(ctx).await
+                    } else {
+                        mutation.eval(ctx).await
+                    }
+                }
+                IR::When(condition, expr) => {
+                    if condition.render(ctx).trim() == "true" {
+                        expr.eval(ctx).await
+                    } else {
+                        Ok(ConstValue::Null)
+                    }
+                }
                 IR::Entity(map) => {
                     let representations = ctx.path_arg(&["representations"]);
 
@@ -237,4 +253,122 @@ mod tests {
             assert_eq!(actual, expected);
         }
     }
+
+    mod when {
+        use super::*;
+        use crate::core::blueprint::{Blueprint, DynamicValue};
+        use crate::core::http::RequestContext;
+        use crate::core::ir::EmptyResolverContext;
+        use crate::core::Mustache;
+
+        async fn eval_when(condition: &str) -> ConstValue {
+            let value = IR::Dynamic(DynamicValue::Value(ConstValue::from(42)));
+            let ir = IR::When(Mustache::parse(condition), Box::new(value));
+            let runtime = crate::cli::runtime::init(&Blueprint::default());
+            let req_ctx = RequestContext::new(runtime);
+            let res_ctx = EmptyResolverContext {};
+            let mut eval_ctx = EvalContext::new(&req_ctx, &res_ctx);
+
+            ir.eval(&mut eval_ctx).await.unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_when() {
+            assert_eq!(eval_when("true").await, ConstValue::from(42));
+            assert_eq!(eval_when("false").await, ConstValue::Null);
+            assert_eq!(eval_when("{{.args.missing}}").await, ConstValue::Null);
+        }
+    }
+
+    mod by_operation {
+        use async_graphql::{Name, ServerError, Value};
+
+        use super::*;
+        use crate::core::blueprint::{Blueprint, DynamicValue};
+        use crate::core::http::RequestContext;
+        use crate::core::ir::{EmptyResolverContext, SelectionField};
+
+        #[derive(Clone)]
+        struct QueryContext;
+
+        impl ResolverContextLike for QueryContext {
+            fn value(&self) -> Option<&Value> {
+                None
+            }
+
+            fn args(&self) -> Option<&IndexMap<Name, Value>> {
+                None
+            }
+
+            fn field(&self) -> Option<SelectionField> {
+                None
+            }
+
+            fn is_query(&self) -> bool {
+                true
+            }
+
+            fn add_error(&self, _: ServerError) {}
+        }
+
+        #[derive(Clone)]
+        struct SubscriptionContext;
+
+        impl ResolverContextLike for SubscriptionContext {
+            fn value(&self) -> Option<&Value> {
+                None
+            }
+
+            fn args(&self) -> Option<&IndexMap<Name, Value>> {
+                None
+            }
+
+            fn field(&self) -> Option<SelectionField> {
+                None
+            }
+
+            fn is_query(&self) -> bool {
+                false
+            }
+
+            fn is_subscription(&self) -> bool {
+                true
+            }
+
+            fn add_error(&self, _: ServerError) {}
+        }
+
+        fn by_operation() -> IR {
+            IR::ByOperation {
+                query: Box::new(IR::Dynamic(DynamicValue::Value(ConstValue::from("GET")))),
+                mutation: Box::new(IR::Dynamic(DynamicValue::Value(ConstValue::from("POST")))),
+                subscription: Box::new(IR::Dynamic(DynamicValue::Value(ConstValue::from("POLL")))),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_by_operation() {
+            let ir = by_operation();
+            let runtime = crate::cli::runtime::init(&Blueprint::default());
+            let req_ctx = RequestContext::new(runtime);
+
+            let mut eval_ctx = EvalContext::new(&req_ctx, &QueryContext);
+            assert_eq!(
+                ir.eval(&mut eval_ctx).await.unwrap(),
+                ConstValue::from("GET")
+            );
+
+            let mut eval_ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+            assert_eq!(
+                ir.eval(&mut eval_ctx).await.unwrap(),
+                ConstValue::from("POST")
+            );
+
+            let mut eval_ctx = EvalContext::new(&req_ctx, &SubscriptionContext);
+            assert_eq!(
+                ir.eval(&mut eval_ctx).await.unwrap(),
+                ConstValue::from("POLL")
+            );
+        }
+    }
 }
@@ -6,8 +6,10 @@ use async_graphql::{ServerError, Value};
 use http::header::HeaderMap;
 
 use super::{GraphQLOperationContext, RelatedFields, ResolverContextLike, SelectionField};
+use crate::core::blueprint::Tenant;
 use crate::core::document::print_directives;
 use crate::core::http::RequestContext;
+use crate::core::json::{JsonLike, Projection};
 
 // TODO: rename to ResolverContext
 #[derive(Clone)]
@@ -43,6 +45,10 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
         self.graphql_ctx.is_query()
     }
 
+    pub fn is_subscription(&self) -> bool {
+        self.graphql_ctx.is_subscription()
+    }
+
     pub fn new(req_ctx: &'a RequestContext, graphql_ctx: &'a Ctx) -> EvalContext<'a, Ctx> {
         Self {
             request_ctx: req_ctx,
@@ -103,9 +109,46 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
         &self.request_ctx.server.vars
     }
 
+    /// Name of the field that is resolved, as it's written in the response.
+    pub fn field_name(&self) -> Option<String> {
+        self.graphql_ctx
+            .field()
+            .map(|field| field.name().to_string())
+    }
+
     pub fn add_error(&self, error: ServerError) {
         self.graphql_ctx.add_error(error)
     }
+
+    /// See [ResolverContextLike::projection].
+    pub fn projection(&self) -> Option<Projection> {
+        self.graphql_ctx.projection()
+    }
+
+    /// The id of the tenant of the request, see `@server(tenant:)`. The ids
+    /// that aren't a DNS label are left out, see [Tenant::is_valid_id].
+    pub fn tenant_id(&self) -> Option<String> {
+        let tenant = self.request_ctx.server.tenant.as_ref()?;
+        let id = tenant.id.render(self);
+        Tenant::is_valid_id(&id).then_some(id)
+    }
+
+    /// A value of the request context, see `@server(context:)`, eg.
+    /// `["flags", "beta"]` for `{{.ctx.flags.beta}}`.
+    pub fn context_value<T: AsRef<str>>(&self, path: &[T]) -> Option<&'a Value> {
+        let (key, path) = path.split_first()?;
+        self.request_ctx
+            .context_values
+            .get(key.as_ref())?
+            .get_path(path)
+    }
+
+    /// The fields the query selects on the resolved value, written as set by
+    /// `@upstream(selectedFields:)`, eg. `id,name`.
+    pub fn selected_fields(&self) -> Option<String> {
+        let selection = self.graphql_ctx.selection()?;
+        Some(self.request_ctx.upstream.selected_fields.format(&selection))
+    }
 }
 
 impl<Ctx: ResolverContextLike> GraphQLOperationContext for EvalContext<'_, Ctx> {
@@ -1,23 +1,28 @@
 use std::sync::Arc;
 
 use async_graphql::from_value;
+use async_graphql_value::{ConstValue, Name};
+use hyper::body::Bytes;
+use indexmap::IndexMap;
+use reqwest::header::{HeaderMap, HeaderName};
 use reqwest::Request;
 use tailcall_valid::Validator;
 
 use super::model::DataLoaderId;
 use super::request::DynamicRequest;
 use super::{EvalContext, ResolverContextLike};
+use crate::core::config::ResponseFormat;
 use crate::core::data_loader::{DataLoader, Loader};
 use crate::core::grpc::protobuf::ProtobufOperation;
 use crate::core::grpc::request::execute_grpc_request;
 use crate::core::grpc::request_template::RenderedRequestTemplate;
 use crate::core::http::{
-    cache_policy, DataLoaderRequest, HttpDataLoader, RequestTemplate, Response,
+    cache_policy, DataLoaderRequest, HttpDataLoader, RequestTemplate, Response, X_REQUEST_ID,
 };
 use crate::core::ir::Error;
-use crate::core::json::JsonLike;
+use crate::core::json::{JsonLike, Projection};
 use crate::core::worker_hooks::WorkerHooks;
-use crate::core::{grpc, http, worker, WorkerIO};
+use crate::core::{grpc, http, worker, HttpIO, WorkerIO};
 
 pub struct WorkerContext<'a> {
     pub worker: &'a Arc<dyn WorkerIO<worker::Event, worker::Command>>,
@@ -47,6 +52,8 @@ pub struct EvalHttp<'a, 'ctx, Context: ResolverContextLike + Sync> {
     evaluation_ctx: &'ctx EvalContext<'a, Context>,
     data_loader: Option<&'a DataLoader<DataLoaderRequest, HttpDataLoader>>,
     request_template: &'a http::RequestTemplate,
+    verify_ssl: bool,
+    signer: Option<&'a http::Signer>,
 }
 
 impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context> {
@@ -66,7 +73,24 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
             None
         };
 
-        Self { evaluation_ctx, data_loader, request_template }
+        Self {
+            evaluation_ctx,
+            data_loader,
+            request_template,
+            verify_ssl: true,
+            signer: None,
+        }
+    }
+
+    /// Sends the request without verifying the TLS certificates when `false`.
+    pub fn verify_ssl(self, verify_ssl: bool) -> Self {
+        Self { verify_ssl, ..self }
+    }
+
+    /// Signs the request after it's rendered and possibly rewritten by the
+    /// `onRequest` hook.
+    pub fn signer(self, signer: Option<&'a http::Signer>) -> Self {
+        Self { signer, ..self }
     }
 
     pub fn init_request(&self) -> Result<DynamicRequest<String>, Error> {
@@ -76,14 +100,38 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
 
     pub async fn execute(
         &self,
-        req: DynamicRequest<String>,
+        mut req: DynamicRequest<String>,
     ) -> Result<Response<async_graphql::Value>, Error> {
         let ctx = &self.evaluation_ctx;
         let dl = &self.data_loader;
+        let names = &self.request_template.request_id_headers;
+        set_request_id(ctx, names, req.request_mut().headers_mut());
         let response = if dl.is_some() {
+            // the data loader signs the request it eventually sends
             execute_request_with_dl(ctx, req, self.data_loader).await?
         } else {
-            execute_raw_request(ctx, req).await?
+            if let Some(signer) = self.signer {
+                signer.sign(req.request_mut())?;
+            }
+            let response = ctx
+                .request_ctx
+                .runtime
+                .http_client(self.verify_ssl)
+                .execute(req.into_request())
+                .await
+                .map_err(Error::from)?;
+            match self.projection(&response) {
+                Some(projection) => response.to_projected_json(&projection)?,
+                None => response.to_field_value(
+                    self.request_template.parse_response,
+                    self.request_template.no_content,
+                )?,
+            }
+        };
+        let response = if self.request_template.method == reqwest::Method::HEAD {
+            head_response(response)
+        } else {
+            response
         };
 
         if ctx.request_ctx.server.get_enable_http_validation() {
@@ -100,6 +148,24 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
         Ok(response)
     }
 
+    /// The projection the body is decoded with, instead of being parsed whole,
+    /// when it's larger than `@upstream(streamingThreshold:)` and the keys
+    /// the query reads are known. The whole value is needed when the response
+    /// is validated against the output schema, and JSON is the only format
+    /// that can be decoded that way.
+    fn projection(&self, response: &Response<Bytes>) -> Option<Projection> {
+        let request_ctx = self.evaluation_ctx.request_ctx;
+        let threshold = request_ctx.upstream.streaming_threshold?;
+        if response.body.len() <= threshold
+            || self.request_template.parse_response != ResponseFormat::Json
+            || self.request_template.method == reqwest::Method::HEAD
+            || request_ctx.server.get_enable_http_validation()
+        {
+            return None;
+        }
+        self.evaluation_ctx.projection()
+    }
+
     #[async_recursion::async_recursion]
     pub async fn execute_with_worker<'worker: 'async_recursion>(
         &self,
@@ -161,9 +227,11 @@ pub async fn execute_request_with_dl<
         .map(|s| s.headers)
         .unwrap_or_default();
 
+    let batch_key = req.batch_key().cloned();
     let (req, batching_value) = req.into_parts();
-    let endpoint_key =
-        crate::core::http::DataLoaderRequest::new(req, headers).with_batching_value(batching_value);
+    let endpoint_key = crate::core::http::DataLoaderRequest::new(req, headers)
+        .with_batching_value(batching_value)
+        .with_batch_key(batch_key);
 
     Ok(data_loader
         .unwrap()
@@ -173,6 +241,47 @@ pub async fn execute_request_with_dl<
         .unwrap_or_default())
 }
 
+/// A HEAD response has no body, its value is made of the headers of the
+/// response instead, eg. `{"headers": {"content-length": "42"}}`. The values of
+/// a repeated header are joined with `, `.
+fn head_response(response: Response<async_graphql::Value>) -> Response<async_graphql::Value> {
+    let mut headers: IndexMap<Name, String> = IndexMap::new();
+    for (name, value) in response.headers.iter() {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        headers
+            .entry(Name::new(name.as_str()))
+            .and_modify(|values| {
+                values.push_str(", ");
+                values.push_str(&value);
+            })
+            .or_insert_with(|| value.into_owned());
+    }
+    let headers = headers
+        .into_iter()
+        .map(|(name, value)| (name, ConstValue::String(value)))
+        .collect();
+    let body = ConstValue::Object(IndexMap::from([(
+        Name::new("headers"),
+        ConstValue::Object(headers),
+    )]));
+
+    Response { body, ..response }
+}
+
+/// Tags the upstream request with the id of the incoming one, in
+/// `x-request-id` and in the headers of `names`.
+pub fn set_request_id<Ctx: ResolverContextLike>(
+    ctx: &EvalContext<'_, Ctx>,
+    names: &[HeaderName],
+    headers: &mut HeaderMap,
+) {
+    let request_id = ctx.request_ctx.request_id.header_value();
+    for name in names {
+        headers.insert(name.clone(), request_id.clone());
+    }
+    headers.insert(X_REQUEST_ID, request_id);
+}
+
 pub fn set_headers<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     res: &Response<async_graphql::Value>,
@@ -209,14 +318,11 @@ fn set_cookie_headers<Ctx: ResolverContextLike>(
     }
 }
 
-pub async fn execute_raw_request<Ctx: ResolverContextLike>(
-    ctx: &EvalContext<'_, Ctx>,
+pub async fn execute_raw_request(
+    http: &Arc<dyn HttpIO>,
     req: DynamicRequest<String>,
 ) -> Result<Response<async_graphql::Value>, Error> {
-    let response = ctx
-        .request_ctx
-        .runtime
-        .http
+    let response = http
         .execute(req.into_request())
         .await
         .map_err(Error::from)?
@@ -1,19 +1,44 @@
+use std::time::Duration;
+
+use async_graphql::ServerError;
 use async_graphql_value::ConstValue;
 
 use super::eval_http::{
     execute_grpc_request_with_dl, execute_raw_grpc_request, execute_raw_request,
-    execute_request_with_dl, parse_graphql_response, set_headers, EvalHttp, WorkerContext,
+    execute_request_with_dl, parse_graphql_response, set_headers, set_request_id, EvalHttp,
+    WorkerContext,
 };
 use super::model::{CacheKey, IO};
 use super::{DynamicRequest, EvalContext, ResolverContextLike};
-use crate::core::config::GraphQLOperationType;
+use crate::core::config::{GraphQLOperationType, StatusBehavior};
 use crate::core::data_loader::DataLoader;
-use crate::core::graphql::GraphqlDataLoader;
+use crate::core::graphql::{GraphqlDataLoader, RootFieldRequest};
 use crate::core::grpc;
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::DataLoaderRequest;
+use crate::core::http::{is_failure, DataLoaderRequest, Response, UpstreamResponse};
 use crate::core::ir::Error;
 
+/// Times a request answered with a status mapped to `RETRY` is sent again.
+const MAX_RETRIES: usize = 2;
+
+/// Delay before the first retry, doubled for each of the next ones.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Longest `Retry-After` waited for, the field fails instead of retrying when
+/// the upstream asks for a longer one.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+/// The URL of a request as shown in the errors, without the userinfo and the
+/// query, which can hold credentials.
+fn redacted_url(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.set_query(None);
+    url.set_fragment(None);
+    url.to_string()
+}
+
 pub async fn eval_io<Ctx>(io: &IO, ctx: &mut EvalContext<'_, Ctx>) -> Result<ConstValue, Error>
 where
     Ctx: ResolverContextLike + Sync,
@@ -45,39 +70,188 @@ where
     Ctx: ResolverContextLike + Sync,
 {
     match io {
-        IO::Http { req_template, dl_id, hook, .. } => {
+        IO::Http {
+            req_template,
+            dl_id,
+            hook,
+            circuit_breaker,
+            verify_ssl,
+            signer,
+            timeout,
+            paginate,
+            map_response_body,
+            status_codes,
+            ..
+        } => {
             let event_worker = &ctx.request_ctx.runtime.cmd_worker;
             let js_worker = &ctx.request_ctx.runtime.worker;
-            let eval_http = EvalHttp::new(ctx, req_template, dl_id);
+            let eval_http = EvalHttp::new(ctx, req_template, dl_id)
+                .verify_ssl(*verify_ssl)
+                .signer(signer.as_ref());
+            let tenant = ctx
+                .request_ctx
+                .server
+                .tenant
+                .as_ref()
+                .filter(|_| req_template.uses_tenant());
+            if tenant.is_some() && ctx.tenant_id().is_none() {
+                return Err(Error::Tenant("The request has no valid tenant".to_string()));
+            }
             let request = eval_http.init_request()?;
-            let response = match (&event_worker, js_worker, hook) {
-                (Some(worker), Some(js_worker), Some(hook)) => {
-                    let worker_ctx = WorkerContext::new(worker, js_worker, hook);
-                    eval_http.execute_with_worker(request, worker_ctx).await?
+            if let Some(tenant) = tenant {
+                let url = request.request().url();
+                if !tenant.is_allowed(url) {
+                    let origin = url.origin().ascii_serialization();
+                    return Err(Error::Tenant(format!(
+                        "`{origin}` is not one of the allowedHosts of the tenant"
+                    )));
+                }
+            }
+            let host = {
+                let url = request.request().url();
+                let port = url.port_or_known_default().unwrap_or_default();
+                format!("{}:{port}", url.host_str().unwrap_or_default())
+            };
+            let url = (timeout.is_some() || !status_codes.is_empty())
+                .then(|| redacted_url(request.request().url()));
+
+            let ctx = &*ctx;
+            // The fields batched by a data loader check the circuit here as
+            // well, before asking the loader for their key, and each records
+            // the outcome of the request it shared.
+            let acquire = || {
+                circuit_breaker
+                    .as_ref()
+                    .map(|settings| ctx.request_ctx.circuit_breakers.acquire(&host, settings))
+                    .transpose()
+            };
+            let mut permit = acquire()?;
+            let url = &url;
+            let eval_http = &eval_http;
+            let send = |request| async move {
+                match (&event_worker, js_worker, hook) {
+                    (Some(worker), Some(js_worker), Some(hook)) => {
+                        let worker_ctx = WorkerContext::new(worker, js_worker, hook);
+                        eval_http.execute_with_worker(request, worker_ctx).await
+                    }
+                    _ => eval_http.execute(request).await,
+                }
+            };
+            let run = |request| async move {
+                let execute = async {
+                    match paginate {
+                        Some(paginate) => {
+                            let pages = paginate.execute(request, send).await?;
+                            if let Some(warning) = pages.warning {
+                                ctx.add_error(ServerError::new(warning, None));
+                            }
+                            Ok(pages.response)
+                        }
+                        None => send(request).await,
+                    }
+                };
+                match timeout {
+                    Some(timeout) => match tokio::time::timeout(*timeout, execute).await {
+                        Ok(response) => response,
+                        Err(_) => {
+                            let url = url.clone().unwrap_or_default();
+                            Err(Error::Timeout { field: ctx.field_name(), url, timeout: *timeout })
+                        }
+                    },
+                    None => execute.await,
                 }
-                _ => eval_http.execute(request).await?,
             };
 
-            Ok(response.body)
+            // The successful responses are mapped as well, so that a `when` can
+            // turn a 2xx with an error in its body into an error.
+            let action = |response: &Result<Response<ConstValue>, Error>| {
+                let response = match response {
+                    Ok(res) if status_codes.maps(res.status.as_u16()) => UpstreamResponse {
+                        status: res.status.as_u16(),
+                        body: res.body.clone().into_json().unwrap_or_default(),
+                    },
+                    Ok(_) => return None,
+                    Err(error) => UpstreamResponse::from_error(error)?,
+                };
+                let code = status_codes.find(&response)?;
+                Some((code.behavior, code, response))
+            };
+            let mut response = run(request).await;
+            let mut backoff = RETRY_BACKOFF;
+            for _ in 0..MAX_RETRIES {
+                if !matches!(action(&response), Some((StatusBehavior::Retry, _, _))) {
+                    break;
+                }
+                let delay = match response.as_ref().err().and_then(Error::retry_after) {
+                    Some(delay) if delay > MAX_RETRY_AFTER => break,
+                    Some(delay) => delay.max(backoff),
+                    None => backoff,
+                };
+                // Every attempt is recorded, so that an upstream which keeps
+                // failing opens the circuit while it's being retried.
+                if let Some(permit) = permit.take() {
+                    permit.record(!is_failure(&response));
+                }
+                tokio::time::sleep(delay).await;
+                backoff *= 2;
+                permit = acquire()?;
+                response = run(eval_http.init_request()?).await;
+            }
+
+            if let Some(permit) = permit {
+                permit.record(!is_failure(&response));
+            }
+
+            let response = match (action(&response), response) {
+                (Some((StatusBehavior::Null, _, _)), _) => return Ok(ConstValue::Null),
+                (Some((StatusBehavior::Error, code, response)), _) => Err(code.error(&response)),
+                (Some((StatusBehavior::RateLimitError, _, response)), _) => {
+                    let url = url.clone().unwrap_or_default();
+                    Err(Error::RateLimited { url, status: response.status })
+                }
+                (_, response) => response,
+            };
+
+            let body = response?.body;
+            match map_response_body {
+                Some(path) => path
+                    .select(body)
+                    .map_err(|e| Error::Deserialize(e.to_string())),
+                None => Ok(body),
+            }
         }
-        IO::GraphQL { req_template, field_name, dl_id, .. } => {
-            let req = req_template.to_request(ctx)?;
-            let request = DynamicRequest::new(req);
-            let res = if ctx.request_ctx.upstream.batch.is_some()
-                && matches!(req_template.operation_type, GraphQLOperationType::Query)
-            {
+        IO::GraphQL { req_template, field_name, batch, dl_id, .. } => {
+            let mut req = req_template.to_request(ctx)?;
+            set_request_id(ctx, &[], req.headers_mut());
+            let is_query = matches!(req_template.operation_type, GraphQLOperationType::Query);
+            let merge_loader = ctx
+                .request_ctx
+                .gql_merge_loader
+                .as_ref()
+                .filter(|_| is_query && !batch);
+            let res = if let Some(data_loader) = merge_loader {
+                let key =
+                    RootFieldRequest::new(&req, field_name, req_template.render_root_field(ctx));
+                data_loader
+                    .load_one(key)
+                    .await
+                    .map_err(Error::from)?
+                    .unwrap_or_default()
+            } else if ctx.request_ctx.upstream.batch.is_some() && is_query {
+                let request = DynamicRequest::new(req);
                 let data_loader: Option<&DataLoader<DataLoaderRequest, GraphqlDataLoader>> =
                     dl_id.and_then(|dl| ctx.request_ctx.gql_data_loaders.get(dl.as_usize()));
                 execute_request_with_dl(ctx, request, data_loader).await?
             } else {
-                execute_raw_request(ctx, request).await?
+                execute_raw_request(&ctx.request_ctx.runtime.http, DynamicRequest::new(req)).await?
             };
 
             set_headers(ctx, &res);
             parse_graphql_response(ctx, res, field_name)
         }
         IO::Grpc { req_template, dl_id, hook, .. } => {
-            let rendered = req_template.render(ctx)?;
+            let mut rendered = req_template.render(ctx)?;
+            set_request_id(ctx, &[], &mut rendered.headers);
             let worker = &ctx.request_ctx.runtime.worker;
 
             let res = if ctx.request_ctx.upstream.batch.is_some() &&
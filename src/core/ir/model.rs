@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::num::NonZeroU64;
+use std::time::Duration;
 
 use async_graphql::Value;
 use strum_macros::Display;
@@ -10,8 +11,9 @@ use super::{EvalContext, ResolverContextLike};
 use crate::core::blueprint::{Auth, DynamicValue};
 use crate::core::config::group_by::GroupBy;
 use crate::core::graphql::{self};
+use crate::core::json::JsonPath;
 use crate::core::worker_hooks::WorkerHooks;
-use crate::core::{grpc, http};
+use crate::core::{grpc, http, Mustache};
 
 #[derive(Clone, Debug, Display)]
 pub enum IR {
@@ -28,6 +30,16 @@ pub enum IR {
     /// Merges the result of multiple IRs together
     Merge(Vec<IR>),
     Discriminate(Discriminator, Box<IR>),
+    /// Evaluates the IR only when the condition renders to `true`, otherwise
+    /// resolves to null
+    When(Mustache, Box<IR>),
+    /// Evaluates `query`, `mutation` or `subscription` depending on the
+    /// operation the field is resolved as part of
+    ByOperation {
+        query: Box<IR>,
+        mutation: Box<IR>,
+        subscription: Box<IR>,
+    },
     /// Apollo Federation _entities resolver
     Entity(HashMap<String, IR>),
     /// Apollo Federation _service resolver
@@ -50,6 +62,20 @@ pub enum IO {
         is_list: bool,
         dedupe: bool,
         hook: Option<WorkerHooks>,
+        circuit_breaker: Option<http::CircuitBreaker>,
+        verify_ssl: bool,
+        signer: Option<http::Signer>,
+        /// Fails the request with [super::Error::Timeout] when it takes longer.
+        timeout: Option<Duration>,
+        /// Body of the batched request that carries the keys of all the
+        /// requests in `{{.batch.keys}}`.
+        batch_body: Option<DynamicValue<Value>>,
+        /// Follows the next pages and resolves to the items of all of them.
+        paginate: Option<http::Paginate>,
+        /// Picks the part of the response body the field resolves to.
+        map_response_body: Option<JsonPath>,
+        /// What is done when the upstream answers with these error statuses.
+        status_codes: http::StatusCodes,
     },
     GraphQL {
         req_template: graphql::RequestTemplate,
@@ -136,13 +162,18 @@ impl IR {
         match self {
             IR::IO(io) => io_modifier(io),
             IR::Cache(cache) => io_modifier(&mut cache.io),
-            IR::Discriminate(_, ir) | IR::Protect(_, ir) | IR::Path(ir, _) => {
+            IR::Discriminate(_, ir) | IR::Protect(_, ir) | IR::Path(ir, _) | IR::When(_, ir) => {
                 ir.modify_io(io_modifier)
             }
             IR::Pipe(ir1, ir2) => {
                 ir1.modify_io(io_modifier);
                 ir2.modify_io(io_modifier);
             }
+            IR::ByOperation { query, mutation, subscription } => {
+                query.modify_io(io_modifier);
+                mutation.modify_io(io_modifier);
+                subscription.modify_io(io_modifier);
+            }
             IR::Entity(hash_map) => {
                 for ir in hash_map.values_mut() {
                     ir.modify_io(io_modifier);
@@ -193,6 +224,12 @@ impl IR {
                     IR::Discriminate(discriminator, expr) => {
                         IR::Discriminate(discriminator, expr.modify_box(modifier))
                     }
+                    IR::When(condition, expr) => IR::When(condition, expr.modify_box(modifier)),
+                    IR::ByOperation { query, mutation, subscription } => IR::ByOperation {
+                        query: query.modify_box(modifier),
+                        mutation: mutation.modify_box(modifier),
+                        subscription: subscription.modify_box(modifier),
+                    },
                     IR::Entity(map) => IR::Entity(
                         map.into_iter()
                             .map(|(k, v)| (k, v.modify(modifier)))
@@ -1,19 +1,31 @@
+use async_graphql_value::ConstValue;
+
 /// Holds necessary information for request execution.
 pub struct DynamicRequest<Value> {
     request: reqwest::Request,
     /// used for request body batching.
     batching_value: Option<Value>,
+    /// The key of the request sent in `{{.batch.keys}}`.
+    batch_key: Option<ConstValue>,
 }
 
 impl<Value> DynamicRequest<Value> {
     pub fn new(request: reqwest::Request) -> Self {
-        Self { request, batching_value: None }
+        Self { request, batching_value: None, batch_key: None }
     }
 
     pub fn with_batching_value(self, body_key: Option<Value>) -> Self {
         Self { batching_value: body_key, ..self }
     }
 
+    pub fn with_batch_key(self, batch_key: Option<ConstValue>) -> Self {
+        Self { batch_key, ..self }
+    }
+
+    pub fn batch_key(&self) -> Option<&ConstValue> {
+        self.batch_key.as_ref()
+    }
+
     pub fn request(&self) -> &reqwest::Request {
         &self.request
     }
@@ -5,12 +5,31 @@ use async_graphql::{Name, ServerError, Value};
 use async_graphql_value::ConstValue;
 use indexmap::IndexMap;
 
+use crate::core::json::Projection;
+
 pub trait ResolverContextLike: Clone {
     fn value(&self) -> Option<&Value>;
     fn args(&self) -> Option<&IndexMap<Name, Value>>;
     fn field(&self) -> Option<SelectionField>;
     fn is_query(&self) -> bool;
     fn add_error(&self, error: ServerError);
+
+    /// Whether the field is resolved as part of a subscription.
+    fn is_subscription(&self) -> bool {
+        false
+    }
+
+    /// The keys of the resolved value that the query reads, when they're all
+    /// known before the value is fetched.
+    fn projection(&self) -> Option<Projection> {
+        None
+    }
+
+    /// The keys of the resolved value that the query selects, whatever the
+    /// resolver. It's `None` when they aren't all known from the query.
+    fn selection(&self) -> Option<Projection> {
+        None
+    }
 }
 
 #[derive(Clone)]
@@ -64,6 +83,10 @@ impl ResolverContextLike for ResolverContext<'_> {
         self.inner.ctx.query_env.operation.node.ty == OperationType::Query
     }
 
+    fn is_subscription(&self) -> bool {
+        self.inner.ctx.query_env.operation.node.ty == OperationType::Subscription
+    }
+
     fn add_error(&self, error: ServerError) {
         self.inner.ctx.add_error(error)
     }
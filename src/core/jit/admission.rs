@@ -0,0 +1,336 @@
+//! Rules that reject an operation by looking at its plan, before any of it is
+//! executed.
+
+use std::sync::Arc;
+
+use async_graphql_value::ConstValue;
+use futures_util::future::join_all;
+use serde::Serialize;
+use tailcall_valid::{Valid, Validator};
+
+use super::{BuildError, Field, OperationPlan};
+use crate::core::Type;
+
+/// A rule that looks things up before accepting an operation, eg. whether a
+/// persisted query exists in a remote store, or how many requests a client
+/// has left in a shared rate limiter. It's checked against the plan with the
+/// variables of the request resolved.
+#[async_trait::async_trait]
+pub trait AsyncRule: Send + Sync {
+    async fn validate(&self, plan: &OperationPlan<ConstValue>) -> Valid<(), String>;
+}
+
+/// Checks the rules concurrently and fails with the failures of all of them.
+#[derive(Clone, Default)]
+pub struct AsyncRuleChain {
+    rules: Vec<Arc<dyn AsyncRule>>,
+}
+
+impl AsyncRuleChain {
+    /// Adds a rule to the chain.
+    pub fn rule(mut self, rule: impl AsyncRule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub async fn validate(&self, plan: &OperationPlan<ConstValue>) -> Valid<(), String> {
+        let results = join_all(self.rules.iter().map(|rule| rule.validate(plan))).await;
+        Valid::from_iter(results, |result| result).unit()
+    }
+}
+
+/// Rejects the operations that are estimated to make more upstream requests
+/// than the limit, see [OperationPlan::estimated_fetch_count].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FetchCountRule(pub usize);
+
+impl FetchCountRule {
+    pub fn check<A>(&self, plan: &OperationPlan<A>) -> Result<(), BuildError> {
+        let count = plan.estimated_fetch_count();
+        if count > self.0 {
+            Err(BuildError::FetchCountExceeded { count, limit: self.0 })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Rejects the operations whose complexity is over the limit. A field counts
+/// for one plus the complexity of its selection, which is multiplied by the
+/// list multiplier for each level of list the field returns, so that nested
+/// lists count for the items they can expand to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueryComplexity {
+    limit: usize,
+    list_multiplier: usize,
+}
+
+impl QueryComplexity {
+    pub fn new(limit: usize) -> Self {
+        Self { limit, list_multiplier: 10 }
+    }
+
+    /// The number of items a list is estimated to have, 10 by default. With
+    /// `1` the complexity is the number of fields of the operation.
+    pub fn with_list_multiplier(self, list_multiplier: usize) -> Self {
+        Self { list_multiplier, ..self }
+    }
+
+    pub fn complexity<A>(&self, plan: &OperationPlan<A>) -> usize {
+        plan.selection
+            .iter()
+            .map(|field| self.complexity_helper(field))
+            .fold(0, usize::saturating_add)
+    }
+
+    fn complexity_helper<A>(&self, field: &Field<A>) -> usize {
+        let children = field
+            .selection
+            .iter()
+            .map(|field| self.complexity_helper(field))
+            .fold(0, usize::saturating_add);
+        let multiplier = (0..list_depth(&field.type_of)).fold(1, |multiplier: usize, _| {
+            multiplier.saturating_mul(self.list_multiplier)
+        });
+
+        children.saturating_mul(multiplier).saturating_add(1)
+    }
+
+    /// Measures the plan, with the complexity of every field it selects.
+    pub fn report<A>(&self, plan: &OperationPlan<A>) -> ComplexityReport {
+        let mut fields = vec![];
+        for field in &plan.selection {
+            self.report_helper(field, field.output_name.clone(), &mut fields);
+        }
+
+        ComplexityReport {
+            complexity: self.complexity(plan),
+            depth: plan.depth(),
+            estimated_fetch_count: plan.estimated_fetch_count(),
+            fields,
+        }
+    }
+
+    fn report_helper<A>(&self, field: &Field<A>, path: String, fields: &mut Vec<FieldComplexity>) {
+        let complexity = self.complexity_helper(field);
+        fields.push(FieldComplexity { path: path.clone(), complexity });
+        for child in &field.selection {
+            let child_path = format!("{}.{}", path, child.output_name);
+            self.report_helper(child, child_path, fields);
+        }
+    }
+
+    pub fn check<A>(&self, plan: &OperationPlan<A>) -> Result<(), BuildError> {
+        let complexity = self.complexity(plan);
+        if complexity > self.limit {
+            Err(BuildError::ComplexityExceeded { complexity, limit: self.limit })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The measures of an operation, served by `GET /admin/complexity`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityReport {
+    pub complexity: usize,
+    pub depth: usize,
+    pub estimated_fetch_count: usize,
+    pub fields: Vec<FieldComplexity>,
+}
+
+/// The complexity of a field, selection included, at its path in the
+/// response.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldComplexity {
+    pub path: String,
+    pub complexity: usize,
+}
+
+/// The number of lists the type is nested in, eg. 2 for `[[Comment]]`.
+fn list_depth(type_of: &Type) -> usize {
+    match type_of {
+        Type::List { of_type, .. } => 1 + list_depth(of_type),
+        Type::Named { .. } => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::ConstValue;
+
+    use super::*;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::ConfigModule;
+    use crate::core::jit::transform::InputResolver;
+    use crate::core::jit::{self, Error, Variables};
+    use crate::include_config;
+
+    const QUERY: &str = r#"{ posts { id user { id comments { body } } } }"#;
+
+    fn blueprint() -> Blueprint {
+        let config = include_config!("./fixtures/dedupe.graphql").unwrap();
+        Blueprint::try_from(&ConfigModule::from(config)).unwrap()
+    }
+
+    #[test]
+    fn test_fetch_count_rule() {
+        let plan = jit::Request::<ConstValue>::new(QUERY)
+            .create_plan(&blueprint())
+            .unwrap();
+
+        assert_eq!(FetchCountRule(3).check(&plan), Ok(()));
+        assert_eq!(
+            FetchCountRule(2).check(&plan),
+            Err(BuildError::FetchCountExceeded { count: 3, limit: 2 })
+        );
+    }
+
+    #[test]
+    fn test_max_fetch_count() {
+        let mut blueprint = blueprint();
+        blueprint.server.max_fetch_count = Some(2);
+
+        let error = jit::Request::<ConstValue>::new(QUERY)
+            .create_plan(&blueprint)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::BuildError(BuildError::FetchCountExceeded { count: 3, limit: 2 })
+        ));
+        assert!(jit::Request::<ConstValue>::new(r#"{ posts { id } }"#)
+            .create_plan(&blueprint)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_max_complexity() {
+        let mut blueprint = blueprint();
+        blueprint.server.max_complexity = Some(100);
+
+        let error = jit::Request::<ConstValue>::new(QUERY)
+            .create_plan(&blueprint)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::BuildError(BuildError::ComplexityExceeded { complexity: 141, limit: 100 })
+        ));
+        assert!(jit::Request::<ConstValue>::new(r#"{ posts { id } }"#)
+            .create_plan(&blueprint)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_query_complexity() {
+        let plan = jit::Request::<ConstValue>::new(QUERY)
+            .create_plan(&blueprint())
+            .unwrap();
+
+        // posts: 1 + (id + user) * 10, user: 1 + id + comments,
+        // comments: 1 + body * 10
+        assert_eq!(QueryComplexity::new(200).complexity(&plan), 141);
+        assert_eq!(
+            QueryComplexity::new(200)
+                .with_list_multiplier(1)
+                .complexity(&plan),
+            plan.size()
+        );
+        assert_eq!(QueryComplexity::new(141).check(&plan), Ok(()));
+        assert_eq!(
+            QueryComplexity::new(100).check(&plan),
+            Err(BuildError::ComplexityExceeded { complexity: 141, limit: 100 })
+        );
+    }
+
+    #[test]
+    fn test_complexity_report() {
+        let plan = jit::Request::<ConstValue>::new(QUERY)
+            .create_plan(&blueprint())
+            .unwrap();
+        let report = QueryComplexity::new(200).report(&plan);
+
+        let fields = report
+            .fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.complexity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("posts", 141),
+                ("posts.id", 1),
+                ("posts.user", 13),
+                ("posts.user.id", 1),
+                ("posts.user.comments", 11),
+                ("posts.user.comments.body", 1),
+            ]
+        );
+        assert_eq!(report.complexity, 141);
+        assert_eq!(report.depth, 4);
+        assert_eq!(report.estimated_fetch_count, 3);
+    }
+
+    struct Deny(&'static str);
+
+    #[async_trait::async_trait]
+    impl AsyncRule for Deny {
+        async fn validate(&self, _: &OperationPlan<ConstValue>) -> Valid<(), String> {
+            Valid::fail(self.0.to_string())
+        }
+    }
+
+    struct Allow;
+
+    #[async_trait::async_trait]
+    impl AsyncRule for Allow {
+        async fn validate(&self, _: &OperationPlan<ConstValue>) -> Valid<(), String> {
+            tokio::task::yield_now().await;
+            Valid::succeed(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_rule_chain() {
+        let plan = jit::Request::<ConstValue>::new(QUERY)
+            .create_plan(&blueprint())
+            .unwrap();
+        let plan = InputResolver::new(plan)
+            .resolve_input(&Variables::default())
+            .unwrap();
+
+        assert!(AsyncRuleChain::default().validate(&plan).await.is_succeed());
+        assert!(AsyncRuleChain::default()
+            .rule(Allow)
+            .validate(&plan)
+            .await
+            .is_succeed());
+
+        let error = AsyncRuleChain::default()
+            .rule(Deny("unknown persisted query"))
+            .rule(Allow)
+            .rule(Deny("rate limited"))
+            .validate(&plan)
+            .await
+            .to_result()
+            .unwrap_err();
+        let messages = error
+            .as_vec()
+            .iter()
+            .map(|cause| cause.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["unknown persisted query", "rate limited"]);
+    }
+
+    #[test]
+    fn test_nested_list_complexity() {
+        let list_of_lists = Type::from("Comment".to_string()).into_list().into_list();
+
+        assert_eq!(list_depth(&list_of_lists), 2);
+        assert_eq!(list_depth(&Type::from("Comment".to_string())), 0);
+    }
+}
@@ -201,9 +201,13 @@ impl<'a> Builder<'a> {
                         let child_fields =
                             self.iter(&gql_field.selection_set.node, type_of.name(), fragments);
 
-                        let ir = match field_def {
-                            QueryField::Field((field_def, _)) => field_def.resolver.clone(),
-                            _ => None,
+                        let (ir, omit_if_null, fallback_key) = match field_def {
+                            QueryField::Field((field_def, _)) => (
+                                field_def.resolver.clone(),
+                                field_def.omit_if_null,
+                                field_def.fallback_key.clone(),
+                            ),
+                            _ => (None, false, None),
                         };
 
                         let scalar = if self.index.type_is_scalar(type_of.name()) {
@@ -236,6 +240,8 @@ impl<'a> Builder<'a> {
                             pos: selection.pos.into(),
                             directives,
                             scalar,
+                            omit_if_null,
+                            fallback_key,
                         };
 
                         fields.push(field);
@@ -255,6 +261,8 @@ impl<'a> Builder<'a> {
                             directives,
                             is_enum: false,
                             scalar: Some(scalar::Scalar::Empty),
+                            omit_if_null: false,
+                            fallback_key: None,
                         };
 
                         fields.push(typename_field);
@@ -290,7 +298,7 @@ impl<'a> Builder<'a> {
         match ty {
             OperationType::Query => Some(self.index.get_query()),
             OperationType::Mutation => self.index.get_mutation(),
-            OperationType::Subscription => None,
+            OperationType::Subscription => self.index.get_subscription(),
         }
     }
 
@@ -336,6 +344,10 @@ impl<'a> Builder<'a> {
             .ok_or(BuildError::RootOperationTypeNotDefined { operation: operation.ty })?;
         let fields = self.iter(&operation.selection_set.node, name, &fragments);
 
+        if operation.ty == OperationType::Subscription && fields.len() != 1 {
+            return Err(BuildError::SubscriptionRootFieldCount);
+        }
+
         let is_introspection_query = operation.selection_set.node.items.iter().any(|f| {
             if let Selection::Field(Positioned { node: gql_field, .. }) = &f.node {
                 let query = gql_field.name.node.as_str();
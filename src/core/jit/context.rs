@@ -6,7 +6,9 @@ use indexmap::IndexMap;
 
 use super::error::*;
 use super::{Field, OperationPlan, Positioned};
+use crate::core::ir::model::{IO, IR};
 use crate::core::ir::{ResolverContextLike, SelectionField};
+use crate::core::json::Projection;
 
 #[derive(Debug)]
 pub struct RequestContext<'a, Input> {
@@ -103,9 +105,42 @@ impl ResolverContextLike for Context<'_, ConstValue, ConstValue> {
         self.request.plan().is_query()
     }
 
-    fn add_error(&self, error: ServerError) {
+    fn is_subscription(&self) -> bool {
+        self.request.plan().is_subscription()
+    }
+
+    /// The errors that don't tell where they happened point to the field.
+    fn add_error(&self, mut error: ServerError) {
+        if error.locations.is_empty() {
+            error.locations.push(self.field.pos.into());
+        }
         self.request.add_error(error.into())
     }
+
+    /// Only the fields resolved by a plain HTTP call are decoded with a
+    /// projection. The hooks, deduplication and batching can hand the same
+    /// value to other selections, and the pagination reads keys that aren't
+    /// selected.
+    fn projection(&self) -> Option<Projection> {
+        match &self.field.ir {
+            Some(IR::IO(IO::Http {
+                hook: None,
+                dedupe: false,
+                group_by: None,
+                paginate: None,
+                map_response_body: None,
+                ..
+            })) => match self.field.projection()? {
+                Projection::All => None,
+                projection => Some(projection),
+            },
+            _ => None,
+        }
+    }
+
+    fn selection(&self) -> Option<Projection> {
+        self.field.projection()
+    }
 }
 
 #[cfg(test)]
@@ -119,6 +154,7 @@ mod test {
     use crate::core::ir::ResolverContextLike;
     use crate::core::jit::transform::InputResolver;
     use crate::core::jit::{OperationPlan, Request};
+    use crate::core::json::Projection;
 
     fn setup(query: &str) -> anyhow::Result<OperationPlan<ConstValue>> {
         let sdl = std::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER)?;
@@ -149,4 +185,27 @@ mod test {
         let ctx = Context::new(&plan.selection[0], &env);
         assert!(ctx.is_query());
     }
+
+    #[test]
+    fn test_projection() {
+        let plan = setup("query {posts {id title} user(id: 1) {name}}").unwrap();
+        let env = RequestContext::new(&plan);
+        let projection = |index: usize| Context::new(&plan.selection[index], &env).projection();
+
+        assert_eq!(
+            projection(0),
+            Some(Projection::Fields(
+                [("id", Projection::All), ("title", Projection::All)]
+                    .into_iter()
+                    .map(|(name, projection)| (name.to_string(), projection))
+                    .collect()
+            ))
+        );
+        assert!(projection(1).is_some());
+
+        let plan = setup("query {posts {id user {name}}}").unwrap();
+        let env = RequestContext::new(&plan);
+        let ctx = Context::new(&plan.selection[0], &env);
+        assert_eq!(ctx.projection(), None);
+    }
 }
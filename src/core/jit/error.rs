@@ -14,6 +14,14 @@ pub enum BuildError {
     OperationNotFound(String),
     #[error("Operation name required in request")]
     OperationNameRequired,
+    #[error("Subscription must select exactly one top level field")]
+    SubscriptionRootFieldCount,
+    #[error(
+        "Operation is estimated to make {count} upstream requests, exceeding the limit of {limit}"
+    )]
+    FetchCountExceeded { count: usize, limit: usize },
+    #[error("Operation has a complexity of {complexity}, exceeding the limit of {limit}")]
+    ComplexityExceeded { complexity: usize, limit: usize },
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -34,6 +42,11 @@ pub enum ValidationError {
     // with async_graphql error message for this case
     #[error(r#"internal: invalid value for scalar "{type_of}", expected "FieldValue::Value""#)]
     ScalarInvalid { type_of: String },
+    #[error(r#"internal: expected a value of type "{type_of}", found {value_type}"#)]
+    ScalarMismatch {
+        type_of: String,
+        value_type: &'static str,
+    },
     #[error(r#"internal: invalid item for enum "{type_of}""#)]
     EnumInvalid { type_of: String },
     #[error("internal: non-null types require a return value")]
@@ -52,6 +65,8 @@ pub enum Error {
     Validation(#[from] ValidationError),
     #[error("{0}")]
     ServerError(async_graphql::ServerError),
+    #[error("Subscriptions are only supported over Server-Sent Events, send the request with `Accept: text/event-stream`")]
+    SubscriptionRequiresStream,
     #[error("Unexpected error")]
     Unknown,
 }
@@ -64,7 +79,9 @@ impl ErrorExtensions for Error {
             Error::IR(error) => error.extend(),
             Error::Validation(error) => error.extend(),
             Error::ServerError(error) => error.extend(),
-            Error::Unknown => super::graphql_error::Error::new(self.to_string()),
+            Error::SubscriptionRequiresStream | Error::Unknown => {
+                super::graphql_error::Error::new(self.to_string())
+            }
         }
     }
 }
@@ -1,12 +1,14 @@
 use std::fmt::Debug;
 use std::mem;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use chrono::Utc;
 use derive_getters::Getters;
 use futures_util::future::join_all;
 
 use super::context::{Context, RequestContext};
-use super::{OperationPlan, Positioned, Response, Store};
+use super::{FieldId, OperationPlan, Positioned, Response, Store};
 use crate::core::ir::model::IR;
 use crate::core::ir::TypedValue;
 use crate::core::jit;
@@ -14,6 +16,15 @@ use crate::core::jit::synth::Synth;
 use crate::core::json::{JsonLike, JsonLikeList};
 
 type SharedStore<Output, Error> = Arc<Mutex<Store<Result<Output, Positioned<Error>>>>>;
+type SharedTimings = Arc<Mutex<Vec<FieldTiming>>>;
+
+/// Time taken by the resolver of a field.
+#[derive(Clone, Debug)]
+pub struct FieldTiming {
+    pub id: FieldId,
+    pub duration: Duration,
+    pub is_err: bool,
+}
 
 ///
 /// Default GraphQL executor that takes in a GraphQL Request and produces a
@@ -21,6 +32,7 @@ type SharedStore<Output, Error> = Arc<Mutex<Store<Result<Output, Positioned<Erro
 pub struct Executor<'a, IRExec, Input> {
     ctx: RequestContext<'a, Input>,
     exec: IRExec,
+    timings: Option<SharedTimings>,
 }
 
 impl<'a, Input, Value, Exec> Executor<'a, Exec, Input>
@@ -30,12 +42,28 @@ where
     Exec: IRExecutor<Input = Input, Output = Value, Error = jit::Error>,
 {
     pub fn new(plan: &'a OperationPlan<Input>, exec: Exec) -> Self {
-        Self { exec, ctx: RequestContext::new(plan) }
+        Self { exec, ctx: RequestContext::new(plan), timings: None }
+    }
+
+    /// Measures the time taken by the resolver of every field.
+    pub fn track_fields(mut self) -> Self {
+        self.timings = Some(Arc::new(Mutex::new(vec![])));
+        self
+    }
+
+    /// Time taken by the resolvers, empty unless [Executor::track_fields] is
+    /// enabled.
+    pub fn field_timings(&self) -> Vec<FieldTiming> {
+        self.timings
+            .as_ref()
+            .map(|timings| timings.lock().unwrap().clone())
+            .unwrap_or_default()
     }
 
     pub async fn store(&self) -> Store<Result<Value, Positioned<jit::Error>>> {
         let store = Arc::new(Mutex::new(Store::new()));
-        let mut ctx = ExecutorInner::new(store.clone(), &self.exec, &self.ctx);
+        let mut ctx =
+            ExecutorInner::new(store.clone(), self.timings.clone(), &self.exec, &self.ctx);
         ctx.init().await;
 
         let store = mem::replace(&mut *store.lock().unwrap(), Store::new());
@@ -47,6 +75,7 @@ where
         Output: JsonLike<'a> + Default,
     {
         let mut response = Response::new(synth.synthesize());
+        response.add_errors(synth.errors());
         response.add_errors(self.ctx.errors().clone());
         response
     }
@@ -55,6 +84,7 @@ where
 #[derive(Getters)]
 struct ExecutorInner<'a, Input, Output, Error, Exec> {
     store: SharedStore<Output, Error>,
+    timings: Option<SharedTimings>,
     ir_exec: &'a Exec,
     request: &'a RequestContext<'a, Input>,
 }
@@ -67,10 +97,11 @@ where
 {
     fn new(
         store: SharedStore<Output, Error>,
+        timings: Option<SharedTimings>,
         ir_exec: &'a Exec,
         env: &'a RequestContext<Input>,
     ) -> Self {
-        Self { store, ir_exec, request: env }
+        Self { store, timings, ir_exec, request: env }
     }
 
     async fn init(&mut self) {
@@ -100,11 +131,74 @@ where
         Ok(())
     }
 
+    /// Resolves the field for every item of the parent list. The items that
+    /// fail resolve to null and their errors are stored by the path of the
+    /// item, so that the other items are kept.
+    async fn execute_items<'b>(
+        &'b self,
+        ir: &'b IR,
+        ctx: &'b Context<'b, Input, Output>,
+        value: &'b Output,
+    ) -> Result<Output, Error> {
+        let field = ctx.field();
+        let plan = self.request.plan();
+
+        let mut items = vec![];
+        collect_items(value, &mut vec![], &mut items);
+        // the resolver is only executed for the items the field is part of, for
+        // the fragments on unions and interfaces
+        items.retain(|(_, item)| plan.field_is_part_of_value(field, *item));
+
+        let results = join_all(items.iter().map(|(_, item)| {
+            let ctx = ctx.with_value(*item);
+            async move { self.ir_exec.execute(ir, &ctx).await }
+        }))
+        .await;
+
+        let mut values = Vec::with_capacity(results.len());
+        let mut store = self.store.lock().unwrap();
+        for ((data_path, _), result) in items.into_iter().zip(results) {
+            match result {
+                Ok(item) => values.push(item),
+                Err(error) => {
+                    let error = Positioned::new(error, field.pos);
+                    store.set_item(&field.id, data_path, Err(error));
+                    values.push(Output::null());
+                }
+            }
+        }
+        drop(store);
+
+        // map the items to their results preserving the shape of the list
+        let mut values = values.into_iter();
+        value.map_ref(&mut |value| {
+            if plan.field_is_part_of_value(field, value) {
+                Ok(values.next().unwrap_or(Output::null()))
+            } else {
+                Ok(Output::null())
+            }
+        })
+    }
+
     async fn execute<'b>(&'b self, ctx: &'b Context<'b, Input, Output>) -> Result<(), Error> {
         let field = ctx.field();
 
         if let Some(ir) = &field.ir {
-            let result = self.ir_exec.execute(ir, ctx).await;
+            let started = Utc::now();
+            let result = match ctx.value() {
+                Some(value) if value.as_array().is_some() => {
+                    self.execute_items(ir, ctx, value).await
+                }
+                _ => self.ir_exec.execute(ir, ctx).await,
+            };
+
+            if let Some(timings) = &self.timings {
+                timings.lock().unwrap().push(FieldTiming {
+                    id: field.id.clone(),
+                    duration: (Utc::now() - started).to_std().unwrap_or_default(),
+                    is_err: result.is_err(),
+                });
+            }
 
             if let Ok(value) = &result {
                 self.iter_field(ctx, value).await?;
@@ -141,6 +235,24 @@ where
     }
 }
 
+/// The items of a list, nested or not, with the indexes that lead to them.
+fn collect_items<'v, Value: JsonLike<'v>>(
+    value: &'v Value,
+    data_path: &mut Vec<usize>,
+    items: &mut Vec<(Vec<usize>, &'v Value)>,
+) {
+    match value.as_array() {
+        Some(array) => {
+            for (index, item) in array.iter().enumerate() {
+                data_path.push(index);
+                collect_items(item, data_path, items);
+                data_path.pop();
+            }
+        }
+        None => items.push((data_path.clone(), value)),
+    }
+}
+
 /// Executor for IR
 pub trait IRExecutor {
     type Input;
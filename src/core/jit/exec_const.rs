@@ -1,20 +1,21 @@
 use std::sync::Arc;
 
 use async_graphql_value::{ConstValue, Value};
-use futures_util::future::join_all;
 use tailcall_valid::Validator;
 
 use super::context::Context;
 use super::exec::{Executor, IRExecutor};
 use super::graphql_error::GraphQLError;
+use super::middleware::{run_middlewares, ExecutionMiddleware};
 use super::{transform, AnyResponse, BuildError, Error, OperationPlan, Request, Response, Result};
 use crate::core::app_context::AppContext;
+use crate::core::blueprint::add_directives;
 use crate::core::http::RequestContext;
 use crate::core::ir::model::IR;
-use crate::core::ir::{self, EmptyResolverContext, EvalContext};
+use crate::core::ir::{EmptyResolverContext, EvalContext};
 use crate::core::jit::synth::Synth;
 use crate::core::jit::transform::InputResolver;
-use crate::core::json::{JsonLike, JsonLikeList};
+use crate::core::json::JsonLike;
 use crate::core::Transform;
 
 /// A specialized executor that executes with async_graphql::Value
@@ -89,18 +90,50 @@ impl ConstValueExecutor {
             }
         };
 
-        let exec = ConstValueExec::new(&plan, req_ctx);
+        if !app_ctx.async_rules.is_empty() {
+            if let Err(err) = app_ctx.async_rules.validate(&plan).await.to_result() {
+                let resp: Response<ConstValue> = Response::default();
+                let errors = err
+                    .as_vec()
+                    .iter()
+                    .map(|cause| GraphQLError::new(cause.message.clone(), None))
+                    .collect();
+                return resp.with_errors(errors).into();
+            }
+        }
+
+        let exec = ConstValueExec::new(req_ctx, &app_ctx.middlewares);
         // PERF: remove this particular clone?
         let vars = request.variables.clone();
-        let exe = Executor::new(&plan, exec);
+        let mut exe = Executor::new(&plan, exec);
+        if app_ctx.usage.is_some() {
+            exe = exe.track_fields();
+        }
         let store = exe.store().await;
-        let synth = Synth::new(&plan, store, vars);
+        if let Some(usage) = &app_ctx.usage {
+            usage.record_fields(
+                &request.query,
+                request.operation_name.as_deref(),
+                &plan,
+                &exe.field_timings(),
+            );
+        }
+        let synth = Synth::new(&plan, store, vars)
+            .lenient_coercion(app_ctx.blueprint.server.enable_lenient_coercion);
 
         let resp: Response<serde_json_borrow::Value> = exe.execute(&synth).await;
 
         if is_introspection_query {
+            let query = request.query.clone();
+            let operation_name = request.operation_name.clone();
             let async_req = async_graphql::Request::from(request).only_introspection();
-            let async_resp = app_ctx.execute(async_req).await;
+            let mut async_resp = app_ctx.execute(async_req).await;
+            add_directives(
+                &mut async_resp.data,
+                &query,
+                operation_name.as_deref(),
+                &app_ctx.blueprint,
+            );
 
             resp.merge_with(&async_resp).into()
         } else {
@@ -110,13 +143,16 @@ impl ConstValueExecutor {
 }
 
 struct ConstValueExec<'a> {
-    plan: &'a OperationPlan<ConstValue>,
     req_context: &'a RequestContext,
+    middlewares: &'a [Arc<dyn ExecutionMiddleware>],
 }
 
 impl<'a> ConstValueExec<'a> {
-    pub fn new(plan: &'a OperationPlan<ConstValue>, req_context: &'a RequestContext) -> Self {
-        Self { req_context, plan }
+    pub fn new(
+        req_context: &'a RequestContext,
+        middlewares: &'a [Arc<dyn ExecutionMiddleware>],
+    ) -> Self {
+        Self { req_context, middlewares }
     }
 
     async fn call(
@@ -136,7 +172,14 @@ impl<'a> ConstValueExec<'a> {
         let req_context = &self.req_context;
         let mut eval_ctx = EvalContext::new(req_context, ctx);
 
-        Ok(ir.eval(&mut eval_ctx).await?)
+        if self.middlewares.is_empty() {
+            return Ok(ir.eval(&mut eval_ctx).await?);
+        }
+
+        run_middlewares(self.middlewares, ctx.field(), req_context, || async move {
+            Ok(ir.eval(&mut eval_ctx).await?)
+        })
+        .await
     }
 }
 
@@ -150,44 +193,7 @@ impl IRExecutor for ConstValueExec<'_> {
         ir: &'a IR,
         ctx: &'a Context<'a, Self::Input, Self::Output>,
     ) -> Result<Self::Output> {
-        let field = ctx.field();
-
-        match ctx.value() {
-            // TODO: check that field is expected list and it's a list of the required deepness
-            Some(value) if value.as_array().is_some() => {
-                let mut tasks = Vec::new();
-
-                // collect the async tasks first before creating the final result
-                value.for_each(&mut |value| {
-                    // execute the resolver only for fields that are related to current value
-                    // for fragments on union/interface
-                    if self.plan.field_is_part_of_value(field, value) {
-                        let ctx = ctx.with_value(value);
-                        tasks.push(async move { self.call(&ctx, ir).await })
-                    }
-                });
-
-                let results = join_all(tasks).await;
-
-                let mut iter = results.into_iter();
-
-                // map input value to the calculated results preserving the shape
-                // of the input
-                Ok(value.map_ref(&mut |value| {
-                    // for fragments on union/interface we will
-                    // have less entries for resolved values based on the type
-                    // pull from the result only field is related and fill with null otherwise
-                    if self.plan.field_is_part_of_value(field, value) {
-                        iter.next().unwrap_or(Err(ir::Error::IO(
-                            "Expected value to be present".to_string(),
-                        )
-                        .into()))
-                    } else {
-                        Ok(Self::Output::default())
-                    }
-                })?)
-            }
-            _ => Ok(self.call(ctx, ir).await?),
-        }
+        // the lists are resolved item by item by the executor
+        Ok(self.call(ctx, ir).await?)
     }
 }
@@ -5,21 +5,26 @@ use std::sync::Arc;
 
 use async_graphql::{BatchRequest, Value};
 use async_graphql_value::{ConstValue, Extensions};
-use futures_util::stream::FuturesOrdered;
+use chrono::Utc;
+use futures_timer::Delay;
+use futures_util::stream::{self, BoxStream, FuturesOrdered};
 use futures_util::StreamExt;
 use tailcall_hasher::TailcallHasher;
 
-use super::{AnyResponse, BatchResponse, Response};
+use super::middleware::{run_after, run_before};
+use super::{AnyResponse, BatchResponse, Middleware, Response};
 use crate::core::app_context::AppContext;
 use crate::core::async_graphql_hyper::OperationId;
+use crate::core::blueprint::Poll;
 use crate::core::http::RequestContext;
-use crate::core::jit::{self, ConstValueExecutor, OPHash, Pos, Positioned};
+use crate::core::jit::{self, ConstValueExecutor, OPHash, OperationPlan, Pos, Positioned};
 
 #[derive(Clone)]
 pub struct JITExecutor {
     app_ctx: Arc<AppContext>,
     req_ctx: Arc<RequestContext>,
     operation_id: OperationId,
+    middlewares: Vec<Arc<dyn Middleware>>,
 }
 
 impl JITExecutor {
@@ -28,7 +33,15 @@ impl JITExecutor {
         req_ctx: Arc<RequestContext>,
         operation_id: OperationId,
     ) -> Self {
-        Self { app_ctx, req_ctx, operation_id }
+        let middlewares = app_ctx.request_middlewares.clone();
+        Self { app_ctx, req_ctx, operation_id, middlewares }
+    }
+
+    /// Adds a middleware to the end of the chain run around the requests,
+    /// after the ones registered on the [AppContext].
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
     }
 
     #[inline(always)]
@@ -68,6 +81,28 @@ impl JITExecutor {
 
         OPHash::new(hasher.finish())
     }
+
+    fn get_or_create_executor(
+        &self,
+        hash: &OPHash,
+        jit_request: &jit::Request<ConstValue>,
+    ) -> jit::Result<ConstValueExecutor> {
+        if let Some(op) = self.app_ctx.operation_plans.get(hash) {
+            return Ok(ConstValueExecutor::from(op.value().clone()));
+        }
+
+        let exec = ConstValueExecutor::try_new(jit_request, &self.app_ctx)?;
+        self.app_ctx
+            .operation_plans
+            .insert(hash.clone(), exec.plan.clone());
+        Ok(exec)
+    }
+
+    fn error_response(error: jit::Error) -> AnyResponse<Vec<u8>> {
+        Response::<async_graphql::Value>::default()
+            .with_errors(vec![Positioned::new(error, Pos::default())])
+            .into()
+    }
 }
 
 impl JITExecutor {
@@ -78,48 +113,149 @@ impl JITExecutor {
         // TODO: hash considering only the query itself ignoring specified operation and
         // variables that could differ for the same query
         let hash = Self::req_hash(&request);
+        let request = jit::Request::from(request);
 
         async move {
-            if let Some(response) = self.app_ctx.const_execution_cache.get(&hash) {
-                return response.clone();
-            }
-
-            let jit_request = jit::Request::from(request);
-            let exec = if let Some(op) = self.app_ctx.operation_plans.get(&hash) {
-                ConstValueExecutor::from(op.value().clone())
-            } else {
-                let exec = match ConstValueExecutor::try_new(&jit_request, &self.app_ctx) {
-                    Ok(exec) => exec,
-                    Err(error) => {
-                        return Response::<async_graphql::Value>::default()
-                            .with_errors(vec![Positioned::new(error, Pos::default())])
-                            .into()
-                    }
-                };
-                self.app_ctx
-                    .operation_plans
-                    .insert(hash.clone(), exec.plan.clone());
-                exec
+            let (ran, rejection) = run_before(&self.middlewares, &request).await;
+            let response = match rejection {
+                Some(error) => Self::error_response(error),
+                None => self.execute_with_usage(hash, request).await,
             };
+            run_after(&self.middlewares[..ran], &response).await;
+
+            response
+        }
+    }
+
+    async fn execute_with_usage(
+        &self,
+        hash: OPHash,
+        request: jit::Request<ConstValue>,
+    ) -> AnyResponse<Vec<u8>> {
+        let Some(usage) = self.app_ctx.usage.clone() else {
+            return self.execute_operation(hash, request).await;
+        };
+
+        let query = request.query.clone();
+        let operation_name = request.operation_name.clone();
+        let started = Utc::now();
+        let response = self.execute_operation(hash, request).await;
+        usage.record_operation(
+            &query,
+            operation_name.as_deref(),
+            (Utc::now() - started).to_std().unwrap_or_default(),
+            !response.is_ok,
+        );
+
+        response
+    }
+
+    async fn execute_operation(
+        &self,
+        hash: OPHash,
+        jit_request: jit::Request<ConstValue>,
+    ) -> AnyResponse<Vec<u8>> {
+        if let Some(response) = self.app_ctx.const_execution_cache.get(&hash) {
+            return response.clone();
+        }
+
+        let exec = match self.get_or_create_executor(&hash, &jit_request) {
+            Ok(exec) => exec,
+            Err(error) => return Self::error_response(error),
+        };
+
+        if exec.plan.is_subscription() {
+            return Self::error_response(jit::Error::SubscriptionRequiresStream);
+        }
+
+        self.execute_plan(hash, exec, jit_request).await
+    }
+
+    async fn execute_plan(
+        &self,
+        hash: OPHash,
+        exec: ConstValueExecutor,
+        jit_request: jit::Request<ConstValue>,
+    ) -> AnyResponse<Vec<u8>> {
+        let is_const = exec.plan.is_const;
+        let is_protected = exec.plan.is_protected;
+
+        let response = if exec.plan.can_dedupe() {
+            self.dedupe_and_exec(exec, jit_request).await
+        } else {
+            self.exec(exec, jit_request).await
+        };
+
+        // Cache the response if it's constant and not wrapped with protected.
+        if is_const && !is_protected {
+            self.app_ctx
+                .const_execution_cache
+                .insert(hash, response.clone());
+        }
+
+        response
+    }
 
-            let is_const = exec.plan.is_const;
-            let is_protected = exec.plan.is_protected;
+    /// Executes the request as a stream of responses. Subscriptions re-execute
+    /// their plan on the poll interval and yield a response whenever the
+    /// result changes, other operations yield a single response.
+    ///
+    /// Polling stops as soon as the stream is dropped.
+    pub fn execute_stream(
+        self,
+        request: async_graphql::Request,
+    ) -> BoxStream<'static, AnyResponse<Vec<u8>>> {
+        let hash = Self::req_hash(&request);
+        let jit_request = jit::Request::from(request);
+        let middlewares = self.middlewares.clone();
 
-            let response = if exec.plan.can_dedupe() {
-                self.dedupe_and_exec(exec, jit_request).await
-            } else {
-                self.exec(exec, jit_request).await
+        stream::once(async move {
+            let (ran, rejection) = run_before(&middlewares, &jit_request).await;
+            let responses = match rejection {
+                Some(error) => stream::once(async { Self::error_response(error) }).boxed(),
+                None => self.stream_operation(hash, jit_request),
             };
+            let middlewares = Arc::new(middlewares[..ran].to_vec());
+            responses.then(move |response| {
+                let middlewares = middlewares.clone();
+                async move {
+                    run_after(&middlewares, &response).await;
+                    response
+                }
+            })
+        })
+        .flatten()
+        .boxed()
+    }
 
-            // Cache the response if it's constant and not wrapped with protected.
-            if is_const && !is_protected {
-                self.app_ctx
-                    .const_execution_cache
-                    .insert(hash, response.clone());
-            }
+    fn stream_operation(
+        self,
+        hash: OPHash,
+        jit_request: jit::Request<ConstValue>,
+    ) -> BoxStream<'static, AnyResponse<Vec<u8>>> {
+        let exec = match self.get_or_create_executor(&hash, &jit_request) {
+            Ok(exec) => exec,
+            Err(error) => return stream::once(async { Self::error_response(error) }).boxed(),
+        };
 
-            response
+        if !exec.plan.is_subscription() {
+            return stream::once(async move { self.execute_plan(hash, exec, jit_request).await })
+                .boxed();
         }
+
+        let poller = Poller {
+            poll: exec.plan.poll().cloned(),
+            plan: exec.plan,
+            request: jit_request,
+            executor: self,
+            last: None,
+        };
+
+        stream::unfold(poller, |mut poller| async move {
+            let response = poller.next().await?;
+            Some((response, poller))
+        })
+        .boxed()
     }
 
     /// Execute a GraphQL batch query.
@@ -137,6 +273,47 @@ impl JITExecutor {
     }
 }
 
+/// Re-executes the plan of a polling subscription and skips the responses
+/// that are identical to the previous one.
+struct Poller {
+    executor: JITExecutor,
+    plan: OperationPlan<Value>,
+    request: jit::Request<ConstValue>,
+    poll: Option<Poll>,
+    /// Hash of the last delivered response
+    last: Option<u64>,
+}
+
+impl Poller {
+    async fn next(&mut self) -> Option<AnyResponse<Vec<u8>>> {
+        loop {
+            if self.last.is_some() {
+                // a subscription without polling settings yields only once
+                Delay::new(self.poll.as_ref()?.interval).await;
+            }
+
+            // the request context caches the results of IO, so every poll
+            // needs a fresh one to see the changes of the upstream
+            let app_ctx = &self.executor.app_ctx;
+            let req_ctx = RequestContext::from(app_ctx.as_ref())
+                .allowed_headers(self.executor.req_ctx.allowed_headers.clone());
+            let response = ConstValueExecutor::from(self.plan.clone())
+                .execute(app_ctx, &req_ctx, self.request.clone())
+                .await;
+
+            let mut hasher = TailcallHasher::default();
+            response.body.hash(&mut hasher);
+            let hash = hasher.finish();
+
+            let emit_unchanged = self.poll.as_ref().is_some_and(|poll| poll.emit_unchanged);
+            if self.last != Some(hash) || emit_unchanged {
+                self.last = Some(hash);
+                return Some(response);
+            }
+        }
+    }
+}
+
 // TODO: used only for introspection, simplify somehow?
 impl From<jit::Request<Value>> for async_graphql::Request {
     fn from(value: jit::Request<Value>) -> Self {
@@ -0,0 +1,428 @@
+//! Hooks that run around the resolver of every field, registered on
+//! [AppContext](crate::core::app_context::AppContext).
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_graphql_value::ConstValue;
+
+use super::{AnyResponse, Error, Field, Request, Response, Result};
+use crate::core::http::RequestContext;
+
+/// Runs custom logic around the evaluation of a field's resolver.
+///
+/// The `before` hooks run in registration order and the `after` hooks in the
+/// reverse order, of every middleware whose `before` hook ran.
+#[async_trait::async_trait]
+pub trait ExecutionMiddleware: Send + Sync {
+    /// Called before the resolver is evaluated. Returning a value or an error
+    /// skips the resolver, and the `before` hooks of the later middlewares,
+    /// and uses it as the result instead.
+    async fn before(
+        &self,
+        _field: &Field<ConstValue>,
+        _ctx: &RequestContext,
+    ) -> Result<Option<ConstValue>> {
+        Ok(None)
+    }
+
+    /// Called with the result of the resolver, or of the middleware that
+    /// short-circuited, and the time it took to get it.
+    async fn after(
+        &self,
+        _field: &Field<ConstValue>,
+        result: Result<ConstValue>,
+        _elapsed: Duration,
+    ) -> Result<ConstValue> {
+        result
+    }
+}
+
+/// Runs custom logic around the execution of every request of a
+/// [JITExecutor](super::JITExecutor), eg. logging, metrics or auth checks.
+/// The middlewares are registered on
+/// [AppContext](crate::core::app_context::AppContext), or added to a single
+/// executor.
+///
+/// The `before` hooks run in the order the middlewares were added and the
+/// `after` hooks in the reverse order, of every middleware whose `before` hook
+/// ran. For a subscription, `before` runs once and `after` runs with every
+/// response of the stream.
+#[async_trait::async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called before the request is executed. Returning an error rejects the
+    /// request, without running the `before` hooks of the later middlewares,
+    /// and responds with the error.
+    async fn before(&self, _request: &Request<ConstValue>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the response of the request, or with the rejection.
+    async fn after(&self, _response: &Response<ConstValue>) {}
+}
+
+/// Runs the `before` hooks of the middlewares until one of them rejects the
+/// request, and returns how many ran along with the rejection.
+pub(crate) async fn run_before(
+    middlewares: &[Arc<dyn Middleware>],
+    request: &Request<ConstValue>,
+) -> (usize, Option<Error>) {
+    let mut ran = 0;
+    for middleware in middlewares {
+        ran += 1;
+        if let Err(error) = middleware.before(request).await {
+            return (ran, Some(error));
+        }
+    }
+    (ran, None)
+}
+
+/// Runs the `after` hooks of the middlewares in the reverse order.
+pub(crate) async fn run_after(
+    middlewares: &[Arc<dyn Middleware>],
+    response: &AnyResponse<Vec<u8>>,
+) {
+    if middlewares.is_empty() {
+        return;
+    }
+
+    let response = response.to_response();
+    for middleware in middlewares.iter().rev() {
+        middleware.after(&response).await;
+    }
+}
+
+/// Evaluates the resolver of `field` with `eval`, running the middlewares
+/// around it.
+pub async fn run_middlewares<F, Fut>(
+    middlewares: &[Arc<dyn ExecutionMiddleware>],
+    field: &Field<ConstValue>,
+    ctx: &RequestContext,
+    eval: F,
+) -> Result<ConstValue>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<ConstValue>>,
+{
+    let start = Instant::now();
+    let mut ran = 0;
+    let mut short_circuit = None;
+
+    for middleware in middlewares {
+        ran += 1;
+        match middleware.before(field, ctx).await {
+            Ok(None) => continue,
+            Ok(Some(value)) => short_circuit = Some(Ok(value)),
+            Err(err) => short_circuit = Some(Err(err)),
+        }
+        break;
+    }
+
+    let mut result = match short_circuit {
+        Some(result) => result,
+        None => eval().await,
+    };
+
+    let elapsed = start.elapsed();
+    for middleware in middlewares[..ran].iter().rev() {
+        result = middleware.after(field, result, elapsed).await;
+    }
+
+    result
+}
+
+/// Logs the fields whose resolvers take longer than the threshold.
+pub struct SlowResolverLogger {
+    threshold: Duration,
+}
+
+impl SlowResolverLogger {
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutionMiddleware for SlowResolverLogger {
+    async fn after(
+        &self,
+        field: &Field<ConstValue>,
+        result: Result<ConstValue>,
+        elapsed: Duration,
+    ) -> Result<ConstValue> {
+        if elapsed >= self.threshold {
+            tracing::warn!(
+                "Resolver of `{}` took {}ms, over the threshold of {}ms",
+                field.name,
+                elapsed.as_millis(),
+                self.threshold.as_millis()
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures_util::StreamExt;
+    use http::HeaderMap;
+    use tailcall_valid::Validator;
+
+    use super::*;
+    use crate::core::app_context::AppContext;
+    use crate::core::async_graphql_hyper::{GraphQLRequest, GraphQLRequestLike};
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::ir;
+    use crate::core::jit::{self, Error};
+    use crate::core::rest::EndpointSet;
+    use crate::core::runtime::test::init;
+    use crate::include_config;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    struct Recorder {
+        name: &'static str,
+        log: Log,
+        short_circuit: Option<Result<ConstValue>>,
+    }
+
+    impl Recorder {
+        fn new(name: &'static str, log: &Log) -> Self {
+            Self { name, log: log.clone(), short_circuit: None }
+        }
+
+        fn short_circuit(self, result: Result<ConstValue>) -> Self {
+            Self { short_circuit: Some(result), ..self }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExecutionMiddleware for Recorder {
+        async fn before(
+            &self,
+            field: &Field<ConstValue>,
+            _: &RequestContext,
+        ) -> Result<Option<ConstValue>> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before {} {}", self.name, field.name));
+            self.short_circuit.clone().transpose()
+        }
+
+        async fn after(
+            &self,
+            _: &Field<ConstValue>,
+            result: Result<ConstValue>,
+            _: Duration,
+        ) -> Result<ConstValue> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("after {}", self.name));
+            result
+        }
+    }
+
+    fn field() -> Field<ConstValue> {
+        let config = include_config!("./fixtures/dedupe.graphql").unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let plan = jit::Request::<ConstValue>::new("{ posts { id } }")
+            .create_plan(&blueprint)
+            .unwrap();
+        plan.selection[0].clone()
+    }
+
+    async fn run(middlewares: &[Arc<dyn ExecutionMiddleware>], log: &Log) -> Result<ConstValue> {
+        let log = log.clone();
+        run_middlewares(
+            middlewares,
+            &field(),
+            &RequestContext::default(),
+            || async move {
+                log.lock().unwrap().push("eval".to_string());
+                Ok(ConstValue::from("resolved"))
+            },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_ordering() {
+        let log = Log::default();
+        let middlewares: Vec<Arc<dyn ExecutionMiddleware>> = vec![
+            Arc::new(Recorder::new("a", &log)),
+            Arc::new(Recorder::new("b", &log)),
+        ];
+
+        let result = run(&middlewares, &log).await.unwrap();
+
+        assert_eq!(result, ConstValue::from("resolved"));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "before a posts",
+                "before b posts",
+                "eval",
+                "after b",
+                "after a"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_circuit_with_value() {
+        let log = Log::default();
+        let middlewares: Vec<Arc<dyn ExecutionMiddleware>> = vec![
+            Arc::new(Recorder::new("a", &log)),
+            Arc::new(Recorder::new("b", &log).short_circuit(Ok(ConstValue::from("cached")))),
+            Arc::new(Recorder::new("c", &log)),
+        ];
+
+        let result = run(&middlewares, &log).await.unwrap();
+
+        assert_eq!(result, ConstValue::from("cached"));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["before a posts", "before b posts", "after b", "after a"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_circuit_with_error() {
+        let log = Log::default();
+        let error = Error::IR(ir::Error::IO("quota exceeded".to_string()));
+        let middlewares: Vec<Arc<dyn ExecutionMiddleware>> = vec![
+            Arc::new(Recorder::new("a", &log).short_circuit(Err(error))),
+            Arc::new(Recorder::new("b", &log)),
+        ];
+
+        let result = run(&middlewares, &log).await;
+
+        assert!(result.unwrap_err().to_string().contains("quota exceeded"));
+        assert_eq!(*log.lock().unwrap(), vec!["before a posts", "after a"]);
+    }
+
+    struct Gate {
+        name: &'static str,
+        log: Log,
+        reject: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Middleware for Gate {
+        async fn before(&self, request: &Request<ConstValue>) -> Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before {} {}", self.name, request.query));
+            if self.reject {
+                Err(Error::IR(ir::Error::IO("unauthorized".to_string())))
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn after(&self, response: &Response<ConstValue>) {
+            self.log.lock().unwrap().push(format!(
+                "after {} {}",
+                self.name,
+                response.errors.is_empty()
+            ));
+        }
+    }
+
+    fn gate(name: &'static str, log: &Log, reject: bool) -> Gate {
+        Gate { name, log: log.clone(), reject }
+    }
+
+    fn executor(gates: Vec<Gate>) -> jit::JITExecutor {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query { user: User @expr(body: {id: 1}) }
+            type User { id: Int }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let app_ctx = gates.into_iter().fold(
+            AppContext::new(blueprint, init(None), EndpointSet::default()),
+            AppContext::request_middleware,
+        );
+        let app_ctx = Arc::new(app_ctx);
+        let req_ctx = Arc::new(RequestContext::from(app_ctx.as_ref()));
+        let operation_id =
+            GraphQLRequest(async_graphql::Request::new("")).operation_id(&HeaderMap::new());
+        jit::JITExecutor::new(app_ctx, req_ctx, operation_id)
+    }
+
+    #[tokio::test]
+    async fn test_request_middleware() {
+        let log = Log::default();
+        let executor = executor(vec![gate("a", &log, false)])
+            .with_middleware(Arc::new(gate("b", &log, false)));
+
+        let response = executor
+            .execute(async_graphql::Request::new("{ user { id } }"))
+            .await;
+
+        assert!(response.is_ok);
+        assert_eq!(
+            String::from_utf8_lossy(&response.body),
+            r#"{"data":{"user":{"id":1}}}"#
+        );
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "before a { user { id } }",
+                "before b { user { id } }",
+                "after b true",
+                "after a true"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_middleware_rejection() {
+        let log = Log::default();
+        let executor = executor(vec![gate("a", &log, true), gate("b", &log, false)]);
+
+        let response = executor
+            .execute(async_graphql::Request::new("{ user { id } }"))
+            .await;
+
+        assert!(!response.is_ok);
+        assert!(String::from_utf8_lossy(&response.body).contains("unauthorized"));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["before a { user { id } }", "after a false"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_middleware_stream() {
+        let log = Log::default();
+        let executor = executor(vec![gate("a", &log, false)]);
+
+        let responses = executor
+            .execute_stream(async_graphql::Request::new("{ user { id } }"))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(responses.len(), 1);
+        assert_eq!(
+            String::from_utf8_lossy(&responses[0].body),
+            r#"{"data":{"user":{"id":1}}}"#
+        );
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["before a { user { id } }", "after a true"]
+        );
+    }
+}
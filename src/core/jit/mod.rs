@@ -1,5 +1,7 @@
+mod admission;
 mod exec;
 pub mod graphql_error;
+mod middleware;
 mod model;
 mod store;
 mod synth;
@@ -19,9 +21,13 @@ pub mod fixtures;
 mod graphql_executor;
 
 // Public Exports
+pub use admission::*;
+pub use builder::Builder;
 pub use error::*;
+pub use exec::FieldTiming;
 pub use exec_const::*;
 pub use graphql_executor::*;
+pub use middleware::*;
 pub use model::*;
 pub use request::*;
 pub use response::*;
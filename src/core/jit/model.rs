@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::num::NonZeroU64;
 use std::sync::Arc;
@@ -7,13 +7,14 @@ use std::sync::Arc;
 use async_graphql::parser::types::{ConstDirective, OperationType};
 use async_graphql::{Name, Positioned as AsyncPositioned, ServerError};
 use async_graphql_value::ConstValue;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 use super::Error;
-use crate::core::blueprint::Index;
-use crate::core::ir::model::IR;
+use crate::core::blueprint::{Index, Poll, QueryField};
+use crate::core::ir::model::{IO, IR};
 use crate::core::ir::TypedValue;
-use crate::core::json::{JsonLike, JsonLikeOwned};
+use crate::core::json::{JsonLike, JsonLikeOwned, Projection};
 use crate::core::path::PathString;
 use crate::core::scalar::Scalar;
 
@@ -93,6 +94,40 @@ impl<Input> Field<Input> {
     pub fn iter(&self) -> impl Iterator<Item = &Field<Input>> {
         self.selection.iter()
     }
+
+    /// The keys of the field's value that its selection reads, with the
+    /// fallback keys of `upstreamKeyCase`. It's `None` when a nested field has
+    /// a resolver, since it can read any key of the value it's resolved from.
+    pub fn projection(&self) -> Option<Projection> {
+        if self.selection.is_empty() {
+            return Some(Projection::All);
+        }
+        let type_name = self.type_of.name();
+        let mut fields = IndexMap::new();
+        for field in self.iter() {
+            if field.name == "__typename"
+                || field
+                    .type_condition
+                    .as_ref()
+                    .is_some_and(|type_condition| type_condition != type_name)
+            {
+                continue;
+            }
+            if field.ir.is_some() {
+                return None;
+            }
+            // the value is read from the fallback key when the name is missing
+            let projection = field.projection()?;
+            for key in std::iter::once(&field.name).chain(&field.fallback_key) {
+                let projection = match fields.shift_remove(key) {
+                    Some(other) => Projection::merge(other, projection.clone()),
+                    None => projection.clone(),
+                };
+                fields.insert(key.clone(), projection);
+            }
+        }
+        Some(Projection::Fields(fields))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -191,6 +226,12 @@ pub struct Field<Input> {
     pub directives: Vec<Directive<Input>>,
     pub is_enum: bool,
     pub scalar: Option<Scalar>,
+    /// Leave the field out of the response when it's `null`, see
+    /// `@modify(omitIfNull:)`.
+    pub omit_if_null: bool,
+    /// The key the value is read from when the parent value has no key named
+    /// after the field, see `@server(upstreamKeyCase:)`.
+    pub fallback_key: Option<String>,
 }
 
 pub struct DFS<'a, Input> {
@@ -260,6 +301,8 @@ impl<Input> Field<Input> {
                 .collect::<Result<_, _>>()?,
             is_enum: self.is_enum,
             scalar: self.scalar,
+            omit_if_null: self.omit_if_null,
+            fallback_key: self.fallback_key,
         })
     }
 }
@@ -384,6 +427,24 @@ impl<Input> OperationPlan<Input> {
         self.operation_type == OperationType::Query
     }
 
+    /// Check if current graphQL operation is subscription
+    pub fn is_subscription(&self) -> bool {
+        self.operation_type == OperationType::Subscription
+    }
+
+    /// Returns the polling settings of the field selected by a subscription
+    pub fn poll(&self) -> Option<&Poll> {
+        if !self.is_subscription() {
+            return None;
+        }
+
+        let field = self.selection.first()?;
+        match self.index.get_field(&self.root_name, &field.name)? {
+            QueryField::Field((definition, _)) => definition.poll.as_ref(),
+            QueryField::InputField(_) => None,
+        }
+    }
+
     /// Returns a flat [Field] representation
     pub fn iter_dfs(&self) -> DFS<Input> {
         DFS { stack: vec![self.selection.iter()] }
@@ -397,6 +458,43 @@ impl<Input> OperationPlan<Input> {
         self.selection.iter().map(count).sum()
     }
 
+    /// Returns the number of levels of the deepest selection, eg. 2 for
+    /// `{ posts { id } }`
+    pub fn depth(&self) -> usize {
+        fn depth<A>(field: &Field<A>) -> usize {
+            1 + field.selection.iter().map(depth).max().unwrap_or(0)
+        }
+        self.selection.iter().map(depth).max().unwrap_or(0)
+    }
+
+    /// Returns the number of fields in the plan that are resolved by calling
+    /// an upstream over `@http` or `@grpc`. A field is counted once, even
+    /// when it's resolved for every item of a list.
+    pub fn estimated_fetch_count(&self) -> usize {
+        fn is_fetch(ir: &IR) -> bool {
+            match ir {
+                IR::IO(io) => matches!(io, IO::Http { .. } | IO::Grpc { .. }),
+                IR::Cache(cache) => matches!(*cache.io, IO::Http { .. } | IO::Grpc { .. }),
+                IR::Path(ir, _) => is_fetch(ir),
+                IR::Protect(_, ir) => is_fetch(ir),
+                IR::Map(map) => is_fetch(&map.input),
+                IR::Pipe(ir, ir1) => is_fetch(ir) || is_fetch(ir1),
+                IR::Merge(vec) => vec.iter().any(is_fetch),
+                IR::Discriminate(_, ir) => is_fetch(ir),
+                IR::When(_, ir) => is_fetch(ir),
+                IR::ByOperation { query, mutation, subscription } => {
+                    is_fetch(query) || is_fetch(mutation) || is_fetch(subscription)
+                }
+                IR::Entity(hash_map) => hash_map.values().any(is_fetch),
+                IR::Dynamic(_) | IR::ContextPath(_) | IR::Service(_) => false,
+            }
+        }
+
+        self.iter_dfs()
+            .filter(|field| field.ir.as_ref().is_some_and(is_fetch))
+            .count()
+    }
+
     /// Check if the field is of scalar type
     pub fn field_is_scalar(&self, field: &Field<Input>) -> bool {
         self.index.type_is_scalar(field.type_of.name())
@@ -438,6 +536,50 @@ impl<Input> OperationPlan<Input> {
     }
 }
 
+impl OperationPlan<async_graphql_value::Value> {
+    /// Returns the names of the variables the plan depends on: the ones
+    /// passed to the arguments of the fields, which are also all that the
+    /// `@http` templates can read through `.args`, and the ones deciding
+    /// `@skip` and `@include`. The variables sent but not referenced don't
+    /// change the result, so they can be left out of a cache key.
+    pub fn variables_used(&self) -> HashSet<String> {
+        fn collect(value: &async_graphql_value::Value, names: &mut HashSet<String>) {
+            match value {
+                async_graphql_value::Value::Variable(name) => {
+                    names.insert(name.to_string());
+                }
+                async_graphql_value::Value::List(list) => {
+                    list.iter().for_each(|value| collect(value, names))
+                }
+                async_graphql_value::Value::Object(map) => {
+                    map.values().for_each(|value| collect(value, names))
+                }
+                _ => {}
+            }
+        }
+
+        let mut names = HashSet::new();
+        for field in self.iter_dfs() {
+            for arg in &field.args {
+                arg.value
+                    .iter()
+                    .chain(arg.default_value.iter())
+                    .for_each(|value| collect(value, &mut names));
+            }
+            for directive in &field.directives {
+                directive
+                    .arguments
+                    .iter()
+                    .for_each(|(_, value)| collect(value, &mut names));
+            }
+            for variable in field.skip.iter().chain(field.include.iter()) {
+                names.insert(variable.as_str().to_string());
+            }
+        }
+        names
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Directive<Input> {
     pub name: String,
@@ -574,11 +716,13 @@ impl From<ServerError> for Positioned<Error> {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
+
     use async_graphql::parser::types::ConstDirective;
     use async_graphql::Request;
     use async_graphql_value::ConstValue;
 
-    use super::{Directive, OperationPlan};
+    use super::{Directive, OperationPlan, Projection};
     use crate::core::blueprint::Blueprint;
     use crate::core::config::ConfigModule;
     use crate::core::jit;
@@ -625,4 +769,57 @@ mod test {
 
         assert!(actual.is_dedupe);
     }
+
+    #[test]
+    fn test_estimated_fetch_count() {
+        assert_eq!(plan(r#"{ posts { id } }"#).estimated_fetch_count(), 1);
+        assert_eq!(
+            plan(r#"{ posts { id user { id comments { body } } } users { name } }"#)
+                .estimated_fetch_count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(plan(r#"{ posts { id } }"#).depth(), 2);
+        assert_eq!(
+            plan(r#"{ posts { id user { id comments { body } } } users { name } }"#).depth(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_projection() {
+        let posts = plan(r#"{ posts { id title t: title __typename ... on Post { body } } }"#);
+        let expected = Projection::Fields(
+            ["id", "title", "body"]
+                .into_iter()
+                .map(|name| (name.to_string(), Projection::All))
+                .collect(),
+        );
+        assert_eq!(posts.selection[0].projection(), Some(expected));
+
+        let posts = plan(r#"{ posts { id user { name } } }"#);
+        assert_eq!(posts.selection[0].projection(), None);
+    }
+
+    #[test]
+    fn test_variables_used() {
+        let config = include_config!("./fixtures/jsonplaceholder-mutation.graphql").unwrap();
+        let module = ConfigModule::from(config);
+        let bp = Blueprint::try_from(&module).unwrap();
+        let query = r#"
+            query ($id: ID!, $limit: Int, $withPosts: Boolean!) {
+                user(id: $id) { id name }
+                posts @include(if: $withPosts) { id }
+            }
+        "#;
+        let plan = jit::Request::from(Request::new(query))
+            .create_plan(&bp)
+            .unwrap();
+
+        let expected = HashSet::from(["id".to_string(), "withPosts".to_string()]);
+        assert_eq!(plan.variables_used(), expected);
+    }
 }
@@ -5,7 +5,9 @@ use async_graphql_value::ConstValue;
 use serde::Deserialize;
 use tailcall_valid::Validator;
 
-use super::{transform, Builder, OperationPlan, Result, Variables};
+use super::{
+    transform, Builder, FetchCountRule, OperationPlan, QueryComplexity, Result, Variables,
+};
 use crate::core::blueprint::Blueprint;
 use crate::core::transform::TransformerOps;
 use crate::core::Transform;
@@ -40,12 +42,31 @@ impl Request<ConstValue> {
     pub fn create_plan(
         &self,
         blueprint: &Blueprint,
+    ) -> Result<OperationPlan<async_graphql_value::Value>> {
+        let plan = self.build_plan(blueprint)?;
+
+        if let Some(limit) = blueprint.server.max_fetch_count {
+            FetchCountRule(limit).check(&plan)?;
+        }
+
+        if let Some(limit) = blueprint.server.max_complexity {
+            QueryComplexity::new(limit).check(&plan)?;
+        }
+
+        Ok(plan)
+    }
+
+    /// Builds the plan without checking it against the admission rules, to
+    /// look into the operations that would be rejected.
+    pub fn build_plan(
+        &self,
+        blueprint: &Blueprint,
     ) -> Result<OperationPlan<async_graphql_value::Value>> {
         let doc = async_graphql::parser::parse_query(&self.query)?;
         let builder = Builder::new(blueprint, &doc);
         let plan = builder.build(self.operation_name.as_deref())?;
 
-        transform::CheckConst::new()
+        let plan = transform::CheckConst::new()
             .pipe(transform::CheckProtected::new())
             .pipe(transform::AuthPlanner::new())
             .pipe(transform::CheckDedupe::new())
@@ -56,7 +77,9 @@ impl Request<ConstValue> {
             // both transformers are infallible right now
             // but we can't just unwrap this in stable rust
             // so convert to the Unknown error
-            .map_err(|_| super::Error::Unknown)
+            .map_err(|_| super::Error::Unknown)?;
+
+        Ok(plan)
     }
 }
 
@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
+use async_graphql::Name;
+use async_graphql_value::ConstValue;
 use derive_setters::Setters;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::graphql_error::GraphQLError;
 use super::Positioned;
@@ -90,6 +92,64 @@ where
     }
 }
 
+/// Partial result of a deferred fragment as described by the
+/// [incremental delivery](https://github.com/graphql/graphql-spec/pull/742) spec.
+#[derive(Clone, Debug, Default)]
+pub struct ResponsePatch {
+    /// Path to the value the data belongs to, list items are addressed by
+    /// their index.
+    pub path: Vec<String>,
+    pub data: ConstValue,
+}
+
+impl Response<ConstValue> {
+    /// Applies the patch to the data of the response. The data of the patch is
+    /// deep merged into the value at the patch path, missing objects along the
+    /// path are created while patches that point to a missing list item or
+    /// inside a scalar are ignored.
+    pub fn merge(mut self, patch: ResponsePatch) -> Self {
+        if let Some(target) = lookup_mut(&mut self.data, &patch.path) {
+            deep_merge(target, patch.data);
+        }
+
+        self
+    }
+}
+
+fn lookup_mut<'a>(value: &'a mut ConstValue, path: &[String]) -> Option<&'a mut ConstValue> {
+    let Some((key, path)) = path.split_first() else {
+        return Some(value);
+    };
+
+    if value.is_null() {
+        *value = ConstValue::Object(Default::default());
+    }
+
+    let next = match value {
+        ConstValue::Object(map) => map.entry(Name::new(key)).or_insert(ConstValue::Null),
+        ConstValue::List(list) => list.get_mut(key.parse::<usize>().ok()?)?,
+        _ => return None,
+    };
+
+    lookup_mut(next, path)
+}
+
+fn deep_merge(target: &mut ConstValue, patch: ConstValue) {
+    match (target, patch) {
+        (ConstValue::Object(target), ConstValue::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
 /// Represents a GraphQL response in a serialized byte format.
 #[derive(Clone)]
 pub struct AnyResponse<Body> {
@@ -134,6 +194,27 @@ impl<V: Serialize> From<Response<V>> for AnyResponse<Vec<u8>> {
     }
 }
 
+impl AnyResponse<Vec<u8>> {
+    /// Deserializes the body back into the response it was serialized from.
+    pub fn to_response(&self) -> Response<ConstValue> {
+        #[derive(Default, Deserialize)]
+        #[serde(default)]
+        struct Body {
+            data: ConstValue,
+            errors: Vec<GraphQLError>,
+            extensions: Vec<(String, ConstValue)>,
+        }
+
+        let body: Body = serde_json::from_slice(&self.body).unwrap_or_default();
+        Response {
+            data: body.data,
+            errors: body.errors,
+            extensions: body.extensions,
+            cache_control: self.cache_control.clone(),
+        }
+    }
+}
+
 pub enum BatchResponse<Body> {
     Single(AnyResponse<Body>),
     Batch(Vec<AnyResponse<Body>>),
@@ -167,8 +248,9 @@ impl<Body> BatchResponse<Body> {
 #[cfg(test)]
 mod test {
     use async_graphql_value::ConstValue;
+    use serde_json::json;
 
-    use super::Response;
+    use super::{Response, ResponsePatch};
     use crate::core::jit::graphql_error::GraphQLError;
     use crate::core::jit::{self, Pos, Positioned};
 
@@ -295,4 +377,75 @@ mod test {
         let merged_resp = resp2.merge_with(&resp1);
         insta::assert_json_snapshot!(merged_resp);
     }
+
+    fn patch(path: &[&str], data: serde_json::Value) -> ResponsePatch {
+        ResponsePatch {
+            path: path.iter().map(|key| key.to_string()).collect(),
+            data: ConstValue::from_json(data).unwrap(),
+        }
+    }
+
+    fn response(data: serde_json::Value) -> Response<ConstValue> {
+        Response::new(Ok(ConstValue::from_json(data).unwrap()))
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let resp = response(json!({"user": {"id": 1, "address": {"city": "Delhi"}}}));
+
+        let merged = resp
+            .merge(patch(
+                &["user"],
+                json!({"name": "foo", "address": {"zip": "110001"}}),
+            ))
+            .merge(patch(&["user", "posts"], json!({"count": 2})));
+
+        assert_eq!(
+            merged.data.into_json().unwrap(),
+            json!({
+                "user": {
+                    "id": 1,
+                    "address": {"city": "Delhi", "zip": "110001"},
+                    "name": "foo",
+                    "posts": {"count": 2}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_patch_into_list_item() {
+        let resp = response(json!({"posts": [{"id": 1}, {"id": 2}]}));
+
+        let merged = resp.merge(patch(&["posts", "1"], json!({"title": "bar"})));
+
+        assert_eq!(
+            merged.data.into_json().unwrap(),
+            json!({"posts": [{"id": 1}, {"id": 2, "title": "bar"}]})
+        );
+    }
+
+    #[test]
+    fn test_merge_patch_into_empty_response() {
+        let resp = Response::<ConstValue>::default();
+
+        let merged = resp.merge(patch(&[], json!({"user": {"id": 1}})));
+
+        assert_eq!(merged.data.into_json().unwrap(), json!({"user": {"id": 1}}));
+    }
+
+    #[test]
+    fn test_merge_patch_with_invalid_path() {
+        let resp = response(json!({"posts": [{"id": 1}], "count": 1}));
+
+        let merged = resp
+            .merge(patch(&["posts", "5"], json!({"title": "bar"})))
+            .merge(patch(&["posts", "first"], json!({"title": "bar"})))
+            .merge(patch(&["count", "value"], json!({"title": "bar"})));
+
+        assert_eq!(
+            merged.data.into_json().unwrap(),
+            json!({"posts": [{"id": 1}], "count": 1})
+        );
+    }
 }
@@ -37,6 +37,9 @@ impl DataPath {
 #[derive(Debug)]
 pub struct Store<Data> {
     data: HashMap<usize, Data>,
+    /// The data of single items of the fields resolved for every item of a
+    /// list, by the [DataPath] of the item, eg. the error of one of them.
+    items: HashMap<usize, HashMap<Vec<usize>, Data>>,
 }
 
 impl<Data> Default for Store<Data> {
@@ -47,7 +50,7 @@ impl<Data> Default for Store<Data> {
 
 impl<Data> Store<Data> {
     pub fn new() -> Self {
-        Store { data: HashMap::new() }
+        Store { data: HashMap::new(), items: HashMap::new() }
     }
 
     pub fn set_data(&mut self, field_id: FieldId, data: Data) {
@@ -61,4 +64,17 @@ impl<Data> Store<Data> {
     pub fn get(&self, field_id: &FieldId) -> Option<&Data> {
         self.data.get(&field_id.as_usize())
     }
+
+    pub fn set_item(&mut self, field_id: &FieldId, data_path: Vec<usize>, data: Data) {
+        self.items
+            .entry(field_id.as_usize())
+            .or_default()
+            .insert(data_path, data);
+    }
+
+    pub fn get_item(&self, field_id: &FieldId, data_path: &DataPath) -> Option<&Data> {
+        self.items
+            .get(&field_id.as_usize())?
+            .get(data_path.as_slice())
+    }
 }
@@ -0,0 +1,233 @@
+use std::borrow::Cow;
+
+use crate::core::json::{JsonLike, JsonPrimitive};
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "Boolean", "String", "ID"];
+
+/// Checks if the type is one of the scalars defined by the GraphQL spec.
+pub fn is_builtin(type_of: &str) -> bool {
+    BUILTIN_SCALARS.contains(&type_of)
+}
+
+/// Coerces an upstream value into one of the built-in scalars according to
+/// the [result coercion](https://spec.graphql.org/October2021/#sec-Scalars.Result-Coercion-and-Serialization)
+/// rules. Returns `None` if the value can't represent the scalar. In
+/// `lenient` mode strings are additionally parsed into numbers and booleans.
+pub fn coerce_builtin<'a, Value, Output>(
+    type_of: &str,
+    value: &'a Value,
+    lenient: bool,
+) -> Option<Output>
+where
+    Value: JsonLike<'a>,
+    Output: JsonLike<'a>,
+{
+    match type_of {
+        "Int" => as_int(value, lenient)
+            .and_then(|n| i32::try_from(n).ok())
+            .map(number),
+        "Float" => as_float(value, lenient).map(number),
+        "Boolean" => {
+            as_bool(value, lenient).map(|b| Output::from_primitive(JsonPrimitive::Bool(b)))
+        }
+        "String" => as_string(value).map(Output::string),
+        // integers are serialized in their string form, like the IDs sent as
+        // strings
+        "ID" => match as_int(value, false) {
+            Some(n) => Some(Output::string(Cow::Owned(n.to_string()))),
+            None => value.as_str().map(|s| Output::string(Cow::Borrowed(s))),
+        },
+        _ => None,
+    }
+}
+
+/// Name of the kind of the value used in error messages.
+pub fn value_type<'a, Value: JsonLike<'a>>(value: &'a Value) -> &'static str {
+    if value.is_null() {
+        "null"
+    } else if value.as_bool().is_some() {
+        "Boolean"
+    } else if value.as_i64().is_some_and(|n| i32::try_from(n).is_ok()) {
+        "Int"
+    } else if value.as_i64().is_some() || value.as_u64().is_some() || value.as_f64().is_some() {
+        "Float"
+    } else if value.as_str().is_some() {
+        "String"
+    } else if value.as_array().is_some() {
+        "List"
+    } else {
+        "Object"
+    }
+}
+
+fn number<'a, Output: JsonLike<'a>, N: Into<serde_json::Number>>(n: N) -> Output {
+    Output::from_primitive(JsonPrimitive::Number(n.into()))
+}
+
+fn as_int<'a, Value: JsonLike<'a>>(value: &'a Value, lenient: bool) -> Option<i64> {
+    if let Some(n) = value.as_i64() {
+        return Some(n);
+    }
+
+    // floats without a fractional part can be represented without losing
+    // information, as long as they are in the range of i64
+    if let Some(n) = value
+        .as_f64()
+        .filter(|n| n.fract() == 0.0 && *n >= i64::MIN as f64 && *n < i64::MAX as f64)
+    {
+        return Some(n as i64);
+    }
+
+    if lenient {
+        value.as_str().and_then(|s| s.trim().parse().ok())
+    } else {
+        None
+    }
+}
+
+fn as_float<'a, Value: JsonLike<'a>>(
+    value: &'a Value,
+    lenient: bool,
+) -> Option<serde_json::Number> {
+    if let Some(n) = value.as_i64() {
+        return Some(n.into());
+    }
+
+    let n = value.as_f64().or_else(|| {
+        if lenient {
+            value.as_str().and_then(|s| s.trim().parse().ok())
+        } else {
+            None
+        }
+    })?;
+
+    serde_json::Number::from_f64(n)
+}
+
+fn as_bool<'a, Value: JsonLike<'a>>(value: &'a Value, lenient: bool) -> Option<bool> {
+    if let Some(b) = value.as_bool() {
+        return Some(b);
+    }
+
+    if lenient {
+        match value.as_str()? {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+fn as_string<'a, Value: JsonLike<'a>>(value: &'a Value) -> Option<Cow<'a, str>> {
+    if let Some(s) = value.as_str() {
+        Some(Cow::Borrowed(s))
+    } else if let Some(b) = value.as_bool() {
+        Some(Cow::Owned(b.to_string()))
+    } else if let Some(n) = value.as_i64() {
+        Some(Cow::Owned(n.to_string()))
+    } else if let Some(n) = value.as_u64() {
+        Some(Cow::Owned(n.to_string()))
+    } else {
+        value.as_f64().map(|n| Cow::Owned(n.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::ConstValue;
+    use serde_json::json;
+
+    use super::*;
+
+    fn coerce(type_of: &str, value: serde_json::Value, lenient: bool) -> Option<ConstValue> {
+        let value = ConstValue::from_json(value).unwrap();
+        coerce_builtin::<ConstValue, ConstValue>(type_of, &value, lenient)
+    }
+
+    fn value(value: serde_json::Value) -> Option<ConstValue> {
+        Some(ConstValue::from_json(value).unwrap())
+    }
+
+    #[test]
+    fn test_int() {
+        assert_eq!(coerce("Int", json!(1), false), value(json!(1)));
+        assert_eq!(coerce("Int", json!(1.0), false), value(json!(1)));
+        assert_eq!(coerce("Int", json!(1.5), false), None);
+        assert_eq!(coerce("Int", json!("1"), false), None);
+        assert_eq!(coerce("Int", json!(true), false), None);
+        assert_eq!(coerce("Int", json!("1"), true), value(json!(1)));
+        assert_eq!(coerce("Int", json!("one"), true), None);
+    }
+
+    #[test]
+    fn test_int_range() {
+        assert_eq!(
+            coerce("Int", json!(i32::MAX), false),
+            value(json!(i32::MAX))
+        );
+        assert_eq!(
+            coerce("Int", json!(i32::MIN), false),
+            value(json!(i32::MIN))
+        );
+        assert_eq!(coerce("Int", json!(2147483648_i64), false), None);
+        assert_eq!(coerce("Int", json!(-2147483649_i64), false), None);
+        assert_eq!(coerce("Int", json!(1e20), false), None);
+        assert_eq!(coerce("Int", json!("2147483648"), true), None);
+        assert_eq!(
+            coerce("ID", json!(2147483648_i64), false),
+            value(json!("2147483648"))
+        );
+        assert_eq!(coerce("ID", json!(1e20), false), None);
+    }
+
+    #[test]
+    fn test_value_type() {
+        let value_type = |value| value_type(&ConstValue::from_json(value).unwrap());
+        assert_eq!(value_type(json!(1)), "Int");
+        assert_eq!(value_type(json!(2147483648_i64)), "Float");
+        assert_eq!(value_type(json!(1.5)), "Float");
+        assert_eq!(value_type(json!("1")), "String");
+    }
+
+    #[test]
+    fn test_float() {
+        assert_eq!(coerce("Float", json!(1.5), false), value(json!(1.5)));
+        assert_eq!(coerce("Float", json!(1), false), value(json!(1)));
+        assert_eq!(coerce("Float", json!("1.5"), false), None);
+        assert_eq!(coerce("Float", json!("1.5"), true), value(json!(1.5)));
+    }
+
+    #[test]
+    fn test_boolean() {
+        assert_eq!(coerce("Boolean", json!(true), false), value(json!(true)));
+        assert_eq!(coerce("Boolean", json!("true"), false), None);
+        assert_eq!(coerce("Boolean", json!(1), false), None);
+        assert_eq!(coerce("Boolean", json!("false"), true), value(json!(false)));
+    }
+
+    #[test]
+    fn test_string() {
+        assert_eq!(coerce("String", json!("a"), false), value(json!("a")));
+        assert_eq!(coerce("String", json!(1), false), value(json!("1")));
+        assert_eq!(coerce("String", json!(true), false), value(json!("true")));
+        assert_eq!(coerce("String", json!([1]), false), None);
+        assert_eq!(coerce("String", json!({"a": 1}), true), None);
+    }
+
+    #[test]
+    fn test_id() {
+        assert_eq!(coerce("ID", json!("a"), false), value(json!("a")));
+        assert_eq!(coerce("ID", json!(1), false), value(json!("1")));
+        assert_eq!(coerce("ID", json!(1.0), false), value(json!("1")));
+        assert_eq!(coerce("ID", json!(true), false), None);
+        assert_eq!(coerce("ID", json!(1.5), false), None);
+    }
+
+    #[test]
+    fn test_is_builtin() {
+        assert!(is_builtin("Int"));
+        assert!(!is_builtin("Email"));
+    }
+}
@@ -1,3 +1,4 @@
+mod coerce;
 mod synth;
 
 pub use synth::Synth;
@@ -1,9 +1,12 @@
 use std::borrow::Cow;
+use std::sync::Mutex;
 
+use super::coerce::{coerce_builtin, is_builtin, value_type};
 use crate::core::jit::model::{Field, OperationPlan, Variables};
 use crate::core::jit::store::{DataPath, Store};
 use crate::core::jit::{Error, PathSegment, Positioned, ValidationError};
 use crate::core::json::{JsonLike, JsonObjectLike};
+use crate::core::Type;
 
 type ValueStore<Value> = Store<Result<Value, Positioned<Error>>>;
 
@@ -11,6 +14,8 @@ pub struct Synth<'a, Value> {
     plan: &'a OperationPlan<Value>,
     store: ValueStore<Value>,
     variables: Variables<Value>,
+    lenient_coercion: bool,
+    errors: Mutex<Vec<Positioned<Error>>>,
 }
 
 impl<'a, Value> Synth<'a, Value> {
@@ -20,7 +25,26 @@ impl<'a, Value> Synth<'a, Value> {
         store: ValueStore<Value>,
         variables: Variables<Value>,
     ) -> Self {
-        Self { plan, store, variables }
+        Self {
+            plan,
+            store,
+            variables,
+            lenient_coercion: false,
+            errors: Mutex::new(vec![]),
+        }
+    }
+
+    /// Allows upstream values to be converted between strings, numbers and
+    /// booleans to match the declared scalar type.
+    pub fn lenient_coercion(mut self, lenient_coercion: bool) -> Self {
+        self.lenient_coercion = lenient_coercion;
+        self
+    }
+
+    /// Field errors that were resolved by setting the field to null during
+    /// synthesis.
+    pub fn errors(&self) -> Vec<Positioned<Error>> {
+        self.errors.lock().unwrap().clone()
     }
 }
 
@@ -46,9 +70,16 @@ where
             if !self.include(child) {
                 continue;
             }
-            // TODO: in case of error set `child.output_name` to null
-            // and append error to response error array
-            let val = self.iter(child, None, &DataPath::new(), &mut path, Some(root_name))?;
+            // the errors of the root resolvers fail the whole data, the errors
+            // below them are bubbled to their nearest nullable position
+            if let Some(Err(error)) = self.store.get(&child.id) {
+                return Err(error.clone());
+            }
+            let val = self.iter(child, None, &DataPath::new(), &mut path, Some(root_name));
+            let val = self.bubble(val, child.type_of.is_nullable())?;
+            if child.omit_if_null && val.is_null() {
+                continue;
+            }
             data.insert_key(&child.output_name, val);
         }
 
@@ -69,10 +100,34 @@ where
         Output: JsonLike<'a>,
     {
         path.push(PathSegment::Field(Cow::Borrowed(&node.output_name)));
+        let result = self.iter_field(node, value, data_path, path, root_name);
+        path.pop();
+
+        result
+    }
 
-        let result = match self.store.get(&node.id) {
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    fn iter_field<Output>(
+        &'a self,
+        node: &'a Field<Value>,
+        value: Option<&'a Value>,
+        data_path: &DataPath,
+        path: &mut Vec<PathSegment<'a>>,
+        root_name: Option<&'a str>,
+    ) -> Result<Output, Positioned<Error>>
+    where
+        Output: JsonLike<'a>,
+    {
+        match self.store.get(&node.id) {
             Some(value) => {
-                let mut value = value.as_ref().map_err(Clone::clone)?;
+                // the resolver failed for the whole field or for this item
+                let mut value = match (value, self.store.get_item(&node.id, data_path)) {
+                    (Err(error), _) | (_, Some(Err(error))) => {
+                        return Err(self.to_location_error(error.value.clone(), node, path));
+                    }
+                    (Ok(value), _) => value,
+                };
 
                 for index in data_path.as_slice() {
                     if let Some(arr) = value.as_array() {
@@ -85,16 +140,34 @@ where
                 if node.type_of.is_list() != value.as_array().is_some() {
                     return self.node_nullable_guard(node, path, None);
                 }
-                self.iter_inner(node, value, data_path, path)
+                self.iter_inner(node, &node.type_of, value, data_path, path)
             }
             None => match value {
-                Some(result) => self.iter_inner(node, result, data_path, path),
+                Some(result) => self.iter_inner(node, &node.type_of, result, data_path, path),
                 None => self.node_nullable_guard(node, path, root_name),
             },
-        };
+        }
+    }
 
-        path.pop();
-        result
+    /// Handles a field error, of its resolver or of its value, according to
+    /// the [spec](https://spec.graphql.org/October2021/#sec-Handling-Field-Errors):
+    /// if the position is nullable the error is recorded and null is
+    /// returned, otherwise the error propagates to the parent position.
+    fn bubble<Output>(
+        &'a self,
+        result: Result<Output, Positioned<Error>>,
+        nullable: bool,
+    ) -> Result<Output, Positioned<Error>>
+    where
+        Output: JsonLike<'a>,
+    {
+        match result {
+            Err(error) if nullable => {
+                self.errors.lock().unwrap().push(error);
+                Ok(Output::null())
+            }
+            result => result,
+        }
     }
 
     /// This guard ensures to return Null value only if node type permits it, in
@@ -122,11 +195,15 @@ where
         }
     }
 
+    /// Synthesizes the value at a single position of the field. The position
+    /// is either the field itself or an item of a list, and `type_of` is the
+    /// type at that position, eg. `Int!` for the items of a `[Int!]` field.
     #[allow(clippy::too_many_arguments)]
     #[inline(always)]
     fn iter_inner<Output>(
         &'a self,
         node: &'a Field<Value>,
+        type_of: &'a Type,
         value: &'a Value,
         data_path: &DataPath,
         path: &mut Vec<PathSegment<'a>>,
@@ -140,16 +217,22 @@ where
         }
 
         let eval_result = if value.is_null() {
-            // check the nullability of this type unwrapping list modifier
-            let is_nullable = match &node.type_of {
-                crate::core::Type::Named { non_null, .. } => !*non_null,
-                crate::core::Type::List { of_type, .. } => of_type.is_nullable(),
-            };
-            if is_nullable {
+            if type_of.is_nullable() {
                 Ok(Output::null())
             } else {
                 Err(ValidationError::ValueRequired.into())
             }
+        } else if let (true, Some(arr)) = (type_of.is_list(), value.as_array()) {
+            return self.iter_list(node, type_of, arr, data_path, path);
+        } else if node.scalar.is_some() && is_builtin(node.type_of.name()) {
+            match coerce_builtin(node.type_of.name(), value, self.lenient_coercion) {
+                Some(value) => Ok(value),
+                None => Err(ValidationError::ScalarMismatch {
+                    type_of: node.type_of.name().to_string(),
+                    value_type: value_type(value),
+                }
+                .into()),
+            }
         } else if node.scalar.is_some() {
             let scalar = node.scalar.as_ref().unwrap();
 
@@ -157,7 +240,7 @@ where
             // by async_graphql anyway so it should be done after replacing
             // default engine with JIT
             if scalar.validate(value) {
-                Ok(Output::clone_from(value))
+                Ok(scalar.serialize(value))
             } else {
                 Err(
                     ValidationError::ScalarInvalid { type_of: node.type_of.name().to_string() }
@@ -201,26 +284,22 @@ where
                             let value = if child.name == "__typename" {
                                 Output::string(node.value_type(value).into())
                             } else {
-                                let val = obj.get_key(child.name.as_str());
-                                self.iter(child, val, data_path, path, None)?
+                                let val = obj
+                                    .get_key(child.name.as_str())
+                                    .or_else(|| obj.get_key(child.fallback_key.as_deref()?));
+                                let val = self.iter(child, val, data_path, path, None);
+                                self.bubble(val, child.type_of.is_nullable())?
                             };
+                            if child.omit_if_null && value.is_null() {
+                                continue;
+                            }
                             fields.push((child.output_name.as_str(), value));
                         }
                     }
 
                     Ok(Output::object(Output::JsonObject::from_vec(fields)))
                 }
-                (Some(arr), _) => {
-                    let mut ans = Vec::with_capacity(arr.len());
-                    for (i, val) in arr.iter().enumerate() {
-                        path.push(PathSegment::Index(i));
-                        let val =
-                            self.iter_inner(node, val, &data_path.clone().with_index(i), path)?;
-                        path.pop();
-                        ans.push(val);
-                    }
-                    Ok(Output::array(ans))
-                }
+                (Some(arr), _) => return self.iter_list(node, type_of, arr, data_path, path),
                 _ => Ok(Output::clone_from(value)),
             }
         };
@@ -228,6 +307,40 @@ where
         eval_result.map_err(|e| self.to_location_error(e, node, path))
     }
 
+    /// Synthesizes every item of the list. An error in a nullable item only
+    /// nulls that item, while an error in a non-null item propagates and nulls
+    /// the whole list or its nearest nullable ancestor.
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    fn iter_list<Output>(
+        &'a self,
+        node: &'a Field<Value>,
+        type_of: &'a Type,
+        arr: &'a [Value],
+        data_path: &DataPath,
+        path: &mut Vec<PathSegment<'a>>,
+    ) -> Result<Output, Positioned<Error>>
+    where
+        Output: JsonLike<'a>,
+    {
+        // arrays returned for a non-list type are synthesized item by item with
+        // the same type and nullable items
+        let (item_type, is_item_nullable) = match type_of {
+            Type::List { of_type, .. } => (of_type.as_ref(), of_type.is_nullable()),
+            Type::Named { .. } => (type_of, true),
+        };
+
+        let mut ans = Vec::with_capacity(arr.len());
+        for (i, val) in arr.iter().enumerate() {
+            path.push(PathSegment::Index(i));
+            let val = self.iter_inner(node, item_type, val, &data_path.clone().with_index(i), path);
+            path.pop();
+            ans.push(self.bubble(val, is_item_nullable)?);
+        }
+
+        Ok(Output::array(ans))
+    }
+
     fn to_location_error(
         &'a self,
         error: Error,
@@ -251,17 +364,19 @@ where
 mod tests {
     use async_graphql_value::ConstValue;
     use serde::{Deserialize, Serialize};
+    use serde_json::json;
     use tailcall_valid::Validator;
 
     use super::ValueStore;
     use crate::core::blueprint::Blueprint;
     use crate::core::config::{Config, ConfigModule};
+    use crate::core::ir;
     use crate::core::jit::builder::Builder;
     use crate::core::jit::fixtures::JP;
     use crate::core::jit::model::{FieldId, Variables};
     use crate::core::jit::store::Store;
     use crate::core::jit::synth::Synth;
-    use crate::core::jit::OperationPlan;
+    use crate::core::jit::{Error, OperationPlan, Positioned};
     use crate::core::json::JsonLike;
 
     const POSTS: &str = r#"
@@ -473,4 +588,432 @@ mod tests {
         let val: serde_json_borrow::Value = synth.synthesize().unwrap();
         insta::assert_snapshot!(serde_json::to_string_pretty(&val).unwrap())
     }
+
+    const COERCION_CONFIG: &str = r#"
+        schema { query: Query }
+
+        type Query {
+            foo: Foo @http(url: "http://localhost/foo")
+            bar: Foo! @http(url: "http://localhost/bar")
+        }
+
+        type Foo {
+            int: Int
+            float: Float
+            bool: Boolean
+            name: String
+            status: Status
+            required: Int!
+            items: [Int]
+            requiredItems: [Int!]
+            posts: [Post]
+            postsItemsRequired: [Post!]
+            postsRequired: [Post]!
+            postsAllRequired: [Post!]!
+        }
+
+        type Post {
+            id: Int!
+            author: User! @http(url: "http://localhost/users/{{.value.id}}")
+        }
+
+        type User {
+            name: String
+        }
+
+        enum Status {
+            ACTIVE
+            INACTIVE
+        }
+    "#;
+
+    /// Synthesizes the response for a query whose single root field resolved
+    /// to `data`. Returns the data along with the recorded errors as
+    /// `(message, path)` pairs.
+    fn synth_coerced(
+        query: &str,
+        data: serde_json::Value,
+        lenient: bool,
+    ) -> (serde_json::Value, Vec<(String, serde_json::Value)>) {
+        synth_with_config(COERCION_CONFIG, query, data, lenient)
+    }
+
+    fn synth_with_config(
+        config: &str,
+        query: &str,
+        data: serde_json::Value,
+        lenient: bool,
+    ) -> (serde_json::Value, Vec<(String, serde_json::Value)>) {
+        synth_with_store(config, query, lenient, |_, store| {
+            store.set_data(FieldId::new(0), Ok(ConstValue::from_json(data).unwrap()));
+        })
+    }
+
+    /// Synthesizes the response for a query whose store is filled by
+    /// `fill_store`, which is given the plan to look up the fields.
+    fn synth_with_store(
+        config: &str,
+        query: &str,
+        lenient: bool,
+        fill_store: impl FnOnce(&OperationPlan<ConstValue>, &mut ValueStore<ConstValue>),
+    ) -> (serde_json::Value, Vec<(String, serde_json::Value)>) {
+        let doc = async_graphql::parser::parse_query(query).unwrap();
+        let config = Config::from_sdl(config).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let plan = Builder::new(&blueprint, &doc).build(None).unwrap();
+        let plan = plan
+            .try_map(|v| Deserialize::deserialize(v.into_json().unwrap()))
+            .unwrap();
+
+        let mut store: ValueStore<ConstValue> = Store::new();
+        fill_store(&plan, &mut store);
+
+        let synth = Synth::new(&plan, store, Variables::new()).lenient_coercion(lenient);
+        let (data, mut errors) = match synth.synthesize::<ConstValue>() {
+            Ok(data) => (data.into_json().unwrap(), vec![]),
+            Err(error) => (serde_json::Value::Null, vec![error]),
+        };
+        errors.extend(synth.errors());
+
+        let errors = errors
+            .into_iter()
+            .map(|e| (e.value.to_string(), serde_json::to_value(&e.path).unwrap()))
+            .collect();
+
+        (data, errors)
+    }
+
+    fn mismatch(
+        type_of: &str,
+        value_type: &str,
+        path: serde_json::Value,
+    ) -> (String, serde_json::Value) {
+        (
+            format!(r#"internal: expected a value of type "{type_of}", found {value_type}"#),
+            path,
+        )
+    }
+
+    #[test]
+    fn test_coercion_mismatches() {
+        let (data, errors) = synth_coerced(
+            "{ foo { int float bool name status } }",
+            json!({"int": "1", "float": true, "bool": "yes", "name": ["foo"], "status": "UNKNOWN"}),
+            false,
+        );
+
+        assert_eq!(
+            data,
+            json!({"foo": {"int": null, "float": null, "bool": null, "name": null, "status": null}})
+        );
+        assert_eq!(
+            errors,
+            vec![
+                mismatch("Int", "String", json!(["foo", "int"])),
+                mismatch("Float", "Boolean", json!(["foo", "float"])),
+                mismatch("Boolean", "String", json!(["foo", "bool"])),
+                mismatch("String", "List", json!(["foo", "name"])),
+                (
+                    r#"internal: invalid item for enum "Status""#.to_string(),
+                    json!(["foo", "status"])
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coercion_valid_values() {
+        let (data, errors) = synth_coerced(
+            "{ foo { int float bool name status } }",
+            json!({"int": 1.0, "float": 1, "bool": false, "name": 42, "status": "ACTIVE"}),
+            false,
+        );
+
+        assert_eq!(
+            data,
+            json!({"foo": {"int": 1, "float": 1, "bool": false, "name": "42", "status": "ACTIVE"}})
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_coercion_bubbles_to_nullable_parent() {
+        let (data, errors) = synth_coerced(
+            "{ foo { name required } }",
+            json!({"name": "foo", "required": "1"}),
+            false,
+        );
+
+        assert_eq!(data, json!({"foo": null}));
+        assert_eq!(
+            errors,
+            vec![mismatch("Int", "String", json!(["foo", "required"]))]
+        );
+    }
+
+    #[test]
+    fn test_coercion_bubbles_to_root() {
+        let (data, errors) =
+            synth_coerced("{ bar { required } }", json!({"required": true}), false);
+
+        assert_eq!(data, serde_json::Value::Null);
+        assert_eq!(
+            errors,
+            vec![mismatch("Int", "Boolean", json!(["bar", "required"]))]
+        );
+    }
+
+    #[test]
+    fn test_coercion_list_items() {
+        let (data, errors) = synth_coerced(
+            "{ foo { items requiredItems } }",
+            json!({"items": [1, "2", 3], "requiredItems": [1, "2", 3]}),
+            false,
+        );
+
+        assert_eq!(
+            data,
+            json!({"foo": {"items": [1, null, 3], "requiredItems": null}})
+        );
+        assert_eq!(
+            errors,
+            vec![
+                mismatch("Int", "String", json!(["foo", "items", 1])),
+                mismatch("Int", "String", json!(["foo", "requiredItems", 1])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lenient_coercion() {
+        let (data, errors) = synth_coerced(
+            "{ foo { int float bool name } }",
+            json!({"int": "1", "float": "1.5", "bool": "true", "name": "foo"}),
+            true,
+        );
+
+        assert_eq!(
+            data,
+            json!({"foo": {"int": 1, "float": 1.5, "bool": true, "name": "foo"}})
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_list_bubbling() {
+        let post = |id: serde_json::Value| json!({"id": id});
+        let cases = [
+            // a failing item is nulled in place
+            (
+                "posts",
+                json!({"foo": {"posts": [post(json!(1)), null, post(json!(3))]}}),
+            ),
+            // a failing non-null item nulls the list
+            (
+                "postsItemsRequired",
+                json!({"foo": {"postsItemsRequired": null}}),
+            ),
+            (
+                "postsRequired",
+                json!({"foo": {"postsRequired": [post(json!(1)), null, post(json!(3))]}}),
+            ),
+            // neither the item nor the list can be null, so the parent is nulled
+            ("postsAllRequired", json!({"foo": null})),
+        ];
+
+        for (field, expected) in cases {
+            let query = format!("{{ foo {{ {field} {{ id }} }} }}");
+            let value = json!({ field: [post(json!(1)), post(json!("2")), post(json!(3))] });
+
+            let (data, errors) = synth_coerced(&query, value, false);
+
+            assert_eq!(data, expected, "{field}");
+            assert_eq!(
+                errors,
+                vec![mismatch("Int", "String", json!(["foo", field, 1, "id"]))],
+                "{field}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_list_bubbling_resolver_errors() {
+        let post = json!({"author": {"name": "foo"}});
+        let cases = [
+            // the failing item is nulled in place
+            ("posts", json!({"foo": {"posts": [post, null, post]}})),
+            // a failing non-null item nulls the list
+            (
+                "postsItemsRequired",
+                json!({"foo": {"postsItemsRequired": null}}),
+            ),
+            (
+                "postsRequired",
+                json!({"foo": {"postsRequired": [post, null, post]}}),
+            ),
+            // neither the item nor the list can be null, so the parent is nulled
+            ("postsAllRequired", json!({"foo": null})),
+        ];
+
+        for (field, expected) in cases {
+            let query = format!("{{ foo {{ {field} {{ author {{ name }} }} }} }}");
+            let posts = json!({ field: [{"id": 1}, {"id": 2}, {"id": 3}] });
+
+            // the resolver of the author of the second post fails, the
+            // executor nulls that item and keeps its error by its index
+            let (data, errors) = synth_with_store(COERCION_CONFIG, &query, false, |plan, store| {
+                let author = plan
+                    .iter_dfs()
+                    .find(|field| field.name == "author")
+                    .unwrap();
+                let user = json!({"name": "foo"});
+                let authors = json!([user, null, user]);
+                let error = Error::IR(ir::Error::IO("failed".to_string()));
+
+                store.set_data(FieldId::new(0), Ok(ConstValue::from_json(posts).unwrap()));
+                store.set(&author.id, Ok(ConstValue::from_json(authors).unwrap()));
+                store.set_item(&author.id, vec![1], Err(Positioned::new(error, author.pos)));
+            });
+
+            assert_eq!(data, expected, "{field}");
+            assert_eq!(
+                errors,
+                vec![(
+                    "IOException: failed".to_string(),
+                    json!(["foo", field, 1, "author"])
+                )],
+                "{field}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_nested_resolver_error() {
+        // the resolver fails for the whole field, which nulls the nearest
+        // nullable position instead of the whole data
+        let (data, errors) = synth_with_store(
+            COERCION_CONFIG,
+            "{ foo { posts { id author { name } } } }",
+            false,
+            |plan, store| {
+                let author = plan
+                    .iter_dfs()
+                    .find(|field| field.name == "author")
+                    .unwrap();
+                let posts = json!({"posts": [{"id": 1}]});
+                let error = Error::IR(ir::Error::IO("failed".to_string()));
+
+                store.set_data(FieldId::new(0), Ok(ConstValue::from_json(posts).unwrap()));
+                store.set(&author.id, Err(Positioned::new(error, author.pos)));
+            },
+        );
+
+        assert_eq!(data, json!({"foo": {"posts": [null]}}));
+        assert_eq!(
+            errors,
+            vec![(
+                "IOException: failed".to_string(),
+                json!(["foo", "posts", 0, "author"])
+            )]
+        );
+    }
+
+    #[test]
+    fn test_list_null_items() {
+        let cases = [
+            ("posts", json!({"foo": {"posts": [{"id": 1}, null]}}), None),
+            (
+                "postsItemsRequired",
+                json!({"foo": {"postsItemsRequired": null}}),
+                Some(json!(["foo", "postsItemsRequired", 1])),
+            ),
+            (
+                "postsRequired",
+                json!({"foo": {"postsRequired": [{"id": 1}, null]}}),
+                None,
+            ),
+            (
+                "postsAllRequired",
+                json!({"foo": null}),
+                Some(json!(["foo", "postsAllRequired", 1])),
+            ),
+        ];
+
+        for (field, expected, error_path) in cases {
+            let query = format!("{{ foo {{ {field} {{ id }} }} }}");
+            let value = json!({ field: [{"id": 1}, null] });
+
+            let (data, errors) = synth_coerced(&query, value, false);
+
+            let expected_errors = error_path
+                .map(|path| {
+                    let message = "internal: non-null types require a return value";
+                    (message.to_string(), path)
+                })
+                .into_iter()
+                .collect::<Vec<_>>();
+            assert_eq!(data, expected, "{field}");
+            assert_eq!(errors, expected_errors, "{field}");
+        }
+    }
+
+    #[test]
+    fn test_null_list_with_required_items() {
+        let (data, errors) = synth_coerced(
+            "{ foo { requiredItems postsItemsRequired { id } } }",
+            json!({"requiredItems": null, "postsItemsRequired": null}),
+            false,
+        );
+
+        assert_eq!(
+            data,
+            json!({"foo": {"requiredItems": null, "postsItemsRequired": null}})
+        );
+        assert!(errors.is_empty());
+    }
+
+    const OMIT_IF_NULL_CONFIG: &str = r#"
+        schema @server @upstream {
+            query: Query
+        }
+
+        type Query {
+            foo: Foo @http(url: "http://localhost/foo")
+            foos: [Foo] @http(url: "http://localhost/foos")
+        }
+
+        type Foo {
+            name: String
+            nick: String @modify(omitIfNull: true)
+        }
+    "#;
+
+    #[test]
+    fn test_omit_if_null() {
+        let synth = |query: &str, data: serde_json::Value| {
+            let (data, errors) = synth_with_config(OMIT_IF_NULL_CONFIG, query, data, false);
+            assert!(errors.is_empty());
+            data
+        };
+
+        assert_eq!(
+            synth("{ foo { name nick } }", json!({"name": null, "nick": null})),
+            json!({"foo": {"name": null}})
+        );
+        assert_eq!(
+            synth("{ foo { name nick } }", json!({"name": "a", "nick": "b"})),
+            json!({"foo": {"name": "a", "nick": "b"}})
+        );
+        assert_eq!(
+            synth("{ foo { alias: nick } }", json!({"nick": null})),
+            json!({"foo": {}})
+        );
+        assert_eq!(
+            synth(
+                "{ foos { nick } }",
+                json!([{"nick": "a"}, {"nick": null}, {}])
+            ),
+            json!({"foos": [{"nick": "a"}, {}, {}]})
+        );
+    }
 }
@@ -75,7 +75,12 @@ pub fn update_ir(ir: &mut IR, vec: &mut Vec<Auth>) {
             update_ir(ir1, vec);
             update_ir(ir2, vec);
         }
-        IR::Discriminate(_, ir) => {
+        IR::ByOperation { query, mutation, subscription } => {
+            update_ir(query, vec);
+            update_ir(mutation, vec);
+            update_ir(subscription, vec);
+        }
+        IR::Discriminate(_, ir) | IR::When(_, ir) => {
             update_ir(ir, vec);
         }
         IR::Merge(irs) => {
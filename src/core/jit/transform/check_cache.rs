@@ -29,6 +29,12 @@ fn check_cache(ir: &IR) -> Option<NonZeroU64> {
         },
         IR::Merge(vec) => vec.iter().map(check_cache).min().unwrap_or_default(),
         IR::Discriminate(_, ir) => check_cache(ir),
+        IR::When(_, ir) => check_cache(ir),
+        IR::ByOperation { query, mutation, subscription } => [query, mutation, subscription]
+            .into_iter()
+            .map(|ir| check_cache(ir))
+            .collect::<Option<Vec<_>>>()
+            .and_then(|ages| ages.into_iter().min()),
         IR::Entity(hash_map) => hash_map.values().map(check_cache).min().unwrap_or_default(),
         IR::Dynamic(_) | IR::ContextPath(_) | IR::Map(_) | IR::Service(_) => None,
     }
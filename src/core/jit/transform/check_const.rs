@@ -27,6 +27,10 @@ pub fn is_const(ir: &IR) -> bool {
         IR::Pipe(ir, ir1) => is_const(ir) && is_const(ir1),
         IR::Merge(vec) => vec.iter().all(is_const),
         IR::Discriminate(_, ir) => is_const(ir),
+        IR::When(_, _) => false,
+        IR::ByOperation { query, mutation, subscription } => {
+            is_const(query) && is_const(mutation) && is_const(subscription)
+        }
         IR::Entity(hash_map) => hash_map.values().all(is_const),
         IR::Service(_) => true,
     }
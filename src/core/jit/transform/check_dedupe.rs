@@ -23,6 +23,10 @@ fn check_dedupe(ir: &IR) -> bool {
         IR::Pipe(ir, ir1) => check_dedupe(ir) && check_dedupe(ir1),
         IR::Merge(vec) => vec.iter().all(check_dedupe),
         IR::Discriminate(_, ir) => check_dedupe(ir),
+        IR::When(_, ir) => check_dedupe(ir),
+        IR::ByOperation { query, mutation, subscription } => {
+            check_dedupe(query) && check_dedupe(mutation) && check_dedupe(subscription)
+        }
         IR::Entity(hash_map) => hash_map.values().all(check_dedupe),
         IR::Dynamic(_) => true,
         IR::ContextPath(_) => true,
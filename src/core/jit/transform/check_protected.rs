@@ -27,6 +27,10 @@ pub fn is_protected(ir: &IR) -> bool {
         IR::Pipe(ir, ir1) => is_protected(ir) || is_protected(ir1),
         IR::Merge(vec) => vec.iter().all(is_protected),
         IR::Discriminate(_, ir) => is_protected(ir),
+        IR::When(_, ir) => is_protected(ir),
+        IR::ByOperation { query, mutation, subscription } => {
+            is_protected(query) || is_protected(mutation) || is_protected(subscription)
+        }
         IR::Entity(hash_map) => hash_map.values().any(is_protected),
         IR::Service(_) => false,
     }
@@ -252,4 +252,25 @@ mod tests {
 
         assert_eq!(actual, expected)
     }
+
+    #[test]
+    fn test_group_by_int64_key() {
+        let arr = vec![
+            (json!(9007199254740993i64), json!({"id": 1})),
+            (json!(9007199254740992i64), json!({"id": 2})),
+        ];
+        let input: Vec<(&serde_json::Value, &serde_json::Value)> =
+            arr.iter().map(|a| (&a.0, &a.1)).collect();
+
+        let actual = serde_json::to_value(group_by_key(input)).unwrap();
+
+        let expected = json!(
+            {
+                "9007199254740993": [{"id": 1}],
+                "9007199254740992": [{"id": 2}],
+            }
+        );
+
+        assert_eq!(actual, expected)
+    }
 }
@@ -0,0 +1,78 @@
+use async_graphql_value::ConstValue;
+use serde_json::Value;
+
+/// A JSONPath expression, eg. `$.data.items[*]`, that picks the part of a
+/// response body a field resolves to. It's parsed once, when the blueprint is
+/// compiled.
+#[derive(Clone, Debug)]
+pub struct JsonPath {
+    path: String,
+    compiled: serde_json_path::JsonPath,
+    is_list: bool,
+}
+
+impl PartialEq for JsonPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.is_list == other.is_list
+    }
+}
+
+impl Eq for JsonPath {}
+
+impl JsonPath {
+    /// Fails when the expression isn't a valid JSONPath. A list field resolves
+    /// to all the matches, the other fields to the first one.
+    pub fn try_new(path: &str, is_list: bool) -> Result<Self, String> {
+        let compiled = serde_json_path::JsonPath::parse(path).map_err(|e| e.to_string())?;
+        Ok(Self { path: path.to_string(), compiled, is_list })
+    }
+
+    pub fn select(&self, value: ConstValue) -> anyhow::Result<ConstValue> {
+        let value = value.into_json()?;
+        let matches = self.compiled.query(&value);
+        let value = if self.is_list {
+            Value::Array(matches.all().into_iter().cloned().collect())
+        } else {
+            matches.first().cloned().unwrap_or_default()
+        };
+        Ok(ConstValue::from_json(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn body() -> ConstValue {
+        ConstValue::from_json(json!({
+            "data": {"items": [{"id": 1}, {"id": 2}], "total": 2}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_select_list() {
+        let path = JsonPath::try_new("$.data.items[*]", true).unwrap();
+
+        let actual = path.select(body()).unwrap();
+
+        let expected = ConstValue::from_json(json!([{"id": 1}, {"id": 2}])).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_select_first_match() {
+        let path = JsonPath::try_new("$.data.items[*].id", false).unwrap();
+        assert_eq!(path.select(body()).unwrap(), ConstValue::from(1));
+
+        let path = JsonPath::try_new("$.data.missing", false).unwrap();
+        assert_eq!(path.select(body()).unwrap(), ConstValue::Null);
+    }
+
+    #[test]
+    fn test_invalid_path() {
+        assert!(JsonPath::try_new("$.data[", true).is_err());
+    }
+}
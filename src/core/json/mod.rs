@@ -2,14 +2,18 @@ mod borrow;
 mod graphql;
 mod json_like;
 mod json_like_list;
+mod json_path;
 mod json_schema;
+mod projection;
 mod serde;
 
 use std::collections::HashMap;
 
 pub use json_like::*;
 pub use json_like_list::*;
+pub use json_path::*;
 pub use json_schema::*;
+pub use projection::*;
 
 // Highly micro-optimized and benchmarked version of get_path_all
 // Any further changes should be verified with benchmarks
@@ -40,13 +44,7 @@ fn group_by_key<'json, J: JsonLike<'json>>(
 ) -> HashMap<String, Vec<&'json J>> {
     let mut map: HashMap<String, Vec<&'json J>> = HashMap::new();
     for (key, value) in src {
-        // Need to handle number and string keys
-        let key_str = key
-            .as_str()
-            .map(|a| a.to_string())
-            .or_else(|| key.as_f64().map(|a| a.to_string()));
-
-        if let Some(key) = key_str {
+        if let Some(key) = key_string(key) {
             if let Some(values) = map.get_mut(&key) {
                 values.push(value);
             } else {
@@ -56,3 +54,65 @@ fn group_by_key<'json, J: JsonLike<'json>>(
     }
     map
 }
+
+/// The key an item is grouped by, from a number or a string. Integers are read
+/// before floats so that the keys of 64-bit ids aren't rounded.
+pub fn key_string<'json, J: JsonLike<'json>>(key: &'json J) -> Option<String> {
+    key.as_str()
+        .map(|a| a.to_string())
+        .or_else(|| key.as_i64().map(|a| a.to_string()))
+        .or_else(|| key.as_u64().map(|a| a.to_string()))
+        .or_else(|| key.as_f64().map(|a| a.to_string()))
+}
+
+fn gather_items<'json, J: JsonLike<'json>>(
+    root: &'json J,
+    path: &[String],
+    mut items: Vec<&'json J>,
+) -> Vec<&'json J> {
+    if let Some(root) = root.as_array() {
+        for value in root.iter() {
+            items = gather_items(value, path, items);
+        }
+    } else if let Some((key, tail)) = path.split_first() {
+        if let Some(value) = root.get_key(key) {
+            items = gather_items(value, tail, items);
+        }
+    } else {
+        items.push(root);
+    }
+
+    items
+}
+
+/// Gathers the items at `path`, descending into the arrays on the way, and
+/// groups them by the values at the `keys` paths within each item. Items that
+/// don't have all the keys are skipped.
+pub fn group_by_keys<'json, J: JsonLike<'json>>(
+    root: &'json J,
+    path: &[String],
+    keys: &[Vec<String>],
+) -> HashMap<String, Vec<&'json J>> {
+    let mut map: HashMap<String, Vec<&'json J>> = HashMap::new();
+    for item in gather_items(root, path, vec![]) {
+        let components = keys
+            .iter()
+            .map(|key| item.get_path(key.as_slice()).and_then(key_string))
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(components) = components {
+            map.entry(join_key(components)).or_default().push(item);
+        }
+    }
+    map
+}
+
+/// Joins the components of a composite key such that they can't collide with
+/// the components of another key. A single component is used as is.
+pub fn join_key(mut components: Vec<String>) -> String {
+    if components.len() == 1 {
+        components.remove(0)
+    } else {
+        serde_json::to_string(&components).unwrap_or_default()
+    }
+}
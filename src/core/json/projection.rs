@@ -0,0 +1,209 @@
+use std::fmt;
+
+use async_graphql_value::{ConstValue, Name};
+use indexmap::IndexMap;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+
+/// The part of a JSON value a query reads. Decoding a body with it keeps only
+/// the selected keys and skips the others as they're read, so the unselected
+/// parts of a large response are never built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Projection {
+    /// The whole value is kept.
+    All,
+    /// Only these keys of the objects are kept, the same projection applies to
+    /// each item of a list.
+    Fields(IndexMap<String, Projection>),
+}
+
+impl Projection {
+    /// Combines the keys of both projections, eg. when the same field is
+    /// selected twice with different sub-selections.
+    pub fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Projection::Fields(mut fields), Projection::Fields(other)) => {
+                for (name, projection) in other {
+                    let projection = match fields.shift_remove(&name) {
+                        Some(current) => current.merge(projection),
+                        None => projection,
+                    };
+                    fields.insert(name, projection);
+                }
+                Projection::Fields(fields)
+            }
+            _ => Projection::All,
+        }
+    }
+
+    /// Decodes the JSON body, keeping only the projected keys.
+    pub fn decode(&self, body: &[u8]) -> serde_json::Result<ConstValue> {
+        let mut deserializer = serde_json::Deserializer::from_slice(body);
+        let value = self.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(value)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for &Projection {
+    type Value = ConstValue;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        match self {
+            Projection::All => ConstValue::deserialize(deserializer),
+            Projection::Fields(_) => deserializer.deserialize_any(ProjectionVisitor(self)),
+        }
+    }
+}
+
+struct ProjectionVisitor<'a>(&'a Projection);
+
+impl<'de> Visitor<'de> for ProjectionVisitor<'_> {
+    type Value = ConstValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(ConstValue::Null)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(ConstValue::Boolean(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(ConstValue::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(ConstValue::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(serde_json::Number::from_f64(value)
+            .map(ConstValue::Number)
+            .unwrap_or(ConstValue::Null))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(ConstValue::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+        Ok(ConstValue::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(item) = seq.next_element_seed(self.0)? {
+            items.push(item);
+        }
+        Ok(ConstValue::List(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let Projection::Fields(fields) = self.0 else {
+            unreachable!("the whole values are decoded without the visitor")
+        };
+        let mut object = IndexMap::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match fields.get(&key) {
+                Some(projection) => {
+                    let value = map.next_value_seed(projection)?;
+                    object.insert(Name::new(key), value);
+                }
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(ConstValue::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn fields<const N: usize>(fields: [(&str, Projection); N]) -> Projection {
+        Projection::Fields(
+            fields
+                .into_iter()
+                .map(|(name, projection)| (name.to_string(), projection))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_decode() {
+        let body = json!([
+            {"id": 1, "name": "a", "bio": "long", "address": {"city": "x", "zip": "1"}, "tags": ["t"]},
+            {"id": 2, "name": "b", "bio": "long", "address": null, "tags": []},
+        ]);
+        let projection = fields([
+            ("id", Projection::All),
+            ("address", fields([("city", Projection::All)])),
+            ("tags", Projection::All),
+        ]);
+
+        let actual = projection.decode(body.to_string().as_bytes()).unwrap();
+
+        let expected = ConstValue::from_json(json!([
+            {"id": 1, "address": {"city": "x"}, "tags": ["t"]},
+            {"id": 2, "address": null, "tags": []},
+        ]))
+        .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_same_as_full_parse() {
+        let body = json!({"users": [{"id": 1, "score": 1.5, "ok": true, "nested": {"a": [1, {"b": null}]}}]});
+        let projection = fields([(
+            "users",
+            fields([
+                ("id", Projection::All),
+                ("score", Projection::All),
+                ("ok", Projection::All),
+                ("nested", Projection::All),
+            ]),
+        )]);
+
+        let actual = projection.decode(body.to_string().as_bytes()).unwrap();
+
+        assert_eq!(actual, ConstValue::from_json(body).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let projection = fields([("id", Projection::All)]);
+
+        assert!(projection.decode(br#"{"id": 1"#).is_err());
+        assert!(projection.decode(br#"{"id": 1} {}"#).is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let left = fields([("a", fields([("b", Projection::All)]))]);
+        let right = fields([
+            ("a", fields([("c", Projection::All)])),
+            ("d", Projection::All),
+        ]);
+
+        let actual = left.merge(right);
+
+        let expected = fields([
+            (
+                "a",
+                fields([("b", Projection::All), ("c", Projection::All)]),
+            ),
+            ("d", Projection::All),
+        ]);
+        assert_eq!(actual, expected);
+        assert_eq!(Projection::All.merge(expected), Projection::All);
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::module_inception)]
 #![allow(clippy::mutable_key_type)]
 
+pub mod apollo_usage;
 pub mod app_context;
 pub mod async_graphql_hyper;
 mod auth;
@@ -18,6 +19,7 @@ pub mod generator;
 pub mod graphql;
 pub mod grpc;
 pub mod has_headers;
+pub mod has_uploads;
 pub mod helpers;
 pub mod http;
 pub mod ir;
@@ -36,9 +38,11 @@ pub mod runtime;
 pub mod scalar;
 pub mod schema_extension;
 mod serde_value_ext;
+pub mod timing;
 pub mod tracing;
 mod transform;
 pub mod try_fold;
+pub mod valid_ext;
 pub mod variance;
 pub mod worker;
 pub mod worker_hooks;
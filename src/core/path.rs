@@ -2,6 +2,7 @@
 //! structure.
 use std::borrow::Cow;
 
+use async_graphql_value::ConstValue;
 use serde_json::json;
 
 use crate::core::ir::{EvalContext, ResolverContextLike};
@@ -13,6 +14,13 @@ use crate::core::json::JsonLike;
 /// This is typically used in evaluating mustache templates.
 pub trait PathString {
     fn path_string<'a, T: AsRef<str>>(&'a self, path: &'a [T]) -> Option<Cow<'a, str>>;
+
+    /// Same as [PathString::path_string], but keeps the JSON type of the
+    /// value, so that numbers and booleans aren't turned into strings.
+    fn path_value<'a, T: AsRef<str>>(&'a self, path: &'a [T]) -> Option<ConstValue> {
+        self.path_string(path)
+            .map(|value| ConstValue::String(value.into_owned()))
+    }
 }
 
 /// PathValue trait provides a method for accessing values from JSON-like
@@ -36,6 +44,11 @@ impl PathString for serde_json::Value {
             _ => Cow::Owned(a.to_string()),
         })
     }
+
+    fn path_value<'a, T: AsRef<str>>(&'a self, path: &'a [T]) -> Option<ConstValue> {
+        self.get_path(path)
+            .and_then(|value| ConstValue::from_json(value.clone()).ok())
+    }
 }
 
 fn convert_value(value: Cow<'_, async_graphql::Value>) -> Option<Cow<'_, str>> {
@@ -79,6 +92,7 @@ impl<Ctx: ResolverContextLike> EvalContext<'_, Ctx> {
                 "vars" => Some(ValueString::String(Cow::Owned(
                     json!(ctx.vars()).to_string(),
                 ))),
+                "fields" => Some(ValueString::String(Cow::Owned(ctx.selected_fields()?))),
                 _ => None,
             };
         }
@@ -94,6 +108,10 @@ impl<Ctx: ResolverContextLike> EvalContext<'_, Ctx> {
                     ctx.var(tail[0].as_ref())?,
                 ))),
                 "env" => Some(ValueString::String(ctx.env_var(tail[0].as_ref())?)),
+                "ctx" => Some(ValueString::Value(Cow::Borrowed(ctx.context_value(tail)?))),
+                "tenant" if tail[0].as_ref() == "id" => {
+                    Some(ValueString::String(Cow::Owned(ctx.tenant_id()?)))
+                }
                 _ => None,
             })
     }
@@ -112,6 +130,13 @@ impl<Ctx: ResolverContextLike> PathString for EvalContext<'_, Ctx> {
             ValueString::Value(value) => convert_value(value),
         })
     }
+
+    fn path_value<T: AsRef<str>>(&self, path: &[T]) -> Option<ConstValue> {
+        self.to_raw_value(path).map(|value| match value {
+            ValueString::String(value) => ConstValue::String(value.into_owned()),
+            ValueString::Value(value) => value.into_owned(),
+        })
+    }
 }
 
 impl<Ctx: ResolverContextLike> PathGraphql for EvalContext<'_, Ctx> {
@@ -456,6 +481,46 @@ mod tests {
             assert_eq!(EVAL_CTX.path_string(&["baz", "key"]), None);
         }
 
+        #[test]
+        fn path_to_const_value() {
+            let path_value = |path: &[&str]| PathString::path_value(&*EVAL_CTX, path);
+
+            assert_eq!(path_value(&["value", "bool"]), Some(Value::Boolean(true)));
+            assert_eq!(
+                path_value(&["value", "number"]),
+                Some(Value::Number(Number::from(2)))
+            );
+            assert_eq!(
+                path_value(&["value", "str"]),
+                Some(Value::String("str-test".to_owned()))
+            );
+            assert_eq!(
+                path_value(&["args", "nested"]),
+                Some(TEST_ARGS["nested"].clone())
+            );
+            assert_eq!(path_value(&["value", "missing"]), None);
+            assert_eq!(
+                path_value(&["headers", "x-existing"]),
+                Some(Value::String("header".to_owned()))
+            );
+            assert_eq!(
+                path_value(&["env", "existing"]),
+                Some(Value::String("env".to_owned()))
+            );
+            assert_eq!(path_value(&["foo", "key"]), None);
+
+            let json = serde_json::json!({"limit": 10, "nested": {"flag": false}});
+            assert_eq!(
+                json.path_value(&["limit"]),
+                Some(Value::Number(Number::from(10)))
+            );
+            assert_eq!(
+                json.path_value(&["nested", "flag"]),
+                Some(Value::Boolean(false))
+            );
+            assert_eq!(json.path_value(&["missing"]), None);
+        }
+
         #[test]
         fn path_to_graphql_string() {
             // value
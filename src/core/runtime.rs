@@ -16,6 +16,9 @@ pub struct TargetRuntime {
     pub http: Arc<dyn HttpIO>,
     /// HTTP client optimized for HTTP/2 requests.
     pub http2_only: Arc<dyn HttpIO>,
+    /// HTTP client that skips the verification of TLS certificates, used by
+    /// the `@http` fields that opt out of it.
+    pub http_insecure: Arc<dyn HttpIO>,
     /// Interface for accessing environment variables specific to the target
     /// environment.
     pub env: Arc<dyn EnvIO>,
@@ -38,6 +41,16 @@ impl TargetRuntime {
     pub fn add_extensions(&mut self, extensions: Vec<SchemaExtension>) {
         self.extensions = Arc::new(extensions);
     }
+
+    /// Picks the HTTP client depending on whether the TLS certificates have to
+    /// be verified.
+    pub fn http_client(&self, verify_ssl: bool) -> &Arc<dyn HttpIO> {
+        if verify_ssl {
+            &self.http
+        } else {
+            &self.http_insecure
+        }
+    }
 }
 
 #[cfg(test)]
@@ -59,7 +72,7 @@ pub mod test {
     use crate::cli::javascript::init_worker_io;
     use crate::core::blueprint::Upstream;
     use crate::core::cache::InMemoryCache;
-    use crate::core::http::Response;
+    use crate::core::http::{Response, StatusError};
     use crate::core::runtime::TargetRuntime;
     use crate::core::worker::{Command, Event};
     use crate::core::{blueprint, EnvIO, FileIO, HttpIO};
@@ -119,12 +132,7 @@ pub mod test {
     impl HttpIO for TestHttp {
         async fn execute(&self, request: reqwest::Request) -> Result<Response<Bytes>> {
             let response = self.client.execute(request).await;
-            Response::from_reqwest(
-                response?
-                    .error_for_status()
-                    .map_err(|err| err.without_url())?,
-            )
-            .await
+            Response::from_reqwest(StatusError::check(response?).await?).await
         }
     }
 
@@ -177,6 +185,7 @@ pub mod test {
     pub fn init(script: Option<blueprint::Script>) -> TargetRuntime {
         let http = TestHttp::init(&Default::default());
         let http2 = TestHttp::init(&Upstream::default().http2_only(true));
+        let http_insecure = TestHttp::init(&Upstream::default().verify_ssl(false));
 
         let file = TestFileIO::init();
         let env = TestEnvIO::init();
@@ -184,6 +193,7 @@ pub mod test {
         TargetRuntime {
             http,
             http2_only: http2,
+            http_insecure,
             env: Arc::new(env),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::default()),
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
 
@@ -49,7 +50,9 @@ pub enum Scalar {
     /// Field whose value is a 32-bit signed integer.
     #[gen_doc(ty = "Integer")]
     Int32,
-    /// Field whose value is a 64-bit signed integer.
+    /// Field whose value is a 64-bit signed integer. It's sent as a string in
+    /// the responses, so that clients reading numbers as doubles don't lose
+    /// precision, and accepted as a string or a number in the inputs.
     #[gen_doc(ty = "Integer")]
     Int64,
     /// Field whose value is a 128-bit signed integer.
@@ -73,6 +76,11 @@ pub enum Scalar {
     /// Field whose value is a sequence of bytes.
     #[gen_doc(ty = "String")]
     Bytes,
+    /// Field whose value is a file sent with a multipart request, as the `id`
+    /// of its part, its `filename`, `mimeType` and `size` in bytes. It can only
+    /// be used in input positions.
+    #[gen_doc(ty = "Object")]
+    Upload,
 }
 
 fn eval_str<'a, Value: JsonLike<'a>, F: Fn(&str) -> bool>(val: &'a Value, fxn: F) -> bool {
@@ -128,8 +136,14 @@ impl Scalar {
             }
             Scalar::Url => eval_str(value, |s| url::Url::parse(s).is_ok()),
             Scalar::Bytes => value.as_str().is_some(),
+            Scalar::Upload => {
+                value.get_key("filename").and_then(|v| v.as_str()).is_some()
+                    && value.get_key("size").and_then(|v| v.as_u64()).is_some()
+            }
 
-            Scalar::Int64 => eval_str(value, |s| s.parse::<i64>().is_ok()),
+            Scalar::Int64 => {
+                value.as_i64().is_some() || eval_str(value, |s| s.parse::<i64>().is_ok())
+            }
             Scalar::UInt64 => eval_str(value, |s| s.parse::<u64>().is_ok()),
             Scalar::Int128 => eval_str(value, |s| s.parse::<i128>().is_ok()),
             Scalar::UInt128 => eval_str(value, |s| s.parse::<u128>().is_ok()),
@@ -143,6 +157,36 @@ impl Scalar {
             Scalar::UInt32 => eval_unsigned(value, u32::try_from),
         }
     }
+
+    /// The value sent in the responses for a valid `value`, see
+    /// [Scalar::Int64].
+    pub fn serialize<'a, Value: JsonLike<'a>, Output: JsonLike<'a>>(
+        &self,
+        value: &'a Value,
+    ) -> Output {
+        match (self, value.as_i64()) {
+            (Scalar::Int64, Some(n)) => Output::string(Cow::Owned(n.to_string())),
+            _ => Output::clone_from(value),
+        }
+    }
+
+    /// The specification the values follow, exposed as `specifiedByURL` in
+    /// the introspection.
+    pub fn specified_by_url(&self) -> Option<&'static str> {
+        match self {
+            Scalar::Date | Scalar::DateTime => {
+                Some("https://datatracker.ietf.org/doc/html/rfc3339")
+            }
+            Scalar::Email => {
+                Some("https://html.spec.whatwg.org/multipage/input.html#valid-e-mail-address")
+            }
+            Scalar::PhoneNumber => Some("https://en.wikipedia.org/wiki/E.164"),
+            Scalar::Url => Some("https://datatracker.ietf.org/doc/html/rfc3986"),
+            Scalar::JSON => Some("https://datatracker.ietf.org/doc/html/rfc8259"),
+            _ => None,
+        }
+    }
+
     pub fn find(name: &str) -> Option<&Scalar> {
         CUSTOM_SCALARS.get(name)
     }
@@ -336,13 +380,15 @@ mod test {
             Scalar::Int64,
             ConstValue::String("125".to_string()),
             ConstValue::String("-15".to_string()),
-            ConstValue::String(i64::MAX.to_string())
+            ConstValue::String(i64::MAX.to_string()),
+            ConstValue::Number(Number::from(15)),
+            ConstValue::Number(Number::from(i64::MIN))
         }
 
         test_scalar_invalid! {
             Scalar::Int64,
             ConstValue::Null,
-            ConstValue::Number(Number::from(15)),
+            ConstValue::Number(Number::from(u64::MAX)),
             ConstValue::Number(
                 Number::from_f64(1.25).unwrap()
             ),
@@ -506,6 +552,23 @@ mod test {
         }
     }
 
+    mod upload {
+        use serde_json::json;
+
+        use super::{ConstValue, Scalar};
+
+        test_scalar_valid! {
+            Scalar::Upload,
+            ConstValue::from_json(json!({"filename": "a.txt", "mimeType": "text/plain", "size": 10})).unwrap()
+        }
+        test_scalar_invalid! {
+            Scalar::Upload,
+            ConstValue::Null,
+            ConstValue::String("a.txt".to_string()),
+            ConstValue::from_json(json!({"filename": "a.txt"})).unwrap()
+        }
+    }
+
     mod url {
         use super::{ConstValue, Scalar};
 
@@ -1,15 +1,22 @@
 use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 use std::{env, fmt};
 
 use colored::Colorize;
 use tracing::level_filters::LevelFilter;
 use tracing::{Event, Level, Metadata, Subscriber};
-use tracing_subscriber::filter::{filter_fn, FilterFn};
+use tracing_subscriber::filter::{filter_fn, EnvFilter, FilterFn, ParseError};
 use tracing_subscriber::fmt::format::Writer;
-use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::registry::LookupSpan;
-use tracing_subscriber::{registry, Layer};
+use tracing_subscriber::{registry, reload, Layer};
+
+pub use crate::core::config::LogFormat;
+
+/// The format set with the `--log-format` flag.
+static LOG_FORMAT: OnceLock<LogFormat> = OnceLock::new();
+
 struct FmtLevel<'a> {
     level: &'a Level,
     ansi: bool,
@@ -89,18 +96,93 @@ pub fn get_log_level() -> Option<Level> {
         .and_then(|v| Level::from_str(&v).ok())
 }
 
+/// Sets the format of the logs from the `--log-format` flag, which takes
+/// precedence over the env variables.
+pub fn set_log_format(format: LogFormat) {
+    let _ = LOG_FORMAT.set(format);
+}
+
+pub fn get_log_format() -> Option<LogFormat> {
+    const LONG_ENV_FORMAT_VAR_NAME: &str = "TAILCALL_LOG_FORMAT";
+    const SHORT_ENV_FORMAT_VAR_NAME: &str = "TC_LOG_FORMAT";
+
+    LOG_FORMAT.get().copied().or_else(|| {
+        env::var(LONG_ENV_FORMAT_VAR_NAME)
+            .or(env::var(SHORT_ENV_FORMAT_VAR_NAME))
+            .ok()
+            .and_then(|v| LogFormat::from_str(&v).ok())
+    })
+}
+
 pub fn default_tracing<S>() -> impl Layer<S>
 where
-    S: Subscriber,
+    S: Subscriber + 'static,
     for<'a> S: registry::LookupSpan<'a>,
 {
-    tracing_subscriber::fmt::layer()
-        .without_time()
-        .with_target(false)
-        .event_format(CliFmt)
-        .with_filter(LevelFilter::from_level(
-            get_log_level().unwrap_or(Level::INFO),
-        ))
+    tracing_with_format(get_log_format().unwrap_or_default()).with_filter(LevelFilter::from_level(
+        get_log_level().unwrap_or(Level::INFO),
+    ))
+}
+
+/// The filter of the logs of the server: the `tailcall` targets at the level of
+/// `TAILCALL_LOG_LEVEL`, refined by the directives of
+/// `@telemetry(logFilter:)`, eg. `tailcall::core::http=debug`.
+pub fn log_filter(directives: Option<&str>) -> Result<EnvFilter, ParseError> {
+    let level = get_log_level().unwrap_or(Level::INFO);
+    let mut filter = format!("tailcall={level}");
+    if let Some(directives) = directives.filter(|directives| !directives.trim().is_empty()) {
+        // the later directive for a target replaces the earlier one
+        filter.push(',');
+        filter.push_str(directives);
+    }
+    EnvFilter::builder().parse(filter)
+}
+
+/// Replaces the [log_filter] of the running server.
+#[derive(Clone)]
+pub struct LogFilterHandle(Arc<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>);
+
+impl LogFilterHandle {
+    pub fn new<S: Subscriber + 'static>(handle: reload::Handle<EnvFilter, S>) -> Self {
+        Self(Arc::new(move |filter| handle.reload(filter)))
+    }
+
+    pub fn reload(&self, filter: EnvFilter) -> Result<(), reload::Error> {
+        (self.0)(filter)
+    }
+}
+
+/// Prints the logs in the format, the JSON lines carry the fields of the spans
+/// of the event, eg. the `request.id` of the request it is logged for.
+pub fn tracing_with_format<S>(format: LogFormat) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + 'static,
+    for<'a> S: registry::LookupSpan<'a>,
+{
+    tracing_to_writer(format, std::io::stdout)
+}
+
+fn tracing_to_writer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + 'static,
+    for<'a> S: registry::LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .without_time()
+            .with_target(false)
+            .event_format(CliFmt)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    }
 }
 
 pub fn tailcall_filter_target() -> FilterFn<impl Fn(&Metadata<'_>) -> bool> {
@@ -110,3 +192,191 @@ pub fn tailcall_filter_target() -> FilterFn<impl Fn(&Metadata<'_>) -> bool> {
 pub fn filter_target(name: &'static str) -> FilterFn<impl Fn(&Metadata<'_>) -> bool> {
     filter_fn(move |metadata: &Metadata<'_>| metadata.target().starts_with(name))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use hyper::{Body, Method, Request};
+    use serde_json::Value;
+    use tailcall_valid::Validator;
+    use tracing_subscriber::fmt::MakeWriter;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::{reload, Layer};
+
+    use super::{log_filter, tracing_to_writer, LogFilterHandle, LogFormat};
+    use crate::core::app_context::AppContext;
+    use crate::core::async_graphql_hyper::GraphQLRequest;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::http::{handle_request, X_REQUEST_ID};
+    use crate::core::rest::EndpointSet;
+    use crate::core::runtime::test::init;
+
+    /// Keeps the logs written in memory.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl MakeWriter<'_> for Capture {
+        type Writer = Self;
+
+        fn make_writer(&self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    impl Capture {
+        fn lines(&self) -> Vec<Value> {
+            let logs = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+            logs.lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+
+        fn messages(&self) -> Vec<String> {
+            self.lines()
+                .iter()
+                .map(|line| line["message"].as_str().unwrap().to_string())
+                .collect()
+        }
+    }
+
+    /// Logs an event at each level for the http module and the server.
+    fn log_events() {
+        tracing::debug!(target: "tailcall::core::http", "http debug");
+        tracing::info!(target: "tailcall::core::http", "http info");
+        tracing::debug!(target: "tailcall::cli::server", "server debug");
+        tracing::info!(target: "tailcall::cli::server", "server info");
+        tracing::info!(target: "hyper::proto", "hyper info");
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let capture = Capture::default();
+        let filter = log_filter(Some("tailcall::core::http=debug,tailcall=warn")).unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_to_writer(LogFormat::Json, capture.clone()).with_filter(filter));
+
+        tracing::subscriber::with_default(subscriber, log_events);
+
+        assert_eq!(capture.messages(), ["http debug", "http info"]);
+    }
+
+    #[test]
+    fn test_log_filter_reload() {
+        let capture = Capture::default();
+        let (filter, handle) = reload::Layer::new(log_filter(None).unwrap());
+        let handle = LogFilterHandle::new(handle);
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_to_writer(LogFormat::Json, capture.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            log_events();
+            handle
+                .reload(log_filter(Some("tailcall::cli=debug")).unwrap())
+                .unwrap();
+            log_events();
+        });
+
+        assert_eq!(
+            capture.messages(),
+            [
+                "http info",
+                "server info",
+                "http info",
+                "server debug",
+                "server info",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_log_filter_invalid() {
+        assert!(log_filter(Some("tailcall=loud")).is_err());
+        assert!(log_filter(Some("")).is_ok());
+    }
+
+    #[test]
+    fn test_json_format() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_to_writer(LogFormat::Json, capture.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("Starting");
+            tracing::warn!(user = 1, "Fetching the user");
+        });
+
+        let lines = capture.lines();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["message"], "Starting");
+        assert!(lines[0].get("span").is_none());
+
+        assert!(lines[1]["timestamp"].is_string());
+        assert_eq!(lines[1]["level"], "WARN");
+        assert_eq!(lines[1]["target"], module_path!());
+        assert_eq!(lines[1]["message"], "Fetching the user");
+        assert_eq!(lines[1]["user"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_json_format_request_id() -> anyhow::Result<()> {
+        let sdl = r#"
+            schema { query: Query }
+            type Query { user: User @expr(body: {id: 1}) }
+            type User { id: Int }
+        "#;
+        let config = Config::from_sdl(sdl).to_result()?;
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        let app_ctx = Arc::new(AppContext::new(
+            blueprint,
+            init(None),
+            EndpointSet::default(),
+        ));
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_to_writer(LogFormat::Json, capture.clone()));
+
+        let request_id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("http://localhost:8000/graphql")
+            .header(X_REQUEST_ID, request_id)
+            .body(Body::from(r#"{"query": "{ user { id } }"}"#))?;
+        let guard = tracing::subscriber::set_default(subscriber);
+        handle_request::<GraphQLRequest>(req, app_ctx).await?;
+        drop(guard);
+
+        // the events logged while the request is handled carry its id
+        let lines = capture.lines();
+        let line = lines
+            .iter()
+            .find(|line| {
+                line["message"]
+                    .as_str()
+                    .is_some_and(|message| message.starts_with("GraphQL request"))
+            })
+            .unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["span"]["request.id"], request_id);
+        assert_eq!(line["spans"][0]["name"], "handle_request");
+        assert_eq!(line["spans"][0]["request.id"], request_id);
+
+        Ok(())
+    }
+}
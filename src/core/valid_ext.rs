@@ -0,0 +1,62 @@
+use tailcall_valid::{Valid, Validator};
+
+pub trait ValidExt<A, E> {
+    /// Chains a validation that depends on the value. Same as
+    /// [Validator::and_then], named after [Iterator::flat_map] to make the
+    /// nesting explicit.
+    fn flat_map<B>(self, f: impl FnOnce(A) -> Valid<B, E>) -> Valid<B, E>;
+}
+
+impl<A, E> ValidExt<A, E> for Valid<A, E> {
+    fn flat_map<B>(self, f: impl FnOnce(A) -> Valid<B, E>) -> Valid<B, E> {
+        self.and_then(f)
+    }
+}
+
+pub trait ValidFlatten<A, E> {
+    /// Removes one level of nesting, keeping the errors of both levels, like
+    /// [Option::flatten].
+    fn flatten(self) -> Valid<A, E>;
+}
+
+impl<A, E> ValidFlatten<A, E> for Valid<Valid<A, E>, E> {
+    fn flatten(self) -> Valid<A, E> {
+        self.and_then(|valid| valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let valid: Valid<Valid<i32, &str>, &str> = Valid::succeed(Valid::succeed(1));
+        assert_eq!(valid.flatten().to_result().unwrap(), 1);
+
+        let valid: Valid<Valid<i32, &str>, &str> = Valid::succeed(Valid::fail("inner"));
+        assert!(valid
+            .flatten()
+            .to_result()
+            .unwrap_err()
+            .to_string()
+            .contains("inner"));
+
+        let valid: Valid<Valid<i32, &str>, &str> = Valid::fail("outer");
+        assert!(valid
+            .flatten()
+            .to_result()
+            .unwrap_err()
+            .to_string()
+            .contains("outer"));
+    }
+
+    #[test]
+    fn test_flat_map() {
+        let valid = Valid::<i32, &str>::succeed(1).flat_map(|a| Valid::succeed(a + 1));
+        assert_eq!(valid.to_result().unwrap(), 2);
+
+        let valid = Valid::<i32, &str>::succeed(1).flat_map(|_| Valid::<i32, &str>::fail("failed"));
+        assert!(valid.to_result().is_err());
+    }
+}
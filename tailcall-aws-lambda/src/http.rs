@@ -4,7 +4,7 @@ use anyhow::Result;
 use hyper::body::Bytes;
 use lambda_http::RequestExt;
 use reqwest::Client;
-use tailcall::core::http::Response;
+use tailcall::core::http::{Response, StatusError};
 use tailcall::core::HttpIO;
 
 #[derive(Clone)]
@@ -28,12 +28,7 @@ impl LambdaHttp {
 impl HttpIO for LambdaHttp {
     async fn execute(&self, request: reqwest::Request) -> Result<Response<Bytes>> {
         let req_str = format!("{} {}", request.method(), request.url());
-        let response = self
-            .client
-            .execute(request)
-            .await?
-            .error_for_status()
-            .map_err(|err| err.without_url())?;
+        let response = StatusError::check(self.client.execute(request).await?).await?;
         let res = Response::from_reqwest(response).await?;
         tracing::info!("{} {}", req_str, res.status.as_u16());
         Ok(res)
@@ -55,7 +55,8 @@ pub fn init_runtime() -> TargetRuntime {
     let http = init_http();
     TargetRuntime {
         http: http.clone(),
-        http2_only: http,
+        http2_only: http.clone(),
+        http_insecure: http,
         file: init_file(),
         env: init_env(),
         cache: init_cache(),
@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 use async_std::task::spawn_local;
 use hyper::body::Bytes;
 use reqwest::Client;
-use tailcall::core::http::Response;
+use tailcall::core::http::{Response, StatusError};
 use tailcall::core::HttpIO;
 
 use crate::to_anyhow;
@@ -37,11 +37,7 @@ impl HttpIO for CloudflareHttp {
         let url = request.url().clone();
         // TODO: remove spawn local
         let res = spawn_local(async move {
-            let response = client
-                .execute(request)
-                .await?
-                .error_for_status()
-                .map_err(|err| err.without_url())?;
+            let response = StatusError::check(client.execute(request).await?).await?;
             Response::from_reqwest(response).await
         })
         .await?;
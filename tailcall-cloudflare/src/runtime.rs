@@ -37,6 +37,7 @@ pub fn init(env: Rc<worker::Env>) -> anyhow::Result<TargetRuntime> {
     Ok(TargetRuntime {
         http: http.clone(),
         http2_only: http.clone(),
+        http_insecure: http.clone(),
         env: init_env(env.clone()),
         file: init_file(env.clone(), &bucket_id)?,
         cache: init_cache(env),
@@ -1,4 +1,5 @@
-use std::hash::Hasher;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
 
 use fxhash::FxHasher;
 
@@ -19,3 +20,44 @@ impl Hasher for TailcallHasher {
         self.hasher.write(bytes)
     }
 }
+
+/// Builds a [TailcallHasher] for every hashed key.
+pub type TailcallBuildHasher = BuildHasherDefault<TailcallHasher>;
+
+/// A `HashMap` that hashes its keys with [TailcallHasher].
+pub type TailcallHashMap<K, V> = HashMap<K, V, TailcallBuildHasher>;
+
+/// Shorthands for the maps that use [TailcallBuildHasher].
+pub trait TailcallHashMapExt<K, V> {
+    /// Returns the value of the key, inserting `V::default()` first when the
+    /// key is missing.
+    fn get_or_insert_default(&mut self, key: K) -> &mut V
+    where
+        V: Default;
+}
+
+impl<K: Eq + Hash, V> TailcallHashMapExt<K, V> for TailcallHashMap<K, V> {
+    fn get_or_insert_default(&mut self, key: K) -> &mut V
+    where
+        V: Default,
+    {
+        self.entry(key).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_or_insert_default() {
+        let mut map = TailcallHashMap::<&str, Vec<u32>>::default();
+
+        map.get_or_insert_default("a").push(1);
+        map.get_or_insert_default("a").push(2);
+        map.get_or_insert_default("b");
+
+        assert_eq!(map["a"], vec![1, 2]);
+        assert!(map["b"].is_empty());
+    }
+}
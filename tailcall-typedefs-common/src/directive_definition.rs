@@ -22,11 +22,25 @@ pub struct Attrs {
 
 pub fn from_directive_location(str: DirectiveLocation) -> String {
     match str {
+        DirectiveLocation::Query => String::from("QUERY"),
+        DirectiveLocation::Mutation => String::from("MUTATION"),
+        DirectiveLocation::Subscription => String::from("SUBSCRIPTION"),
+        DirectiveLocation::Field => String::from("FIELD"),
+        DirectiveLocation::FragmentDefinition => String::from("FRAGMENT_DEFINITION"),
+        DirectiveLocation::FragmentSpread => String::from("FRAGMENT_SPREAD"),
+        DirectiveLocation::InlineFragment => String::from("INLINE_FRAGMENT"),
+        DirectiveLocation::VariableDefinition => String::from("VARIABLE_DEFINITION"),
         DirectiveLocation::Schema => String::from("SCHEMA"),
+        DirectiveLocation::Scalar => String::from("SCALAR"),
         DirectiveLocation::Object => String::from("OBJECT"),
         DirectiveLocation::FieldDefinition => String::from("FIELD_DEFINITION"),
+        DirectiveLocation::ArgumentDefinition => String::from("ARGUMENT_DEFINITION"),
+        DirectiveLocation::Interface => String::from("INTERFACE"),
+        DirectiveLocation::Union => String::from("UNION"),
+        DirectiveLocation::Enum => String::from("ENUM"),
         DirectiveLocation::EnumValue => String::from("ENUM_VALUE"),
-        _ => String::from("FIELD_DEFINITION"),
+        DirectiveLocation::InputObject => String::from("INPUT_OBJECT"),
+        DirectiveLocation::InputFieldDefinition => String::from("INPUT_FIELD_DEFINITION"),
     }
 }
 
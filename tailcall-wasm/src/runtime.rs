@@ -29,12 +29,15 @@ fn init_cache() -> Arc<InMemoryCache<IoId, ConstValue>> {
 pub fn init_rt() -> TargetRuntime {
     let http = init_http();
     let http2_only = init_http();
+    // the TLS certificates are verified by the fetch API of the browser
+    let http_insecure = init_http();
     let file = init_file();
     let env = init_env();
     let cache = init_cache();
     TargetRuntime {
         http,
         http2_only,
+        http_insecure,
         env,
         file,
         cache,
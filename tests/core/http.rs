@@ -8,7 +8,7 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use http::header::{HeaderName, HeaderValue};
 use hyper::body::Bytes;
-use tailcall::core::http::Response;
+use tailcall::core::http::{Response, StatusError};
 use tailcall::core::HttpIO;
 
 use super::runtime::{ExecutionMock, ExecutionSpec};
@@ -115,7 +115,16 @@ impl HttpIO for Http {
         let status_code = reqwest::StatusCode::from_u16(mock_response.0.status)?;
 
         if status_code.is_client_error() || status_code.is_server_error() {
-            return Err(anyhow::format_err!("Status code error"));
+            // Keep the status and the body in the error like the real client
+            // does, so that they can be mapped by `@http(onStatus:)`.
+            let body = mock_response.0.body.map(|body| body.to_bytes());
+            let response = http::Response::builder()
+                .status(status_code)
+                .body(body.unwrap_or_default())?;
+            let error = StatusError::check(reqwest::Response::from(response))
+                .await
+                .unwrap_err();
+            return Err(error.context("Status code error"));
         }
 
         let mut response = Response { status: status_code, ..Default::default() };
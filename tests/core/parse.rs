@@ -19,10 +19,9 @@ use tailcall::core::config::{ConfigModule, Link, RuntimeConfig, Source};
 use tailcall::core::merge_right::MergeRight;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::worker::{Command, Event};
-use tailcall::core::{EnvIO, WorkerIO};
+use tailcall::core::{EnvIO, HttpIO, WorkerIO};
 
 use super::file::File;
-use super::http::Http;
 use super::model::*;
 use super::runtime::ExecutionSpec;
 
@@ -281,13 +280,14 @@ impl ExecutionSpec {
         &self,
         config: &ConfigModule,
         env: HashMap<String, String>,
-        http: Arc<Http>,
+        http: Arc<dyn HttpIO>,
     ) -> Arc<AppContext> {
         let blueprint = Blueprint::try_from(config).unwrap();
 
         let script = blueprint.server.script.clone();
 
         let http2_only = http.clone();
+        let http_insecure = http.clone();
 
         let http_worker: Option<Arc<dyn WorkerIO<Event, Command>>> =
             if let Some(script) = script.clone() {
@@ -306,6 +306,7 @@ impl ExecutionSpec {
         let runtime = TargetRuntime {
             http,
             http2_only,
+            http_insecure,
             file: Arc::new(File::new(self.clone())),
             env: Arc::new(Env::init(env)),
             cache: Arc::new(InMemoryCache::default()),
@@ -11,12 +11,15 @@ use tailcall::cli::javascript::init_worker_io;
 use tailcall::core::blueprint::Script;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::config::RuntimeConfig;
+use tailcall::core::http::{
+    Cassette, CassetteHttp, CassetteRecorder, TAILCALL_RECORD, TAILCALL_REPLAY,
+};
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::worker::{Command, Event};
+use tailcall::core::HttpIO;
 
 use super::env::Env;
 use super::file::TestFileIO;
-use super::http::Http;
 use super::model::*;
 
 #[derive(Clone, Setters)]
@@ -62,8 +65,59 @@ impl ExecutionMock {
     }
 }
 
+/// The cassette of a spec, when `TAILCALL_RECORD` or `TAILCALL_REPLAY` is set
+/// to a directory, which holds a cassette per spec named after it. Recording
+/// keeps the traffic answered by the mocks, replaying serves the spec from its
+/// cassette instead of the mocks.
+pub enum SpecCassette {
+    Record(Arc<CassetteRecorder>),
+    Replay(Arc<Cassette>),
+}
+
+impl SpecCassette {
+    pub fn from_env(spec: &ExecutionSpec) -> anyhow::Result<Option<Self>> {
+        let path = |dir: String| {
+            Path::new(&dir)
+                .join(format!("{}.json", spec.safe_name))
+                .to_string_lossy()
+                .into_owned()
+        };
+        if let Ok(dir) = std::env::var(TAILCALL_REPLAY) {
+            let content = std::fs::read_to_string(path(dir))?;
+            let cassette = serde_json::from_str(&content)?;
+            Ok(Some(SpecCassette::Replay(Arc::new(cassette))))
+        } else if let Ok(dir) = std::env::var(TAILCALL_RECORD) {
+            let file = Arc::new(TestFileIO::init());
+            let recorder = CassetteRecorder::new(path(dir), Cassette::default(), file);
+            Ok(Some(SpecCassette::Record(Arc::new(recorder))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn wrap(&self, http: Arc<dyn HttpIO>) -> Arc<dyn HttpIO> {
+        match self {
+            SpecCassette::Record(recorder) => {
+                Arc::new(CassetteHttp::record(recorder.clone(), http))
+            }
+            SpecCassette::Replay(cassette) => Arc::new(CassetteHttp::replay(cassette.clone())),
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self, SpecCassette::Replay(_))
+    }
+
+    /// Waits until the interactions of the spec are written.
+    pub async fn flush(&self) {
+        if let SpecCassette::Record(recorder) = self {
+            recorder.flush().await;
+        }
+    }
+}
+
 pub fn create_runtime(
-    http_client: Arc<Http>,
+    http_client: Arc<dyn HttpIO>,
     env: Option<HashMap<String, String>>,
     script: Option<Script>,
 ) -> TargetRuntime {
@@ -71,12 +125,15 @@ pub fn create_runtime(
 
     let http2 = http_client.clone();
 
+    let http_insecure = http_client.clone();
+
     let file = TestFileIO::init();
     let env = Env::init(env);
 
     TargetRuntime {
         http,
         http2_only: http2,
+        http_insecure,
         env: Arc::new(env),
         file: Arc::new(file),
         cache: Arc::new(InMemoryCache::default()),
@@ -20,14 +20,14 @@ use tailcall::core::config::{Config, ConfigModule, ConfigReaderContext, LinkType
 use tailcall::core::http::handle_request;
 use tailcall::core::mustache::PathStringEval;
 use tailcall::core::print_schema::print_schema;
-use tailcall::core::Mustache;
+use tailcall::core::{HttpIO, Mustache};
 use tailcall_prettier::Parser;
 use tailcall_valid::{Cause, Valid, ValidationError, Validator};
 
 use super::file::File;
 use super::http::Http;
 use super::model::*;
-use super::runtime::ExecutionSpec;
+use super::runtime::{ExecutionSpec, SpecCassette};
 use crate::core::runtime;
 
 #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
@@ -137,15 +137,12 @@ async fn check_identity(spec: &ExecutionSpec, reader_ctx: &ConfigReaderContext<'
 async fn run_query_tests_on_spec(
     spec: ExecutionSpec,
     config_module: &ConfigModule,
-    mock_http_client: Arc<Http>,
+    http: Arc<dyn HttpIO>,
+    mock_http_client: Option<Arc<Http>>,
 ) {
     if let Some(tests) = spec.test.as_ref() {
         let app_ctx = spec
-            .app_context(
-                config_module,
-                spec.env.clone().unwrap_or_default(),
-                mock_http_client.clone(),
-            )
+            .app_context(config_module, spec.env.clone().unwrap_or_default(), http)
             .await;
 
         // test: Run test specs
@@ -178,7 +175,10 @@ async fn run_query_tests_on_spec(
             insta::assert_json_snapshot!(snapshot_name, response);
         }
 
-        mock_http_client.test_hits(&spec.path);
+        // the mocks aren't hit when the spec is replayed from its cassette
+        if let Some(mock_http_client) = mock_http_client {
+            mock_http_client.test_hits(&spec.path);
+        }
     }
 }
 
@@ -189,8 +189,19 @@ async fn test_spec(spec: ExecutionSpec) {
     let _guard = insta::Settings::bind_to_scope(&insta_settings);
 
     let mock_http_client = Arc::new(Http::new(&spec));
-
-    let mut runtime = runtime::create_runtime(mock_http_client.clone(), spec.env.clone(), None);
+    let cassette = SpecCassette::from_env(&spec)
+        .with_context(|| format!("cassette of {}", spec.path.display()))
+        .unwrap();
+    let http: Arc<dyn HttpIO> = match &cassette {
+        Some(cassette) => cassette.wrap(mock_http_client.clone()),
+        None => mock_http_client.clone(),
+    };
+    let mock_http_client = match &cassette {
+        Some(cassette) if cassette.is_replay() => None,
+        _ => Some(mock_http_client),
+    };
+
+    let mut runtime = runtime::create_runtime(http.clone(), spec.env.clone(), None);
     runtime.file = Arc::new(File::new(spec.clone()));
 
     let runtime_clone = runtime.clone();
@@ -246,7 +257,11 @@ async fn test_spec(spec: ExecutionSpec) {
     insta::assert_snapshot!(snapshot_name, formatted);
 
     // run query tests
-    run_query_tests_on_spec(spec, &config_module, mock_http_client).await;
+    run_query_tests_on_spec(spec, &config_module, http, mock_http_client).await;
+
+    if let Some(cassette) = cassette {
+        cassette.flush().await;
+    }
 }
 
 pub async fn load_and_test_execution_spec(path: &Path) -> anyhow::Result<()> {
@@ -251,4 +251,30 @@ mod tests {
 
         insta::assert_json_snapshot!(response);
     }
+
+    #[tokio::test]
+    async fn test_introspection() {
+        let request = Request::new(include_str!("../src/core/blueprint/introspection.graphql"));
+        let executor = TestExecutor::try_new().await.unwrap();
+        let mut response = executor.run(request).await.unwrap();
+
+        // The types, their fields and the directives are sorted by name, the
+        // introspection types of async-graphql itself are left out.
+        let schema = &mut response["data"]["__schema"];
+        let types = schema["types"].as_array_mut().unwrap();
+        types.retain(|type_| !type_["name"].as_str().unwrap().starts_with("__"));
+        for type_ in types.iter_mut() {
+            if let Some(fields) = type_["fields"].as_array_mut() {
+                sort_by_name(fields);
+            }
+        }
+        sort_by_name(types);
+        sort_by_name(schema["directives"].as_array_mut().unwrap());
+
+        insta::assert_json_snapshot!(response);
+    }
+
+    fn sort_by_name(values: &mut [serde_json::Value]) {
+        values.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    }
 }
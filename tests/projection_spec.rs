@@ -0,0 +1,96 @@
+//! Checks that decoding a large upstream response with a projection needs a
+//! fraction of the memory of the full parse, by counting the allocations.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_graphql_value::ConstValue;
+use hyper::body::Bytes;
+use serde_json::json;
+use tailcall::core::http::Response;
+use tailcall::core::json::Projection;
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// The highest memory usage while `f` runs, above the usage before it.
+fn peak_memory<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let value = f();
+    let peak = PEAK.load(Ordering::SeqCst);
+    drop(value);
+    peak - before
+}
+
+fn large_body() -> Bytes {
+    let users = (0..20_000)
+        .map(|id| {
+            json!({
+                "id": id,
+                "name": format!("user {id}"),
+                "bio": "lorem ipsum dolor sit amet ".repeat(8),
+                "address": {"street": "Kulas Light", "city": "Gwenborough", "zipcode": "92998-3874"},
+                "tags": ["a", "b", "c"],
+            })
+        })
+        .collect::<Vec<_>>();
+    Bytes::from(serde_json::to_vec(&users).unwrap())
+}
+
+#[test]
+fn test_projected_parse_memory() {
+    let response = Response { body: large_body(), ..Response::empty() };
+    let projection = Projection::Fields(
+        ["id", "name"]
+            .into_iter()
+            .map(|name| (name.to_string(), Projection::All))
+            .collect(),
+    );
+
+    let full = peak_memory(|| response.clone().to_json::<ConstValue>().unwrap());
+    let projected = peak_memory(|| response.clone().to_projected_json(&projection).unwrap());
+
+    assert!(
+        projected * 4 < full,
+        "the projected parse used {projected} bytes, the full parse {full} bytes"
+    );
+
+    let full = response.clone().to_json::<ConstValue>().unwrap().body;
+    let projected = response.to_projected_json(&projection).unwrap().body;
+    let ConstValue::List(users) = full else {
+        panic!("the body is a list")
+    };
+    let expected = users
+        .into_iter()
+        .map(|user| match user {
+            ConstValue::Object(mut user) => {
+                user.retain(|name, _| name.as_str() == "id" || name.as_str() == "name");
+                ConstValue::Object(user)
+            }
+            user => user,
+        })
+        .collect();
+    assert_eq!(projected, ConstValue::List(expected));
+}
@@ -134,6 +134,7 @@ pub mod test {
     pub fn init(script: Option<Script>) -> TargetRuntime {
         let http = TestHttp::init(&Default::default());
         let http2 = TestHttp::init(&Upstream::default().http2_only(true));
+        let http_insecure = TestHttp::init(&Upstream::default().verify_ssl(false));
 
         let file = TestFileIO::init();
         let env = TestEnvIO::init();
@@ -141,6 +142,7 @@ pub mod test {
         TargetRuntime {
             http,
             http2_only: http2,
+            http_insecure,
             env: Arc::new(env),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::default()),
@@ -258,6 +260,62 @@ mod server_spec {
         assert!(server.start().await.is_err())
     }
 
+    #[cfg(unix)]
+    async fn unix_request(path: &str, request: String) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut stream = tokio::net::UnixStream::connect(path).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn server_start_unix_socket() {
+        let socket = "/tmp/tailcall-server-start.sock";
+        let configs = &["tests/server/config/server-start-unix.graphql"];
+        let runtime = crate::test::init(None);
+        let reader = ConfigReader::init(runtime);
+        let config = reader.read_all(configs).await.unwrap();
+        let mut server = Server::new(config);
+        let server_up_receiver = server.server_up_receiver();
+
+        tokio::spawn(async move {
+            server.start().await.unwrap();
+        });
+
+        server_up_receiver
+            .await
+            .expect("Server did not start up correctly");
+
+        let query = json!({ "query": "{ greet }" }).to_string();
+        let response = unix_request(
+            socket,
+            format!(
+                "POST /graphql HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                query.len(),
+                query
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(
+            response.ends_with(r#"{"data":{"greet":"Hello World!"}}"#),
+            "{}",
+            response
+        );
+
+        let response = unix_request(
+            socket,
+            "GET /status HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n".to_string(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    }
+
     #[tokio::test]
     async fn server_start_http2_ec() {
         test_server(